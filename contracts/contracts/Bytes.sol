@@ -195,6 +195,11 @@ library Bytes {
         r = bytesToBytes32(_data, _offset);
     }
 
+    function readUInt256(bytes memory _data, uint _offset) internal pure returns (uint new_offset, uint256 r) {
+        new_offset = _offset + 32;
+        r = uint256(bytesToBytes32(_data, _offset));
+    }
+
     // Helper function for hex conversion.
     function halfByteToHex(byte _byte) internal pure returns (byte _hexByte) {
         require(uint8(_byte) | 0xf == 0xf, "hbh11");  // half byte's value is out of 0..15 range.
@@ -45,20 +45,50 @@ contract Config {
     uint256 constant EXPECT_VERIFICATION_IN = 2 days / BLOCK_PERIOD;
 
     uint256 constant NOOP_BYTES = 1 * 8;
-    uint256 constant DEPOSIT_BYTES = 6 * 8;
-    uint256 constant TRANSFER_TO_NEW_BYTES = 5 * 8;
-    uint256 constant PARTIAL_EXIT_BYTES = 6 * 8;
-    uint256 constant TRANSFER_BYTES = 2 * 8;
+    uint256 constant DEPOSIT_BYTES = 12 * 8;
+    uint256 constant TRANSFER_TO_NEW_BYTES = 9 * 8;
+    uint256 constant PARTIAL_EXIT_BYTES = 8 * 8;
+    uint256 constant TRANSFER_BYTES = 6 * 8;
 
     /// @notice Full exit operation length
     uint256 constant FULL_EXIT_BYTES = 6 * 8;
 
     /// @notice OnchainWithdrawal data length
-    uint256 constant ONCHAIN_WITHDRAWAL_BYTES = 1 + 20 + 2; // (uint8 addToPendingWithdrawalsQueue, address _to, uint16 _tokenId, uint128 _amount)
+    uint256 constant ONCHAIN_WITHDRAWAL_BYTES = 1 + 20 + 2 + 32; // (uint8 addToPendingWithdrawalsQueue, address _to, uint16 _collectionId, uint256 _tokenId)
 
     /// @notice ChangePubKey operation length
     uint256 constant CHANGE_PUBKEY_BYTES = 6 * 8;
 
+    /// @notice BatchTransfer operation header length (opcode, from and to account ids, recipient address, fee, tokens count), padded to whole chunks
+    uint256 constant BATCH_TRANSFER_HEADER_BYTES = 4 * 8;
+
+    /// @notice BatchTransfer offset of the tokens count in the header
+    uint256 constant BATCH_TRANSFER_TOKENS_COUNT_OFFSET = 1 + 3 + 3 + 20 + 2;
+
+    /// @notice BatchTransfer length of each moved token (collection id, token id), padded to whole chunks
+    uint256 constant BATCH_TRANSFER_TOKEN_BYTES = 5 * 8;
+
+    /// @notice Swap operation length
+    uint256 constant SWAP_BYTES = 10 * 8;
+
+    /// @notice Mint operation length
+    uint256 constant MINT_BYTES = 12 * 8;
+
+    /// @notice Burn operation length
+    uint256 constant BURN_BYTES = 5 * 8;
+
+    /// @notice Approve operation length
+    uint256 constant APPROVE_BYTES = 6 * 8;
+
+    /// @notice ApproveForAll operation length
+    uint256 constant APPROVE_FOR_ALL_BYTES = 2 * 8;
+
+    /// @notice ForcedWithdraw operation length
+    uint256 constant FORCED_WITHDRAW_BYTES = 8 * 8;
+
+    /// @notice DepositEth operation length
+    uint256 constant DEPOSIT_ETH_BYTES = 5 * 8;
+
     /// @notice Expiration delta for priority request to be satisfied (in ETH blocks)
     /// NOTE: Priority expiration should be > EXPECT_VERIFICATION_IN, otherwise incorrect block with priority op could not be reverted.
     uint256 constant PRIORITY_EXPIRATION = 3 days / BLOCK_PERIOD;
@@ -62,7 +62,7 @@ contract DeployFactory is TokenDeployInit {
         for (uint i = 0; i < tokens.length; ++i) {
             _governance.addToken(tokens[i]);
         }
-        _governance.setERC721Address(getERC721Address());
+        _governance.addToken(getERC721Address());
         _governance.setValidator(_validator, true);
         _governance.changeGovernor(_finalGovernor);
     }
@@ -31,6 +31,7 @@ contract Events {
 
     /// @notice Event emitted when user send a transaction to deposit her funds
     event OnchainDeposit721(
+        address indexed collection,
         uint256 tokenId,
         address indexed owner
     );
@@ -64,7 +65,9 @@ contract Events {
         uint32 franklinBlockId,
         uint24 accountId,
         address owner,
-        uint16 tokenId
+        uint16 collectionId,
+        uint256 tokenId,
+        bool success
     );
 
     event FullExitCommit(
@@ -74,6 +77,15 @@ contract Events {
         uint16 tokenId,
         uint128 amount
     );
+
+    event ForcedWithdrawCommit(
+        uint32 franklinBlockId,
+        uint24 accountId,
+        address owner,
+        uint16 collectionId,
+        uint256 tokenId,
+        bool success
+    );
 }
 
 /// @title Upgrade events
@@ -24,8 +24,11 @@ contract Governance is Config {
         bool isActive
     );
 
-    event NewERC721Address(
-        address indexed tokenAddr
+    /// @notice Minter's status changed
+    event MinterStatusUpdate(
+        address indexed minterAddress,
+        uint16 indexed collectionId,
+        bool isActive
     );
 
     /// @notice Address which will exercise governance over the network i.e. add tokens, change validator set, conduct upgrades
@@ -43,7 +46,10 @@ contract Governance is Config {
     /// @notice List of permitted validators
     mapping(address => bool) public validators;
 
-    address public erc721address;
+    /// @notice List of accounts permitted to mint tokens of a collection inside the rollup, by collection id
+    /// @dev Only collections minted on L1 by the zkSync contract alone may have minters,
+    /// so their tokens exist on L1 only after being withdrawn from the rollup
+    mapping(uint16 => mapping(address => bool)) public minters;
 
     constructor() public {}
 
@@ -88,6 +94,17 @@ contract Governance is Config {
         emit ValidatorStatusUpdate(_validator, _active);
     }
 
+    /// @notice Change minter status (active or not active) for a collection
+    /// @param _minter Minter address
+    /// @param _collectionId Id of the registered collection, it must be minted on L1 by the zkSync contract alone
+    /// @param _active Active flag
+    function setMinter(address _minter, uint16 _collectionId, bool _active) external {
+        requireGovernor(msg.sender);
+        require(tokenAddresses[_collectionId] != address(0), "gsm11"); // collection is not registered
+        minters[_collectionId][_minter] = _active;
+        emit MinterStatusUpdate(_minter, _collectionId, _active);
+    }
+
     /// @notice Check if specified address is is governor
     /// @param _address Address to check
     function requireGovernor(address _address) public view {
@@ -100,6 +117,13 @@ contract Governance is Config {
         require(validators[_address], "grr21"); // validator is not active
     }
 
+    /// @notice Checks if minter is active for the collection
+    /// @param _address Minter address
+    /// @param _collectionId Collection id
+    function requireActiveMinter(address _address, uint16 _collectionId) external view {
+        require(minters[_collectionId][_address], "grr31"); // minter is not active
+    }
+
     /// @notice Validate token id (must be less than or equal to total tokens amount)
     /// @param _tokenId Token id
     /// @return bool flag that indicates if token id is less than or equal to total tokens amount
@@ -116,10 +140,4 @@ contract Governance is Config {
         return tokenId;
     }
 
-    function setERC721Address(address _tokenAddr) external {
-        requireGovernor(msg.sender);
-        erc721address = _tokenAddr;
-        emit NewERC721Address(_tokenAddr);
-    }
-
 }
@@ -17,14 +17,28 @@ library Operations {
         _CloseAccount, // used for correct op id offset
         Transfer,
         FullExit,
-        ChangePubKey
+        ChangePubKey,
+        BatchTransfer,
+        Swap,
+        Mint,
+        Burn,
+        Approve,
+        ApproveForAll,
+        ForcedWithdraw,
+        DepositEth
     }
 
     // Byte lengths
 
     uint8 constant TOKEN_BYTES = 2;
 
-    uint8 constant TOKENID_BYTES = 2;
+    uint8 constant TOKENID_BYTES = 32;
+
+    /// @notice Registered ERC-721 collection id bytes length
+    uint8 constant COLLECTIONID_BYTES = 2;
+
+    /// @notice Token content hash bytes length
+    uint8 constant CONTENT_HASH_BYTES = 32;
 
     uint8 constant PUBKEY_BYTES = 32;
 
@@ -45,6 +59,9 @@ library Operations {
     /// @notice Signature (for example full exit signature) bytes length
     uint8 constant SIGNATURE_BYTES = 64;
 
+    /// @notice Success flag of a priority operation that may fail, bytes length
+    uint8 constant SUCCESS_FLAG_BYTES = 1;
+
     // Deposit pubdata
 
     struct Deposit {
@@ -75,7 +92,7 @@ library Operations {
     function writeDepositPubdata(Deposit memory op) internal pure returns (bytes memory buf) {
         buf = abi.encodePacked(
             new bytes(ACCOUNT_ID_BYTES),          // accountId (ignored)
-            Bytes.toBytesFromUInt16(op.tokenId),  // tokenId
+            Bytes.toBytesFromUInt256(op.tokenId), // tokenId
             Bytes.toBytesFromUInt128(op.amount),  // amount
             Bytes.toBytesFromAddress(op.owner)    // owner
         );
@@ -83,21 +100,25 @@ library Operations {
 
     /// @notice Check that deposit pubdata from request and block matches
     function depositPubdataMatch(bytes memory _lhs, bytes memory _rhs) internal pure returns (bool) {
-        // We must ignore `accountId` because it is present in block pubdata but not in priority queue
-        bytes memory lhs_trimmed = Bytes.slice(_lhs, ACCOUNT_ID_BYTES, PACKED_DEPOSIT721_PUBDATA_BYTES - ACCOUNT_ID_BYTES);
-        bytes memory rhs_trimmed = Bytes.slice(_rhs, ACCOUNT_ID_BYTES, PACKED_DEPOSIT721_PUBDATA_BYTES - ACCOUNT_ID_BYTES);
+        // We must ignore `accountId` and `success` because they are present in block pubdata but not in priority queue
+        uint length = PACKED_DEPOSIT721_PUBDATA_BYTES - ACCOUNT_ID_BYTES - SUCCESS_FLAG_BYTES;
+        bytes memory lhs_trimmed = Bytes.slice(_lhs, ACCOUNT_ID_BYTES, length);
+        bytes memory rhs_trimmed = Bytes.slice(_rhs, ACCOUNT_ID_BYTES, length);
         return keccak256(lhs_trimmed) == keccak256(rhs_trimmed);
     }
 
 
     struct Deposit721 {
         uint24 accountId;
-        uint16 tokenId;
+        uint16 collectionId;
+        uint256 tokenId;
         address owner;
+        bytes32 contentHash;
+        bool success;
     }
 
     uint public constant PACKED_DEPOSIT721_PUBDATA_BYTES = 
-        ACCOUNT_ID_BYTES + TOKENID_BYTES + ADDRESS_BYTES;
+        ACCOUNT_ID_BYTES + COLLECTIONID_BYTES + TOKENID_BYTES + ADDRESS_BYTES + CONTENT_HASH_BYTES + SUCCESS_FLAG_BYTES;
 
     /// Deserialize deposit pubdata
     function readDeposit721Pubdata(bytes memory _data) internal pure
@@ -105,9 +126,12 @@ library Operations {
     {
         // NOTE: there is no check that variable sizes are same as constants (i.e. TOKEN_BYTES), fix if possible.
         uint offset = 0;
-        (offset, parsed.accountId) = Bytes.readUInt24(_data, offset); // accountId
-        (offset, parsed.tokenId) = Bytes.readUInt16(_data, offset);   // tokenId
-        (offset, parsed.owner) = Bytes.readAddress(_data, offset);    // owner
+        (offset, parsed.accountId) = Bytes.readUInt24(_data, offset);    // accountId
+        (offset, parsed.collectionId) = Bytes.readUInt16(_data, offset); // collectionId
+        (offset, parsed.tokenId) = Bytes.readUInt256(_data, offset);     // tokenId
+        (offset, parsed.owner) = Bytes.readAddress(_data, offset);       // owner
+        (offset, parsed.contentHash) = Bytes.readBytes32(_data, offset); // contentHash
+        (offset, parsed.success) = Bytes.readBool(_data, offset);        // success
 
         require(offset == PACKED_DEPOSIT721_PUBDATA_BYTES, "rdp10"); // reading invalid deposit pubdata size
     }
@@ -115,9 +139,11 @@ library Operations {
     /// Serialize deposit pubdata
     function writeDeposit721Pubdata(Deposit721 memory op) internal pure returns (bytes memory buf) {
         buf = abi.encodePacked(
-            new bytes(ACCOUNT_ID_BYTES),          // accountId (ignored)
-            Bytes.toBytesFromUInt16(op.tokenId), //Bytes.toBytesFromUInt256(op.tokenId),  // tokenId // TODO ADE must be encoded as u256
-            Bytes.toBytesFromAddress(op.owner)    // owner
+            new bytes(ACCOUNT_ID_BYTES),              // accountId (ignored)
+            Bytes.toBytesFromUInt16(op.collectionId), // collectionId
+            Bytes.toBytesFromUInt256(op.tokenId),     // tokenId
+            Bytes.toBytesFromAddress(op.owner),       // owner
+            op.contentHash                            // contentHash
         );
     }
 
@@ -163,22 +189,108 @@ library Operations {
         return lhs == rhs;
     }
 
+    // ForcedWithdraw pubdata
+
+    struct ForcedWithdraw {
+        uint24 accountId;
+        address owner;
+        uint16 collectionId;
+        uint256 tokenId;
+        bool success;
+    }
+
+    uint public constant PACKED_FORCED_WITHDRAW_PUBDATA_BYTES = 
+        ACCOUNT_ID_BYTES + ADDRESS_BYTES + COLLECTIONID_BYTES + TOKENID_BYTES + SUCCESS_FLAG_BYTES;
+
+    function readForcedWithdrawPubdata(bytes memory _data) internal pure
+        returns (ForcedWithdraw memory parsed)
+    {
+        uint offset = 0;
+        (offset, parsed.accountId) = Bytes.readUInt24(_data, offset);    // accountId
+        (offset, parsed.owner) = Bytes.readAddress(_data, offset);       // owner
+        (offset, parsed.collectionId) = Bytes.readUInt16(_data, offset); // collectionId
+        (offset, parsed.tokenId) = Bytes.readUInt256(_data, offset);     // tokenId
+        (offset, parsed.success) = Bytes.readBool(_data, offset);        // success
+
+        require(offset == PACKED_FORCED_WITHDRAW_PUBDATA_BYTES, "rfw10"); // reading invalid forced withdraw pubdata size
+    }
+
+    function writeForcedWithdrawPubdata(ForcedWithdraw memory op) internal pure returns (bytes memory buf) {
+        buf = abi.encodePacked(
+            Bytes.toBytesFromUInt24(op.accountId),    // accountId
+            Bytes.toBytesFromAddress(op.owner),       // owner
+            Bytes.toBytesFromUInt16(op.collectionId), // collectionId
+            Bytes.toBytesFromUInt256(op.tokenId)      // tokenId
+        );
+    }
+
+    /// @notice Check that forced withdraw pubdata from request and block matches
+    function forcedWithdrawPubdataMatch(bytes memory _lhs, bytes memory _rhs) internal pure returns (bool) {
+        // `success` is ignored because it is present in block pubdata but not in priority queue
+        bytes memory lhs_trimmed = Bytes.slice(_lhs, 0, PACKED_FORCED_WITHDRAW_PUBDATA_BYTES - SUCCESS_FLAG_BYTES);
+        bytes memory rhs_trimmed = Bytes.slice(_rhs, 0, PACKED_FORCED_WITHDRAW_PUBDATA_BYTES - SUCCESS_FLAG_BYTES);
+        return keccak256(lhs_trimmed) == keccak256(rhs_trimmed);
+    }
+
+    // DepositEth pubdata
+
+    struct DepositEth {
+        uint24 accountId;
+        uint128 amount;
+        address owner;
+    }
+
+    uint public constant PACKED_DEPOSIT_ETH_PUBDATA_BYTES = 
+        ACCOUNT_ID_BYTES + AMOUNT_BYTES + ADDRESS_BYTES;
+
+    /// Deserialize eth deposit pubdata
+    function readDepositEthPubdata(bytes memory _data) internal pure
+        returns (DepositEth memory parsed)
+    {
+        uint offset = 0;
+        (offset, parsed.accountId) = Bytes.readUInt24(_data, offset); // accountId
+        (offset, parsed.amount) = Bytes.readUInt128(_data, offset);   // amount
+        (offset, parsed.owner) = Bytes.readAddress(_data, offset);    // owner
+
+        require(offset == PACKED_DEPOSIT_ETH_PUBDATA_BYTES, "rde10"); // reading invalid eth deposit pubdata size
+    }
+
+    /// Serialize eth deposit pubdata
+    function writeDepositEthPubdata(DepositEth memory op) internal pure returns (bytes memory buf) {
+        buf = abi.encodePacked(
+            new bytes(ACCOUNT_ID_BYTES),         // accountId (ignored)
+            Bytes.toBytesFromUInt128(op.amount), // amount
+            Bytes.toBytesFromAddress(op.owner)   // owner
+        );
+    }
+
+    /// @notice Check that eth deposit pubdata from request and block matches
+    function depositEthPubdataMatch(bytes memory _lhs, bytes memory _rhs) internal pure returns (bool) {
+        // We must ignore `accountId` because it is present in block pubdata but not in priority queue
+        bytes memory lhs_trimmed = Bytes.slice(_lhs, ACCOUNT_ID_BYTES, PACKED_DEPOSIT_ETH_PUBDATA_BYTES - ACCOUNT_ID_BYTES);
+        bytes memory rhs_trimmed = Bytes.slice(_rhs, ACCOUNT_ID_BYTES, PACKED_DEPOSIT_ETH_PUBDATA_BYTES - ACCOUNT_ID_BYTES);
+        return keccak256(lhs_trimmed) == keccak256(rhs_trimmed);
+    }
+
     // PartialExit pubdata
     
     struct PartialExit {
         //uint24 accountId; -- present in pubdata, ignored at serialization
-        uint16 tokenId;
+        uint16 collectionId;
+        uint256 tokenId;
         uint128 amount;
         //uint16 fee; -- present in pubdata, ignored at serialization
         address owner;
+        //uint24 feePayerId; -- present in pubdata, ignored at serialization
     }
 
     function readPartialExitPubdata(bytes memory _data, uint _offset) internal pure
         returns (PartialExit memory parsed)
     {
         // NOTE: there is no check that variable sizes are same as constants (i.e. TOKEN_BYTES), fix if possible.
-        uint offset = _offset + ACCOUNT_ID_BYTES;                   // accountId (ignored)
-        (offset, parsed.tokenId) = Bytes.readUInt16(_data, offset); // tokenId
+        uint offset = _offset + ACCOUNT_ID_BYTES;                        // accountId (ignored)
+        (offset, parsed.collectionId) = Bytes.readUInt16(_data, offset); // collectionId
+        (offset, parsed.tokenId) = Bytes.readUInt256(_data, offset);     // tokenId
         //(offset, parsed.amount) = Bytes.readUInt128(_data, offset); // amount
         offset += FEE_BYTES;                                        // fee (ignored)
         (offset, parsed.owner) = Bytes.readAddress(_data, offset);  // owner
@@ -186,14 +298,52 @@ library Operations {
 
     function writePartialExitPubdata(PartialExit memory op) internal pure returns (bytes memory buf) {
         buf = abi.encodePacked(
-            new bytes(ACCOUNT_ID_BYTES),          // accountId (ignored)
-            Bytes.toBytesFromUInt16(op.tokenId),  // tokenId
-            Bytes.toBytesFromUInt128(op.amount),  // amount
-            new bytes(FEE_BYTES),                 // fee (ignored)
-            Bytes.toBytesFromAddress(op.owner)    // owner
+            new bytes(ACCOUNT_ID_BYTES),              // accountId (ignored)
+            Bytes.toBytesFromUInt16(op.collectionId), // collectionId
+            Bytes.toBytesFromUInt256(op.tokenId),     // tokenId
+            Bytes.toBytesFromUInt128(op.amount),      // amount
+            new bytes(FEE_BYTES),                     // fee (ignored)
+            Bytes.toBytesFromAddress(op.owner)        // owner
         );
     }
 
+    // Mint pubdata
+
+    struct Mint {
+        //uint24 accountId; -- present in pubdata, ignored at serialization
+        uint16 collectionId;
+        uint256 tokenId;
+        //uint16 fee; -- present in pubdata, ignored at serialization
+        address minter;
+    }
+
+    function readMintPubdata(bytes memory _data, uint _offset) internal pure
+        returns (Mint memory parsed)
+    {
+        uint offset = _offset + ACCOUNT_ID_BYTES;                        // accountId (ignored)
+        (offset, parsed.collectionId) = Bytes.readUInt16(_data, offset); // collectionId
+        (offset, parsed.tokenId) = Bytes.readUInt256(_data, offset);     // tokenId
+        offset += FEE_BYTES;                                             // fee (ignored)
+        (offset, parsed.minter) = Bytes.readAddress(_data, offset);      // minter
+    }
+
+    // Burn pubdata
+
+    struct Burn {
+        //uint24 accountId; -- present in pubdata, ignored at serialization
+        uint16 collectionId;
+        uint256 tokenId;
+        //uint16 fee; -- present in pubdata, ignored at serialization
+    }
+
+    function readBurnPubdata(bytes memory _data, uint _offset) internal pure
+        returns (Burn memory parsed)
+    {
+        uint offset = _offset + ACCOUNT_ID_BYTES;                        // accountId (ignored)
+        (offset, parsed.collectionId) = Bytes.readUInt16(_data, offset); // collectionId
+        (offset, parsed.tokenId) = Bytes.readUInt256(_data, offset);     // tokenId
+    }
+
     // ChangePubKey
 
     struct ChangePubKey {
@@ -218,12 +368,13 @@ library Operations {
     // Withdrawal data process
 
     function readWithdrawalData(bytes memory _data, uint _offset) internal pure
-        returns (bool _addToPendingWithdrawalsQueue, address _to, uint16 _tokenId, uint128 _amount)
+        returns (bool _addToPendingWithdrawalsQueue, address _to, uint16 _collectionId, uint256 _tokenId, uint128 _amount)
     {
         uint offset = _offset;
         (offset, _addToPendingWithdrawalsQueue) = Bytes.readBool(_data, offset);
         (offset, _to) = Bytes.readAddress(_data, offset);
-        (offset, _tokenId) = Bytes.readUInt16(_data, offset);
+        (offset, _collectionId) = Bytes.readUInt16(_data, offset);
+        (offset, _tokenId) = Bytes.readUInt256(_data, offset);
     }
 
 }
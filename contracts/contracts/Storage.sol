@@ -36,7 +36,8 @@ contract Storage {
     /// @notice verified withdrawal pending to be executed.
     struct PendingWithdrawal {
         address to;
-        uint16 tokenId;
+        uint16 collectionId;
+        uint256 tokenId;
     }
     
     /// @notice Verified but not executed withdrawals for addresses stored in here (key is pendingWithdrawal's index)
@@ -80,8 +81,8 @@ contract Storage {
         bytes pubData;
     }
 
-    /// @notice Flag indicates that a user has exited certain token balance (per account id and tokenId)
-    mapping(uint24 => mapping(uint16 => bool)) public exited;
+    /// @notice Flag indicates that a user has exited certain NFT (per account id, collectionId and tokenId)
+    mapping(uint24 => mapping(uint16 => mapping(uint256 => bool))) public exited;
 
     /// @notice Flag indicates that exodus (mass exit) mode is triggered
     /// @notice Once it was raised, it can not be cleared again, and all users must exit
@@ -115,6 +116,9 @@ contract Storage {
     /// @dev Used in checks: if the request matches the operation on Rollup contract and if provided number of requests is not too big
     uint64 public totalCommittedPriorityRequests;
 
+    /// @notice Flag indicates that NFT was burned in the rollup (per collectionId and tokenId), it can't be deposited again
+    mapping(uint16 => mapping(uint256 => bool)) public burned;
+
     /// @notice Packs address and token id into single word to use as a key in balances mapping
     function packAddressAndTokenId(address _address, uint16 _tokenId) internal pure returns (bytes22) {
         return bytes22(uint176(uint(_address) | (_tokenId << 160)));
@@ -45,19 +45,17 @@ contract Verifier is KeysWithPlonkVerifier {
         bytes32 _root_hash,
         uint24 _accountId,
         address _owner,
-        uint16 _tokenId,
-        uint128 _amount,
+        uint16 _collectionId,
+        uint256 _tokenId,
         uint256[] calldata _proof
     ) external view returns (bool) {
-        bytes32 commitment = sha256(abi.encodePacked(_root_hash, _accountId, _owner, _tokenId, _amount));
+        bytes32 commitment = sha256(abi.encodePacked(_root_hash, _accountId, _owner, _collectionId, _tokenId));
 
         uint256[] memory inputs = new uint256[](1);
         uint256 mask = (~uint256(0)) >> 3;
         inputs[0] = uint256(commitment) & mask;
         Proof memory proof = deserialize_proof(1, inputs, _proof);
-        // TODO ADE no exit for now
-        //VerificationKey memory vk = getVkExit();
-        //return verify(proof, vk);
-        return false;
+        VerificationKey memory vk = getVkExit();
+        return verify(proof, vk);
     }
 }
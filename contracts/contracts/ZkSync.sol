@@ -118,15 +118,19 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
         return callSuccess;
     }
 
-    /// @notice Sends ERC-721
-    /// @param _token Token address
+    /// @notice Sends ERC-721, the token is minted on the collection if it was minted inside the rollup and does not exist yet
+    /// @param _token Collection (ERC-721 contract) address
     /// @param _to Address of recipient
     /// @param _tokenId Token id
     /// @return bool flag indicating that transfer is successful
-    function sendERC721NoRevert(address _token, address _to, uint16 _tokenId) internal returns (bool) {
-        (bool callSuccess,) = _token.call.gas(ERC721_WITHDRAWAL_GAS_LIMIT)(
-            abi.encodeWithSignature("safeTransferFrom(address,address,uint256)", address(this), _to, uint256(_tokenId))
+    function sendERC721NoRevert(address _token, address _to, uint256 _tokenId) internal returns (bool) {
+        (bool tokenExists,) = _token.staticcall.gas(ERC721_WITHDRAWAL_GAS_LIMIT)(
+            abi.encodeWithSignature("ownerOf(uint256)", _tokenId)
         );
+        bytes memory callData = tokenExists
+            ? abi.encodeWithSignature("safeTransferFrom(address,address,uint256)", address(this), _to, _tokenId)
+            : abi.encodeWithSignature("mint(address,uint256)", _to, _tokenId);
+        (bool callSuccess,) = _token.call.gas(ERC721_WITHDRAWAL_GAS_LIMIT)(callData);
         return callSuccess;
     }
 
@@ -144,12 +148,13 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
         }
 
         for (uint32 i = startIndex; i < startIndex + toProcess; ++i) {
-            uint16 tokenId = pendingWithdrawals[i].tokenId;
+            uint16 collectionId = pendingWithdrawals[i].collectionId;
+            uint256 tokenId = pendingWithdrawals[i].tokenId;
             address to = pendingWithdrawals[i].to;
             // send fails are ignored hence there is always a direct way to withdraw.
             delete pendingWithdrawals[i];
 
-            sendERC721NoRevert(governance.erc721address(), to, tokenId);
+            sendERC721NoRevert(governance.tokenAddresses(collectionId), to, tokenId);
 
             // TODO ADE: check result of sendERC721NoRevert, and updated balancesToWithdraw!
             
@@ -197,6 +202,10 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
                 Operations.Deposit memory op = Operations.readDepositPubdata(priorityRequests[id].pubData);
                 bytes22 packedBalanceKey = packAddressAndTokenId(op.owner, op.tokenId);
                 balancesToWithdraw[packedBalanceKey].balanceToWithdraw += op.amount;
+            } else if (priorityRequests[id].opType == Operations.OpType.DepositEth) {
+                Operations.DepositEth memory op = Operations.readDepositEthPubdata(priorityRequests[id].pubData);
+                bytes22 packedBalanceKey = packAddressAndTokenId(op.owner, 0);
+                balancesToWithdraw[packedBalanceKey].balanceToWithdraw += op.amount;
             }
             delete priorityRequests[id];
         }
@@ -204,17 +213,26 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
         totalOpenPriorityRequests -= toProcess;
     }
 
-    /// @notice Deposit ETH to Layer 2 - transfer ether from user into contract, validate it, register deposit
+    /// @notice Deposit ETH to Layer 2 - transfer ether from user into contract, register deposit to the account balance
+    /// @dev Fees of the Layer 2 txs are paid from this balance
     /// @param _franklinAddr The receiver Layer 2 address
     function depositETH(address _franklinAddr) external payable nonReentrant {
         requireActive();
-        registerDeposit(0, SafeCast.toUint128(msg.value), _franklinAddr);
+        registerDepositEth(SafeCast.toUint128(msg.value), _franklinAddr);
     }
 
     function onERC721Received(address operator, address from, uint256 tokenId, bytes calldata data) external returns (bytes4) {
         requireActive();
-        require(msg.sender == governance.erc721address());
-        registerDeposit721(tokenId, from);
+        // the collection is the ERC-721 contract calling back, it must be registered in governance
+        uint16 collectionId = governance.validateTokenAddress(msg.sender);
+        require(!burned[collectionId][tokenId], "fdb11"); // token was burned in the rollup
+        // `data` of `safeTransferFrom` carries the content hash committed to by the token leaf, zero if omitted
+        bytes32 contentHash;
+        if (data.length != 0) {
+            require(data.length == 32, "fdb12"); // content hash must be 32 bytes
+            contentHash = abi.decode(data, (bytes32));
+        }
+        registerDeposit721(collectionId, tokenId, from, contentHash);
         return this.onERC721Received.selector;
     }
 
@@ -282,6 +300,26 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
         balancesToWithdraw[packedBalanceKey].gasReserveValue = 0xff;
     }
 
+    /// @notice Register forced withdraw request of a single token - pack pubdata, add priority request
+    /// @dev The request fails in the rollup if the account does not belong to the sender or does not hold the token
+    /// @param _accountId Numerical id of the account
+    /// @param _collectionId Registered collection id of the token
+    /// @param _tokenId Token id within the collection
+    function forcedWithdraw(uint24 _accountId, uint16 _collectionId, uint256 _tokenId) external nonReentrant {
+        requireActive();
+
+        // Priority Queue request
+        Operations.ForcedWithdraw memory op = Operations.ForcedWithdraw({
+            accountId:    _accountId,
+            owner:        msg.sender,
+            collectionId: _collectionId,
+            tokenId:      _tokenId,
+            success:      false // unknown at this point
+        });
+        bytes memory pubData = Operations.writeForcedWithdrawPubdata(op);
+        addPriorityRequest(Operations.OpType.ForcedWithdraw, pubData);
+    }
+
     /// @notice Register deposit request - pack pubdata, add priority request and emit OnchainDeposit event
     /// @param _token Token by id
     /// @param _amount Token amount
@@ -311,22 +349,49 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
         );
     }
 
-    function registerDeposit721(
-        uint256 _tokenId,
+    /// @notice Register eth deposit request - pack pubdata, add priority request and emit OnchainDeposit event
+    /// @param _amount Ether amount in wei
+    /// @param _owner Receiver
+    function registerDepositEth(
+        uint128 _amount,
         address _owner
     ) internal {
-        //require(governance.isValidTokenId(_token), "rgd11"); // invalid token id // TODO ADE can check with msg.sender (address of ERC-721 contract)
-
         // Priority Queue request
-        Operations.Deposit721 memory op = Operations.Deposit721({
+        Operations.DepositEth memory op = Operations.DepositEth({
             accountId:  0, // unknown at this point
-            tokenId:    uint16(_tokenId),
+            amount:     _amount,
             owner:      _owner
         });
+        bytes memory pubData = Operations.writeDepositEthPubdata(op);
+        addPriorityRequest(Operations.OpType.DepositEth, pubData);
+
+        emit OnchainDeposit(
+            msg.sender,
+            0,
+            _amount,
+            _owner
+        );
+    }
+
+    function registerDeposit721(
+        uint16 _collectionId,
+        uint256 _tokenId,
+        address _owner,
+        bytes32 _contentHash
+    ) internal {
+        // Priority Queue request
+        Operations.Deposit721 memory op = Operations.Deposit721({
+            accountId:      0, // unknown at this point
+            collectionId:   _collectionId,
+            tokenId:        _tokenId,
+            owner:          _owner,
+            contentHash:    _contentHash
+        });
         bytes memory pubData = Operations.writeDeposit721Pubdata(op);
         addPriorityRequest(Operations.OpType.Deposit, pubData);
 
         emit OnchainDeposit721(
+            msg.sender,
             _tokenId,
             _owner
         );
@@ -472,7 +537,13 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
                     bytes memory pubData = Bytes.slice(_publicData, pubdataOffset + 1, DEPOSIT_BYTES - 1);
                     
                     Operations.Deposit721 memory depositData = Operations.readDeposit721Pubdata(pubData);
-                    emit DepositCommit(_blockNumber, depositData.accountId, depositData.owner, depositData.tokenId);
+                    emit DepositCommit(_blockNumber, depositData.accountId, depositData.owner, depositData.collectionId, depositData.tokenId, depositData.success);
+
+                    // failed deposit is refunded to its owner, the account had no free token slot or the token is already in the rollup
+                    if (!depositData.success) {
+                        bool addToPendingWithdrawalsQueue = true;
+                        withdrawalsDataHash = keccak256(abi.encode(withdrawalsDataHash, addToPendingWithdrawalsQueue, depositData.owner, depositData.collectionId, depositData.tokenId, uint128(0)));
+                    }
 
                     OnchainOperation memory onchainOp = OnchainOperation(
                         Operations.OpType.Deposit,
@@ -486,7 +557,7 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
                     Operations.PartialExit memory data = Operations.readPartialExitPubdata(_publicData, pubdataOffset + 1);
 
                     bool addToPendingWithdrawalsQueue = true;
-                    withdrawalsDataHash = keccak256(abi.encode(withdrawalsDataHash, addToPendingWithdrawalsQueue, data.owner, data.tokenId, data.amount));
+                    withdrawalsDataHash = keccak256(abi.encode(withdrawalsDataHash, addToPendingWithdrawalsQueue, data.owner, data.collectionId, data.tokenId, data.amount));
 
                     pubDataPtr += PARTIAL_EXIT_BYTES;
                 } else if (opType == uint8(Operations.OpType.FullExit)) {
@@ -524,6 +595,55 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
                     processedOperationsRequiringEthWitness++;
 
                     pubDataPtr += CHANGE_PUBKEY_BYTES;
+                } else if (opType == uint8(Operations.OpType.BatchTransfer)) {
+                    (, uint8 tokensCount) = Bytes.readUint8(_publicData, pubdataOffset + BATCH_TRANSFER_TOKENS_COUNT_OFFSET);
+                    pubDataPtr += BATCH_TRANSFER_HEADER_BYTES + tokensCount * BATCH_TRANSFER_TOKEN_BYTES;
+                } else if (opType == uint8(Operations.OpType.Swap)) {
+                    pubDataPtr += SWAP_BYTES;
+                } else if (opType == uint8(Operations.OpType.Mint)) {
+                    Operations.Mint memory data = Operations.readMintPubdata(_publicData, pubdataOffset + 1);
+                    governance.requireActiveMinter(data.minter, data.collectionId);
+
+                    pubDataPtr += MINT_BYTES;
+                } else if (opType == uint8(Operations.OpType.Burn)) {
+                    Operations.Burn memory data = Operations.readBurnPubdata(_publicData, pubdataOffset + 1);
+                    burned[data.collectionId][data.tokenId] = true;
+
+                    pubDataPtr += BURN_BYTES;
+                } else if (opType == uint8(Operations.OpType.Approve)) {
+                    pubDataPtr += APPROVE_BYTES;
+                } else if (opType == uint8(Operations.OpType.ApproveForAll)) {
+                    pubDataPtr += APPROVE_FOR_ALL_BYTES;
+                } else if (opType == uint8(Operations.OpType.ForcedWithdraw)) {
+                    bytes memory pubData = Bytes.slice(_publicData, pubdataOffset + 1, Operations.PACKED_FORCED_WITHDRAW_PUBDATA_BYTES);
+
+                    Operations.ForcedWithdraw memory forcedWithdrawData = Operations.readForcedWithdrawPubdata(pubData);
+                    emit ForcedWithdrawCommit(_blockNumber, forcedWithdrawData.accountId, forcedWithdrawData.owner, forcedWithdrawData.collectionId, forcedWithdrawData.tokenId, forcedWithdrawData.success);
+
+                    if (forcedWithdrawData.success) {
+                        bool addToPendingWithdrawalsQueue = true;
+                        withdrawalsDataHash = keccak256(abi.encode(withdrawalsDataHash, addToPendingWithdrawalsQueue, forcedWithdrawData.owner, forcedWithdrawData.collectionId, forcedWithdrawData.tokenId, uint128(0)));
+                    }
+
+                    OnchainOperation memory onchainOp = OnchainOperation(
+                        Operations.OpType.ForcedWithdraw,
+                        pubData
+                    );
+                    commitNextPriorityOperation(onchainOp, currentPriorityRequestId);
+                    currentPriorityRequestId++;
+
+                    pubDataPtr += FORCED_WITHDRAW_BYTES;
+                } else if (opType == uint8(Operations.OpType.DepositEth)) {
+                    bytes memory pubData = Bytes.slice(_publicData, pubdataOffset + 1, Operations.PACKED_DEPOSIT_ETH_PUBDATA_BYTES);
+
+                    OnchainOperation memory onchainOp = OnchainOperation(
+                        Operations.OpType.DepositEth,
+                        pubData
+                    );
+                    commitNextPriorityOperation(onchainOp, currentPriorityRequestId);
+                    currentPriorityRequestId++;
+
+                    pubDataPtr += DEPOSIT_ETH_BYTES;
                 } else {
                     revert("fpp14"); // unsupported op
                 }
@@ -632,6 +752,10 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
             require(Operations.depositPubdataMatch(priorReqPubdata, _onchainOp.pubData), "vnp13");
         } else if (_onchainOp.opType == Operations.OpType.FullExit) {
             require(Operations.fullExitPubdataMatch(priorReqPubdata, _onchainOp.pubData), "vnp14");
+        } else if (_onchainOp.opType == Operations.OpType.ForcedWithdraw) {
+            require(Operations.forcedWithdrawPubdataMatch(priorReqPubdata, _onchainOp.pubData), "vnp16");
+        } else if (_onchainOp.opType == Operations.OpType.DepositEth) {
+            require(Operations.depositEthPubdataMatch(priorReqPubdata, _onchainOp.pubData), "vnp17");
         } else {
             revert("vnp15"); // invalid or non-priority operation
         }
@@ -650,18 +774,14 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
 
         uint offset = 0;
         while (offset < withdrawalsData.length) {
-            (bool addToPendingWithdrawalsQueue, address _to, uint16 _tokenId, uint128 _amount) = Operations.readWithdrawalData(withdrawalsData, offset);
-            bytes22 packedBalanceKey = packAddressAndTokenId(_to, _tokenId);
-
-            uint128 balance = balancesToWithdraw[packedBalanceKey].balanceToWithdraw;
-            balancesToWithdraw[packedBalanceKey].balanceToWithdraw = balance.add(_amount);
+            (bool addToPendingWithdrawalsQueue, address _to, uint16 _collectionId, uint256 _tokenId, uint128 _amount) = Operations.readWithdrawalData(withdrawalsData, offset);
 
             if (addToPendingWithdrawalsQueue) {
-                pendingWithdrawals[firstPendingWithdrawalIndex + numberOfPendingWithdrawals] = PendingWithdrawal(_to, _tokenId);
+                pendingWithdrawals[firstPendingWithdrawalIndex + numberOfPendingWithdrawals] = PendingWithdrawal(_to, _collectionId, _tokenId);
                 numberOfPendingWithdrawals++;
             }
 
-            withdrawalsDataHash = keccak256(abi.encode(withdrawalsDataHash, addToPendingWithdrawalsQueue, _to, _tokenId, _amount));
+            withdrawalsDataHash = keccak256(abi.encode(withdrawalsDataHash, addToPendingWithdrawalsQueue, _to, _collectionId, _tokenId, _amount));
             offset += ONCHAIN_WITHDRAWAL_BYTES;
         }
         require(withdrawalsDataHash == expectedWithdrawalsDataHash, "pow12"); // pow12 - withdrawals data hash not matches with expected value /// <<<--- PB ICI!!
@@ -755,20 +875,18 @@ contract ZkSync is UpgradeableMaster, Storage, Config, Events, ReentrancyGuard,
         }
     }
 
-    /// @notice Withdraws token from Franklin to root chain in case of exodus mode. User must provide proof that he owns funds
+    /// @notice Withdraws NFT from Franklin to root chain in case of exodus mode. User must provide proof that he owns the token
     /// @param _accountId Id of the account in the tree
+    /// @param _collectionId Id of the collection the token belongs to
+    /// @param _tokenId ERC-721 id of the token
     /// @param _proof Proof
-    /// @param _tokenId Verified token id
-    /// @param _amount Amount for owner (must be total amount, not part of it)
-    function exit(uint24 _accountId, uint16 _tokenId, uint128 _amount, uint256[] calldata _proof) external nonReentrant {
-        bytes22 packedBalanceKey = packAddressAndTokenId(msg.sender, _tokenId);
+    function exit(uint24 _accountId, uint16 _collectionId, uint256 _tokenId, uint256[] calldata _proof) external nonReentrant {
         require(exodusMode, "fet11"); // must be in exodus mode
-        require(!exited[_accountId][_tokenId], "fet12"); // already exited
-        require(verifier.verifyExitProof(blocks[totalBlocksVerified].stateRoot, _accountId, msg.sender, _tokenId, _amount, _proof), "fet13"); // verification failed
+        require(!exited[_accountId][_collectionId][_tokenId], "fet12"); // already exited
+        require(verifier.verifyExitProof(blocks[totalBlocksVerified].stateRoot, _accountId, msg.sender, _collectionId, _tokenId, _proof), "fet13"); // verification failed
 
-        uint128 balance = balancesToWithdraw[packedBalanceKey].balanceToWithdraw;
-        balancesToWithdraw[packedBalanceKey].balanceToWithdraw = balance.add(_amount);
-        exited[_accountId][_tokenId] = true;
+        exited[_accountId][_collectionId][_tokenId] = true;
+        require(sendERC721NoRevert(governance.tokenAddresses(_collectionId), msg.sender, _tokenId), "fet14"); // token transfer failed
     }
 
     function setAuthPubkeyHash(bytes calldata _pubkey_hash, uint32 _nonce) external nonReentrant {
@@ -1,18 +1,23 @@
 // External deps
 use crypto_exports::franklin_crypto::{
-    bellman::{ConstraintSystem, SynthesisError},
-    circuit::Assignment,
+    bellman::{pairing::ff::Field, ConstraintSystem, SynthesisError},
+    circuit::{boolean::Boolean, expression::Expression, num::AllocatedNum, Assignment},
+    rescue::RescueEngine,
 };
 // Workspace deps
-use crate::element::CircuitElement;
-use crypto_exports::franklin_crypto::rescue::RescueEngine;
-use models::circuit::account::CircuitAccount;
+use crate::{
+    allocated_structures::AllocatedOperationData, element::CircuitElement, utils::multi_and,
+};
+use models::circuit::account::{CircuitAccount, Token};
 
 #[derive(Clone, Debug)]
 pub struct AccountWitness<E: RescueEngine> {
     pub nonce: Option<E::Fr>,
     pub pub_key_hash: Option<E::Fr>,
     pub address: Option<E::Fr>,
+    pub balance: Option<E::Fr>,
+    /// Root of the tree of the operators approved for all the account tokens.
+    pub operators_root: Option<E::Fr>,
 }
 
 impl<E: RescueEngine> AccountWitness<E> {
@@ -21,6 +26,8 @@ impl<E: RescueEngine> AccountWitness<E> {
             nonce: Some(circuit_account.nonce),
             pub_key_hash: Some(circuit_account.pub_key_hash),
             address: Some(circuit_account.address),
+            balance: Some(circuit_account.balance),
+            operators_root: Some(circuit_account.operators.root_hash()),
         }
     }
 }
@@ -29,6 +36,8 @@ pub struct AccountContent<E: RescueEngine> {
     pub nonce: CircuitElement<E>,
     pub pub_key_hash: CircuitElement<E>,
     pub address: CircuitElement<E>,
+    pub balance: CircuitElement<E>,
+    pub operators_root: AllocatedNum<E>,
 }
 
 impl<E: RescueEngine> AccountContent<E> {
@@ -54,10 +63,298 @@ impl<E: RescueEngine> AccountContent<E> {
             models::params::ETH_ADDRESS_BIT_WIDTH,
         )?;
 
+        let balance = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "balance"),
+            || witness.balance.grab(),
+            models::params::BALANCE_BIT_WIDTH,
+        )?;
+
+        let operators_root = AllocatedNum::alloc(cs.namespace(|| "operators_root"), || {
+            witness.operators_root.grab()
+        })?;
+
         Ok(Self {
             nonce,
             pub_key_hash,
             address,
+            balance,
+            operators_root,
+        })
+    }
+}
+
+/// Token subtree leaf opened by the operation branch, all zeros for an empty slot.
+#[derive(Clone, Debug)]
+pub struct TokenWitness<E: RescueEngine> {
+    pub collection_id: Option<E::Fr>,
+    pub id_hi: Option<E::Fr>,
+    pub id_lo: Option<E::Fr>,
+    pub content_hash_hi: Option<E::Fr>,
+    pub content_hash_lo: Option<E::Fr>,
+    pub approved: Option<E::Fr>,
+}
+
+impl<E: RescueEngine> TokenWitness<E> {
+    pub fn from_token(token: &Token<E>) -> Self {
+        Self {
+            collection_id: Some(token.collection_id),
+            id_hi: Some(token.id_hi),
+            id_lo: Some(token.id_lo),
+            content_hash_hi: Some(token.content_hash_hi),
+            content_hash_lo: Some(token.content_hash_lo),
+            approved: Some(token.approved),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TokenContent<E: RescueEngine> {
+    pub collection_id: CircuitElement<E>,
+    pub id_hi: CircuitElement<E>,
+    pub id_lo: CircuitElement<E>,
+    pub content_hash_hi: CircuitElement<E>,
+    pub content_hash_lo: CircuitElement<E>,
+    /// Operator approved for the token, zero if there is none.
+    pub approved: CircuitElement<E>,
+}
+
+impl<E: RescueEngine> TokenContent<E> {
+    pub fn from_witness<CS: ConstraintSystem<E>>(
+        mut cs: CS,
+        witness: &TokenWitness<E>,
+    ) -> Result<Self, SynthesisError> {
+        let collection_id = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "collection_id"),
+            || witness.collection_id.grab(),
+            models::params::COLLECTIONID_BIT_WIDTH,
+        )?;
+
+        let id_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "id_hi"),
+            || witness.id_hi.grab(),
+            models::params::TOKENID_LIMB_BIT_WIDTH,
+        )?;
+
+        let id_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "id_lo"),
+            || witness.id_lo.grab(),
+            models::params::TOKENID_LIMB_BIT_WIDTH,
+        )?;
+
+        let content_hash_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "content_hash_hi"),
+            || witness.content_hash_hi.grab(),
+            models::params::CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+
+        let content_hash_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "content_hash_lo"),
+            || witness.content_hash_lo.grab(),
+            models::params::CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+
+        let approved = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "approved"),
+            || witness.approved.grab(),
+            models::params::ACCOUNT_ID_BIT_WIDTH,
+        )?;
+
+        Ok(Self {
+            collection_id,
+            id_hi,
+            id_lo,
+            content_hash_hi,
+            content_hash_lo,
+            approved,
+        })
+    }
+
+    /// Token moved by the operation, as given by its arguments. A token gets to
+    /// a new owner without an approved operator.
+    pub fn from_op_data(op_data: &AllocatedOperationData<E>) -> Self {
+        Self {
+            collection_id: op_data.collection_id.clone(),
+            id_hi: op_data.token_id_hi.clone(),
+            id_lo: op_data.token_id_lo.clone(),
+            content_hash_hi: op_data.content_hash_hi.clone(),
+            content_hash_lo: op_data.content_hash_lo.clone(),
+            approved: op_data.no_operator.clone(),
+        }
+    }
+
+    /// Token sold by the order of the second account of a swap.
+    pub fn from_second_order(op_data: &AllocatedOperationData<E>) -> Self {
+        Self {
+            collection_id: op_data.second_collection_id.clone(),
+            id_hi: op_data.second_token_id_hi.clone(),
+            id_lo: op_data.second_token_id_lo.clone(),
+            content_hash_hi: op_data.second_content_hash_hi.clone(),
+            content_hash_lo: op_data.second_content_hash_lo.clone(),
+            approved: op_data.no_operator.clone(),
+        }
+    }
+
+    /// Bits of the token subtree leaf, same layout as `models::circuit::account::Token`.
+    pub fn leaf_bits_le(&self) -> Vec<Boolean> {
+        let mut bits = self.id_lo.get_bits_le();
+        bits.extend(self.id_hi.get_bits_le());
+        bits.extend(self.collection_id.get_bits_le());
+        bits.extend(self.content_hash_lo.get_bits_le());
+        bits.extend(self.content_hash_hi.get_bits_le());
+        bits.extend(self.approved.get_bits_le());
+        bits
+    }
+
+    /// Bits of the token list tree leaf, the same as of the subtree leaf
+    /// without content hash and approved operator.
+    pub fn nft_leaf_bits_le(&self) -> Vec<Boolean> {
+        let mut bits = self.id_lo.get_bits_le();
+        bits.extend(self.id_hi.get_bits_le());
+        bits.extend(self.collection_id.get_bits_le());
+        bits.resize(
+            bits.len()
+                + models::params::CONTENT_HASH_BIT_WIDTH
+                + models::params::ACCOUNT_ID_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+        bits
+    }
+
+    /// An empty slot holds the all-zero leaf. Limbs and the operator id are at most 128 bits wide,
+    /// so their sum can't overflow the field and is zero only if each of them is.
+    pub fn is_empty<CS: ConstraintSystem<E>>(&self, mut cs: CS) -> Result<Boolean, SynthesisError> {
+        let limbs_sum = Expression::from(&self.collection_id.get_number())
+            + Expression::from(&self.id_hi.get_number())
+            + Expression::from(&self.id_lo.get_number())
+            + Expression::from(&self.content_hash_hi.get_number())
+            + Expression::from(&self.content_hash_lo.get_number())
+            + Expression::from(&self.approved.get_number());
+
+        Ok(Boolean::from(Expression::equals(
+            cs.namespace(|| "are all token limbs zero"),
+            limbs_sum,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?))
+    }
+
+    /// Compares the held tokens and their content, whoever is approved for them.
+    pub fn equals<CS: ConstraintSystem<E>>(
+        mut cs: CS,
+        x: &Self,
+        y: &Self,
+    ) -> Result<Boolean, SynthesisError> {
+        let flags = vec![
+            CircuitElement::equals(
+                cs.namespace(|| "is collection_id equal"),
+                &x.collection_id,
+                &y.collection_id,
+            )?,
+            CircuitElement::equals(cs.namespace(|| "is id_hi equal"), &x.id_hi, &y.id_hi)?,
+            CircuitElement::equals(cs.namespace(|| "is id_lo equal"), &x.id_lo, &y.id_lo)?,
+            CircuitElement::equals(
+                cs.namespace(|| "is content_hash_hi equal"),
+                &x.content_hash_hi,
+                &y.content_hash_hi,
+            )?,
+            CircuitElement::equals(
+                cs.namespace(|| "is content_hash_lo equal"),
+                &x.content_hash_lo,
+                &y.content_hash_lo,
+            )?,
+        ];
+        multi_and(cs.namespace(|| "are tokens equal"), &flags)
+    }
+
+    pub fn conditionally_select<CS: ConstraintSystem<E>>(
+        mut cs: CS,
+        x: &Self,
+        y: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            collection_id: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen collection_id"),
+                &x.collection_id,
+                &y.collection_id,
+                condition,
+            )?,
+            id_hi: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen id_hi"),
+                &x.id_hi,
+                &y.id_hi,
+                condition,
+            )?,
+            id_lo: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen id_lo"),
+                &x.id_lo,
+                &y.id_lo,
+                condition,
+            )?,
+            content_hash_hi: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen content_hash_hi"),
+                &x.content_hash_hi,
+                &y.content_hash_hi,
+                condition,
+            )?,
+            content_hash_lo: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen content_hash_lo"),
+                &x.content_hash_lo,
+                &y.content_hash_lo,
+                condition,
+            )?,
+            approved: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen approved"),
+                &x.approved,
+                &y.approved,
+                condition,
+            )?,
+        })
+    }
+
+    /// Empties the slot if the condition holds.
+    pub fn conditionally_clear<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError> {
+        let zero = || Expression::constant::<CS>(E::Fr::zero());
+        Ok(Self {
+            collection_id: CircuitElement::conditionally_select_with_number_strict(
+                cs.namespace(|| "cleared collection_id"),
+                zero(),
+                &self.collection_id,
+                condition,
+            )?,
+            id_hi: CircuitElement::conditionally_select_with_number_strict(
+                cs.namespace(|| "cleared id_hi"),
+                zero(),
+                &self.id_hi,
+                condition,
+            )?,
+            id_lo: CircuitElement::conditionally_select_with_number_strict(
+                cs.namespace(|| "cleared id_lo"),
+                zero(),
+                &self.id_lo,
+                condition,
+            )?,
+            content_hash_hi: CircuitElement::conditionally_select_with_number_strict(
+                cs.namespace(|| "cleared content_hash_hi"),
+                zero(),
+                &self.content_hash_hi,
+                condition,
+            )?,
+            content_hash_lo: CircuitElement::conditionally_select_with_number_strict(
+                cs.namespace(|| "cleared content_hash_lo"),
+                zero(),
+                &self.content_hash_lo,
+                condition,
+            )?,
+            approved: CircuitElement::conditionally_select_with_number_strict(
+                cs.namespace(|| "cleared approved"),
+                zero(),
+                &self.approved,
+                condition,
+            )?,
         })
     }
 }
@@ -1,7 +1,10 @@
 // External deps
 use crypto_exports::franklin_crypto::{
     bellman::{
-        pairing::{ff::PrimeField, Engine},
+        pairing::{
+            ff::{Field, PrimeField},
+            Engine,
+        },
         ConstraintSystem, SynthesisError,
     },
     circuit::{
@@ -13,7 +16,7 @@ use crypto_exports::franklin_crypto::{
 use models::params as franklin_constants;
 // Local deps
 use crate::{
-    account::{self, AccountContent},
+    account::{self, AccountContent, TokenContent},
     element::CircuitElement,
     operation::{Operation, OperationBranch},
     utils,
@@ -25,7 +28,8 @@ pub struct AllocatedOperationBranch<E: RescueEngine> {
     pub account_id: CircuitElement<E>,
     //pub balance: CircuitElement<E>,
     pub token_audit_path: Vec<AllocatedNum<E>>,
-    //pub token: CircuitElement<E>,
+    pub token: TokenContent<E>,
+    pub token_slot: CircuitElement<E>,
 }
 
 impl<E: RescueEngine> AllocatedOperationBranch<E> {
@@ -66,6 +70,15 @@ impl<E: RescueEngine> AllocatedOperationBranch<E> {
             franklin_constants::balance_tree_depth(),
         )?;
         let token = token.pad(franklin_constants::TOKEN_BIT_WIDTH);*/
+        let token = account::TokenContent::from_witness(
+            cs.namespace(|| "allocate token_content"),
+            &operation_branch.witness.token_witness,
+        )?;
+        let token_slot = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "token_slot"),
+            || Ok(operation_branch.token_slot.grab()?),
+            franklin_constants::token_tree_depth(),
+        )?;
         let token_audit_path = utils::allocate_numbers_vec(
             cs.namespace(|| "token_audit_path"),
             &operation_branch.witness.token_subtree_path,
@@ -80,8 +93,9 @@ impl<E: RescueEngine> AllocatedOperationBranch<E> {
             account_audit_path,
             account_id: account_address,
             //balance,
-            //token,
+            token,
             token_audit_path,
+            token_slot,
         })
     }
 }
@@ -98,7 +112,7 @@ pub struct AllocatedOperationData<E: Engine> {
     //pub amount_packed: CircuitElement<E>,
     pub fee_packed: CircuitElement<E>,
     //pub amount_unpacked: CircuitElement<E>,
-    //pub full_amount: CircuitElement<E>,
+    pub full_amount: CircuitElement<E>,
     pub fee: CircuitElement<E>,
     pub first_sig_msg: CircuitElement<E>,
     pub second_sig_msg: CircuitElement<E>,
@@ -108,7 +122,34 @@ pub struct AllocatedOperationData<E: Engine> {
     pub pub_nonce: CircuitElement<E>,
     //pub a: CircuitElement<E>,
     //pub b: CircuitElement<E>,
-    pub token_id: CircuitElement<E>,
+    pub collection_id: CircuitElement<E>,
+    pub token_id_hi: CircuitElement<E>,
+    pub token_id_lo: CircuitElement<E>,
+    pub content_hash_hi: CircuitElement<E>,
+    pub content_hash_lo: CircuitElement<E>,
+    pub operator_pub_key_hash: CircuitElement<E>,
+    pub operator_id: CircuitElement<E>,
+    pub is_operator: CircuitElement<E>,
+    pub approved: CircuitElement<E>,
+    pub operator_path: Vec<AllocatedNum<E>>,
+    /// Constant zero operator id, the approval of a token that changes its owner.
+    pub no_operator: CircuitElement<E>,
+    pub valid_from: CircuitElement<E>,
+    pub valid_until: CircuitElement<E>,
+    pub fee_payer: CircuitElement<E>,
+    pub tokens_count: CircuitElement<E>,
+    pub tokens_root: CircuitElement<E>,
+    pub token_index: CircuitElement<E>,
+    pub token_list_path: Vec<AllocatedNum<E>>,
+    pub second_collection_id: CircuitElement<E>,
+    pub second_token_id_hi: CircuitElement<E>,
+    pub second_token_id_lo: CircuitElement<E>,
+    pub second_content_hash_hi: CircuitElement<E>,
+    pub second_content_hash_lo: CircuitElement<E>,
+    pub second_fee_packed: CircuitElement<E>,
+    pub second_fee: CircuitElement<E>,
+    pub second_tokens_count: CircuitElement<E>,
+    pub second_tokens_root: CircuitElement<E>,
 }
 
 impl<E: RescueEngine> AllocatedOperationData<E> {
@@ -118,12 +159,12 @@ impl<E: RescueEngine> AllocatedOperationData<E> {
             franklin_constants::ETH_ADDRESS_BIT_WIDTH,
         );
 
-        /*let full_amount = CircuitElement::unsafe_empty_of_some_length(
+        let full_amount = CircuitElement::unsafe_empty_of_some_length(
             zero_element.clone(),
             franklin_constants::BALANCE_BIT_WIDTH,
         );
 
-        let amount_packed = CircuitElement::unsafe_empty_of_some_length(
+        /*let amount_packed = CircuitElement::unsafe_empty_of_some_length(
             zero_element.clone(),
             franklin_constants::AMOUNT_EXPONENT_BIT_WIDTH
                 + franklin_constants::AMOUNT_MANTISSA_BIT_WIDTH,
@@ -179,11 +220,129 @@ impl<E: RescueEngine> AllocatedOperationData<E> {
             franklin_constants::BALANCE_BIT_WIDTH,
         );*/
 
-        let token_id = CircuitElement::unsafe_empty_of_some_length(
-            zero_element,
-            franklin_constants::TOKENID_BIT_WIDTH,
+        let collection_id = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::COLLECTIONID_BIT_WIDTH,
+        );
+
+        let token_id_hi = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        );
+
+        let token_id_lo = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        );
+
+        let content_hash_hi = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        );
+
+        let content_hash_lo = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        );
+
+        let operator_pub_key_hash = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::NEW_PUBKEY_HASH_WIDTH,
+        );
+
+        let operator_id = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+
+        let is_operator = CircuitElement::unsafe_empty_of_some_length(zero_element.clone(), 1);
+
+        let approved = CircuitElement::unsafe_empty_of_some_length(zero_element.clone(), 1);
+
+        let operator_path = vec![zero_element.clone(); franklin_constants::account_tree_depth()];
+
+        let no_operator = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+
+        let valid_from = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        );
+
+        let valid_until = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        );
+
+        let fee_payer = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+
+        let tokens_count = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TOKENS_COUNT_BIT_WIDTH,
+        );
+
+        let tokens_root = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::FR_BIT_WIDTH,
+        );
+
+        let token_index = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::NFT_LIST_TREE_DEPTH,
+        );
+
+        let second_collection_id = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::COLLECTIONID_BIT_WIDTH,
+        );
+
+        let second_token_id_hi = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        );
+
+        let second_token_id_lo = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        );
+
+        let second_content_hash_hi = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
         );
 
+        let second_content_hash_lo = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        );
+
+        let second_fee_packed = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::FEE_EXPONENT_BIT_WIDTH + franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+        );
+
+        let second_fee = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::BALANCE_BIT_WIDTH,
+        );
+
+        let second_tokens_count = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::TOKENS_COUNT_BIT_WIDTH,
+        );
+
+        let second_tokens_root = CircuitElement::unsafe_empty_of_some_length(
+            zero_element.clone(),
+            franklin_constants::FR_BIT_WIDTH,
+        );
+
+        let token_list_path = vec![zero_element; franklin_constants::NFT_LIST_TREE_DEPTH];
+
         Ok(AllocatedOperationData {
             eth_address,
             pub_nonce,
@@ -191,14 +350,40 @@ impl<E: RescueEngine> AllocatedOperationData<E> {
             fee_packed,
             fee,
             //amount_unpacked,
-            //full_amount,
+            full_amount,
             first_sig_msg,
             second_sig_msg,
             third_sig_msg,
             new_pubkey_hash,
             //a,
             //b,
-            token_id,
+            collection_id,
+            token_id_hi,
+            token_id_lo,
+            content_hash_hi,
+            content_hash_lo,
+            operator_pub_key_hash,
+            operator_id,
+            is_operator,
+            approved,
+            operator_path,
+            no_operator,
+            valid_from,
+            valid_until,
+            fee_payer,
+            tokens_count,
+            tokens_root,
+            token_index,
+            token_list_path,
+            second_collection_id,
+            second_token_id_hi,
+            second_token_id_lo,
+            second_content_hash_hi,
+            second_content_hash_lo,
+            second_fee_packed,
+            second_fee,
+            second_tokens_count,
+            second_tokens_root,
         })
     }
 
@@ -212,12 +397,12 @@ impl<E: RescueEngine> AllocatedOperationData<E> {
             franklin_constants::ETH_ADDRESS_BIT_WIDTH,
         )?;
 
-        /*let full_amount = CircuitElement::from_fe_with_known_length(
+        let full_amount = CircuitElement::from_fe_with_known_length(
             cs.namespace(|| "full_amount"),
             || op.args.full_amount.grab(),
             franklin_constants::BALANCE_BIT_WIDTH,
         )?;
-        let amount_packed = CircuitElement::from_fe_with_known_length(
+        /*let amount_packed = CircuitElement::from_fe_with_known_length(
             cs.namespace(|| "amount_packed"),
             || op.args.amount_packed.grab(),
             franklin_constants::AMOUNT_EXPONENT_BIT_WIDTH
@@ -294,11 +479,150 @@ impl<E: RescueEngine> AllocatedOperationData<E> {
             || op.args.b.grab(),
             franklin_constants::BALANCE_BIT_WIDTH,
         )?;*/
-        let token_id = CircuitElement::from_fe_with_known_length(
-            cs.namespace(|| "token_id"),
-            || op.args.token_id.grab(),
-            franklin_constants::TOKENID_BIT_WIDTH,
+        let collection_id = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "collection_id"),
+            || op.args.collection_id.grab(),
+            franklin_constants::COLLECTIONID_BIT_WIDTH,
+        )?;
+        let token_id_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "token_id_hi"),
+            || op.args.token_id_hi.grab(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
         )?;
+        let token_id_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "token_id_lo"),
+            || op.args.token_id_lo.grab(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        )?;
+        let content_hash_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "content_hash_hi"),
+            || op.args.content_hash_hi.grab(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+        let content_hash_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "content_hash_lo"),
+            || op.args.content_hash_lo.grab(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+        let operator_pub_key_hash = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "operator_pub_key_hash"),
+            || op.args.operator_pub_key_hash.grab(),
+            franklin_constants::NEW_PUBKEY_HASH_WIDTH,
+        )?;
+        let operator_id = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "operator_id"),
+            || op.args.operator_id.grab(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        )?;
+        let is_operator = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "is_operator"),
+            || op.args.is_operator.grab(),
+            1,
+        )?;
+        let approved = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "approved"),
+            || op.args.approved.grab(),
+            1,
+        )?;
+        let operator_path =
+            utils::allocate_numbers_vec(cs.namespace(|| "operator_path"), &op.args.operator_path)?;
+        assert_eq!(
+            operator_path.len(),
+            franklin_constants::account_tree_depth()
+        );
+        let zero = AllocatedNum::alloc(cs.namespace(|| "no_operator"), || Ok(E::Fr::zero()))?;
+        zero.assert_zero(cs.namespace(|| "enforce zero on no_operator"))?;
+        let no_operator = CircuitElement::unsafe_empty_of_some_length(
+            zero,
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        let valid_from = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "valid_from"),
+            || op.args.valid_from.grab(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        )?;
+        let valid_until = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "valid_until"),
+            || op.args.valid_until.grab(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        )?;
+        let fee_payer = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "fee_payer"),
+            || op.args.fee_payer.grab(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        )?;
+        let tokens_count = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "tokens_count"),
+            || op.args.tokens_count.grab(),
+            franklin_constants::TOKENS_COUNT_BIT_WIDTH,
+        )?;
+        let tokens_root = CircuitElement::from_fe(cs.namespace(|| "tokens_root"), || {
+            op.args.tokens_root.grab()
+        })?;
+        let token_index = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "token_index"),
+            || op.args.token_index.grab(),
+            franklin_constants::NFT_LIST_TREE_DEPTH,
+        )?;
+        let token_list_path = utils::allocate_numbers_vec(
+            cs.namespace(|| "token_list_path"),
+            &op.args.token_list_path,
+        )?;
+        assert_eq!(
+            token_list_path.len(),
+            franklin_constants::NFT_LIST_TREE_DEPTH
+        );
+        let second_collection_id = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_collection_id"),
+            || op.args.second_collection_id.grab(),
+            franklin_constants::COLLECTIONID_BIT_WIDTH,
+        )?;
+        let second_token_id_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_token_id_hi"),
+            || op.args.second_token_id_hi.grab(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        )?;
+        let second_token_id_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_token_id_lo"),
+            || op.args.second_token_id_lo.grab(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        )?;
+        let second_content_hash_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_content_hash_hi"),
+            || op.args.second_content_hash_hi.grab(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+        let second_content_hash_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_content_hash_lo"),
+            || op.args.second_content_hash_lo.grab(),
+            franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+        let second_fee_packed = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_fee_packed"),
+            || op.args.second_fee.grab(),
+            franklin_constants::FEE_EXPONENT_BIT_WIDTH + franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+        )?;
+        let second_fee_parsed = parse_with_exponent_le(
+            cs.namespace(|| "parse second fee"),
+            &second_fee_packed.get_bits_le(),
+            franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+            10,
+        )?;
+        let second_fee = CircuitElement::from_number_with_known_length(
+            cs.namespace(|| "second_fee"),
+            second_fee_parsed,
+            franklin_constants::BALANCE_BIT_WIDTH,
+        )?;
+        let second_tokens_count = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "second_tokens_count"),
+            || op.args.second_tokens_count.grab(),
+            franklin_constants::TOKENS_COUNT_BIT_WIDTH,
+        )?;
+        let second_tokens_root =
+            CircuitElement::from_fe(cs.namespace(|| "second_tokens_root"), || {
+                op.args.second_tokens_root.grab()
+            })?;
 
         Ok(AllocatedOperationData {
             eth_address,
@@ -307,14 +631,61 @@ impl<E: RescueEngine> AllocatedOperationData<E> {
             fee_packed,
             fee,
             //amount_unpacked,
-            //full_amount,
+            full_amount,
             first_sig_msg,
             second_sig_msg,
             third_sig_msg,
             new_pubkey_hash,
             //a,
             //b,
-            token_id,
+            collection_id,
+            token_id_hi,
+            token_id_lo,
+            content_hash_hi,
+            content_hash_lo,
+            operator_pub_key_hash,
+            operator_id,
+            is_operator,
+            approved,
+            operator_path,
+            no_operator,
+            valid_from,
+            valid_until,
+            fee_payer,
+            tokens_count,
+            tokens_root,
+            token_index,
+            token_list_path,
+            second_collection_id,
+            second_token_id_hi,
+            second_token_id_lo,
+            second_content_hash_hi,
+            second_content_hash_lo,
+            second_fee_packed,
+            second_fee,
+            second_tokens_count,
+            second_tokens_root,
         })
     }
+
+    /// Bits of the full 256-bit token id, most significant limb first.
+    pub fn token_id_bits_be(&self) -> Vec<Boolean> {
+        let mut bits = self.token_id_hi.get_bits_be();
+        bits.extend(self.token_id_lo.get_bits_be());
+        bits
+    }
+
+    /// Bits of the full 256-bit token id, least significant limb first.
+    pub fn token_id_bits_le(&self) -> Vec<Boolean> {
+        let mut bits = self.token_id_lo.get_bits_le();
+        bits.extend(self.token_id_hi.get_bits_le());
+        bits
+    }
+
+    /// Bits of the full 256-bit content hash, most significant limb first.
+    pub fn content_hash_bits_be(&self) -> Vec<Boolean> {
+        let mut bits = self.content_hash_hi.get_bits_be();
+        bits.extend(self.content_hash_lo.get_bits_be());
+        bits
+    }
 }
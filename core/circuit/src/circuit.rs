@@ -18,15 +18,21 @@ use crypto_exports::franklin_crypto::{
 };
 // Workspace deps
 use models::{
+    circuit::account::Token,
     node::{
         operations::{ChangePubKeyOp, NoopOp},
-        CloseOp, DepositOp, FullExitOp, TransferOp, TransferToNewOp, WithdrawOp,
+        ApproveForAllOp, ApproveOp, BatchTransferOp, BurnOp, CloseOp, DepositEthOp, DepositOp,
+        ForcedWithdrawOp, FullExitOp, MintOp, SwapOp, TransferOp, TransferToNewOp, WithdrawOp,
     },
-    params::{self, FR_BIT_WIDTH_PADDED, SIGNED_TRANSFER_BIT_WIDTH},
+    params::{
+        self, FR_BIT_WIDTH_PADDED, SIGNED_BATCH_TRANSFER_BIT_WIDTH, SIGNED_ORDER_BIT_WIDTH,
+        SIGNED_TRANSFER_BIT_WIDTH,
+    },
+    primitives::GetBits,
 };
 // Local deps
 use crate::{
-    account::{AccountContent, AccountWitness},
+    account::{AccountContent, AccountWitness, TokenContent},
     allocated_structures::*,
     element::CircuitElement,
     operation::Operation,
@@ -37,7 +43,7 @@ use crate::{
     utils::{allocate_numbers_vec, allocate_sum, multi_and, pack_bits_to_element},
 };
 
-const DIFFERENT_TRANSACTIONS_TYPE_NUMBER: usize = 8;
+const DIFFERENT_TRANSACTIONS_TYPE_NUMBER: usize = 16;
 pub struct FranklinCircuit<'a, E: RescueEngine + JubjubEngine> {
     pub rescue_params: &'a <E as RescueEngine>::Params,
     pub jubjub_params: &'a <E as JubjubEngine>::Params,
@@ -50,7 +56,9 @@ pub struct FranklinCircuit<'a, E: RescueEngine + JubjubEngine> {
     pub pub_data_commitment: Option<E::Fr>,
     pub operations: Vec<Operation<E>>,
 
-    pub validator_balances: Vec<Option<E::Fr>>,
+    /// Root of the operator account token subtree. NFT operations do not collect fees
+    /// into it, so it is the same before and after the block.
+    pub validator_token_root: Option<E::Fr>,
     pub validator_audit_path: Vec<Option<E::Fr>>,
     pub validator_account: AccountWitness<E>,
 }
@@ -66,7 +74,7 @@ impl<'a, E: RescueEngine + JubjubEngine> std::clone::Clone for FranklinCircuit<'
             pub_data_commitment: self.pub_data_commitment,
             operations: self.operations.clone(),
 
-            validator_balances: self.validator_balances.clone(),
+            validator_token_root: self.validator_token_root,
             validator_audit_path: self.validator_audit_path.clone(),
             validator_account: self.validator_account.clone(),
         }
@@ -75,6 +83,11 @@ impl<'a, E: RescueEngine + JubjubEngine> std::clone::Clone for FranklinCircuit<'
 
 struct PreviousData<E: RescueEngine> {
     op_data: AllocatedOperationData<E>,
+    /// Accounts of the first chunk of the op, its later chunks may only change them.
+    lhs_account_id: CircuitElement<E>,
+    rhs_account_id: CircuitElement<E>,
+    /// Message signed in the first chunk of the op, a sponsor of the op signs its hash later.
+    tx_sig_msg: Vec<CircuitElement<E>>,
 }
 
 // Implementation of our circuit:
@@ -89,8 +102,22 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
         // we only need this for consistency of first operation
         let zero_circuit_element = CircuitElement::unsafe_empty_of_some_length(zero.clone(), 256);
 
+        let empty_op_data = AllocatedOperationData::empty_from_zero(zero.clone())?;
         let mut prev = PreviousData {
-            op_data: AllocatedOperationData::empty_from_zero(zero.clone())?,
+            tx_sig_msg: vec![
+                empty_op_data.first_sig_msg.clone(),
+                empty_op_data.second_sig_msg.clone(),
+                empty_op_data.third_sig_msg.clone(),
+            ],
+            op_data: empty_op_data,
+            lhs_account_id: CircuitElement::unsafe_empty_of_some_length(
+                zero.clone(),
+                params::ACCOUNT_ID_BIT_WIDTH,
+            ),
+            rhs_account_id: CircuitElement::unsafe_empty_of_some_length(
+                zero.clone(),
+                params::ACCOUNT_ID_BIT_WIDTH,
+            ),
         };
         // this is only public input to our circuit
         let public_data_commitment =
@@ -108,11 +135,12 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
         let validator_address_bits = validator_address_padded.get_bits_le();
         assert_eq!(validator_address_bits.len(), params::ACCOUNT_ID_BIT_WIDTH);
 
-        let mut validator_balances = allocate_numbers_vec(
-            cs.namespace(|| "validator_balances"),
-            &self.validator_balances,
-        )?;
-        //assert_eq!(validator_balances.len(), params::total_tokens());
+        // the operator token subtree is as deep as any other account one, so it is
+        // witnessed by its root rather than by the full list of leaves
+        let validator_token_root =
+            AllocatedNum::alloc(cs.namespace(|| "validator_token_root"), || {
+                self.validator_token_root.grab()
+            })?;
 
         let validator_audit_path = allocate_numbers_vec(
             cs.namespace(|| "validator_audit_path"),
@@ -130,15 +158,12 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
 
         let old_root =
             CircuitElement::from_number(cs.namespace(|| "old_root"), rolling_root.clone())?;
+        // ETH fees of the block, all of them go to the validator
+        let mut fees = zero.clone();
+
         // first chunk of block should always have number 0
         let mut next_chunk_number = zero;
 
-        // declare vector of fees, that will be collected during block processing
-        let mut fees = vec![];
-        let fees_len = params::total_tokens();
-        for _ in 0..fees_len {
-            fees.push(zero_circuit_element.get_number());
-        }
         // vector of pub_data_bits that will be aggregated during block processing
         let mut block_pub_data_bits = vec![];
 
@@ -153,8 +178,14 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
         for (i, operation) in self.operations.iter().enumerate() {
             let cs = &mut cs.namespace(|| format!("chunk number {}", i));
 
+            let op_data = AllocatedOperationData::from_witness(
+                cs.namespace(|| "allocated_operation_data"),
+                operation,
+            )?;
+
             let (next_chunk, chunk_data) = self.verify_correct_chunking(
                 &operation,
+                &op_data,
                 &next_chunk_number,
                 cs.namespace(|| "verify_correct_chunking"),
             )?;
@@ -180,14 +211,12 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
                 &allocated_chunk_data,
             )?;
             // calculate root for given account data
-            let op_data = AllocatedOperationData::from_witness(
-                cs.namespace(|| "allocated_operation_data"),
-                operation,
-            )?;
-            let (state_root, is_account_empty, _subtree_root) = check_account_data(
+            let token_slot = current_branch.token_slot.get_bits_le();
+            let (state_root, is_account_empty, subtree_root) = check_account_data(
                 cs.namespace(|| "calculate account root"),
                 &current_branch,
-                &op_data.token_id,
+                &current_branch.token.leaf_bits_le(),
+                &token_slot,
                 self.rescue_params,
             )?;
 
@@ -204,17 +233,19 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
                 &lhs,
                 &rhs,
                 &operation,
+                &op_data,
                 &allocated_chunk_data,
                 &is_account_empty,
                 &operation_pub_data_chunk.get_number(),
-                // &subtree_root, // Close disable
+                &subtree_root,
                 &mut fees,
                 &mut prev,
             )?;
             let (new_state_root, _, _) = check_account_data(
                 cs.namespace(|| "calculate new account root"),
                 &current_branch,
-                &op_data.token_id,
+                &current_branch.token.leaf_bits_le(),
+                &token_slot,
                 self.rescue_params,
             )?;
 
@@ -232,30 +263,24 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
             |lc| lc + CS::one(),
         );
 
-        // calculate operator's balance_tree root hash from whole tree representation
-        let old_operator_balance_root = calculate_root_from_full_representation_fees(
-            cs.namespace(|| "calculate_root_from_full_representation_fees before"),
-            &validator_balances,
-            self.rescue_params,
+        let validator_token_root = CircuitElement::from_number(
+            cs.namespace(|| "validator_token_root_ce"),
+            validator_token_root,
         )?;
 
         let mut operator_account_data = vec![];
-        let old_operator_state_root = {
-            let balance_root = CircuitElement::from_number(
-                cs.namespace(|| "old_operator_balance_root_ce"),
-                old_operator_balance_root,
-            )?;
-            calc_account_state_tree_root(
-                cs.namespace(|| "old_operator_state_root"),
-                &balance_root,
-                &self.rescue_params,
-            )?
-        };
+        let old_operator_state_root = calc_account_state_tree_root(
+            cs.namespace(|| "old_operator_state_root"),
+            &validator_token_root,
+            &validator_account.balance,
+            &validator_account.operators_root,
+            &self.rescue_params,
+        )?;
         operator_account_data.extend(validator_account.nonce.get_bits_le());
         operator_account_data.extend(validator_account.pub_key_hash.get_bits_le());
         operator_account_data.extend(validator_account.address.get_bits_le());
         operator_account_data
-        .extend(old_operator_state_root.into_padded_le_bits(FR_BIT_WIDTH_PADDED));
+            .extend(old_operator_state_root.into_padded_le_bits(FR_BIT_WIDTH_PADDED));
 
         let root_from_operator = allocate_merkle_root(
             cs.namespace(|| "root from operator_account"),
@@ -273,39 +298,31 @@ impl<'a, E: RescueEngine + JubjubEngine> Circuit<E> for FranklinCircuit<'a, E> {
             |lc| lc + rolling_root.get_variable(),
         );
 
-        //apply fees to operator balances
-        for i in 0..fees_len {
-            validator_balances[i] = allocate_sum(
-                cs.namespace(|| format!("validator balance number i {}", i)),
-                &validator_balances[i],
-                &fees[i],
-            )?;
-        }
-
-        // calculate operator's balance_tree root from all leafs
-        let new_operator_balance_root = calculate_root_from_full_representation_fees(
-            cs.namespace(|| "calculate_root_from_full_representation_fees after"),
-            &validator_balances,
-            self.rescue_params,
+        //apply fees to operator balance
+        let validator_balance = allocate_sum(
+            cs.namespace(|| "validator balance with fees"),
+            &validator_account.balance.get_number(),
+            &fees,
+        )?;
+        let validator_balance = CircuitElement::from_number_with_known_length(
+            cs.namespace(|| "validator balance with fees ce"),
+            validator_balance,
+            params::BALANCE_BIT_WIDTH,
         )?;
 
         let mut operator_account_data = vec![];
-        let new_operator_state_root = {
-            let balance_root = CircuitElement::from_number(
-                cs.namespace(|| "new_operator_balance_root_ce"),
-                new_operator_balance_root,
-            )?;
-            calc_account_state_tree_root(
-                cs.namespace(|| "new_operator_state_root"),
-                &balance_root,
-                &self.rescue_params,
-            )?
-        };
+        let new_operator_state_root = calc_account_state_tree_root(
+            cs.namespace(|| "new_operator_state_root"),
+            &validator_token_root,
+            &validator_balance,
+            &validator_account.operators_root,
+            &self.rescue_params,
+        )?;
         operator_account_data.extend(validator_account.nonce.get_bits_le());
         operator_account_data.extend(validator_account.pub_key_hash.get_bits_le());
         operator_account_data.extend(validator_account.address.get_bits_le());
         operator_account_data
-        .extend(new_operator_state_root.into_padded_le_bits(FR_BIT_WIDTH_PADDED));
+            .extend(new_operator_state_root.into_padded_le_bits(FR_BIT_WIDTH_PADDED));
 
         let root_from_operator_after_fees = allocate_merkle_root(
             cs.namespace(|| "root from operator_account after fees"),
@@ -381,6 +398,7 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
     fn verify_correct_chunking<CS: ConstraintSystem<E>>(
         &self,
         op: &Operation<E>,
+        op_data: &AllocatedOperationData<E>,
         next_chunk_number: &AllocatedNum<E>,
         mut cs: CS,
     ) -> Result<(AllocatedNum<E>, AllocatedChunkData<E>), SynthesisError> {
@@ -403,6 +421,23 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &max_chunks_last_coeffs,
             &max_chunks_powers,
         )?;
+
+        // batch transfer is as long as its token list, so its last chunk is not on the polynomial
+        let is_batch_transfer = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_batch_transfer"),
+            &tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(BatchTransferOp::OP_CODE)),
+        )?);
+        let batch_transfer_max_chunk = (0..BatchTransferOp::TOKEN_CHUNKS).fold(
+            Expression::u64::<CS>(BatchTransferOp::HEADER_CHUNKS as u64 - 1),
+            |max_chunk, _| max_chunk + Expression::from(&op_data.tokens_count.get_number()),
+        );
+        let max_chunk = Expression::conditionally_select(
+            cs.namespace(|| "max_chunk of the op"),
+            batch_transfer_max_chunk,
+            &max_chunk,
+            &is_batch_transfer,
+        )?;
         let operation_chunk_number =
             AllocatedNum::alloc(cs.namespace(|| "operation_chunk_number"), || {
                 op.chunk.grab()
@@ -493,6 +528,18 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
                     &second.account.address,
                     &is_left,
                 )?,
+                balance: CircuitElement::conditionally_select(
+                    cs.namespace(|| "chosen account balance"),
+                    &first.account.balance,
+                    &second.account.balance,
+                    &is_left,
+                )?,
+                operators_root: AllocatedNum::conditionally_select(
+                    cs.namespace(|| "chosen operators_root"),
+                    &first.account.operators_root,
+                    &second.account.operators_root,
+                    &is_left,
+                )?,
             },
             account_audit_path: select_vec_ifeq(
                 cs.namespace(|| "account_audit_path"),
@@ -520,12 +567,18 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
                 &first.token_audit_path,
                 &second.token_audit_path,
             )?,
-            /*token: CircuitElement::conditionally_select(
+            token: TokenContent::conditionally_select(
                 cs.namespace(|| "chosen token"),
                 &first.token,
                 &second.token,
                 &is_left,
-            )?,*/
+            )?,
+            token_slot: CircuitElement::conditionally_select(
+                cs.namespace(|| "chosen token_slot"),
+                &first.token_slot,
+                &second.token_slot,
+                &is_left,
+            )?,
         })
     }
 
@@ -536,11 +589,12 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         lhs: &AllocatedOperationBranch<E>,
         rhs: &AllocatedOperationBranch<E>,
         op: &Operation<E>,
+        op_data: &AllocatedOperationData<E>,
         chunk_data: &AllocatedChunkData<E>,
         is_account_empty: &Boolean,
         ext_pubdata_chunk: &AllocatedNum<E>,
-        // subtree_root: &CircuitElement<E>, // Close disable
-        fees: &mut [AllocatedNum<E>],
+        subtree_root: &CircuitElement<E>,
+        fees: &mut AllocatedNum<E>,
         prev: &mut PreviousData<E>,
     ) -> Result<(), SynthesisError> {
         /*cs.enforce(
@@ -560,8 +614,6 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             self.jubjub_params,
         )?;
 
-        let op_data =
-            AllocatedOperationData::from_witness(cs.namespace(|| "allocated_operation_data"), op)?;
         // ensure op_data is equal to previous
         {
             let mut is_op_data_correct_flags = vec![];
@@ -595,16 +647,147 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
                 &op_data.new_pubkey_hash,
                 &prev.op_data.new_pubkey_hash,
             )?);
+
+            let mut is_token_data_correct_flags = vec![];
+            is_token_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is collection_id equal to previous"),
+                &op_data.collection_id,
+                &prev.op_data.collection_id,
+            )?);
+            is_token_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is token_id_hi equal to previous"),
+                &op_data.token_id_hi,
+                &prev.op_data.token_id_hi,
+            )?);
+            is_token_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is token_id_lo equal to previous"),
+                &op_data.token_id_lo,
+                &prev.op_data.token_id_lo,
+            )?);
+            is_token_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is content_hash_hi equal to previous"),
+                &op_data.content_hash_hi,
+                &prev.op_data.content_hash_hi,
+            )?);
+            is_token_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is content_hash_lo equal to previous"),
+                &op_data.content_hash_lo,
+                &prev.op_data.content_hash_lo,
+            )?);
+            let is_token_data_equal_to_previous = multi_and(
+                cs.namespace(|| "is_token_data_equal_to_previous"),
+                &is_token_data_correct_flags,
+            )?;
+            // batch transfer moves the next token of its list in the first chunk of each token block
+            let is_batch_transfer = Boolean::from(Expression::equals(
+                cs.namespace(|| "is_batch_transfer"),
+                &chunk_data.tx_type.get_number(),
+                Expression::u64::<CS>(u64::from(BatchTransferOp::OP_CODE)),
+            )?);
+            let is_next_token_chunk = Boolean::from(Expression::equals(
+                cs.namespace(|| "is_next_token_chunk"),
+                batch_transfer_token_chunk::<E, CS>(&chunk_data.chunk_number, &op_data.token_index),
+                Expression::u64::<CS>(0),
+            )?);
+            let is_next_batch_token = Boolean::and(
+                cs.namespace(|| "is_next_batch_token"),
+                &is_batch_transfer,
+                &is_next_token_chunk,
+            )?;
+            is_op_data_correct_flags.push(multi_or(
+                cs.namespace(|| "is token data correct"),
+                &[is_token_data_equal_to_previous, is_next_batch_token],
+            )?);
             is_op_data_correct_flags.push(CircuitElement::equals(
-                cs.namespace(|| "is token_id equal to previous"),
-                &op_data.token_id,
-                &prev.op_data.token_id,
+                cs.namespace(|| "is operator_pub_key_hash equal to previous"),
+                &op_data.operator_pub_key_hash,
+                &prev.op_data.operator_pub_key_hash,
             )?);
-            /*is_op_data_correct_flags.push(CircuitElement::equals(
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is operator_id equal to previous"),
+                &op_data.operator_id,
+                &prev.op_data.operator_id,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is is_operator equal to previous"),
+                &op_data.is_operator,
+                &prev.op_data.is_operator,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is approved equal to previous"),
+                &op_data.approved,
+                &prev.op_data.approved,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is valid_from equal to previous"),
+                &op_data.valid_from,
+                &prev.op_data.valid_from,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is valid_until equal to previous"),
+                &op_data.valid_until,
+                &prev.op_data.valid_until,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is fee_payer equal to previous"),
+                &op_data.fee_payer,
+                &prev.op_data.fee_payer,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
                 cs.namespace(|| "is full_amount equal to previous"),
                 &op_data.full_amount,
                 &prev.op_data.full_amount,
-            )?);*/
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is tokens_count equal to previous"),
+                &op_data.tokens_count,
+                &prev.op_data.tokens_count,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is tokens_root equal to previous"),
+                &op_data.tokens_root,
+                &prev.op_data.tokens_root,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_collection_id equal to previous"),
+                &op_data.second_collection_id,
+                &prev.op_data.second_collection_id,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_token_id_hi equal to previous"),
+                &op_data.second_token_id_hi,
+                &prev.op_data.second_token_id_hi,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_token_id_lo equal to previous"),
+                &op_data.second_token_id_lo,
+                &prev.op_data.second_token_id_lo,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_content_hash_hi equal to previous"),
+                &op_data.second_content_hash_hi,
+                &prev.op_data.second_content_hash_hi,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_content_hash_lo equal to previous"),
+                &op_data.second_content_hash_lo,
+                &prev.op_data.second_content_hash_lo,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_fee_packed equal to previous"),
+                &op_data.second_fee_packed,
+                &prev.op_data.second_fee_packed,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_tokens_count equal to previous"),
+                &op_data.second_tokens_count,
+                &prev.op_data.second_tokens_count,
+            )?);
+            is_op_data_correct_flags.push(CircuitElement::equals(
+                cs.namespace(|| "is second_tokens_root equal to previous"),
+                &op_data.second_tokens_root,
+                &prev.op_data.second_tokens_root,
+            )?);
 
             let is_op_data_equal_to_previous = multi_and(
                 cs.namespace(|| "is_op_data_equal_to_previous"),
@@ -625,6 +808,35 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             )?;
         }
         prev.op_data = op_data.clone();
+        prev.lhs_account_id = CircuitElement::conditionally_select(
+            cs.namespace(|| "lhs account of the op"),
+            &lhs.account_id,
+            &prev.lhs_account_id,
+            &chunk_data.is_chunk_first,
+        )?;
+        prev.rhs_account_id = CircuitElement::conditionally_select(
+            cs.namespace(|| "rhs account of the op"),
+            &rhs.account_id,
+            &prev.rhs_account_id,
+            &chunk_data.is_chunk_first,
+        )?;
+        let op_sig_msg = [
+            &op_data.first_sig_msg,
+            &op_data.second_sig_msg,
+            &op_data.third_sig_msg,
+        ];
+        for (i, (msg, prev_msg)) in op_sig_msg
+            .iter()
+            .zip(prev.tx_sig_msg.iter_mut())
+            .enumerate()
+        {
+            *prev_msg = CircuitElement::conditionally_select(
+                cs.namespace(|| format!("signed message of the op, part {}", i)),
+                msg,
+                prev_msg,
+                &chunk_data.is_chunk_first,
+            )?;
+        }
 
         let signer_key = unpack_point_if_possible(
             cs.namespace(|| "unpack pubkey"),
@@ -642,21 +854,41 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             generator,
         )?;
 
-        /*let diff_a_b =
-            Expression::from(&op_data.a.get_number()) - Expression::from(&op_data.b.get_number());
+        // a sponsor signs the hash of the message signed in the first chunk of the op along with
+        // its account id, see `Sponsorship`, messages of the ops that can be sponsored take all
+        // the `MAX_CIRCUIT_MSG_HASH_BITS` bits
+        let mut signed_tx_bits = vec![];
+        for msg in &prev.tx_sig_msg {
+            signed_tx_bits.extend(msg.get_bits_le());
+        }
+        let mut serialized_sponsorship_bits = vec![Boolean::constant(true); 8];
+        serialized_sponsorship_bits.extend(cur.account_id.get_bits_be());
+        serialized_sponsorship_bits.extend(sha256::sha256(
+            cs.namespace(|| "hash of the signed tx"),
+            &signed_tx_bits,
+        )?);
+        let is_sponsorship_serialized_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_sponsorship_serialized_correct"),
+            serialized_sponsorship_bits,
+            &op_data,
+        )?;
+
+        // fee is paid in ETH from the balance of the current branch account
+        let diff_balance_fee = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
 
-        let diff_a_b_bits = diff_a_b.into_bits_le_fixed(
+        let diff_balance_fee_bits = diff_balance_fee.into_bits_le_fixed(
             cs.namespace(|| "balance-fee bits"),
             params::BALANCE_BIT_WIDTH,
         )?;
 
-        let diff_a_b_bits_repacked = Expression::from_le_bits::<CS>(&diff_a_b_bits);
+        let diff_balance_fee_bits_repacked = Expression::from_le_bits::<CS>(&diff_balance_fee_bits);
 
-        let is_a_geq_b = Boolean::from(Expression::equals(
-            cs.namespace(|| "is_a_geq_b: diff equal to repacked"),
-            diff_a_b,
-            diff_a_b_bits_repacked,
-        )?);*/
+        let is_balance_geq_fee = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_balance_geq_fee: diff equal to repacked"),
+            diff_balance_fee,
+            diff_balance_fee_bits_repacked,
+        )?);
 
         let mut op_flags = vec![];
         op_flags.push(self.deposit(
@@ -673,12 +905,14 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &lhs,
             &rhs,
             &chunk_data,
-            //&is_a_geq_b,
+            &is_balance_geq_fee,
             &is_account_empty,
             &op_data,
             &signer_key,
             &ext_pubdata_chunk,
             &signature_data.is_verified,
+            &is_sponsorship_serialized_correct,
+            prev,
         )?);
         op_flags.push(self.transfer_to_new(
             cs.namespace(|| "transfer_to_new"),
@@ -686,34 +920,104 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &lhs,
             &rhs,
             &chunk_data,
-            //&is_a_geq_b,
+            &is_balance_geq_fee,
+            &is_account_empty,
+            &op_data,
+            &signer_key,
+            &ext_pubdata_chunk,
+            &signature_data.is_verified,
+            &is_sponsorship_serialized_correct,
+            prev,
+        )?);
+        op_flags.push(self.batch_transfer(
+            cs.namespace(|| "batch_transfer"),
+            &mut cur,
+            &lhs,
+            &chunk_data,
+            &is_balance_geq_fee,
             &is_account_empty,
             &op_data,
             &signer_key,
             &ext_pubdata_chunk,
             &signature_data.is_verified,
+            prev,
+        )?);
+        op_flags.push(self.swap(
+            cs.namespace(|| "swap"),
+            &mut cur,
+            &lhs,
+            &chunk_data,
+            &is_balance_geq_fee,
+            &op_data,
+            &signer_key,
+            &ext_pubdata_chunk,
+            &signature_data.is_verified,
+            prev,
+        )?);
+        op_flags.push(self.mint(
+            cs.namespace(|| "mint"),
+            &mut cur,
+            &chunk_data,
+            &is_balance_geq_fee,
+            &op_data,
+            &signer_key,
+            &ext_pubdata_chunk,
+            &signature_data.is_verified,
+        )?);
+        op_flags.push(self.burn(
+            cs.namespace(|| "burn"),
+            &mut cur,
+            &chunk_data,
+            &is_balance_geq_fee,
+            &op_data,
+            &signer_key,
+            &ext_pubdata_chunk,
+            &signature_data.is_verified,
+        )?);
+        op_flags.push(self.approve(
+            cs.namespace(|| "approve"),
+            &mut cur,
+            &chunk_data,
+            &is_balance_geq_fee,
+            &op_data,
+            &signer_key,
+            &ext_pubdata_chunk,
+            &signature_data.is_verified,
+            prev,
+        )?);
+        op_flags.push(self.approve_for_all(
+            cs.namespace(|| "approve_for_all"),
+            &mut cur,
+            &chunk_data,
+            &is_balance_geq_fee,
+            &op_data,
+            &signer_key,
+            &ext_pubdata_chunk,
+            &signature_data.is_verified,
+            prev,
         )?);
         op_flags.push(self.withdraw(
             cs.namespace(|| "withdraw"),
             &mut cur,
             &chunk_data,
-            //&is_a_geq_b,
+            &is_balance_geq_fee,
             &op_data,
             &signer_key,
             &ext_pubdata_chunk,
             &signature_data.is_verified,
+            &is_sponsorship_serialized_correct,
+            prev,
+        )?);
+        op_flags.push(self.close_account(
+            cs.namespace(|| "close_account"),
+            &mut cur,
+            &chunk_data,
+            &ext_pubdata_chunk,
+            &op_data,
+            &signer_key,
+            &subtree_root,
+            &signature_data.is_verified,
         )?);
-        // Close disable.
-        //  op_flags.push(self.close_account(
-        //      cs.namespace(|| "close_account"),
-        //      &mut cur,
-        //      &chunk_data,
-        //      &ext_pubdata_chunk,
-        //      &op_data,
-        //      &signer_key,
-        //      &subtree_root,
-        //      &signature_data.is_verified,
-        //  )?);
         // TODO ADE full exit is currently disabled
         /*op_flags.push(self.full_exit(
             cs.namespace(|| "full_exit"),
@@ -722,6 +1026,13 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &op_data,
             &ext_pubdata_chunk,
         )?);*/
+        op_flags.push(self.forced_withdraw(
+            cs.namespace(|| "forced_withdraw"),
+            &mut cur,
+            &chunk_data,
+            &op_data,
+            &ext_pubdata_chunk,
+        )?);
         op_flags.push(self.change_pubkey_offchain(
             cs.namespace(|| "change_pubkey_offchain"),
             &mut cur,
@@ -729,6 +1040,14 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &op_data,
             &ext_pubdata_chunk,
         )?);
+        op_flags.push(self.deposit_eth(
+            cs.namespace(|| "deposit_eth"),
+            &mut cur,
+            &chunk_data,
+            &is_account_empty,
+            &op_data,
+            &ext_pubdata_chunk,
+        )?);
         op_flags.push(self.noop(cs.namespace(|| "noop"), &chunk_data, &ext_pubdata_chunk)?);
 
         let op_valid = multi_or(cs.namespace(|| "op_valid"), &op_flags)?;
@@ -738,28 +1057,53 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &op_valid,
             &Boolean::constant(true),
         )?;
-        /*for (i, fee) in fees.iter_mut().enumerate().take(params::total_tokens()) {
-            let sum = Expression::from(&*fee) + Expression::from(&op_data.fee.get_number());
 
-            let is_token_correct = Boolean::from(Expression::equals(
-                cs.namespace(|| format!("is token equal to number {}", i)),
-                &lhs.token.get_number(),
-                Expression::constant::<CS>(E::Fr::from_str(&i.to_string()).unwrap()),
-            )?);
+        let mut is_fee_op_flags = vec![];
+        for op_code in &[
+            TransferOp::OP_CODE,
+            TransferToNewOp::OP_CODE,
+            WithdrawOp::OP_CODE,
+            BatchTransferOp::OP_CODE,
+            SwapOp::OP_CODE,
+            MintOp::OP_CODE,
+            BurnOp::OP_CODE,
+            ApproveOp::OP_CODE,
+            ApproveForAllOp::OP_CODE,
+        ] {
+            is_fee_op_flags.push(Boolean::from(Expression::equals(
+                cs.namespace(|| format!("is tx type equal to {}", op_code)),
+                &chunk_data.tx_type.get_number(),
+                Expression::u64::<CS>(u64::from(*op_code)),
+            )?));
+        }
+        let is_fee_op = multi_or(cs.namespace(|| "is_fee_op"), &is_fee_op_flags)?;
 
-            let should_update = Boolean::and(
-                cs.namespace(|| format!("should update fee number {}", i)),
-                &is_token_correct,
-                &chunk_data.is_chunk_last.clone(),
-            )?;
+        let should_collect_fee = Boolean::and(
+            cs.namespace(|| "should collect fee"),
+            &is_fee_op,
+            &chunk_data.is_chunk_last,
+        )?;
 
-            *fee = Expression::conditionally_select(
-                cs.namespace(|| format!("update fee number {}", i)),
-                sum,
-                &fee.clone(),
-                &should_update,
-            )?;
-        }*/
+        // swap collects the fees of both orders
+        let is_swap = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_swap"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(SwapOp::OP_CODE)),
+        )?);
+        let op_fee = Expression::conditionally_select(
+            cs.namespace(|| "fee of the op"),
+            Expression::from(&op_data.fee.get_number())
+                + Expression::from(&op_data.second_fee.get_number()),
+            &op_data.fee.get_number(),
+            &is_swap,
+        )?;
+        let sum = Expression::from(&*fees) + Expression::from(&op_fee);
+        *fees = Expression::conditionally_select(
+            cs.namespace(|| "update fees"),
+            sum,
+            &fees.clone(),
+            &should_collect_fee,
+        )?;
 
         Ok(())
     }
@@ -769,11 +1113,13 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         mut cs: CS,
         cur: &mut AllocatedOperationBranch<E>,
         chunk_data: &AllocatedChunkData<E>,
-        //is_a_geq_b: &Boolean,
+        is_balance_geq_fee: &Boolean,
         op_data: &AllocatedOperationData<E>,
         signer_key: &AllocatedSignerPubkey<E>,
         ext_pubdata_chunk: &AllocatedNum<E>,
         is_sig_verified: &Boolean,
+        is_sponsorship_serialized_correct: &Boolean,
+        prev: &PreviousData<E>,
     ) -> Result<Boolean, SynthesisError> {
         let mut base_valid_flags = vec![];
         //construct pubdata
@@ -781,11 +1127,13 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
 
         pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
         pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
-        pubdata_bits.extend(op_data.token_id.get_bits_be()); //TOKEN_BIT_WIDTH=16
-                                                             //pubdata_bits.extend(op_data.full_amount.get_bits_be()); //AMOUNT_PACKED=24
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //COLLECTIONID_BIT_WIDTH=16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //TOKENID_BIT_WIDTH=256
+                                                         //pubdata_bits.extend(op_data.full_amount.get_bits_be()); //AMOUNT_PACKED=24
         pubdata_bits.extend(op_data.fee_packed.get_bits_be()); //FEE_PACKED=8
         pubdata_bits.extend(op_data.eth_address.get_bits_be()); //ETH_ADDRESS=160
-                                                                //        assert_eq!(pubdata_bits.len(), 30 * 8);
+        pubdata_bits.extend(op_data.fee_payer.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+                                                              //        assert_eq!(pubdata_bits.len(), 30 * 8);
         pubdata_bits.resize(
             WithdrawOp::CHUNKS * params::CHUNK_BIT_WIDTH,
             Boolean::constant(false),
@@ -799,10 +1147,14 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         serialized_tx_bits.extend(cur.account_id.get_bits_be());
         serialized_tx_bits.extend(cur.account.address.get_bits_be());
         serialized_tx_bits.extend(op_data.eth_address.get_bits_be());
-        serialized_tx_bits.extend(op_data.token_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.token_id_bits_be());
         //serialized_tx_bits.extend(op_data.full_amount.get_bits_be());
         serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
         serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        // the window is only signed, the block timestamp is not an input, see `Withdraw::valid_from`
+        serialized_tx_bits.extend(op_data.valid_from.get_bits_be());
+        serialized_tx_bits.extend(op_data.valid_until.get_bits_be());
         assert_eq!(serialized_tx_bits.len(), params::SIGNED_WITHDRAW_BIT_WIDTH);
 
         let pubdata_chunk = select_pubdata_chunk(
@@ -865,22 +1217,22 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
 
         lhs_valid_flags.push(is_base_valid.clone());
 
-        // check operation arguments
-        /*let is_a_correct =
-            CircuitElement::equals(cs.namespace(|| "is_a_correct"), &op_data.a, &cur.balance)?;
-
-        lhs_valid_flags.push(is_a_correct);*/
-
-        /*let sum_amount_fee = Expression::from(&op_data.full_amount.get_number())
-            + Expression::from(&op_data.fee.get_number());
-
-        let is_b_correct = Boolean::from(Expression::equals(
-            cs.namespace(|| "is_b_correct"),
-            &op_data.b.get_number(),
-            sum_amount_fee.clone(),
+        // the token leaves the account
+        let op_token = TokenContent::from_op_data(op_data);
+        let is_token_correct =
+            TokenContent::equals(cs.namespace(|| "is_token_correct"), &cur.token, &op_token)?;
+        lhs_valid_flags.push(is_token_correct);
+
+        // fee is charged here unless the withdraw is sponsored
+        let is_fee_payer = CircuitElement::equals(
+            cs.namespace(|| "is_fee_payer"),
+            &op_data.fee_payer,
+            &cur.account_id,
+        )?;
+        lhs_valid_flags.push(multi_or(
+            cs.namespace(|| "is fee covered"),
+            &[is_balance_geq_fee.clone(), is_fee_payer.not()],
         )?);
-        lhs_valid_flags.push(is_b_correct);
-        lhs_valid_flags.push(is_a_geq_b.clone());*/
 
         lhs_valid_flags.push(no_nonce_overflow(
             cs.namespace(|| "no nonce overflow"),
@@ -893,24 +1245,60 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         let mut ohs_valid_flags = vec![];
         ohs_valid_flags.push(is_base_valid);
         ohs_valid_flags.push(is_first_chunk.not());
+        let (is_sponsor_chunk_correct, is_sponsor_charged) = is_sponsor_account_correct(
+            cs.namespace(|| "is_sponsor_account_correct"),
+            &chunk_data.chunk_number,
+            WithdrawOp::SPONSOR_CHUNK,
+            &cur,
+            &op_data,
+            &signer_key,
+            is_sig_verified,
+            is_sponsorship_serialized_correct,
+            is_balance_geq_fee,
+            prev,
+        )?;
+        ohs_valid_flags.push(is_sponsor_chunk_correct);
         let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
 
+        // the sponsor pays the fee in its chunk
+        let should_charge_sponsor = Boolean::and(
+            cs.namespace(|| "should_charge_sponsor"),
+            &is_ohs_valid,
+            &is_sponsor_charged,
+        )?;
+        let updated_sponsor_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "charged sponsor balance"),
+            updated_sponsor_balance,
+            &cur.account.balance,
+            &should_charge_sponsor,
+        )?;
+
         let tx_valid = multi_or(
             cs.namespace(|| "tx_valid"),
             &[lhs_valid.clone(), is_ohs_valid],
         )?;
 
-        //let updated_balance = Expression::from(&cur.balance.get_number()) - sum_amount_fee;
+        let should_charge_fee = Boolean::and(
+            cs.namespace(|| "should_charge_fee"),
+            &lhs_valid,
+            &is_fee_payer,
+        )?;
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
 
         //mutate current branch if it is first chunk of valid withdraw transaction
-        /*cur.balance = CircuitElement::conditionally_select_with_number_strict(
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
             cs.namespace(|| "mutated balance"),
             updated_balance,
-            &cur.balance,
-            &lhs_valid,
+            &cur.account.balance,
+            &should_charge_fee,
         )?;
-        cur.balance
-            .enforce_length(cs.namespace(|| "mutated balance is still correct length"))?;*/
+
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "withdrawn token"), &lhs_valid)?;
 
         let updated_nonce =
             Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
@@ -1060,6 +1448,10 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         unimplemented!()
     }
 
+    /// Number of deposited tokens is `full_amount`, it is published as the success flag.
+    /// A successful deposit puts the token into an empty slot of the account, the circuit can't
+    /// prove that a failed one was sent to an account without free slots or that the token is
+    /// held by another account.
     fn deposit<CS: ConstraintSystem<E>>(
         &self,
         mut cs: CS,
@@ -1069,13 +1461,31 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         op_data: &AllocatedOperationData<E>,
         ext_pubdata_chunk: &AllocatedNum<E>,
     ) -> Result<Boolean, SynthesisError> {
+        let is_success = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_success"),
+            &op_data.full_amount.get_number(),
+            Expression::u64::<CS>(1),
+        )?);
+        let is_failure = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_failure"),
+            &op_data.full_amount.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
         //construct pubdata
         let mut pubdata_bits = vec![];
         pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
         pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
-        pubdata_bits.extend(op_data.token_id.get_bits_be()); //TOKENID_BIT_WIDTH=16
-                                                             //pubdata_bits.extend(op_data.full_amount.get_bits_be()); //AMOUNT_PACKED=24
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //COLLECTIONID_BIT_WIDTH=16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //TOKENID_BIT_WIDTH=256
+                                                         //pubdata_bits.extend(op_data.full_amount.get_bits_be()); //AMOUNT_PACKED=24
         pubdata_bits.extend(op_data.eth_address.get_bits_be()); //ETH_KEY_BIT_WIDTH=160
+        pubdata_bits.extend(op_data.content_hash_bits_be()); //CONTENT_HASH_BIT_WIDTH=256
+        pubdata_bits.resize(
+            pubdata_bits.len() + params::SUCCESS_FLAG_BIT_WIDTH - 1,
+            Boolean::constant(false),
+        );
+        pubdata_bits.push(is_success.clone()); //SUCCESS_FLAG_BIT_WIDTH=8
         pubdata_bits.resize(
             DepositOp::CHUNKS * params::CHUNK_BIT_WIDTH, //TODO: move to constant
             Boolean::constant(false),
@@ -1111,6 +1521,10 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             Expression::u64::<CS>(u64::from(DepositOp::OP_CODE)),
         )?);
         is_valid_flags.push(is_deposit);
+        is_valid_flags.push(multi_or(
+            cs.namespace(|| "is success flag boolean"),
+            &[is_success.clone(), is_failure],
+        )?);
 
         // verify if address is to previous one (if existed)
         let is_pub_equal_to_previous = CircuitElement::equals(
@@ -1128,32 +1542,27 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         .not();
         is_valid_flags.push(is_pubkey_correct);
 
-        //verify correct amounts
-        /*let is_a_correct = CircuitElement::equals(
-            cs.namespace(|| "a == amount"),
-            &op_data.full_amount,
-            &op_data.a,
+        // a successful deposit takes an empty slot of the account
+        let is_slot_empty = cur.token.is_empty(cs.namespace(|| "is_slot_empty"))?;
+        let is_slot_correct = multi_or(
+            cs.namespace(|| "slot is empty or not first chunk or failure"),
+            &[is_slot_empty, is_first_chunk.not(), is_success.not()],
         )?;
-
-        is_valid_flags.push(is_a_correct);*/
+        is_valid_flags.push(is_slot_correct);
 
         let tx_valid = multi_and(cs.namespace(|| "is_tx_valid"), &is_valid_flags)?;
 
-        let is_valid_first = Boolean::and(
-            cs.namespace(|| "is valid and first"),
-            &tx_valid,
-            &is_first_chunk,
+        let is_valid_first = multi_and(
+            cs.namespace(|| "is valid and first and success"),
+            &[tx_valid.clone(), is_first_chunk, is_success],
         )?;
 
-        /*let updated_balance = Expression::from(&cur.balance.get_number())
-            + Expression::from(&op_data.full_amount.get_number());
-
-        cur.balance = CircuitElement::conditionally_select_with_number_strict(
-            cs.namespace(|| "mutated balance"),
-            updated_balance,
-            &cur.balance,
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "deposited token"),
+            &TokenContent::from_op_data(op_data),
+            &cur.token,
             &is_valid_first,
-        )?;*/
+        )?;
 
         // update pub_key
         cur.account.address = CircuitElement::conditionally_select(
@@ -1165,11 +1574,13 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         Ok(tx_valid)
     }
 
-    fn change_pubkey_offchain<CS: ConstraintSystem<E>>(
+    /// ETH only pays the fees of the account operations, there is no way to withdraw it back to L1.
+    fn deposit_eth<CS: ConstraintSystem<E>>(
         &self,
         mut cs: CS,
         cur: &mut AllocatedOperationBranch<E>,
         chunk_data: &AllocatedChunkData<E>,
+        is_account_empty: &Boolean,
         op_data: &AllocatedOperationData<E>,
         ext_pubdata_chunk: &AllocatedNum<E>,
     ) -> Result<Boolean, SynthesisError> {
@@ -1177,12 +1588,10 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         let mut pubdata_bits = vec![];
         pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
         pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
-        pubdata_bits.extend(op_data.new_pubkey_hash.get_bits_be()); //ETH_KEY_BIT_WIDTH=160
+        pubdata_bits.extend(op_data.full_amount.get_bits_be()); //BALANCE_BIT_WIDTH=128
         pubdata_bits.extend(op_data.eth_address.get_bits_be()); //ETH_KEY_BIT_WIDTH=160
-                                                                // NOTE: nonce if verified implicitly here. Current account nonce goes to pubdata and to contract.
-        pubdata_bits.extend(op_data.pub_nonce.get_bits_be()); //TOKEN_BIT_WIDTH=16
         pubdata_bits.resize(
-            ChangePubKeyOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            DepositEthOp::CHUNKS * params::CHUNK_BIT_WIDTH,
             Boolean::constant(false),
         );
 
@@ -1199,7 +1608,7 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             cs.namespace(|| "select_pubdata_chunk"),
             &pubdata_bits,
             &chunk_data.chunk_number,
-            ChangePubKeyOp::CHUNKS,
+            DepositEthOp::CHUNKS,
         )?;
 
         let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
@@ -1210,36 +1619,132 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         is_valid_flags.push(is_pubdata_chunk_correct);
 
         // verify correct tx_code
-        let is_change_pubkey_offchain = Boolean::from(Expression::equals(
-            cs.namespace(|| "is_change_pubkey_offchain"),
+        let is_deposit_eth = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_deposit_eth"),
             &chunk_data.tx_type.get_number(),
-            Expression::u64::<CS>(u64::from(ChangePubKeyOp::OP_CODE)),
+            Expression::u64::<CS>(u64::from(DepositEthOp::OP_CODE)),
         )?);
-        is_valid_flags.push(is_change_pubkey_offchain);
+        is_valid_flags.push(is_deposit_eth);
 
         // verify if address is to previous one (if existed)
-        let is_address_correct = CircuitElement::equals(
-            cs.namespace(|| "is_address_correct"),
+        let is_pub_equal_to_previous = CircuitElement::equals(
+            cs.namespace(|| "is_address_equal_to_previous"),
             &op_data.eth_address,
             &cur.account.address,
         )?;
 
-        is_valid_flags.push(is_address_correct);
+        //keys are same or account is empty
+        let is_pubkey_correct = Boolean::and(
+            cs.namespace(|| "acc not empty and keys are not the same"),
+            &is_pub_equal_to_previous.not(),
+            &is_account_empty.not(),
+        )?
+        .not();
+        is_valid_flags.push(is_pubkey_correct);
 
         let tx_valid = multi_and(cs.namespace(|| "is_tx_valid"), &is_valid_flags)?;
 
-        let is_pub_nonce_valid = CircuitElement::equals(
-            cs.namespace(|| "is_pub_nonce_valid"),
-            &cur.account.nonce,
-            &op_data.pub_nonce,
-        )?;
-        let no_nonce_overflow = no_nonce_overflow(
-            cs.namespace(|| "no nonce overflow"),
-            &cur.account.nonce.get_number(),
+        let is_valid_first = Boolean::and(
+            cs.namespace(|| "is valid and first"),
+            &tx_valid,
+            &is_first_chunk,
         )?;
-        let is_valid_first = multi_and(
-            cs.namespace(|| "is_valid_first"),
-            &[
+
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            + Expression::from(&op_data.full_amount.get_number());
+
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated balance"),
+            updated_balance,
+            &cur.account.balance,
+            &is_valid_first,
+        )?;
+
+        // update pub_key
+        cur.account.address = CircuitElement::conditionally_select(
+            cs.namespace(|| "mutated_pubkey"),
+            &op_data.eth_address,
+            &cur.account.address,
+            &is_valid_first,
+        )?;
+        Ok(tx_valid)
+    }
+
+    fn change_pubkey_offchain<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        op_data: &AllocatedOperationData<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+    ) -> Result<Boolean, SynthesisError> {
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.new_pubkey_hash.get_bits_be()); //ETH_KEY_BIT_WIDTH=160
+        pubdata_bits.extend(op_data.eth_address.get_bits_be()); //ETH_KEY_BIT_WIDTH=160
+                                                                // NOTE: nonce if verified implicitly here. Current account nonce goes to pubdata and to contract.
+        pubdata_bits.extend(op_data.pub_nonce.get_bits_be()); //TOKEN_BIT_WIDTH=16
+        pubdata_bits.resize(
+            ChangePubKeyOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        //useful below
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let mut is_valid_flags = vec![];
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            ChangePubKeyOp::CHUNKS,
+        )?;
+
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        is_valid_flags.push(is_pubdata_chunk_correct);
+
+        // verify correct tx_code
+        let is_change_pubkey_offchain = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_change_pubkey_offchain"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(ChangePubKeyOp::OP_CODE)),
+        )?);
+        is_valid_flags.push(is_change_pubkey_offchain);
+
+        // verify if address is to previous one (if existed)
+        let is_address_correct = CircuitElement::equals(
+            cs.namespace(|| "is_address_correct"),
+            &op_data.eth_address,
+            &cur.account.address,
+        )?;
+
+        is_valid_flags.push(is_address_correct);
+
+        let tx_valid = multi_and(cs.namespace(|| "is_tx_valid"), &is_valid_flags)?;
+
+        let is_pub_nonce_valid = CircuitElement::equals(
+            cs.namespace(|| "is_pub_nonce_valid"),
+            &cur.account.nonce,
+            &op_data.pub_nonce,
+        )?;
+        let no_nonce_overflow = no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?;
+        let is_valid_first = multi_and(
+            cs.namespace(|| "is_valid_first"),
+            &[
                 tx_valid.clone(),
                 is_first_chunk,
                 is_pub_nonce_valid,
@@ -1266,104 +1771,114 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         Ok(tx_valid)
     }
 
-    // Close disable
-    // fn close_account<CS: ConstraintSystem<E>>(
-    //     &self,
-    //     mut cs: CS,
-    //     cur: &mut AllocatedOperationBranch<E>,
-    //     chunk_data: &AllocatedChunkData<E>,
-    //     ext_pubdata_chunk: &AllocatedNum<E>,
-    //     op_data: &AllocatedOperationData<E>,
-    //     signer_key: &AllocatedSignerPubkey<E>,
-    //     subtree_root: &CircuitElement<E>,
-    //     is_sig_verified: &Boolean,
-    // ) -> Result<Boolean, SynthesisError> {
-    //     let mut is_valid_flags = vec![];
-    //     //construct pubdata
-    //     let mut pubdata_bits = vec![];
-    //     pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
-    //     pubdata_bits.extend(cur.account_address.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
-    //     pubdata_bits.resize(
-    //         params::CHUNK_BIT_WIDTH,
-    //         Boolean::constant(false),
-    //     );
-
-    //     // construct signature message preimage (serialized_tx)
-    //     let mut serialized_tx_bits = vec![];
-    //     serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
-    //     serialized_tx_bits.extend(cur.account.pub_key_hash.get_bits_be());
-    //     serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
-
-    //     let pubdata_chunk = select_pubdata_chunk(
-    //         cs.namespace(|| "select_pubdata_chunk"),
-    //         &pubdata_bits,
-    //         &chunk_data.chunk_number,
-    //         1,
-    //     )?;
-
-    //     let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
-    //         cs.namespace(|| "is_pubdata_equal"),
-    //         &pubdata_chunk,
-    //         ext_pubdata_chunk,
-    //     )?);
-    //     is_valid_flags.push(is_pubdata_chunk_correct);
-
-    //     let is_close_account = Boolean::from(Expression::equals(
-    //         cs.namespace(|| "is_deposit"),
-    //         &chunk_data.tx_type.get_number(),
-    //         Expression::u64::<CS>(4), //close_account tx_type
-    //     )?);
-    //     is_valid_flags.push(is_close_account.clone());
-
-    //     let tmp = CircuitAccount::<E>::empty_balances_root_hash();
-    //     let mut r_repr = E::Fr::zero().into_repr();
-    //     r_repr.read_be(&tmp[..]).unwrap();
-    //     let empty_root = E::Fr::from_repr(r_repr).unwrap();
-
-    //     let are_balances_empty = Boolean::from(Expression::equals(
-    //         cs.namespace(|| "are_balances_empty"),
-    //         &subtree_root.get_number(),
-    //         Expression::constant::<CS>(empty_root), //This is precalculated root_hash of subtree with empty balances
-    //     )?);
-    //     is_valid_flags.push(are_balances_empty);
-
-    //     let is_serialized_tx_correct = verify_signature_message_construction(
-    //         cs.namespace(|| "is_serialized_tx_correct"),
-    //         serialized_tx_bits,
-    //         &op_data,
-    //     )?;
-
-    //     is_valid_flags.push(is_serialized_tx_correct);
-    //     is_valid_flags.push(is_sig_verified.clone());
-    //     let is_signer_valid = CircuitElement::equals(
-    //         cs.namespace(|| "signer_key_correct"),
-    //         &signer_key.pubkey.get_hash(),
-    //         &cur.account.pub_key_hash, //earlier we ensured that this new_pubkey_hash is equal to current if existed
-    //     )?;
-
-    //     is_valid_flags.push(is_signer_valid);
-
-    //     let tx_valid = multi_and(cs.namespace(|| "is_tx_valid"), &is_valid_flags)?;
-
-    //     // below we conditionally update state if it is valid operation
-
-    //     // update pub_key
-    //     cur.account.pub_key_hash = CircuitElement::conditionally_select_with_number_strict(
-    //         cs.namespace(|| "mutated_pubkey"),
-    //         Expression::constant::<CS>(E::Fr::zero()),
-    //         &cur.account.pub_key_hash,
-    //         &tx_valid,
-    //     )?;
-    //     // update nonce
-    //     cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
-    //         cs.namespace(|| "update cur nonce"),
-    //         Expression::constant::<CS>(E::Fr::zero()),
-    //         &cur.account.nonce,
-    //         &tx_valid,
-    //     )?;
-
-    //     Ok(tx_valid)
-    // }
+    fn close_account<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        subtree_root: &CircuitElement<E>,
+        is_sig_verified: &Boolean,
+    ) -> Result<Boolean, SynthesisError> {
+        let mut is_valid_flags = vec![];
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.resize(
+            CloseOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message preimage (serialized_tx)
+        let mut serialized_tx_bits = vec![];
+        serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_tx_bits.extend(cur.account.address.get_bits_be());
+        serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            CloseOp::CHUNKS,
+        )?;
+
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        is_valid_flags.push(is_pubdata_chunk_correct);
+
+        let is_close_account = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_close_account"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(CloseOp::OP_CODE)),
+        )?);
+        is_valid_flags.push(is_close_account);
+
+        // account can't be closed while it holds tokens or ETH
+        let are_tokens_empty = Boolean::from(Expression::equals(
+            cs.namespace(|| "are_tokens_empty"),
+            &subtree_root.get_number(),
+            Expression::constant::<CS>(empty_token_subtree_root::<E>(self.rescue_params)),
+        )?);
+        is_valid_flags.push(are_tokens_empty);
+
+        let is_balance_empty = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_balance_empty"),
+            &cur.account.balance.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+        is_valid_flags.push(is_balance_empty);
+
+        let is_serialized_tx_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_tx_correct"),
+            serialized_tx_bits,
+            &op_data,
+        )?;
+
+        is_valid_flags.push(is_serialized_tx_correct);
+        is_valid_flags.push(is_sig_verified.clone());
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &cur.account.pub_key_hash,
+        )?;
+
+        is_valid_flags.push(is_signer_valid);
+
+        let tx_valid = multi_and(cs.namespace(|| "is_tx_valid"), &is_valid_flags)?;
+
+        // below we conditionally update state if it is valid operation,
+        // closed account leaf is empty, so that its id can be given to a new account
+
+        // update pub_key
+        cur.account.pub_key_hash = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated_pubkey"),
+            Expression::constant::<CS>(E::Fr::zero()),
+            &cur.account.pub_key_hash,
+            &tx_valid,
+        )?;
+        // update address
+        cur.account.address = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated_address"),
+            Expression::constant::<CS>(E::Fr::zero()),
+            &cur.account.address,
+            &tx_valid,
+        )?;
+        // update nonce
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            Expression::constant::<CS>(E::Fr::zero()),
+            &cur.account.nonce,
+            &tx_valid,
+        )?;
+
+        Ok(tx_valid)
+    }
 
     fn noop<CS: ConstraintSystem<E>>(
         &self,
@@ -1409,21 +1924,25 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         lhs: &AllocatedOperationBranch<E>,
         rhs: &AllocatedOperationBranch<E>,
         chunk_data: &AllocatedChunkData<E>,
-        //is_a_geq_b: &Boolean,
+        is_balance_geq_fee: &Boolean,
         is_account_empty: &Boolean,
         op_data: &AllocatedOperationData<E>,
         signer_key: &AllocatedSignerPubkey<E>,
         ext_pubdata_chunk: &AllocatedNum<E>,
         is_sig_verified: &Boolean,
+        is_sponsorship_serialized_correct: &Boolean,
+        prev: &PreviousData<E>,
     ) -> Result<Boolean, SynthesisError> {
         let mut pubdata_bits = vec![];
         pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //8
         pubdata_bits.extend(lhs.account_id.get_bits_be()); //24
-        pubdata_bits.extend(op_data.token_id.get_bits_be()); //16
-                                                             //pubdata_bits.extend(op_data.amount_packed.get_bits_be()); //24
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //256
+                                                         //pubdata_bits.extend(op_data.amount_packed.get_bits_be()); //24
         pubdata_bits.extend(op_data.eth_address.get_bits_be()); //160
         pubdata_bits.extend(rhs.account_id.get_bits_be()); //24
         pubdata_bits.extend(op_data.fee_packed.get_bits_be()); //8
+        pubdata_bits.extend(op_data.fee_payer.get_bits_be()); //24
         pubdata_bits.resize(
             TransferToNewOp::CHUNKS * params::CHUNK_BIT_WIDTH,
             Boolean::constant(false),
@@ -1440,10 +1959,14 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         serialized_tx_bits.extend(lhs.account_id.get_bits_be());
         serialized_tx_bits.extend(lhs.account.address.get_bits_be());
         serialized_tx_bits.extend(op_data.eth_address.get_bits_be());
-        serialized_tx_bits.extend(op_data.token_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.token_id_bits_be());
         //serialized_tx_bits.extend(op_data.amount_packed.get_bits_be());
         serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
         serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        // the window is only signed, the block timestamp is not an input, see `Transfer::valid_from`
+        serialized_tx_bits.extend(op_data.valid_from.get_bits_be());
+        serialized_tx_bits.extend(op_data.valid_until.get_bits_be());
         assert_eq!(serialized_tx_bits.len(), SIGNED_TRANSFER_BIT_WIDTH);
 
         let pubdata_chunk = select_pubdata_chunk(
@@ -1475,21 +1998,23 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         )?);
         lhs_valid_flags.push(is_first_chunk.clone());
 
-        /*let is_a_correct =
-            CircuitElement::equals(cs.namespace(|| "is_a_correct"), &op_data.a, &cur.balance)?;
-        lhs_valid_flags.push(is_a_correct);*/
-
-        /*let sum_amount_fee = Expression::from(&op_data.amount_unpacked.get_number())
-            + Expression::from(&op_data.fee.get_number());
-        let is_b_correct = Boolean::from(Expression::equals(
-            cs.namespace(|| "is_b_correct"),
-            &op_data.b.get_number(),
-            sum_amount_fee.clone(),
+        // the token leaves the sender
+        let op_token = TokenContent::from_op_data(op_data);
+        let is_token_correct =
+            TokenContent::equals(cs.namespace(|| "is_token_correct"), &cur.token, &op_token)?;
+        lhs_valid_flags.push(is_token_correct);
+
+        // fee is charged from the sender unless the transfer is sponsored
+        let is_fee_payer = CircuitElement::equals(
+            cs.namespace(|| "is_fee_payer"),
+            &op_data.fee_payer,
+            &cur.account_id,
+        )?;
+        lhs_valid_flags.push(multi_or(
+            cs.namespace(|| "is fee covered"),
+            &[is_balance_geq_fee.clone(), is_fee_payer.not()],
         )?);
 
-        lhs_valid_flags.push(is_b_correct);
-        lhs_valid_flags.push(is_a_geq_b.clone());*/
-
         lhs_valid_flags.push(no_nonce_overflow(
             cs.namespace(|| "no nonce overflow"),
             &cur.account.nonce.get_number(),
@@ -1517,10 +2042,12 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         )?;
         lhs_valid_flags.push(is_sig_correct);
 
-        let is_signer_valid = CircuitElement::equals(
+        let is_signer_valid = is_signer_owner_or_operator(
             cs.namespace(|| "signer_key_correect"),
             &signer_key.pubkey.get_hash(),
-            &lhs.account.pub_key_hash,
+            &lhs,
+            &op_data,
+            self.rescue_params,
         )?;
         debug!(
             "signer_key.pubkey.get_hash(): {:?}",
@@ -1544,7 +2071,13 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
 
         lhs_valid_flags.push(is_signer_valid);
         let lhs_valid = multi_and(cs.namespace(|| "lhs_valid"), &lhs_valid_flags)?;
-        //let updated_balance_value = Expression::from(&cur.balance.get_number()) - sum_amount_fee;
+        let should_charge_fee = Boolean::and(
+            cs.namespace(|| "should_charge_fee"),
+            &lhs_valid,
+            &is_fee_payer,
+        )?;
+        let updated_balance_value = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
 
         let updated_nonce =
             Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
@@ -1559,12 +2092,16 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         )?;
 
         //update balance
-        /*cur.balance = CircuitElement::conditionally_select_with_number_strict(
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
             cs.namespace(|| "updated cur balance"),
             updated_balance_value,
-            &cur.balance,
-            &lhs_valid,
-        )?;*/
+            &cur.account.balance,
+            &should_charge_fee,
+        )?;
+
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "sent token"), &lhs_valid)?;
 
         let mut rhs_valid_flags = vec![];
 
@@ -1577,16 +2114,15 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         rhs_valid_flags.push(is_second_chunk.clone());
         rhs_valid_flags.push(is_transfer.clone());
         rhs_valid_flags.push(is_account_empty.clone());
+        rhs_valid_flags.push(cur.token.is_empty(cs.namespace(|| "is rhs slot empty"))?);
         let rhs_valid = multi_and(cs.namespace(|| "rhs_valid"), &rhs_valid_flags)?;
 
-        /*cur.balance = CircuitElement::conditionally_select(
-            cs.namespace(|| "mutated balance"),
-            &op_data.amount_unpacked,
-            &cur.balance,
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "received token"),
+            &op_token,
+            &cur.token,
             &rhs_valid,
         )?;
-        cur.balance
-            .enforce_length(cs.namespace(|| "mutated balance is still correct length"))?; // TODO: this is actually redundant, cause they are both enforced to be of appropriate length*/
 
         cur.account.address = CircuitElement::conditionally_select(
             cs.namespace(|| "mutated_pubkey"),
@@ -1600,9 +2136,43 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         ohs_valid_flags.push(is_first_chunk.not());
         ohs_valid_flags.push(is_second_chunk.not());
         ohs_valid_flags.push(is_transfer);
+        ohs_valid_flags.push(is_operator_account_correct(
+            cs.namespace(|| "is_operator_account_correct"),
+            &chunk_data.chunk_number,
+            &cur,
+            &op_data,
+        )?);
+        let (is_sponsor_chunk_correct, is_sponsor_charged) = is_sponsor_account_correct(
+            cs.namespace(|| "is_sponsor_account_correct"),
+            &chunk_data.chunk_number,
+            TransferToNewOp::SPONSOR_CHUNK,
+            &cur,
+            &op_data,
+            &signer_key,
+            is_sig_verified,
+            is_sponsorship_serialized_correct,
+            is_balance_geq_fee,
+            prev,
+        )?;
+        ohs_valid_flags.push(is_sponsor_chunk_correct);
 
         let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
 
+        // the sponsor pays the fee in its chunk
+        let should_charge_sponsor = Boolean::and(
+            cs.namespace(|| "should_charge_sponsor"),
+            &is_ohs_valid,
+            &is_sponsor_charged,
+        )?;
+        let updated_sponsor_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "charged sponsor balance"),
+            updated_sponsor_balance,
+            &cur.account.balance,
+            &should_charge_sponsor,
+        )?;
+
         let is_op_valid = multi_or(
             cs.namespace(|| "is_op_valid"),
             &[is_ohs_valid, lhs_valid, rhs_valid],
@@ -1617,22 +2187,25 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         lhs: &AllocatedOperationBranch<E>,
         rhs: &AllocatedOperationBranch<E>,
         chunk_data: &AllocatedChunkData<E>,
-        //is_a_geq_b: &Boolean,
+        is_balance_geq_fee: &Boolean,
         is_account_empty: &Boolean,
         op_data: &AllocatedOperationData<E>,
         signer_key: &AllocatedSignerPubkey<E>,
         ext_pubdata_chunk: &AllocatedNum<E>,
         is_sig_verified: &Boolean,
+        is_sponsorship_serialized_correct: &Boolean,
+        prev: &PreviousData<E>,
     ) -> Result<Boolean, SynthesisError> {
         // construct pubdata
         let mut pubdata_bits = vec![];
         pubdata_bits.extend(chunk_data.tx_type.get_bits_be());
         pubdata_bits.extend(lhs.account_id.get_bits_be());
-        //pubdata_bits.extend(cur.token.get_bits_be());
+        pubdata_bits.extend(op_data.collection_id.get_bits_be());
+        pubdata_bits.extend(op_data.token_id_bits_be());
         pubdata_bits.extend(rhs.account_id.get_bits_be());
-        pubdata_bits.extend(op_data.token_id.get_bits_be());
         //pubdata_bits.extend(op_data.amount_packed.get_bits_be());
         pubdata_bits.extend(op_data.fee_packed.get_bits_be());
+        pubdata_bits.extend(op_data.fee_payer.get_bits_be());
 
         pubdata_bits.resize(
             TransferOp::CHUNKS * params::CHUNK_BIT_WIDTH,
@@ -1647,10 +2220,14 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         serialized_tx_bits.extend(lhs.account_id.get_bits_be());
         serialized_tx_bits.extend(lhs.account.address.get_bits_be());
         serialized_tx_bits.extend(rhs.account.address.get_bits_be());
-        serialized_tx_bits.extend(op_data.token_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.token_id_bits_be());
         //serialized_tx_bits.extend(op_data.amount_packed.get_bits_be());
         serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
         serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        // the window is only signed, the block timestamp is not an input, see `Transfer::valid_from`
+        serialized_tx_bits.extend(op_data.valid_from.get_bits_be());
+        serialized_tx_bits.extend(op_data.valid_until.get_bits_be());
         assert_eq!(serialized_tx_bits.len(), SIGNED_TRANSFER_BIT_WIDTH);
 
         let pubdata_chunk = select_pubdata_chunk(
@@ -1683,25 +2260,24 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
             &chunk_data.chunk_number,
             Expression::constant::<CS>(E::Fr::zero()),
         )?);
-        lhs_valid_flags.push(is_first_chunk);
-
-        // check operation arguments
-        //let is_a_correct =
-        //    CircuitElement::equals(cs.namespace(|| "is_a_correct"), &op_data.a, &cur.balance)?;
-
-        //lhs_valid_flags.push(is_a_correct);
-
-        /*let sum_amount_fee = Expression::from(&op_data.amount_unpacked.get_number())
-            + Expression::from(&op_data.fee.get_number());
-
-        let is_b_correct = Boolean::from(Expression::equals(
-            cs.namespace(|| "is_b_correct"),
-            &op_data.b.get_number(),
-            sum_amount_fee.clone(),
-        )?);*/
+        lhs_valid_flags.push(is_first_chunk.clone());
 
-        //lhs_valid_flags.push(is_b_correct);
-        //lhs_valid_flags.push(is_a_geq_b.clone());
+        // the token leaves the sender
+        let op_token = TokenContent::from_op_data(op_data);
+        let is_token_correct =
+            TokenContent::equals(cs.namespace(|| "is_token_correct"), &cur.token, &op_token)?;
+        lhs_valid_flags.push(is_token_correct);
+
+        // fee is charged from the sender unless the transfer is sponsored
+        let is_fee_payer = CircuitElement::equals(
+            cs.namespace(|| "is_fee_payer"),
+            &op_data.fee_payer,
+            &cur.account_id,
+        )?;
+        lhs_valid_flags.push(multi_or(
+            cs.namespace(|| "is fee covered"),
+            &[is_balance_geq_fee.clone(), is_fee_payer.not()],
+        )?);
         lhs_valid_flags.push(is_sig_verified.clone());
         lhs_valid_flags.push(no_nonce_overflow(
             cs.namespace(|| "no nonce overflow"),
@@ -1716,10 +2292,12 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         lhs_valid_flags.push(is_serialized_tx_correct);
 
         // TODO: add flag for is account address is correct(!)
-        let is_signer_valid = CircuitElement::equals(
+        let is_signer_valid = is_signer_owner_or_operator(
             cs.namespace(|| "signer_key_correct"),
             &signer_key.pubkey.get_hash(),
-            &lhs.account.pub_key_hash,
+            &lhs,
+            &op_data,
+            self.rescue_params,
         )?;
         lhs_valid_flags.push(is_signer_valid);
 
@@ -1727,7 +2305,13 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
 
         let lhs_valid = multi_and(cs.namespace(|| "lhs_valid"), &lhs_valid_flags)?;
 
-        //let updated_balance = Expression::from(&cur.balance.get_number()) - sum_amount_fee;
+        let should_charge_fee = Boolean::and(
+            cs.namespace(|| "should_charge_fee"),
+            &lhs_valid,
+            &is_fee_payer,
+        )?;
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
 
         let updated_nonce =
             Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
@@ -1742,63 +2326,1488 @@ impl<'a, E: RescueEngine + JubjubEngine> FranklinCircuit<'a, E> {
         )?;
 
         //update balance
-        /*cur.balance = CircuitElement::conditionally_select_with_number_strict(
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
             cs.namespace(|| "updated cur balance"),
             updated_balance,
-            &cur.balance,
-            &lhs_valid,
-        )?;*/
+            &cur.account.balance,
+            &should_charge_fee,
+        )?;
+
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "sent token"), &lhs_valid)?;
 
         // rhs
         let mut rhs_valid_flags = vec![];
-        rhs_valid_flags.push(is_transfer);
+        rhs_valid_flags.push(is_transfer.clone());
 
         let is_chunk_second = Boolean::from(Expression::equals(
             cs.namespace(|| "is_chunk_second"),
             &chunk_data.chunk_number,
             Expression::u64::<CS>(1),
         )?);
-        rhs_valid_flags.push(is_chunk_second);
+        rhs_valid_flags.push(is_chunk_second.clone());
         rhs_valid_flags.push(is_account_empty.not());
+        rhs_valid_flags.push(cur.token.is_empty(cs.namespace(|| "is rhs slot empty"))?);
 
-        rhs_valid_flags.push(is_pubdata_chunk_correct);
+        rhs_valid_flags.push(is_pubdata_chunk_correct.clone());
         let is_rhs_valid = multi_and(cs.namespace(|| "is_rhs_valid"), &rhs_valid_flags)?;
 
-        // calculate new rhs balance value
-        /*let updated_balance = Expression::from(&cur.balance.get_number())
-        + Expression::from(&op_data.amount_unpacked.get_number());*/
-
-        //update balance
-        /*cur.balance = CircuitElement::conditionally_select_with_number_strict(
-            cs.namespace(|| "updated_balance rhs"),
-            updated_balance,
-            &cur.balance,
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "received token"),
+            &op_token,
+            &cur.token,
             &is_rhs_valid,
-        )?;*/
+        )?;
 
-        Ok(Boolean::and(
-            cs.namespace(|| "lhs_valid nand rhs_valid"),
-            &lhs_valid.not(),
-            &is_rhs_valid.not(),
-        )?
-        .not())
+        // ohs
+        let mut ohs_valid_flags = vec![];
+        ohs_valid_flags.push(is_pubdata_chunk_correct);
+        ohs_valid_flags.push(is_first_chunk.not());
+        ohs_valid_flags.push(is_chunk_second.not());
+        ohs_valid_flags.push(is_transfer);
+        ohs_valid_flags.push(is_operator_account_correct(
+            cs.namespace(|| "is_operator_account_correct"),
+            &chunk_data.chunk_number,
+            &cur,
+            &op_data,
+        )?);
+        let (is_sponsor_chunk_correct, is_sponsor_charged) = is_sponsor_account_correct(
+            cs.namespace(|| "is_sponsor_account_correct"),
+            &chunk_data.chunk_number,
+            TransferOp::SPONSOR_CHUNK,
+            &cur,
+            &op_data,
+            &signer_key,
+            is_sig_verified,
+            is_sponsorship_serialized_correct,
+            is_balance_geq_fee,
+            prev,
+        )?;
+        ohs_valid_flags.push(is_sponsor_chunk_correct);
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        // the sponsor pays the fee in its chunk
+        let should_charge_sponsor = Boolean::and(
+            cs.namespace(|| "should_charge_sponsor"),
+            &is_ohs_valid,
+            &is_sponsor_charged,
+        )?;
+        let updated_sponsor_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "charged sponsor balance"),
+            updated_sponsor_balance,
+            &cur.account.balance,
+            &should_charge_sponsor,
+        )?;
+
+        multi_or(
+            cs.namespace(|| "is_op_valid"),
+            &[lhs_valid, is_rhs_valid, is_ohs_valid],
+        )
     }
-}
 
-pub fn check_account_data<E: RescueEngine, CS: ConstraintSystem<E>>(
-    mut cs: CS,
-    cur: &AllocatedOperationBranch<E>,
-    token_id: &CircuitElement<E>,
-    params: &E::Params,
-) -> Result<(AllocatedNum<E>, Boolean, CircuitElement<E>), SynthesisError> {
-    //first we prove calculate root of the subtree to obtain account_leaf_data:
-    let (cur_account_leaf_bits, is_account_empty, subtree_root) = allocate_account_leaf_bits(
-        cs.namespace(|| "allocate current_account_leaf_hash"),
-        cur,
-        token_id,
-        params,
-    )?;
-    Ok((
+    /// Batch transfer is applied in chunks of three kinds:
+    /// - the first chunk charges the fee of the sender and increments its nonce,
+    ///   the rest of the header chunks change nothing;
+    /// - the first chunk of a token block removes the token from the sender;
+    /// - the second chunk of a token block adds the token to the recipient,
+    ///   the rest of the token block changes nothing.
+    ///
+    /// The signature commits to the root of the list tree of the tokens,
+    /// every moved token is checked to be on the list with its audit path.
+    fn batch_transfer<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        lhs: &AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        is_balance_geq_fee: &Boolean,
+        is_account_empty: &Boolean,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        is_sig_verified: &Boolean,
+        prev: &PreviousData<E>,
+    ) -> Result<Boolean, SynthesisError> {
+        // construct pubdata of the header and of the token of the chunk
+        let mut header_pubdata_bits = vec![];
+        header_pubdata_bits.extend(chunk_data.tx_type.get_bits_be());
+        header_pubdata_bits.extend(prev.lhs_account_id.get_bits_be());
+        header_pubdata_bits.extend(prev.rhs_account_id.get_bits_be());
+        header_pubdata_bits.extend(op_data.eth_address.get_bits_be());
+        header_pubdata_bits.extend(op_data.fee_packed.get_bits_be());
+        header_pubdata_bits.extend(op_data.tokens_count.get_bits_be());
+        header_pubdata_bits.resize(
+            BatchTransferOp::HEADER_CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        let mut token_pubdata_bits = vec![];
+        token_pubdata_bits.extend(op_data.collection_id.get_bits_be());
+        token_pubdata_bits.extend(op_data.token_id_bits_be());
+        token_pubdata_bits.resize(
+            BatchTransferOp::TOKEN_CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message preimage (serialized_tx)
+        let mut serialized_tx_bits = vec![];
+        serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_tx_bits.extend(lhs.account_id.get_bits_be());
+        serialized_tx_bits.extend(lhs.account.address.get_bits_be());
+        serialized_tx_bits.extend(op_data.eth_address.get_bits_be());
+        serialized_tx_bits.extend(op_data.tokens_count.get_bits_be());
+        serialized_tx_bits.extend(
+            op_data
+                .tokens_root
+                .clone()
+                .into_padded_be_bits(FR_BIT_WIDTH_PADDED),
+        );
+        serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
+        serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(serialized_tx_bits.len(), SIGNED_BATCH_TRANSFER_BIT_WIDTH);
+
+        let mut is_header_chunk_flags = vec![];
+        for i in 0..BatchTransferOp::HEADER_CHUNKS {
+            is_header_chunk_flags.push(Boolean::from(Expression::equals(
+                cs.namespace(|| format!("is chunk number {}", i)),
+                &chunk_data.chunk_number,
+                Expression::u64::<CS>(i as u64),
+            )?));
+        }
+        let is_header_chunk = multi_or(cs.namespace(|| "is_header_chunk"), &is_header_chunk_flags)?;
+
+        let token_chunk =
+            batch_transfer_token_chunk::<E, CS>(&chunk_data.chunk_number, &op_data.token_index);
+        let mut is_token_chunk = vec![];
+        for i in 0..BatchTransferOp::TOKEN_CHUNKS {
+            is_token_chunk.push(Boolean::from(Expression::equals(
+                cs.namespace(|| format!("is token chunk number {}", i)),
+                token_chunk.clone(),
+                Expression::u64::<CS>(i as u64),
+            )?));
+        }
+        let is_token_block_chunk =
+            multi_or(cs.namespace(|| "is_token_block_chunk"), &is_token_chunk)?;
+
+        let header_pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_header_pubdata_chunk"),
+            &header_pubdata_bits,
+            &chunk_data.chunk_number,
+            BatchTransferOp::HEADER_CHUNKS,
+        )?;
+        let token_pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_token_pubdata_chunk"),
+            &token_pubdata_bits,
+            token_chunk,
+            BatchTransferOp::TOKEN_CHUNKS,
+        )?;
+        let pubdata_chunk = Expression::conditionally_select(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &header_pubdata_chunk,
+            &token_pubdata_chunk,
+            &is_header_chunk,
+        )?;
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_correct"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+
+        // verify correct tx_code
+        let is_batch_transfer = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_batch_transfer"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(BatchTransferOp::OP_CODE)),
+        )?);
+
+        // the token of the chunk is on the signed list
+        let op_token = TokenContent::from_op_data(op_data);
+        let token_list_root = allocate_merkle_root(
+            cs.namespace(|| "token list root"),
+            &op_token.nft_leaf_bits_le(),
+            &op_data.token_index.get_bits_le(),
+            &op_data.token_list_path,
+            self.rescue_params,
+        )?;
+        let is_token_listed = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_token_listed"),
+            &token_list_root,
+            &op_data.tokens_root.get_number(),
+        )?);
+
+        let mut base_valid_flags = vec![];
+        base_valid_flags.push(is_pubdata_chunk_correct);
+        base_valid_flags.push(is_batch_transfer);
+        base_valid_flags.push(multi_or(
+            cs.namespace(|| "is chunk of header or token block"),
+            &[is_header_chunk.clone(), is_token_block_chunk],
+        )?);
+        base_valid_flags.push(multi_or(
+            cs.namespace(|| "is token listed or header chunk"),
+            &[is_token_listed, is_header_chunk],
+        )?);
+        let base_valid = multi_and(cs.namespace(|| "base_valid"), &base_valid_flags)?;
+
+        // lhs
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+        let is_tokens_count_zero = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_tokens_count_zero"),
+            &op_data.tokens_count.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let mut lhs_valid_flags = vec![];
+        lhs_valid_flags.push(base_valid.clone());
+        lhs_valid_flags.push(is_first_chunk.clone());
+        lhs_valid_flags.push(is_balance_geq_fee.clone());
+        lhs_valid_flags.push(is_tokens_count_zero.not());
+        lhs_valid_flags.push(no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?);
+
+        let is_serialized_tx_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_tx_correct"),
+            serialized_tx_bits,
+            &op_data,
+        )?;
+        lhs_valid_flags.push(is_serialized_tx_correct);
+        lhs_valid_flags.push(is_sig_verified.clone());
+
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &lhs.account.pub_key_hash,
+        )?;
+        lhs_valid_flags.push(is_signer_valid);
+
+        let lhs_valid = multi_and(cs.namespace(|| "lhs_valid"), &lhs_valid_flags)?;
+
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        let updated_nonce =
+            Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
+
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            updated_nonce,
+            &cur.account.nonce,
+            &lhs_valid,
+        )?;
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "updated cur balance"),
+            updated_balance,
+            &cur.account.balance,
+            &lhs_valid,
+        )?;
+
+        // the token leaves the sender
+        let mut remove_valid_flags = vec![];
+        remove_valid_flags.push(base_valid.clone());
+        remove_valid_flags.push(is_token_chunk[0].clone());
+        remove_valid_flags.push(CircuitElement::equals(
+            cs.namespace(|| "is sender account"),
+            &cur.account_id,
+            &prev.lhs_account_id,
+        )?);
+        remove_valid_flags.push(TokenContent::equals(
+            cs.namespace(|| "is_token_correct"),
+            &cur.token,
+            &op_token,
+        )?);
+        let is_remove_valid = multi_and(cs.namespace(|| "is_remove_valid"), &remove_valid_flags)?;
+
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "sent token"), &is_remove_valid)?;
+
+        // the token is received by the recipient, a new account gets its address with it
+        let mut add_valid_flags = vec![];
+        add_valid_flags.push(base_valid.clone());
+        add_valid_flags.push(is_token_chunk[1].clone());
+        add_valid_flags.push(CircuitElement::equals(
+            cs.namespace(|| "is recipient account"),
+            &cur.account_id,
+            &prev.rhs_account_id,
+        )?);
+        add_valid_flags.push(cur.token.is_empty(cs.namespace(|| "is rhs slot empty"))?);
+        let is_address_correct = CircuitElement::equals(
+            cs.namespace(|| "is_address_correct"),
+            &op_data.eth_address,
+            &cur.account.address,
+        )?;
+        add_valid_flags.push(multi_or(
+            cs.namespace(|| "is address correct or account empty"),
+            &[is_address_correct, is_account_empty.clone()],
+        )?);
+        let is_add_valid = multi_and(cs.namespace(|| "is_add_valid"), &add_valid_flags)?;
+
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "received token"),
+            &op_token,
+            &cur.token,
+            &is_add_valid,
+        )?;
+        cur.account.address = CircuitElement::conditionally_select(
+            cs.namespace(|| "recipient address"),
+            &op_data.eth_address,
+            &cur.account.address,
+            &is_add_valid,
+        )?;
+
+        // ohs
+        let mut ohs_valid_flags = vec![];
+        ohs_valid_flags.push(base_valid);
+        ohs_valid_flags.push(is_first_chunk.not());
+        ohs_valid_flags.push(is_token_chunk[0].not());
+        ohs_valid_flags.push(is_token_chunk[1].not());
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        multi_or(
+            cs.namespace(|| "is_op_valid"),
+            &[lhs_valid, is_remove_valid, is_add_valid, is_ohs_valid],
+        )
+    }
+
+    /// Swap is applied in four chunks, so that it works for accounts without free token slots:
+    /// - the first account gives its token away and pays its fee (signed by its order);
+    /// - the second account gives its token away and pays its fee (signed by its order);
+    /// - the token of the first account is added to the second one;
+    /// - the token of the second account is added to the first one.
+    ///
+    /// Each added token is checked to be on the list of tokens its recipient order accepts.
+    fn swap<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        lhs: &AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        is_balance_geq_fee: &Boolean,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        is_sig_verified: &Boolean,
+        prev: &PreviousData<E>,
+    ) -> Result<Boolean, SynthesisError> {
+        let token_a = TokenContent::from_op_data(op_data);
+        let token_b = TokenContent::from_second_order(op_data);
+
+        // construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be());
+        pubdata_bits.extend(prev.lhs_account_id.get_bits_be());
+        pubdata_bits.extend(op_data.collection_id.get_bits_be());
+        pubdata_bits.extend(op_data.token_id_bits_be());
+        pubdata_bits.extend(prev.rhs_account_id.get_bits_be());
+        pubdata_bits.extend(op_data.second_collection_id.get_bits_be());
+        pubdata_bits.extend(op_data.second_token_id_hi.get_bits_be());
+        pubdata_bits.extend(op_data.second_token_id_lo.get_bits_be());
+        pubdata_bits.extend(op_data.fee_packed.get_bits_be());
+        pubdata_bits.extend(op_data.second_fee_packed.get_bits_be());
+        pubdata_bits.resize(
+            SwapOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message preimages of both orders (serialized_tx)
+        let mut serialized_order_a_bits = vec![];
+        serialized_order_a_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_order_a_bits.extend(lhs.account_id.get_bits_be());
+        serialized_order_a_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_order_a_bits.extend(op_data.token_id_bits_be());
+        serialized_order_a_bits.extend(op_data.tokens_count.get_bits_be());
+        serialized_order_a_bits.extend(
+            op_data
+                .tokens_root
+                .clone()
+                .into_padded_be_bits(FR_BIT_WIDTH_PADDED),
+        );
+        serialized_order_a_bits.extend(op_data.fee_packed.get_bits_be());
+        serialized_order_a_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(serialized_order_a_bits.len(), SIGNED_ORDER_BIT_WIDTH);
+
+        let mut serialized_order_b_bits = vec![];
+        serialized_order_b_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_order_b_bits.extend(cur.account_id.get_bits_be());
+        serialized_order_b_bits.extend(op_data.second_collection_id.get_bits_be());
+        serialized_order_b_bits.extend(op_data.second_token_id_hi.get_bits_be());
+        serialized_order_b_bits.extend(op_data.second_token_id_lo.get_bits_be());
+        serialized_order_b_bits.extend(op_data.second_tokens_count.get_bits_be());
+        serialized_order_b_bits.extend(
+            op_data
+                .second_tokens_root
+                .clone()
+                .into_padded_be_bits(FR_BIT_WIDTH_PADDED),
+        );
+        serialized_order_b_bits.extend(op_data.second_fee_packed.get_bits_be());
+        serialized_order_b_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(serialized_order_b_bits.len(), SIGNED_ORDER_BIT_WIDTH);
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            SwapOp::CHUNKS,
+        )?;
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_correct"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+
+        // verify correct tx_code
+        let is_swap = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_swap"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(SwapOp::OP_CODE)),
+        )?);
+
+        let mut is_chunk = vec![];
+        for i in 0..4 {
+            is_chunk.push(Boolean::from(Expression::equals(
+                cs.namespace(|| format!("is chunk number {}", i)),
+                &chunk_data.chunk_number,
+                Expression::u64::<CS>(i),
+            )?));
+        }
+
+        let is_serialized_order_a_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_order_a_correct"),
+            serialized_order_a_bits,
+            &op_data,
+        )?;
+        let is_serialized_order_b_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_order_b_correct"),
+            serialized_order_b_bits,
+            &op_data,
+        )?;
+
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &cur.account.pub_key_hash,
+        )?;
+
+        let is_account_a = CircuitElement::equals(
+            cs.namespace(|| "is account a"),
+            &cur.account_id,
+            &prev.lhs_account_id,
+        )?;
+        let is_account_b = CircuitElement::equals(
+            cs.namespace(|| "is account b"),
+            &cur.account_id,
+            &prev.rhs_account_id,
+        )?;
+
+        let is_nonce_valid = no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?;
+
+        // the second account pays its own fee
+        let diff_balance_second_fee = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.second_fee.get_number());
+        let diff_balance_second_fee_bits = diff_balance_second_fee.into_bits_le_fixed(
+            cs.namespace(|| "balance-second fee bits"),
+            params::BALANCE_BIT_WIDTH,
+        )?;
+        let is_balance_geq_second_fee = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_balance_geq_second_fee: diff equal to repacked"),
+            diff_balance_second_fee,
+            Expression::from_le_bits::<CS>(&diff_balance_second_fee_bits),
+        )?);
+
+        // the token given away by the second account is accepted by the first one and vice versa
+        let token_list_index = op_data.token_index.get_bits_le();
+        let token_a_list_root = allocate_merkle_root(
+            cs.namespace(|| "token a list root"),
+            &token_a.nft_leaf_bits_le(),
+            &token_list_index,
+            &op_data.token_list_path,
+            self.rescue_params,
+        )?;
+        let is_token_a_accepted = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_token_a_accepted"),
+            &token_a_list_root,
+            &op_data.second_tokens_root.get_number(),
+        )?);
+        let token_b_list_root = allocate_merkle_root(
+            cs.namespace(|| "token b list root"),
+            &token_b.nft_leaf_bits_le(),
+            &token_list_index,
+            &op_data.token_list_path,
+            self.rescue_params,
+        )?;
+        let is_token_b_accepted = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_token_b_accepted"),
+            &token_b_list_root,
+            &op_data.tokens_root.get_number(),
+        )?);
+
+        let base_valid = Boolean::and(
+            cs.namespace(|| "base_valid"),
+            &is_pubdata_chunk_correct,
+            &is_swap,
+        )?;
+
+        // the first account gives its token away
+        let mut remove_a_valid_flags = vec![];
+        remove_a_valid_flags.push(base_valid.clone());
+        remove_a_valid_flags.push(is_chunk[0].clone());
+        remove_a_valid_flags.push(
+            CircuitElement::equals(
+                cs.namespace(|| "is swap with self"),
+                &prev.lhs_account_id,
+                &prev.rhs_account_id,
+            )?
+            .not(),
+        );
+        remove_a_valid_flags.push(TokenContent::equals(
+            cs.namespace(|| "is token a correct"),
+            &cur.token,
+            &token_a,
+        )?);
+        remove_a_valid_flags.push(is_balance_geq_fee.clone());
+        remove_a_valid_flags.push(is_nonce_valid.clone());
+        remove_a_valid_flags.push(is_serialized_order_a_correct);
+        remove_a_valid_flags.push(is_sig_verified.clone());
+        remove_a_valid_flags.push(is_signer_valid.clone());
+        let is_remove_a_valid =
+            multi_and(cs.namespace(|| "is_remove_a_valid"), &remove_a_valid_flags)?;
+
+        // the second account gives its token away
+        let mut remove_b_valid_flags = vec![];
+        remove_b_valid_flags.push(base_valid.clone());
+        remove_b_valid_flags.push(is_chunk[1].clone());
+        remove_b_valid_flags.push(is_account_b.clone());
+        remove_b_valid_flags.push(TokenContent::equals(
+            cs.namespace(|| "is token b correct"),
+            &cur.token,
+            &token_b,
+        )?);
+        remove_b_valid_flags.push(is_balance_geq_second_fee);
+        remove_b_valid_flags.push(is_nonce_valid);
+        remove_b_valid_flags.push(is_serialized_order_b_correct);
+        remove_b_valid_flags.push(is_sig_verified.clone());
+        remove_b_valid_flags.push(is_signer_valid);
+        let is_remove_b_valid =
+            multi_and(cs.namespace(|| "is_remove_b_valid"), &remove_b_valid_flags)?;
+
+        let is_remove_valid = multi_or(
+            cs.namespace(|| "is_remove_valid"),
+            &[is_remove_a_valid.clone(), is_remove_b_valid.clone()],
+        )?;
+        let fee = Expression::conditionally_select(
+            cs.namespace(|| "fee of the account"),
+            &op_data.fee.get_number(),
+            &op_data.second_fee.get_number(),
+            &is_remove_a_valid,
+        )?;
+        let updated_balance =
+            Expression::from(&cur.account.balance.get_number()) - Expression::from(&fee);
+        let updated_nonce =
+            Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
+
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            updated_nonce,
+            &cur.account.nonce,
+            &is_remove_valid,
+        )?;
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "updated cur balance"),
+            updated_balance,
+            &cur.account.balance,
+            &is_remove_valid,
+        )?;
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "given away token"), &is_remove_valid)?;
+
+        // the second account receives the token of the first one
+        let is_slot_empty = cur.token.is_empty(cs.namespace(|| "is cur slot empty"))?;
+        let add_a_valid = multi_and(
+            cs.namespace(|| "is_add_a_valid"),
+            &[
+                base_valid.clone(),
+                is_chunk[2].clone(),
+                is_account_b,
+                is_slot_empty.clone(),
+                is_token_a_accepted,
+            ],
+        )?;
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "received token a"),
+            &token_a,
+            &cur.token,
+            &add_a_valid,
+        )?;
+
+        // the first account receives the token of the second one
+        let add_b_valid = multi_and(
+            cs.namespace(|| "is_add_b_valid"),
+            &[
+                base_valid.clone(),
+                is_chunk[3].clone(),
+                is_account_a,
+                is_slot_empty,
+                is_token_b_accepted,
+            ],
+        )?;
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "received token b"),
+            &token_b,
+            &cur.token,
+            &add_b_valid,
+        )?;
+
+        // ohs
+        let mut ohs_valid_flags = vec![base_valid];
+        ohs_valid_flags.extend(is_chunk.iter().map(Boolean::not));
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        multi_or(
+            cs.namespace(|| "is_op_valid"),
+            &[
+                is_remove_a_valid,
+                is_remove_b_valid,
+                add_a_valid,
+                add_b_valid,
+                is_ohs_valid,
+            ],
+        )
+    }
+
+    /// Uniqueness of the minted token id is checked by the state keeper, the circuit puts
+    /// the token into an empty slot of the minter, the contract checks that the minter
+    /// is authorized in the governance contract.
+    fn mint<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        is_balance_geq_fee: &Boolean,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        is_sig_verified: &Boolean,
+    ) -> Result<Boolean, SynthesisError> {
+        let mut base_valid_flags = vec![];
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //COLLECTIONID_BIT_WIDTH=16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //TOKENID_BIT_WIDTH=256
+        pubdata_bits.extend(op_data.fee_packed.get_bits_be()); //FEE_PACKED=16
+        pubdata_bits.extend(op_data.eth_address.get_bits_be()); //ETH_ADDRESS=160
+        pubdata_bits.extend(op_data.content_hash_bits_be()); //CONTENT_HASH_BIT_WIDTH=256
+        pubdata_bits.resize(
+            MintOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message
+        let mut serialized_tx_bits = vec![];
+        serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_tx_bits.extend(cur.account_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.eth_address.get_bits_be());
+        serialized_tx_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.token_id_bits_be());
+        serialized_tx_bits.extend(op_data.content_hash_bits_be());
+        serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
+        serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(serialized_tx_bits.len(), params::SIGNED_MINT_BIT_WIDTH);
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            MintOp::CHUNKS,
+        )?;
+
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        base_valid_flags.push(is_pubdata_chunk_correct);
+
+        // verify correct tx_code
+        let is_mint = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_mint"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(MintOp::OP_CODE)),
+        )?);
+        base_valid_flags.push(is_mint);
+
+        let is_serialized_tx_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_tx_correct"),
+            serialized_tx_bits,
+            &op_data,
+        )?;
+        let is_signed_correctly = multi_and(
+            cs.namespace(|| "is_signed_correctly"),
+            &[is_serialized_tx_correct, is_sig_verified.clone()],
+        )?;
+        let is_sig_correct = multi_or(
+            cs.namespace(|| "sig is valid or not first chunk"),
+            &[is_signed_correctly, is_first_chunk.clone().not()],
+        )?;
+        base_valid_flags.push(is_sig_correct);
+
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &cur.account.pub_key_hash,
+        )?;
+        base_valid_flags.push(is_signer_valid);
+
+        // published minter address is the address of the account
+        let is_minter_address_correct = CircuitElement::equals(
+            cs.namespace(|| "is_minter_address_correct"),
+            &op_data.eth_address,
+            &cur.account.address,
+        )?;
+        base_valid_flags.push(is_minter_address_correct);
+
+        let is_base_valid = multi_and(cs.namespace(|| "valid base mint"), &base_valid_flags)?;
+
+        let mut lhs_valid_flags = vec![];
+        lhs_valid_flags.push(is_first_chunk.clone());
+        lhs_valid_flags.push(is_base_valid.clone());
+        // the token takes an empty slot of the minter
+        lhs_valid_flags.push(cur.token.is_empty(cs.namespace(|| "is_slot_empty"))?);
+        lhs_valid_flags.push(is_balance_geq_fee.clone());
+        lhs_valid_flags.push(no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?);
+        let lhs_valid = multi_and(cs.namespace(|| "is_lhs_valid"), &lhs_valid_flags)?;
+
+        let mut ohs_valid_flags = vec![];
+        ohs_valid_flags.push(is_base_valid);
+        ohs_valid_flags.push(is_first_chunk.not());
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        let tx_valid = multi_or(
+            cs.namespace(|| "tx_valid"),
+            &[lhs_valid.clone(), is_ohs_valid],
+        )?;
+
+        //mutate current branch if it is first chunk of valid mint transaction
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated balance"),
+            updated_balance,
+            &cur.account.balance,
+            &lhs_valid,
+        )?;
+
+        cur.token = TokenContent::conditionally_select(
+            cs.namespace(|| "minted token"),
+            &TokenContent::from_op_data(op_data),
+            &cur.token,
+            &lhs_valid,
+        )?;
+
+        let updated_nonce =
+            Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            updated_nonce,
+            &cur.account.nonce,
+            &lhs_valid,
+        )?;
+
+        Ok(tx_valid)
+    }
+
+    fn burn<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        is_balance_geq_fee: &Boolean,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        is_sig_verified: &Boolean,
+    ) -> Result<Boolean, SynthesisError> {
+        let mut base_valid_flags = vec![];
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //COLLECTIONID_BIT_WIDTH=16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //TOKENID_BIT_WIDTH=256
+        pubdata_bits.extend(op_data.fee_packed.get_bits_be()); //FEE_PACKED=16
+        pubdata_bits.resize(
+            BurnOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message
+        let mut serialized_tx_bits = vec![];
+        serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_tx_bits.extend(cur.account_id.get_bits_be());
+        serialized_tx_bits.extend(cur.account.address.get_bits_be());
+        serialized_tx_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.token_id_bits_be());
+        serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
+        serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(serialized_tx_bits.len(), params::SIGNED_BURN_BIT_WIDTH);
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            BurnOp::CHUNKS,
+        )?;
+
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        base_valid_flags.push(is_pubdata_chunk_correct);
+
+        // verify correct tx_code
+        let is_burn = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_burn"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(BurnOp::OP_CODE)),
+        )?);
+        base_valid_flags.push(is_burn);
+
+        let is_serialized_tx_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_tx_correct"),
+            serialized_tx_bits,
+            &op_data,
+        )?;
+        let is_signed_correctly = multi_and(
+            cs.namespace(|| "is_signed_correctly"),
+            &[is_serialized_tx_correct, is_sig_verified.clone()],
+        )?;
+        let is_sig_correct = multi_or(
+            cs.namespace(|| "sig is valid or not first chunk"),
+            &[is_signed_correctly, is_first_chunk.clone().not()],
+        )?;
+        base_valid_flags.push(is_sig_correct);
+
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &cur.account.pub_key_hash,
+        )?;
+        base_valid_flags.push(is_signer_valid);
+
+        let is_base_valid = multi_and(cs.namespace(|| "valid base burn"), &base_valid_flags)?;
+
+        let mut lhs_valid_flags = vec![];
+        lhs_valid_flags.push(is_first_chunk.clone());
+        lhs_valid_flags.push(is_base_valid.clone());
+        // the token is destroyed
+        let op_token = TokenContent::from_op_data(op_data);
+        let is_token_correct =
+            TokenContent::equals(cs.namespace(|| "is_token_correct"), &cur.token, &op_token)?;
+        lhs_valid_flags.push(is_token_correct);
+        lhs_valid_flags.push(is_balance_geq_fee.clone());
+        lhs_valid_flags.push(no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?);
+        let lhs_valid = multi_and(cs.namespace(|| "is_lhs_valid"), &lhs_valid_flags)?;
+
+        let mut ohs_valid_flags = vec![];
+        ohs_valid_flags.push(is_base_valid);
+        ohs_valid_flags.push(is_first_chunk.not());
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        let tx_valid = multi_or(
+            cs.namespace(|| "tx_valid"),
+            &[lhs_valid.clone(), is_ohs_valid],
+        )?;
+
+        //mutate current branch if it is first chunk of valid burn transaction
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated balance"),
+            updated_balance,
+            &cur.account.balance,
+            &lhs_valid,
+        )?;
+
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "burned token"), &lhs_valid)?;
+
+        let updated_nonce =
+            Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            updated_nonce,
+            &cur.account.nonce,
+            &lhs_valid,
+        )?;
+
+        Ok(tx_valid)
+    }
+
+    /// Approve sets the operator of the token in the first chunk. The second chunk opens
+    /// the account of the operator to bind the published operator id to the signed address.
+    /// Revoke signs the zero address and publishes the id of the account itself.
+    fn approve<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        is_balance_geq_fee: &Boolean,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        is_sig_verified: &Boolean,
+        prev: &PreviousData<E>,
+    ) -> Result<Boolean, SynthesisError> {
+        let mut base_valid_flags = vec![];
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(prev.lhs_account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.operator_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //COLLECTIONID_BIT_WIDTH=16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //TOKENID_BIT_WIDTH=256
+        pubdata_bits.extend(op_data.fee_packed.get_bits_be()); //FEE_PACKED=16
+        pubdata_bits.resize(
+            ApproveOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message
+        let mut serialized_tx_bits = vec![];
+        serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_tx_bits.extend(cur.account_id.get_bits_be());
+        serialized_tx_bits.extend(cur.account.address.get_bits_be());
+        serialized_tx_bits.extend(op_data.eth_address.get_bits_be());
+        serialized_tx_bits.extend(op_data.collection_id.get_bits_be());
+        serialized_tx_bits.extend(op_data.token_id_bits_be());
+        serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
+        serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(serialized_tx_bits.len(), params::SIGNED_APPROVE_BIT_WIDTH);
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            ApproveOp::CHUNKS,
+        )?;
+
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+        let is_second_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_second_chunk"),
+            &chunk_data.chunk_number,
+            Expression::u64::<CS>(1),
+        )?);
+
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        base_valid_flags.push(is_pubdata_chunk_correct);
+
+        // verify correct tx_code
+        let is_approve = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_approve"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(ApproveOp::OP_CODE)),
+        )?);
+        base_valid_flags.push(is_approve);
+
+        let is_base_valid = multi_and(cs.namespace(|| "valid base approve"), &base_valid_flags)?;
+
+        let is_revoke = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_revoke"),
+            &op_data.eth_address.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let mut lhs_valid_flags = vec![];
+        lhs_valid_flags.push(is_first_chunk.clone());
+        lhs_valid_flags.push(is_base_valid.clone());
+
+        let is_serialized_tx_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_tx_correct"),
+            serialized_tx_bits,
+            &op_data,
+        )?;
+        lhs_valid_flags.push(is_serialized_tx_correct);
+        lhs_valid_flags.push(is_sig_verified.clone());
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &cur.account.pub_key_hash,
+        )?;
+        lhs_valid_flags.push(is_signer_valid);
+
+        // the approval changes the operator of the token, not the token itself
+        let op_token = TokenContent::from_op_data(op_data);
+        let is_token_correct =
+            TokenContent::equals(cs.namespace(|| "is_token_correct"), &cur.token, &op_token)?;
+        lhs_valid_flags.push(is_token_correct);
+        lhs_valid_flags.push(is_balance_geq_fee.clone());
+        lhs_valid_flags.push(no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?);
+
+        // revoke publishes the account itself, zero in the token leaf is no operator
+        let is_operator_self = CircuitElement::equals(
+            cs.namespace(|| "is operator the account"),
+            &op_data.operator_id,
+            &cur.account_id,
+        )?;
+        let is_operator_id_zero = Boolean::from(Expression::equals(
+            cs.namespace(|| "is operator id zero"),
+            &op_data.operator_id.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+        let is_revoke_published = Boolean::and(
+            cs.namespace(|| "is revoke published"),
+            &is_revoke,
+            &is_operator_self,
+        )?;
+        let is_approval_published = multi_and(
+            cs.namespace(|| "is approval published"),
+            &[
+                is_revoke.not(),
+                is_operator_self.not(),
+                is_operator_id_zero.not(),
+            ],
+        )?;
+        lhs_valid_flags.push(multi_or(
+            cs.namespace(|| "is operator id correct"),
+            &[is_revoke_published, is_approval_published],
+        )?);
+        let lhs_valid = multi_and(cs.namespace(|| "is_lhs_valid"), &lhs_valid_flags)?;
+
+        // the operator account has the signed address
+        let mut rhs_valid_flags = vec![];
+        rhs_valid_flags.push(is_second_chunk.clone());
+        rhs_valid_flags.push(is_base_valid.clone());
+        let is_operator_id = CircuitElement::equals(
+            cs.namespace(|| "is account the operator"),
+            &cur.account_id,
+            &op_data.operator_id,
+        )?;
+        let is_operator_address = CircuitElement::equals(
+            cs.namespace(|| "is address the operator one"),
+            &cur.account.address,
+            &op_data.eth_address,
+        )?;
+        let is_operator_account = Boolean::and(
+            cs.namespace(|| "is operator account"),
+            &is_operator_id,
+            &is_operator_address,
+        )?;
+        rhs_valid_flags.push(multi_or(
+            cs.namespace(|| "is operator account correct"),
+            &[is_revoke.clone(), is_operator_account],
+        )?);
+        let rhs_valid = multi_and(cs.namespace(|| "is_rhs_valid"), &rhs_valid_flags)?;
+
+        let mut ohs_valid_flags = vec![];
+        ohs_valid_flags.push(is_base_valid);
+        ohs_valid_flags.push(is_first_chunk.not());
+        ohs_valid_flags.push(is_second_chunk.not());
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        let tx_valid = multi_or(
+            cs.namespace(|| "tx_valid"),
+            &[lhs_valid.clone(), rhs_valid, is_ohs_valid],
+        )?;
+
+        //mutate current branch if it is first chunk of valid approve transaction
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated balance"),
+            updated_balance,
+            &cur.account.balance,
+            &lhs_valid,
+        )?;
+
+        let approved = CircuitElement::conditionally_select(
+            cs.namespace(|| "approved operator"),
+            &op_data.no_operator,
+            &op_data.operator_id,
+            &is_revoke,
+        )?;
+        cur.token.approved = CircuitElement::conditionally_select(
+            cs.namespace(|| "mutated approved operator"),
+            &approved,
+            &cur.token.approved,
+            &lhs_valid,
+        )?;
+
+        let updated_nonce =
+            Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            updated_nonce,
+            &cur.account.nonce,
+            &lhs_valid,
+        )?;
+
+        Ok(tx_valid)
+    }
+
+    /// Approve for all sets the leaf of the operator in the operators tree of the account
+    /// in the first chunk, the second chunk opens the account of the operator
+    /// to bind the published operator id to the signed address.
+    fn approve_for_all<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        is_balance_geq_fee: &Boolean,
+        op_data: &AllocatedOperationData<E>,
+        signer_key: &AllocatedSignerPubkey<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+        is_sig_verified: &Boolean,
+        prev: &PreviousData<E>,
+    ) -> Result<Boolean, SynthesisError> {
+        // approval flag is published and signed as a byte
+        let mut approved_bits = vec![Boolean::constant(false); 7];
+        approved_bits.extend(op_data.approved.get_bits_be());
+
+        let mut base_valid_flags = vec![];
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(prev.lhs_account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.operator_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(approved_bits.clone()); //8
+        pubdata_bits.extend(op_data.fee_packed.get_bits_be()); //FEE_PACKED=16
+        pubdata_bits.resize(
+            ApproveForAllOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        // construct signature message
+        let mut serialized_tx_bits = vec![];
+        serialized_tx_bits.extend(chunk_data.tx_type.get_bits_be());
+        serialized_tx_bits.extend(cur.account_id.get_bits_be());
+        serialized_tx_bits.extend(cur.account.address.get_bits_be());
+        serialized_tx_bits.extend(op_data.eth_address.get_bits_be());
+        serialized_tx_bits.extend(approved_bits);
+        serialized_tx_bits.extend(op_data.fee_packed.get_bits_be());
+        serialized_tx_bits.extend(cur.account.nonce.get_bits_be());
+        assert_eq!(
+            serialized_tx_bits.len(),
+            params::SIGNED_APPROVE_FOR_ALL_BIT_WIDTH
+        );
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            ApproveForAllOp::CHUNKS,
+        )?;
+
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        base_valid_flags.push(is_pubdata_chunk_correct);
+
+        // verify correct tx_code
+        let is_approve_for_all = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_approve_for_all"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(ApproveForAllOp::OP_CODE)),
+        )?);
+        base_valid_flags.push(is_approve_for_all);
+
+        let is_base_valid = multi_and(
+            cs.namespace(|| "valid base approve for all"),
+            &base_valid_flags,
+        )?;
+
+        let mut lhs_valid_flags = vec![];
+        lhs_valid_flags.push(is_first_chunk.clone());
+        lhs_valid_flags.push(is_base_valid.clone());
+
+        let is_serialized_tx_correct = verify_signature_message_construction(
+            cs.namespace(|| "is_serialized_tx_correct"),
+            serialized_tx_bits,
+            &op_data,
+        )?;
+        lhs_valid_flags.push(is_serialized_tx_correct);
+        lhs_valid_flags.push(is_sig_verified.clone());
+        let is_signer_valid = CircuitElement::equals(
+            cs.namespace(|| "signer_key_correct"),
+            &signer_key.pubkey.get_hash(),
+            &cur.account.pub_key_hash,
+        )?;
+        lhs_valid_flags.push(is_signer_valid);
+        lhs_valid_flags.push(is_balance_geq_fee.clone());
+        lhs_valid_flags.push(no_nonce_overflow(
+            cs.namespace(|| "no nonce overflow"),
+            &cur.account.nonce.get_number(),
+        )?);
+
+        // operators tree is opened at the operator id with the old leaf,
+        // the new leaf is hashed along the same path
+        let old_operators_root = allocate_merkle_root(
+            cs.namespace(|| "old operators root"),
+            &op_data.is_operator.get_bits_le(),
+            &op_data.operator_id.get_bits_le(),
+            &op_data.operator_path,
+            self.rescue_params,
+        )?;
+        let is_operators_root_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is operators root correct"),
+            &old_operators_root,
+            &cur.account.operators_root,
+        )?);
+        lhs_valid_flags.push(is_operators_root_correct);
+        let lhs_valid = multi_and(cs.namespace(|| "is_lhs_valid"), &lhs_valid_flags)?;
+
+        // the operator account has the signed address
+        let mut rhs_valid_flags = vec![];
+        rhs_valid_flags.push(is_first_chunk.not());
+        rhs_valid_flags.push(is_base_valid);
+        rhs_valid_flags.push(CircuitElement::equals(
+            cs.namespace(|| "is account the operator"),
+            &cur.account_id,
+            &op_data.operator_id,
+        )?);
+        rhs_valid_flags.push(CircuitElement::equals(
+            cs.namespace(|| "is address the operator one"),
+            &cur.account.address,
+            &op_data.eth_address,
+        )?);
+        let rhs_valid = multi_and(cs.namespace(|| "is_rhs_valid"), &rhs_valid_flags)?;
+
+        let tx_valid = multi_or(cs.namespace(|| "tx_valid"), &[lhs_valid.clone(), rhs_valid])?;
+
+        //mutate current branch if it is first chunk of valid approve for all transaction
+        let updated_balance = Expression::from(&cur.account.balance.get_number())
+            - Expression::from(&op_data.fee.get_number());
+        cur.account.balance = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "mutated balance"),
+            updated_balance,
+            &cur.account.balance,
+            &lhs_valid,
+        )?;
+
+        let new_operators_root = allocate_merkle_root(
+            cs.namespace(|| "new operators root"),
+            &op_data.approved.get_bits_le(),
+            &op_data.operator_id.get_bits_le(),
+            &op_data.operator_path,
+            self.rescue_params,
+        )?;
+        cur.account.operators_root = AllocatedNum::conditionally_select(
+            cs.namespace(|| "mutated operators root"),
+            &new_operators_root,
+            &cur.account.operators_root,
+            &lhs_valid,
+        )?;
+
+        let updated_nonce =
+            Expression::from(&cur.account.nonce.get_number()) + Expression::u64::<CS>(1);
+        cur.account.nonce = CircuitElement::conditionally_select_with_number_strict(
+            cs.namespace(|| "update cur nonce"),
+            updated_nonce,
+            &cur.account.nonce,
+            &lhs_valid,
+        )?;
+
+        Ok(tx_valid)
+    }
+
+    /// Number of withdrawn tokens is `full_amount`, it is published as the success flag.
+    /// Request succeeds if the account belongs to the requester and the slot holds the token,
+    /// the circuit can't prove that the token is absent from the other slots of the account.
+    fn forced_withdraw<CS: ConstraintSystem<E>>(
+        &self,
+        mut cs: CS,
+        cur: &mut AllocatedOperationBranch<E>,
+        chunk_data: &AllocatedChunkData<E>,
+        op_data: &AllocatedOperationData<E>,
+        ext_pubdata_chunk: &AllocatedNum<E>,
+    ) -> Result<Boolean, SynthesisError> {
+        let is_success = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_success"),
+            &op_data.full_amount.get_number(),
+            Expression::u64::<CS>(1),
+        )?);
+        let is_failure = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_failure"),
+            &op_data.full_amount.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        //construct pubdata
+        let mut pubdata_bits = vec![];
+        pubdata_bits.extend(chunk_data.tx_type.get_bits_be()); //TX_TYPE_BIT_WIDTH=8
+        pubdata_bits.extend(cur.account_id.get_bits_be()); //ACCOUNT_TREE_DEPTH=24
+        pubdata_bits.extend(op_data.eth_address.get_bits_be()); //ETH_ADDRESS=160
+        pubdata_bits.extend(op_data.collection_id.get_bits_be()); //COLLECTIONID_BIT_WIDTH=16
+        pubdata_bits.extend(op_data.token_id_bits_be()); //TOKENID_BIT_WIDTH=256
+        pubdata_bits.resize(
+            pubdata_bits.len() + params::SUCCESS_FLAG_BIT_WIDTH - 1,
+            Boolean::constant(false),
+        );
+        pubdata_bits.push(is_success.clone()); //SUCCESS_FLAG_BIT_WIDTH=8
+        pubdata_bits.resize(
+            ForcedWithdrawOp::CHUNKS * params::CHUNK_BIT_WIDTH,
+            Boolean::constant(false),
+        );
+
+        let pubdata_chunk = select_pubdata_chunk(
+            cs.namespace(|| "select_pubdata_chunk"),
+            &pubdata_bits,
+            &chunk_data.chunk_number,
+            ForcedWithdrawOp::CHUNKS,
+        )?;
+
+        let is_first_chunk = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_first_chunk"),
+            &chunk_data.chunk_number,
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?);
+
+        let mut base_valid_flags = vec![];
+        let is_pubdata_chunk_correct = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_pubdata_equal"),
+            &pubdata_chunk,
+            ext_pubdata_chunk,
+        )?);
+        base_valid_flags.push(is_pubdata_chunk_correct);
+
+        // verify correct tx_code
+        let is_forced_withdraw = Boolean::from(Expression::equals(
+            cs.namespace(|| "is_forced_withdraw"),
+            &chunk_data.tx_type.get_number(),
+            Expression::u64::<CS>(u64::from(ForcedWithdrawOp::OP_CODE)),
+        )?);
+        base_valid_flags.push(is_forced_withdraw);
+        base_valid_flags.push(multi_or(
+            cs.namespace(|| "is success flag boolean"),
+            &[is_success.clone(), is_failure],
+        )?);
+        let is_base_valid = multi_and(
+            cs.namespace(|| "valid base forced_withdraw"),
+            &base_valid_flags,
+        )?;
+
+        // published flag is the result of the request
+        let is_address_correct = CircuitElement::equals(
+            cs.namespace(|| "is_address_correct"),
+            &cur.account.address,
+            &op_data.eth_address,
+        )?;
+        let is_token_correct = TokenContent::equals(
+            cs.namespace(|| "is_token_correct"),
+            &cur.token,
+            &TokenContent::from_op_data(op_data),
+        )?;
+        let is_withdrawable = Boolean::and(
+            cs.namespace(|| "is_withdrawable"),
+            &is_address_correct,
+            &is_token_correct,
+        )?;
+        let is_success_correct = Boolean::xor(
+            cs.namespace(|| "is success flag mismatched"),
+            &is_withdrawable,
+            &is_success,
+        )?
+        .not();
+
+        let first_chunk_valid = multi_and(
+            cs.namespace(|| "first_chunk_valid"),
+            &[
+                is_first_chunk.clone(),
+                is_base_valid.clone(),
+                is_success_correct,
+            ],
+        )?;
+
+        let mut ohs_valid_flags = vec![];
+        ohs_valid_flags.push(is_base_valid);
+        ohs_valid_flags.push(is_first_chunk.not());
+        let is_ohs_valid = multi_and(cs.namespace(|| "is_ohs_valid"), &ohs_valid_flags)?;
+
+        let tx_valid = multi_or(
+            cs.namespace(|| "tx_valid"),
+            &[first_chunk_valid.clone(), is_ohs_valid],
+        )?;
+
+        //mutate current branch if it is first chunk of a successful forced withdraw
+        let success_account_update = Boolean::and(
+            cs.namespace(|| "success_account_update"),
+            &first_chunk_valid,
+            &is_success,
+        )?;
+        cur.token = cur
+            .token
+            .conditionally_clear(cs.namespace(|| "withdrawn token"), &success_account_update)?;
+
+        Ok(tx_valid)
+    }
+}
+
+pub fn check_account_data<E: RescueEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    cur: &AllocatedOperationBranch<E>,
+    token_leaf: &[Boolean],
+    token_slot: &[Boolean],
+    params: &E::Params,
+) -> Result<(AllocatedNum<E>, Boolean, CircuitElement<E>), SynthesisError> {
+    //first we prove calculate root of the subtree to obtain account_leaf_data:
+    let (cur_account_leaf_bits, is_account_empty, subtree_root) = allocate_account_leaf_bits(
+        cs.namespace(|| "allocate current_account_leaf_hash"),
+        cur,
+        token_leaf,
+        token_slot,
+        params,
+    )?;
+    Ok((
         allocate_merkle_root(
             cs.namespace(|| "account_merkle_root"),
             &cur_account_leaf_bits,
@@ -1811,20 +3820,23 @@ pub fn check_account_data<E: RescueEngine, CS: ConstraintSystem<E>>(
     ))
 }
 
-/// Account tree state will be extended in the future, so for current balance tree we
-/// append emtpy hash to reserve place for the future tree before hashing.
+/// Account state is the token subtree root hashed together with the ETH balance of the account,
+/// the balance takes the place that was reserved by an empty padding before.
+/// The root of the tree of operators approved for all the account tokens follows them.
 pub fn calc_account_state_tree_root<E: RescueEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
-    balance_root: &CircuitElement<E>,
+    token_root: &CircuitElement<E>,
+    balance: &CircuitElement<E>,
+    operators_root: &AllocatedNum<E>,
     params: &E::Params,
 ) -> Result<CircuitElement<E>, SynthesisError> {
-    let state_tree_root_input = balance_root.get_number();
-    let empty_root_padding =
-        AllocatedNum::zero(cs.namespace(|| "allocate zero element for padding"))?;
-
     let mut sponge_output = rescue::rescue_hash(
-        cs.namespace(|| "hash state root and balance root"),
-        &[state_tree_root_input, empty_root_padding],
+        cs.namespace(|| "hash token root, balance and operators root"),
+        &[
+            token_root.get_number(),
+            balance.get_number(),
+            operators_root.clone(),
+        ],
         params,
     )?;
 
@@ -1837,16 +3849,18 @@ pub fn calc_account_state_tree_root<E: RescueEngine, CS: ConstraintSystem<E>>(
 pub fn allocate_account_leaf_bits<E: RescueEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
     branch: &AllocatedOperationBranch<E>,
-    token_id: &CircuitElement<E>,
+    token_leaf: &[Boolean],
+    token_slot: &[Boolean],
     params: &E::Params,
 ) -> Result<(Vec<Boolean>, Boolean, CircuitElement<E>), SynthesisError> {
     //first we prove calculate root of the subtree to obtain account_leaf_data:
 
-    let token_data = &token_id.get_bits_le(); // TODO ADE token_id is missing in branch?
+    // token leaf holds the full 256-bit id and the collection id,
+    // its index is the slot the token was given when it entered the account
     let token_root = allocate_merkle_root(
         cs.namespace(|| "token_subtree_root"),
-        token_data,
-        &token_id.get_bits_le(),
+        token_leaf,
+        token_slot,
         &branch.token_audit_path,
         params,
     )?;
@@ -1869,6 +3883,8 @@ pub fn allocate_account_leaf_bits<E: RescueEngine, CS: ConstraintSystem<E>>(
     let state_tree_root = calc_account_state_tree_root(
         cs.namespace(|| "state_tree_root"),
         &token_subtree_root,
+        &branch.account.balance,
+        &branch.account.operators_root,
         params,
     )?;
 
@@ -1972,13 +3988,14 @@ fn select_vec_ifeq<
     Ok(resulting_vector)
 }
 
-fn select_pubdata_chunk<E: JubjubEngine, CS: ConstraintSystem<E>>(
+fn select_pubdata_chunk<E: JubjubEngine, CS: ConstraintSystem<E>, EX: Into<Expression<E>>>(
     mut cs: CS,
     pubdata_bits: &[Boolean],
-    chunk_number: &AllocatedNum<E>,
+    chunk_number: EX,
     total_chunks: usize,
 ) -> Result<AllocatedNum<E>, SynthesisError> {
     assert_eq!(pubdata_bits.len(), total_chunks * params::CHUNK_BIT_WIDTH);
+    let chunk_number: Expression<E> = chunk_number.into();
     let mut result =
         AllocatedNum::alloc(
             cs.namespace(|| "result pubdata chunk"),
@@ -1995,7 +4012,7 @@ fn select_pubdata_chunk<E: JubjubEngine, CS: ConstraintSystem<E>>(
         result = Expression::select_ifeq(
             cs.namespace(|| "select if correct chunk number"),
             Expression::u64::<CS>(i as u64),
-            chunk_number,
+            chunk_number.clone(),
             &current_chunk,
             &result,
         )?;
@@ -2004,6 +4021,195 @@ fn select_pubdata_chunk<E: JubjubEngine, CS: ConstraintSystem<E>>(
     Ok(result)
 }
 
+/// Number of the chunk inside the block of the batch transfer token, it is out of
+/// `0..BatchTransferOp::TOKEN_CHUNKS` for the chunks of other tokens and of the header.
+fn batch_transfer_token_chunk<E: JubjubEngine, CS: ConstraintSystem<E>>(
+    chunk_number: &AllocatedNum<E>,
+    token_index: &CircuitElement<E>,
+) -> Expression<E> {
+    (0..BatchTransferOp::TOKEN_CHUNKS).fold(
+        Expression::from(chunk_number)
+            - Expression::u64::<CS>(BatchTransferOp::HEADER_CHUNKS as u64),
+        |token_chunk, _| token_chunk - Expression::from(&token_index.get_number()),
+    )
+}
+
+/// Checks that the operation is signed either by the account itself or by an operator
+/// the account approved for the token or for all of its tokens.
+/// Operator key hash is zero when the account signs by itself, otherwise the operator
+/// account is bound to it by `is_operator_account_correct`.
+fn is_signer_owner_or_operator<E: RescueEngine + JubjubEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    signer_pub_key_hash: &CircuitElement<E>,
+    branch: &AllocatedOperationBranch<E>,
+    op_data: &AllocatedOperationData<E>,
+    params: &<E as RescueEngine>::Params,
+) -> Result<Boolean, SynthesisError> {
+    let is_signer_account = CircuitElement::equals(
+        cs.namespace(|| "is signer the account"),
+        signer_pub_key_hash,
+        &branch.account.pub_key_hash,
+    )?;
+    let is_operator_set = Boolean::from(Expression::equals(
+        cs.namespace(|| "is operator pub key hash zero"),
+        &op_data.operator_pub_key_hash.get_number(),
+        Expression::constant::<CS>(E::Fr::zero()),
+    )?)
+    .not();
+    let is_signer_operator = CircuitElement::equals(
+        cs.namespace(|| "is signer the operator"),
+        signer_pub_key_hash,
+        &op_data.operator_pub_key_hash,
+    )?;
+
+    // zero in the token leaf means no approval, the fee account can't be approved for a token
+    let is_operator_id_set = Boolean::from(Expression::equals(
+        cs.namespace(|| "is operator id zero"),
+        &op_data.operator_id.get_number(),
+        Expression::constant::<CS>(E::Fr::zero()),
+    )?)
+    .not();
+    let is_token_approved_to_operator = CircuitElement::equals(
+        cs.namespace(|| "is token approved to the operator"),
+        &branch.token.approved,
+        &op_data.operator_id,
+    )?;
+    let is_token_operator = Boolean::and(
+        cs.namespace(|| "is token operator"),
+        &is_operator_id_set,
+        &is_token_approved_to_operator,
+    )?;
+
+    // operators tree of the account is opened at the operator id
+    let operators_root = allocate_merkle_root(
+        cs.namespace(|| "operators_root"),
+        &op_data.is_operator.get_bits_le(),
+        &op_data.operator_id.get_bits_le(),
+        &op_data.operator_path,
+        params,
+    )?;
+    let is_operators_root_correct = Boolean::from(Expression::equals(
+        cs.namespace(|| "is operators root correct"),
+        &operators_root,
+        &branch.account.operators_root,
+    )?);
+    let is_operator_for_all = Boolean::and(
+        cs.namespace(|| "is operator for all tokens"),
+        &is_operators_root_correct,
+        &op_data.is_operator.get_bits_le()[0],
+    )?;
+
+    let is_operator_approved = multi_or(
+        cs.namespace(|| "is operator approved"),
+        &[is_token_operator, is_operator_for_all],
+    )?;
+    let is_signer_approved_operator = multi_and(
+        cs.namespace(|| "is signer the approved operator"),
+        &[is_operator_set, is_signer_operator, is_operator_approved],
+    )?;
+    multi_or(
+        cs.namespace(|| "is signer valid"),
+        &[is_signer_account, is_signer_approved_operator],
+    )
+}
+
+/// The third chunk of a transfer signed by an operator opens the account of the operator,
+/// which binds the operator id to the key hash the signature is checked against.
+fn is_operator_account_correct<E: RescueEngine + JubjubEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    chunk_number: &AllocatedNum<E>,
+    cur: &AllocatedOperationBranch<E>,
+    op_data: &AllocatedOperationData<E>,
+) -> Result<Boolean, SynthesisError> {
+    let is_chunk_third = Boolean::from(Expression::equals(
+        cs.namespace(|| "is_chunk_third"),
+        chunk_number,
+        Expression::u64::<CS>(2),
+    )?);
+    let is_operator_unset = Boolean::from(Expression::equals(
+        cs.namespace(|| "is operator pub key hash zero"),
+        &op_data.operator_pub_key_hash.get_number(),
+        Expression::constant::<CS>(E::Fr::zero()),
+    )?);
+    let is_operator_id = CircuitElement::equals(
+        cs.namespace(|| "is account the operator"),
+        &cur.account_id,
+        &op_data.operator_id,
+    )?;
+    let is_operator_pub_key_hash = CircuitElement::equals(
+        cs.namespace(|| "is account key the operator one"),
+        &cur.account.pub_key_hash,
+        &op_data.operator_pub_key_hash,
+    )?;
+    let is_operator_account = Boolean::and(
+        cs.namespace(|| "is operator account"),
+        &is_operator_id,
+        &is_operator_pub_key_hash,
+    )?;
+    multi_or(
+        cs.namespace(|| "is operator account correct"),
+        &[is_chunk_third.not(), is_operator_unset, is_operator_account],
+    )
+}
+
+/// The chunk `sponsor_chunk` of a sponsored op opens the account of the sponsor, which pays
+/// the fee if it has signed the op and its balance covers the fee.
+/// Returns whether the chunk is correct and whether the fee is charged from the account.
+fn is_sponsor_account_correct<E: RescueEngine + JubjubEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    chunk_number: &AllocatedNum<E>,
+    sponsor_chunk: usize,
+    cur: &AllocatedOperationBranch<E>,
+    op_data: &AllocatedOperationData<E>,
+    signer_key: &AllocatedSignerPubkey<E>,
+    is_sig_verified: &Boolean,
+    is_sponsorship_serialized_correct: &Boolean,
+    is_balance_geq_fee: &Boolean,
+    prev: &PreviousData<E>,
+) -> Result<(Boolean, Boolean), SynthesisError> {
+    let is_sponsor_chunk = Boolean::from(Expression::equals(
+        cs.namespace(|| "is_sponsor_chunk"),
+        chunk_number,
+        Expression::u64::<CS>(sponsor_chunk as u64),
+    )?);
+    let is_paid_by_sender = CircuitElement::equals(
+        cs.namespace(|| "is fee paid by the sender"),
+        &op_data.fee_payer,
+        &prev.lhs_account_id,
+    )?;
+    let is_sponsor_charged = Boolean::and(
+        cs.namespace(|| "is sponsor charged"),
+        &is_sponsor_chunk,
+        &is_paid_by_sender.not(),
+    )?;
+
+    let is_sponsor_id = CircuitElement::equals(
+        cs.namespace(|| "is account the sponsor"),
+        &cur.account_id,
+        &op_data.fee_payer,
+    )?;
+    let is_signer_sponsor = CircuitElement::equals(
+        cs.namespace(|| "is signer the sponsor"),
+        &signer_key.pubkey.get_hash(),
+        &cur.account.pub_key_hash,
+    )?;
+    let is_sponsorship_valid = multi_and(
+        cs.namespace(|| "is sponsorship valid"),
+        &[
+            is_sponsor_id,
+            is_signer_sponsor,
+            is_sig_verified.clone(),
+            is_sponsorship_serialized_correct.clone(),
+            is_balance_geq_fee.clone(),
+        ],
+    )?;
+    let is_chunk_correct = multi_or(
+        cs.namespace(|| "is sponsor account correct"),
+        &[is_sponsor_charged.not(), is_sponsorship_valid],
+    )?;
+    Ok((is_chunk_correct, is_sponsor_charged))
+}
+
 fn multi_or<E: JubjubEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
     x: &[Boolean],
@@ -2022,54 +4228,6 @@ fn multi_or<E: JubjubEngine, CS: ConstraintSystem<E>>(
     Ok(result)
 }
 
-//TODO: we can use fees: &[Expression<E>] if needed, though no real need
-fn calculate_root_from_full_representation_fees<E: RescueEngine, CS: ConstraintSystem<E>>(
-    mut cs: CS,
-    fees: &[AllocatedNum<E>],
-    params: &E::Params,
-) -> Result<AllocatedNum<E>, SynthesisError> {
-    //assert_eq!(fees.len(), params::total_tokens());
-    let mut fee_hashes = vec![];
-    for (index, fee) in fees.iter().cloned().enumerate() {
-        let cs = &mut cs.namespace(|| format!("fee hashing index number {}", index));
-
-        fee.limit_number_of_bits(
-            cs.namespace(|| "ensure that fees are short enough"),
-            params::BALANCE_BIT_WIDTH,
-        )?;
-
-        let mut sponge_output =
-            rescue::rescue_hash(cs.namespace(|| "hash the fee leaf content"), &[fee], params)?;
-
-        assert_eq!(sponge_output.len(), 1);
-
-        let tmp = sponge_output.pop().expect("must get a single element");
-
-        fee_hashes.push(tmp);
-    }
-    let mut hash_vec = fee_hashes;
-
-    for i in 0..params::token_tree_depth() {
-        let cs = &mut cs.namespace(|| format!("merkle tree level index number {}", i));
-        let chunks = hash_vec.chunks(2);
-        let mut new_hashes = vec![];
-        for (chunk_number, x) in chunks.enumerate() {
-            let cs = &mut cs.namespace(|| format!("chunk number {}", chunk_number));
-
-            let mut sponge_output = rescue::rescue_hash(cs, &x, params)?;
-
-            assert_eq!(sponge_output.len(), 1);
-
-            let tmp = sponge_output.pop().expect("must get a single element");
-
-            new_hashes.push(tmp);
-        }
-        hash_vec = new_hashes;
-    }
-    assert_eq!(hash_vec.len(), 1);
-    Ok(hash_vec[0].clone())
-}
-
 fn generate_maxchunk_polynomial<E: JubjubEngine>() -> Vec<E::Fr> {
     use crypto_exports::franklin_crypto::interpolation::interpolate;
 
@@ -2089,6 +4247,18 @@ fn generate_maxchunk_polynomial<E: JubjubEngine>() -> Vec<E::Fr> {
     points.push(get_xy(TransferToNewOp::OP_CODE, TransferToNewOp::CHUNKS));
     points.push(get_xy(FullExitOp::OP_CODE, FullExitOp::CHUNKS));
     points.push(get_xy(ChangePubKeyOp::OP_CODE, ChangePubKeyOp::CHUNKS));
+    points.push(get_xy(DepositEthOp::OP_CODE, DepositEthOp::CHUNKS));
+    points.push(get_xy(SwapOp::OP_CODE, SwapOp::CHUNKS));
+    points.push(get_xy(MintOp::OP_CODE, MintOp::CHUNKS));
+    points.push(get_xy(BurnOp::OP_CODE, BurnOp::CHUNKS));
+    points.push(get_xy(ApproveOp::OP_CODE, ApproveOp::CHUNKS));
+    points.push(get_xy(ApproveForAllOp::OP_CODE, ApproveForAllOp::CHUNKS));
+    points.push(get_xy(ForcedWithdrawOp::OP_CODE, ForcedWithdrawOp::CHUNKS));
+    // only the shortest batch transfer is on the polynomial, see `verify_correct_chunking`
+    points.push(get_xy(
+        BatchTransferOp::OP_CODE,
+        BatchTransferOp::chunks_for(1),
+    ));
 
     let interpolation = interpolate::<E>(&points[..]).expect("must interpolate");
     assert_eq!(interpolation.len(), DIFFERENT_TRANSACTIONS_TYPE_NUMBER);
@@ -2096,6 +4266,18 @@ fn generate_maxchunk_polynomial<E: JubjubEngine>() -> Vec<E::Fr> {
     interpolation
 }
 
+/// Root of the token subtree without tokens, the same one `CircuitAccount::default` has.
+fn empty_token_subtree_root<E: RescueEngine>(rescue_params: &E::Params) -> E::Fr {
+    use crypto_exports::franklin_crypto::rescue::rescue_hash;
+
+    let empty_leaf = multipack::compute_multipacking::<E>(&Token::<E>::default().get_bits_le());
+    let mut root = rescue_hash::<E>(rescue_params, &empty_leaf)[0];
+    for _ in 0..params::token_tree_depth() {
+        root = rescue_hash::<E>(rescue_params, &[root, root])[0];
+    }
+    root
+}
+
 fn no_nonce_overflow<E: JubjubEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
     nonce: &AllocatedNum<E>,
@@ -5,7 +5,7 @@ use crypto_exports::franklin_crypto::{
         pairing::ff::{Field, PrimeField, PrimeFieldRepr},
         Circuit, ConstraintSystem, SynthesisError,
     },
-    circuit::{boolean::Boolean, num::AllocatedNum, sha256, Assignment},
+    circuit::{boolean::Boolean, expression::Expression, num::AllocatedNum, sha256, Assignment},
     rescue::RescueEngine,
 };
 // Workspace deps
@@ -14,10 +14,10 @@ use models::{
         utils::{append_be_fixed_width, be_bit_vector_into_bytes},
         CircuitAccountTree,
     },
-    node::{AccountId, Engine, Fr, TokenId},
+    node::{AccountId, Engine, Fr, Nft},
     params::{
-        ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, BALANCE_BIT_WIDTH, FR_BIT_WIDTH_PADDED,
-        SUBTREE_HASH_WIDTH_PADDED, TOKENID_BIT_WIDTH,
+        ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, COLLECTIONID_BIT_WIDTH, CONTENT_HASH_LIMB_BIT_WIDTH,
+        FR_BIT_WIDTH_PADDED, SUBTREE_HASH_WIDTH_PADDED, TOKENID_LIMB_BIT_WIDTH,
     },
 };
 // Local deps
@@ -26,22 +26,34 @@ use crate::{
     circuit::check_account_data,
     element::CircuitElement,
     operation::{OperationBranch, OperationBranchWitness},
-    witness::utils::{apply_leaf_operation, get_audits},
+    witness::utils::{
+        apply_leaf_operation, content_hash_limbs, get_audits, get_token_content_hash,
+        get_token_slot, get_token_witness, nft_limbs,
+    },
 };
 
+/// Proves that an account holds a given NFT in the state with the given root.
+///
+/// Used in the exodus mode, when the only way to get a token out of the rollup
+/// is to prove its ownership at the last verified state.
 #[derive(Clone)]
 pub struct ZksyncExitCircuit<'a, E: RescueEngine> {
     pub params: &'a E::Params,
-    /// The old root of the tree
+    /// Hash of the root, account id, owner address, collection id and token id
     pub pub_data_commitment: Option<E::Fr>,
     pub root_hash: Option<E::Fr>,
     pub account_audit_data: OperationBranch<E>,
+    pub collection_id: Option<E::Fr>,
+    pub token_id_hi: Option<E::Fr>,
+    pub token_id_lo: Option<E::Fr>,
+    /// Content hash of the token is not a part of the commitment, it is only needed to open the leaf
+    pub content_hash_hi: Option<E::Fr>,
+    pub content_hash_lo: Option<E::Fr>,
 }
 
 // Implementation of our circuit:
 impl<'a, E: RescueEngine> Circuit<E> for ZksyncExitCircuit<'a, E> {
     fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
-        /*
         // this is only public input to our circuit
         let public_data_commitment =
             AllocatedNum::alloc(cs.namespace(|| "public_data_commitment"), || {
@@ -56,10 +68,60 @@ impl<'a, E: RescueEngine> Circuit<E> for ZksyncExitCircuit<'a, E> {
             cs.namespace(|| "lhs"),
             &self.account_audit_data,
         )?;
+
+        let collection_id = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "collection_id"),
+            || self.collection_id.grab(),
+            COLLECTIONID_BIT_WIDTH,
+        )?;
+        let token_id_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "token_id_hi"),
+            || self.token_id_hi.grab(),
+            TOKENID_LIMB_BIT_WIDTH,
+        )?;
+        let token_id_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "token_id_lo"),
+            || self.token_id_lo.grab(),
+            TOKENID_LIMB_BIT_WIDTH,
+        )?;
+        let content_hash_hi = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "content_hash_hi"),
+            || self.content_hash_hi.grab(),
+            CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+        let content_hash_lo = CircuitElement::from_fe_with_known_length(
+            cs.namespace(|| "content_hash_lo"),
+            || self.content_hash_lo.grab(),
+            CONTENT_HASH_LIMB_BIT_WIDTH,
+        )?;
+
+        // collection 0 is never registered, it only marks empty token leaves
+        let is_empty_leaf = Expression::equals(
+            cs.namespace(|| "is_empty_leaf"),
+            &collection_id.get_number(),
+            Expression::constant::<CS>(E::Fr::zero()),
+        )?;
+        Boolean::enforce_equal(
+            cs.namespace(|| "token leaf is not empty"),
+            &Boolean::from(is_empty_leaf),
+            &Boolean::constant(false),
+        )?;
+
+        // same layout as `models::circuit::account::Token`
+        let mut token_leaf = token_id_lo.get_bits_le();
+        token_leaf.extend(token_id_hi.get_bits_le());
+        token_leaf.extend(collection_id.get_bits_le());
+        token_leaf.extend(content_hash_lo.get_bits_le());
+        token_leaf.extend(content_hash_hi.get_bits_le());
+        // approved operator doesn't matter for the exit, it is taken from the opened leaf
+        token_leaf.extend(branch.token.approved.get_bits_le());
+
         // calculate root for given account data
         let (state_root, _, _) = check_account_data(
             cs.namespace(|| "calculate account root"),
             &branch,
+            &token_leaf,
+            &branch.token_slot.get_bits_le(),
             self.params,
         )?;
 
@@ -77,8 +139,9 @@ impl<'a, E: RescueEngine> Circuit<E> for ZksyncExitCircuit<'a, E> {
             initial_hash_data.extend(root_hash_ce.into_padded_be_bits(FR_BIT_WIDTH_PADDED));
             initial_hash_data.extend(branch.account_id.get_bits_be());
             initial_hash_data.extend(branch.account.address.get_bits_be());
-            initial_hash_data.extend(branch.token.get_bits_be());
-            initial_hash_data.extend(branch.balance.get_bits_be());
+            initial_hash_data.extend(collection_id.get_bits_be());
+            initial_hash_data.extend(token_id_hi.get_bits_be());
+            initial_hash_data.extend(token_id_lo.get_bits_be());
 
             let mut hash_block =
                 sha256::sha256(cs.namespace(|| "sha256 of pub data"), &initial_hash_data)?;
@@ -96,28 +159,29 @@ impl<'a, E: RescueEngine> Circuit<E> for ZksyncExitCircuit<'a, E> {
                 |lc| lc + final_hash.get_variable(),
             );
         }
-        Ok(())*/
-        unimplemented!()
+        Ok(())
     }
 }
 
+/// Creates the exit circuit proving that account `account_id` holds `token` in the given tree.
+///
+/// Panics if the account does not hold the token.
 pub fn create_exit_circuit_with_public_input(
     account_tree: &mut CircuitAccountTree,
     account_id: AccountId,
-    token_id: TokenId,
+    token: &Nft,
 ) -> ZksyncExitCircuit<'static, Engine> {
-    /*let account_address_fe = Fr::from_str(&account_id.to_string()).unwrap();
-    let token_id_fe = Fr::from_str(&token_id.to_string()).unwrap();
+    let account_address_fe = Fr::from_str(&account_id.to_string()).unwrap();
+    let token_slot = get_token_slot(account_tree, account_id, token);
+    let token_slot_fe = Fr::from_str(&token_slot.to_string()).unwrap();
+    let (collection_id, token_id_hi, token_id_lo) = nft_limbs(token);
+    let content_hash = get_token_content_hash(account_tree, account_id, token_slot);
+    let (content_hash_hi, content_hash_lo) = content_hash_limbs(&content_hash);
     let root_hash = account_tree.root_hash();
-    let (account_witness, _, balance, _) = apply_leaf_operation(
-        account_tree,
-        account_id,
-        u32::from(token_id),
-        |_| {},
-        |_| {},
-    );
-    let (audit_path, audit_balance_path) =
-        get_audits(account_tree, account_id, u32::from(token_id));
+    let (account_witness, _, _, _) =
+        apply_leaf_operation(account_tree, account_id, None, None, |_| {});
+    let (audit_path, audit_token_path) = get_audits(account_tree, account_id, token_slot);
+    let token_witness = get_token_witness(account_tree, account_id, token_slot);
 
     let mut pubdata_commitment = Vec::new();
     append_be_fixed_width(
@@ -135,8 +199,21 @@ pub fn create_exit_circuit_with_public_input(
         .expect("account should be in the tree")
         .address;
     append_be_fixed_width(&mut pubdata_commitment, &account_address, ADDRESS_WIDTH);
-    append_be_fixed_width(&mut pubdata_commitment, &token_id_fe, TOKEN_BIT_WIDTH);
-    append_be_fixed_width(&mut pubdata_commitment, &balance, BALANCE_BIT_WIDTH);
+    append_be_fixed_width(
+        &mut pubdata_commitment,
+        &collection_id,
+        COLLECTIONID_BIT_WIDTH,
+    );
+    append_be_fixed_width(
+        &mut pubdata_commitment,
+        &token_id_hi,
+        TOKENID_LIMB_BIT_WIDTH,
+    );
+    append_be_fixed_width(
+        &mut pubdata_commitment,
+        &token_id_lo,
+        TOKENID_LIMB_BIT_WIDTH,
+    );
 
     let mut h = Sha256::new();
 
@@ -158,55 +235,89 @@ pub fn create_exit_circuit_with_public_input(
         root_hash: Some(root_hash),
         account_audit_data: OperationBranch {
             address: Some(account_address_fe),
-            token: Some(token_id_fe),
+            token_slot: Some(token_slot_fe),
             witness: OperationBranchWitness {
                 account_witness,
                 account_path: audit_path,
-                balance_value: Some(balance),
-                balance_subtree_path: audit_balance_path,
+                token_witness,
+                token_subtree_path: audit_token_path,
             },
         },
-    }*/
-    unimplemented!()
+        collection_id: Some(collection_id),
+        token_id_hi: Some(token_id_hi),
+        token_id_lo: Some(token_id_lo),
+        content_hash_hi: Some(content_hash_hi),
+        content_hash_lo: Some(content_hash_lo),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use bigdecimal::BigDecimal;
     use crypto_exports::franklin_crypto::circuit::test::TestConstraintSystem;
     use models::circuit::account::CircuitAccount;
     use models::circuit::CircuitAccountTree;
-    use models::node::Account;
+    use models::node::{Account, ContentHash, TokenId};
 
-    #[test]
-    #[ignore]
-    fn test_zksync_exit_circuit_correct_proof() {
-        let test_account_id = 0xde;
-        let token_id = 0x1d;
+    fn test_tree(account_id: AccountId, tokens: &[Nft]) -> CircuitAccountTree {
         let mut test_account = Account::default_with_address(
             &"abababababababababababababababababababab".parse().unwrap(),
         );
-        //test_account.set_balance(token_id, BigDecimal::from(0xbeef));
+        for token in tokens {
+            test_account.add_token(*token, ContentHash::repeat_byte(0x11));
+        }
         test_account.nonce = 0xbabe;
 
         let mut circuit_account_tree =
             CircuitAccountTree::new(models::params::account_tree_depth());
-        circuit_account_tree.insert(test_account_id, CircuitAccount::from(test_account));
+        circuit_account_tree.insert(account_id, CircuitAccount::from(test_account));
+        circuit_account_tree
+    }
+
+    #[test]
+    fn test_zksync_exit_circuit_correct_proof() {
+        let test_account_id = 0xde;
+        let tokens = [
+            Nft::new(1, TokenId::from(0x1d)),
+            Nft::new(2, TokenId::max_value()),
+        ];
+        let mut circuit_account_tree = test_tree(test_account_id, &tokens);
 
-        let zksync_exit_circuit = create_exit_circuit_with_public_input(
+        for token in &tokens {
+            let zksync_exit_circuit = create_exit_circuit_with_public_input(
+                &mut circuit_account_tree,
+                test_account_id,
+                token,
+            );
+
+            let mut cs = TestConstraintSystem::<Engine>::new();
+            zksync_exit_circuit.synthesize(&mut cs).unwrap();
+
+            println!("unconstrained: {}", cs.find_unconstrained());
+            println!("number of constraints {}", cs.num_constraints());
+            if let Some(err) = cs.which_is_unsatisfied() {
+                panic!("ERROR satisfying in {}", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zksync_exit_circuit_wrong_token() {
+        let test_account_id = 0xde;
+        let token = Nft::new(1, TokenId::from(0x1d));
+        let mut circuit_account_tree = test_tree(test_account_id, &[token]);
+
+        let mut zksync_exit_circuit = create_exit_circuit_with_public_input(
             &mut circuit_account_tree,
             test_account_id,
-            token_id,
+            &token,
         );
+        // claim a token from the same collection the account does not hold
+        zksync_exit_circuit.token_id_lo = Some(Fr::from_str("30").unwrap());
 
         let mut cs = TestConstraintSystem::<Engine>::new();
         zksync_exit_circuit.synthesize(&mut cs).unwrap();
 
-        println!("unconstrained: {}", cs.find_unconstrained());
-        println!("number of constraints {}", cs.num_constraints());
-        if let Some(err) = cs.which_is_unsatisfied() {
-            panic!("ERROR satisfying in {}", err);
-        }
+        assert!(cs.which_is_unsatisfied().is_some());
     }
 }
@@ -6,7 +6,7 @@ use crypto_exports::franklin_crypto::{
 };
 use serde::{Deserialize, Serialize};
 // Workspace
-use crate::account::AccountWitness;
+use crate::account::{AccountWitness, TokenWitness};
 
 #[derive(Clone, Debug)]
 pub struct OperationBranchWitness<E: RescueEngine> {
@@ -14,12 +14,16 @@ pub struct OperationBranchWitness<E: RescueEngine> {
     pub account_path: Vec<Option<E::Fr>>,
 
     //pub balance_value: Option<E::Fr>,
+    /// Token subtree leaf in the slot of the branch.
+    pub token_witness: TokenWitness<E>,
     pub token_subtree_path: Vec<Option<E::Fr>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct OperationBranch<E: RescueEngine> {
     pub address: Option<E::Fr>,
+    /// Slot of the token subtree leaf touched by the operation.
+    pub token_slot: Option<E::Fr>,
 
     pub witness: OperationBranchWitness<E>,
 }
@@ -45,12 +49,50 @@ pub struct OperationArguments<E: RescueEngine> {
     //pub a: Option<E::Fr>,
     //pub b: Option<E::Fr>,
     //pub amount_packed: Option<E::Fr>,
-    //pub full_amount: Option<E::Fr>,
-    pub token_id: Option<E::Fr>,
+    /// Deposited ETH amount in wei.
+    pub full_amount: Option<E::Fr>,
+    pub collection_id: Option<E::Fr>,
+    pub token_id_hi: Option<E::Fr>,
+    pub token_id_lo: Option<E::Fr>,
+    pub content_hash_hi: Option<E::Fr>,
+    pub content_hash_lo: Option<E::Fr>,
+    /// Key hash of the operator approved by the account who signed the operation instead
+    /// of the account, zero when the account signs by itself.
+    pub operator_pub_key_hash: Option<E::Fr>,
+    /// Account of the operator who signed the operation or whom the operation approves,
+    /// zero if there is none.
+    pub operator_id: Option<E::Fr>,
+    /// Operator leaf in the operators tree of the account before the operation,
+    /// one if the operator is approved for all the account tokens, with the audit path to it.
+    pub is_operator: Option<E::Fr>,
+    pub operator_path: Vec<Option<E::Fr>>,
+    /// Approval for all the account tokens given to the operator by the operation.
+    pub approved: Option<E::Fr>,
+    /// Bounds of the tx validity window, zero and `u32::MAX` for an unbounded window.
+    pub valid_from: Option<E::Fr>,
+    pub valid_until: Option<E::Fr>,
+    /// Account paying the fee of the operation, either its sponsor or the initiator account.
+    pub fee_payer: Option<E::Fr>,
     pub fee: Option<E::Fr>,
     pub new_pub_key_hash: Option<E::Fr>,
     pub eth_address: Option<E::Fr>,
     pub pub_nonce: Option<E::Fr>,
+    /// Number of tokens on the token list of the operation and the root of its list tree.
+    pub tokens_count: Option<E::Fr>,
+    pub tokens_root: Option<E::Fr>,
+    /// Position of the token of the chunk on the token list, with the audit path to it.
+    pub token_index: Option<E::Fr>,
+    pub token_list_path: Vec<Option<E::Fr>>,
+    /// Token, fee and list tree root of the order of the second account of a swap,
+    /// the order of the first account is described by the fields above.
+    pub second_collection_id: Option<E::Fr>,
+    pub second_token_id_hi: Option<E::Fr>,
+    pub second_token_id_lo: Option<E::Fr>,
+    pub second_content_hash_hi: Option<E::Fr>,
+    pub second_content_hash_lo: Option<E::Fr>,
+    pub second_fee: Option<E::Fr>,
+    pub second_tokens_count: Option<E::Fr>,
+    pub second_tokens_root: Option<E::Fr>,
 }
 
 #[derive(Clone)]
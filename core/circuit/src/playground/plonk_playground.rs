@@ -27,8 +27,10 @@ fn test_transpile_deposit_franklin_existing_account() {
             token: 0,
             amount: BigDecimal::from(1),
             to: deposit_to_account_address,
+            content_hash: Default::default(),
         },
         account_id: deposit_to_account_id,
+        success: true,
     };
 
     plasma_state.apply_deposit_op(&deposit_op);
@@ -108,8 +110,10 @@ fn test_new_transpile_deposit_franklin_existing_account_validate_only() {
             token: 0,
             amount: BigDecimal::from(1),
             to: deposit_to_account_address,
+            content_hash: Default::default(),
         },
         account_id: deposit_to_account_id,
+        success: true,
     };
 
     for _ in 0..NUM_DEPOSITS {
@@ -217,8 +221,10 @@ fn test_new_transpile_deposit_franklin_existing_account() {
             token: 0,
             amount: BigDecimal::from(1),
             to: deposit_to_account_address,
+            content_hash: Default::default(),
         },
         account_id: deposit_to_account_id,
+        success: true,
     };
 
     for _ in 0..NUM_DEPOSITS {
@@ -392,8 +398,10 @@ fn test_fma_transpile_deposit_franklin_existing_account() {
             token: 0,
             amount: BigDecimal::from(1),
             to: deposit_to_account_address,
+            content_hash: Default::default(),
         },
         account_id: deposit_to_account_id,
+        success: true,
     };
 
     for _ in 0..NUM_DEPOSITS {
@@ -0,0 +1,309 @@
+// External deps
+use crypto_exports::franklin_crypto::{
+    bellman::pairing::{
+        bn256::{Bn256, Fr},
+        ff::{Field, PrimeField},
+    },
+    rescue::RescueEngine,
+};
+// Workspace deps
+use models::{
+    circuit::{
+        account::CircuitAccountTree,
+        utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
+    },
+    node::{operations::ApproveOp, Nft, TokenSlot},
+    params as franklin_constants,
+    primitives::{big_decimal_to_u128, convert_to_float},
+};
+// Local deps
+use crate::{
+    operation::{Operation, OperationArguments, OperationBranch, OperationBranchWitness},
+    witness::{
+        utils::{
+            append_nft_be, apply_leaf_operation, content_hash_limbs, get_audits,
+            get_current_branch, get_token_content_hash, get_token_slot, get_token_witness,
+            nft_limbs, SigDataInput,
+        },
+        Witness,
+    },
+};
+
+pub struct ApproveData {
+    pub fee: u128,
+    pub token: Nft,
+    pub token_slot: TokenSlot,
+    pub account_address: u32,
+    /// Revoked approval is published as the approval of the account itself.
+    pub operator_address: u32,
+    /// Ethereum address of the operator the account signs for, zero for the revoke.
+    pub operator_eth_address: Fr,
+}
+
+pub struct ApproveWitness<E: RescueEngine> {
+    pub before: OperationBranch<E>,
+    pub after: OperationBranch<E>,
+    /// Branch opened by the second chunk, the one of the operator account
+    /// or of the account itself if the approval is revoked.
+    pub operator_after: OperationBranch<E>,
+    pub operator_address: Option<E::Fr>,
+    pub args: OperationArguments<E>,
+    pub before_root: Option<E::Fr>,
+    pub after_root: Option<E::Fr>,
+    pub tx_type: Option<E::Fr>,
+}
+
+impl Witness for ApproveWitness<Bn256> {
+    type OperationType = ApproveOp;
+    type CalculateOpsInput = SigDataInput;
+
+    fn apply_tx(tree: &mut CircuitAccountTree, approve: &ApproveOp) -> Self {
+        let approve_data = ApproveData {
+            fee: big_decimal_to_u128(&approve.tx.fee),
+            token: approve.tx.nft(),
+            token_slot: get_token_slot(tree, approve.account_id, &approve.tx.nft()),
+            account_address: approve.account_id,
+            operator_address: approve.operator_id.unwrap_or(approve.account_id),
+            operator_eth_address: eth_address_to_fr(&approve.tx.operator),
+        };
+        Self::apply_data(tree, &approve_data)
+    }
+
+    fn get_pubdata(&self) -> Vec<bool> {
+        let mut pubdata_bits = vec![];
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.tx_type.unwrap(),
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.before.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.operator_address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_nft_be(&mut pubdata_bits, &self.args);
+
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.fee.unwrap(),
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+        );
+        pubdata_bits.resize(
+            ApproveOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH,
+            false,
+        );
+        pubdata_bits
+    }
+
+    fn calculate_operations(&self, input: SigDataInput) -> Vec<Operation<Bn256>> {
+        let pubdata_chunks: Vec<_> = self
+            .get_pubdata()
+            .chunks(64)
+            .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
+            .collect();
+
+        let operation_zero = Operation {
+            new_root: self.after_root,
+            tx_type: self.tx_type,
+            chunk: Some(Fr::from_str("0").unwrap()),
+            pubdata_chunk: Some(pubdata_chunks[0]),
+            first_sig_msg: Some(input.first_sig_msg),
+            second_sig_msg: Some(input.second_sig_msg),
+            third_sig_msg: Some(input.third_sig_msg),
+            signature_data: input.signature.clone(),
+            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
+            args: self.args.clone(),
+            lhs: self.before.clone(),
+            rhs: self.before.clone(),
+        };
+
+        let mut operations = vec![operation_zero];
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(1) {
+            operations.push(Operation {
+                new_root: self.after_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(input.first_sig_msg),
+                second_sig_msg: Some(input.second_sig_msg),
+                third_sig_msg: Some(input.third_sig_msg),
+                signature_data: input.signature.clone(),
+                signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
+                args: self.args.clone(),
+                lhs: self.after.clone(),
+                rhs: if i == 1 {
+                    self.operator_after.clone()
+                } else {
+                    self.after.clone()
+                },
+            });
+        }
+        operations
+    }
+}
+
+impl<E: RescueEngine> ApproveWitness<E> {
+    pub fn get_sig_bits(&self) -> Vec<bool> {
+        let mut sig_bits = vec![];
+        append_be_fixed_width(
+            &mut sig_bits,
+            &Fr::from_str("12").unwrap(), //Corresponding tx_type
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.before.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.before.witness.account_witness.address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.eth_address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        append_nft_be(&mut sig_bits, &self.args);
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.fee.unwrap(),
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.before.witness.account_witness.nonce.unwrap(),
+            franklin_constants::NONCE_BIT_WIDTH,
+        );
+        sig_bits
+    }
+}
+
+impl ApproveWitness<Bn256> {
+    fn apply_data(tree: &mut CircuitAccountTree, approve: &ApproveData) -> Self {
+        //preparing data and base witness
+        let before_root = tree.root_hash();
+        debug!("Initial root = {}", before_root);
+        let (audit_path_before, audit_token_path_before) =
+            get_audits(tree, approve.account_address, approve.token_slot);
+        let token_before = get_token_witness(tree, approve.account_address, approve.token_slot);
+
+        let capacity = tree.capacity();
+        assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
+        let account_address_fe = Fr::from_str(&approve.account_address.to_string()).unwrap();
+        let operator_address_fe = Fr::from_str(&approve.operator_address.to_string()).unwrap();
+        let token_slot_fe = Fr::from_str(&approve.token_slot.to_string()).unwrap();
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&approve.token);
+        let content_hash =
+            get_token_content_hash(tree, approve.account_address, approve.token_slot);
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&content_hash);
+
+        let fee_as_field_element = Fr::from_str(&approve.fee.to_string()).unwrap();
+
+        let fee_bits = convert_to_float(
+            approve.fee,
+            franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+            10,
+        )
+        .unwrap();
+
+        let fee_encoded: Fr = le_bit_vector_into_field_element(&fee_bits);
+
+        // revoked approval leaves zero in the token leaf
+        let approved_fe = if approve.operator_address == approve.account_address {
+            Fr::zero()
+        } else {
+            operator_address_fe
+        };
+
+        //applying approve
+
+        let (account_witness_before, account_witness_after, _, _) =
+            apply_leaf_operation(tree, approve.account_address, None, None, |acc| {
+                acc.balance.sub_assign(&fee_as_field_element);
+                acc.nonce.add_assign(&Fr::from_str("1").unwrap());
+                let mut token = acc
+                    .subtree
+                    .get(approve.token_slot)
+                    .cloned()
+                    .expect("approved token is not in the account");
+                token.approved = approved_fe;
+                acc.subtree.insert(approve.token_slot, token);
+            });
+
+        let after_root = tree.root_hash();
+        debug!("After root = {}", after_root);
+        let (audit_path_after, audit_token_path_after) =
+            get_audits(tree, approve.account_address, approve.token_slot);
+        let token_after = get_token_witness(tree, approve.account_address, approve.token_slot);
+        let operator_after = get_current_branch(tree, approve.operator_address, 0);
+
+        ApproveWitness {
+            before: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_before,
+                    account_path: audit_path_before,
+                    token_witness: token_before,
+                    token_subtree_path: audit_token_path_before,
+                },
+            },
+            after: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_after,
+                    account_path: audit_path_after,
+                    token_witness: token_after,
+                    token_subtree_path: audit_token_path_after,
+                },
+            },
+            operator_after,
+            operator_address: Some(operator_address_fe),
+            args: OperationArguments {
+                eth_address: Some(approve.operator_eth_address),
+                fee: Some(fee_encoded),
+                full_amount: Some(Fr::zero()),
+                pub_nonce: Some(Fr::zero()),
+                new_pub_key_hash: Some(Fr::zero()),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(operator_address_fe),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::zero()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
+            },
+            before_root: Some(before_root),
+            after_root: Some(after_root),
+            tx_type: Some(Fr::from_str("12").unwrap()),
+        }
+    }
+}
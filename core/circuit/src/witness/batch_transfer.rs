@@ -0,0 +1,298 @@
+// External deps
+use crypto_exports::franklin_crypto::{
+    bellman::pairing::{
+        bn256::{Bn256, Fr},
+        ff::{Field, PrimeField},
+    },
+    rescue::RescueEngine,
+};
+// Workspace deps
+use models::{
+    circuit::{
+        account::CircuitAccountTree,
+        utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
+    },
+    node::{operations::BatchTransferOp, tx::nfts_list_tree, AccountId, ContentHash, Nft},
+    params as franklin_constants,
+    primitives::{big_decimal_to_u128, convert_to_float},
+};
+// Local deps
+use crate::{
+    operation::{Operation, OperationArguments, OperationBranch},
+    witness::{
+        utils::{
+            append_nft_be, apply_leaf_operation, content_hash_limbs, get_current_branch,
+            get_free_token_slot, get_token_content_hash, get_token_slot, nft_limbs, SigDataInput,
+        },
+        Witness,
+    },
+};
+
+#[derive(Debug)]
+pub struct BatchTransferData {
+    pub fee: u128,
+    pub tokens: Vec<Nft>,
+    pub from_account_address: AccountId,
+    pub to_account_address: AccountId,
+    pub to_address: Fr,
+}
+
+/// Single chunk of the batch transfer, with the branch it changes.
+pub struct BatchTransferStepWitness<E: RescueEngine> {
+    pub lhs: OperationBranch<E>,
+    pub rhs: OperationBranch<E>,
+    pub args: OperationArguments<E>,
+    pub after_root: Option<E::Fr>,
+}
+
+/// Batch transfer takes `BatchTransferOp::HEADER_CHUNKS` chunks of the header and
+/// `BatchTransferOp::TOKEN_CHUNKS` chunks per token:
+/// - the first chunk charges the fee of the sender and increments its nonce;
+/// - the first chunk of a token block removes the token from the sender;
+/// - the second chunk of a token block adds the token to the recipient.
+///
+/// The other chunks change nothing.
+pub struct BatchTransferWitness<E: RescueEngine> {
+    /// One step per chunk, in the order of the chunks
+    pub steps: Vec<BatchTransferStepWitness<E>>,
+    pub before_root: Option<E::Fr>,
+    pub tx_type: Option<E::Fr>,
+}
+
+impl Witness for BatchTransferWitness<Bn256> {
+    type OperationType = BatchTransferOp;
+    type CalculateOpsInput = SigDataInput;
+
+    fn apply_tx(tree: &mut CircuitAccountTree, batch_transfer: &BatchTransferOp) -> Self {
+        let batch_transfer_data = BatchTransferData {
+            fee: big_decimal_to_u128(&batch_transfer.tx.fee),
+            tokens: batch_transfer.tx.tokens.clone(),
+            from_account_address: batch_transfer.from,
+            to_account_address: batch_transfer.to,
+            to_address: eth_address_to_fr(&batch_transfer.tx.to),
+        };
+        Self::apply_data(tree, &batch_transfer_data)
+    }
+
+    fn get_pubdata(&self) -> Vec<bool> {
+        let header = &self.steps[0];
+
+        let mut pubdata_bits = vec![];
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.tx_type.unwrap(),
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &header.lhs.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &header.rhs.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &header.args.eth_address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &header.args.fee.unwrap(),
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &header.args.tokens_count.unwrap(),
+            franklin_constants::TOKENS_COUNT_BIT_WIDTH,
+        );
+        pubdata_bits.resize(
+            BatchTransferOp::HEADER_CHUNKS * franklin_constants::CHUNK_BIT_WIDTH,
+            false,
+        );
+        for token_block in
+            self.steps[BatchTransferOp::HEADER_CHUNKS..].chunks(BatchTransferOp::TOKEN_CHUNKS)
+        {
+            let token_offset = pubdata_bits.len();
+            append_nft_be(&mut pubdata_bits, &token_block[0].args);
+            pubdata_bits.resize(
+                token_offset + BatchTransferOp::TOKEN_CHUNKS * franklin_constants::CHUNK_BIT_WIDTH,
+                false,
+            );
+        }
+        pubdata_bits
+    }
+
+    fn calculate_operations(&self, input: SigDataInput) -> Vec<Operation<Bn256>> {
+        let pubdata_chunks: Vec<_> = self
+            .get_pubdata()
+            .chunks(64)
+            .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
+            .collect();
+
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| Operation {
+                new_root: step.after_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunks[i]),
+                first_sig_msg: Some(input.first_sig_msg),
+                second_sig_msg: Some(input.second_sig_msg),
+                third_sig_msg: Some(input.third_sig_msg),
+                signature_data: input.signature.clone(),
+                signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
+                args: step.args.clone(),
+                lhs: step.lhs.clone(),
+                rhs: step.rhs.clone(),
+            })
+            .collect()
+    }
+}
+
+impl BatchTransferWitness<Bn256> {
+    fn apply_data(tree: &mut CircuitAccountTree, batch_transfer: &BatchTransferData) -> Self {
+        let capacity = tree.capacity();
+        assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
+
+        let from = batch_transfer.from_account_address;
+        let to = batch_transfer.to_account_address;
+        let fee_as_field_element = Fr::from_str(&batch_transfer.fee.to_string()).unwrap();
+        let fee_bits = convert_to_float(
+            batch_transfer.fee,
+            franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+            10,
+        )
+        .unwrap();
+        let fee_encoded: Fr = le_bit_vector_into_field_element(&fee_bits);
+
+        // every chunk carries the list tree root and the audit path to its token
+        let tokens_list_tree = nfts_list_tree(&batch_transfer.tokens);
+        let args = |token_index: usize, content_hash: &ContentHash| {
+            let (collection_id, token_id_hi, token_id_lo) =
+                nft_limbs(&batch_transfer.tokens[token_index]);
+            let (content_hash_hi, content_hash_lo) = content_hash_limbs(content_hash);
+            OperationArguments {
+                eth_address: Some(batch_transfer.to_address),
+                fee: Some(fee_encoded),
+                full_amount: Some(Fr::zero()),
+                pub_nonce: Some(Fr::zero()),
+                new_pub_key_hash: Some(Fr::zero()),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::from_str(&from.to_string()).unwrap()),
+                tokens_count: Some(Fr::from_str(&batch_transfer.tokens.len().to_string()).unwrap()),
+                tokens_root: Some(tokens_list_tree.root_hash()),
+                token_index: Some(Fr::from_str(&token_index.to_string()).unwrap()),
+                token_list_path: tokens_list_tree
+                    .merkle_path(token_index as u32)
+                    .into_iter()
+                    .map(|e| Some(e.0))
+                    .collect(),
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
+            }
+        };
+
+        let before_root = tree.root_hash();
+        debug!("Batch transfer initial root = {}", before_root);
+
+        let mut steps =
+            Vec::with_capacity(BatchTransferOp::chunks_for(batch_transfer.tokens.len()));
+
+        // header, the fee is charged and the nonce is incremented in its first chunk
+        let slot = get_token_slot(tree, from, &batch_transfer.tokens[0]);
+        let header_args = args(0, &get_token_content_hash(tree, from, slot));
+        let lhs = get_current_branch(tree, from, slot);
+        let rhs = get_current_branch(tree, to, get_free_token_slot(tree, to));
+        apply_leaf_operation(tree, from, None, None, |acc| {
+            acc.balance.sub_assign(&fee_as_field_element);
+            acc.nonce.add_assign(&Fr::from_str("1").unwrap());
+        });
+        steps.push(BatchTransferStepWitness {
+            lhs,
+            rhs,
+            args: header_args.clone(),
+            after_root: Some(tree.root_hash()),
+        });
+        for _ in 1..BatchTransferOp::HEADER_CHUNKS {
+            let branch = get_current_branch(tree, from, slot);
+            steps.push(BatchTransferStepWitness {
+                lhs: branch.clone(),
+                rhs: branch,
+                args: header_args.clone(),
+                after_root: Some(tree.root_hash()),
+            });
+        }
+
+        for (i, token) in batch_transfer.tokens.iter().enumerate() {
+            // slots are taken from the current state, previous tokens of the batch are moved
+            let from_slot = get_token_slot(tree, from, token);
+            let content_hash = get_token_content_hash(tree, from, from_slot);
+            let token_args = args(i, &content_hash);
+
+            let branch = get_current_branch(tree, from, from_slot);
+            apply_leaf_operation(tree, from, None, Some(from_slot), |_| {});
+            steps.push(BatchTransferStepWitness {
+                lhs: branch.clone(),
+                rhs: branch,
+                args: token_args.clone(),
+                after_root: Some(tree.root_hash()),
+            });
+
+            // recipient may be a new account, it gets its address with the first token
+            let to_slot = get_free_token_slot(tree, to);
+            let branch = get_current_branch(tree, to, to_slot);
+            apply_leaf_operation(
+                tree,
+                to,
+                Some((to_slot, *token, content_hash)),
+                None,
+                |acc| acc.address = batch_transfer.to_address,
+            );
+            steps.push(BatchTransferStepWitness {
+                lhs: branch.clone(),
+                rhs: branch,
+                args: token_args.clone(),
+                after_root: Some(tree.root_hash()),
+            });
+
+            for _ in 2..BatchTransferOp::TOKEN_CHUNKS {
+                let branch = get_current_branch(tree, to, to_slot);
+                steps.push(BatchTransferStepWitness {
+                    lhs: branch.clone(),
+                    rhs: branch,
+                    args: token_args.clone(),
+                    after_root: Some(tree.root_hash()),
+                });
+            }
+        }
+        debug!("Batch transfer final root = {}", tree.root_hash());
+
+        BatchTransferWitness {
+            steps,
+            before_root: Some(before_root),
+            tx_type: Some(Fr::from_str(&BatchTransferOp::OP_CODE.to_string()).unwrap()),
+        }
+    }
+}
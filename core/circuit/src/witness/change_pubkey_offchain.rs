@@ -21,7 +21,7 @@ use crate::{
         Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
     },
     witness::{
-        utils::{apply_leaf_operation, get_audits},
+        utils::{apply_leaf_operation, get_audits, get_token_witness},
         Witness,
     },
 };
@@ -126,6 +126,7 @@ impl ChangePubkeyOffChainWitness<Bn256> {
         debug!("Initial root = {}", before_root);
         let (audit_path_before, audit_token_path_before) =
             get_audits(tree, change_pubkey_offcahin.account_id, 0);
+        let token_before = get_token_witness(tree, change_pubkey_offcahin.account_id, 0);
 
         let capacity = tree.capacity();
         assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
@@ -149,36 +150,65 @@ impl ChangePubkeyOffChainWitness<Bn256> {
         debug!("After root = {}", after_root);
         let (audit_path_after, audit_token_path_after) =
             get_audits(tree, change_pubkey_offcahin.account_id, 0);
+        let token_after = get_token_witness(tree, change_pubkey_offcahin.account_id, 0);
 
         ChangePubkeyOffChainWitness {
             before: OperationBranch {
                 address: Some(account_id_fe),
+                token_slot: Some(Fr::zero()),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_before,
                     account_path: audit_path_before,
                     //balance_value: Some(balance_before),
+                    token_witness: token_before,
                     token_subtree_path: audit_token_path_before,
                 },
             },
             after: OperationBranch {
                 address: Some(account_id_fe),
+                token_slot: Some(Fr::zero()),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_after,
                     account_path: audit_path_after,
                     //balance_value: Some(balance_after),
+                    token_witness: token_after,
                     token_subtree_path: audit_token_path_after,
                 },
             },
             args: OperationArguments {
                 eth_address: Some(change_pubkey_offcahin.address),
                 //amount_packed: Some(Fr::zero()),
-                //full_amount: Some(Fr::zero()),
+                full_amount: Some(Fr::zero()),
                 fee: Some(Fr::zero()),
                 //a: Some(a),
                 //b: Some(b),
                 pub_nonce: Some(change_pubkey_offcahin.nonce),
                 new_pub_key_hash: Some(change_pubkey_offcahin.new_pubkey_hash),
-                token_id: Some(Fr::zero()),
+                collection_id: Some(Fr::zero()),
+                token_id_hi: Some(Fr::zero()),
+                token_id_lo: Some(Fr::zero()),
+                content_hash_hi: Some(Fr::zero()),
+                content_hash_lo: Some(Fr::zero()),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::zero()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
             },
             before_root: Some(before_root),
             after_root: Some(after_root),
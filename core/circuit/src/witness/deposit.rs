@@ -9,28 +9,36 @@ use crypto_exports::franklin_crypto::{
 // Workspace deps
 use models::{
     circuit::{
-        account::CircuitAccountTree,
+        account::{CircuitAccount, CircuitAccountTree},
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
-    node::operations::DepositOp,
+    node::{operations::DepositOp, ContentHash, Nft, TokenSlot},
     params as franklin_constants,
 };
 // Local deps
 use crate::{
+    account::AccountWitness,
     operation::{
         Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
     },
     witness::{
-        utils::{apply_leaf_operation, get_audits},
+        utils::{
+            append_content_hash_be, append_nft_be, apply_leaf_operation, content_hash_limbs,
+            get_audits, get_free_token_slot, get_token_witness, nft_limbs,
+        },
         Witness,
     },
 };
 
 pub struct DepositData {
     //pub amount: u128,
-    pub token_id: u32,
+    pub token: Nft,
+    pub content_hash: ContentHash,
+    /// Free slot of the account, zero when the deposit fails.
+    pub token_slot: TokenSlot,
     pub account_address: u32,
     pub address: Fr,
+    pub success: bool,
 }
 
 pub struct DepositWitness<E: RescueEngine> {
@@ -40,6 +48,7 @@ pub struct DepositWitness<E: RescueEngine> {
     pub before_root: Option<E::Fr>,
     pub after_root: Option<E::Fr>,
     pub tx_type: Option<E::Fr>,
+    pub success: bool,
 }
 
 impl Witness for DepositWitness<Bn256> {
@@ -47,10 +56,18 @@ impl Witness for DepositWitness<Bn256> {
     type CalculateOpsInput = ();
 
     fn apply_tx(tree: &mut CircuitAccountTree, deposit: &DepositOp) -> Self {
+        let token_slot = if deposit.success {
+            get_free_token_slot(tree, deposit.account_id)
+        } else {
+            0
+        };
         let deposit_data = DepositData {
-            token_id: u32::from(deposit.priority_op.token_id),
+            token: deposit.priority_op.nft(),
+            content_hash: deposit.priority_op.content_hash,
+            token_slot,
             account_address: deposit.account_id,
             address: eth_address_to_fr(&deposit.priority_op.to),
+            success: deposit.success,
         };
         Self::apply_data(tree, &deposit_data)
     }
@@ -78,19 +95,21 @@ impl Witness for DepositWitness<Bn256> {
             &self.args.full_amount.unwrap(),
             franklin_constants::BALANCE_BIT_WIDTH,
         );*/
-        append_be_fixed_width(
-            &mut pubdata_bits,
-            &self.args.token_id.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );
+        append_nft_be(&mut pubdata_bits, &self.args);
 
         append_be_fixed_width(
             &mut pubdata_bits,
             &self.args.eth_address.unwrap(),
             franklin_constants::ETH_ADDRESS_BIT_WIDTH,
         );
+        append_content_hash_be(&mut pubdata_bits, &self.args);
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &Fr::from_str(&(self.success as u8).to_string()).unwrap(),
+            franklin_constants::SUCCESS_FLAG_BIT_WIDTH,
+        );
         //        assert_eq!(pubdata_bits.len(), 37 * 8);
-        pubdata_bits.resize(6 * franklin_constants::CHUNK_BIT_WIDTH, false);
+        pubdata_bits.resize(DepositOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH, false);
         pubdata_bits
     }
 
@@ -126,88 +145,23 @@ impl Witness for DepositWitness<Bn256> {
             rhs: self.before.clone(),
         };
 
-        let operation_one = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("1").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[1]),
-            first_sig_msg: Some(*first_sig_msg),
-            second_sig_msg: Some(*second_sig_msg),
-            third_sig_msg: Some(*third_sig_msg),
-            signature_data: signature_data.clone(),
-            signer_pub_key_packed: signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-        };
-
-        let operation_two = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("2").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[2]),
-            first_sig_msg: Some(*first_sig_msg),
-            second_sig_msg: Some(*second_sig_msg),
-            third_sig_msg: Some(*third_sig_msg),
-            signer_pub_key_packed: signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-            signature_data: signature_data.clone(),
-        };
-
-        let operation_three = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("3").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[3]),
-            first_sig_msg: Some(*first_sig_msg),
-            second_sig_msg: Some(*second_sig_msg),
-            third_sig_msg: Some(*third_sig_msg),
-            signer_pub_key_packed: signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-            signature_data: signature_data.clone(),
-        };
-
-        let operation_four = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("4").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[4]),
-            first_sig_msg: Some(*first_sig_msg),
-            second_sig_msg: Some(*second_sig_msg),
-            third_sig_msg: Some(*third_sig_msg),
-            signer_pub_key_packed: signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-            signature_data: signature_data.clone(),
-        };
-
-        let operation_five = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("5").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[5]),
-            first_sig_msg: Some(*first_sig_msg),
-            second_sig_msg: Some(*second_sig_msg),
-            third_sig_msg: Some(*third_sig_msg),
-            signer_pub_key_packed: signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-            signature_data: signature_data.clone(),
-        };
-        let operations: Vec<Operation<_>> = vec![
-            operation_zero,
-            operation_one,
-            operation_two,
-            operation_three,
-            operation_four,
-            operation_five,
-        ];
+        let mut operations = vec![operation_zero];
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(1) {
+            operations.push(Operation {
+                new_root: self.after_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(*first_sig_msg),
+                second_sig_msg: Some(*second_sig_msg),
+                third_sig_msg: Some(*third_sig_msg),
+                signature_data: signature_data.clone(),
+                signer_pub_key_packed: signer_pub_key_packed.to_vec(),
+                args: self.args.clone(),
+                lhs: self.after.clone(),
+                rhs: self.after.clone(),
+            });
+        }
         operations
     }
 }
@@ -236,11 +190,7 @@ impl<E: RescueEngine> DepositWitness<E> {
             &self.args.full_amount.unwrap(),
             franklin_constants::BALANCE_BIT_WIDTH,
         );*/
-        append_be_fixed_width(
-            &mut sig_bits,
-            &self.args.token_id.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );
+        append_nft_be(&mut sig_bits, &self.args);
         append_be_fixed_width(
             &mut sig_bits,
             &self.before.witness.account_witness.nonce.unwrap(),
@@ -256,69 +206,113 @@ impl DepositWitness<Bn256> {
         let before_root = tree.root_hash();
         debug!("deposit Initial root = {}", before_root);
         let (audit_path_before, audit_token_path_before) =
-            get_audits(tree, deposit.account_address, deposit.token_id);
+            get_audits(tree, deposit.account_address, deposit.token_slot);
+        let token_before = get_token_witness(tree, deposit.account_address, deposit.token_slot);
 
         let capacity = tree.capacity();
         assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
         let account_address_fe = Fr::from_str(&deposit.account_address.to_string()).unwrap();
-        let token_fe = Fr::from_str(&deposit.token_id.to_string()).unwrap();
+        let token_slot_fe = Fr::from_str(&deposit.token_slot.to_string()).unwrap();
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&deposit.token);
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&deposit.content_hash);
         //let amount_as_field_element = Fr::from_str(&deposit.amount.to_string()).unwrap();
         //debug!("amount_as_field_element is: {}", amount_as_field_element);
         //calculate a and b
         //let a = amount_as_field_element;
         //let b = Fr::zero();
 
-        //applying deposit
-        let (account_witness_before, account_witness_after, balance_before, balance_after) =
-            apply_leaf_operation(
+        //applying deposit, a failed deposit leaves the tree untouched
+        let (account_witness_before, account_witness_after) = if deposit.success {
+            let (account_witness_before, account_witness_after, _, _) = apply_leaf_operation(
                 tree,
                 deposit.account_address,
-                Some(deposit.token_id),
+                Some((deposit.token_slot, deposit.token, deposit.content_hash)),
                 None,
                 |acc| {
                     assert!((acc.address == deposit.address) || (acc.address == Fr::zero()));
                     acc.address = deposit.address;
                 },
             );
+            (account_witness_before, account_witness_after)
+        } else {
+            let account_witness = AccountWitness::from_circuit_account(
+                &tree
+                    .get(deposit.account_address)
+                    .cloned()
+                    .unwrap_or_else(CircuitAccount::default),
+            );
+            (account_witness.clone(), account_witness)
+        };
 
         let after_root = tree.root_hash();
         debug!("deposit After root = {}", after_root);
         let (audit_path_after, audit_token_path_after) =
-            get_audits(tree, deposit.account_address, deposit.token_id);
+            get_audits(tree, deposit.account_address, deposit.token_slot);
+        let token_after = get_token_witness(tree, deposit.account_address, deposit.token_slot);
 
         DepositWitness {
             before: OperationBranch {
                 address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_before,
                     account_path: audit_path_before,
                     //balance_value: Some(balance_before),
+                    token_witness: token_before,
                     token_subtree_path: audit_token_path_before,
                 },
             },
             after: OperationBranch {
                 address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_after,
                     account_path: audit_path_after,
                     //balance_value: Some(balance_after),
+                    token_witness: token_after,
                     token_subtree_path: audit_token_path_after,
                 },
             },
             args: OperationArguments {
                 eth_address: Some(deposit.address),
                 //amount_packed: Some(Fr::zero()),
-                //full_amount: Some(amount_as_field_element),
+                // number of deposited tokens, the circuit publishes it as the success flag
+                full_amount: Some(Fr::from_str(&(deposit.success as u8).to_string()).unwrap()),
                 fee: Some(Fr::zero()),
                 //a: Some(a),
                 //b: Some(b),
                 pub_nonce: Some(Fr::zero()),
                 new_pub_key_hash: Some(Fr::zero()),
-                token_id: Some(token_fe),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::zero()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
             },
             before_root: Some(before_root),
             after_root: Some(after_root),
             tx_type: Some(Fr::from_str("1").unwrap()),
+            success: deposit.success,
         }
     }
 }
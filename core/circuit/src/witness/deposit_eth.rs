@@ -0,0 +1,228 @@
+// External deps
+use crypto_exports::franklin_crypto::{
+    bellman::pairing::{
+        bn256::{Bn256, Fr},
+        ff::{Field, PrimeField},
+    },
+    rescue::RescueEngine,
+};
+// Workspace deps
+use models::{
+    circuit::{
+        account::CircuitAccountTree,
+        utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
+    },
+    node::operations::DepositEthOp,
+    params as franklin_constants,
+};
+// Local deps
+use crate::{
+    operation::{
+        Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
+    },
+    witness::{
+        utils::{apply_leaf_operation, get_audits, get_token_witness},
+        Witness,
+    },
+};
+
+pub struct DepositEthData {
+    pub amount: u128,
+    pub account_address: u32,
+    pub address: Fr,
+}
+
+pub struct DepositEthWitness<E: RescueEngine> {
+    pub before: OperationBranch<E>,
+    pub after: OperationBranch<E>,
+    pub args: OperationArguments<E>,
+    /// Deposited amount in wei.
+    pub amount: Option<E::Fr>,
+    pub before_root: Option<E::Fr>,
+    pub after_root: Option<E::Fr>,
+    pub tx_type: Option<E::Fr>,
+}
+
+impl Witness for DepositEthWitness<Bn256> {
+    type OperationType = DepositEthOp;
+    type CalculateOpsInput = ();
+
+    fn apply_tx(tree: &mut CircuitAccountTree, deposit_eth: &DepositEthOp) -> Self {
+        let deposit_eth_data = DepositEthData {
+            amount: deposit_eth
+                .priority_op
+                .amount
+                .to_string()
+                .parse()
+                .expect("eth deposit amount fits into u128"),
+            account_address: deposit_eth.account_id,
+            address: eth_address_to_fr(&deposit_eth.priority_op.to),
+        };
+        Self::apply_data(tree, &deposit_eth_data)
+    }
+
+    fn get_pubdata(&self) -> Vec<bool> {
+        let mut pubdata_bits = vec![];
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.tx_type.unwrap(),
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.before.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.amount.unwrap(),
+            franklin_constants::BALANCE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.eth_address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        pubdata_bits.resize(
+            DepositEthOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH,
+            false,
+        );
+        pubdata_bits
+    }
+
+    fn calculate_operations(&self, _input: ()) -> Vec<Operation<Bn256>> {
+        let pubdata_chunks: Vec<_> = self
+            .get_pubdata()
+            .chunks(64)
+            .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
+            .collect();
+
+        let empty_sig_data = SignatureData {
+            r_packed: vec![Some(false); 256],
+            s: vec![Some(false); 256],
+        };
+        let mut operations = vec![];
+        operations.push(Operation {
+            new_root: self.after_root,
+            tx_type: self.tx_type,
+            chunk: Some(Fr::from_str("0").unwrap()),
+            pubdata_chunk: Some(pubdata_chunks[0]),
+            first_sig_msg: Some(Fr::zero()),
+            second_sig_msg: Some(Fr::zero()),
+            third_sig_msg: Some(Fr::zero()),
+            signer_pub_key_packed: vec![Some(false); 256],
+            args: self.args.clone(),
+            lhs: self.before.clone(),
+            rhs: self.before.clone(),
+            signature_data: empty_sig_data.clone(),
+        });
+
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(1) {
+            operations.push(Operation {
+                new_root: self.after_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(Fr::zero()),
+                second_sig_msg: Some(Fr::zero()),
+                third_sig_msg: Some(Fr::zero()),
+                signer_pub_key_packed: vec![Some(false); 256],
+                args: self.args.clone(),
+                lhs: self.after.clone(),
+                rhs: self.after.clone(),
+                signature_data: empty_sig_data.clone(),
+            });
+        }
+
+        operations
+    }
+}
+
+impl DepositEthWitness<Bn256> {
+    fn apply_data(tree: &mut CircuitAccountTree, deposit_eth: &DepositEthData) -> Self {
+        //preparing data and base witness
+        let before_root = tree.root_hash();
+        debug!("Initial root = {}", before_root);
+        let (audit_path_before, audit_token_path_before) =
+            get_audits(tree, deposit_eth.account_address, 0);
+        let token_before = get_token_witness(tree, deposit_eth.account_address, 0);
+
+        let capacity = tree.capacity();
+        assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
+        let account_address_fe = Fr::from_str(&deposit_eth.account_address.to_string()).unwrap();
+        let amount_fe = Fr::from_str(&deposit_eth.amount.to_string()).unwrap();
+
+        //applying eth deposit, the token subtree is untouched
+        let (account_witness_before, account_witness_after, _, _) =
+            apply_leaf_operation(tree, deposit_eth.account_address, None, None, |acc| {
+                assert!((acc.address == deposit_eth.address) || (acc.address == Fr::zero()));
+                acc.address = deposit_eth.address;
+                acc.balance.add_assign(&amount_fe);
+            });
+
+        let after_root = tree.root_hash();
+        debug!("After root = {}", after_root);
+        let (audit_path_after, audit_token_path_after) =
+            get_audits(tree, deposit_eth.account_address, 0);
+        let token_after = get_token_witness(tree, deposit_eth.account_address, 0);
+
+        DepositEthWitness {
+            before: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(Fr::zero()),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_before,
+                    account_path: audit_path_before,
+                    token_witness: token_before,
+                    token_subtree_path: audit_token_path_before,
+                },
+            },
+            after: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(Fr::zero()),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_after,
+                    account_path: audit_path_after,
+                    token_witness: token_after,
+                    token_subtree_path: audit_token_path_after,
+                },
+            },
+            args: OperationArguments {
+                eth_address: Some(deposit_eth.address),
+                fee: Some(Fr::zero()),
+                full_amount: Some(amount_fe),
+                pub_nonce: Some(Fr::zero()),
+                new_pub_key_hash: Some(Fr::zero()),
+                collection_id: Some(Fr::zero()),
+                token_id_hi: Some(Fr::zero()),
+                token_id_lo: Some(Fr::zero()),
+                content_hash_hi: Some(Fr::zero()),
+                content_hash_lo: Some(Fr::zero()),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::zero()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
+            },
+            amount: Some(amount_fe),
+            before_root: Some(before_root),
+            after_root: Some(after_root),
+            tx_type: Some(Fr::from_str("15").unwrap()),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+// External deps
+use crypto_exports::franklin_crypto::{
+    bellman::pairing::{
+        bn256::{Bn256, Fr},
+        ff::{Field, PrimeField},
+    },
+    rescue::RescueEngine,
+};
+// Workspace deps
+use models::{
+    circuit::{
+        account::{CircuitAccount, CircuitAccountTree},
+        utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
+    },
+    node::{operations::ForcedWithdrawOp, ContentHash, Nft, TokenSlot},
+    params as franklin_constants,
+};
+// Local deps
+use crate::{
+    account::AccountWitness,
+    operation::{
+        Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
+    },
+    witness::{
+        utils::{
+            append_nft_be, apply_leaf_operation, content_hash_limbs, get_audits,
+            get_token_content_hash, get_token_slot, get_token_witness, nft_limbs,
+        },
+        Witness,
+    },
+};
+
+pub struct ForcedWithdrawData {
+    pub token: Nft,
+    /// Slot of the withdrawn token, zero when the request fails.
+    pub token_slot: TokenSlot,
+    pub account_address: u32,
+    pub eth_address: Fr,
+    pub success: bool,
+}
+
+pub struct ForcedWithdrawWitness<E: RescueEngine> {
+    pub before: OperationBranch<E>,
+    pub after: OperationBranch<E>,
+    pub args: OperationArguments<E>,
+    pub before_root: Option<E::Fr>,
+    pub after_root: Option<E::Fr>,
+    pub tx_type: Option<E::Fr>,
+    pub success: bool,
+}
+
+impl Witness for ForcedWithdrawWitness<Bn256> {
+    type OperationType = ForcedWithdrawOp;
+    type CalculateOpsInput = ();
+
+    fn apply_tx(tree: &mut CircuitAccountTree, forced_withdraw: &ForcedWithdrawOp) -> Self {
+        let account_address = forced_withdraw.priority_op.account_id;
+        let token = forced_withdraw.priority_op.nft();
+        let token_slot = if forced_withdraw.success {
+            get_token_slot(tree, account_address, &token)
+        } else {
+            0
+        };
+        let forced_withdraw_data = ForcedWithdrawData {
+            token,
+            token_slot,
+            account_address,
+            eth_address: eth_address_to_fr(&forced_withdraw.priority_op.eth_address),
+            success: forced_withdraw.success,
+        };
+        Self::apply_data(tree, &forced_withdraw_data)
+    }
+
+    fn get_pubdata(&self) -> Vec<bool> {
+        let mut pubdata_bits = vec![];
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.tx_type.unwrap(),
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.before.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.eth_address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        append_nft_be(&mut pubdata_bits, &self.args);
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &Fr::from_str(&(self.success as u8).to_string()).unwrap(),
+            franklin_constants::SUCCESS_FLAG_BIT_WIDTH,
+        );
+        pubdata_bits.resize(
+            ForcedWithdrawOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH,
+            false,
+        );
+        pubdata_bits
+    }
+
+    fn calculate_operations(&self, _input: ()) -> Vec<Operation<Bn256>> {
+        let pubdata_chunks: Vec<_> = self
+            .get_pubdata()
+            .chunks(64)
+            .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
+            .collect();
+
+        let empty_sig_data = SignatureData {
+            r_packed: vec![Some(false); 256],
+            s: vec![Some(false); 256],
+        };
+        let mut operations = vec![];
+        operations.push(Operation {
+            new_root: self.after_root,
+            tx_type: self.tx_type,
+            chunk: Some(Fr::from_str("0").unwrap()),
+            pubdata_chunk: Some(pubdata_chunks[0]),
+            first_sig_msg: Some(Fr::zero()),
+            second_sig_msg: Some(Fr::zero()),
+            third_sig_msg: Some(Fr::zero()),
+            signer_pub_key_packed: vec![Some(false); 256],
+            args: self.args.clone(),
+            lhs: self.before.clone(),
+            rhs: self.before.clone(),
+            signature_data: empty_sig_data.clone(),
+        });
+
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(1) {
+            operations.push(Operation {
+                new_root: self.after_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(Fr::zero()),
+                second_sig_msg: Some(Fr::zero()),
+                third_sig_msg: Some(Fr::zero()),
+                signer_pub_key_packed: vec![Some(false); 256],
+                args: self.args.clone(),
+                lhs: self.after.clone(),
+                rhs: self.after.clone(),
+                signature_data: empty_sig_data.clone(),
+            });
+        }
+
+        operations
+    }
+}
+
+impl ForcedWithdrawWitness<Bn256> {
+    fn apply_data(tree: &mut CircuitAccountTree, forced_withdraw: &ForcedWithdrawData) -> Self {
+        //preparing data and base witness
+        let before_root = tree.root_hash();
+        debug!("Initial root = {}", before_root);
+        let (audit_path_before, audit_token_path_before) = get_audits(
+            tree,
+            forced_withdraw.account_address,
+            forced_withdraw.token_slot,
+        );
+        let token_before = get_token_witness(
+            tree,
+            forced_withdraw.account_address,
+            forced_withdraw.token_slot,
+        );
+
+        let capacity = tree.capacity();
+        assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
+        let account_address_fe =
+            Fr::from_str(&forced_withdraw.account_address.to_string()).unwrap();
+        let token_slot_fe = Fr::from_str(&forced_withdraw.token_slot.to_string()).unwrap();
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&forced_withdraw.token);
+
+        //applying forced withdraw, a failed request leaves the tree untouched
+        let (account_witness_before, account_witness_after, content_hash) =
+            if forced_withdraw.success {
+                let content_hash = get_token_content_hash(
+                    tree,
+                    forced_withdraw.account_address,
+                    forced_withdraw.token_slot,
+                );
+                let (account_witness_before, account_witness_after, _, _) = apply_leaf_operation(
+                    tree,
+                    forced_withdraw.account_address,
+                    None,
+                    Some(forced_withdraw.token_slot),
+                    |_| {},
+                );
+                (account_witness_before, account_witness_after, content_hash)
+            } else {
+                let account_witness = AccountWitness::from_circuit_account(
+                    &tree
+                        .get(forced_withdraw.account_address)
+                        .cloned()
+                        .unwrap_or_else(CircuitAccount::default),
+                );
+                (
+                    account_witness.clone(),
+                    account_witness,
+                    ContentHash::zero(),
+                )
+            };
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&content_hash);
+
+        let after_root = tree.root_hash();
+        debug!("After root = {}", after_root);
+        let (audit_path_after, audit_token_path_after) = get_audits(
+            tree,
+            forced_withdraw.account_address,
+            forced_withdraw.token_slot,
+        );
+        let token_after = get_token_witness(
+            tree,
+            forced_withdraw.account_address,
+            forced_withdraw.token_slot,
+        );
+
+        ForcedWithdrawWitness {
+            before: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_before,
+                    account_path: audit_path_before,
+                    token_witness: token_before,
+                    token_subtree_path: audit_token_path_before,
+                },
+            },
+            after: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_after,
+                    account_path: audit_path_after,
+                    token_witness: token_after,
+                    token_subtree_path: audit_token_path_after,
+                },
+            },
+            args: OperationArguments {
+                eth_address: Some(forced_withdraw.eth_address),
+                fee: Some(Fr::zero()),
+                // number of withdrawn tokens, the circuit publishes it as the success flag
+                full_amount: Some(
+                    Fr::from_str(&(forced_withdraw.success as u8).to_string()).unwrap(),
+                ),
+                pub_nonce: Some(Fr::zero()),
+                new_pub_key_hash: Some(Fr::zero()),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::zero()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
+            },
+            before_root: Some(before_root),
+            after_root: Some(after_root),
+            tx_type: Some(Fr::from_str("14").unwrap()),
+            success: forced_withdraw.success,
+        }
+    }
+}
@@ -12,7 +12,7 @@ use models::{
         account::CircuitAccountTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
-    node::operations::FullExitOp,
+    node::{operations::FullExitOp, TokenId},
     params as franklin_constants,
 };
 // Local deps
@@ -27,7 +27,7 @@ use crate::{
 };
 
 pub struct FullExitData {
-    pub token: u32,
+    pub token: TokenId,
     pub account_address: u32,
     pub eth_address: Fr,
     pub full_exit_amount: Fr,
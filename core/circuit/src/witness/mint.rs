@@ -0,0 +1,279 @@
+// External deps
+use crypto_exports::franklin_crypto::{
+    bellman::pairing::{
+        bn256::{Bn256, Fr},
+        ff::{Field, PrimeField},
+    },
+    rescue::RescueEngine,
+};
+// Workspace deps
+use models::{
+    circuit::{
+        account::CircuitAccountTree,
+        utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
+    },
+    node::{operations::MintOp, ContentHash, Nft, TokenSlot},
+    params as franklin_constants,
+    primitives::{big_decimal_to_u128, convert_to_float},
+};
+// Local deps
+use crate::{
+    operation::{Operation, OperationArguments, OperationBranch, OperationBranchWitness},
+    witness::{
+        utils::{
+            append_content_hash_be, append_nft_be, apply_leaf_operation, content_hash_limbs,
+            get_audits, get_free_token_slot, get_token_witness, nft_limbs, SigDataInput,
+        },
+        Witness,
+    },
+};
+
+pub struct MintData {
+    pub fee: u128,
+    pub token: Nft,
+    pub content_hash: ContentHash,
+    pub token_slot: TokenSlot,
+    pub account_address: u32,
+    pub eth_address: Fr,
+}
+
+pub struct MintWitness<E: RescueEngine> {
+    pub before: OperationBranch<E>,
+    pub after: OperationBranch<E>,
+    pub args: OperationArguments<E>,
+    pub before_root: Option<E::Fr>,
+    pub after_root: Option<E::Fr>,
+    pub tx_type: Option<E::Fr>,
+}
+
+impl Witness for MintWitness<Bn256> {
+    type OperationType = MintOp;
+    type CalculateOpsInput = SigDataInput;
+
+    fn apply_tx(tree: &mut CircuitAccountTree, mint: &MintOp) -> Self {
+        let mint_data = MintData {
+            fee: big_decimal_to_u128(&mint.tx.fee),
+            token: mint.tx.nft(),
+            content_hash: mint.tx.content_hash,
+            token_slot: get_free_token_slot(tree, mint.account_id),
+            account_address: mint.account_id,
+            eth_address: eth_address_to_fr(&mint.tx.account),
+        };
+        Self::apply_data(tree, &mint_data)
+    }
+
+    fn get_pubdata(&self) -> Vec<bool> {
+        let mut pubdata_bits = vec![];
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.tx_type.unwrap(),
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.before.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_nft_be(&mut pubdata_bits, &self.args);
+
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.fee.unwrap(),
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+        );
+
+        // the contract checks that the minter is authorized by the governance
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.eth_address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        append_content_hash_be(&mut pubdata_bits, &self.args);
+        pubdata_bits.resize(MintOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH, false);
+        pubdata_bits
+    }
+
+    fn calculate_operations(&self, input: SigDataInput) -> Vec<Operation<Bn256>> {
+        let pubdata_chunks: Vec<_> = self
+            .get_pubdata()
+            .chunks(64)
+            .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
+            .collect();
+
+        let operation_zero = Operation {
+            new_root: self.after_root,
+            tx_type: self.tx_type,
+            chunk: Some(Fr::from_str("0").unwrap()),
+            pubdata_chunk: Some(pubdata_chunks[0]),
+            first_sig_msg: Some(input.first_sig_msg),
+            second_sig_msg: Some(input.second_sig_msg),
+            third_sig_msg: Some(input.third_sig_msg),
+            signature_data: input.signature.clone(),
+            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
+            args: self.args.clone(),
+            lhs: self.before.clone(),
+            rhs: self.before.clone(),
+        };
+
+        let mut operations = vec![operation_zero];
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(1) {
+            operations.push(Operation {
+                new_root: self.after_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(input.first_sig_msg),
+                second_sig_msg: Some(input.second_sig_msg),
+                third_sig_msg: Some(input.third_sig_msg),
+                signature_data: input.signature.clone(),
+                signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
+                args: self.args.clone(),
+                lhs: self.after.clone(),
+                rhs: self.after.clone(),
+            });
+        }
+        operations
+    }
+}
+
+impl<E: RescueEngine> MintWitness<E> {
+    pub fn get_sig_bits(&self) -> Vec<bool> {
+        let mut sig_bits = vec![];
+        append_be_fixed_width(
+            &mut sig_bits,
+            &Fr::from_str("10").unwrap(), //Corresponding tx_type
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.before.witness.account_witness.pub_key_hash.unwrap(),
+            franklin_constants::NEW_PUBKEY_HASH_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.eth_address.unwrap(),
+            franklin_constants::ETH_ADDRESS_BIT_WIDTH,
+        );
+        append_nft_be(&mut sig_bits, &self.args);
+        append_content_hash_be(&mut sig_bits, &self.args);
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.fee.unwrap(),
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.before.witness.account_witness.nonce.unwrap(),
+            franklin_constants::NONCE_BIT_WIDTH,
+        );
+        sig_bits
+    }
+}
+
+impl MintWitness<Bn256> {
+    fn apply_data(tree: &mut CircuitAccountTree, mint: &MintData) -> Self {
+        //preparing data and base witness
+        let before_root = tree.root_hash();
+        debug!("mint Initial root = {}", before_root);
+        let (audit_path_before, audit_token_path_before) =
+            get_audits(tree, mint.account_address, mint.token_slot);
+        let token_before = get_token_witness(tree, mint.account_address, mint.token_slot);
+
+        let capacity = tree.capacity();
+        assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
+        let account_address_fe = Fr::from_str(&mint.account_address.to_string()).unwrap();
+        let token_slot_fe = Fr::from_str(&mint.token_slot.to_string()).unwrap();
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&mint.token);
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&mint.content_hash);
+
+        let fee_as_field_element = Fr::from_str(&mint.fee.to_string()).unwrap();
+
+        let fee_bits = convert_to_float(
+            mint.fee,
+            franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+            franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+            10,
+        )
+        .unwrap();
+
+        let fee_encoded: Fr = le_bit_vector_into_field_element(&fee_bits);
+
+        //applying mint
+        let (account_witness_before, account_witness_after, _, _) = apply_leaf_operation(
+            tree,
+            mint.account_address,
+            Some((mint.token_slot, mint.token, mint.content_hash)),
+            None,
+            |acc| {
+                assert_eq!(acc.address, mint.eth_address);
+                acc.balance.sub_assign(&fee_as_field_element);
+                acc.nonce.add_assign(&Fr::from_str("1").unwrap());
+            },
+        );
+
+        let after_root = tree.root_hash();
+        debug!("mint After root = {}", after_root);
+        let (audit_path_after, audit_token_path_after) =
+            get_audits(tree, mint.account_address, mint.token_slot);
+        let token_after = get_token_witness(tree, mint.account_address, mint.token_slot);
+
+        MintWitness {
+            before: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_before,
+                    account_path: audit_path_before,
+                    token_witness: token_before,
+                    token_subtree_path: audit_token_path_before,
+                },
+            },
+            after: OperationBranch {
+                address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
+                witness: OperationBranchWitness {
+                    account_witness: account_witness_after,
+                    account_path: audit_path_after,
+                    token_witness: token_after,
+                    token_subtree_path: audit_token_path_after,
+                },
+            },
+            args: OperationArguments {
+                eth_address: Some(mint.eth_address),
+                fee: Some(fee_encoded),
+                full_amount: Some(Fr::zero()),
+                pub_nonce: Some(Fr::zero()),
+                new_pub_key_hash: Some(Fr::zero()),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::zero()),
+                valid_until: Some(Fr::zero()),
+                fee_payer: Some(Fr::zero()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
+            },
+            before_root: Some(before_root),
+            after_root: Some(after_root),
+            tx_type: Some(Fr::from_str("10").unwrap()),
+        }
+    }
+}
@@ -6,21 +6,37 @@ use crate::operation::Operation;
 
 // Public re-exports
 pub use self::{
+    approve::ApproveWitness,
+    approve_for_all::ApproveForAllWitness,
+    batch_transfer::BatchTransferWitness,
+    burn::BurnWitness,
     change_pubkey_offchain::ChangePubkeyOffChainWitness,
     close_account::CloseAccountWitness,
     deposit::DepositWitness,
+    deposit_eth::DepositEthWitness,
+    forced_withdraw::ForcedWithdrawWitness,
     full_exit::FullExitWitness,
+    mint::MintWitness,
+    swap::SwapWitness,
     transfer::TransferWitness,
     transfer_to_new::TransferToNewWitness,
     utils::{SigDataInput, WitnessBuilder},
     withdraw::WithdrawWitness,
 };
 
+pub mod approve;
+pub mod approve_for_all;
+pub mod batch_transfer;
+pub mod burn;
 pub mod change_pubkey_offchain;
 pub mod close_account;
 pub mod deposit;
+pub mod deposit_eth;
+pub mod forced_withdraw;
 pub mod full_exit;
+pub mod mint;
 pub mod noop;
+pub mod swap;
 pub mod transfer;
 pub mod transfer_to_new;
 pub mod withdraw;
@@ -4,14 +4,17 @@ use crypto_exports::franklin_crypto::bellman::pairing::{
     ff::{Field, PrimeField},
 };
 // Workspace deps
-use models::circuit::{account::CircuitAccountTree, utils::le_bit_vector_into_field_element};
+use models::{
+    circuit::{account::CircuitAccountTree, utils::le_bit_vector_into_field_element},
+    params as franklin_constants,
+};
 // Local deps
 use crate::{
     account::AccountWitness,
     operation::{
         Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
     },
-    witness::utils::get_audits,
+    witness::utils::{get_audits, get_token_witness},
 };
 
 pub fn noop_operation(tree: &CircuitAccountTree, acc_id: u32) -> Operation<Bn256> {
@@ -29,6 +32,7 @@ pub fn noop_operation(tree: &CircuitAccountTree, acc_id: u32) -> Operation<Bn256
         .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
         .collect();
     let (audit_account, audit_token) = get_audits(tree, acc_id, 0);
+    let token_witness = get_token_witness(tree, acc_id, 0);
 
     Operation {
         new_root: Some(tree.root_hash()),
@@ -44,37 +48,69 @@ pub fn noop_operation(tree: &CircuitAccountTree, acc_id: u32) -> Operation<Bn256
         args: OperationArguments {
             eth_address: Some(Fr::zero()),
             //amount_packed: Some(Fr::zero()),
-            //full_amount: Some(Fr::zero()),
+            full_amount: Some(Fr::zero()),
             fee: Some(Fr::zero()),
             //a: Some(Fr::zero()),
             //b: Some(Fr::zero()),
             pub_nonce: Some(Fr::zero()),
             new_pub_key_hash: Some(Fr::zero()),
-            token_id: Some(Fr::zero()),
+            collection_id: Some(Fr::zero()),
+            token_id_hi: Some(Fr::zero()),
+            token_id_lo: Some(Fr::zero()),
+            content_hash_hi: Some(Fr::zero()),
+            content_hash_lo: Some(Fr::zero()),
+            operator_pub_key_hash: Some(Fr::zero()),
+            operator_id: Some(Fr::zero()),
+            is_operator: Some(Fr::zero()),
+            operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+            approved: Some(Fr::zero()),
+            valid_from: Some(Fr::zero()),
+            valid_until: Some(Fr::zero()),
+            fee_payer: Some(Fr::zero()),
+            tokens_count: Some(Fr::zero()),
+            tokens_root: Some(Fr::zero()),
+            token_index: Some(Fr::zero()),
+            token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+            second_collection_id: Some(Fr::zero()),
+            second_token_id_hi: Some(Fr::zero()),
+            second_token_id_lo: Some(Fr::zero()),
+            second_content_hash_hi: Some(Fr::zero()),
+            second_content_hash_lo: Some(Fr::zero()),
+            second_fee: Some(Fr::zero()),
+            second_tokens_count: Some(Fr::zero()),
+            second_tokens_root: Some(Fr::zero()),
         },
         lhs: OperationBranch {
             address: Some(account_address_fe),
+            token_slot: Some(Fr::zero()),
             witness: OperationBranchWitness {
                 account_witness: AccountWitness {
                     nonce: Some(acc.nonce),
                     pub_key_hash: Some(acc.pub_key_hash),
                     address: Some(acc.address),
+                    balance: Some(acc.balance),
+                    operators_root: Some(acc.operators.root_hash()),
                 },
                 account_path: audit_account.clone(),
                 //balance_value: Some(balance_value),
+                token_witness: token_witness.clone(),
                 token_subtree_path: audit_token.clone(),
             },
         },
         rhs: OperationBranch {
             address: Some(account_address_fe),
+            token_slot: Some(Fr::zero()),
             witness: OperationBranchWitness {
                 account_witness: AccountWitness {
                     nonce: Some(acc.nonce),
                     pub_key_hash: Some(acc.pub_key_hash),
                     address: Some(acc.address),
+                    balance: Some(acc.balance),
+                    operators_root: Some(acc.operators.root_hash()),
                 },
                 account_path: audit_account,
                 //balance_value: Some(balance_value),
+                token_witness,
                 token_subtree_path: audit_token,
             },
         },
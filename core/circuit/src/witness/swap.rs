@@ -0,0 +1,356 @@
+// External deps
+use crypto_exports::franklin_crypto::{
+    bellman::pairing::{
+        bn256::{Bn256, Fr},
+        ff::{Field, PrimeField},
+    },
+    rescue::RescueEngine,
+};
+// Workspace deps
+use models::{
+    circuit::{
+        account::{CircuitAccount, CircuitAccountTree, CircuitTokenTree},
+        utils::{append_be_fixed_width, le_bit_vector_into_field_element},
+    },
+    node::{operations::SwapOp, tx::nfts_list_tree, AccountId, Nft},
+    params as franklin_constants,
+    primitives::{big_decimal_to_u128, convert_to_float},
+};
+// Local deps
+use crate::{
+    operation::{Operation, OperationArguments, OperationBranch},
+    witness::{
+        utils::{
+            append_nft_be, apply_leaf_operation, content_hash_limbs, get_current_branch,
+            get_free_token_slot, get_token_content_hash, get_token_slot, nft_limbs, SigDataInput,
+        },
+        Witness,
+    },
+};
+
+#[derive(Debug)]
+pub struct SwapData {
+    pub fee_a: u128,
+    pub fee_b: u128,
+    pub account_a: AccountId,
+    pub token_a: Nft,
+    pub tokens_buy_a: Vec<Nft>,
+    pub account_b: AccountId,
+    pub token_b: Nft,
+    pub tokens_buy_b: Vec<Nft>,
+}
+
+/// Single change of the account tree made by the swap, it takes one chunk.
+pub struct SwapStepWitness<E: RescueEngine> {
+    pub lhs: OperationBranch<E>,
+    pub rhs: OperationBranch<E>,
+    pub args: OperationArguments<E>,
+    pub after_root: Option<E::Fr>,
+}
+
+/// Swap is applied in four steps, so that it works for accounts without free token slots:
+/// 1) `token_a` is removed from the account `a`, its fee is charged and its nonce is incremented (signed by `a`);
+/// 2) `token_b` is removed from the account `b`, its fee is charged and its nonce is incremented (signed by `b`);
+/// 3) `token_a` is added to the account `b`;
+/// 4) `token_b` is added to the account `a`.
+///
+/// The account changed by the first step is `lhs`, the one changed by the other steps is `rhs`.
+/// Every step carries both orders, the added tokens come with the audit paths to them
+/// in the token lists of their recipients.
+pub struct SwapWitness<E: RescueEngine> {
+    pub steps: Vec<SwapStepWitness<E>>,
+    pub lhs_after: OperationBranch<E>,
+    pub rhs_after: OperationBranch<E>,
+    pub before_root: Option<E::Fr>,
+    pub tx_type: Option<E::Fr>,
+}
+
+impl Witness for SwapWitness<Bn256> {
+    type OperationType = SwapOp;
+    /// Signature data of the accounts `a` and `b`
+    type CalculateOpsInput = (SigDataInput, SigDataInput);
+
+    fn apply_tx(tree: &mut CircuitAccountTree, swap: &SwapOp) -> Self {
+        let swap_data = SwapData {
+            fee_a: big_decimal_to_u128(&swap.tx.order_a.fee),
+            fee_b: big_decimal_to_u128(&swap.tx.order_b.fee),
+            account_a: swap.account_a,
+            token_a: swap.tx.order_a.token_sell,
+            tokens_buy_a: swap.tx.order_a.tokens_buy.clone(),
+            account_b: swap.account_b,
+            token_b: swap.tx.order_b.token_sell,
+            tokens_buy_b: swap.tx.order_b.tokens_buy.clone(),
+        };
+        Self::apply_data(tree, &swap_data)
+    }
+
+    fn get_pubdata(&self) -> Vec<bool> {
+        let remove_a = &self.steps[0];
+        let remove_b = &self.steps[1];
+        let args = &remove_a.args;
+
+        let mut pubdata_bits = vec![];
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.tx_type.unwrap(),
+            franklin_constants::TX_TYPE_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &remove_a.lhs.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_nft_be(&mut pubdata_bits, args);
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &remove_b.rhs.address.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &args.second_collection_id.unwrap(),
+            franklin_constants::COLLECTIONID_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &args.second_token_id_hi.unwrap(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &args.second_token_id_lo.unwrap(),
+            franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+        );
+        for fee in &[args.fee, args.second_fee] {
+            append_be_fixed_width(
+                &mut pubdata_bits,
+                &fee.unwrap(),
+                franklin_constants::FEE_MANTISSA_BIT_WIDTH
+                    + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+            );
+        }
+        pubdata_bits.resize(SwapOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH, false);
+        pubdata_bits
+    }
+
+    fn calculate_operations(&self, input: (SigDataInput, SigDataInput)) -> Vec<Operation<Bn256>> {
+        let (input_a, input_b) = input;
+        let pubdata_chunks: Vec<_> = self
+            .get_pubdata()
+            .chunks(64)
+            .map(|x| le_bit_vector_into_field_element(&x.to_vec()))
+            .collect();
+
+        let operation = |chunk: usize,
+                         new_root: Option<Fr>,
+                         args: &OperationArguments<Bn256>,
+                         lhs: &OperationBranch<Bn256>,
+                         rhs: &OperationBranch<Bn256>| {
+            // the token of `b` is only given away with the signature of `b`
+            let input = if chunk == 1 { &input_b } else { &input_a };
+            Operation {
+                new_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&chunk.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunks[chunk]),
+                first_sig_msg: Some(input.first_sig_msg),
+                second_sig_msg: Some(input.second_sig_msg),
+                third_sig_msg: Some(input.third_sig_msg),
+                signature_data: input.signature.clone(),
+                signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
+                args: args.clone(),
+                lhs: lhs.clone(),
+                rhs: rhs.clone(),
+            }
+        };
+
+        let mut operations = Vec::with_capacity(SwapOp::CHUNKS);
+        for (i, step) in self.steps.iter().enumerate() {
+            operations.push(operation(
+                i,
+                step.after_root,
+                &step.args,
+                &step.lhs,
+                &step.rhs,
+            ));
+        }
+        let last_step = self.steps.last().expect("swap steps");
+        for i in operations.len()..SwapOp::CHUNKS {
+            operations.push(operation(
+                i,
+                last_step.after_root,
+                &last_step.args,
+                &self.lhs_after,
+                &self.rhs_after,
+            ));
+        }
+        operations
+    }
+}
+
+impl SwapWitness<Bn256> {
+    fn apply_data(tree: &mut CircuitAccountTree, swap: &SwapData) -> Self {
+        let capacity = tree.capacity();
+        assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
+
+        let fee_encoded = |fee: u128| -> Fr {
+            let fee_bits = convert_to_float(
+                fee,
+                franklin_constants::FEE_EXPONENT_BIT_WIDTH,
+                franklin_constants::FEE_MANTISSA_BIT_WIDTH,
+                10,
+            )
+            .unwrap();
+            le_bit_vector_into_field_element(&fee_bits)
+        };
+        let charge_fee = |fee: u128| {
+            let fee = Fr::from_str(&fee.to_string()).unwrap();
+            move |acc: &mut CircuitAccount<Bn256>| {
+                acc.balance.sub_assign(&fee);
+                acc.nonce.add_assign(&Fr::from_str("1").unwrap());
+            }
+        };
+
+        let before_root = tree.root_hash();
+        debug!("Swap initial root = {}", before_root);
+
+        let slot_a = get_token_slot(tree, swap.account_a, &swap.token_a);
+        let content_hash_a = get_token_content_hash(tree, swap.account_a, slot_a);
+        let slot_b = get_token_slot(tree, swap.account_b, &swap.token_b);
+        let content_hash_b = get_token_content_hash(tree, swap.account_b, slot_b);
+
+        // every step carries both orders, the second one in the `second_*` fields
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&swap.token_a);
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&content_hash_a);
+        let (second_collection_id, second_token_id_hi, second_token_id_lo) =
+            nft_limbs(&swap.token_b);
+        let (second_content_hash_hi, second_content_hash_lo) = content_hash_limbs(&content_hash_b);
+        let list_a = nfts_list_tree(&swap.tokens_buy_a);
+        let list_b = nfts_list_tree(&swap.tokens_buy_b);
+        let args = OperationArguments {
+            eth_address: Some(Fr::zero()),
+            fee: Some(fee_encoded(swap.fee_a)),
+            full_amount: Some(Fr::zero()),
+            pub_nonce: Some(Fr::zero()),
+            new_pub_key_hash: Some(Fr::zero()),
+            collection_id: Some(collection_id),
+            token_id_hi: Some(token_id_hi),
+            token_id_lo: Some(token_id_lo),
+            content_hash_hi: Some(content_hash_hi),
+            content_hash_lo: Some(content_hash_lo),
+            operator_pub_key_hash: Some(Fr::zero()),
+            operator_id: Some(Fr::zero()),
+            is_operator: Some(Fr::zero()),
+            operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+            approved: Some(Fr::zero()),
+            valid_from: Some(Fr::zero()),
+            valid_until: Some(Fr::zero()),
+            fee_payer: Some(Fr::zero()),
+            tokens_count: Some(Fr::from_str(&swap.tokens_buy_a.len().to_string()).unwrap()),
+            tokens_root: Some(list_a.root_hash()),
+            token_index: Some(Fr::zero()),
+            token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+            second_collection_id: Some(second_collection_id),
+            second_token_id_hi: Some(second_token_id_hi),
+            second_token_id_lo: Some(second_token_id_lo),
+            second_content_hash_hi: Some(second_content_hash_hi),
+            second_content_hash_lo: Some(second_content_hash_lo),
+            second_fee: Some(fee_encoded(swap.fee_b)),
+            second_tokens_count: Some(Fr::from_str(&swap.tokens_buy_b.len().to_string()).unwrap()),
+            second_tokens_root: Some(list_b.root_hash()),
+        };
+        // added token comes with the audit path to it in the token list of its recipient
+        let accepted_token_args = |list: &CircuitTokenTree, tokens_buy: &[Nft], token: &Nft| {
+            let token_index = tokens_buy
+                .iter()
+                .position(|accepted| accepted == token)
+                .expect("token is not accepted by the order");
+            OperationArguments {
+                token_index: Some(Fr::from_str(&token_index.to_string()).unwrap()),
+                token_list_path: list
+                    .merkle_path(token_index as u32)
+                    .into_iter()
+                    .map(|e| Some(e.0))
+                    .collect(),
+                ..args.clone()
+            }
+        };
+
+        let mut steps = Vec::with_capacity(4);
+
+        let lhs = get_current_branch(tree, swap.account_a, slot_a);
+        let rhs = get_current_branch(tree, swap.account_b, slot_a);
+        apply_leaf_operation(
+            tree,
+            swap.account_a,
+            None,
+            Some(slot_a),
+            charge_fee(swap.fee_a),
+        );
+        steps.push(SwapStepWitness {
+            lhs,
+            rhs,
+            args: args.clone(),
+            after_root: Some(tree.root_hash()),
+        });
+
+        let lhs = get_current_branch(tree, swap.account_a, slot_b);
+        let rhs = get_current_branch(tree, swap.account_b, slot_b);
+        apply_leaf_operation(
+            tree,
+            swap.account_b,
+            None,
+            Some(slot_b),
+            charge_fee(swap.fee_b),
+        );
+        steps.push(SwapStepWitness {
+            lhs,
+            rhs,
+            args: args.clone(),
+            after_root: Some(tree.root_hash()),
+        });
+
+        let slot = get_free_token_slot(tree, swap.account_b);
+        let lhs = get_current_branch(tree, swap.account_a, slot);
+        let rhs = get_current_branch(tree, swap.account_b, slot);
+        apply_leaf_operation(
+            tree,
+            swap.account_b,
+            Some((slot, swap.token_a, content_hash_a)),
+            None,
+            |_| {},
+        );
+        steps.push(SwapStepWitness {
+            lhs,
+            rhs,
+            args: accepted_token_args(&list_b, &swap.tokens_buy_b, &swap.token_a),
+            after_root: Some(tree.root_hash()),
+        });
+
+        let slot = get_free_token_slot(tree, swap.account_a);
+        let lhs = get_current_branch(tree, swap.account_b, slot);
+        let rhs = get_current_branch(tree, swap.account_a, slot);
+        apply_leaf_operation(
+            tree,
+            swap.account_a,
+            Some((slot, swap.token_b, content_hash_b)),
+            None,
+            |_| {},
+        );
+        steps.push(SwapStepWitness {
+            lhs,
+            rhs,
+            args: accepted_token_args(&list_a, &swap.tokens_buy_a, &swap.token_b),
+            after_root: Some(tree.root_hash()),
+        });
+        debug!("Swap final root = {}", tree.root_hash());
+
+        SwapWitness {
+            steps,
+            lhs_after: get_current_branch(tree, swap.account_a, slot),
+            rhs_after: get_current_branch(tree, swap.account_b, slot),
+            before_root: Some(before_root),
+            tx_type: Some(Fr::from_str(&SwapOp::OP_CODE.to_string()).unwrap()),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::ApproveOp, Address, Approve, ContentHash, Nft, TokenId};
+// Local deps
+use crate::witness::{
+    approve::ApproveWitness,
+    tests::test_utils::{corrupted_input_test_scenario, generic_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+/// Creates an account holding a token and the account of the operator.
+fn accounts_with_token(balance: u64) -> (Vec<WitnessTestAccount>, Nft) {
+    let mut accounts = vec![
+        WitnessTestAccount::new(1, balance),
+        WitnessTestAccount::new_empty(2),
+    ];
+    let token = Nft::new(1, TokenId::from(1));
+    accounts[0]
+        .account
+        .add_token(token, ContentHash::zero())
+        .expect("token subtree is full");
+    (accounts, token)
+}
+
+/// Approves the second account for the token, or revokes the approval if there is no operator.
+fn approve_op(
+    account: &WitnessTestAccount,
+    operator: Option<&WitnessTestAccount>,
+    token: Nft,
+    fee: u64,
+) -> ApproveOp {
+    ApproveOp {
+        tx: Approve::new_signed(
+            account.id,
+            account.account.address,
+            operator.map_or_else(Address::zero, |operator| operator.account.address),
+            token.collection_id,
+            token.token_id,
+            BigDecimal::from(fee),
+            account.account.nonce,
+            &account.zksync_account.private_key,
+        )
+        .expect("approve creation failed"),
+        account_id: account.id,
+        operator_id: operator.map(|operator| operator.id),
+    }
+}
+
+/// Basic check for execution of `Approve` operation in circuit.
+/// Here we create an account and approve another account for its token.
+#[test]
+#[ignore]
+fn test_approve() {
+    // Test vector of (initial_balance, fee_amount).
+    let test_vector = vec![
+        (10, 3),                // Basic approve
+        (0, 0),                 // Zero fee
+        (std::u64::MAX, 10000), // Very big fee
+    ];
+
+    for (initial_balance, fee_amount) in test_vector {
+        // Input data.
+        let (accounts, token) = accounts_with_token(initial_balance);
+        let approve_op = approve_op(&accounts[0], Some(&accounts[1]), token, fee_amount);
+
+        // Additional data required for performing the operation.
+        let input =
+            SigDataInput::from_approve_op(&approve_op).expect("SigDataInput creation failed");
+
+        generic_test_scenario::<ApproveWitness<Bn256>, _>(
+            &accounts,
+            approve_op,
+            input,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_approve_op(&op)
+                    .expect("approve should be success");
+                vec![fee]
+            },
+        );
+    }
+}
+
+/// Checks that the revoke of the approval is executed in circuit.
+#[test]
+#[ignore]
+fn test_approve_revoke() {
+    // Input data.
+    let (accounts, token) = accounts_with_token(10);
+    let approve_op = approve_op(&accounts[0], None, token, 3);
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_approve_op(&approve_op).expect("SigDataInput creation failed");
+
+    generic_test_scenario::<ApproveWitness<Bn256>, _>(
+        &accounts,
+        approve_op,
+        input,
+        |plasma_state, op| {
+            let (fee, _) = plasma_state
+                .apply_approve_op(&op)
+                .expect("revoke should be success");
+            vec![fee]
+        },
+    );
+}
+
+/// Checks that corrupted signature data leads to unsatisfied constraints in circuit.
+#[test]
+#[ignore]
+fn corrupted_ops_input() {
+    // Incorrect signature data will lead to `op_valid` constraint failure.
+    // See `circuit.rs` for details.
+    const EXPECTED_PANIC_MSG: &str = "op_valid is true";
+
+    // Legit input data.
+    let (accounts, token) = accounts_with_token(10);
+    let approve_op = approve_op(&accounts[0], Some(&accounts[1]), token, 3);
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_approve_op(&approve_op).expect("SigDataInput creation failed");
+
+    // Test vector with values corrupted one by one.
+    let test_vector = input.corrupted_variations();
+
+    for input in test_vector {
+        corrupted_input_test_scenario::<ApproveWitness<Bn256>, _>(
+            &accounts,
+            approve_op.clone(),
+            input,
+            EXPECTED_PANIC_MSG,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_approve_op(&op)
+                    .expect("approve should be success");
+                vec![fee]
+            },
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::ApproveForAllOp, ApproveForAll};
+// Local deps
+use crate::witness::{
+    approve_for_all::ApproveForAllWitness,
+    tests::test_utils::{corrupted_input_test_scenario, generic_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+fn approve_for_all_op(
+    account: &WitnessTestAccount,
+    operator: &WitnessTestAccount,
+    approved: bool,
+    fee: u64,
+) -> ApproveForAllOp {
+    ApproveForAllOp {
+        tx: ApproveForAll::new_signed(
+            account.id,
+            account.account.address,
+            operator.account.address,
+            approved,
+            BigDecimal::from(fee),
+            account.account.nonce,
+            &account.zksync_account.private_key,
+        )
+        .expect("approve for all creation failed"),
+        account_id: account.id,
+        operator_id: operator.id,
+    }
+}
+
+/// Basic check for execution of `ApproveForAll` operation in circuit.
+/// Here we create two accounts and approve the second one for all the tokens of the first one.
+#[test]
+#[ignore]
+fn test_approve_for_all() {
+    // Test vector of (initial_balance, fee_amount, approved).
+    let test_vector = vec![
+        (10, 3, true),                // Basic approve
+        (0, 0, true),                 // Zero fee
+        (std::u64::MAX, 10000, true), // Very big fee
+        (10, 3, false),               // Revoke of a missing approval
+    ];
+
+    for (initial_balance, fee_amount, approved) in test_vector {
+        // Input data.
+        let accounts = vec![
+            WitnessTestAccount::new(1, initial_balance),
+            WitnessTestAccount::new_empty(2),
+        ];
+        let approve_for_all_op =
+            approve_for_all_op(&accounts[0], &accounts[1], approved, fee_amount);
+
+        // Additional data required for performing the operation.
+        let input = SigDataInput::from_approve_for_all_op(&approve_for_all_op)
+            .expect("SigDataInput creation failed");
+
+        generic_test_scenario::<ApproveForAllWitness<Bn256>, _>(
+            &accounts,
+            approve_for_all_op,
+            input,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_approve_for_all_op(&op)
+                    .expect("approve for all should be success");
+                vec![fee]
+            },
+        );
+    }
+}
+
+/// Checks that corrupted signature data leads to unsatisfied constraints in circuit.
+#[test]
+#[ignore]
+fn corrupted_ops_input() {
+    // Incorrect signature data will lead to `op_valid` constraint failure.
+    // See `circuit.rs` for details.
+    const EXPECTED_PANIC_MSG: &str = "op_valid is true";
+
+    // Legit input data.
+    let accounts = vec![
+        WitnessTestAccount::new(1, 10),
+        WitnessTestAccount::new_empty(2),
+    ];
+    let approve_for_all_op = approve_for_all_op(&accounts[0], &accounts[1], true, 3);
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_approve_for_all_op(&approve_for_all_op)
+        .expect("SigDataInput creation failed");
+
+    // Test vector with values corrupted one by one.
+    let test_vector = input.corrupted_variations();
+
+    for input in test_vector {
+        corrupted_input_test_scenario::<ApproveForAllWitness<Bn256>, _>(
+            &accounts,
+            approve_for_all_op.clone(),
+            input,
+            EXPECTED_PANIC_MSG,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_approve_for_all_op(&op)
+                    .expect("approve for all should be success");
+                vec![fee]
+            },
+        );
+    }
+}
@@ -0,0 +1,109 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::BatchTransferOp, BatchTransfer, ContentHash, Nft, TokenId};
+use plasma::state::CollectedFee;
+// Local deps
+use crate::witness::{
+    batch_transfer::BatchTransferWitness,
+    tests::test_utils::{generic_test_scenario, incorrect_op_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+/// Creates a sender holding `tokens_count` tokens and an empty recipient.
+fn accounts_with_tokens(tokens_count: u64) -> (Vec<WitnessTestAccount>, Vec<Nft>) {
+    let mut accounts = vec![
+        WitnessTestAccount::new(1, 10),
+        WitnessTestAccount::new_empty(2),
+    ];
+    let tokens: Vec<_> = (0..tokens_count)
+        .map(|i| Nft::new(1, TokenId::from(i + 1)))
+        .collect();
+    for token in &tokens {
+        accounts[0]
+            .account
+            .add_token(*token, ContentHash::zero())
+            .expect("sender token subtree is full");
+    }
+    (accounts, tokens)
+}
+
+fn batch_transfer_op(
+    accounts: &[WitnessTestAccount],
+    tokens: Vec<Nft>,
+    fee: u64,
+) -> BatchTransferOp {
+    let (account_from, account_to) = (&accounts[0], &accounts[1]);
+    BatchTransferOp {
+        tx: BatchTransfer::new_signed(
+            account_from.id,
+            account_from.account.address,
+            account_to.account.address,
+            tokens,
+            BigDecimal::from(fee),
+            account_from.account.nonce,
+            &account_from.zksync_account.private_key,
+        )
+        .expect("batch transfer creation failed"),
+        from: account_from.id,
+        to: account_to.id,
+    }
+}
+
+/// Basic check for execution of `BatchTransfer` operation in circuit.
+/// Here we create two accounts and move the tokens of the first one to the second one.
+#[test]
+#[ignore]
+fn test_batch_transfer_success() {
+    // Test vector of (tokens_count, fee_amount).
+    let test_vector = vec![
+        (1, 3), // Single token
+        (3, 3), // Several tokens
+        (2, 0), // Zero fee
+    ];
+
+    for (tokens_count, fee_amount) in test_vector {
+        // Input data.
+        let (accounts, tokens) = accounts_with_tokens(tokens_count);
+        let batch_transfer_op = batch_transfer_op(&accounts, tokens, fee_amount);
+
+        // Additional data required for performing the operation.
+        let input = SigDataInput::from_batch_transfer_op(&batch_transfer_op)
+            .expect("SigDataInput creation failed");
+
+        generic_test_scenario::<BatchTransferWitness<Bn256>, _>(
+            &accounts,
+            batch_transfer_op,
+            input,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_batch_transfer_op(&op)
+                    .expect("batch transfer should be success");
+                vec![fee]
+            },
+        );
+    }
+}
+
+/// Checks that the batch transfer can't charge more fee than the sender has.
+#[test]
+#[ignore]
+fn test_batch_transfer_fee_exceeds_balance() {
+    // Operation is not valid, since the balance of the sender does not cover the fee.
+    const ERR_MSG: &str = "op_valid is true/enforce equal to one";
+
+    let (accounts, tokens) = accounts_with_tokens(2);
+    let batch_transfer_op = batch_transfer_op(&accounts, tokens, 11);
+
+    let input = SigDataInput::from_batch_transfer_op(&batch_transfer_op)
+        .expect("SigDataInput creation failed");
+
+    incorrect_op_test_scenario::<BatchTransferWitness<Bn256>, _>(
+        &accounts,
+        batch_transfer_op,
+        input,
+        ERR_MSG,
+        || vec![CollectedFee { amount: 11.into() }],
+    );
+}
@@ -0,0 +1,106 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::BurnOp, Burn, ContentHash, Nft, TokenId};
+// Local deps
+use crate::witness::{
+    burn::BurnWitness,
+    tests::test_utils::{corrupted_input_test_scenario, generic_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+/// Creates an account holding a token.
+fn account_with_token(balance: u64) -> (Vec<WitnessTestAccount>, Nft) {
+    let mut accounts = vec![WitnessTestAccount::new(1, balance)];
+    let token = Nft::new(1, TokenId::from(1));
+    accounts[0]
+        .account
+        .add_token(token, ContentHash::zero())
+        .expect("token subtree is full");
+    (accounts, token)
+}
+
+fn burn_op(account: &WitnessTestAccount, token: Nft, fee: u64) -> BurnOp {
+    BurnOp {
+        tx: Burn::new_signed(
+            account.id,
+            account.account.address,
+            token.collection_id,
+            token.token_id,
+            BigDecimal::from(fee),
+            account.account.nonce,
+            &account.zksync_account.private_key,
+        )
+        .expect("burn creation failed"),
+        account_id: account.id,
+    }
+}
+
+/// Basic check for execution of `Burn` operation in circuit.
+/// Here we create an account and burn its token.
+#[test]
+#[ignore]
+fn test_burn() {
+    // Test vector of (initial_balance, fee_amount).
+    let test_vector = vec![
+        (10, 3),                // Basic burn
+        (0, 0),                 // Zero fee
+        (std::u64::MAX, 10000), // Very big fee
+    ];
+
+    for (initial_balance, fee_amount) in test_vector {
+        // Input data.
+        let (accounts, token) = account_with_token(initial_balance);
+        let burn_op = burn_op(&accounts[0], token, fee_amount);
+
+        // Additional data required for performing the operation.
+        let input = SigDataInput::from_burn_op(&burn_op).expect("SigDataInput creation failed");
+
+        generic_test_scenario::<BurnWitness<Bn256>, _>(
+            &accounts,
+            burn_op,
+            input,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_burn_op(&op)
+                    .expect("burn should be success");
+                vec![fee]
+            },
+        );
+    }
+}
+
+/// Checks that corrupted signature data leads to unsatisfied constraints in circuit.
+#[test]
+#[ignore]
+fn corrupted_ops_input() {
+    // Incorrect signature data will lead to `op_valid` constraint failure.
+    // See `circuit.rs` for details.
+    const EXPECTED_PANIC_MSG: &str = "op_valid is true";
+
+    // Legit input data.
+    let (accounts, token) = account_with_token(10);
+    let burn_op = burn_op(&accounts[0], token, 3);
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_burn_op(&burn_op).expect("SigDataInput creation failed");
+
+    // Test vector with values corrupted one by one.
+    let test_vector = input.corrupted_variations();
+
+    for input in test_vector {
+        corrupted_input_test_scenario::<BurnWitness<Bn256>, _>(
+            &accounts,
+            burn_op.clone(),
+            input,
+            EXPECTED_PANIC_MSG,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_burn_op(&op)
+                    .expect("burn should be success");
+                vec![fee]
+            },
+        );
+    }
+}
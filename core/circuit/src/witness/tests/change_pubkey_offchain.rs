@@ -66,11 +66,6 @@ fn test_incorrect_change_pubkey_account() {
         change_pkhash_op,
         (),
         ERR_MSG,
-        || {
-            vec![CollectedFee {
-                token: 0,
-                amount: 0.into(),
-            }]
-        },
+        || vec![CollectedFee { amount: 0.into() }],
     );
 }
@@ -0,0 +1,64 @@
+// External deps
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::operations::CloseOp;
+use plasma::state::CollectedFee;
+// Local deps
+use crate::witness::{
+    close_account::CloseAccountWitness,
+    tests::test_utils::{generic_test_scenario, incorrect_op_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+/// Checks that an account without tokens and ETH can be closed.
+#[test]
+#[ignore]
+fn test_close_account() {
+    // Input data.
+    let accounts = vec![WitnessTestAccount::new_empty(1)];
+    let account = &accounts[0];
+    let close_op = CloseOp {
+        tx: account.zksync_account.sign_close(None, true),
+        account_id: account.id,
+    };
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_close_op(&close_op).expect("SigDataInput creation failed");
+
+    generic_test_scenario::<CloseAccountWitness<Bn256>, _>(
+        &accounts,
+        close_op,
+        input,
+        |plasma_state, op| {
+            let (fee, _) = plasma_state
+                .apply_close_op(&op)
+                .expect("close should be success");
+            vec![fee]
+        },
+    );
+}
+
+/// Checks that an account holding ETH can't be closed.
+#[test]
+#[ignore]
+fn test_incorrect_close_account_with_balance() {
+    // Operation is not valid, since the account balance is not empty.
+    const ERR_MSG: &str = "op_valid is true/enforce equal to one";
+
+    let accounts = vec![WitnessTestAccount::new(1, 10)];
+    let account = &accounts[0];
+    let close_op = CloseOp {
+        tx: account.zksync_account.sign_close(None, true),
+        account_id: account.id,
+    };
+
+    let input = SigDataInput::from_close_op(&close_op).expect("SigDataInput creation failed");
+
+    incorrect_op_test_scenario::<CloseAccountWitness<Bn256>, _>(
+        &accounts,
+        close_op,
+        input,
+        ERR_MSG,
+        || vec![CollectedFee { amount: 0.into() }],
+    );
+}
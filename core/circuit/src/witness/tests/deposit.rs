@@ -23,8 +23,10 @@ fn test_deposit_in_empty_leaf() {
             token: 0,
             amount: BigDecimal::from(1),
             to: account.account.address,
+            content_hash: Default::default(),
         },
         account_id: account.id,
+        success: true,
     };
 
     generic_test_scenario::<DepositWitness<Bn256>, _>(
@@ -61,8 +63,10 @@ fn test_deposit_existing_account() {
                 token: token_id,
                 amount: BigDecimal::from(token_amount),
                 to: account.account.address,
+                content_hash: Default::default(),
             },
             account_id: account.id,
+            success: true,
         };
 
         generic_test_scenario::<DepositWitness<Bn256>, _>(
@@ -96,8 +100,10 @@ fn test_incorrect_deposit_address() {
             token: TOKEN_ID,
             amount: BigDecimal::from(TOKEN_AMOUNT),
             to: Default::default(),
+            content_hash: Default::default(),
         },
         account_id: account.id,
+        success: true,
     };
 
     // Attempt to apply incorrect operation should result in an assertion failure.
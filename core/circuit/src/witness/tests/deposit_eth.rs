@@ -0,0 +1,66 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::DepositEthOp, DepositEth};
+// Local deps
+use crate::witness::{
+    deposit_eth::DepositEthWitness,
+    tests::test_utils::{generic_test_scenario, WitnessTestAccount},
+};
+
+/// Checks that ETH can be deposited to a new account.
+#[test]
+#[ignore]
+fn test_deposit_eth_in_empty_leaf() {
+    // Input data.
+    let accounts = &[];
+    let account = WitnessTestAccount::new_empty(1); // Will not be included into PlasmaState
+    let deposit_eth_op = DepositEthOp {
+        priority_op: DepositEth {
+            from: account.account.address,
+            amount: BigDecimal::from(1),
+            to: account.account.address,
+        },
+        account_id: account.id,
+    };
+
+    generic_test_scenario::<DepositEthWitness<Bn256>, _>(
+        accounts,
+        deposit_eth_op,
+        (),
+        |plasma_state, op| {
+            plasma_state.apply_deposit_eth_op(op);
+            vec![]
+        },
+    );
+}
+
+/// Checks that ETH deposits are added to the balance of an existing account.
+#[test]
+#[ignore]
+fn test_deposit_eth_existing_account() {
+    for amount in &[0, 1, std::u64::MAX] {
+        // Input data.
+        let accounts = vec![WitnessTestAccount::new(1, 10)];
+        let account = &accounts[0];
+        let deposit_eth_op = DepositEthOp {
+            priority_op: DepositEth {
+                from: account.account.address,
+                amount: BigDecimal::from(*amount),
+                to: account.account.address,
+            },
+            account_id: account.id,
+        };
+
+        generic_test_scenario::<DepositEthWitness<Bn256>, _>(
+            &accounts,
+            deposit_eth_op,
+            (),
+            |plasma_state, op| {
+                plasma_state.apply_deposit_eth_op(op);
+                vec![]
+            },
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// External deps
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{
+    operations::ForcedWithdrawOp, Address, ContentHash, ForcedWithdraw, Nft, TokenId,
+};
+// Local deps
+use crate::witness::{
+    forced_withdraw::ForcedWithdrawWitness,
+    tests::test_utils::{generic_test_scenario, incorrect_op_test_scenario, WitnessTestAccount},
+};
+
+/// Creates an account holding a token.
+fn account_with_token() -> (Vec<WitnessTestAccount>, Nft) {
+    let mut accounts = vec![WitnessTestAccount::new(1, 10)];
+    let token = Nft::new(1, TokenId::from(1));
+    accounts[0]
+        .account
+        .add_token(token, ContentHash::zero())
+        .expect("token subtree is full");
+    (accounts, token)
+}
+
+fn forced_withdraw_op(
+    account: &WitnessTestAccount,
+    eth_address: Address,
+    token: Nft,
+    success: bool,
+) -> ForcedWithdrawOp {
+    ForcedWithdrawOp {
+        priority_op: ForcedWithdraw {
+            account_id: account.id,
+            eth_address,
+            collection_id: token.collection_id,
+            token_id: token.token_id,
+        },
+        success,
+    }
+}
+
+/// Checks that `ForcedWithdraw` takes the token out of the account of the requester.
+#[test]
+#[ignore]
+fn test_forced_withdraw_success() {
+    let (accounts, token) = account_with_token();
+    let op = forced_withdraw_op(&accounts[0], accounts[0].account.address, token, true);
+
+    generic_test_scenario::<ForcedWithdrawWitness<Bn256>, _>(
+        &accounts,
+        op,
+        (),
+        |plasma_state, op| {
+            plasma_state.apply_forced_withdraw_op(&op);
+            vec![]
+        },
+    );
+}
+
+/// Checks that the failed `ForcedWithdraw` leaves the account untouched.
+#[test]
+#[ignore]
+fn test_forced_withdraw_failure() {
+    // Test vector of (requested by the owner, requested token is held).
+    let test_vector = vec![
+        (false, true), // Account does not belong to the requester
+        (true, false), // Account does not hold the token
+    ];
+
+    for (is_owner, is_held) in test_vector {
+        let (accounts, token) = account_with_token();
+        let eth_address = if is_owner {
+            accounts[0].account.address
+        } else {
+            Address::zero()
+        };
+        let token = if is_held {
+            token
+        } else {
+            Nft::new(1, TokenId::from(2))
+        };
+        let op = forced_withdraw_op(&accounts[0], eth_address, token, false);
+
+        generic_test_scenario::<ForcedWithdrawWitness<Bn256>, _>(
+            &accounts,
+            op,
+            (),
+            |plasma_state, op| {
+                plasma_state.apply_forced_withdraw_op(&op);
+                vec![]
+            },
+        );
+    }
+}
+
+/// Checks that the operator can't fail the request of the owner of the token.
+#[test]
+#[ignore]
+fn test_incorrect_forced_withdraw_failure() {
+    // Operation is not valid, since the published flag does not match the account.
+    const ERR_MSG: &str = "op_valid is true/enforce equal to one";
+
+    let (accounts, token) = account_with_token();
+    let op = forced_withdraw_op(&accounts[0], accounts[0].account.address, token, false);
+
+    incorrect_op_test_scenario::<ForcedWithdrawWitness<Bn256>, _>(
+        &accounts,
+        op,
+        (),
+        ERR_MSG,
+        Vec::new,
+    );
+}
@@ -0,0 +1,96 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::MintOp, ContentHash, Mint, TokenId};
+// Local deps
+use crate::witness::{
+    mint::MintWitness,
+    tests::test_utils::{corrupted_input_test_scenario, generic_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+fn mint_op(account: &WitnessTestAccount, token_id: u64, fee: u64) -> MintOp {
+    MintOp {
+        tx: Mint::new_signed(
+            account.id,
+            account.account.address,
+            1,
+            TokenId::from(token_id),
+            ContentHash::zero(),
+            BigDecimal::from(fee),
+            account.account.nonce,
+            &account.zksync_account.private_key,
+        )
+        .expect("mint creation failed"),
+        account_id: account.id,
+    }
+}
+
+/// Basic check for execution of `Mint` operation in circuit.
+/// Here we create an account and mint a token into it.
+#[test]
+#[ignore]
+fn test_mint() {
+    // Test vector of (initial_balance, token_id, fee_amount).
+    let test_vector = vec![
+        (10, 1, 3),                // Basic mint
+        (0, 2, 0),                 // Zero fee
+        (std::u64::MAX, 3, 10000), // Very big fee
+    ];
+
+    for (initial_balance, token_id, fee_amount) in test_vector {
+        // Input data.
+        let accounts = vec![WitnessTestAccount::new(1, initial_balance)];
+        let mint_op = mint_op(&accounts[0], token_id, fee_amount);
+
+        // Additional data required for performing the operation.
+        let input = SigDataInput::from_mint_op(&mint_op).expect("SigDataInput creation failed");
+
+        generic_test_scenario::<MintWitness<Bn256>, _>(
+            &accounts,
+            mint_op,
+            input,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_mint_op(&op)
+                    .expect("mint should be success");
+                vec![fee]
+            },
+        );
+    }
+}
+
+/// Checks that corrupted signature data leads to unsatisfied constraints in circuit.
+#[test]
+#[ignore]
+fn corrupted_ops_input() {
+    // Incorrect signature data will lead to `op_valid` constraint failure.
+    // See `circuit.rs` for details.
+    const EXPECTED_PANIC_MSG: &str = "op_valid is true";
+
+    // Legit input data.
+    let accounts = vec![WitnessTestAccount::new(1, 10)];
+    let mint_op = mint_op(&accounts[0], 1, 3);
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_mint_op(&mint_op).expect("SigDataInput creation failed");
+
+    // Test vector with values corrupted one by one.
+    let test_vector = input.corrupted_variations();
+
+    for input in test_vector {
+        corrupted_input_test_scenario::<MintWitness<Bn256>, _>(
+            &accounts,
+            mint_op.clone(),
+            input,
+            EXPECTED_PANIC_MSG,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_mint_op(&op)
+                    .expect("mint should be success");
+                vec![fee]
+            },
+        );
+    }
+}
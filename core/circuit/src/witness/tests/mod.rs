@@ -28,10 +28,19 @@ use crate::{
     },
 };
 
+mod approve;
+mod approve_for_all;
+mod batch_transfer;
+mod burn;
 mod change_pubkey_offchain;
+mod close_account;
 mod deposit;
+mod deposit_eth;
+mod forced_withdraw;
 mod full_exit;
+mod mint;
 mod noop;
+mod swap;
 pub(crate) mod test_utils;
 mod transfer;
 mod transfer_to_new;
@@ -71,8 +80,10 @@ fn apply_many_ops() -> FranklinCircuit<'static, Bn256> {
                 token: *token_id,
                 amount: BigDecimal::from(*token_amount),
                 to: account.account.address,
+                content_hash: Default::default(),
             },
             account_id: account.id,
+            success: true,
         });
 
     // Transfer ETH to an existing account.
@@ -141,7 +141,7 @@ fn test_noop() {
 
     // Perform the `noop` operation and collect the data required for circuit instance creation.
     let operation = noop_operation(&tree, validator_address_number);
-    let (_, validator_account_witness) = apply_fee(&mut tree, validator_address_number, 0, 0);
+    let (_, validator_account_witness) = apply_fee(&mut tree, validator_address_number, 0);
     let (validator_audit_path, _) = get_audits(&tree, validator_address_number, 0);
 
     let public_data_commitment = public_data_commitment::<Bn256>(
@@ -212,7 +212,7 @@ fn incorrect_circuit_pubdata() {
 
     // Perform the `noop` operation and collect the data required for circuit instance creation.
     let operation = noop_operation(&tree, validator_address_number);
-    let (_, validator_account_witness) = apply_fee(&mut tree, validator_address_number, 0, 0);
+    let (_, validator_account_witness) = apply_fee(&mut tree, validator_address_number, 0);
     let (validator_audit_path, _) = get_audits(&tree, validator_address_number, 0);
 
     let correct_hash = tree.root_hash();
@@ -0,0 +1,123 @@
+// External deps
+use bigdecimal::BigDecimal;
+use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
+// Workspace deps
+use models::node::{operations::SwapOp, ContentHash, Nft, Order, Swap, TokenId};
+use plasma::state::CollectedFee;
+// Local deps
+use crate::witness::{
+    swap::SwapWitness,
+    tests::test_utils::{generic_test_scenario, incorrect_op_test_scenario, WitnessTestAccount},
+    utils::SigDataInput,
+};
+
+/// Creates two accounts holding a token each.
+fn accounts_with_tokens() -> (Vec<WitnessTestAccount>, Nft, Nft) {
+    let mut accounts = vec![
+        WitnessTestAccount::new(1, 10),
+        WitnessTestAccount::new(2, 10),
+    ];
+    let token_a = Nft::new(1, TokenId::from(1));
+    let token_b = Nft::new(2, TokenId::from(1));
+    for (account, token) in accounts.iter_mut().zip(&[token_a, token_b]) {
+        account
+            .account
+            .add_token(*token, ContentHash::zero())
+            .expect("token subtree is full");
+    }
+    (accounts, token_a, token_b)
+}
+
+fn order(account: &WitnessTestAccount, token_sell: Nft, tokens_buy: Vec<Nft>, fee: u64) -> Order {
+    Order::new_signed(
+        account.id,
+        account.account.address,
+        token_sell,
+        tokens_buy,
+        BigDecimal::from(fee),
+        account.account.nonce,
+        &account.zksync_account.private_key,
+    )
+    .expect("order creation failed")
+}
+
+fn swap_op(
+    accounts: &[WitnessTestAccount],
+    (token_a, tokens_buy_a, fee_a): (Nft, Vec<Nft>, u64),
+    (token_b, tokens_buy_b, fee_b): (Nft, Vec<Nft>, u64),
+) -> SwapOp {
+    let (account_a, account_b) = (&accounts[0], &accounts[1]);
+    SwapOp {
+        tx: Swap::new(
+            order(account_a, token_a, tokens_buy_a, fee_a),
+            order(account_b, token_b, tokens_buy_b, fee_b),
+        ),
+        account_a: account_a.id,
+        account_b: account_b.id,
+    }
+}
+
+/// Basic check for execution of `Swap` operation in circuit.
+/// Here we create two accounts and exchange their tokens.
+#[test]
+#[ignore]
+fn test_swap_success() {
+    // Test vector of (fee_a, fee_b, accepts several tokens).
+    let test_vector = vec![
+        (3, 2, false), // Single accepted token
+        (3, 2, true),  // Several accepted tokens
+        (0, 0, false), // Zero fees
+    ];
+
+    for (fee_a, fee_b, several_tokens) in test_vector {
+        // Input data.
+        let (accounts, token_a, token_b) = accounts_with_tokens();
+        let mut tokens_buy_a = vec![token_b];
+        let mut tokens_buy_b = vec![token_a];
+        if several_tokens {
+            tokens_buy_a.insert(0, Nft::new(3, TokenId::from(1)));
+            tokens_buy_b.push(Nft::new(3, TokenId::from(2)));
+        }
+        let swap_op = swap_op(
+            &accounts,
+            (token_a, tokens_buy_a, fee_a),
+            (token_b, tokens_buy_b, fee_b),
+        );
+
+        // Additional data required for performing the operation.
+        let input = SigDataInput::from_swap_op(&swap_op).expect("SigDataInput creation failed");
+
+        generic_test_scenario::<SwapWitness<Bn256>, _>(
+            &accounts,
+            swap_op,
+            input,
+            |plasma_state, op| {
+                let (fee, _) = plasma_state
+                    .apply_swap_op(&op)
+                    .expect("swap should be success");
+                vec![fee]
+            },
+        );
+    }
+}
+
+/// Checks that the second account can't pay more fee than it has.
+#[test]
+#[ignore]
+fn test_swap_second_fee_exceeds_balance() {
+    // Operation is not valid, since the balance of the second account does not cover its fee.
+    const ERR_MSG: &str = "op_valid is true/enforce equal to one";
+
+    let (accounts, token_a, token_b) = accounts_with_tokens();
+    let swap_op = swap_op(
+        &accounts,
+        (token_a, vec![token_b], 3),
+        (token_b, vec![token_a], 11),
+    );
+
+    let input = SigDataInput::from_swap_op(&swap_op).expect("SigDataInput creation failed");
+
+    incorrect_op_test_scenario::<SwapWitness<Bn256>, _>(&accounts, swap_op, input, ERR_MSG, || {
+        vec![CollectedFee { amount: 14.into() }]
+    });
+}
@@ -2,7 +2,7 @@
 use bigdecimal::BigDecimal;
 use crypto_exports::franklin_crypto::bellman::pairing::bn256::Bn256;
 // Workspace deps
-use models::node::operations::TransferOp;
+use models::node::{operations::TransferOp, ContentHash, Nft, Sponsorship, TokenId, Transfer};
 use plasma::state::CollectedFee;
 // Local deps
 use crate::witness::{
@@ -209,7 +209,6 @@ fn test_incorrect_transfer_account_from() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
@@ -263,7 +262,6 @@ fn test_incorrect_transfer_account_to() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
@@ -322,7 +320,6 @@ fn test_incorrect_transfer_amount() {
             ERR_MSG,
             || {
                 vec![CollectedFee {
-                    token: TOKEN_ID,
                     amount: fee_amount.into(),
                 }]
             },
@@ -383,9 +380,68 @@ fn test_transfer_replay() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
     );
 }
+
+/// Checks that the fee of a sponsored transfer is charged from the sponsor,
+/// so the sender does not need any balance.
+#[test]
+#[ignore]
+fn test_sponsored_transfer() {
+    const FEE_AMOUNT: u64 = 3;
+
+    // Input data.
+    let mut accounts = vec![
+        WitnessTestAccount::new(1, 0),
+        WitnessTestAccount::new_empty(2),
+        WitnessTestAccount::new(3, 10),
+    ];
+    let token = Nft::new(1, TokenId::from(1));
+    accounts[0]
+        .account
+        .add_token(token, ContentHash::zero())
+        .expect("token subtree is full");
+    let (account_from, account_to, sponsor) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    let mut transfer = Transfer::new_signed(
+        account_from.id,
+        account_from.account.address,
+        account_to.account.address,
+        token.collection_id,
+        token.token_id,
+        BigDecimal::from(FEE_AMOUNT),
+        account_from.account.nonce,
+        None,
+        None,
+        &account_from.zksync_account.private_key,
+    )
+    .expect("transfer creation failed");
+    transfer.sponsor = Some(Sponsorship::new_signed(
+        &transfer.get_bytes(),
+        sponsor.id,
+        &sponsor.zksync_account.private_key,
+    ));
+    let transfer_op = TransferOp {
+        tx: transfer,
+        from: account_from.id,
+        to: account_to.id,
+    };
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_transfer_op(&transfer_op).expect("SigDataInput creation failed");
+
+    generic_test_scenario::<TransferWitness<Bn256>, _>(
+        &accounts,
+        transfer_op,
+        input,
+        |plasma_state, op| {
+            let (fee, _) = plasma_state
+                .apply_transfer_op(&op)
+                .expect("transfer should be success");
+            vec![fee]
+        },
+    );
+}
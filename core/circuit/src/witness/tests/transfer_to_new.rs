@@ -167,7 +167,6 @@ fn test_incorrect_transfer_account_from() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
@@ -222,7 +221,6 @@ fn test_incorrect_transfer_account_to() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
@@ -280,7 +278,6 @@ fn test_incorrect_transfer_amount() {
             ERR_MSG,
             || {
                 vec![CollectedFee {
-                    token: TOKEN_ID,
                     amount: fee_amount.into(),
                 }]
             },
@@ -344,7 +341,6 @@ fn test_transfer_replay() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
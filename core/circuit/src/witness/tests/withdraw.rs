@@ -157,7 +157,6 @@ fn test_incorrect_withdraw_account_from() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
@@ -212,7 +211,6 @@ fn test_incorrect_withdraw_amount() {
             ERR_MSG,
             || {
                 vec![CollectedFee {
-                    token: TOKEN_ID,
                     amount: fee_amount.into(),
                 }]
             },
@@ -274,7 +272,6 @@ fn test_withdraw_replay() {
         ERR_MSG,
         || {
             vec![CollectedFee {
-                token: TOKEN_ID,
                 amount: FEE_AMOUNT.into(),
             }]
         },
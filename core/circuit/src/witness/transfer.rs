@@ -12,7 +12,7 @@ use models::{
         account::CircuitAccountTree,
         utils::{append_be_fixed_width, le_bit_vector_into_field_element},
     },
-    node::operations::TransferOp,
+    node::{operations::TransferOp, AccountId, Nft, TokenSlot},
     params as franklin_constants,
     primitives::{big_decimal_to_u128, convert_to_float},
 };
@@ -20,7 +20,12 @@ use models::{
 use crate::{
     operation::{Operation, OperationArguments, OperationBranch, OperationBranchWitness},
     witness::{
-        utils::{apply_leaf_operation, get_audits, SigDataInput},
+        utils::{
+            append_nft_be, apply_leaf_operation, charge_sponsor, content_hash_limbs, get_audits,
+            get_current_branch, get_free_token_slot, get_operator_audit, get_operator_pub_key_hash,
+            get_signing_operator, get_token_content_hash, get_token_slot, get_token_witness,
+            nft_limbs, SigDataInput,
+        },
         Witness,
     },
 };
@@ -29,9 +34,20 @@ use crate::{
 pub struct TransferData {
     //pub amount: u128,
     pub fee: u128,
-    pub token_id: u32,
+    pub token: Nft,
+    pub from_token_slot: TokenSlot,
+    pub to_token_slot: TokenSlot,
     pub from_account_address: u32,
     pub to_account_address: u32,
+    /// Approved operator who signed the transfer instead of the sender.
+    pub operator: Option<AccountId>,
+    pub operator_pub_key_hash: Fr,
+    pub valid_from: u32,
+    pub valid_until: u32,
+    /// Account paying the fee, differs from the sender for a sponsored transfer.
+    pub fee_payer: u32,
+    /// Signature of the sponsor, checked in the chunk `TransferOp::SPONSOR_CHUNK`.
+    pub sponsor_sig_data: Option<SigDataInput>,
 }
 
 pub struct TransferWitness<E: RescueEngine> {
@@ -41,10 +57,21 @@ pub struct TransferWitness<E: RescueEngine> {
     pub to_before: OperationBranch<E>,
     pub to_intermediate: OperationBranch<E>,
     pub to_after: OperationBranch<E>,
+    /// Branch opened by the third chunk, the one of the operator who signed the transfer
+    /// or of the recipient if the sender signed it.
+    pub operator_after: OperationBranch<E>,
+    /// Branch opened by the chunk `TransferOp::SPONSOR_CHUNK`, the one of the sponsor before
+    /// it pays the fee or of the recipient if the transfer is not sponsored.
+    pub sponsor_before: OperationBranch<E>,
+    /// Branch of the recipient opened by the chunks after the sponsor one.
+    pub to_final: OperationBranch<E>,
+    pub sponsor_sig_data: Option<SigDataInput>,
     pub args: OperationArguments<E>,
     pub before_root: Option<E::Fr>,
     pub intermediate_root: Option<E::Fr>,
     pub after_root: Option<E::Fr>,
+    /// Root after the sponsor pays the fee, same as `after_root` if the transfer is not sponsored.
+    pub final_root: Option<E::Fr>,
     pub tx_type: Option<E::Fr>,
 }
 
@@ -53,12 +80,33 @@ impl Witness for TransferWitness<Bn256> {
     type CalculateOpsInput = SigDataInput;
 
     fn apply_tx(tree: &mut CircuitAccountTree, transfer: &TransferOp) -> Self {
+        let from_token_slot = get_token_slot(tree, transfer.from, &transfer.tx.nft());
         let transfer_data = TransferData {
             //amount: big_decimal_to_u128(&transfer.tx.amount),
             fee: big_decimal_to_u128(&transfer.tx.fee),
-            token_id: u32::from(transfer.tx.token_id),
+            token: transfer.tx.nft(),
+            from_token_slot,
+            to_token_slot: get_free_token_slot(tree, transfer.to),
             from_account_address: transfer.from,
             to_account_address: transfer.to,
+            valid_from: transfer.tx.valid_from.unwrap_or(0),
+            valid_until: transfer.tx.valid_until.unwrap_or(u32::max_value()),
+            fee_payer: transfer.tx.fee_payer(),
+            sponsor_sig_data: transfer.tx.sponsor.as_ref().map(|sponsor| {
+                SigDataInput::from_sponsorship(sponsor, &transfer.tx.get_bytes())
+                    .expect("sponsor signature data")
+            }),
+            operator: get_signing_operator(
+                tree,
+                transfer.from,
+                from_token_slot,
+                &transfer.tx.signature.pub_key,
+            ),
+            operator_pub_key_hash: get_operator_pub_key_hash(
+                tree,
+                transfer.from,
+                &transfer.tx.signature.pub_key,
+            ),
         };
         // le_bit_vector_into_field_element()
         Self::apply_data(tree, &transfer_data)
@@ -78,28 +126,24 @@ impl Witness for TransferWitness<Bn256> {
             &self.from_before.address.unwrap(),
             franklin_constants::ACCOUNT_ID_BIT_WIDTH,
         );
-        /*append_be_fixed_width(
-            &mut pubdata_bits,
-            &self.from_before.token.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );*/
+        append_nft_be(&mut pubdata_bits, &self.args);
         append_be_fixed_width(
             &mut pubdata_bits,
             &self.to_before.address.unwrap(),
             franklin_constants::ACCOUNT_ID_BIT_WIDTH,
         );
-        append_be_fixed_width(
-            &mut pubdata_bits,
-            &self.args.token_id.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );
 
         append_be_fixed_width(
             &mut pubdata_bits,
             &self.args.fee.unwrap(),
             franklin_constants::FEE_MANTISSA_BIT_WIDTH + franklin_constants::FEE_EXPONENT_BIT_WIDTH,
         );
-        pubdata_bits.resize(2 * franklin_constants::CHUNK_BIT_WIDTH, false); //TODO verify if right padding is okay
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.fee_payer.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        pubdata_bits.resize(TransferOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH, false); //TODO verify if right padding is okay
         pubdata_bits
     }
 
@@ -139,7 +183,38 @@ impl Witness for TransferWitness<Bn256> {
             lhs: self.from_intermediate.clone(),
             rhs: self.to_intermediate.clone(),
         };
-        vec![operation_zero, operation_one]
+        let mut operations = vec![operation_zero, operation_one];
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(2) {
+            let sig_data = match &self.sponsor_sig_data {
+                Some(sponsor_sig_data) if i == TransferOp::SPONSOR_CHUNK => sponsor_sig_data,
+                _ => &input,
+            };
+            operations.push(Operation {
+                new_root: if i < TransferOp::SPONSOR_CHUNK {
+                    self.after_root
+                } else {
+                    self.final_root
+                },
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(sig_data.first_sig_msg),
+                second_sig_msg: Some(sig_data.second_sig_msg),
+                third_sig_msg: Some(sig_data.third_sig_msg),
+                signature_data: sig_data.signature.clone(),
+                signer_pub_key_packed: sig_data.signer_pub_key_packed.to_vec(),
+                args: self.args.clone(),
+                lhs: self.from_after.clone(),
+                rhs: if i == 2 {
+                    self.operator_after.clone()
+                } else if i == TransferOp::SPONSOR_CHUNK {
+                    self.sponsor_before.clone()
+                } else {
+                    self.to_final.clone()
+                },
+            });
+        }
+        operations
     }
 }
 
@@ -178,11 +253,7 @@ impl<E: RescueEngine> TransferWitness<E> {
             franklin_constants::AMOUNT_MANTISSA_BIT_WIDTH
                 + franklin_constants::AMOUNT_EXPONENT_BIT_WIDTH,
         );*/
-        append_be_fixed_width(
-            &mut sig_bits,
-            &self.args.token_id.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );
+        append_nft_be(&mut sig_bits, &self.args);
         append_be_fixed_width(
             &mut sig_bits,
             &self.args.fee.unwrap(),
@@ -193,6 +264,16 @@ impl<E: RescueEngine> TransferWitness<E> {
             &self.from_before.witness.account_witness.nonce.unwrap(),
             franklin_constants::NONCE_BIT_WIDTH,
         );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.valid_from.unwrap(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.valid_until.unwrap(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        );
         sig_bits
     }
 }
@@ -202,18 +283,36 @@ impl TransferWitness<Bn256> {
         //preparing data and base witness
         let before_root = tree.root_hash();
         debug!("Initial root = {}", before_root);
-        let (audit_path_from_before, audit_token_path_from_before) =
-            get_audits(tree, transfer.from_account_address, transfer.token_id);
+        let (audit_path_from_before, audit_token_path_from_before) = get_audits(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
+        let token_from_before = get_token_witness(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
 
         let (audit_path_to_before, audit_token_path_to_before) =
-            get_audits(tree, transfer.to_account_address, transfer.token_id);
+            get_audits(tree, transfer.to_account_address, transfer.to_token_slot);
+        let token_to_before =
+            get_token_witness(tree, transfer.to_account_address, transfer.to_token_slot);
 
         let capacity = tree.capacity();
         assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
         let account_address_from_fe =
             Fr::from_str(&transfer.from_account_address.to_string()).unwrap();
         let account_address_to_fe = Fr::from_str(&transfer.to_account_address.to_string()).unwrap();
-        let token_id_fe = Fr::from_str(&transfer.token_id.to_string()).unwrap();
+        let token_slot_from_fe = Fr::from_str(&transfer.from_token_slot.to_string()).unwrap();
+        let token_slot_to_fe = Fr::from_str(&transfer.to_token_slot.to_string()).unwrap();
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&transfer.token);
+        let content_hash = get_token_content_hash(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&content_hash);
 
         let fee_as_field_element = Fr::from_str(&transfer.fee.to_string()).unwrap();
 
@@ -227,6 +326,10 @@ impl TransferWitness<Bn256> {
 
         let fee_encoded: Fr = le_bit_vector_into_field_element(&fee_bits);
 
+        let operator_id = transfer.operator.unwrap_or(0);
+        let (is_operator, operator_path) =
+            get_operator_audit(tree, transfer.from_account_address, operator_id);
+
         //applying first transfer part
         let (
             account_witness_from_before,
@@ -237,8 +340,12 @@ impl TransferWitness<Bn256> {
             tree,
             transfer.from_account_address,
             None,
-            Some(transfer.token_id),
+            Some(transfer.from_token_slot),
             |acc| {
+                // sponsored fee is charged from the sponsor in its chunk
+                if transfer.fee_payer == transfer.from_account_address {
+                    acc.balance.sub_assign(&fee_as_field_element);
+                }
                 acc.nonce.add_assign(&Fr::from_str("1").unwrap());
             },
         );
@@ -246,11 +353,21 @@ impl TransferWitness<Bn256> {
         let intermediate_root = tree.root_hash();
         debug!("Intermediate root = {}", intermediate_root);
 
-        let (audit_path_from_intermediate, audit_token_path_from_intermediate) =
-            get_audits(tree, transfer.from_account_address, transfer.token_id);
+        let (audit_path_from_intermediate, audit_token_path_from_intermediate) = get_audits(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
+        let token_from_intermediate = get_token_witness(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
 
         let (audit_path_to_intermediate, audit_token_path_to_intermediate) =
-            get_audits(tree, transfer.to_account_address, transfer.token_id);
+            get_audits(tree, transfer.to_account_address, transfer.to_token_slot);
+        let token_to_intermediate =
+            get_token_witness(tree, transfer.to_account_address, transfer.to_token_slot);
 
         let (
             account_witness_to_intermediate,
@@ -260,16 +377,54 @@ impl TransferWitness<Bn256> {
         ) = apply_leaf_operation(
             tree,
             transfer.to_account_address,
-            Some(transfer.token_id),
+            Some((transfer.to_token_slot, transfer.token, content_hash)),
             None,
             |_| {},
         );
         let after_root = tree.root_hash();
-        let (audit_path_from_after, audit_token_path_from_after) =
-            get_audits(tree, transfer.from_account_address, transfer.token_id);
+        let (audit_path_from_after, audit_token_path_from_after) = get_audits(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
+        let token_from_after = get_token_witness(
+            tree,
+            transfer.from_account_address,
+            transfer.from_token_slot,
+        );
 
         let (audit_path_to_after, audit_token_path_to_after) =
-            get_audits(tree, transfer.to_account_address, transfer.token_id);
+            get_audits(tree, transfer.to_account_address, transfer.to_token_slot);
+        let token_to_after =
+            get_token_witness(tree, transfer.to_account_address, transfer.to_token_slot);
+        let operator_after = transfer
+            .operator
+            .map(|operator| get_current_branch(tree, operator, 0));
+        let sponsor_before = if transfer.fee_payer != transfer.from_account_address {
+            Some(charge_sponsor(
+                tree,
+                transfer.fee_payer,
+                &fee_as_field_element,
+            ))
+        } else {
+            None
+        };
+        let final_root = tree.root_hash();
+        let to_final =
+            get_current_branch(tree, transfer.to_account_address, transfer.to_token_slot);
+
+        let to_after = OperationBranch {
+            address: Some(account_address_to_fe),
+            token_slot: Some(token_slot_to_fe),
+            witness: OperationBranchWitness {
+                account_witness: account_witness_to_after,
+                account_path: audit_path_to_after,
+                //balance_value: Some(balance_to_after),
+                //balance_subtree_path: audit_balance_path_to_after,
+                token_witness: token_to_after,
+                token_subtree_path: audit_token_path_to_after,
+            },
+        };
 
         //calculate a and b
         //let a = balance_from_before;
@@ -279,78 +434,108 @@ impl TransferWitness<Bn256> {
         TransferWitness {
             from_before: OperationBranch {
                 address: Some(account_address_from_fe),
+                token_slot: Some(token_slot_from_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_from_before,
                     account_path: audit_path_from_before,
                     //balance_value: Some(balance_from_before),
                     //balance_subtree_path: audit_balance_path_from_before,
+                    token_witness: token_from_before,
                     token_subtree_path: audit_token_path_from_before,
                 },
             },
             from_intermediate: OperationBranch {
                 address: Some(account_address_from_fe),
+                token_slot: Some(token_slot_from_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_from_intermediate.clone(),
                     account_path: audit_path_from_intermediate,
                     //balance_value: Some(balance_from_intermediate),
                     //balance_subtree_path: audit_balance_path_from_intermediate,
+                    token_witness: token_from_intermediate,
                     token_subtree_path: audit_token_path_from_intermediate,
                 },
             },
             from_after: OperationBranch {
                 address: Some(account_address_from_fe),
+                token_slot: Some(token_slot_from_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_from_intermediate,
                     account_path: audit_path_from_after,
                     //balance_value: Some(balance_from_intermediate),
                     //balance_subtree_path: audit_balance_path_from_after,
+                    token_witness: token_from_after,
                     token_subtree_path: audit_token_path_from_after,
                 },
             },
             to_before: OperationBranch {
                 address: Some(account_address_to_fe),
+                token_slot: Some(token_slot_to_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_to_intermediate.clone(),
                     account_path: audit_path_to_before,
                     //balance_value: Some(balance_to_intermediate),
                     //balance_subtree_path: audit_balance_path_to_before,
+                    token_witness: token_to_before,
                     token_subtree_path: audit_token_path_to_before,
                 },
             },
             to_intermediate: OperationBranch {
                 address: Some(account_address_to_fe),
+                token_slot: Some(token_slot_to_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_to_intermediate,
                     account_path: audit_path_to_intermediate,
                     //balance_value: Some(balance_to_intermediate),
                     //balance_subtree_path: audit_balance_path_to_intermediate,
+                    token_witness: token_to_intermediate,
                     token_subtree_path: audit_token_path_to_intermediate,
                 },
             },
-            to_after: OperationBranch {
-                address: Some(account_address_to_fe),
-                witness: OperationBranchWitness {
-                    account_witness: account_witness_to_after,
-                    account_path: audit_path_to_after,
-                    //balance_value: Some(balance_to_after),
-                    //balance_subtree_path: audit_balance_path_to_after,
-                    token_subtree_path: audit_token_path_to_after,
-                },
-            },
+            to_after: to_after.clone(),
+            operator_after: operator_after.unwrap_or_else(|| to_after.clone()),
+            sponsor_before: sponsor_before.unwrap_or(to_after),
+            to_final,
+            sponsor_sig_data: transfer.sponsor_sig_data.clone(),
             args: OperationArguments {
                 eth_address: Some(Fr::zero()),
                 //amount_packed: Some(amount_encoded),
-                //full_amount: Some(amount_as_field_element),
+                full_amount: Some(Fr::zero()),
                 fee: Some(fee_encoded),
                 pub_nonce: Some(Fr::zero()),
                 //a: Some(a),
                 //b: Some(b),
                 new_pub_key_hash: Some(Fr::zero()),
-                token_id: Some(token_id_fe),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(transfer.operator_pub_key_hash),
+                operator_id: Some(Fr::from_str(&operator_id.to_string()).unwrap()),
+                is_operator: Some(is_operator),
+                operator_path,
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::from_str(&transfer.valid_from.to_string()).unwrap()),
+                valid_until: Some(Fr::from_str(&transfer.valid_until.to_string()).unwrap()),
+                fee_payer: Some(Fr::from_str(&transfer.fee_payer.to_string()).unwrap()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
             },
             before_root: Some(before_root),
             intermediate_root: Some(intermediate_root),
             after_root: Some(after_root),
+            final_root: Some(final_root),
             tx_type: Some(Fr::from_str("5").unwrap()),
         }
     }
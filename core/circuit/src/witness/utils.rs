@@ -8,31 +8,35 @@ use crypto_exports::franklin_crypto::{
     },
     eddsa::{PrivateKey, PublicKey},
     jubjub::{FixedGenerators, JubjubEngine},
-    rescue::bn256::Bn256RescueParams,
+    rescue::{bn256::Bn256RescueParams, RescueEngine},
 };
 use crypto_exports::rand::{Rng, SeedableRng, XorShiftRng};
 // Workspace deps
 use models::{
     circuit::{
         account::{CircuitAccount, CircuitAccountTree, Token},
-        utils::{be_bit_vector_into_bytes, le_bit_vector_into_field_element},
+        utils::{append_be_fixed_width, be_bit_vector_into_bytes, le_bit_vector_into_field_element},
     },
     merkle_tree::{hasher::Hasher, PedersenHasher, RescueHasher},
     node::{
-        operations::{CloseOp, TransferOp, TransferToNewOp, WithdrawOp},
-        tx::PackedPublicKey,
-        AccountId, BlockNumber, Engine,
+        operations::{
+            ApproveForAllOp, ApproveOp, BatchTransferOp, BurnOp, CloseOp, MintOp, SwapOp,
+            TransferOp, TransferToNewOp, WithdrawOp,
+        },
+        tx::{PackedPublicKey, Sponsorship},
+        AccountId, BlockNumber, ContentHash, Engine, Nft, Order, PubKeyHash, TokenId, TokenSlot,
     },
     params as franklin_constants,
-    params::total_tokens,
     primitives::big_decimal_to_u128,
 };
 use plasma::state::CollectedFee;
 // Local deps
 use crate::{
-    account::AccountWitness,
+    account::{AccountWitness, TokenWitness},
     circuit::FranklinCircuit,
-    operation::{Operation, SignatureData},
+    operation::{
+        Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
+    },
     utils::sign_rescue,
 };
 
@@ -48,7 +52,7 @@ pub struct WitnessBuilder<'a> {
     pub pubdata: Vec<bool>,
     pub root_before_fees: Option<Fr>,
     pub root_after_fees: Option<Fr>,
-    pub fee_account_balances: Option<Vec<Option<Fr>>>,
+    pub fee_account_token_root: Option<Fr>,
     pub fee_account_witness: Option<AccountWitness<Engine>>,
     pub fee_account_audit_path: Option<Vec<Option<Fr>>>,
     pub pubdata_commitment: Option<Fr>,
@@ -70,7 +74,7 @@ impl<'a> WitnessBuilder<'a> {
             pubdata: Vec::new(),
             root_before_fees: None,
             root_after_fees: None,
-            fee_account_balances: None,
+            fee_account_token_root: None,
             fee_account_witness: None,
             fee_account_audit_path: None,
             pubdata_commitment: None,
@@ -102,38 +106,25 @@ impl<'a> WitnessBuilder<'a> {
     pub fn collect_fees(&mut self, fees: &[CollectedFee]) {
         self.root_before_fees = Some(self.account_tree.root_hash());
 
-        /*let fee_circuit_account = self
+        let fee_circuit_account = self
             .account_tree
             .get(self.fee_account_id)
-            .expect("fee account is not in the tree");*/
-        let mut fee_circuit_account_balances = Vec::with_capacity(total_tokens());
-        /*for i in 0u32..(total_tokens() as u32) {
-            let balance_value = fee_circuit_account
-                .subtree
-                .get(i)
-                .map(|bal| bal.value)
-                .unwrap_or_else(Fr::zero);
-            fee_circuit_account_balances.push(Some(balance_value));
-        }*/
-        self.fee_account_balances = Some(fee_circuit_account_balances);
-
-        let (mut root_after_fee, mut fee_account_witness) =
-            crate::witness::utils::apply_fee(&mut self.account_tree, self.fee_account_id, 0, 0);
-        for CollectedFee { token, amount } in fees {
-            let (root, acc_witness) = crate::witness::utils::apply_fee(
-                &mut self.account_tree,
-                self.fee_account_id,
-                u32::from(*token),
-                big_decimal_to_u128(amount),
-            );
-            root_after_fee = root;
-            fee_account_witness = acc_witness;
-        }
+            .expect("fee account is not in the tree");
+        self.fee_account_token_root = Some(fee_circuit_account.subtree.root_hash());
+
+        // the circuit credits the validator with the sum of the block fees at once
+        let total_fee = fees
+            .iter()
+            .map(|fee| big_decimal_to_u128(&fee.amount))
+            .sum();
+        let (root_after_fee, fee_account_witness) = crate::witness::utils::apply_fee(
+            &mut self.account_tree,
+            self.fee_account_id,
+            total_fee,
+        );
 
         self.root_after_fees = Some(root_after_fee);
         self.fee_account_witness = Some(fee_account_witness);
-        self.root_before_fees = Some(self.account_tree.root_hash());
-        self.root_after_fees = Some(self.account_tree.root_hash());
     }
 
     /// After fees collected creates public data commitment
@@ -171,9 +162,10 @@ impl<'a> WitnessBuilder<'a> {
                 .fee_account_witness
                 .expect("fee account witness not present"),
             validator_address: Some(Fr::from_str(&self.fee_account_id.to_string()).unwrap()),
-            validator_balances: self
-                .fee_account_balances
-                .expect("fee account balances not present"),
+            validator_token_root: Some(
+                self.fee_account_token_root
+                    .expect("fee account token root not present"),
+            ),
             validator_audit_path: self
                 .fee_account_audit_path
                 .expect("fee account audit path not present"),
@@ -376,10 +368,161 @@ pub fn public_data_commitment<E: JubjubEngine>(
     E::Fr::from_repr(repr).unwrap()
 }
 
+/// Splits the token into the `(collection_id, token_id_hi, token_id_lo)` values
+/// carried by the operation arguments.
+pub fn nft_limbs(token: &Nft) -> (Fr, Fr, Fr) {
+    let token = Token::<Bn256>::new(token, &ContentHash::zero());
+    (token.collection_id, token.id_hi, token.id_lo)
+}
+
+/// Splits the content hash into the `(content_hash_hi, content_hash_lo)` values
+/// carried by the operation arguments.
+pub fn content_hash_limbs(content_hash: &ContentHash) -> (Fr, Fr) {
+    let token = Token::<Bn256>::new(&Nft::new(0, TokenId::zero()), content_hash);
+    (token.content_hash_hi, token.content_hash_lo)
+}
+
+/// Appends the collection id and the full 256-bit token id from the operation arguments
+/// in big-endian order.
+pub fn append_nft_be<E: RescueEngine>(content: &mut Vec<bool>, args: &OperationArguments<E>) {
+    append_be_fixed_width(
+        content,
+        &args.collection_id.unwrap(),
+        franklin_constants::COLLECTIONID_BIT_WIDTH,
+    );
+    append_be_fixed_width(
+        content,
+        &args.token_id_hi.unwrap(),
+        franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+    );
+    append_be_fixed_width(
+        content,
+        &args.token_id_lo.unwrap(),
+        franklin_constants::TOKENID_LIMB_BIT_WIDTH,
+    );
+}
+
+/// Appends the full 256-bit content hash from the operation arguments in big-endian order.
+pub fn append_content_hash_be<E: RescueEngine>(
+    content: &mut Vec<bool>,
+    args: &OperationArguments<E>,
+) {
+    append_be_fixed_width(
+        content,
+        &args.content_hash_hi.unwrap(),
+        franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+    );
+    append_be_fixed_width(
+        content,
+        &args.content_hash_lo.unwrap(),
+        franklin_constants::CONTENT_HASH_LIMB_BIT_WIDTH,
+    );
+}
+
+/// Slot of the token subtree leaf holding the token, see `Account::token_slot`.
+pub fn get_token_slot(tree: &CircuitAccountTree, account_address: u32, token: &Nft) -> TokenSlot {
+    tree.get(account_address)
+        .and_then(|account| account.token_slot(token))
+        .expect("token is not held by the account")
+}
+
+/// Content hash of the token in the slot, it moves together with the token.
+pub fn get_token_content_hash(
+    tree: &CircuitAccountTree,
+    account_address: u32,
+    token_slot: TokenSlot,
+) -> ContentHash {
+    tree.get(account_address)
+        .and_then(|account| account.subtree.get(token_slot))
+        .map(Token::content_hash)
+        .expect("token slot of the account is empty")
+}
+
+/// Key hash of the approved operator who signed the operation on behalf of the account,
+/// zero when the operation is signed by the account itself.
+pub fn get_operator_pub_key_hash(
+    tree: &CircuitAccountTree,
+    account_address: u32,
+    signer_pub_key: &PackedPublicKey,
+) -> Fr {
+    let signer_pub_key_hash = PubKeyHash::from_pubkey(&signer_pub_key.0).to_fr();
+    let account_pub_key_hash = tree
+        .get(account_address)
+        .map(|account| account.pub_key_hash)
+        .unwrap_or_else(Fr::zero);
+    if signer_pub_key_hash == account_pub_key_hash {
+        Fr::zero()
+    } else {
+        signer_pub_key_hash
+    }
+}
+
+/// Operator who signed the operation on behalf of the account, `None` if the account signed it.
+/// The operator is approved either for the token in the slot or for all the account tokens,
+/// the fee account can only be approved for all of them.
+pub fn get_signing_operator(
+    tree: &CircuitAccountTree,
+    account_address: u32,
+    token_slot: TokenSlot,
+    signer_pub_key: &PackedPublicKey,
+) -> Option<AccountId> {
+    let signer_pub_key_hash = PubKeyHash::from_pubkey(&signer_pub_key.0).to_fr();
+    let account = tree.get(account_address)?;
+    if account.pub_key_hash == signer_pub_key_hash {
+        return None;
+    }
+    let token_operator = account.subtree.get(token_slot).map(|token| token.approved);
+    tree.items
+        .iter()
+        .filter(|(_, operator)| operator.pub_key_hash == signer_pub_key_hash)
+        .map(|(&operator_id, _)| operator_id as AccountId)
+        .find(|&operator_id| {
+            let is_token_operator = operator_id != 0
+                && token_operator == Some(Fr::from_str(&operator_id.to_string()).unwrap());
+            let is_operator = account
+                .operators
+                .get(operator_id)
+                .map_or(false, |leaf| leaf.approved);
+            is_token_operator || is_operator
+        })
+}
+
+/// Operators tree leaf of the operator in the account, one if the operator is approved
+/// for all the account tokens, with the audit path to it.
+pub fn get_operator_audit(
+    tree: &CircuitAccountTree,
+    account_address: u32,
+    operator: AccountId,
+) -> (Fr, Vec<Option<Fr>>) {
+    let default_account = CircuitAccount::default();
+    let operators = &tree
+        .get(account_address)
+        .unwrap_or(&default_account)
+        .operators;
+    let is_operator = match operators.get(operator) {
+        Some(leaf) if leaf.approved => Fr::one(),
+        _ => Fr::zero(),
+    };
+    let operator_path = operators
+        .merkle_path(operator)
+        .into_iter()
+        .map(|e| Some(e.0))
+        .collect();
+    (is_operator, operator_path)
+}
+
+/// Slot the account receives a new token into, see `Account::add_token`.
+pub fn get_free_token_slot(tree: &CircuitAccountTree, account_address: u32) -> TokenSlot {
+    tree.get(account_address)
+        .map(|account| account.free_token_slot())
+        .unwrap_or(Some(0))
+        .expect("account token subtree is full")
+}
+
 pub fn get_audits(
     tree: &CircuitAccountTree,
     account_address: u32,
-    token_id: u32,
+    token_slot: TokenSlot,
 ) -> (Vec<Option<Fr>>, Vec<Option<Fr>>) {
     let default_account = CircuitAccount::default();
     let audit_account: Vec<Option<Fr>> = tree
@@ -392,18 +535,32 @@ pub fn get_audits(
         .get(account_address)
         .unwrap_or(&default_account)
         .subtree
-        .merkle_path(token_id)
+        .merkle_path(token_slot)
         .into_iter()
         .map(|e| Some(e.0))
         .collect();
     (audit_account, audit_token)
 }
 
+/// Token subtree leaf in the slot, the all-zero one if the slot is empty.
+pub fn get_token_witness(
+    tree: &CircuitAccountTree,
+    account_address: u32,
+    token_slot: TokenSlot,
+) -> TokenWitness<Bn256> {
+    let token = tree
+        .get(account_address)
+        .and_then(|account| account.subtree.get(token_slot))
+        .cloned()
+        .unwrap_or_default();
+    TokenWitness::from_token(&token)
+}
+
 pub fn apply_leaf_operation<Fa: Fn(&mut CircuitAccount<Bn256>) -> ()>(
     tree: &mut CircuitAccountTree,
     account_address: u32,
-    token_id_to_add: Option<u32>,
-    token_id_to_remove: Option<u32>,
+    token_to_add: Option<(TokenSlot, Nft, ContentHash)>,
+    token_to_remove: Option<TokenSlot>,
     fa: Fa,
 ) -> (AccountWitness<Bn256>, AccountWitness<Bn256>, Fr, Fr) {
     let default_account = CircuitAccount::default();
@@ -418,16 +575,13 @@ pub fn apply_leaf_operation<Fa: Fn(&mut CircuitAccount<Bn256>) -> ()>(
     //let balance_before = balance.value;
 
     //let balance_after = balance.value;
-    if let Some(token_id) = token_id_to_add {
-        account.subtree.insert(
-            token_id,
-            Token {
-                id: Fr::from_str(&token_id.to_string()).unwrap(),
-            },
-        );
+    if let Some((slot, token, content_hash)) = token_to_add {
+        account
+            .subtree
+            .insert(slot, Token::new(&token, &content_hash));
     }
-    if let Some(token_id) = token_id_to_remove {
-        account.subtree.remove(token_id);
+    if let Some(slot) = token_to_remove {
+        account.subtree.remove(slot);
     }
     //account.subtree.insert(token, balance);
 
@@ -443,21 +597,40 @@ pub fn apply_leaf_operation<Fa: Fn(&mut CircuitAccount<Bn256>) -> ()>(
     )
 }
 
+/// Branch of the account in the current state of the tree, with the audit path to `token_slot`.
+pub fn get_current_branch(
+    tree: &mut CircuitAccountTree,
+    account_id: AccountId,
+    token_slot: TokenSlot,
+) -> OperationBranch<Bn256> {
+    let (account_witness, _, _, _) = apply_leaf_operation(tree, account_id, None, None, |_| {});
+    let (account_path, token_subtree_path) = get_audits(tree, account_id, token_slot);
+    let token_witness = get_token_witness(tree, account_id, token_slot);
+    OperationBranch {
+        address: Some(Fr::from_str(&account_id.to_string()).unwrap()),
+        token_slot: Some(Fr::from_str(&token_slot.to_string()).unwrap()),
+        witness: OperationBranchWitness {
+            account_witness,
+            account_path,
+            token_witness,
+            token_subtree_path,
+        },
+    }
+}
+
+/// Credits the fee to the ETH balance of the validator account.
 pub fn apply_fee(
     tree: &mut CircuitAccountTree,
     validator_address: u32,
-    token: u32,
     fee: u128,
 ) -> (Fr, AccountWitness<Bn256>) {
-    //let fee_fe = Fr::from_str(&fee.to_string()).unwrap();
+    let fee_fe = Fr::from_str(&fee.to_string()).unwrap();
     let mut validator_leaf = tree
         .remove(validator_address)
         .expect("validator_leaf is empty");
     let validator_account_witness = AccountWitness::from_circuit_account(&validator_leaf);
 
-    //let mut balance = validator_leaf.subtree.remove(token).unwrap_or_default();
-    //balance.value.add_assign(&fee_fe);
-    //validator_leaf.subtree.insert(token, balance);
+    validator_leaf.balance.add_assign(&fee_fe);
 
     tree.insert(validator_address, validator_leaf);
 
@@ -465,6 +638,21 @@ pub fn apply_fee(
     (root_after_fee, validator_account_witness)
 }
 
+/// Charges the sponsored fee from the sponsor account, like `PlasmaState::collect_fee` does.
+/// Charges the fee of a sponsored tx from the sponsor, returns the branch of the sponsor
+/// before the fee is charged, which is opened by the sponsor chunk of the tx.
+pub fn charge_sponsor(
+    tree: &mut CircuitAccountTree,
+    sponsor_address: u32,
+    fee: &Fr,
+) -> OperationBranch<Bn256> {
+    let sponsor_before = get_current_branch(tree, sponsor_address, 0);
+    apply_leaf_operation(tree, sponsor_address, None, None, |acc| {
+        acc.balance.sub_assign(fee)
+    });
+    sponsor_before
+}
+
 pub fn fr_from_bytes(bytes: Vec<u8>) -> Fr {
     let mut fr_repr = <Fr as PrimeField>::Repr::default();
     fr_repr.read_be(&*bytes).unwrap();
@@ -573,6 +761,36 @@ impl SigDataInput {
         )
     }
 
+    pub fn from_batch_transfer_op(batch_transfer_op: &BatchTransferOp) -> Result<Self, String> {
+        let sign_packed = batch_transfer_op
+            .tx
+            .signature
+            .signature
+            .serialize_packed()
+            .expect("signature serialize");
+        SigDataInput::new(
+            &sign_packed,
+            &batch_transfer_op.tx.get_bytes(),
+            &batch_transfer_op.tx.signature.pub_key,
+        )
+    }
+
+    /// Returns the signature data of the orders of the accounts `a` and `b`.
+    pub fn from_swap_op(swap_op: &SwapOp) -> Result<(Self, Self), String> {
+        let sig_data = |order: &Order| {
+            let sign_packed = order
+                .signature
+                .signature
+                .serialize_packed()
+                .expect("signature serialize");
+            SigDataInput::new(&sign_packed, &order.get_bytes(), &order.signature.pub_key)
+        };
+        Ok((
+            sig_data(&swap_op.tx.order_a)?,
+            sig_data(&swap_op.tx.order_b)?,
+        ))
+    }
+
     pub fn from_withdraw_op(withdraw_op: &WithdrawOp) -> Result<Self, String> {
         let sign_packed = withdraw_op
             .tx
@@ -587,6 +805,76 @@ impl SigDataInput {
         )
     }
 
+    pub fn from_mint_op(mint_op: &MintOp) -> Result<Self, String> {
+        let sign_packed = mint_op
+            .tx
+            .signature
+            .signature
+            .serialize_packed()
+            .expect("signature serialize");
+        SigDataInput::new(
+            &sign_packed,
+            &mint_op.tx.get_bytes(),
+            &mint_op.tx.signature.pub_key,
+        )
+    }
+
+    pub fn from_burn_op(burn_op: &BurnOp) -> Result<Self, String> {
+        let sign_packed = burn_op
+            .tx
+            .signature
+            .signature
+            .serialize_packed()
+            .expect("signature serialize");
+        SigDataInput::new(
+            &sign_packed,
+            &burn_op.tx.get_bytes(),
+            &burn_op.tx.signature.pub_key,
+        )
+    }
+
+    pub fn from_approve_op(approve_op: &ApproveOp) -> Result<Self, String> {
+        let sign_packed = approve_op
+            .tx
+            .signature
+            .signature
+            .serialize_packed()
+            .expect("signature serialize");
+        SigDataInput::new(
+            &sign_packed,
+            &approve_op.tx.get_bytes(),
+            &approve_op.tx.signature.pub_key,
+        )
+    }
+
+    pub fn from_approve_for_all_op(approve_for_all_op: &ApproveForAllOp) -> Result<Self, String> {
+        let sign_packed = approve_for_all_op
+            .tx
+            .signature
+            .signature
+            .serialize_packed()
+            .expect("signature serialize");
+        SigDataInput::new(
+            &sign_packed,
+            &approve_for_all_op.tx.get_bytes(),
+            &approve_for_all_op.tx.signature.pub_key,
+        )
+    }
+
+    /// Returns the signature data of the sponsor of the tx with the given bytes.
+    pub fn from_sponsorship(sponsorship: &Sponsorship, tx_bytes: &[u8]) -> Result<Self, String> {
+        let sign_packed = sponsorship
+            .signature
+            .signature
+            .serialize_packed()
+            .expect("signature serialize");
+        SigDataInput::new(
+            &sign_packed,
+            &Sponsorship::get_bytes(tx_bytes, sponsorship.account_id),
+            &sponsorship.signature.pub_key,
+        )
+    }
+
     /// Provides a vector of copies of this `SigDataInput` object, all with one field
     /// set to incorrect value.
     /// Used for circuit tests.
@@ -12,7 +12,7 @@ use models::{
         account::CircuitAccountTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
-    node::operations::WithdrawOp,
+    node::{operations::WithdrawOp, Nft, TokenSlot},
     params as franklin_constants,
     primitives::{big_decimal_to_u128, convert_to_float},
 };
@@ -20,24 +20,43 @@ use models::{
 use crate::{
     operation::{Operation, OperationArguments, OperationBranch, OperationBranchWitness},
     witness::{
-        utils::{apply_leaf_operation, get_audits, SigDataInput},
+        utils::{
+            append_nft_be, apply_leaf_operation, charge_sponsor, content_hash_limbs, get_audits,
+            get_current_branch, get_token_content_hash, get_token_slot, get_token_witness,
+            nft_limbs, SigDataInput,
+        },
         Witness,
     },
 };
 
 pub struct WithdrawData {
     pub fee: u128,
-    pub token_id: u32,
+    pub token: Nft,
+    pub token_slot: TokenSlot,
     pub account_address: u32,
     pub eth_address: Fr,
+    pub valid_from: u32,
+    pub valid_until: u32,
+    /// Account paying the fee, differs from the withdrawing account for a sponsored withdraw.
+    pub fee_payer: u32,
+    /// Signature of the sponsor, checked in the chunk `WithdrawOp::SPONSOR_CHUNK`.
+    pub sponsor_sig_data: Option<SigDataInput>,
 }
 
 pub struct WithdrawWitness<E: RescueEngine> {
     pub before: OperationBranch<E>,
     pub after: OperationBranch<E>,
+    /// Branch opened by the chunk `WithdrawOp::SPONSOR_CHUNK`, the one of the sponsor before
+    /// it pays the fee or of the withdrawing account if the withdraw is not sponsored.
+    pub sponsor_before: OperationBranch<E>,
+    /// Branch of the withdrawing account opened by the chunks after the sponsor one.
+    pub final_branch: OperationBranch<E>,
+    pub sponsor_sig_data: Option<SigDataInput>,
     pub args: OperationArguments<E>,
     pub before_root: Option<E::Fr>,
     pub after_root: Option<E::Fr>,
+    /// Root after the sponsor pays the fee, same as `after_root` if the withdraw is not sponsored.
+    pub final_root: Option<E::Fr>,
     pub tx_type: Option<E::Fr>,
 }
 
@@ -48,8 +67,16 @@ impl Witness for WithdrawWitness<Bn256> {
     fn apply_tx(tree: &mut CircuitAccountTree, withdraw: &WithdrawOp) -> Self {
         let withdraw_data = WithdrawData {
             fee: big_decimal_to_u128(&withdraw.tx.fee),
-            token_id: u32::from(withdraw.tx.token_id),
+            token: withdraw.tx.nft(),
+            token_slot: get_token_slot(tree, withdraw.account_id, &withdraw.tx.nft()),
             account_address: withdraw.account_id,
+            valid_from: withdraw.tx.valid_from.unwrap_or(0),
+            valid_until: withdraw.tx.valid_until.unwrap_or(u32::max_value()),
+            fee_payer: withdraw.tx.fee_payer(),
+            sponsor_sig_data: withdraw.tx.sponsor.as_ref().map(|sponsor| {
+                SigDataInput::from_sponsorship(sponsor, &withdraw.tx.get_bytes())
+                    .expect("sponsor signature data")
+            }),
             eth_address: eth_address_to_fr(&withdraw.tx.to),
         };
         // le_bit_vector_into_field_element()
@@ -79,11 +106,7 @@ impl Witness for WithdrawWitness<Bn256> {
             &self.args.full_amount.unwrap(),
             franklin_constants::BALANCE_BIT_WIDTH,
         );*/
-        append_be_fixed_width(
-            &mut pubdata_bits,
-            &self.args.token_id.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );
+        append_nft_be(&mut pubdata_bits, &self.args);
 
         append_be_fixed_width(
             &mut pubdata_bits,
@@ -96,7 +119,12 @@ impl Witness for WithdrawWitness<Bn256> {
             &self.args.eth_address.unwrap(),
             franklin_constants::ETH_ADDRESS_BIT_WIDTH,
         );
-        pubdata_bits.resize(6 * franklin_constants::CHUNK_BIT_WIDTH, false);
+        append_be_fixed_width(
+            &mut pubdata_bits,
+            &self.args.fee_payer.unwrap(),
+            franklin_constants::ACCOUNT_ID_BIT_WIDTH,
+        );
+        pubdata_bits.resize(WithdrawOp::CHUNKS * franklin_constants::CHUNK_BIT_WIDTH, false);
         pubdata_bits
     }
 
@@ -122,86 +150,35 @@ impl Witness for WithdrawWitness<Bn256> {
             rhs: self.before.clone(),
         };
 
-        let operation_one = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("1").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[1]),
-            first_sig_msg: Some(input.first_sig_msg),
-            second_sig_msg: Some(input.second_sig_msg),
-            third_sig_msg: Some(input.third_sig_msg),
-            signature_data: input.signature.clone(),
-            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-        };
-
-        let operation_two = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("2").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[2]),
-            first_sig_msg: Some(input.first_sig_msg),
-            second_sig_msg: Some(input.second_sig_msg),
-            third_sig_msg: Some(input.third_sig_msg),
-            signature_data: input.signature.clone(),
-            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-        };
-
-        let operation_three = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("3").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[3]),
-            first_sig_msg: Some(input.first_sig_msg),
-            second_sig_msg: Some(input.second_sig_msg),
-            third_sig_msg: Some(input.third_sig_msg),
-            signature_data: input.signature.clone(),
-            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-        };
-        let operation_four = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("4").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[4]),
-            first_sig_msg: Some(input.first_sig_msg),
-            second_sig_msg: Some(input.second_sig_msg),
-            third_sig_msg: Some(input.third_sig_msg),
-            signature_data: input.signature.clone(),
-            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-        };
-        let operation_five = Operation {
-            new_root: self.after_root,
-            tx_type: self.tx_type,
-            chunk: Some(Fr::from_str("5").unwrap()),
-            pubdata_chunk: Some(pubdata_chunks[5]),
-            first_sig_msg: Some(input.first_sig_msg),
-            second_sig_msg: Some(input.second_sig_msg),
-            third_sig_msg: Some(input.third_sig_msg),
-            signature_data: input.signature.clone(),
-            signer_pub_key_packed: input.signer_pub_key_packed.to_vec(),
-            args: self.args.clone(),
-            lhs: self.after.clone(),
-            rhs: self.after.clone(),
-        };
-        vec![
-            operation_zero,
-            operation_one,
-            operation_two,
-            operation_three,
-            operation_four,
-            operation_five,
-        ]
+        let mut operations = vec![operation_zero];
+        for (i, pubdata_chunk) in pubdata_chunks.iter().cloned().enumerate().skip(1) {
+            let sig_data = match &self.sponsor_sig_data {
+                Some(sponsor_sig_data) if i == WithdrawOp::SPONSOR_CHUNK => sponsor_sig_data,
+                _ => &input,
+            };
+            let (new_root, branch) = if i < WithdrawOp::SPONSOR_CHUNK {
+                (self.after_root, &self.after)
+            } else if i == WithdrawOp::SPONSOR_CHUNK {
+                (self.final_root, &self.sponsor_before)
+            } else {
+                (self.final_root, &self.final_branch)
+            };
+            operations.push(Operation {
+                new_root,
+                tx_type: self.tx_type,
+                chunk: Some(Fr::from_str(&i.to_string()).unwrap()),
+                pubdata_chunk: Some(pubdata_chunk),
+                first_sig_msg: Some(sig_data.first_sig_msg),
+                second_sig_msg: Some(sig_data.second_sig_msg),
+                third_sig_msg: Some(sig_data.third_sig_msg),
+                signature_data: sig_data.signature.clone(),
+                signer_pub_key_packed: sig_data.signer_pub_key_packed.to_vec(),
+                args: self.args.clone(),
+                lhs: branch.clone(),
+                rhs: branch.clone(),
+            });
+        }
+        operations
     }
 }
 
@@ -233,11 +210,7 @@ impl<E: RescueEngine> WithdrawWitness<E> {
             &self.args.full_amount.unwrap(),
             franklin_constants::BALANCE_BIT_WIDTH,
         );*/
-        append_be_fixed_width(
-            &mut sig_bits,
-            &self.args.token_id.unwrap(),
-            franklin_constants::TOKENID_BIT_WIDTH,
-        );
+        append_nft_be(&mut sig_bits, &self.args);
         append_be_fixed_width(
             &mut sig_bits,
             &self.args.fee.unwrap(),
@@ -248,6 +221,16 @@ impl<E: RescueEngine> WithdrawWitness<E> {
             &self.before.witness.account_witness.nonce.unwrap(),
             franklin_constants::NONCE_BIT_WIDTH,
         );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.valid_from.unwrap(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        );
+        append_be_fixed_width(
+            &mut sig_bits,
+            &self.args.valid_until.unwrap(),
+            franklin_constants::TIMESTAMP_BIT_WIDTH,
+        );
         sig_bits
     }
 }
@@ -258,12 +241,17 @@ impl WithdrawWitness<Bn256> {
         let before_root = tree.root_hash();
         debug!("Initial root = {}", before_root);
         let (audit_path_before, audit_token_path_before) =
-            get_audits(tree, withdraw.account_address, withdraw.token_id);
+            get_audits(tree, withdraw.account_address, withdraw.token_slot);
+        let token_before = get_token_witness(tree, withdraw.account_address, withdraw.token_slot);
 
         let capacity = tree.capacity();
         assert_eq!(capacity, 1 << franklin_constants::account_tree_depth());
         let account_address_fe = Fr::from_str(&withdraw.account_address.to_string()).unwrap();
-        let token_fe = Fr::from_str(&withdraw.token_id.to_string()).unwrap();
+        let token_slot_fe = Fr::from_str(&withdraw.token_slot.to_string()).unwrap();
+        let (collection_id, token_id_hi, token_id_lo) = nft_limbs(&withdraw.token);
+        let content_hash =
+            get_token_content_hash(tree, withdraw.account_address, withdraw.token_slot);
+        let (content_hash_hi, content_hash_lo) = content_hash_limbs(&content_hash);
 
         let fee_as_field_element = Fr::from_str(&withdraw.fee.to_string()).unwrap();
 
@@ -286,8 +274,12 @@ impl WithdrawWitness<Bn256> {
                 tree,
                 withdraw.account_address,
                 None,
-                Some(withdraw.token_id),
+                Some(withdraw.token_slot),
                 |acc| {
+                    // sponsored fee is charged from the sponsor in its chunk
+                    if withdraw.fee_payer == withdraw.account_address {
+                        acc.balance.sub_assign(&fee_as_field_element);
+                    }
                     acc.nonce.add_assign(&Fr::from_str("1").unwrap());
                 },
             );
@@ -295,7 +287,30 @@ impl WithdrawWitness<Bn256> {
         let after_root = tree.root_hash();
         debug!("After root = {}", after_root);
         let (audit_path_after, audit_token_path_after) =
-            get_audits(tree, withdraw.account_address, withdraw.token_id);
+            get_audits(tree, withdraw.account_address, withdraw.token_slot);
+        let token_after = get_token_witness(tree, withdraw.account_address, withdraw.token_slot);
+        let after = OperationBranch {
+            address: Some(account_address_fe),
+            token_slot: Some(token_slot_fe),
+            witness: OperationBranchWitness {
+                account_witness: account_witness_after,
+                account_path: audit_path_after,
+                //balance_value: Some(balance_after),
+                token_witness: token_after,
+                token_subtree_path: audit_token_path_after,
+            },
+        };
+        let sponsor_before = if withdraw.fee_payer != withdraw.account_address {
+            Some(charge_sponsor(
+                tree,
+                withdraw.fee_payer,
+                &fee_as_field_element,
+            ))
+        } else {
+            None
+        };
+        let final_root = tree.root_hash();
+        let final_branch = get_current_branch(tree, withdraw.account_address, withdraw.token_slot);
 
         //let a = balance_before;
         //let mut b = amount_as_field_element;
@@ -304,35 +319,57 @@ impl WithdrawWitness<Bn256> {
         WithdrawWitness {
             before: OperationBranch {
                 address: Some(account_address_fe),
+                token_slot: Some(token_slot_fe),
                 witness: OperationBranchWitness {
                     account_witness: account_witness_before,
                     account_path: audit_path_before,
                     //balance_value: Some(balance_before),
+                    token_witness: token_before,
                     token_subtree_path: audit_token_path_before,
                 },
             },
-            after: OperationBranch {
-                address: Some(account_address_fe),
-                witness: OperationBranchWitness {
-                    account_witness: account_witness_after,
-                    account_path: audit_path_after,
-                    //balance_value: Some(balance_after),
-                    token_subtree_path: audit_token_path_after,
-                },
-            },
+            sponsor_before: sponsor_before.unwrap_or_else(|| after.clone()),
+            after,
+            final_branch,
+            sponsor_sig_data: withdraw.sponsor_sig_data.clone(),
             args: OperationArguments {
                 eth_address: Some(withdraw.eth_address),
                 //amount_packed: Some(amount_encoded),
-                //full_amount: Some(amount_as_field_element),
+                full_amount: Some(Fr::zero()),
                 fee: Some(fee_encoded),
                 pub_nonce: Some(Fr::zero()),
                 //a: Some(a),
                 //b: Some(b),
                 new_pub_key_hash: Some(Fr::zero()),
-                token_id: Some(token_fe),
+                collection_id: Some(collection_id),
+                token_id_hi: Some(token_id_hi),
+                token_id_lo: Some(token_id_lo),
+                content_hash_hi: Some(content_hash_hi),
+                content_hash_lo: Some(content_hash_lo),
+                operator_pub_key_hash: Some(Fr::zero()),
+                operator_id: Some(Fr::zero()),
+                is_operator: Some(Fr::zero()),
+                operator_path: vec![Some(Fr::zero()); franklin_constants::account_tree_depth()],
+                approved: Some(Fr::zero()),
+                valid_from: Some(Fr::from_str(&withdraw.valid_from.to_string()).unwrap()),
+                valid_until: Some(Fr::from_str(&withdraw.valid_until.to_string()).unwrap()),
+                fee_payer: Some(Fr::from_str(&withdraw.fee_payer.to_string()).unwrap()),
+                tokens_count: Some(Fr::zero()),
+                tokens_root: Some(Fr::zero()),
+                token_index: Some(Fr::zero()),
+                token_list_path: vec![Some(Fr::zero()); franklin_constants::NFT_LIST_TREE_DEPTH],
+                second_collection_id: Some(Fr::zero()),
+                second_token_id_hi: Some(Fr::zero()),
+                second_token_id_lo: Some(Fr::zero()),
+                second_content_hash_hi: Some(Fr::zero()),
+                second_content_hash_lo: Some(Fr::zero()),
+                second_fee: Some(Fr::zero()),
+                second_tokens_count: Some(Fr::zero()),
+                second_tokens_root: Some(Fr::zero()),
             },
             before_root: Some(before_root),
             after_root: Some(after_root),
+            final_root: Some(final_root),
             tx_type: Some(Fr::from_str("3").unwrap()),
         }
     }
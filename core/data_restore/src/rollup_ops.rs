@@ -80,9 +80,7 @@ impl RollupOpsBlock {
         let mut current_pointer = 0;
         let mut ops = vec![];
         while current_pointer < data.len() {
-            let op_type: u8 = data[current_pointer];
-
-            let pub_data_size = FranklinOp::public_data_length(op_type)?;
+            let pub_data_size = FranklinOp::public_data_length(&data[current_pointer..])?;
 
             let pre = current_pointer;
             let post = pre + pub_data_size;
@@ -103,20 +101,26 @@ mod test {
     use models::node::operations::ChangePubKeyOp;
     use models::node::tx::{ChangePubKey, TxSignature};
     use models::node::{
-        Close, CloseOp, Deposit, DepositOp, FranklinOp, FullExit, FullExitOp, PubKeyHash, Transfer,
-        TransferOp, TransferToNewOp, Withdraw, WithdrawOp,
+        Approve, ApproveForAll, ApproveForAllOp, ApproveOp, BatchTransfer, BatchTransferOp, Burn,
+        BurnOp, Close, CloseOp, ContentHash, Deposit, DepositEth, DepositEthOp, DepositOp,
+        ForcedWithdraw, ForcedWithdrawOp, FranklinOp, FullExit, FullExitOp, Mint, MintOp, Nft,
+        Order, PubKeyHash, Sponsorship, Swap, SwapOp, TokenId, Transfer, TransferOp,
+        TransferToNewOp, Withdraw, WithdrawOp,
     };
 
     #[test]
     fn test_deposit() {
         let priority_op = Deposit {
             from: "1111111111111111111111111111111111111111".parse().unwrap(),
-            token_id: 1,
+            collection_id: 1,
+            token_id: TokenId::from(1),
             to: "7777777777777777777777777777777777777777".parse().unwrap(),
+            content_hash: ContentHash::repeat_byte(0x11),
         };
         let op1 = FranklinOp::Deposit(Box::new(DepositOp {
             priority_op,
             account_id: 6,
+            success: true,
         }));
         let pub_data1 = op1.public_data();
         let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
@@ -134,9 +138,12 @@ mod test {
             "7777777777777777777777777777777777777777".parse().unwrap(),
             [9u8; 20].into(),
             1,
+            TokenId::from(1),
             BigDecimal::from(10),
             2,
             None,
+            None,
+            None,
         );
         let op1 = FranklinOp::Withdraw(Box::new(WithdrawOp { tx, account_id: 3 }));
         let pub_data1 = op1.public_data();
@@ -180,6 +187,50 @@ mod test {
         assert_eq!(pub_data1, pub_data2);
     }
 
+    #[test]
+    fn test_deposit_eth() {
+        let priority_op = DepositEth {
+            from: [9u8; 20].into(),
+            amount: BigDecimal::from(1_000_000_000_000_000_000u64),
+            to: [7u8; 20].into(),
+        };
+        let op1 = FranklinOp::DepositEth(Box::new(DepositEthOp {
+            priority_op,
+            account_id: 6,
+        }));
+        let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_forced_withdraw() {
+        for &success in &[true, false] {
+            let priority_op = ForcedWithdraw {
+                account_id: 11,
+                eth_address: [9u8; 20].into(),
+                collection_id: 3,
+                token_id: TokenId::from(12),
+            };
+            let op1 = FranklinOp::ForcedWithdraw(Box::new(ForcedWithdrawOp {
+                priority_op,
+                success,
+            }));
+            let pub_data1 = op1.public_data();
+            let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+                .expect("cant get ops from data")
+                .pop()
+                .expect("empty ops array");
+            let pub_data2 = op2.public_data();
+            assert_eq!(pub_data1, pub_data2);
+            assert_eq!(op2.withdrawal_data().is_some(), success);
+        }
+    }
+
     #[test]
     fn test_transfer_to_new() {
         let tx = Transfer::new(
@@ -187,9 +238,12 @@ mod test {
             "7777777777777777777777777777777777777777".parse().unwrap(),
             "8888888888888888888888888888888888888888".parse().unwrap(),
             1,
+            TokenId::from(1),
             BigDecimal::from(10),
             3,
             None,
+            None,
+            None,
         );
         let op1 = FranklinOp::TransferToNew(Box::new(TransferToNewOp {
             tx,
@@ -212,16 +266,223 @@ mod test {
             "7777777777777777777777777777777777777777".parse().unwrap(),
             "8888888888888888888888888888888888888888".parse().unwrap(),
             1,
+            TokenId::from(1),
+            BigDecimal::from(10),
+            3,
+            None,
+            None,
+            None,
+        );
+        let op1 = FranklinOp::Transfer(Box::new(TransferOp {
+            tx,
+            from: 11,
+            to: 12,
+        }));
+        let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_sponsored_transfer() {
+        let mut tx = Transfer::new(
+            11,
+            "7777777777777777777777777777777777777777".parse().unwrap(),
+            "8888888888888888888888888888888888888888".parse().unwrap(),
+            1,
+            TokenId::from(1),
             BigDecimal::from(10),
             3,
             None,
+            None,
+            None,
         );
+        tx.sponsor = Some(Sponsorship {
+            account_id: 13,
+            signature: TxSignature::default(),
+        });
         let op1 = FranklinOp::Transfer(Box::new(TransferOp {
             tx,
             from: 11,
             to: 12,
         }));
         let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        match &op2 {
+            FranklinOp::Transfer(op) => assert_eq!(op.tx.fee_payer(), 13),
+            _ => panic!("transfer op expected"),
+        }
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_batch_transfer() {
+        let batch = |tokens_count: u16| {
+            let tokens = (1..=tokens_count)
+                .map(|i| Nft::new(i, TokenId::from(i)))
+                .collect();
+            let tx = BatchTransfer::new(
+                11,
+                "7777777777777777777777777777777777777777".parse().unwrap(),
+                "8888888888888888888888888888888888888888".parse().unwrap(),
+                tokens,
+                BigDecimal::from(10),
+                3,
+                None,
+            );
+            FranklinOp::BatchTransfer(Box::new(BatchTransferOp {
+                tx,
+                from: 11,
+                to: 12,
+            }))
+        };
+        // ops of different length in a row
+        let ops = vec![batch(1), batch(5)];
+        let pub_data1 = ops
+            .iter()
+            .flat_map(|op| op.public_data())
+            .collect::<Vec<_>>();
+        let ops2 =
+            RollupOpsBlock::get_rollup_ops_from_data(&pub_data1).expect("cant get ops from data");
+        assert_eq!(ops2.len(), 2);
+        let pub_data2 = ops2
+            .iter()
+            .flat_map(|op| op.public_data())
+            .collect::<Vec<_>>();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_swap() {
+        let token_a = Nft::new(1, TokenId::from(1));
+        let token_b = Nft::new(2, TokenId::max_value());
+        let order_a = Order::new(
+            11,
+            "7777777777777777777777777777777777777777".parse().unwrap(),
+            token_a,
+            vec![token_b],
+            BigDecimal::from(10),
+            3,
+            None,
+        );
+        let order_b = Order::new(
+            12,
+            "8888888888888888888888888888888888888888".parse().unwrap(),
+            token_b,
+            vec![token_a],
+            BigDecimal::from(20),
+            5,
+            None,
+        );
+        let tx = Swap::new(order_a, order_b);
+        let op1 = FranklinOp::Swap(Box::new(SwapOp {
+            tx,
+            account_a: 11,
+            account_b: 12,
+        }));
+        let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_mint() {
+        let tx = Mint::new(
+            3,
+            "7777777777777777777777777777777777777777".parse().unwrap(),
+            1,
+            TokenId::max_value(),
+            ContentHash::repeat_byte(0x11),
+            BigDecimal::from(10),
+            0,
+            None,
+        );
+        let op1 = FranklinOp::Mint(Box::new(MintOp { tx, account_id: 3 }));
+        let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_burn() {
+        let tx = Burn::new(
+            3,
+            "7777777777777777777777777777777777777777".parse().unwrap(),
+            1,
+            TokenId::from(20),
+            BigDecimal::from(10),
+            2,
+            None,
+        );
+        let op1 = FranklinOp::Burn(Box::new(BurnOp { tx, account_id: 3 }));
+        let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_approve() {
+        let tx = Approve::new(
+            3,
+            "7777777777777777777777777777777777777777".parse().unwrap(),
+            "8888888888888888888888888888888888888888".parse().unwrap(),
+            1,
+            TokenId::from(20),
+            BigDecimal::from(10),
+            2,
+            None,
+        );
+        let op1 = FranklinOp::Approve(Box::new(ApproveOp {
+            tx,
+            account_id: 3,
+            operator_id: Some(4),
+        }));
+        let pub_data1 = op1.public_data();
+        let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
+            .expect("cant get ops from data")
+            .pop()
+            .expect("empty ops array");
+        let pub_data2 = op2.public_data();
+        assert_eq!(pub_data1, pub_data2);
+    }
+
+    #[test]
+    fn test_approve_for_all() {
+        let tx = ApproveForAll::new(
+            3,
+            "7777777777777777777777777777777777777777".parse().unwrap(),
+            "8888888888888888888888888888888888888888".parse().unwrap(),
+            true,
+            BigDecimal::from(10),
+            2,
+            None,
+        );
+        let op1 = FranklinOp::ApproveForAll(Box::new(ApproveForAllOp {
+            tx,
+            account_id: 3,
+            operator_id: 4,
+        }));
+        let pub_data1 = op1.public_data();
         let op2 = RollupOpsBlock::get_rollup_ops_from_data(&pub_data1)
             .expect("cant get ops from data")
             .pop()
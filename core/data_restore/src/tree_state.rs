@@ -151,6 +151,129 @@ impl TreeState {
                         &mut ops,
                     );
                 }
+                FranklinOp::Mint(mut op) => {
+                    // Mint op comes with empty Nonce field
+                    let account = self
+                        .state
+                        .get_account(op.account_id)
+                        .ok_or_else(|| format_err!("Mint fail: Nonexistent account"))?;
+                    op.tx.nonce = account.nonce;
+
+                    let tx = FranklinTx::Mint(Box::new(op.tx.clone()));
+                    let (fee, updates) = self
+                        .state
+                        .apply_mint_op(&op)
+                        .map_err(|e| format_err!("Mint fail: {}", e))?;
+                    let tx_result = OpSuccess {
+                        fee: Some(fee),
+                        updates,
+                        executed_op: FranklinOp::Mint(op),
+                    };
+                    current_op_block_index = self.update_from_tx(
+                        tx,
+                        tx_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
+                FranklinOp::Burn(mut op) => {
+                    // Burn op comes with empty Account Address and Nonce fields
+                    let account = self
+                        .state
+                        .get_account(op.account_id)
+                        .ok_or_else(|| format_err!("Burn fail: Nonexistent account"))?;
+                    op.tx.from = account.address;
+                    op.tx.nonce = account.nonce;
+
+                    let tx = FranklinTx::Burn(Box::new(op.tx.clone()));
+                    let (fee, updates) = self
+                        .state
+                        .apply_burn_op(&op)
+                        .map_err(|e| format_err!("Burn fail: {}", e))?;
+                    let tx_result = OpSuccess {
+                        fee: Some(fee),
+                        updates,
+                        executed_op: FranklinOp::Burn(op),
+                    };
+                    current_op_block_index = self.update_from_tx(
+                        tx,
+                        tx_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
+                FranklinOp::Approve(mut op) => {
+                    // Approve op comes with empty Account and Operator Address and Nonce fields
+                    let account = self
+                        .state
+                        .get_account(op.account_id)
+                        .ok_or_else(|| format_err!("Approve fail: Nonexistent account"))?;
+                    op.tx.from = account.address;
+                    op.tx.nonce = account.nonce;
+                    if let Some(operator_id) = op.operator_id {
+                        let operator = self
+                            .state
+                            .get_account(operator_id)
+                            .ok_or_else(|| format_err!("Approve fail: Nonexistent operator"))?;
+                        op.tx.operator = operator.address;
+                    }
+
+                    let tx = FranklinTx::Approve(Box::new(op.tx.clone()));
+                    let (fee, updates) = self
+                        .state
+                        .apply_approve_op(&op)
+                        .map_err(|e| format_err!("Approve fail: {}", e))?;
+                    let tx_result = OpSuccess {
+                        fee: Some(fee),
+                        updates,
+                        executed_op: FranklinOp::Approve(op),
+                    };
+                    current_op_block_index = self.update_from_tx(
+                        tx,
+                        tx_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
+                FranklinOp::ApproveForAll(mut op) => {
+                    // ApproveForAll op comes with empty Account and Operator Address and Nonce fields
+                    let account = self
+                        .state
+                        .get_account(op.account_id)
+                        .ok_or_else(|| format_err!("ApproveForAll fail: Nonexistent account"))?;
+                    let operator = self
+                        .state
+                        .get_account(op.operator_id)
+                        .ok_or_else(|| format_err!("ApproveForAll fail: Nonexistent operator"))?;
+                    op.tx.from = account.address;
+                    op.tx.operator = operator.address;
+                    op.tx.nonce = account.nonce;
+
+                    let tx = FranklinTx::ApproveForAll(Box::new(op.tx.clone()));
+                    let (fee, updates) = self
+                        .state
+                        .apply_approve_for_all_op(&op)
+                        .map_err(|e| format_err!("ApproveForAll fail: {}", e))?;
+                    let tx_result = OpSuccess {
+                        fee: Some(fee),
+                        updates,
+                        executed_op: FranklinOp::ApproveForAll(op),
+                    };
+                    current_op_block_index = self.update_from_tx(
+                        tx,
+                        tx_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
                 FranklinOp::Close(mut op) => {
                     // Close op comes with empty Account Address and Nonce fields
                     let account = self
@@ -211,6 +334,68 @@ impl TreeState {
                         &mut ops,
                     );
                 }
+                FranklinOp::BatchTransfer(mut op) => {
+                    // Batch transfer op comes with empty From Address and Nonce fields
+                    let from = self
+                        .state
+                        .get_account(op.from)
+                        .ok_or_else(|| format_err!("BatchTransfer fail: Nonexistent account"))?;
+                    op.tx.from = from.address;
+                    op.tx.nonce = from.nonce;
+
+                    let tx = FranklinTx::BatchTransfer(Box::new(op.tx.clone()));
+                    let (fee, updates) = self
+                        .state
+                        .apply_batch_transfer_op(&op)
+                        .map_err(|e| format_err!("BatchTransfer fail: {}", e))?;
+                    let tx_result = OpSuccess {
+                        fee: Some(fee),
+                        updates,
+                        executed_op: FranklinOp::BatchTransfer(op),
+                    };
+                    current_op_block_index = self.update_from_tx(
+                        tx,
+                        tx_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
+                FranklinOp::Swap(mut op) => {
+                    // Swap op comes with empty Address and Nonce fields of both accounts
+                    let account_a = self
+                        .state
+                        .get_account(op.account_a)
+                        .ok_or_else(|| format_err!("Swap fail: Nonexistent account"))?;
+                    let account_b = self
+                        .state
+                        .get_account(op.account_b)
+                        .ok_or_else(|| format_err!("Swap fail: Nonexistent account"))?;
+                    op.tx.order_a.account = account_a.address;
+                    op.tx.order_a.nonce = account_a.nonce;
+                    op.tx.order_b.account = account_b.address;
+                    op.tx.order_b.nonce = account_b.nonce;
+
+                    let tx = FranklinTx::Swap(Box::new(op.tx.clone()));
+                    let (fee, updates) = self
+                        .state
+                        .apply_swap_op(&op)
+                        .map_err(|e| format_err!("Swap fail: {}", e))?;
+                    let tx_result = OpSuccess {
+                        fee: Some(fee),
+                        updates,
+                        executed_op: FranklinOp::Swap(op),
+                    };
+                    current_op_block_index = self.update_from_tx(
+                        tx,
+                        tx_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
                 FranklinOp::FullExit(op) => {
                     let priority_op = FranklinPriorityOp::FullExit(op.priority_op);
                     let op_result = self.state.execute_priority_op(priority_op.clone());
@@ -223,6 +408,30 @@ impl TreeState {
                         &mut ops,
                     );
                 }
+                FranklinOp::DepositEth(op) => {
+                    let priority_op = FranklinPriorityOp::DepositEth(op.priority_op);
+                    let op_result = self.state.execute_priority_op(priority_op.clone());
+                    current_op_block_index = self.update_from_priority_operation(
+                        priority_op,
+                        op_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
+                FranklinOp::ForcedWithdraw(op) => {
+                    let priority_op = FranklinPriorityOp::ForcedWithdraw(op.priority_op);
+                    let op_result = self.state.execute_priority_op(priority_op.clone());
+                    current_op_block_index = self.update_from_priority_operation(
+                        priority_op,
+                        op_result,
+                        &mut fees,
+                        &mut accounts_updated,
+                        current_op_block_index,
+                        &mut ops,
+                    );
+                }
                 FranklinOp::ChangePubKeyOffchain(mut op) => {
                     let account = self.state.get_account(op.account_id).ok_or_else(|| {
                         format_err!("ChangePubKeyOffChain fail: Nonexistent account")
@@ -392,21 +601,35 @@ mod test {
     use crate::tree_state::TreeState;
     use bigdecimal::BigDecimal;
     use models::node::{
-        Deposit, DepositOp, FranklinOp, Transfer, TransferOp, TransferToNewOp, Withdraw, WithdrawOp,
+        Deposit, DepositEth, DepositEthOp, DepositOp, FranklinOp, Nft, TokenId, Transfer,
+        TransferOp, TransferToNewOp, Withdraw, WithdrawOp,
     };
 
     #[test]
     fn test_update_tree_with_one_tx_per_block() {
         let tx1 = Deposit {
             from: [1u8; 20].into(),
-            token_id: 1,
+            collection_id: 1,
+            token_id: TokenId::from(1),
             to: [7u8; 20].into(),
+            content_hash: Default::default(),
         };
         let op1 = FranklinOp::Deposit(Box::new(DepositOp {
             priority_op: tx1,
             account_id: 0,
+            success: true,
+        }));
+        // fees of the txs are paid from the eth balance
+        let op1_eth = FranklinOp::DepositEth(Box::new(DepositEthOp {
+            priority_op: DepositEth {
+                from: [1u8; 20].into(),
+                amount: BigDecimal::from(10),
+                to: [7u8; 20].into(),
+            },
+            account_id: 0,
         }));
-        let pub_data1 = op1.public_data();
+        let mut pub_data1 = op1.public_data();
+        pub_data1.extend_from_slice(&op1_eth.public_data());
         let ops1 =
             RollupOpsBlock::get_rollup_ops_from_data(&pub_data1).expect("cant get ops from data 1");
         let block1 = RollupOpsBlock {
@@ -417,12 +640,15 @@ mod test {
 
         let tx2 = Deposit {
             from: [1u8; 20].into(),
-            token_id: 2,
+            collection_id: 1,
+            token_id: TokenId::from(2),
             to: [7u8; 20].into(),
+            content_hash: Default::default(),
         };
         let op2 = FranklinOp::Deposit(Box::new(DepositOp {
             priority_op: tx2,
             account_id: 0,
+            success: true,
         }));
         let pub_data2 = op2.public_data();
         let ops2 =
@@ -437,10 +663,13 @@ mod test {
             0,
             [7u8; 20].into(),
             [7u8; 20].into(),
-            2,
+            1,
+            TokenId::from(2),
             BigDecimal::from(1),
             1,
             None,
+            None,
+            None,
         );
         let op3 = FranklinOp::Withdraw(Box::new(WithdrawOp {
             tx: tx3,
@@ -460,9 +689,12 @@ mod test {
             [7u8; 20].into(),
             [8u8; 20].into(),
             1,
+            TokenId::from(1),
             BigDecimal::from(1),
             3,
             None,
+            None,
+            None,
         );
         let op4 = FranklinOp::TransferToNew(Box::new(TransferToNewOp {
             tx: tx4,
@@ -483,16 +715,28 @@ mod test {
             [8u8; 20].into(),
             [7u8; 20].into(),
             1,
+            TokenId::from(1),
             BigDecimal::from(1),
             1,
             None,
+            None,
+            None,
         );
+        let op5_eth = FranklinOp::DepositEth(Box::new(DepositEthOp {
+            priority_op: DepositEth {
+                from: [1u8; 20].into(),
+                amount: BigDecimal::from(5),
+                to: [8u8; 20].into(),
+            },
+            account_id: 1,
+        }));
         let op5 = FranklinOp::Transfer(Box::new(TransferOp {
             tx: tx5,
             from: 1,
             to: 0,
         }));
-        let pub_data5 = op5.public_data();
+        let mut pub_data5 = op5_eth.public_data();
+        pub_data5.extend_from_slice(&op5.public_data());
         let ops5 =
             RollupOpsBlock::get_rollup_ops_from_data(&pub_data5).expect("cant get ops from data 5");
         let block5 = RollupOpsBlock {
@@ -536,34 +780,52 @@ mod test {
 
         let zero_acc = tree.get_account(0).expect("Cant get 0 account");
         assert_eq!(zero_acc.address, [7u8; 20].into());
-        assert!(zero_acc.has_token(1));
+        assert!(zero_acc.has_token(Nft::new(1, TokenId::from(1))));
+        // account 0 is the fee account, it pays 2 fees and collects 3
+        assert_eq!(zero_acc.balance, BigDecimal::from(11));
 
         let first_acc = tree.get_account(1).expect("Cant get 0 account");
         assert_eq!(first_acc.address, [8u8; 20].into());
-        assert!(!first_acc.has_token(1));
+        assert!(!first_acc.has_token(Nft::new(1, TokenId::from(1))));
+        assert_eq!(first_acc.balance, BigDecimal::from(4));
     }
 
     #[test]
     fn test_update_tree_with_multiple_txs_per_block() {
         let tx1 = Deposit {
             from: [1u8; 20].into(),
-            token_id: 1,
+            collection_id: 1,
+            token_id: TokenId::from(1),
             to: [7u8; 20].into(),
+            content_hash: Default::default(),
         };
         let op1 = FranklinOp::Deposit(Box::new(DepositOp {
             priority_op: tx1,
             account_id: 0,
+            success: true,
         }));
-        let pub_data1 = op1.public_data();
+        let op1_eth = FranklinOp::DepositEth(Box::new(DepositEthOp {
+            priority_op: DepositEth {
+                from: [1u8; 20].into(),
+                amount: BigDecimal::from(10),
+                to: [7u8; 20].into(),
+            },
+            account_id: 0,
+        }));
+        let mut pub_data1 = op1.public_data();
+        pub_data1.extend_from_slice(&op1_eth.public_data());
 
         let tx2 = Deposit {
             from: [1u8; 20].into(),
-            token_id: 2,
+            collection_id: 1,
+            token_id: TokenId::from(2),
             to: [7u8; 20].into(),
+            content_hash: Default::default(),
         };
         let op2 = FranklinOp::Deposit(Box::new(DepositOp {
             priority_op: tx2,
             account_id: 0,
+            success: true,
         }));
         let pub_data2 = op2.public_data();
 
@@ -571,10 +833,13 @@ mod test {
             0,
             [7u8; 20].into(),
             [9u8; 20].into(),
-            2,
+            1,
+            TokenId::from(2),
             BigDecimal::from(1),
             2,
             None,
+            None,
+            None,
         );
         let op3 = FranklinOp::Withdraw(Box::new(WithdrawOp {
             tx: tx3,
@@ -587,9 +852,12 @@ mod test {
             [7u8; 20].into(),
             [8u8; 20].into(),
             1,
+            TokenId::from(1),
             BigDecimal::from(1),
             3,
             None,
+            None,
+            None,
         );
         let op4 = FranklinOp::TransferToNew(Box::new(TransferToNewOp {
             tx: tx4,
@@ -603,16 +871,28 @@ mod test {
             [8u8; 20].into(),
             [7u8; 20].into(),
             1,
+            TokenId::from(1),
             BigDecimal::from(1),
             1,
             None,
+            None,
+            None,
         );
+        let op5_eth = FranklinOp::DepositEth(Box::new(DepositEthOp {
+            priority_op: DepositEth {
+                from: [1u8; 20].into(),
+                amount: BigDecimal::from(5),
+                to: [8u8; 20].into(),
+            },
+            account_id: 1,
+        }));
         let op5 = FranklinOp::Transfer(Box::new(TransferOp {
             tx: tx5,
             from: 1,
             to: 0,
         }));
-        let pub_data5 = op5.public_data();
+        let mut pub_data5 = op5_eth.public_data();
+        pub_data5.extend_from_slice(&op5.public_data());
 
         let mut pub_data = Vec::new();
         pub_data.extend_from_slice(&pub_data1);
@@ -637,12 +917,15 @@ mod test {
 
         let zero_acc = tree.get_account(0).expect("Cant get 0 account");
         assert_eq!(zero_acc.address, [7u8; 20].into());
-        assert!(zero_acc.has_token(1));
-        assert!(!zero_acc.has_token(2));
+        assert!(zero_acc.has_token(Nft::new(1, TokenId::from(1))));
+        // account 0 is the fee account, it pays 2 fees and collects 3
+        assert_eq!(zero_acc.balance, BigDecimal::from(11));
+        assert!(!zero_acc.has_token(Nft::new(1, TokenId::from(2))));
 
         let first_acc = tree.get_account(1).expect("Cant get 0 account");
         assert_eq!(first_acc.address, [8u8; 20].into());
-        assert!(!first_acc.has_token(1));
-        assert!(!first_acc.has_token(2));
+        assert!(!first_acc.has_token(Nft::new(1, TokenId::from(1))));
+        assert_eq!(first_acc.balance, BigDecimal::from(4));
+        assert!(!first_acc.has_token(Nft::new(1, TokenId::from(2))));
     }
 }
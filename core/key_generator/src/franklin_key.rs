@@ -2,7 +2,7 @@
 use std::fs::{remove_file, File};
 use std::path::Path;
 // Workspace deps
-use circuit::account::AccountWitness;
+use circuit::account::{AccountWitness, TokenWitness};
 use circuit::circuit::FranklinCircuit;
 use circuit::exit_circuit::ZksyncExitCircuit;
 use circuit::operation::{
@@ -57,17 +57,33 @@ fn exit_circuit() -> impl Circuit<Engine> + Clone {
         account_audit_data: OperationBranch {
             address: None,
             //token: None,
+            token_slot: None,
             witness: OperationBranchWitness {
                 account_witness: AccountWitness {
                     nonce: None,
                     pub_key_hash: None,
                     address: None,
+                    balance: None,
+                    operators_root: None,
                 },
                 account_path: vec![None; params::account_tree_depth()],
                 //balance_value: None,
+                token_witness: TokenWitness {
+                    collection_id: None,
+                    id_hi: None,
+                    id_lo: None,
+                    content_hash_hi: None,
+                    content_hash_lo: None,
+                    approved: None,
+                },
                 token_subtree_path: vec![None; params::token_tree_depth()],
             },
         },
+        collection_id: None,
+        token_id_hi: None,
+        token_id_lo: None,
+        content_hash_hi: None,
+        content_hash_lo: None,
     }
 }
 
@@ -87,38 +103,84 @@ fn zksync_circuit(block_chunks: usize) -> impl Circuit<Engine> + Clone {
             //a: None,
             //b: None,
             //amount_packed: None,
-            //full_amount: None,
+            full_amount: None,
             fee: None,
             pub_nonce: None,
             new_pub_key_hash: None,
             eth_address: None,
-            token_id: None,
+            collection_id: None,
+            token_id_hi: None,
+            token_id_lo: None,
+            content_hash_hi: None,
+            content_hash_lo: None,
+            operator_pub_key_hash: None,
+            operator_id: None,
+            is_operator: None,
+            operator_path: vec![None; params::account_tree_depth()],
+            approved: None,
+            valid_from: None,
+            valid_until: None,
+            fee_payer: None,
+            tokens_count: None,
+            tokens_root: None,
+            token_index: None,
+            token_list_path: vec![None; params::NFT_LIST_TREE_DEPTH],
+            second_collection_id: None,
+            second_token_id_hi: None,
+            second_token_id_lo: None,
+            second_content_hash_hi: None,
+            second_content_hash_lo: None,
+            second_fee: None,
+            second_tokens_count: None,
+            second_tokens_root: None,
         },
         lhs: OperationBranch {
             address: None,
             //token: None,
+            token_slot: None,
             witness: OperationBranchWitness {
                 account_witness: AccountWitness {
                     nonce: None,
                     pub_key_hash: None,
                     address: None,
+                    balance: None,
+                    operators_root: None,
                 },
                 account_path: vec![None; params::account_tree_depth()],
                 //balance_value: None,
+                token_witness: TokenWitness {
+                    collection_id: None,
+                    id_hi: None,
+                    id_lo: None,
+                    content_hash_hi: None,
+                    content_hash_lo: None,
+                    approved: None,
+                },
                 token_subtree_path: vec![None; params::token_tree_depth()],
             },
         },
         rhs: OperationBranch {
             address: None,
             //token: None,
+            token_slot: None,
             witness: OperationBranchWitness {
                 account_witness: AccountWitness {
                     nonce: None,
                     pub_key_hash: None,
                     address: None,
+                    balance: None,
+                    operators_root: None,
                 },
                 account_path: vec![None; params::account_tree_depth()],
                 //balance_value: None,
+                token_witness: TokenWitness {
+                    collection_id: None,
+                    id_hi: None,
+                    id_lo: None,
+                    content_hash_hi: None,
+                    content_hash_lo: None,
+                    approved: None,
+                },
                 token_subtree_path: vec![None; params::token_tree_depth()],
             },
         },
@@ -131,13 +193,15 @@ fn zksync_circuit(block_chunks: usize) -> impl Circuit<Engine> + Clone {
         validator_address: None,
         block_number: None,
         pub_data_commitment: None,
-        validator_balances: vec![None; params::total_tokens()],
+        validator_token_root: None,
         validator_audit_path: vec![None; params::account_tree_depth()],
         operations: vec![empty_operation; block_chunks],
         validator_account: AccountWitness {
             nonce: None,
             pub_key_hash: None,
             address: None,
+            balance: None,
+            operators_root: None,
         },
     }
 }
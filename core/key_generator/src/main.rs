@@ -33,7 +33,7 @@ fn main() {
     let config = AvailableBlockSizesConfig::from_env();
     let (cmd, _) = cli.subcommand();
     if cmd == "keys" {
-        // TODO ADE: exit is currently disabled make_plonk_exodus_verify_key();
+        make_plonk_exodus_verify_key();
         make_plonk_blocks_verify_keys(config);
     } else if cmd == "contract" {
         create_verifier_contract(config);
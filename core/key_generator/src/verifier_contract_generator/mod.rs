@@ -37,9 +37,8 @@ pub(crate) fn create_verifier_contract(config: AvailableBlockSizesConfig) {
     {
         let exodus_key_path = get_exodus_verification_key_path();
         let exodus_ket_getter_name = "getVkExit";
-        // TODO ADE no exit for now
-        //let exodus_key = rendered_key(exodus_ket_getter_name, exodus_key_path);
-        //templates_for_key_getters.push(exodus_key);
+        let exodus_key = rendered_key(exodus_ket_getter_name, exodus_key_path);
+        templates_for_key_getters.push(exodus_key);
     }
     template_params.insert("keys".to_string(), to_json(templates_for_key_getters));
 
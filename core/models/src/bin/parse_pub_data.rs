@@ -8,10 +8,9 @@ fn main() {
 
     let mut unparsed_data = data.as_slice();
     while !unparsed_data.is_empty() {
-        let op_type = unparsed_data[0];
-        let op_data_len = FranklinOp::public_data_length(op_type).expect("wrong op type");
+        let op_data_len = FranklinOp::public_data_length(unparsed_data).expect("wrong op type");
         assert!(
-            data.len() > op_data_len,
+            unparsed_data.len() >= op_data_len,
             "not enough bytes in the pubdata for current op"
         );
         let (current_op, unparsed) = unparsed_data.split_at(op_data_len);
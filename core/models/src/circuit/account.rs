@@ -1,6 +1,9 @@
+use std::convert::TryInto;
+
+use crate::node::{ContentHash, Nft, TokenSlot};
 use crate::params;
 
-use crate::franklin_crypto::bellman::pairing::ff::{Field, PrimeField};
+use crate::franklin_crypto::bellman::pairing::ff::{Field, PrimeField, PrimeFieldRepr};
 use crate::franklin_crypto::bellman::pairing::Engine;
 
 use crate::franklin_crypto::bellman::pairing::bn256::{Bn256, Fr};
@@ -11,13 +14,18 @@ use crate::primitives::{GetBits, GetBitsFixed};
 
 pub type CircuitAccountTree = SparseMerkleTree<CircuitAccount<Bn256>, Fr, RescueHasher<Bn256>>;
 pub type CircuitTokenTree = SparseMerkleTree<Token<Bn256>, Fr, RescueHasher<Bn256>>;
+pub type CircuitOperatorsTree = SparseMerkleTree<OperatorLeaf, Fr, RescueHasher<Bn256>>;
 
 #[derive(Clone)]
 pub struct CircuitAccount<E: RescueEngine> {
     pub subtree: SparseMerkleTree<Token<E>, E::Fr, RescueHasher<E>>,
+    /// Operators approved by the account for all of its tokens, indexed by the operator account id.
+    pub operators: SparseMerkleTree<OperatorLeaf, E::Fr, RescueHasher<E>>,
     pub nonce: E::Fr,
     pub pub_key_hash: E::Fr,
     pub address: E::Fr,
+    /// ETH balance of the account in wei.
+    pub balance: E::Fr,
 }
 
 impl<E: RescueEngine> GetBits for CircuitAccount<E> {
@@ -57,14 +65,29 @@ impl<E: RescueEngine> GetBits for CircuitAccount<E> {
 }
 
 impl<E: RescueEngine> CircuitAccount<E> {
-    fn get_state_root(&self) -> E::Fr {
-        let balance_root = self.subtree.root_hash();
+    /// Slot of the token subtree leaf holding the token.
+    pub fn token_slot(&self, token: &Nft) -> Option<TokenSlot> {
+        self.subtree
+            .items
+            .iter()
+            .find(|(_, held)| held.holds(token))
+            .map(|(&slot, _)| slot as TokenSlot)
+    }
+
+    /// Lowest token subtree slot without a token, the same one `Account::add_token` picks.
+    pub fn free_token_slot(&self) -> Option<TokenSlot> {
+        (0..params::total_tokens() as TokenSlot).find(|slot| self.subtree.get(*slot).is_none())
+    }
 
-        let state_root_padding = E::Fr::zero();
+    /// ETH balance takes the place reserved next to the token subtree root,
+    /// the root of the operators tree follows it.
+    fn get_state_root(&self) -> E::Fr {
+        let token_root = self.subtree.root_hash();
+        let operators_root = self.operators.root_hash();
 
         self.subtree
             .hasher
-            .hash_elements(vec![balance_root, state_root_padding])
+            .hash_elements(vec![token_root, self.balance, operators_root])
     }
 }
 
@@ -75,23 +98,129 @@ impl std::default::Default for CircuitAccount<Bn256> {
             nonce: Fr::zero(),
             pub_key_hash: Fr::zero(),
             address: Fr::zero(),
+            balance: Fr::zero(),
             subtree: SparseMerkleTree::new(params::token_tree_depth()),
+            operators: SparseMerkleTree::new(params::account_tree_depth()),
         }
     }
 }
+
+/// Operators tree leaf, set if the operator is approved for all the tokens of the account.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OperatorLeaf {
+    pub approved: bool,
+}
+
+impl GetBits for OperatorLeaf {
+    fn get_bits_le(&self) -> Vec<bool> {
+        vec![self.approved]
+    }
+}
+
+/// Token subtree leaf.
+///
+/// ERC-721 token ids are 256 bits wide and do not fit into a single field element,
+/// so the id is stored as two 128-bit limbs, next to the id of the collection it belongs to.
+/// The content hash of the token is split into limbs the same way.
+/// The leaf also keeps the operator the owner approved for the token, the approval is dropped
+/// together with the leaf when the token leaves the account.
 #[derive(Clone, Debug)]
 pub struct Token<E: Engine> {
-    pub id: E::Fr,
+    pub collection_id: E::Fr,
+    pub id_hi: E::Fr,
+    pub id_lo: E::Fr,
+    pub content_hash_hi: E::Fr,
+    pub content_hash_lo: E::Fr,
+    /// Id of the operator account approved for the token, zero if there is none.
+    /// The fee account can't be approved, so zero is never a real operator.
+    pub approved: E::Fr,
+}
+
+impl<E: Engine> Token<E> {
+    pub fn new(nft: &Nft, content_hash: &ContentHash) -> Self {
+        let id_hi = (nft.token_id >> params::TOKENID_LIMB_BIT_WIDTH).low_u128();
+        let id_lo = nft.token_id.low_u128();
+        let (content_hash_hi, content_hash_lo) = content_hash
+            .as_bytes()
+            .split_at(params::CONTENT_HASH_LIMB_BIT_WIDTH / 8);
+        let content_hash_hi = u128::from_be_bytes(content_hash_hi.try_into().unwrap());
+        let content_hash_lo = u128::from_be_bytes(content_hash_lo.try_into().unwrap());
+        Self {
+            collection_id: E::Fr::from_str(&nft.collection_id.to_string()).unwrap(),
+            id_hi: E::Fr::from_str(&id_hi.to_string()).unwrap(),
+            id_lo: E::Fr::from_str(&id_lo.to_string()).unwrap(),
+            content_hash_hi: E::Fr::from_str(&content_hash_hi.to_string()).unwrap(),
+            content_hash_lo: E::Fr::from_str(&content_hash_lo.to_string()).unwrap(),
+            approved: E::Fr::zero(),
+        }
+    }
+
+    /// Checks that the leaf holds the token, whatever its content hash is.
+    pub fn holds(&self, nft: &Nft) -> bool {
+        let token = Self::new(nft, &ContentHash::zero());
+        self.collection_id == token.collection_id
+            && self.id_hi == token.id_hi
+            && self.id_lo == token.id_lo
+    }
+
+    /// Content hash committed to by the leaf.
+    pub fn content_hash(&self) -> ContentHash {
+        let limb_bytes = params::CONTENT_HASH_LIMB_BIT_WIDTH / 8;
+        let mut content_hash = Vec::with_capacity(2 * limb_bytes);
+        for limb in &[self.content_hash_hi, self.content_hash_lo] {
+            let mut bytes = Vec::new();
+            limb.into_repr()
+                .write_be(&mut bytes)
+                .expect("failed to write field element");
+            content_hash.extend_from_slice(&bytes[bytes.len() - limb_bytes..]);
+        }
+        ContentHash::from_slice(&content_hash)
+    }
+}
+
+impl<E: Engine> PartialEq for Token<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.collection_id == other.collection_id
+            && self.id_hi == other.id_hi
+            && self.id_lo == other.id_lo
+            && self.content_hash_hi == other.content_hash_hi
+            && self.content_hash_lo == other.content_hash_lo
+            && self.approved == other.approved
+    }
 }
 
 impl<E: Engine> GetBits for Token<E> {
     fn get_bits_le(&self) -> Vec<bool> {
         let mut leaf_content = Vec::new();
-        leaf_content.extend(self.id.get_bits_le_fixed(params::TOKENID_BIT_WIDTH));
+        leaf_content.extend(self.id_lo.get_bits_le_fixed(params::TOKENID_LIMB_BIT_WIDTH));
+        leaf_content.extend(self.id_hi.get_bits_le_fixed(params::TOKENID_LIMB_BIT_WIDTH));
+        leaf_content.extend(
+            self.collection_id
+                .get_bits_le_fixed(params::COLLECTIONID_BIT_WIDTH),
+        );
+        leaf_content.extend(
+            self.content_hash_lo
+                .get_bits_le_fixed(params::CONTENT_HASH_LIMB_BIT_WIDTH),
+        );
+        leaf_content.extend(
+            self.content_hash_hi
+                .get_bits_le_fixed(params::CONTENT_HASH_LIMB_BIT_WIDTH),
+        );
+        leaf_content.extend(
+            self.approved
+                .get_bits_le_fixed(params::ACCOUNT_ID_BIT_WIDTH),
+        );
         assert!(
-            params::TOKENID_BIT_WIDTH < E::Fr::CAPACITY as usize,
+            params::TOKENID_LIMB_BIT_WIDTH < E::Fr::CAPACITY as usize,
             "due to algebraic nature of the hash we should not overflow the capacity"
         );
+        assert_eq!(
+            leaf_content.len(),
+            params::TOKENID_BIT_WIDTH
+                + params::COLLECTIONID_BIT_WIDTH
+                + params::CONTENT_HASH_BIT_WIDTH
+                + params::ACCOUNT_ID_BIT_WIDTH
+        );
 
         leaf_content
     }
@@ -100,6 +229,13 @@ impl<E: Engine> GetBits for Token<E> {
 impl<E: Engine> std::default::Default for Token<E> {
     //default should be changed: since subtree_root_hash is not zero for all zero balances and subaccounts
     fn default() -> Self {
-        Self { id: E::Fr::zero() }
+        Self {
+            collection_id: E::Fr::zero(),
+            id_hi: E::Fr::zero(),
+            id_lo: E::Fr::zero(),
+            content_hash_hi: E::Fr::zero(),
+            content_hash_lo: E::Fr::zero(),
+            approved: E::Fr::zero(),
+        }
     }
 }
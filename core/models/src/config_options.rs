@@ -1,13 +1,15 @@
 // Built-in deps
+use std::collections::HashMap;
 use std::env;
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::time::Duration;
 // External uses
+use bigdecimal::BigDecimal;
 use futures::{channel::mpsc, executor::block_on, SinkExt};
 use web3::types::{H160, H256};
 // Local uses
-use crate::node::Address;
+use crate::node::{AccountId, Address};
 use crate::params::block_chunk_sizes;
 
 /// If its placed inside thread::spawn closure it will notify channel when this thread panics.
@@ -53,6 +55,24 @@ where
         .unwrap_or_else(|e| panic!("Failed to parse environment variable {}: {:?}", name, e))
 }
 
+/// Parses the sponsor fee quotas given as `<account id>:<fee in wei>` entries separated by commas.
+/// Panics if any of the entries cannot be parsed.
+fn parse_sponsor_fee_quotas(value: &str) -> HashMap<AccountId, BigDecimal> {
+    value
+        .split(',')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.splitn(2, ':');
+            let account_id = parts.next().and_then(|id| id.trim().parse().ok());
+            let amount = parts.next().and_then(|amount| amount.trim().parse().ok());
+            match (account_id, amount) {
+                (Some(account_id), Some(amount)) => (account_id, amount),
+                _ => panic!("Failed to parse the sponsor fee quota: {}", entry),
+            }
+        })
+        .collect()
+}
+
 /// Configuration options for `eth_sender`.
 #[derive(Debug, Clone)]
 pub struct EthSenderOptions {
@@ -126,6 +146,12 @@ pub struct ConfigurationOptions {
     pub available_block_chunk_sizes: Vec<usize>,
     pub eth_watch_poll_interval: Duration,
     pub eth_network: String,
+    pub mempool_max_txs: usize,
+    pub mempool_tx_ttl: Duration,
+    /// Total fee in wei the txs of each sponsor may pay within `sponsor_quota_period`,
+    /// accounts without a quota can not sponsor txs.
+    pub sponsor_fee_quotas: HashMap<AccountId, BigDecimal>,
+    pub sponsor_quota_period: Duration,
 }
 
 impl ConfigurationOptions {
@@ -157,6 +183,10 @@ impl ConfigurationOptions {
                 "ETH_WATCH_POLL_INTERVAL",
             )),
             eth_network: parse_env("ETH_NETWORK"),
+            mempool_max_txs: parse_env("MEMPOOL_MAX_TXS"),
+            mempool_tx_ttl: Duration::from_secs(parse_env::<u64>("MEMPOOL_TX_TTL")),
+            sponsor_fee_quotas: parse_sponsor_fee_quotas(&get_env("SPONSOR_FEE_QUOTAS")),
+            sponsor_quota_period: Duration::from_secs(parse_env::<u64>("SPONSOR_QUOTA_PERIOD")),
         }
     }
 }
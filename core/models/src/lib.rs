@@ -21,6 +21,7 @@ pub use crypto_exports::franklin_crypto;
 pub use crypto_exports::rand;
 
 use crate::node::block::Block;
+use crate::node::tx::TxHash;
 use crate::node::BlockNumber;
 use crate::node::{AccountUpdates, TokenId};
 use crate::prover_utils::EncodedProofPlonk;
@@ -96,6 +97,18 @@ pub struct NetworkStatus {
     pub last_verified: BlockNumber,
     pub total_transactions: u32,
     pub outstanding_txs: u32,
+    /// Set once the contract has entered the exodus mode.
+    pub exodus_mode: Option<ExodusMode>,
+}
+
+/// State of the network after the contract has entered the exodus mode.
+///
+/// No more blocks are accepted by the contract, users have to exit their tokens
+/// with proofs built against the state of the last verified block.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ExodusMode {
+    /// Last block verified on the contract.
+    pub last_verified_block: BlockNumber,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,6 +160,10 @@ pub struct Operation {
 pub struct CommitRequest {
     pub block: Block,
     pub accounts_updated: AccountUpdates,
+    /// Hashes of the txs executed in the block.
+    pub executed_txs: Vec<TxHash>,
+    /// Hashes of the txs that failed to execute in the block.
+    pub failed_txs: Vec<TxHash>,
 }
 
 pub const ACTION_COMMIT: &str = "COMMIT";
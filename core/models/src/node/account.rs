@@ -1,9 +1,10 @@
 use crate::params;
 use crate::primitives::GetBits;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
 
+use bigdecimal::BigDecimal;
 use crypto_exports::franklin_crypto::bellman::pairing::ff::{self, PrimeField};
 use crypto_exports::franklin_crypto::eddsa::PublicKey;
 use failure::ensure;
@@ -11,8 +12,8 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::Engine;
 use super::Fr;
-use super::{AccountId, AccountUpdates, Nonce, TokenId};
-use crate::circuit::account::{CircuitAccount, Token};
+use super::{AccountId, AccountUpdates, ContentHash, Nft, Nonce, TokenSlot};
+use crate::circuit::account::{CircuitAccount, OperatorLeaf, Token};
 use crate::circuit::utils::{eth_address_to_fr, pub_key_hash_bytes};
 use crate::merkle_tree::rescue_hasher::BabyRescueHasher;
 use crate::node::{public_key_from_private, PrivateKey};
@@ -95,8 +96,20 @@ impl<'de> Deserialize<'de> for PubKeyHash {
 pub struct Account {
     pub pub_key_hash: PubKeyHash,
     pub address: Address,
-    tokens: BTreeSet<TokenId>,
+    /// Tokens held by the account together with their content hashes,
+    /// keyed by their token subtree slot.
+    tokens: BTreeMap<TokenSlot, (Nft, ContentHash)>,
     pub nonce: Nonce,
+    /// ETH balance of the account in wei, fees of the account txs are paid from it.
+    /// There is no way to withdraw it back to L1.
+    #[serde(default)]
+    pub balance: BigDecimal,
+    /// Operators allowed to transfer a single token of the account, keyed by the token slot.
+    #[serde(default)]
+    approvals: BTreeMap<TokenSlot, AccountId>,
+    /// Operators allowed to transfer any token of the account.
+    #[serde(default)]
+    operators: BTreeSet<AccountId>,
 }
 
 impl PartialEq for Account {
@@ -118,12 +131,16 @@ pub enum AccountUpdate {
     AddToken {
         old_nonce: Nonce,
         new_nonce: Nonce,
-        token: TokenId,
+        token: Nft,
+        content_hash: ContentHash,
+        slot: TokenSlot,
     },
     RemoveToken {
         old_nonce: Nonce,
         new_nonce: Nonce,
-        token: TokenId,
+        token: Nft,
+        content_hash: ContentHash,
+        slot: TokenSlot,
     },
     ChangePubKeyHash {
         old_pub_key_hash: PubKeyHash,
@@ -131,6 +148,27 @@ pub enum AccountUpdate {
         old_nonce: Nonce,
         new_nonce: Nonce,
     },
+    ChangeApproval {
+        token: Nft,
+        slot: TokenSlot,
+        old_operator: Option<AccountId>,
+        new_operator: Option<AccountId>,
+        old_nonce: Nonce,
+        new_nonce: Nonce,
+    },
+    ChangeApprovalForAll {
+        operator: AccountId,
+        old_approved: bool,
+        new_approved: bool,
+        old_nonce: Nonce,
+        new_nonce: Nonce,
+    },
+    UpdateBalance {
+        old_balance: BigDecimal,
+        new_balance: BigDecimal,
+        old_nonce: Nonce,
+        new_nonce: Nonce,
+    },
 }
 
 // TODO: Check if coding to Fr is the same as in the circuit.
@@ -138,27 +176,23 @@ impl From<Account> for CircuitAccount<super::Engine> {
     fn from(acc: Account) -> Self {
         let mut circuit_account = CircuitAccount::default();
 
-        let tokens: Vec<_> = acc
-            .tokens
-            .iter()
-            .enumerate()
-            .map(|(index, token)| {
-                (
-                    *token as u32,
-                    Token {
-                        id: Fr::from_str(&token.to_string()).unwrap(),
-                    },
-                )
-            })
-            .collect();
-
-        for (i, b) in tokens.into_iter() {
-            circuit_account.subtree.insert(u32::from(i), b);
+        for (&slot, (token, content_hash)) in acc.tokens.iter() {
+            let mut leaf = Token::new(token, content_hash);
+            if let Some(operator) = acc.approved(slot) {
+                leaf.approved = Fr::from_str(&operator.to_string()).unwrap();
+            }
+            circuit_account.subtree.insert(slot, leaf);
+        }
+        for operator in acc.get_operators() {
+            circuit_account
+                .operators
+                .insert(operator, OperatorLeaf { approved: true });
         }
 
         circuit_account.nonce = Fr::from_str(&acc.nonce.to_string()).unwrap();
         circuit_account.pub_key_hash = acc.pub_key_hash.to_fr();
         circuit_account.address = eth_address_to_fr(&acc.address);
+        circuit_account.balance = Fr::from_str(&acc.balance.to_string()).unwrap();
         circuit_account
     }
 }
@@ -178,19 +212,27 @@ impl AccountUpdate {
                 old_nonce,
                 new_nonce,
                 token,
+                content_hash,
+                slot,
             } => AccountUpdate::RemoveToken {
                 old_nonce: *new_nonce,
                 new_nonce: *old_nonce,
                 token: *token,
+                content_hash: *content_hash,
+                slot: *slot,
             },
             AccountUpdate::RemoveToken {
                 old_nonce,
                 new_nonce,
                 token,
+                content_hash,
+                slot,
             } => AccountUpdate::AddToken {
                 old_nonce: *new_nonce,
                 new_nonce: *old_nonce,
                 token: *token,
+                content_hash: *content_hash,
+                slot: *slot,
             },
             AccountUpdate::ChangePubKeyHash {
                 old_pub_key_hash,
@@ -203,6 +245,45 @@ impl AccountUpdate {
                 old_nonce: *new_nonce,
                 new_nonce: *old_nonce,
             },
+            AccountUpdate::ChangeApproval {
+                token,
+                slot,
+                old_operator,
+                new_operator,
+                old_nonce,
+                new_nonce,
+            } => AccountUpdate::ChangeApproval {
+                token: *token,
+                slot: *slot,
+                old_operator: *new_operator,
+                new_operator: *old_operator,
+                old_nonce: *new_nonce,
+                new_nonce: *old_nonce,
+            },
+            AccountUpdate::ChangeApprovalForAll {
+                operator,
+                old_approved,
+                new_approved,
+                old_nonce,
+                new_nonce,
+            } => AccountUpdate::ChangeApprovalForAll {
+                operator: *operator,
+                old_approved: *new_approved,
+                new_approved: *old_approved,
+                old_nonce: *new_nonce,
+                new_nonce: *old_nonce,
+            },
+            AccountUpdate::UpdateBalance {
+                old_balance,
+                new_balance,
+                old_nonce,
+                new_nonce,
+            } => AccountUpdate::UpdateBalance {
+                old_balance: new_balance.clone(),
+                new_balance: old_balance.clone(),
+                old_nonce: *new_nonce,
+                new_nonce: *old_nonce,
+            },
         }
     }
 }
@@ -210,10 +291,13 @@ impl AccountUpdate {
 impl Default for Account {
     fn default() -> Self {
         Self {
-            tokens: BTreeSet::new(),
+            tokens: BTreeMap::new(),
             nonce: 0,
+            balance: BigDecimal::from(0),
             pub_key_hash: PubKeyHash::default(),
             address: Address::zero(),
+            approvals: BTreeMap::new(),
+            operators: BTreeSet::new(),
         }
     }
 }
@@ -244,20 +328,134 @@ impl Account {
         (account, updates)
     }
 
-    pub fn has_token(&self, token_id: TokenId) -> bool {
-        self.tokens.contains(&token_id)
+    pub fn has_token(&self, token: Nft) -> bool {
+        self.token_slot(token).is_some()
     }
 
-    pub fn add_token(&mut self, token_id: TokenId) {
-        self.tokens.insert(token_id);
+    /// Slot of the token subtree leaf holding the token.
+    pub fn token_slot(&self, token: Nft) -> Option<TokenSlot> {
+        self.tokens
+            .iter()
+            .find(|(_, (held, _))| *held == token)
+            .map(|(&slot, _)| slot)
+    }
+
+    /// Content hash the token was deposited or minted with.
+    pub fn content_hash(&self, token: Nft) -> Option<ContentHash> {
+        self.tokens
+            .values()
+            .find(|(held, _)| *held == token)
+            .map(|&(_, content_hash)| content_hash)
+    }
+
+    /// Lowest token subtree slot not used by any token of the account,
+    /// `None` if the account already holds `params::total_tokens()` tokens.
+    pub fn free_token_slot(&self) -> Option<TokenSlot> {
+        // slots are sorted, so the first one that differs from its position is the first gap
+        let slot = (0..)
+            .zip(self.tokens.keys())
+            .find(|&(slot, &used)| slot != used)
+            .map(|(slot, _)| slot)
+            .unwrap_or(self.tokens.len() as TokenSlot);
+        if (slot as usize) < params::total_tokens() {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    /// Puts the token into the lowest free slot of the token subtree and returns the slot,
+    /// or `None` if the account is full.
+    /// The slot stays assigned to the token until it is removed from the account.
+    pub fn add_token(&mut self, token: Nft, content_hash: ContentHash) -> Option<TokenSlot> {
+        let slot = self.free_token_slot()?;
+        self.insert_token(slot, token, content_hash);
+        Some(slot)
+    }
+
+    /// Puts the token into the given slot, used when the slot is already known
+    /// (e.g. when the account is restored from the storage).
+    pub fn insert_token(&mut self, slot: TokenSlot, token: Nft, content_hash: ContentHash) {
+        self.tokens.insert(slot, (token, content_hash));
+    }
+
+    /// Removes the token from the account and frees its slot,
+    /// the content hash is returned so that it moves together with the token.
+    /// The approval of the token, if any, is dropped with it.
+    pub fn remove_token(&mut self, token: Nft) -> Option<(TokenSlot, ContentHash)> {
+        let slot = self.token_slot(token)?;
+        self.approvals.remove(&slot);
+        self.tokens
+            .remove(&slot)
+            .map(|(_, content_hash)| (slot, content_hash))
+    }
+
+    pub fn get_tokens(&self) -> Vec<Nft> {
+        self.tokens.values().map(|&(token, _)| token).collect()
     }
 
-    pub fn remove_token(&mut self, token_id: TokenId) {
-        self.tokens.remove(&token_id);
+    /// Tokens of the account together with their token subtree slots and content hashes.
+    pub fn get_token_slots(&self) -> Vec<(TokenSlot, Nft, ContentHash)> {
+        self.tokens
+            .iter()
+            .map(|(&slot, &(token, content_hash))| (slot, token, content_hash))
+            .collect()
+    }
+
+    /// Operator approved to transfer the token held in the slot.
+    pub fn approved(&self, slot: TokenSlot) -> Option<AccountId> {
+        self.approvals.get(&slot).cloned()
+    }
+
+    /// Sets or clears (with `None`) the operator of the token held in the slot,
+    /// the previous operator is returned.
+    pub fn set_approved(
+        &mut self,
+        slot: TokenSlot,
+        operator: Option<AccountId>,
+    ) -> Option<AccountId> {
+        match operator {
+            Some(operator) => self.approvals.insert(slot, operator),
+            None => self.approvals.remove(&slot),
+        }
+    }
+
+    /// Whether the account is approved to transfer all the tokens of this account.
+    pub fn is_operator(&self, operator: AccountId) -> bool {
+        self.operators.contains(&operator)
+    }
+
+    /// Approves or revokes the operator for all the tokens of the account,
+    /// the previous approval is returned.
+    pub fn set_operator(&mut self, operator: AccountId, approved: bool) -> bool {
+        if approved {
+            !self.operators.insert(operator)
+        } else {
+            self.operators.remove(&operator)
+        }
     }
 
-    pub fn get_tokens(&self) -> Vec<TokenId> {
-        self.tokens.clone().into_iter().collect()
+    /// Whether the operator may transfer the token on behalf of this account,
+    /// either by the approval of the token or by the approval for all tokens.
+    pub fn is_approved(&self, token: Nft, operator: AccountId) -> bool {
+        self.is_operator(operator)
+            || self
+                .token_slot(token)
+                .and_then(|slot| self.approved(slot))
+                .map_or(false, |approved| approved == operator)
+    }
+
+    /// Approved operators of the account tokens, keyed by the token slot.
+    pub fn get_approvals(&self) -> Vec<(TokenSlot, AccountId)> {
+        self.approvals
+            .iter()
+            .map(|(&slot, &operator)| (slot, operator))
+            .collect()
+    }
+
+    /// Operators approved for all the tokens of the account.
+    pub fn get_operators(&self) -> Vec<AccountId> {
+        self.operators.iter().cloned().collect()
     }
 
     pub fn apply_updates(mut account: Option<Self>, updates: &[AccountUpdate]) -> Option<Self> {
@@ -272,9 +470,13 @@ impl Account {
             Some(mut account) => match update {
                 AccountUpdate::Delete { .. } => None,
                 AccountUpdate::AddToken {
-                    new_nonce, token, ..
+                    new_nonce,
+                    token,
+                    content_hash,
+                    slot,
+                    ..
                 } => {
-                    account.add_token(token);
+                    account.insert_token(slot, token, content_hash);
                     account.nonce = new_nonce;
                     Some(account)
                 }
@@ -294,6 +496,35 @@ impl Account {
                     account.nonce = new_nonce;
                     Some(account)
                 }
+                AccountUpdate::ChangeApproval {
+                    slot,
+                    new_operator,
+                    new_nonce,
+                    ..
+                } => {
+                    account.set_approved(slot, new_operator);
+                    account.nonce = new_nonce;
+                    Some(account)
+                }
+                AccountUpdate::ChangeApprovalForAll {
+                    operator,
+                    new_approved,
+                    new_nonce,
+                    ..
+                } => {
+                    account.set_operator(operator, new_approved);
+                    account.nonce = new_nonce;
+                    Some(account)
+                }
+                AccountUpdate::UpdateBalance {
+                    new_balance,
+                    new_nonce,
+                    ..
+                } => {
+                    account.balance = new_balance;
+                    account.nonce = new_nonce;
+                    Some(account)
+                }
                 _ => {
                     error!(
                         "Incorrect update received {:?} for account {:?}",
@@ -321,7 +552,9 @@ impl Account {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::node::{apply_updates, reverse_updates, AccountMap, AccountUpdates};
+    use crate::node::{
+        apply_updates, reverse_updates, AccountMap, AccountUpdates, ContentHash, TokenId,
+    };
 
     #[test]
     fn test_default_account() {
@@ -339,13 +572,17 @@ mod test {
         let add_token = AccountUpdate::AddToken {
             old_nonce: 1,
             new_nonce: 2,
-            token: 1,
+            token: Nft::new(1, TokenId::from(1)),
+            content_hash: ContentHash::repeat_byte(0x11),
+            slot: 0,
         };
 
         let remove_token = AccountUpdate::RemoveToken {
             old_nonce: 1,
             new_nonce: 2,
-            token: 1,
+            token: Nft::new(1, TokenId::from(1)),
+            content_hash: ContentHash::repeat_byte(0x11),
+            slot: 0,
         };
 
         let delete = AccountUpdate::Delete {
@@ -378,7 +615,10 @@ mod test {
             {
                 let mut updated_account = Account::default();
                 updated_account.nonce = 2;
-                updated_account.add_token(1);
+                updated_account.add_token(
+                    Nft::new(1, TokenId::from(1)),
+                    ContentHash::repeat_byte(0x11),
+                );
                 assert_eq!(
                     Account::apply_update(Some(Account::default()), add_token)
                         .unwrap()
@@ -390,7 +630,10 @@ mod test {
         }
         {
             let mut initial_account = Account::default();
-            initial_account.add_token(1);
+            initial_account.add_token(
+                Nft::new(1, TokenId::from(1)),
+                ContentHash::repeat_byte(0x11),
+            );
             let mut updated_account = Account::default();
             updated_account.nonce = 2;
             assert_eq!(
@@ -402,6 +645,161 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_token_slots() {
+        let first = Nft::new(1, TokenId::from(5));
+        let second = Nft::new(1, TokenId::from(1));
+        let third = Nft::new(2, TokenId::from(3));
+
+        let content_hash = ContentHash::repeat_byte(0x11);
+
+        let mut account = Account::default();
+        assert_eq!(account.add_token(first, ContentHash::zero()), Some(0));
+        assert_eq!(account.add_token(second, content_hash), Some(1));
+        assert_eq!(account.add_token(third, ContentHash::zero()), Some(2));
+        assert_eq!(account.content_hash(second), Some(content_hash));
+
+        // removing a token frees its slot and does not move the others
+        assert_eq!(account.remove_token(second), Some((1, content_hash)));
+        assert_eq!(account.remove_token(second), None);
+        assert_eq!(account.content_hash(second), None);
+        assert_eq!(account.token_slot(first), Some(0));
+        assert_eq!(account.token_slot(third), Some(2));
+        assert_eq!(account.free_token_slot(), Some(1));
+
+        let fourth = Nft::new(1, TokenId::from(0));
+        assert_eq!(account.add_token(fourth, content_hash), Some(1));
+        assert_eq!(
+            account.get_token_slots(),
+            vec![
+                (0, first, ContentHash::zero()),
+                (1, fourth, content_hash),
+                (2, third, ContentHash::zero())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_account() {
+        let mut account = Account::default();
+        for slot in 0..params::total_tokens() as TokenSlot {
+            account.insert_token(slot, Nft::new(1, TokenId::from(slot)), ContentHash::zero());
+        }
+        assert_eq!(account.free_token_slot(), None);
+        assert_eq!(
+            account.add_token(Nft::new(2, TokenId::from(0)), ContentHash::zero()),
+            None
+        );
+
+        account.remove_token(Nft::new(1, TokenId::from(7)));
+        assert_eq!(
+            account.add_token(Nft::new(2, TokenId::from(0)), ContentHash::zero()),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_approvals() {
+        let token = Nft::new(1, TokenId::from(1));
+        let other = Nft::new(1, TokenId::from(2));
+
+        let mut account = Account::default();
+        let slot = account.add_token(token, ContentHash::zero()).unwrap();
+        account.add_token(other, ContentHash::zero());
+
+        assert_eq!(account.set_approved(slot, Some(5)), None);
+        assert_eq!(account.approved(slot), Some(5));
+        assert!(account.is_approved(token, 5));
+        assert!(!account.is_approved(other, 5));
+        assert!(!account.is_approved(token, 6));
+
+        assert!(!account.set_operator(6, true));
+        assert!(account.set_operator(6, true));
+        assert!(account.is_approved(token, 6));
+        assert!(account.is_approved(other, 6));
+
+        // approval of the token is dropped together with the token, approval for all stays
+        account.remove_token(token);
+        assert_eq!(account.approved(slot), None);
+        assert!(account.get_approvals().is_empty());
+        assert_eq!(account.get_operators(), vec![6]);
+
+        assert!(account.set_operator(6, false));
+        assert!(!account.is_approved(other, 6));
+    }
+
+    #[test]
+    fn test_approval_updates() {
+        let token = Nft::new(1, TokenId::from(1));
+        let mut account = Account::default();
+        let slot = account.add_token(token, ContentHash::zero()).unwrap();
+
+        let approve = AccountUpdate::ChangeApproval {
+            token,
+            slot,
+            old_operator: None,
+            new_operator: Some(3),
+            old_nonce: 0,
+            new_nonce: 1,
+        };
+        let approve_for_all = AccountUpdate::ChangeApprovalForAll {
+            operator: 4,
+            old_approved: false,
+            new_approved: true,
+            old_nonce: 1,
+            new_nonce: 2,
+        };
+
+        let updated = Account::apply_updates(
+            Some(account.clone()),
+            &[approve.clone(), approve_for_all.clone()],
+        )
+        .unwrap();
+        assert_eq!(updated.nonce, 2);
+        assert_eq!(updated.approved(slot), Some(3));
+        assert!(updated.is_operator(4));
+
+        let reverted = Account::apply_updates(
+            Some(updated),
+            &[approve_for_all.reversed_update(), approve.reversed_update()],
+        )
+        .unwrap();
+        assert_eq!(reverted.nonce, 0);
+        assert_eq!(reverted.approved(slot), None);
+        assert!(!reverted.is_operator(4));
+    }
+
+    #[test]
+    fn test_balance_updates() {
+        let account = Account::default();
+
+        let deposit = AccountUpdate::UpdateBalance {
+            old_balance: BigDecimal::from(0),
+            new_balance: BigDecimal::from(1_000_000),
+            old_nonce: 0,
+            new_nonce: 0,
+        };
+        let fee = AccountUpdate::UpdateBalance {
+            old_balance: BigDecimal::from(1_000_000),
+            new_balance: BigDecimal::from(400_000),
+            old_nonce: 0,
+            new_nonce: 0,
+        };
+
+        let updated =
+            Account::apply_updates(Some(account.clone()), &[deposit.clone(), fee.clone()]).unwrap();
+        assert_eq!(updated.balance, BigDecimal::from(400_000));
+        assert_ne!(updated.get_bits_le(), account.get_bits_le());
+
+        let reverted = Account::apply_updates(
+            Some(updated),
+            &[fee.reversed_update(), deposit.reversed_update()],
+        )
+        .unwrap();
+        assert_eq!(reverted.balance, BigDecimal::from(0));
+        assert_eq!(reverted.get_bits_le(), account.get_bits_le());
+    }
+
     #[test]
     fn test_account_updates() {
         // Create two accounts: 0, 1
@@ -423,7 +821,7 @@ mod test {
             let mut map = AccountMap::default();
             let mut account_1 = Account::default();
             account_1.nonce = 17;
-            account_1.add_token(1);
+            account_1.add_token(Nft::new(1, TokenId::from(1)), ContentHash::zero());
             map.insert(1, account_1);
             let mut account_2 = Account::default();
             account_2.nonce = 36;
@@ -445,7 +843,9 @@ mod test {
                 AccountUpdate::AddToken {
                     old_nonce: 16,
                     new_nonce: 17,
-                    token: 1,
+                    token: Nft::new(1, TokenId::from(1)),
+                    content_hash: ContentHash::zero(),
+                    slot: 0,
                 },
             ));
             updates.push((
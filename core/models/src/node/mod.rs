@@ -1,6 +1,6 @@
 use super::merkle_tree::{RescueHasher, SparseMerkleTree};
 use super::params;
-use super::primitives::{pack_as_float, u128_to_bigdecimal, unpack_float};
+use super::primitives::{big_decimal_to_u128, pack_as_float, u128_to_bigdecimal, unpack_float};
 use crate::franklin_crypto::bellman::pairing::bn256;
 use crate::franklin_crypto::{
     eddsa::{PrivateKey as PrivateKeyImport, PublicKey as PublicKeyImport},
@@ -21,11 +21,20 @@ pub use web3::types::{H256, U128, U256};
 pub use self::account::{Account, AccountUpdate, PubKeyHash};
 pub use self::block::{ExecutedOperations, ExecutedPriorityOp, ExecutedTx};
 pub use self::operations::{
-    CloseOp, DepositOp, FranklinOp, FullExitOp, TransferOp, TransferToNewOp, WithdrawOp,
+    ApproveForAllOp, ApproveOp, BatchTransferOp, BurnOp, CloseOp, DepositEthOp, DepositOp,
+    ForcedWithdrawOp, FranklinOp, FullExitOp, MintOp, SwapOp, TransferOp, TransferToNewOp,
+    WithdrawOp,
+};
+pub use self::priority_ops::{
+    Deposit, DepositEth, ForcedWithdraw, FranklinPriorityOp, FullExit, PriorityOp,
+};
+pub use self::tokens::{
+    MinterStatusEvent, Nft, Token, TokenAddedEvent, TokenGenesisListItem, TokenLike,
+};
+pub use self::tx::{
+    Approve, ApproveForAll, BatchTransfer, Burn, Close, FranklinTx, Mint, Order, Sponsorship, Swap,
+    Transfer, Withdraw,
 };
-pub use self::priority_ops::{Deposit, FranklinPriorityOp, FullExit, PriorityOp};
-pub use self::tokens::{Token, TokenGenesisListItem, TokenLike};
-pub use self::tx::{Close, FranklinTx, Transfer, Withdraw};
 
 pub type Engine = bn256::Bn256;
 pub type Fr = bn256::Fr;
@@ -59,7 +68,14 @@ pub fn reverse_updates(updates: &mut AccountUpdates) {
     }
 }
 
-pub type TokenId = u16;
+/// ERC-721 token id, carried as the full `uint256` accepted by `onERC721Received`.
+pub type TokenId = U256;
+/// Id of the ERC-721 collection registered in the governance contract.
+pub type CollectionId = u16;
+/// Index of the account token subtree leaf holding a token, see `Account::add_token`.
+pub type TokenSlot = u32;
+/// Hash of the token metadata or content, committed to by the token subtree leaf.
+pub type ContentHash = H256;
 
 /// 3 bytes used.
 pub type AccountId = u32;
@@ -108,9 +124,21 @@ pub fn unpack_fee_amount(data: &[u8]) -> Option<BigDecimal> {
     .map(u128_to_bigdecimal)
 }
 
+/// Returns the smallest packable fee amount which is not less than the given one,
+/// so the rounded fee estimate still covers the costs.
 pub fn closest_packable_fee_amount(amount: &BigDecimal) -> BigDecimal {
-    let fee_packed = pack_fee_amount(&amount);
-    unpack_fee_amount(&fee_packed).expect("fee repacking")
+    let amount = big_decimal_to_u128(amount);
+    let max_mantissa = (1u128 << params::FEE_MANTISSA_BIT_WIDTH) - 1;
+    let mut exponent_base = 1u128;
+    // The higher the exponent, the coarser the rounding, so the lowest fitting one is used.
+    for _ in 0..(1 << params::FEE_EXPONENT_BIT_WIDTH) {
+        let mantissa = amount / exponent_base + (amount % exponent_base != 0) as u128;
+        if mantissa <= max_mantissa {
+            return u128_to_bigdecimal(mantissa * exponent_base);
+        }
+        exponent_base *= 10;
+    }
+    panic!("Fee amount is too big to be packed");
 }
 
 pub fn closest_packable_token_amount(amount: &BigDecimal) -> BigDecimal {
@@ -182,11 +210,21 @@ mod test {
             "repacked fee should not be 0"
         );
         assert!(
-            closest_packable_fee < fee,
-            "packable fee should be less then original"
+            closest_packable_fee > fee,
+            "packable fee should be greater then original"
+        );
+        assert_eq!(
+            closest_packable_fee,
+            BigDecimal::from(1_235_000_000),
+            "packable fee should be the closest greater one"
+        );
+        assert_eq!(
+            closest_packable_fee_amount(&closest_packable_fee),
+            closest_packable_fee,
+            "packable fee should not be changed"
         );
         println!(
-            "fee: original: {}, truncated: {}",
+            "fee: original: {}, rounded up: {}",
             fee, closest_packable_fee
         );
 
@@ -3,42 +3,78 @@ use super::AccountId;
 use super::FranklinTx;
 use crate::node::tx::ChangePubKey;
 use crate::node::{
-    pack_fee_amount, pack_token_amount, unpack_fee_amount, unpack_token_amount, Close, Deposit,
-    FranklinPriorityOp, FullExit, PubKeyHash, Transfer, Withdraw,
+    pack_fee_amount, pack_token_amount, unpack_fee_amount, unpack_token_amount, Approve,
+    ApproveForAll, BatchTransfer, Burn, Close, ContentHash, Deposit, DepositEth, ForcedWithdraw,
+    FranklinPriorityOp, FullExit, Mint, Nft, Order, PubKeyHash, Sponsorship, Swap, Transfer,
+    Withdraw,
 };
 use crate::params::{
-    ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, ETH_ADDRESS_BIT_WIDTH, FEE_EXPONENT_BIT_WIDTH,
+    ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, BALANCE_BIT_WIDTH, CHUNK_BIT_WIDTH,
+    COLLECTIONID_BIT_WIDTH, CONTENT_HASH_BIT_WIDTH, ETH_ADDRESS_BIT_WIDTH, FEE_EXPONENT_BIT_WIDTH,
     FEE_MANTISSA_BIT_WIDTH, FR_ADDRESS_LEN, NEW_PUBKEY_HASH_WIDTH, NONCE_BIT_WIDTH,
-    TOKENID_BIT_WIDTH,
+    TOKENID_BIT_WIDTH, TOKENS_COUNT_BIT_WIDTH,
 };
 use crate::primitives::{
-    big_decimal_to_u128, bytes_slice_to_uint128, bytes_slice_to_uint16, bytes_slice_to_uint32,
-    u128_to_bigdecimal,
+    big_decimal_to_u128, bytes_slice_to_uint128, bytes_slice_to_uint16, bytes_slice_to_uint256,
+    bytes_slice_to_uint32, u128_to_bigdecimal, u256_to_be_bytes,
 };
 use bigdecimal::BigDecimal;
 use failure::{ensure, format_err};
 use web3::types::Address;
 
+/// Sponsorship restored from the fee payer of the op pubdata, `None` if the sender pays the fee.
+/// The sponsor signature is unknown from pubdata.
+fn sponsorship_from_fee_payer(fee_payer: AccountId, account_id: AccountId) -> Option<Sponsorship> {
+    if fee_payer == account_id {
+        None
+    } else {
+        Some(Sponsorship {
+            account_id: fee_payer,
+            signature: TxSignature::default(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositOp {
     pub priority_op: Deposit,
     pub account_id: AccountId,
+    /// Whether the token was credited, the deposit fails if the account has no free token slot
+    /// or the token is already held by an account. A failed deposit is refunded to `priority_op.to`.
+    pub success: bool,
 }
 
 impl DepositOp {
-    pub const CHUNKS: usize = 6;
+    pub const CHUNKS: usize = 12;
     pub const OP_CODE: u8 = 0x01;
+    pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [1];
 
     pub fn get_public_data(&self) -> Vec<u8> {
         let mut data = Vec::new();
         data.push(Self::OP_CODE); // opcode
         data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
-        data.extend_from_slice(&self.priority_op.token_id.to_be_bytes());
+        data.extend_from_slice(&self.priority_op.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.priority_op.token_id));
         data.extend_from_slice(&self.priority_op.to.as_bytes());
+        data.extend_from_slice(self.priority_op.content_hash.as_bytes());
+        data.push(self.success as u8);
         data.resize(Self::CHUNKS * 8, 0x00);
         data
     }
 
+    /// Only a failed deposit sends the token back to its owner.
+    fn get_withdrawal_data(&self) -> Option<Vec<u8>> {
+        if self.success {
+            return None;
+        }
+        let mut data = Vec::new();
+        data.extend_from_slice(&Self::WITHDRAW_DATA_PREFIX); // first byte is a bool variable 'addToPendingWithdrawalsQueue'
+        data.extend_from_slice(self.priority_op.to.as_bytes());
+        data.extend_from_slice(&self.priority_op.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.priority_op.token_id));
+        Some(data)
+    }
+
     pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
         ensure!(
             bytes.len() == Self::CHUNKS * 8,
@@ -46,25 +82,42 @@ impl DepositOp {
         );
 
         let account_id_offset = 1;
-        let token_id_offset = account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let collection_id_offset = account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
         let account_address_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
+        let content_hash_offset = account_address_offset + FR_ADDRESS_LEN;
+        let success_offset = content_hash_offset + CONTENT_HASH_BIT_WIDTH / 8;
 
         let account_id = bytes_slice_to_uint32(
             &bytes[account_id_offset..account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8],
         )
         .ok_or_else(|| format_err!("Cant get account id from deposit pubdata"))?;
-        let token_id =
-            bytes_slice_to_uint16(&bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8])
-                .ok_or_else(|| format_err!("Cant get token_id from deposit pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from deposit pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token_id from deposit pubdata"))?;
         let to = Address::from_slice(
             &bytes[account_address_offset..account_address_offset + FR_ADDRESS_LEN],
         );
+        let content_hash = ContentHash::from_slice(&bytes[content_hash_offset..success_offset]);
+        let success = bytes[success_offset] != 0;
 
         let from = Address::default(); // unknown from pubdata.
 
         Ok(Self {
-            priority_op: Deposit { from, token_id, to },
+            priority_op: Deposit {
+                from,
+                collection_id,
+                token_id,
+                to,
+                content_hash,
+            },
             account_id,
+            success,
         })
     }
 }
@@ -99,17 +152,21 @@ pub struct TransferToNewOp {
 }
 
 impl TransferToNewOp {
-    pub const CHUNKS: usize = 5;
+    pub const CHUNKS: usize = 9;
+    /// Chunk charging the fee from the sponsor of a sponsored tx.
+    pub const SPONSOR_CHUNK: usize = 3;
     pub const OP_CODE: u8 = 0x02;
 
     fn get_public_data(&self) -> Vec<u8> {
         let mut data = Vec::new();
         data.push(Self::OP_CODE); // opcode
         data.extend_from_slice(&self.from.to_be_bytes()[1..]);
-        data.extend_from_slice(&self.tx.token_id.to_be_bytes());
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
         data.extend_from_slice(&self.tx.to.as_bytes());
         data.extend_from_slice(&self.to.to_be_bytes()[1..]);
-        //data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.extend_from_slice(&self.tx.fee_payer().to_be_bytes()[1..]);
         data.resize(Self::CHUNKS * 8, 0x00);
         data
     }
@@ -121,10 +178,12 @@ impl TransferToNewOp {
         );
 
         let from_offset = 1;
-        let token_id_offset = from_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let collection_id_offset = from_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
         let to_address_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
         let to_id_offset = to_address_offset + FR_ADDRESS_LEN;
         let fee_offset = to_id_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let fee_payer_offset = fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8;
 
         let from_id =
             bytes_slice_to_uint32(&bytes[from_offset..from_offset + ACCOUNT_ID_BIT_WIDTH / 8])
@@ -138,17 +197,40 @@ impl TransferToNewOp {
                 })?;
         let from = Address::zero(); // It is unknown from pubdata;
         let to = Address::from_slice(&bytes[to_address_offset..to_address_offset + FR_ADDRESS_LEN]);
-        let token_id =
-            bytes_slice_to_uint16(&bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8])
-                .ok_or_else(|| format_err!("Cant get token id from transfer to new pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from transfer to new pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token id from transfer to new pubdata"))?;
         let fee = unpack_fee_amount(
             &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
         )
         .ok_or_else(|| format_err!("Cant get fee from transfer to new pubdata"))?;
+        let fee_payer = bytes_slice_to_uint32(
+            &bytes[fee_payer_offset..fee_payer_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee payer id from transfer to new pubdata"))?;
         let nonce = 0; // It is unknown from pubdata
 
+        let mut tx = Transfer::new(
+            from_id,
+            from,
+            to,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            None, // validity window is unknown from pubdata
+            None,
+            None,
+        );
+        tx.sponsor = sponsorship_from_fee_payer(fee_payer, from_id);
+
         Ok(Self {
-            tx: Transfer::new(from_id, from, to, token_id, fee, nonce, None),
+            tx,
             from: from_id,
             to: to_id,
         })
@@ -163,16 +245,20 @@ pub struct TransferOp {
 }
 
 impl TransferOp {
-    pub const CHUNKS: usize = 2;
+    pub const CHUNKS: usize = 6;
+    /// Chunk charging the fee from the sponsor of a sponsored tx.
+    pub const SPONSOR_CHUNK: usize = 3;
     pub const OP_CODE: u8 = 0x05;
 
     fn get_public_data(&self) -> Vec<u8> {
         let mut data = Vec::new();
         data.push(Self::OP_CODE); // opcode
         data.extend_from_slice(&self.from.to_be_bytes()[1..]);
-        data.extend_from_slice(&self.tx.token_id.to_be_bytes());
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
         data.extend_from_slice(&self.to.to_be_bytes()[1..]);
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.extend_from_slice(&self.tx.fee_payer().to_be_bytes()[1..]);
         data.resize(Self::CHUNKS * 8, 0x00);
         data
     }
@@ -184,15 +270,22 @@ impl TransferOp {
         );
 
         let from_offset = 1;
-        let token_id_offset = from_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let collection_id_offset = from_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
         let to_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
         let fee_offset = to_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let fee_payer_offset = fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8;
 
         let from_address = Address::zero(); // From pubdata its unknown
         let to_address = Address::zero(); // From pubdata its unknown
-        let token_id =
-            bytes_slice_to_uint16(&bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8])
-                .ok_or_else(|| format_err!("Cant get token id from transfer pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from transfer pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token id from transfer pubdata"))?;
         let fee = unpack_fee_amount(
             &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
         )
@@ -203,17 +296,234 @@ impl TransferOp {
                 .ok_or_else(|| format_err!("Cant get from account id from transfer pubdata"))?;
         let to_id = bytes_slice_to_uint32(&bytes[to_offset..to_offset + ACCOUNT_ID_BIT_WIDTH / 8])
             .ok_or_else(|| format_err!("Cant get to account id from transfer pubdata"))?;
+        let fee_payer = bytes_slice_to_uint32(
+            &bytes[fee_payer_offset..fee_payer_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee payer id from transfer pubdata"))?;
+
+        let mut tx = Transfer::new(
+            from_id,
+            from_address,
+            to_address,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            None, // validity window is unknown from pubdata
+            None,
+            None,
+        );
+        tx.sponsor = sponsorship_from_fee_payer(fee_payer, from_id);
 
         Ok(Self {
-            tx: Transfer::new(
-                from_id,
-                from_address,
-                to_address,
-                token_id,
-                fee,
-                nonce,
-                None,
-            ),
+            tx,
+            from: from_id,
+            to: to_id,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapOp {
+    pub tx: Swap,
+    pub account_a: AccountId,
+    pub account_b: AccountId,
+}
+
+impl SwapOp {
+    pub const CHUNKS: usize = 10;
+    pub const OP_CODE: u8 = 0x09;
+
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.account_a.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.tx.order_a.token_sell.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.order_a.token_sell.token_id));
+        data.extend_from_slice(&self.account_b.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.tx.order_b.token_sell.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.order_b.token_sell.token_id));
+        data.extend_from_slice(&pack_fee_amount(&self.tx.order_a.fee));
+        data.extend_from_slice(&pack_fee_amount(&self.tx.order_b.fee));
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for swap pubdata"
+        );
+
+        let account_a_offset = 1;
+        let token_a_offset = account_a_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let account_b_offset = token_a_offset + (COLLECTIONID_BIT_WIDTH + TOKENID_BIT_WIDTH) / 8;
+        let token_b_offset = account_b_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let fee_a_offset = token_b_offset + (COLLECTIONID_BIT_WIDTH + TOKENID_BIT_WIDTH) / 8;
+        let fee_b_offset = fee_a_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8;
+
+        let read_account_id = |offset: usize| {
+            bytes_slice_to_uint32(&bytes[offset..offset + ACCOUNT_ID_BIT_WIDTH / 8])
+                .ok_or_else(|| format_err!("Cant get account id from swap pubdata"))
+        };
+        let read_token = |offset: usize| -> Result<Nft, failure::Error> {
+            let token_id_offset = offset + COLLECTIONID_BIT_WIDTH / 8;
+            let collection_id = bytes_slice_to_uint16(&bytes[offset..token_id_offset])
+                .ok_or_else(|| format_err!("Cant get collection id from swap pubdata"))?;
+            let token_id = bytes_slice_to_uint256(
+                &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+            )
+            .ok_or_else(|| format_err!("Cant get token id from swap pubdata"))?;
+            Ok(Nft::new(collection_id, token_id))
+        };
+        let read_fee = |offset: usize| {
+            unpack_fee_amount(
+                &bytes[offset..offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
+            )
+            .ok_or_else(|| format_err!("Cant get fee from swap pubdata"))
+        };
+
+        let account_a = read_account_id(account_a_offset)?;
+        let token_a = read_token(token_a_offset)?;
+        let account_b = read_account_id(account_b_offset)?;
+        let token_b = read_token(token_b_offset)?;
+        let fee_a = read_fee(fee_a_offset)?;
+        let fee_b = read_fee(fee_b_offset)?;
+        // Addresses, nonces and the rest of the accepted tokens are unknown from pubdata
+        let address = Address::zero();
+        let nonce = 0;
+        let order_a = Order::new(
+            account_a,
+            address,
+            token_a,
+            vec![token_b],
+            fee_a,
+            nonce,
+            None,
+        );
+        let order_b = Order::new(
+            account_b,
+            address,
+            token_b,
+            vec![token_a],
+            fee_b,
+            nonce,
+            None,
+        );
+
+        Ok(Self {
+            tx: Swap::new(order_a, order_b),
+            account_a,
+            account_b,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTransferOp {
+    pub tx: BatchTransfer,
+    pub from: AccountId,
+    pub to: AccountId,
+}
+
+impl BatchTransferOp {
+    pub const OP_CODE: u8 = 0x08;
+    /// opcode | from | to | to address | fee | tokens count
+    pub const HEADER_BYTES: usize = 1
+        + 2 * ACCOUNT_ID_BIT_WIDTH / 8
+        + FR_ADDRESS_LEN
+        + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8
+        + TOKENS_COUNT_BIT_WIDTH / 8;
+    /// collection_id | token_id
+    pub const TOKEN_BYTES: usize = (COLLECTIONID_BIT_WIDTH + TOKENID_BIT_WIDTH) / 8;
+    pub const TOKENS_COUNT_OFFSET: usize = Self::HEADER_BYTES - TOKENS_COUNT_BIT_WIDTH / 8;
+    /// Header and every token are padded to whole chunks, so that the circuit
+    /// knows which token a chunk belongs to from its number alone.
+    pub const HEADER_CHUNKS: usize =
+        (Self::HEADER_BYTES + CHUNK_BIT_WIDTH / 8 - 1) / (CHUNK_BIT_WIDTH / 8);
+    pub const TOKEN_CHUNKS: usize =
+        (Self::TOKEN_BYTES + CHUNK_BIT_WIDTH / 8 - 1) / (CHUNK_BIT_WIDTH / 8);
+
+    /// Unlike other operations batch transfer has variable size, that depends on the number of tokens.
+    pub fn chunks_for(tokens_count: usize) -> usize {
+        Self::HEADER_CHUNKS + tokens_count * Self::TOKEN_CHUNKS
+    }
+
+    pub fn chunks(&self) -> usize {
+        Self::chunks_for(self.tx.tokens.len())
+    }
+
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.from.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.to.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.tx.to.as_bytes());
+        data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.push(self.tx.tokens.len() as u8);
+        data.resize(Self::HEADER_CHUNKS * 8, 0x00);
+        for token in &self.tx.tokens {
+            let token_offset = data.len();
+            data.extend_from_slice(&token.collection_id.to_be_bytes());
+            data.extend_from_slice(&u256_to_be_bytes(&token.token_id));
+            data.resize(token_offset + Self::TOKEN_CHUNKS * 8, 0x00);
+        }
+        data
+    }
+
+    /// Reads the pubdata length of the batch transfer from its header.
+    pub fn public_data_length(bytes: &[u8]) -> Result<usize, failure::Error> {
+        ensure!(
+            bytes.len() >= Self::HEADER_BYTES,
+            "Batch transfer pubdata too short"
+        );
+        let tokens_count = bytes[Self::TOKENS_COUNT_OFFSET] as usize;
+        Ok(Self::chunks_for(tokens_count) * 8)
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::public_data_length(bytes)?,
+            "Wrong bytes length for batch transfer pubdata"
+        );
+
+        let from_offset = 1;
+        let to_offset = from_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let to_address_offset = to_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let fee_offset = to_address_offset + FR_ADDRESS_LEN;
+
+        let from_id =
+            bytes_slice_to_uint32(&bytes[from_offset..from_offset + ACCOUNT_ID_BIT_WIDTH / 8])
+                .ok_or_else(|| {
+                    format_err!("Cant get from account id from batch transfer pubdata")
+                })?;
+        let to_id = bytes_slice_to_uint32(&bytes[to_offset..to_offset + ACCOUNT_ID_BIT_WIDTH / 8])
+            .ok_or_else(|| format_err!("Cant get to account id from batch transfer pubdata"))?;
+        let from = Address::zero(); // It is unknown from pubdata
+        let to = Address::from_slice(&bytes[to_address_offset..to_address_offset + FR_ADDRESS_LEN]);
+        let fee = unpack_fee_amount(
+            &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee from batch transfer pubdata"))?;
+
+        let tokens_count = bytes[Self::TOKENS_COUNT_OFFSET] as usize;
+        let mut tokens = Vec::with_capacity(tokens_count);
+        for token_bytes in bytes[Self::HEADER_CHUNKS * 8..]
+            .chunks_exact(Self::TOKEN_CHUNKS * 8)
+            .take(tokens_count)
+        {
+            let (collection_id_bytes, token_id_bytes) =
+                token_bytes[..Self::TOKEN_BYTES].split_at(COLLECTIONID_BIT_WIDTH / 8);
+            let collection_id = bytes_slice_to_uint16(collection_id_bytes)
+                .ok_or_else(|| format_err!("Cant get collection id from batch transfer pubdata"))?;
+            let token_id = bytes_slice_to_uint256(token_id_bytes)
+                .ok_or_else(|| format_err!("Cant get token id from batch transfer pubdata"))?;
+            tokens.push(Nft::new(collection_id, token_id));
+        }
+        let nonce = 0; // It is unknown from pubdata
+
+        Ok(Self {
+            tx: BatchTransfer::new(from_id, from, to, tokens, fee, nonce, None),
             from: from_id,
             to: to_id,
         })
@@ -227,7 +537,9 @@ pub struct WithdrawOp {
 }
 
 impl WithdrawOp {
-    pub const CHUNKS: usize = 6;
+    pub const CHUNKS: usize = 8;
+    /// Chunk charging the fee from the sponsor of a sponsored tx.
+    pub const SPONSOR_CHUNK: usize = 3;
     pub const OP_CODE: u8 = 0x03;
     pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [1];
 
@@ -235,9 +547,11 @@ impl WithdrawOp {
         let mut data = Vec::new();
         data.push(Self::OP_CODE); // opcode
         data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
-        data.extend_from_slice(&self.tx.token_id.to_be_bytes());
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
         data.extend_from_slice(self.tx.to.as_bytes());
+        data.extend_from_slice(&self.tx.fee_payer().to_be_bytes()[1..]);
         data.resize(Self::CHUNKS * 8, 0x00);
         data
     }
@@ -246,7 +560,8 @@ impl WithdrawOp {
         let mut data = Vec::new();
         data.extend_from_slice(&Self::WITHDRAW_DATA_PREFIX); // first byte is a bool variable 'addToPendingWithdrawalsQueue'
         data.extend_from_slice(self.tx.to.as_bytes());
-        data.extend_from_slice(&self.tx.token_id.to_be_bytes());
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
         data
     }
 
@@ -257,18 +572,25 @@ impl WithdrawOp {
         );
 
         let account_offset = 1;
-        let token_id_offset = account_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let collection_id_offset = account_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
         let fee_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
         let eth_address_offset = fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8;
+        let fee_payer_offset = eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8;
 
         let account_id = bytes_slice_to_uint32(
             &bytes[account_offset..account_offset + ACCOUNT_ID_BIT_WIDTH / 8],
         )
         .ok_or_else(|| format_err!("Cant get account id from withdraw pubdata"))?;
         let from = Address::zero(); // From pubdata it is unknown
-        let token_id =
-            bytes_slice_to_uint16(&bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8])
-                .ok_or_else(|| format_err!("Cant get token id from withdraw pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from withdraw pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token id from withdraw pubdata"))?;
         let to = Address::from_slice(
             &bytes[eth_address_offset..eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8],
         );
@@ -276,12 +598,27 @@ impl WithdrawOp {
             &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
         )
         .ok_or_else(|| format_err!("Cant get fee from withdraw pubdata"))?;
+        let fee_payer = bytes_slice_to_uint32(
+            &bytes[fee_payer_offset..fee_payer_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee payer id from withdraw pubdata"))?;
         let nonce = 0; // From pubdata it is unknown
 
-        Ok(Self {
-            tx: Withdraw::new(account_id, from, to, token_id, fee, nonce, None),
+        let mut tx = Withdraw::new(
             account_id,
-        })
+            from,
+            to,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            None, // validity window is unknown from pubdata
+            None,
+            None,
+        );
+        tx.sponsor = sponsorship_from_fee_payer(fee_payer, account_id);
+
+        Ok(Self { tx, account_id })
     }
 }
 
@@ -455,6 +792,405 @@ impl FullExitOp {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintOp {
+    pub tx: Mint,
+    pub account_id: AccountId,
+}
+
+impl MintOp {
+    pub const CHUNKS: usize = 12;
+    pub const OP_CODE: u8 = 0x0a;
+
+    /// Minter address is a part of pubdata, so the contract checks that the minter
+    /// is authorized in the governance contract.
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
+        data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.extend_from_slice(self.tx.account.as_bytes());
+        data.extend_from_slice(self.tx.content_hash.as_bytes());
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for mint pubdata"
+        );
+
+        let account_offset = 1;
+        let collection_id_offset = account_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
+        let fee_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
+        let eth_address_offset = fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8;
+        let content_hash_offset = eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8;
+
+        let account_id = bytes_slice_to_uint32(
+            &bytes[account_offset..account_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get account id from mint pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from mint pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token id from mint pubdata"))?;
+        let fee = unpack_fee_amount(
+            &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee from mint pubdata"))?;
+        let account = Address::from_slice(
+            &bytes[eth_address_offset..eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8],
+        );
+        let content_hash = ContentHash::from_slice(
+            &bytes[content_hash_offset..content_hash_offset + CONTENT_HASH_BIT_WIDTH / 8],
+        );
+        let nonce = 0; // From pubdata it is unknown
+
+        Ok(Self {
+            tx: Mint::new(
+                account_id,
+                account,
+                collection_id,
+                token_id,
+                content_hash,
+                fee,
+                nonce,
+                None,
+            ),
+            account_id,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnOp {
+    pub tx: Burn,
+    pub account_id: AccountId,
+}
+
+impl BurnOp {
+    pub const CHUNKS: usize = 5;
+    pub const OP_CODE: u8 = 0x0b;
+
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
+        data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for burn pubdata"
+        );
+
+        let account_offset = 1;
+        let collection_id_offset = account_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
+        let fee_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
+
+        let account_id = bytes_slice_to_uint32(
+            &bytes[account_offset..account_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get account id from burn pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from burn pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token id from burn pubdata"))?;
+        let fee = unpack_fee_amount(
+            &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee from burn pubdata"))?;
+        let from = Address::zero(); // From pubdata it is unknown
+        let nonce = 0; // From pubdata it is unknown
+
+        Ok(Self {
+            tx: Burn::new(account_id, from, collection_id, token_id, fee, nonce, None),
+            account_id,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApproveOp {
+    pub tx: Approve,
+    pub account_id: AccountId,
+    /// Account of the approved operator, `None` when the approval is revoked.
+    pub operator_id: Option<AccountId>,
+}
+
+impl ApproveOp {
+    pub const CHUNKS: usize = 6;
+    pub const OP_CODE: u8 = 0x0c;
+
+    fn get_public_data(&self) -> Vec<u8> {
+        // revoked approval is published as the approval of the account itself
+        let operator_id = self.operator_id.unwrap_or(self.account_id);
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        data.extend_from_slice(&operator_id.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.tx.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.tx.token_id));
+        data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for approve pubdata"
+        );
+
+        let account_offset = 1;
+        let operator_offset = account_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let collection_id_offset = operator_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
+        let fee_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
+
+        let account_id = bytes_slice_to_uint32(
+            &bytes[account_offset..account_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get account id from approve pubdata"))?;
+        let operator_id = bytes_slice_to_uint32(
+            &bytes[operator_offset..operator_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get operator id from approve pubdata"))?;
+        let collection_id = bytes_slice_to_uint16(
+            &bytes[collection_id_offset..collection_id_offset + COLLECTIONID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get collection id from approve pubdata"))?;
+        let token_id = bytes_slice_to_uint256(
+            &bytes[token_id_offset..token_id_offset + TOKENID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get token id from approve pubdata"))?;
+        let fee = unpack_fee_amount(
+            &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee from approve pubdata"))?;
+        let from = Address::zero(); // From pubdata it is unknown
+        let operator = Address::zero(); // From pubdata it is unknown
+        let nonce = 0; // From pubdata it is unknown
+
+        Ok(Self {
+            tx: Approve::new(
+                account_id,
+                from,
+                operator,
+                collection_id,
+                token_id,
+                fee,
+                nonce,
+                None,
+            ),
+            account_id,
+            operator_id: if operator_id == account_id {
+                None
+            } else {
+                Some(operator_id)
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApproveForAllOp {
+    pub tx: ApproveForAll,
+    pub account_id: AccountId,
+    pub operator_id: AccountId,
+}
+
+impl ApproveForAllOp {
+    pub const CHUNKS: usize = 2;
+    pub const OP_CODE: u8 = 0x0d;
+
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        data.extend_from_slice(&self.operator_id.to_be_bytes()[1..]);
+        data.push(self.tx.approved as u8);
+        data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for approve for all pubdata"
+        );
+
+        let account_offset = 1;
+        let operator_offset = account_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let approved_offset = operator_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let fee_offset = approved_offset + 1;
+
+        let account_id = bytes_slice_to_uint32(
+            &bytes[account_offset..account_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get account id from approve for all pubdata"))?;
+        let operator_id = bytes_slice_to_uint32(
+            &bytes[operator_offset..operator_offset + ACCOUNT_ID_BIT_WIDTH / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get operator id from approve for all pubdata"))?;
+        let approved = bytes[approved_offset] != 0;
+        let fee = unpack_fee_amount(
+            &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
+        )
+        .ok_or_else(|| format_err!("Cant get fee from approve for all pubdata"))?;
+        let from = Address::zero(); // From pubdata it is unknown
+        let operator = Address::zero(); // From pubdata it is unknown
+        let nonce = 0; // From pubdata it is unknown
+
+        Ok(Self {
+            tx: ApproveForAll::new(account_id, from, operator, approved, fee, nonce, None),
+            account_id,
+            operator_id,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForcedWithdrawOp {
+    pub priority_op: ForcedWithdraw,
+    /// Whether the token was withdrawn, the request fails if the account does not belong to
+    /// the requester or does not hold the token.
+    pub success: bool,
+}
+
+impl ForcedWithdrawOp {
+    pub const CHUNKS: usize = 8;
+    pub const OP_CODE: u8 = 0x0e;
+    pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [1];
+
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.priority_op.account_id.to_be_bytes()[1..]);
+        data.extend_from_slice(self.priority_op.eth_address.as_bytes());
+        data.extend_from_slice(&self.priority_op.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.priority_op.token_id));
+        data.push(self.success as u8);
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    /// Only a successful request sends the token to its owner.
+    fn get_withdrawal_data(&self) -> Option<Vec<u8>> {
+        if !self.success {
+            return None;
+        }
+        let mut data = Vec::new();
+        data.extend_from_slice(&Self::WITHDRAW_DATA_PREFIX); // first byte is a bool variable 'addToPendingWithdrawalsQueue'
+        data.extend_from_slice(self.priority_op.eth_address.as_bytes());
+        data.extend_from_slice(&self.priority_op.collection_id.to_be_bytes());
+        data.extend_from_slice(&u256_to_be_bytes(&self.priority_op.token_id));
+        Some(data)
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for forced withdraw pubdata"
+        );
+
+        let account_id_offset = 1;
+        let eth_address_offset = account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let collection_id_offset = eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8;
+        let token_id_offset = collection_id_offset + COLLECTIONID_BIT_WIDTH / 8;
+        let success_offset = token_id_offset + TOKENID_BIT_WIDTH / 8;
+
+        let account_id = bytes_slice_to_uint32(&bytes[account_id_offset..eth_address_offset])
+            .ok_or_else(|| format_err!("Cant get account id from forced withdraw pubdata"))?;
+        let eth_address = Address::from_slice(&bytes[eth_address_offset..collection_id_offset]);
+        let collection_id = bytes_slice_to_uint16(&bytes[collection_id_offset..token_id_offset])
+            .ok_or_else(|| format_err!("Cant get collection id from forced withdraw pubdata"))?;
+        let token_id = bytes_slice_to_uint256(&bytes[token_id_offset..success_offset])
+            .ok_or_else(|| format_err!("Cant get token id from forced withdraw pubdata"))?;
+        let success = bytes[success_offset] != 0;
+
+        Ok(Self {
+            priority_op: ForcedWithdraw {
+                account_id,
+                eth_address,
+                collection_id,
+                token_id,
+            },
+            success,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositEthOp {
+    pub priority_op: DepositEth,
+    pub account_id: AccountId,
+}
+
+impl DepositEthOp {
+    pub const CHUNKS: usize = 5;
+    pub const OP_CODE: u8 = 0x0f;
+
+    fn get_public_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.push(Self::OP_CODE); // opcode
+        data.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        data.extend_from_slice(&big_decimal_to_u128(&self.priority_op.amount).to_be_bytes());
+        data.extend_from_slice(self.priority_op.to.as_bytes());
+        data.resize(Self::CHUNKS * 8, 0x00);
+        data
+    }
+
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, failure::Error> {
+        ensure!(
+            bytes.len() == Self::CHUNKS * 8,
+            "Wrong bytes length for eth deposit pubdata"
+        );
+
+        let account_id_offset = 1;
+        let amount_offset = account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8;
+        let account_address_offset = amount_offset + BALANCE_BIT_WIDTH / 8;
+
+        let account_id = bytes_slice_to_uint32(&bytes[account_id_offset..amount_offset])
+            .ok_or_else(|| format_err!("Cant get account id from eth deposit pubdata"))?;
+        let amount = bytes_slice_to_uint128(&bytes[amount_offset..account_address_offset])
+            .ok_or_else(|| format_err!("Cant get amount from eth deposit pubdata"))?;
+        let to = Address::from_slice(
+            &bytes[account_address_offset..account_address_offset + FR_ADDRESS_LEN],
+        );
+
+        let from = Address::default(); // unknown from pubdata.
+
+        Ok(Self {
+            priority_op: DepositEth {
+                from,
+                amount: u128_to_bigdecimal(amount),
+                to,
+            },
+            account_id,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum FranklinOp {
@@ -466,6 +1202,14 @@ pub enum FranklinOp {
     Transfer(Box<TransferOp>),
     FullExit(Box<FullExitOp>),
     ChangePubKeyOffchain(Box<ChangePubKeyOp>),
+    BatchTransfer(Box<BatchTransferOp>),
+    Swap(Box<SwapOp>),
+    Mint(Box<MintOp>),
+    Burn(Box<BurnOp>),
+    Approve(Box<ApproveOp>),
+    ApproveForAll(Box<ApproveForAllOp>),
+    ForcedWithdraw(Box<ForcedWithdrawOp>),
+    DepositEth(Box<DepositEthOp>),
 }
 
 impl FranklinOp {
@@ -479,6 +1223,14 @@ impl FranklinOp {
             FranklinOp::Transfer(_) => TransferOp::CHUNKS,
             FranklinOp::FullExit(_) => FullExitOp::CHUNKS,
             FranklinOp::ChangePubKeyOffchain(_) => ChangePubKeyOp::CHUNKS,
+            FranklinOp::BatchTransfer(op) => op.chunks(),
+            FranklinOp::Swap(_) => SwapOp::CHUNKS,
+            FranklinOp::Mint(_) => MintOp::CHUNKS,
+            FranklinOp::Burn(_) => BurnOp::CHUNKS,
+            FranklinOp::Approve(_) => ApproveOp::CHUNKS,
+            FranklinOp::ApproveForAll(_) => ApproveForAllOp::CHUNKS,
+            FranklinOp::ForcedWithdraw(_) => ForcedWithdrawOp::CHUNKS,
+            FranklinOp::DepositEth(_) => DepositEthOp::CHUNKS,
         }
     }
 
@@ -492,6 +1244,14 @@ impl FranklinOp {
             FranklinOp::Transfer(op) => op.get_public_data(),
             FranklinOp::FullExit(op) => op.get_public_data(),
             FranklinOp::ChangePubKeyOffchain(op) => op.get_public_data(),
+            FranklinOp::BatchTransfer(op) => op.get_public_data(),
+            FranklinOp::Swap(op) => op.get_public_data(),
+            FranklinOp::Mint(op) => op.get_public_data(),
+            FranklinOp::Burn(op) => op.get_public_data(),
+            FranklinOp::Approve(op) => op.get_public_data(),
+            FranklinOp::ApproveForAll(op) => op.get_public_data(),
+            FranklinOp::ForcedWithdraw(op) => op.get_public_data(),
+            FranklinOp::DepositEth(op) => op.get_public_data(),
         }
     }
 
@@ -506,6 +1266,8 @@ impl FranklinOp {
         match self {
             FranklinOp::Withdraw(op) => Some(op.get_withdrawal_data()),
             FranklinOp::FullExit(op) => Some(op.get_withdrawal_data()),
+            FranklinOp::Deposit(op) => op.get_withdrawal_data(),
+            FranklinOp::ForcedWithdraw(op) => op.get_withdrawal_data(),
             _ => None,
         }
     }
@@ -535,11 +1297,37 @@ impl FranklinOp {
             ChangePubKeyOp::OP_CODE => Ok(FranklinOp::ChangePubKeyOffchain(Box::new(
                 ChangePubKeyOp::from_public_data(&bytes)?,
             ))),
+            BatchTransferOp::OP_CODE => Ok(FranklinOp::BatchTransfer(Box::new(
+                BatchTransferOp::from_public_data(&bytes)?,
+            ))),
+            SwapOp::OP_CODE => Ok(FranklinOp::Swap(Box::new(SwapOp::from_public_data(
+                &bytes,
+            )?))),
+            MintOp::OP_CODE => Ok(FranklinOp::Mint(Box::new(MintOp::from_public_data(
+                &bytes,
+            )?))),
+            BurnOp::OP_CODE => Ok(FranklinOp::Burn(Box::new(BurnOp::from_public_data(
+                &bytes,
+            )?))),
+            ApproveOp::OP_CODE => Ok(FranklinOp::Approve(Box::new(ApproveOp::from_public_data(
+                &bytes,
+            )?))),
+            ApproveForAllOp::OP_CODE => Ok(FranklinOp::ApproveForAll(Box::new(
+                ApproveForAllOp::from_public_data(&bytes)?,
+            ))),
+            ForcedWithdrawOp::OP_CODE => Ok(FranklinOp::ForcedWithdraw(Box::new(
+                ForcedWithdrawOp::from_public_data(&bytes)?,
+            ))),
+            DepositEthOp::OP_CODE => Ok(FranklinOp::DepositEth(Box::new(
+                DepositEthOp::from_public_data(&bytes)?,
+            ))),
             _ => Err(format_err!("Wrong operation type: {}", &op_type)),
         }
     }
 
-    pub fn public_data_length(op_type: u8) -> Result<usize, failure::Error> {
+    /// Returns the length of the pubdata of the operation `bytes` start with.
+    pub fn public_data_length(bytes: &[u8]) -> Result<usize, failure::Error> {
+        let op_type: u8 = *bytes.first().ok_or_else(|| format_err!("Empty pubdata"))?;
         match op_type {
             NoopOp::OP_CODE => Ok(NoopOp::CHUNKS * 8),
             DepositOp::OP_CODE => Ok(DepositOp::CHUNKS * 8),
@@ -549,6 +1337,14 @@ impl FranklinOp {
             TransferOp::OP_CODE => Ok(TransferOp::CHUNKS * 8),
             FullExitOp::OP_CODE => Ok(FullExitOp::CHUNKS * 8),
             ChangePubKeyOp::OP_CODE => Ok(ChangePubKeyOp::CHUNKS * 8),
+            BatchTransferOp::OP_CODE => BatchTransferOp::public_data_length(bytes),
+            SwapOp::OP_CODE => Ok(SwapOp::CHUNKS * 8),
+            MintOp::OP_CODE => Ok(MintOp::CHUNKS * 8),
+            BurnOp::OP_CODE => Ok(BurnOp::CHUNKS * 8),
+            ApproveOp::OP_CODE => Ok(ApproveOp::CHUNKS * 8),
+            ApproveForAllOp::OP_CODE => Ok(ApproveForAllOp::CHUNKS * 8),
+            ForcedWithdrawOp::OP_CODE => Ok(ForcedWithdrawOp::CHUNKS * 8),
+            DepositEthOp::OP_CODE => Ok(DepositEthOp::CHUNKS * 8),
             _ => Err(format_err!("Wrong operation type: {}", &op_type)),
         }
     }
@@ -557,6 +1353,12 @@ impl FranklinOp {
         match self {
             FranklinOp::Transfer(op) => Ok(FranklinTx::Transfer(Box::new(op.tx.clone()))),
             FranklinOp::TransferToNew(op) => Ok(FranklinTx::Transfer(Box::new(op.tx.clone()))),
+            FranklinOp::BatchTransfer(op) => Ok(FranklinTx::BatchTransfer(Box::new(op.tx.clone()))),
+            FranklinOp::Swap(op) => Ok(FranklinTx::Swap(Box::new(op.tx.clone()))),
+            FranklinOp::Mint(op) => Ok(FranklinTx::Mint(Box::new(op.tx.clone()))),
+            FranklinOp::Burn(op) => Ok(FranklinTx::Burn(Box::new(op.tx.clone()))),
+            FranklinOp::Approve(op) => Ok(FranklinTx::Approve(Box::new(op.tx.clone()))),
+            FranklinOp::ApproveForAll(op) => Ok(FranklinTx::ApproveForAll(Box::new(op.tx.clone()))),
             FranklinOp::Withdraw(op) => Ok(FranklinTx::Withdraw(Box::new(op.tx.clone()))),
             FranklinOp::Close(op) => Ok(FranklinTx::Close(Box::new(op.tx.clone()))),
             FranklinOp::ChangePubKeyOffchain(op) => {
@@ -570,6 +1372,12 @@ impl FranklinOp {
         match self {
             FranklinOp::Deposit(op) => Ok(FranklinPriorityOp::Deposit(op.priority_op.clone())),
             FranklinOp::FullExit(op) => Ok(FranklinPriorityOp::FullExit(op.priority_op.clone())),
+            FranklinOp::ForcedWithdraw(op) => {
+                Ok(FranklinPriorityOp::ForcedWithdraw(op.priority_op.clone()))
+            }
+            FranklinOp::DepositEth(op) => {
+                Ok(FranklinPriorityOp::DepositEth(op.priority_op.clone()))
+            }
             _ => Err(format_err!("Wrong operation type")),
         }
     }
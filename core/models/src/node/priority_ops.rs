@@ -1,22 +1,36 @@
 use super::AccountId;
-use super::TokenId;
+use super::{CollectionId, ContentHash, Nft, TokenId};
 use crate::params::{
-    ACCOUNT_ID_BIT_WIDTH, BALANCE_BIT_WIDTH, ETH_ADDRESS_BIT_WIDTH, FR_ADDRESS_LEN,
-    TOKENID_BIT_WIDTH,
+    ACCOUNT_ID_BIT_WIDTH, BALANCE_BIT_WIDTH, COLLECTIONID_BIT_WIDTH, CONTENT_HASH_BIT_WIDTH,
+    ETH_ADDRESS_BIT_WIDTH, FR_ADDRESS_LEN, TOKENID_BIT_WIDTH,
 };
-use crate::primitives::{bytes_slice_to_uint32, u128_to_bigdecimal};
+use crate::primitives::{
+    bytes_slice_to_uint128, bytes_slice_to_uint16, bytes_slice_to_uint32, u128_to_bigdecimal,
+};
+use bigdecimal::BigDecimal;
 use ethabi::{decode, ParamType};
 use failure::{bail, ensure, format_err};
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
 use web3::types::{Address, Log, U256};
 
-use super::operations::{DepositOp, FullExitOp};
+use super::operations::{DepositEthOp, DepositOp, ForcedWithdrawOp, FullExitOp};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Deposit {
+    /// ERC-721 contract that called `onERC721Received`, i.e. the originating collection
     pub from: Address,
+    pub collection_id: CollectionId,
     pub token_id: TokenId,
     pub to: Address,
+    /// Passed as the `data` of `safeTransferFrom`, zero if the depositor did not provide it
+    #[serde(default)]
+    pub content_hash: ContentHash,
+}
+
+impl Deposit {
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,11 +39,37 @@ pub struct FullExit {
     pub eth_address: Address,
 }
 
+/// Request to withdraw a single token of the account to L1. It is made on the contract by
+/// the owner of the account, so the token can be taken out even if the owner is censored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForcedWithdraw {
+    pub account_id: AccountId,
+    pub eth_address: Address,
+    pub collection_id: CollectionId,
+    pub token_id: TokenId,
+}
+
+impl ForcedWithdraw {
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
+    }
+}
+
+/// ETH deposited to the balance of the account, fees of the account txs are paid from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositEth {
+    pub from: Address,
+    pub amount: BigDecimal,
+    pub to: Address,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum FranklinPriorityOp {
     Deposit(Deposit),
     FullExit(FullExit),
+    ForcedWithdraw(ForcedWithdraw),
+    DepositEth(DepositEth),
 }
 
 impl FranklinPriorityOp {
@@ -54,10 +94,16 @@ impl FranklinPriorityOp {
                 // account_id
                 let (_, pub_data_left) = pub_data_left.split_at(ACCOUNT_ID_BIT_WIDTH / 8);
 
+                // collection_id
+                let (collection_id, pub_data_left) = {
+                    let (collection_id, left) = pub_data_left.split_at(COLLECTIONID_BIT_WIDTH / 8);
+                    (bytes_slice_to_uint16(collection_id).unwrap(), left)
+                };
+
                 // token_id
                 let (token_id, pub_data_left) = {
                     let (token_id, left) = pub_data_left.split_at(TOKENID_BIT_WIDTH / 8);
-                    (u16::from_be_bytes(token_id.try_into().unwrap()), left) // TODO ADE must be encoded as u256!
+                    (TokenId::from_big_endian(token_id), left)
                 };
 
                 // account
@@ -66,6 +112,12 @@ impl FranklinPriorityOp {
                     (Address::from_slice(account), left)
                 };
 
+                // content_hash
+                let (content_hash, pub_data_left) = {
+                    let (content_hash, left) = pub_data_left.split_at(CONTENT_HASH_BIT_WIDTH / 8);
+                    (ContentHash::from_slice(content_hash), left)
+                };
+
                 ensure!(
                     pub_data_left.is_empty(),
                     "DepositOp parse failed: input too big"
@@ -73,8 +125,10 @@ impl FranklinPriorityOp {
 
                 Ok(Self::Deposit(Deposit {
                     from: sender,
+                    collection_id,
                     token_id,
                     to: account,
+                    content_hash,
                 }))
             }
             FullExitOp::OP_CODE => {
@@ -101,6 +155,73 @@ impl FranklinPriorityOp {
                     eth_address,
                 }))
             }
+            ForcedWithdrawOp::OP_CODE => {
+                // account_id
+                let (account_id, pub_data_left) = {
+                    let (account_id, left) = pub_data.split_at(ACCOUNT_ID_BIT_WIDTH / 8);
+                    (bytes_slice_to_uint32(account_id).unwrap(), left)
+                };
+
+                // owner
+                let (eth_address, pub_data_left) = {
+                    let (eth_address, left) = pub_data_left.split_at(ETH_ADDRESS_BIT_WIDTH / 8);
+                    (Address::from_slice(eth_address), left)
+                };
+
+                // collection_id
+                let (collection_id, pub_data_left) = {
+                    let (collection_id, left) = pub_data_left.split_at(COLLECTIONID_BIT_WIDTH / 8);
+                    (bytes_slice_to_uint16(collection_id).unwrap(), left)
+                };
+
+                // token_id
+                let (token_id, pub_data_left) = {
+                    let (token_id, left) = pub_data_left.split_at(TOKENID_BIT_WIDTH / 8);
+                    (TokenId::from_big_endian(token_id), left)
+                };
+
+                ensure!(
+                    pub_data_left.is_empty(),
+                    "ForcedWithdrawOp parse failed: input too big"
+                );
+
+                Ok(Self::ForcedWithdraw(ForcedWithdraw {
+                    account_id,
+                    eth_address,
+                    collection_id,
+                    token_id,
+                }))
+            }
+            DepositEthOp::OP_CODE => {
+                // account_id
+                let (_, pub_data_left) = pub_data.split_at(ACCOUNT_ID_BIT_WIDTH / 8);
+
+                // amount
+                let (amount, pub_data_left) = {
+                    let (amount, left) = pub_data_left.split_at(BALANCE_BIT_WIDTH / 8);
+                    (
+                        u128_to_bigdecimal(bytes_slice_to_uint128(amount).unwrap()),
+                        left,
+                    )
+                };
+
+                // account
+                let (account, pub_data_left) = {
+                    let (account, left) = pub_data_left.split_at(FR_ADDRESS_LEN);
+                    (Address::from_slice(account), left)
+                };
+
+                ensure!(
+                    pub_data_left.is_empty(),
+                    "DepositEthOp parse failed: input too big"
+                );
+
+                Ok(Self::DepositEth(DepositEth {
+                    from: sender,
+                    amount,
+                    to: account,
+                }))
+            }
             _ => {
                 bail!("Unsupported priority op type");
             }
@@ -111,6 +232,8 @@ impl FranklinPriorityOp {
         match self {
             Self::Deposit(_) => DepositOp::CHUNKS,
             Self::FullExit(_) => FullExitOp::CHUNKS,
+            Self::ForcedWithdraw(_) => ForcedWithdrawOp::CHUNKS,
+            Self::DepositEth(_) => DepositEthOp::CHUNKS,
         }
     }
 }
@@ -1,34 +1,107 @@
 use crate::config_options::parse_env;
-use crate::node::{Address, TokenId};
+use crate::node::{Address, CollectionId, TokenId};
+use failure::ensure;
+use std::convert::TryFrom;
 use std::fs::read_to_string;
 use std::path::PathBuf;
+use web3::types::Log;
 
 /// Order of the fields are important (from more specific types to less specific types)
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged, rename_all = "camelCase")]
 pub enum TokenLike {
-    Id(TokenId),
+    Id(CollectionId),
     Address(Address),
     Symbol(String),
 }
 
-impl From<TokenId> for TokenLike {
-    fn from(id: TokenId) -> Self {
+impl From<CollectionId> for TokenLike {
+    fn from(id: CollectionId) -> Self {
         Self::Id(id)
     }
 }
 
-/// Token supported in zkSync protocol
+/// ERC-721 collection registered in the governance contract
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Token {
-    /// id is used for tx signature and serialization
-    pub id: TokenId,
-    /// Contract address of ERC20 token or Address::zero() for "ETH"
+    /// id is assigned by `Governance.addToken` and used for tx signature and serialization
+    pub id: CollectionId,
+    /// Address of the ERC-721 contract
     pub address: Address,
-    /// Token symbol (e.g. "ETH" or "USDC")
+    /// Collection symbol
     pub symbol: String,
 }
 
+/// Single NFT, identified by its collection and its ERC-721 token id inside that collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Nft {
+    pub collection_id: CollectionId,
+    pub token_id: TokenId,
+}
+
+impl Nft {
+    pub fn new(collection_id: CollectionId, token_id: TokenId) -> Self {
+        Self {
+            collection_id,
+            token_id,
+        }
+    }
+}
+
+/// `NewToken` event emitted by the governance contract when a collection is registered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenAddedEvent {
+    pub address: Address,
+    pub id: CollectionId,
+}
+
+impl TryFrom<Log> for TokenAddedEvent {
+    type Error = failure::Error;
+
+    fn try_from(event: Log) -> Result<TokenAddedEvent, failure::Error> {
+        // event NewToken(address indexed token, uint16 indexed tokenId)
+        ensure!(
+            event.topics.len() == 3,
+            "NewToken event should have 3 topics"
+        );
+        Ok(TokenAddedEvent {
+            address: Address::from_slice(&event.topics[1].as_fixed_bytes()[12..]),
+            id: u16::from_be_bytes([event.topics[2][30], event.topics[2][31]]),
+        })
+    }
+}
+
+/// `MinterStatusUpdate` event emitted by the governance contract when an account
+/// is allowed or disallowed to mint tokens of the collection inside the rollup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinterStatusEvent {
+    pub address: Address,
+    pub collection_id: CollectionId,
+    pub is_active: bool,
+}
+
+impl TryFrom<Log> for MinterStatusEvent {
+    type Error = failure::Error;
+
+    fn try_from(event: Log) -> Result<MinterStatusEvent, failure::Error> {
+        // event MinterStatusUpdate(address indexed minterAddress, uint16 indexed collectionId, bool isActive)
+        ensure!(
+            event.topics.len() == 3,
+            "MinterStatusUpdate event should have 3 topics"
+        );
+        ensure!(
+            event.data.0.len() == 32,
+            "MinterStatusUpdate event data should be 32 bytes"
+        );
+        Ok(MinterStatusEvent {
+            address: Address::from_slice(&event.topics[1].as_fixed_bytes()[12..]),
+            collection_id: u16::from_be_bytes([event.topics[2][30], event.topics[2][31]]),
+            is_active: event.data.0[31] != 0,
+        })
+    }
+}
+
 /// Tokens that added when deploying contract
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenGenesisListItem {
@@ -41,7 +114,7 @@ pub struct TokenGenesisListItem {
 }
 
 impl Token {
-    pub fn new(id: TokenId, address: Address, symbol: &str) -> Self {
+    pub fn new(id: CollectionId, address: Address, symbol: &str) -> Self {
         Self {
             id,
             address,
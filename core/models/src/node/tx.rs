@@ -1,14 +1,18 @@
-use super::{Nonce, TokenId};
+use super::{CollectionId, ContentHash, Nft, Nonce, TokenId};
 
 use crate::node::{
-    is_fee_amount_packable, pack_fee_amount, public_key_from_private, AccountId, CloseOp,
-    TransferOp, WithdrawOp,
+    is_fee_amount_packable, pack_fee_amount, public_key_from_private, AccountId, ApproveForAllOp,
+    ApproveOp, BatchTransferOp, BurnOp, CloseOp, MintOp, SwapOp, TransferOp, WithdrawOp,
 };
 use bigdecimal::BigDecimal;
 use crypto::{digest::Digest, sha2::Sha256};
 
 use super::account::PubKeyHash;
 use super::Engine;
+use crate::circuit::{
+    account::{CircuitTokenTree, Token},
+    utils::{append_be_fixed_width, be_bit_vector_into_bytes},
+};
 use crate::franklin_crypto::alt_babyjubjub::fs::FsRepr;
 use crate::franklin_crypto::alt_babyjubjub::JubjubEngine;
 use crate::franklin_crypto::alt_babyjubjub::{edwards, AltJubjubBn256};
@@ -18,13 +22,19 @@ use crate::franklin_crypto::jubjub::FixedGenerators;
 use crate::franklin_crypto::rescue::RescueEngine;
 use crate::misc::utils::format_ether;
 use crate::node::operations::ChangePubKeyOp;
-use crate::params::{JUBJUB_PARAMS, RESCUE_PARAMS};
-use crate::primitives::{pedersen_hash_tx_msg, rescue_hash_tx_msg, u128_to_bigdecimal};
+use crate::params::{
+    FR_BIT_WIDTH_PADDED, JUBJUB_PARAMS, MAX_BATCH_TRANSFER_TOKENS, MAX_ORDER_BUY_TOKENS,
+    NFT_LIST_TREE_DEPTH, RESCUE_PARAMS,
+};
+use crate::primitives::{
+    pedersen_hash_tx_msg, rescue_hash_tx_msg, u128_to_bigdecimal, u256_to_be_bytes,
+};
 use failure::{bail, ensure, format_err};
 use parity_crypto::publickey::{
     public_to_address, recover, sign, KeyPair, Signature as ETHSignature,
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fmt;
 use std::str::FromStr;
@@ -39,76 +49,913 @@ impl AsRef<[u8]> for TxHash {
     fn as_ref(&self) -> &[u8] {
         &self.data
     }
-}
+}
+
+impl ToString for TxHash {
+    fn to_string(&self) -> String {
+        format!("sync-tx:{}", hex::encode(&self.data))
+    }
+}
+
+impl FromStr for TxHash {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ensure!(
+            s.starts_with("sync-tx:"),
+            "TxHash should start with sync-tx:"
+        );
+        let bytes = hex::decode(&s[8..])?;
+        ensure!(bytes.len() == 32, "Size mismatch");
+        Ok(TxHash {
+            data: bytes.as_slice().try_into().unwrap(),
+        })
+    }
+}
+
+impl Serialize for TxHash {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TxHash {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        String::deserialize(deserializer).and_then(|string| {
+            Self::from_str(&string).map_err(|err| Error::custom(err.to_string()))
+        })
+    }
+}
+
+/// Stores precomputed signature verification result to speedup tx execution
+#[derive(Debug, Clone)]
+enum VerifiedSignatureCache {
+    /// No cache scenario
+    NotCached,
+    /// Cached: None if signature is incorrect.
+    Cached(Option<PubKeyHash>),
+}
+
+impl Default for VerifiedSignatureCache {
+    fn default() -> Self {
+        Self::NotCached
+    }
+}
+
+/// Consent of another account to pay the fee of a `Transfer` or `Withdraw` instead of its sender.
+///
+/// The sponsor signs the hash of the tx bytes along with its own account id, so the sponsorship
+/// can neither be moved to another tx nor claimed on behalf of another account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sponsorship {
+    pub account_id: AccountId,
+    pub signature: TxSignature,
+}
+
+impl Sponsorship {
+    /// Distinguishes the message signed by the sponsor from the tx messages.
+    const MSG_PREFIX: u8 = 0xff;
+
+    /// Creates the sponsorship of the tx with the given bytes, signed by the sponsor key.
+    pub fn new_signed(
+        tx_bytes: &[u8],
+        account_id: AccountId,
+        private_key: &PrivateKey<Engine>,
+    ) -> Self {
+        let signature =
+            TxSignature::sign_musig(private_key, &Self::get_bytes(tx_bytes, account_id));
+        Self {
+            account_id,
+            signature,
+        }
+    }
+
+    /// Message signed by the sponsor of the tx with the given bytes.
+    pub fn get_bytes(tx_bytes: &[u8], account_id: AccountId) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.input(tx_bytes);
+        let mut tx_hash = [0u8; 32];
+        hasher.result(&mut tx_hash);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::MSG_PREFIX]);
+        out.extend_from_slice(&account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(&tx_hash);
+        out
+    }
+
+    /// Returns the pub key hash of the sponsor if the signature of the tx with the given bytes is correct.
+    pub fn verify_signature(&self, tx_bytes: &[u8]) -> Option<PubKeyHash> {
+        self.signature
+            .verify_musig(&Self::get_bytes(tx_bytes, self.account_id))
+            .map(|pub_key| PubKeyHash::from_pubkey(&pub_key))
+    }
+}
+
+/// Signed by user.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transfer {
+    pub account_id: AccountId,
+    pub from: Address,
+    pub to: Address,
+    pub collection_id: CollectionId,
+    pub token_id: TokenId,
+    pub fee: BigDecimal,
+    pub nonce: Nonce,
+    /// Unix timestamp (in seconds) the tx can not be executed before, not limited if `None`.
+    ///
+    /// The validity window is signed, but only the operator enforces it, against its own clock
+    /// at the moment the block is built: the block timestamp is neither committed to the
+    /// pubdata nor checked by the circuit or the contract, so the window does not bind
+    /// a dishonest operator.
+    pub valid_from: Option<u32>,
+    /// Unix timestamp (in seconds) the tx can not be executed after, not limited if `None`,
+    /// enforced by the operator only, see `valid_from`.
+    pub valid_until: Option<u32>,
+    pub signature: TxSignature,
+    /// Account paying the fee instead of the sender, the sender pays it if `None`
+    #[serde(default)]
+    pub sponsor: Option<Sponsorship>,
+    #[serde(skip)]
+    cached_signer: VerifiedSignatureCache,
+}
+
+impl Transfer {
+    const TX_TYPE: u8 = 5;
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates transaction from parts
+    /// signature is optional, because sometimes we don't know it (i.e. data_restore)
+    pub fn new(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        fee: BigDecimal,
+        nonce: Nonce,
+        valid_from: Option<u32>,
+        valid_until: Option<u32>,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        let mut tx = Self {
+            account_id,
+            from,
+            to,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            valid_from,
+            valid_until,
+            signature: signature.clone().unwrap_or_default(),
+            sponsor: None,
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            tx.cached_signer = VerifiedSignatureCache::Cached(tx.verify_signature());
+        }
+        tx
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates signed transaction using private key, checks for correcteness
+    pub fn new_signed(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        fee: BigDecimal,
+        nonce: Nonce,
+        valid_from: Option<u32>,
+        valid_until: Option<u32>,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, failure::Error> {
+        let mut tx = Self::new(
+            account_id,
+            from,
+            to,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            valid_from,
+            valid_until,
+            None,
+        );
+        tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
+        if !tx.check_correctness() {
+            bail!("Transfer is incorrect, check amounts");
+        }
+        Ok(tx)
+    }
+
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(&self.from.as_bytes());
+        out.extend_from_slice(&self.to.as_bytes());
+        out.extend_from_slice(&self.collection_id.to_be_bytes());
+        out.extend_from_slice(&u256_to_be_bytes(&self.token_id));
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out.extend_from_slice(&validity_window_bytes(self.valid_from, self.valid_until));
+        out
+    }
+
+    /// NFT moved by this transaction.
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
+    }
+
+    /// Account paying the fee of this transaction, either the sponsor or the sender.
+    pub fn fee_payer(&self) -> AccountId {
+        self.sponsor
+            .as_ref()
+            .map_or(self.account_id, |sponsor| sponsor.account_id)
+    }
+
+    pub fn check_correctness(&mut self) -> bool {
+        let mut valid = self.fee.is_integer()
+            && is_fee_amount_packable(&self.fee)
+            && is_validity_window_correct(self.valid_from, self.valid_until)
+            && is_sponsorship_correct(self.sponsor.as_ref(), self.account_id, &self.get_bytes());
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        };
+        valid
+    }
+
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.get_bytes()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
+
+    /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
+    pub fn get_ethereum_sign_message(&self) -> String {
+        format!(
+            "Transfer {token_id} of collection {collection_id}\n\
+            To: {to:?}\n\
+            Fee: {fee} ETH\n\
+            Nonce: {nonce}\n\
+            Account Id: {account_id}{validity_window}",
+            token_id = self.token_id,
+            collection_id = self.collection_id,
+            to = self.to,
+            fee = format_ether(&self.fee),
+            nonce = self.nonce,
+            account_id = self.account_id,
+            validity_window = validity_window_message(self.valid_from, self.valid_until),
+        )
+    }
+}
+
+/// Moves several NFTs of one account to the same recipient under a single signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchTransfer {
+    pub account_id: AccountId,
+    pub from: Address,
+    pub to: Address,
+    pub tokens: Vec<Nft>,
+    pub fee: BigDecimal,
+    pub nonce: Nonce,
+    pub signature: TxSignature,
+    #[serde(skip)]
+    cached_signer: VerifiedSignatureCache,
+}
+
+impl BatchTransfer {
+    const TX_TYPE: u8 = 8;
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates transaction from parts
+    /// signature is optional, because sometimes we don't know it (i.e. data_restore)
+    pub fn new(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        tokens: Vec<Nft>,
+        fee: BigDecimal,
+        nonce: Nonce,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        let mut tx = Self {
+            account_id,
+            from,
+            to,
+            tokens,
+            fee,
+            nonce,
+            signature: signature.clone().unwrap_or_default(),
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            tx.cached_signer = VerifiedSignatureCache::Cached(tx.verify_signature());
+        }
+        tx
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates signed transaction using private key, checks for correcteness
+    pub fn new_signed(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        tokens: Vec<Nft>,
+        fee: BigDecimal,
+        nonce: Nonce,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, failure::Error> {
+        let mut tx = Self::new(account_id, from, to, tokens, fee, nonce, None);
+        tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
+        if !tx.check_correctness() {
+            bail!("Batch transfer is incorrect, check token list");
+        }
+        Ok(tx)
+    }
+
+    /// Token list does not fit into the signed message, the tx commits to its list tree root.
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(&self.from.as_bytes());
+        out.extend_from_slice(&self.to.as_bytes());
+        out.push(self.tokens.len() as u8);
+        out.extend_from_slice(&self.tokens_hash());
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out
+    }
+
+    /// Root of the list tree of the moved tokens, see `nfts_list_tree`.
+    pub fn tokens_hash(&self) -> Vec<u8> {
+        nfts_hash(&self.tokens)
+    }
+
+    pub fn check_correctness(&mut self) -> bool {
+        let unique_tokens = self.tokens.iter().collect::<HashSet<_>>();
+        let mut valid = self.fee.is_integer()
+            && is_fee_amount_packable(&self.fee)
+            && !self.tokens.is_empty()
+            && self.tokens.len() <= MAX_BATCH_TRANSFER_TOKENS
+            && unique_tokens.len() == self.tokens.len();
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        };
+        valid
+    }
+
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.get_bytes()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
+
+    /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
+    pub fn get_ethereum_sign_message(&self) -> String {
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|token| format!("{} of collection {}", token.token_id, token.collection_id))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "Transfer {count} tokens:\n\
+            {tokens}\n\
+            To: {to:?}\n\
+            Fee: {fee} ETH\n\
+            Nonce: {nonce}\n\
+            Account Id: {account_id}",
+            count = self.tokens.len(),
+            tokens = tokens,
+            to = self.to,
+            fee = format_ether(&self.fee),
+            nonce = self.nonce,
+            account_id = self.account_id,
+        )
+    }
+}
+
+/// Tree with the `i`-th token of the list in its leaf `i`, content hashes are left empty.
+/// A list is committed to by the root of its tree, so that the circuit can check
+/// that a token is on the list with its audit path.
+pub fn nfts_list_tree(tokens: &[Nft]) -> CircuitTokenTree {
+    let mut tree = CircuitTokenTree::new(NFT_LIST_TREE_DEPTH);
+    for (i, token) in tokens.iter().enumerate() {
+        tree.insert(i as u32, Token::new(token, &ContentHash::zero()));
+    }
+    tree
+}
+
+/// Big-endian bytes of the root of the `nfts_list_tree`.
+fn nfts_hash(tokens: &[Nft]) -> Vec<u8> {
+    let mut root_bits = Vec::with_capacity(FR_BIT_WIDTH_PADDED);
+    append_be_fixed_width(
+        &mut root_bits,
+        &nfts_list_tree(tokens).root_hash(),
+        FR_BIT_WIDTH_PADDED,
+    );
+    be_bit_vector_into_bytes(&root_bits)
+}
+
+/// Signed offer of the account to give `token_sell` in exchange for any of `tokens_buy`.
+/// Two orders that accept each other's token are matched into a `Swap`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub account_id: AccountId,
+    pub account: Address,
+    pub token_sell: Nft,
+    pub tokens_buy: Vec<Nft>,
+    /// Fee the account pays when the order is matched, each side of a swap pays its own one
+    pub fee: BigDecimal,
+    pub nonce: Nonce,
+    pub signature: TxSignature,
+    #[serde(skip)]
+    cached_signer: VerifiedSignatureCache,
+}
+
+impl Order {
+    /// Creates order from parts
+    /// signature is optional, because sometimes we don't know it (i.e. data_restore)
+    pub fn new(
+        account_id: AccountId,
+        account: Address,
+        token_sell: Nft,
+        tokens_buy: Vec<Nft>,
+        fee: BigDecimal,
+        nonce: Nonce,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        let mut order = Self {
+            account_id,
+            account,
+            token_sell,
+            tokens_buy,
+            fee,
+            nonce,
+            signature: signature.clone().unwrap_or_default(),
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            order.cached_signer = VerifiedSignatureCache::Cached(order.verify_signature());
+        }
+        order
+    }
+
+    /// Creates signed order using private key, checks for correcteness
+    pub fn new_signed(
+        account_id: AccountId,
+        account: Address,
+        token_sell: Nft,
+        tokens_buy: Vec<Nft>,
+        fee: BigDecimal,
+        nonce: Nonce,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, failure::Error> {
+        let mut order = Self::new(
+            account_id, account, token_sell, tokens_buy, fee, nonce, None,
+        );
+        order.signature = TxSignature::sign_musig(private_key, &order.get_bytes());
+        if !order.check_correctness() {
+            bail!("Order is incorrect, check token list and fee");
+        }
+        Ok(order)
+    }
+
+    /// Accepted tokens do not fit into the signed message, the order commits to their list tree root.
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Swap::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(&self.token_sell.collection_id.to_be_bytes());
+        out.extend_from_slice(&u256_to_be_bytes(&self.token_sell.token_id));
+        out.push(self.tokens_buy.len() as u8);
+        out.extend_from_slice(&nfts_hash(&self.tokens_buy));
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out
+    }
+
+    pub fn hash(&self) -> TxHash {
+        let mut hasher = Sha256::new();
+        hasher.input(&self.get_bytes());
+        let mut out = [0u8; 32];
+        hasher.result(&mut out);
+        TxHash { data: out }
+    }
+
+    /// Returns `true` if the order can be filled with `token`.
+    pub fn accepts(&self, token: &Nft) -> bool {
+        self.tokens_buy.contains(token)
+    }
+
+    pub fn check_correctness(&mut self) -> bool {
+        let unique_tokens = self.tokens_buy.iter().collect::<HashSet<_>>();
+        let mut valid = self.fee.is_integer()
+            && is_fee_amount_packable(&self.fee)
+            && !self.tokens_buy.is_empty()
+            && self.tokens_buy.len() <= MAX_ORDER_BUY_TOKENS
+            && unique_tokens.len() == self.tokens_buy.len()
+            && !self.accepts(&self.token_sell);
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        };
+        valid
+    }
+
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.get_bytes()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
+}
+
+/// Exchange of the tokens of two matching orders of the accounts `a` and `b`.
+/// Each account signs its own order, neither token moves without the other one.
+/// Orders are signed independently and matched later, so each side pays the fee of its own order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Swap {
+    pub order_a: Order,
+    pub order_b: Order,
+}
+
+impl Swap {
+    const TX_TYPE: u8 = 9;
+
+    pub fn new(order_a: Order, order_b: Order) -> Self {
+        Self { order_a, order_b }
+    }
+
+    /// Bytes of both orders, the tx itself is not signed.
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = self.order_a.get_bytes();
+        out.extend_from_slice(&self.order_b.get_bytes());
+        out
+    }
+
+    pub fn check_correctness(&mut self) -> bool {
+        self.order_a.account != self.order_b.account
+            && self.order_a.account_id != self.order_b.account_id
+            && self.order_a.accepts(&self.order_b.token_sell)
+            && self.order_b.accepts(&self.order_a.token_sell)
+            && self.order_a.check_correctness()
+            && self.order_b.check_correctness()
+    }
+
+    /// Returns the signers of the orders of the accounts `a` and `b`.
+    pub fn verify_signatures(&self) -> (Option<PubKeyHash>, Option<PubKeyHash>) {
+        (
+            self.order_a.verify_signature(),
+            self.order_b.verify_signature(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdraw {
+    pub account_id: AccountId,
+    pub from: Address,
+    pub to: Address,
+    pub collection_id: CollectionId,
+    pub token_id: TokenId,
+    pub fee: BigDecimal,
+    pub nonce: Nonce,
+    /// Unix timestamp (in seconds) the tx can not be executed before, not limited if `None`,
+    /// enforced by the operator only, the same way as `Transfer::valid_from`.
+    pub valid_from: Option<u32>,
+    /// Unix timestamp (in seconds) the tx can not be executed after, not limited if `None`,
+    /// enforced by the operator only, the same way as `Transfer::valid_until`.
+    pub valid_until: Option<u32>,
+    pub signature: TxSignature,
+    /// Account paying the fee instead of the sender, the sender pays it if `None`
+    #[serde(default)]
+    pub sponsor: Option<Sponsorship>,
+    #[serde(skip)]
+    cached_signer: VerifiedSignatureCache,
+}
+
+impl Withdraw {
+    const TX_TYPE: u8 = 3;
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates transaction from parts
+    /// signature is optional, because sometimes we don't know it (i.e. data_restore)
+    pub fn new(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        fee: BigDecimal,
+        nonce: Nonce,
+        valid_from: Option<u32>,
+        valid_until: Option<u32>,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        let mut tx = Self {
+            account_id,
+            from,
+            to,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            valid_from,
+            valid_until,
+            signature: signature.clone().unwrap_or_default(),
+            sponsor: None,
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            tx.cached_signer = VerifiedSignatureCache::Cached(tx.verify_signature());
+        }
+        tx
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates signed transaction using private key, checks for correcteness
+    pub fn new_signed(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        fee: BigDecimal,
+        nonce: Nonce,
+        valid_from: Option<u32>,
+        valid_until: Option<u32>,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, failure::Error> {
+        let mut tx = Self::new(
+            account_id,
+            from,
+            to,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            valid_from,
+            valid_until,
+            None,
+        );
+        tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
+        if !tx.check_correctness() {
+            bail!("Transfer is incorrect, check amounts");
+        }
+        Ok(tx)
+    }
+
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(&self.from.as_bytes());
+        out.extend_from_slice(self.to.as_bytes());
+        out.extend_from_slice(&self.collection_id.to_be_bytes());
+        out.extend_from_slice(&u256_to_be_bytes(&self.token_id));
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out.extend_from_slice(&validity_window_bytes(self.valid_from, self.valid_until));
+        out
+    }
+
+    /// NFT moved by this transaction.
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
+    }
+
+    /// Account paying the fee of this transaction, either the sponsor or the sender.
+    pub fn fee_payer(&self) -> AccountId {
+        self.sponsor
+            .as_ref()
+            .map_or(self.account_id, |sponsor| sponsor.account_id)
+    }
+
+    pub fn check_correctness(&mut self) -> bool {
+        let mut valid = self.fee.is_integer()
+            && is_fee_amount_packable(&self.fee)
+            && is_validity_window_correct(self.valid_from, self.valid_until)
+            && is_sponsorship_correct(self.sponsor.as_ref(), self.account_id, &self.get_bytes());
+
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        }
+        valid
+    }
+
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.get_bytes()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
+
+    /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
+    pub fn get_ethereum_sign_message(&self) -> String {
+        format!(
+            "Withdraw {token_id} of collection {collection_id}\n\
+            To: {to:?}\n\
+            Fee: {fee} ETH\n\
+            Nonce: {nonce}\n\
+            Account Id: {account_id}{validity_window}",
+            token_id = self.token_id,
+            collection_id = self.collection_id,
+            to = self.to,
+            fee = format_ether(&self.fee),
+            nonce = self.nonce,
+            account_id = self.account_id,
+            validity_window = validity_window_message(self.valid_from, self.valid_until),
+        )
+    }
+}
+
+/// Creation of a new token inside the rollup by a minter account authorized in the
+/// governance contract. Minted token is put into the minter account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mint {
+    pub account_id: AccountId,
+    pub account: Address,
+    pub collection_id: CollectionId,
+    pub token_id: TokenId,
+    /// Hash of the token metadata or content, committed to by the token subtree leaf
+    pub content_hash: ContentHash,
+    pub fee: BigDecimal,
+    pub nonce: Nonce,
+    pub signature: TxSignature,
+    #[serde(skip)]
+    cached_signer: VerifiedSignatureCache,
+}
+
+impl Mint {
+    const TX_TYPE: u8 = 10;
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates transaction from parts
+    /// signature is optional, because sometimes we don't know it (i.e. data_restore)
+    pub fn new(
+        account_id: AccountId,
+        account: Address,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        content_hash: ContentHash,
+        fee: BigDecimal,
+        nonce: Nonce,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        let mut tx = Self {
+            account_id,
+            account,
+            collection_id,
+            token_id,
+            content_hash,
+            fee,
+            nonce,
+            signature: signature.clone().unwrap_or_default(),
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            tx.cached_signer = VerifiedSignatureCache::Cached(tx.verify_signature());
+        }
+        tx
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates signed transaction using private key, checks for correcteness
+    pub fn new_signed(
+        account_id: AccountId,
+        account: Address,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        content_hash: ContentHash,
+        fee: BigDecimal,
+        nonce: Nonce,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, failure::Error> {
+        let mut tx = Self::new(
+            account_id,
+            account,
+            collection_id,
+            token_id,
+            content_hash,
+            fee,
+            nonce,
+            None,
+        );
+        tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
+        if !tx.check_correctness() {
+            bail!("Mint is incorrect, check fee");
+        }
+        Ok(tx)
+    }
 
-impl ToString for TxHash {
-    fn to_string(&self) -> String {
-        format!("sync-tx:{}", hex::encode(&self.data))
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(self.account.as_bytes());
+        out.extend_from_slice(&self.collection_id.to_be_bytes());
+        out.extend_from_slice(&u256_to_be_bytes(&self.token_id));
+        out.extend_from_slice(self.content_hash.as_bytes());
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out
     }
-}
-
-impl FromStr for TxHash {
-    type Err = failure::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        ensure!(
-            s.starts_with("sync-tx:"),
-            "TxHash should start with sync-tx:"
-        );
-        let bytes = hex::decode(&s[8..])?;
-        ensure!(bytes.len() == 32, "Size mismatch");
-        Ok(TxHash {
-            data: bytes.as_slice().try_into().unwrap(),
-        })
+    /// NFT created by this transaction.
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
     }
-}
 
-impl Serialize for TxHash {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str(&self.to_string())
-    }
-}
+    pub fn check_correctness(&mut self) -> bool {
+        let mut valid = self.fee.is_integer() && is_fee_amount_packable(&self.fee);
 
-impl<'de> Deserialize<'de> for TxHash {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        use serde::de::Error;
-        String::deserialize(deserializer).and_then(|string| {
-            Self::from_str(&string).map_err(|err| Error::custom(err.to_string()))
-        })
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        }
+        valid
     }
-}
 
-/// Stores precomputed signature verification result to speedup tx execution
-#[derive(Debug, Clone)]
-enum VerifiedSignatureCache {
-    /// No cache scenario
-    NotCached,
-    /// Cached: None if signature is incorrect.
-    Cached(Option<PubKeyHash>),
-}
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.get_bytes()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
 
-impl Default for VerifiedSignatureCache {
-    fn default() -> Self {
-        Self::NotCached
+    /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
+    pub fn get_ethereum_sign_message(&self) -> String {
+        format!(
+            "Mint {token_id} of collection {collection_id}\n\
+            Content hash: {content_hash:?}\n\
+            Fee: {fee} ETH\n\
+            Nonce: {nonce}\n\
+            Account Id: {account_id}",
+            token_id = self.token_id,
+            collection_id = self.collection_id,
+            content_hash = self.content_hash,
+            fee = format_ether(&self.fee),
+            nonce = self.nonce,
+            account_id = self.account_id,
+        )
     }
 }
 
-/// Signed by user.
-
+/// Permanent destruction of a token held by the account. Burned token can not
+/// be minted or deposited into the rollup again.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Transfer {
+pub struct Burn {
     pub account_id: AccountId,
     pub from: Address,
-    pub to: Address,
+    pub collection_id: CollectionId,
     pub token_id: TokenId,
-    #[serde(skip)]
     pub fee: BigDecimal,
     pub nonce: Nonce,
     pub signature: TxSignature,
@@ -116,8 +963,8 @@ pub struct Transfer {
     cached_signer: VerifiedSignatureCache,
 }
 
-impl Transfer {
-    const TX_TYPE: u8 = 5;
+impl Burn {
+    const TX_TYPE: u8 = 11;
 
     #[allow(clippy::too_many_arguments)]
     /// Creates transaction from parts
@@ -125,7 +972,7 @@ impl Transfer {
     pub fn new(
         account_id: AccountId,
         from: Address,
-        to: Address,
+        collection_id: CollectionId,
         token_id: TokenId,
         fee: BigDecimal,
         nonce: Nonce,
@@ -134,7 +981,7 @@ impl Transfer {
         let mut tx = Self {
             account_id,
             from,
-            to,
+            collection_id,
             token_id,
             fee,
             nonce,
@@ -152,16 +999,16 @@ impl Transfer {
     pub fn new_signed(
         account_id: AccountId,
         from: Address,
-        to: Address,
+        collection_id: CollectionId,
         token_id: TokenId,
         fee: BigDecimal,
         nonce: Nonce,
         private_key: &PrivateKey<Engine>,
     ) -> Result<Self, failure::Error> {
-        let mut tx = Self::new(account_id, from, to, token_id, fee, nonce, None);
+        let mut tx = Self::new(account_id, from, collection_id, token_id, fee, nonce, None);
         tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
         if !tx.check_correctness() {
-            bail!("Transfer is incorrect, check amounts");
+            bail!("Burn is incorrect, check fee");
         }
         Ok(tx)
     }
@@ -170,21 +1017,27 @@ impl Transfer {
         let mut out = Vec::new();
         out.extend_from_slice(&[Self::TX_TYPE]);
         out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
-        out.extend_from_slice(&self.from.as_bytes());
-        out.extend_from_slice(&self.to.as_bytes());
-        out.extend_from_slice(&self.token_id.to_be_bytes());
-        //out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(self.from.as_bytes());
+        out.extend_from_slice(&self.collection_id.to_be_bytes());
+        out.extend_from_slice(&u256_to_be_bytes(&self.token_id));
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
         out.extend_from_slice(&self.nonce.to_be_bytes());
         out
     }
 
+    /// NFT destroyed by this transaction.
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
+    }
+
     pub fn check_correctness(&mut self) -> bool {
         let mut valid = self.fee.is_integer() && is_fee_amount_packable(&self.fee);
+
         if valid {
             let signer = self.verify_signature();
             valid = valid && signer.is_some();
             self.cached_signer = VerifiedSignatureCache::Cached(signer);
-        };
+        }
         valid
     }
 
@@ -201,26 +1054,29 @@ impl Transfer {
     /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
     pub fn get_ethereum_sign_message(&self) -> String {
         format!(
-            "Transfer {token_id}\n\
-            To: {to:?}\n\
+            "Burn {token_id} of collection {collection_id}\n\
+            Fee: {fee} ETH\n\
             Nonce: {nonce}\n\
             Account Id: {account_id}",
             token_id = self.token_id,
-            to = self.to,
+            collection_id = self.collection_id,
+            fee = format_ether(&self.fee),
             nonce = self.nonce,
             account_id = self.account_id,
         )
     }
 }
 
+/// Grants the operator account the right to transfer one token of the account
+/// until the token leaves the account. Approval of the zero address revokes it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Withdraw {
+pub struct Approve {
     pub account_id: AccountId,
     pub from: Address,
-    pub to: Address,
+    pub operator: Address,
+    pub collection_id: CollectionId,
     pub token_id: TokenId,
-    #[serde(skip)]
     pub fee: BigDecimal,
     pub nonce: Nonce,
     pub signature: TxSignature,
@@ -228,8 +1084,8 @@ pub struct Withdraw {
     cached_signer: VerifiedSignatureCache,
 }
 
-impl Withdraw {
-    const TX_TYPE: u8 = 3;
+impl Approve {
+    const TX_TYPE: u8 = 12;
 
     #[allow(clippy::too_many_arguments)]
     /// Creates transaction from parts
@@ -237,7 +1093,8 @@ impl Withdraw {
     pub fn new(
         account_id: AccountId,
         from: Address,
-        to: Address,
+        operator: Address,
+        collection_id: CollectionId,
         token_id: TokenId,
         fee: BigDecimal,
         nonce: Nonce,
@@ -246,7 +1103,8 @@ impl Withdraw {
         let mut tx = Self {
             account_id,
             from,
-            to,
+            operator,
+            collection_id,
             token_id,
             fee,
             nonce,
@@ -264,16 +1122,26 @@ impl Withdraw {
     pub fn new_signed(
         account_id: AccountId,
         from: Address,
-        to: Address,
+        operator: Address,
+        collection_id: CollectionId,
         token_id: TokenId,
         fee: BigDecimal,
         nonce: Nonce,
         private_key: &PrivateKey<Engine>,
     ) -> Result<Self, failure::Error> {
-        let mut tx = Self::new(account_id, from, to, token_id, fee, nonce, None);
+        let mut tx = Self::new(
+            account_id,
+            from,
+            operator,
+            collection_id,
+            token_id,
+            fee,
+            nonce,
+            None,
+        );
         tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
         if !tx.check_correctness() {
-            bail!("Transfer is incorrect, check amounts");
+            bail!("Approve is incorrect, check fee");
         }
         Ok(tx)
     }
@@ -282,15 +1150,29 @@ impl Withdraw {
         let mut out = Vec::new();
         out.extend_from_slice(&[Self::TX_TYPE]);
         out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
-        out.extend_from_slice(&self.from.as_bytes());
-        out.extend_from_slice(self.to.as_bytes());
-        out.extend_from_slice(&self.token_id.to_be_bytes());
+        out.extend_from_slice(self.from.as_bytes());
+        out.extend_from_slice(self.operator.as_bytes());
+        out.extend_from_slice(&self.collection_id.to_be_bytes());
+        out.extend_from_slice(&u256_to_be_bytes(&self.token_id));
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
         out.extend_from_slice(&self.nonce.to_be_bytes());
         out
     }
 
+    /// NFT the operator is approved for.
+    pub fn nft(&self) -> Nft {
+        Nft::new(self.collection_id, self.token_id)
+    }
+
+    /// Whether the transaction revokes the current approval of the token.
+    pub fn is_revoke(&self) -> bool {
+        self.operator == Address::zero()
+    }
+
     pub fn check_correctness(&mut self) -> bool {
-        let mut valid = self.fee.is_integer() && is_fee_amount_packable(&self.fee);
+        let mut valid = self.fee.is_integer()
+            && is_fee_amount_packable(&self.fee)
+            && self.operator != self.from;
 
         if valid {
             let signer = self.verify_signature();
@@ -313,12 +1195,131 @@ impl Withdraw {
     /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
     pub fn get_ethereum_sign_message(&self) -> String {
         format!(
-            "Withdraw {token_id}\n\
-            To: {to:?}\n\
+            "Approve {operator:?} for {token_id} of collection {collection_id}\n\
+            Fee: {fee} ETH\n\
             Nonce: {nonce}\n\
             Account Id: {account_id}",
+            operator = self.operator,
             token_id = self.token_id,
-            to = self.to,
+            collection_id = self.collection_id,
+            fee = format_ether(&self.fee),
+            nonce = self.nonce,
+            account_id = self.account_id,
+        )
+    }
+}
+
+/// Grants or revokes the right of the operator account to transfer any token of the account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveForAll {
+    pub account_id: AccountId,
+    pub from: Address,
+    pub operator: Address,
+    pub approved: bool,
+    pub fee: BigDecimal,
+    pub nonce: Nonce,
+    pub signature: TxSignature,
+    #[serde(skip)]
+    cached_signer: VerifiedSignatureCache,
+}
+
+impl ApproveForAll {
+    const TX_TYPE: u8 = 13;
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates transaction from parts
+    /// signature is optional, because sometimes we don't know it (i.e. data_restore)
+    pub fn new(
+        account_id: AccountId,
+        from: Address,
+        operator: Address,
+        approved: bool,
+        fee: BigDecimal,
+        nonce: Nonce,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        let mut tx = Self {
+            account_id,
+            from,
+            operator,
+            approved,
+            fee,
+            nonce,
+            signature: signature.clone().unwrap_or_default(),
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            tx.cached_signer = VerifiedSignatureCache::Cached(tx.verify_signature());
+        }
+        tx
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Creates signed transaction using private key, checks for correcteness
+    pub fn new_signed(
+        account_id: AccountId,
+        from: Address,
+        operator: Address,
+        approved: bool,
+        fee: BigDecimal,
+        nonce: Nonce,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, failure::Error> {
+        let mut tx = Self::new(account_id, from, operator, approved, fee, nonce, None);
+        tx.signature = TxSignature::sign_musig(private_key, &tx.get_bytes());
+        if !tx.check_correctness() {
+            bail!("ApproveForAll is incorrect, check fee");
+        }
+        Ok(tx)
+    }
+
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes()[1..]);
+        out.extend_from_slice(self.from.as_bytes());
+        out.extend_from_slice(self.operator.as_bytes());
+        out.extend_from_slice(&[self.approved as u8]);
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out
+    }
+
+    pub fn check_correctness(&mut self) -> bool {
+        let mut valid = self.fee.is_integer()
+            && is_fee_amount_packable(&self.fee)
+            && self.operator != Address::zero()
+            && self.operator != self.from;
+
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        }
+        valid
+    }
+
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.get_bytes()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
+
+    /// Get message that should be signed by Ethereum keys of the account for 2F authentication.
+    pub fn get_ethereum_sign_message(&self) -> String {
+        format!(
+            "{action} {operator:?} for all tokens\n\
+            Fee: {fee} ETH\n\
+            Nonce: {nonce}\n\
+            Account Id: {account_id}",
+            action = if self.approved { "Approve" } else { "Revoke" },
+            operator = self.operator,
+            fee = format_ether(&self.fee),
             nonce = self.nonce,
             account_id = self.account_id,
         )
@@ -431,18 +1432,75 @@ impl ChangePubKey {
 #[serde(tag = "type")]
 pub enum FranklinTx {
     Transfer(Box<Transfer>),
+    BatchTransfer(Box<BatchTransfer>),
+    Swap(Box<Swap>),
     Withdraw(Box<Withdraw>),
     Close(Box<Close>),
     ChangePubKey(Box<ChangePubKey>),
+    Mint(Box<Mint>),
+    Burn(Box<Burn>),
+    Approve(Box<Approve>),
+    ApproveForAll(Box<ApproveForAll>),
+}
+
+/// Signed representation of the tx validity window, an open bound is encoded as the
+/// lowest or the highest timestamp respectively.
+fn validity_window_bytes(valid_from: Option<u32>, valid_until: Option<u32>) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&valid_from.unwrap_or(0).to_be_bytes());
+    out.extend_from_slice(&valid_until.unwrap_or(u32::max_value()).to_be_bytes());
+    out
+}
+
+/// Sponsor should be another account and sign the tx with the given bytes.
+fn is_sponsorship_correct(
+    sponsor: Option<&Sponsorship>,
+    account_id: AccountId,
+    tx_bytes: &[u8],
+) -> bool {
+    sponsor.map_or(true, |sponsor| {
+        sponsor.account_id != account_id && sponsor.verify_signature(tx_bytes).is_some()
+    })
+}
+
+/// Validity window should not end before it starts.
+fn is_validity_window_correct(valid_from: Option<u32>, valid_until: Option<u32>) -> bool {
+    match (valid_from, valid_until) {
+        (Some(valid_from), Some(valid_until)) => valid_from <= valid_until,
+        _ => true,
+    }
+}
+
+/// Lines of the Ethereum sign message describing the tx validity window, empty if not limited.
+fn validity_window_message(valid_from: Option<u32>, valid_until: Option<u32>) -> String {
+    let format_timestamp = |timestamp: u32| {
+        chrono::NaiveDateTime::from_timestamp(i64::from(timestamp), 0)
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string()
+    };
+    let mut message = String::new();
+    if let Some(valid_from) = valid_from {
+        message.push_str(&format!("\nValid from: {}", format_timestamp(valid_from)));
+    }
+    if let Some(valid_until) = valid_until {
+        message.push_str(&format!("\nValid until: {}", format_timestamp(valid_until)));
+    }
+    message
 }
 
 impl FranklinTx {
     pub fn hash(&self) -> TxHash {
         let bytes = match self {
             FranklinTx::Transfer(tx) => tx.get_bytes(),
+            FranklinTx::BatchTransfer(tx) => tx.get_bytes(),
+            FranklinTx::Swap(tx) => tx.get_bytes(),
             FranklinTx::Withdraw(tx) => tx.get_bytes(),
             FranklinTx::Close(tx) => tx.get_bytes(),
             FranklinTx::ChangePubKey(tx) => tx.get_bytes(),
+            FranklinTx::Mint(tx) => tx.get_bytes(),
+            FranklinTx::Burn(tx) => tx.get_bytes(),
+            FranklinTx::Approve(tx) => tx.get_bytes(),
+            FranklinTx::ApproveForAll(tx) => tx.get_bytes(),
         };
 
         let mut hasher = Sha256::new();
@@ -455,48 +1513,101 @@ impl FranklinTx {
     pub fn account(&self) -> Address {
         match self {
             FranklinTx::Transfer(tx) => tx.from,
+            FranklinTx::BatchTransfer(tx) => tx.from,
+            FranklinTx::Swap(tx) => tx.order_a.account,
             FranklinTx::Withdraw(tx) => tx.from,
             FranklinTx::Close(tx) => tx.account,
             FranklinTx::ChangePubKey(tx) => tx.account,
+            FranklinTx::Mint(tx) => tx.account,
+            FranklinTx::Burn(tx) => tx.from,
+            FranklinTx::Approve(tx) => tx.from,
+            FranklinTx::ApproveForAll(tx) => tx.from,
         }
     }
 
     pub fn nonce(&self) -> Nonce {
         match self {
             FranklinTx::Transfer(tx) => tx.nonce,
+            FranklinTx::BatchTransfer(tx) => tx.nonce,
+            FranklinTx::Swap(tx) => tx.order_a.nonce,
             FranklinTx::Withdraw(tx) => tx.nonce,
             FranklinTx::Close(tx) => tx.nonce,
             FranklinTx::ChangePubKey(tx) => tx.nonce,
+            FranklinTx::Mint(tx) => tx.nonce,
+            FranklinTx::Burn(tx) => tx.nonce,
+            FranklinTx::Approve(tx) => tx.nonce,
+            FranklinTx::ApproveForAll(tx) => tx.nonce,
         }
     }
 
     pub fn check_correctness(&mut self) -> bool {
         match self {
             FranklinTx::Transfer(tx) => tx.check_correctness(),
+            FranklinTx::BatchTransfer(tx) => tx.check_correctness(),
+            FranklinTx::Swap(tx) => tx.check_correctness(),
             FranklinTx::Withdraw(tx) => tx.check_correctness(),
             FranklinTx::Close(tx) => tx.check_correctness(),
             FranklinTx::ChangePubKey(tx) => tx.check_correctness(),
+            FranklinTx::Mint(tx) => tx.check_correctness(),
+            FranklinTx::Burn(tx) => tx.check_correctness(),
+            FranklinTx::Approve(tx) => tx.check_correctness(),
+            FranklinTx::ApproveForAll(tx) => tx.check_correctness(),
         }
     }
 
     pub fn get_bytes(&self) -> Vec<u8> {
         match self {
             FranklinTx::Transfer(tx) => tx.get_bytes(),
+            FranklinTx::BatchTransfer(tx) => tx.get_bytes(),
+            FranklinTx::Swap(tx) => tx.get_bytes(),
             FranklinTx::Withdraw(tx) => tx.get_bytes(),
             FranklinTx::Close(tx) => tx.get_bytes(),
             FranklinTx::ChangePubKey(tx) => tx.get_bytes(),
+            FranklinTx::Mint(tx) => tx.get_bytes(),
+            FranklinTx::Burn(tx) => tx.get_bytes(),
+            FranklinTx::Approve(tx) => tx.get_bytes(),
+            FranklinTx::ApproveForAll(tx) => tx.get_bytes(),
         }
     }
 
     pub fn min_chunks(&self) -> usize {
         match self {
             FranklinTx::Transfer(_) => TransferOp::CHUNKS,
+            FranklinTx::BatchTransfer(tx) => BatchTransferOp::chunks_for(tx.tokens.len()),
+            FranklinTx::Swap(_) => SwapOp::CHUNKS,
             FranklinTx::Withdraw(_) => WithdrawOp::CHUNKS,
             FranklinTx::Close(_) => CloseOp::CHUNKS,
             FranklinTx::ChangePubKey(_) => ChangePubKeyOp::CHUNKS,
+            FranklinTx::Mint(_) => MintOp::CHUNKS,
+            FranklinTx::Burn(_) => BurnOp::CHUNKS,
+            FranklinTx::Approve(_) => ApproveOp::CHUNKS,
+            FranklinTx::ApproveForAll(_) => ApproveForAllOp::CHUNKS,
+        }
+    }
+
+    /// Validity window of the tx as `(valid_from, valid_until)` unix timestamps,
+    /// txs other than `Transfer` and `Withdraw` are not limited in time.
+    pub fn validity_window(&self) -> (Option<u32>, Option<u32>) {
+        match self {
+            FranklinTx::Transfer(tx) => (tx.valid_from, tx.valid_until),
+            FranklinTx::Withdraw(tx) => (tx.valid_from, tx.valid_until),
+            _ => (None, None),
         }
     }
 
+    /// Checks whether the tx can be executed at the given unix timestamp.
+    pub fn is_valid_at(&self, timestamp: u32) -> bool {
+        let (valid_from, valid_until) = self.validity_window();
+        valid_from.map_or(true, |valid_from| valid_from <= timestamp)
+            && valid_until.map_or(true, |valid_until| timestamp <= valid_until)
+    }
+
+    /// Checks whether the validity window of the tx has ended by the given unix timestamp.
+    pub fn is_expired(&self, timestamp: u32) -> bool {
+        let (_, valid_until) = self.validity_window();
+        valid_until.map_or(false, |valid_until| timestamp > valid_until)
+    }
+
     pub fn is_withdraw(&self) -> bool {
         match self {
             FranklinTx::Withdraw(_) => true,
@@ -929,8 +2040,11 @@ mod test {
             Address::from(rng.gen::<[u8; 20]>()),
             Address::from(rng.gen::<[u8; 20]>()),
             rng.gen(),
+            TokenId::from_big_endian(&rng.gen::<[u8; 32]>()),
             BigDecimal::from(56_700_000_000u64),
             rng.gen(),
+            Some(rng.gen()),
+            None,
             &key,
         )
         .expect("failed to sign transfer");
@@ -950,9 +2064,18 @@ mod test {
             ("accountId", transfer.account_id.to_be_bytes()[1..].to_vec()),
             ("from", transfer.from.as_bytes().to_vec()),
             ("to", transfer.to.as_bytes().to_vec()),
-            ("token_id", transfer.token_id.to_be_bytes().to_vec()),
+            (
+                "collection_id",
+                transfer.collection_id.to_be_bytes().to_vec(),
+            ),
+            ("token_id", u256_to_be_bytes(&transfer.token_id).to_vec()),
             ("fee", pack_fee_amount(&transfer.fee)),
             ("nonce", transfer.nonce.to_be_bytes().to_vec()),
+            (
+                "valid_from",
+                transfer.valid_from.unwrap().to_be_bytes().to_vec(),
+            ),
+            ("valid_until", u32::max_value().to_be_bytes().to_vec()),
         ];
         println!("Signed transaction fields:");
         let mut field_concat = Vec::new();
@@ -977,8 +2100,11 @@ mod test {
             Address::from(rng.gen::<[u8; 20]>()),
             Address::from(rng.gen::<[u8; 20]>()),
             rng.gen(),
+            TokenId::from_big_endian(&rng.gen::<[u8; 32]>()),
             BigDecimal::from(56_700_000_000u64),
             rng.gen(),
+            Some(rng.gen()),
+            None,
             &key,
         )
         .expect("failed to sign withdraw");
@@ -998,9 +2124,18 @@ mod test {
             ("accountId", withdraw.account_id.to_be_bytes()[1..].to_vec()),
             ("from", withdraw.from.as_bytes().to_vec()),
             ("to", withdraw.to.as_bytes().to_vec()),
-            ("token_id", withdraw.token_id.to_be_bytes().to_vec()),
+            (
+                "collection_id",
+                withdraw.collection_id.to_be_bytes().to_vec(),
+            ),
+            ("token_id", u256_to_be_bytes(&withdraw.token_id).to_vec()),
             ("fee", pack_fee_amount(&withdraw.fee)),
             ("nonce", withdraw.nonce.to_be_bytes().to_vec()),
+            (
+                "valid_from",
+                withdraw.valid_from.unwrap().to_be_bytes().to_vec(),
+            ),
+            ("valid_until", u32::max_value().to_be_bytes().to_vec()),
         ];
         println!("Signed transaction fields:");
         let mut field_concat = Vec::new();
@@ -1171,4 +2306,95 @@ mod test {
             assert_eq!(signature, correct_signature, "signature is incorrect");
         }
     }
+
+    #[test]
+    fn test_validity_window() {
+        let transfer = |valid_from, valid_until| {
+            FranklinTx::Transfer(Box::new(Transfer::new(
+                1,
+                Address::from([1u8; 20]),
+                Address::from([2u8; 20]),
+                1,
+                TokenId::from(1),
+                BigDecimal::from(0),
+                0,
+                valid_from,
+                valid_until,
+                None,
+            )))
+        };
+
+        let unlimited = transfer(None, None);
+        assert!(unlimited.is_valid_at(0));
+        assert!(!unlimited.is_expired(u32::max_value()));
+
+        let limited = transfer(Some(100), Some(200));
+        assert!(!limited.is_valid_at(99));
+        assert!(limited.is_valid_at(100));
+        assert!(limited.is_valid_at(200));
+        assert!(!limited.is_valid_at(201));
+        assert!(!limited.is_expired(200));
+        assert!(limited.is_expired(201));
+
+        // window is signed, so changing it changes the tx
+        assert_ne!(unlimited.get_bytes(), limited.get_bytes());
+        assert_ne!(
+            transfer(Some(100), None).get_bytes(),
+            transfer(None, Some(100)).get_bytes()
+        );
+    }
+
+    #[test]
+    fn test_sponsorship() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let sender_key = PrivateKey(rng.gen());
+        let sponsor_key = PrivateKey(rng.gen());
+        let sponsor_pub_key_hash = PubKeyHash::from_privkey(&sponsor_key);
+
+        let mut transfer = Transfer::new_signed(
+            1,
+            Address::from([1u8; 20]),
+            Address::from([2u8; 20]),
+            1,
+            TokenId::from(1),
+            BigDecimal::from(1000),
+            0,
+            None,
+            None,
+            &sender_key,
+        )
+        .expect("transfer signing failed");
+        assert_eq!(transfer.fee_payer(), 1);
+
+        let sponsorship = Sponsorship::new_signed(&transfer.get_bytes(), 2, &sponsor_key);
+        assert_eq!(
+            sponsorship.verify_signature(&transfer.get_bytes()),
+            Some(sponsor_pub_key_hash.clone())
+        );
+        transfer.sponsor = Some(sponsorship.clone());
+        assert!(transfer.check_correctness());
+        assert_eq!(transfer.fee_payer(), 2);
+
+        // sponsorship is bound to the tx and to the sponsor account
+        let mut other_transfer = transfer.clone();
+        other_transfer.nonce = 1;
+        assert_ne!(
+            sponsorship.verify_signature(&other_transfer.get_bytes()),
+            Some(sponsor_pub_key_hash.clone())
+        );
+        let mut other_sponsor = sponsorship;
+        other_sponsor.account_id = 3;
+        assert_ne!(
+            other_sponsor.verify_signature(&transfer.get_bytes()),
+            Some(sponsor_pub_key_hash)
+        );
+
+        // sender can not sponsor its own tx
+        transfer.sponsor = Some(Sponsorship::new_signed(
+            &transfer.get_bytes(),
+            1,
+            &sender_key,
+        ));
+        assert!(!transfer.check_correctness());
+    }
 }
@@ -11,7 +11,7 @@ use crate::franklin_crypto::group_hash::BlakeHasher;
 use crate::franklin_crypto::rescue::bn256::Bn256RescueParams;
 use crate::merkle_tree::pedersen_hasher::BabyPedersenHasher;
 use crate::merkle_tree::rescue_hasher::BabyRescueHasher;
-use crate::node::TokenId;
+use crate::node::{TokenId, U256};
 
 static mut ACCOUNT_TREE_DEPTH_VALUE: usize = 24;
 /// account_tree_depth.
@@ -41,9 +41,9 @@ pub fn account_tree_depth() -> usize {
     }
 }
 
-static mut TOKEN_TREE_DEPTH_VALUE: usize = 8;
-/// balance tree_depth.
-/// Value must be specified as environment variable at compile time under `BALANCE_TREE_DEPTH_VALUE` key.
+static mut TOKEN_TREE_DEPTH_VALUE: usize = 16;
+/// Depth of the account token subtree, one leaf per NFT held by the account.
+/// Value must be specified as environment variable at compile time under `TOKEN_TREE_DEPTH_VALUE` key.
 pub fn token_tree_depth() -> usize {
     // use of mutable static is unsafe as it can be mutated by multiple threads.
     // There's no risk of data race, the worst that can happen is that we parse
@@ -68,11 +68,12 @@ pub fn token_tree_depth() -> usize {
         TOKEN_TREE_DEPTH_VALUE
     }
 }
-/// Number of supported tokens.
+/// Maximum number of NFTs a single account can hold.
 pub fn total_tokens() -> usize {
     2usize.pow(token_tree_depth() as u32)
 }
-pub const ETH_TOKEN_ID: TokenId = 0;
+
+pub const ETH_TOKEN_ID: TokenId = U256([0; 4]);
 
 pub const ACCOUNT_ID_BIT_WIDTH: usize = 24;
 
@@ -91,19 +92,31 @@ pub const SUBTREE_HASH_WIDTH: usize = 254; //seems to be equal to Bn256::NUM_BIT
 pub const SUBTREE_HASH_WIDTH_PADDED: usize = 256;
 
 /// token_id bit width
-pub const TOKENID_BIT_WIDTH: usize = 16;
+pub const TOKENID_BIT_WIDTH: usize = 256;
+/// token_id does not fit into a field element, so the circuit carries it as two limbs
+pub const TOKENID_LIMB_BIT_WIDTH: usize = 128;
+/// collection_id bit width
+pub const COLLECTIONID_BIT_WIDTH: usize = 16;
+/// content_hash bit width, carried by the circuit as two limbs like token_id
+pub const CONTENT_HASH_BIT_WIDTH: usize = 256;
+pub const CONTENT_HASH_LIMB_BIT_WIDTH: usize = 128;
 pub const BALANCE_BIT_WIDTH: usize = 128;
 
 pub const NEW_PUBKEY_HASH_WIDTH: usize = FR_ADDRESS_LEN * 8;
 pub const ADDRESS_WIDTH: usize = FR_ADDRESS_LEN * 8;
 /// Nonce bit width
 pub const NONCE_BIT_WIDTH: usize = 32;
+/// Bit width of the tx validity window timestamps, it keeps the signed message of
+/// transfer and withdraw within `MAX_CIRCUIT_MSG_HASH_BITS`
+pub const TIMESTAMP_BIT_WIDTH: usize = 32;
 //
 pub const CHUNK_BIT_WIDTH: usize = 64;
 
 pub const MAX_CIRCUIT_MSG_HASH_BITS: usize = 736;
 
 pub const ETH_ADDRESS_BIT_WIDTH: usize = 160;
+/// Bit width of the flag telling whether a priority op that may fail has succeeded
+pub const SUCCESS_FLAG_BIT_WIDTH: usize = 8;
 /// Block number bit width
 pub const BLOCK_NUMBER_BIT_WIDTH: usize = 32;
 
@@ -149,6 +162,15 @@ pub(crate) fn block_chunk_sizes() -> &'static [usize] {
 
 /// Priority op should be executed for this number of eth blocks.
 pub const PRIORITY_EXPIRATION: u64 = 250;
+
+/// L1 gas spent to commit a block, regardless of its size
+pub const COMMIT_BLOCK_GAS: u64 = 200_000;
+/// L1 gas spent to verify the proof of a block, regardless of its size
+pub const VERIFY_BLOCK_GAS: u64 = 500_000;
+/// L1 gas spent on the pubdata of a single chunk
+pub const CHUNK_GAS: u64 = 1_000;
+/// L1 gas spent to complete a withdrawal of a token
+pub const WITHDRAW_COMPLETION_GAS: u64 = 100_000;
 pub const FR_ADDRESS_LEN: usize = 20;
 
 pub const PAD_MSG_BEFORE_HASH_BITS_LEN: usize = 736;
@@ -157,16 +179,93 @@ pub const PAD_MSG_BEFORE_HASH_BITS_LEN: usize = 736;
 pub const SIGNED_WITHDRAW_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
     + ACCOUNT_ID_BIT_WIDTH
     + 2 * ADDRESS_WIDTH
+    + COLLECTIONID_BIT_WIDTH
     + TOKENID_BIT_WIDTH
     + FEE_EXPONENT_BIT_WIDTH
     + FEE_MANTISSA_BIT_WIDTH
-    + NONCE_BIT_WIDTH;
+    + NONCE_BIT_WIDTH
+    + 2 * TIMESTAMP_BIT_WIDTH;
 
 /// Size of the data that is signed for transfer tx
 pub const SIGNED_TRANSFER_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
     + ACCOUNT_ID_BIT_WIDTH
     + 2 * ADDRESS_WIDTH
+    + COLLECTIONID_BIT_WIDTH
+    + TOKENID_BIT_WIDTH
+    + FEE_EXPONENT_BIT_WIDTH
+    + FEE_MANTISSA_BIT_WIDTH
+    + NONCE_BIT_WIDTH
+    + 2 * TIMESTAMP_BIT_WIDTH;
+
+/// Size of the data that is signed for mint tx
+pub const SIGNED_MINT_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
+    + ACCOUNT_ID_BIT_WIDTH
+    + ADDRESS_WIDTH
+    + COLLECTIONID_BIT_WIDTH
+    + TOKENID_BIT_WIDTH
+    + CONTENT_HASH_BIT_WIDTH
+    + FEE_EXPONENT_BIT_WIDTH
+    + FEE_MANTISSA_BIT_WIDTH
+    + NONCE_BIT_WIDTH;
+
+/// Size of the data that is signed for burn tx
+pub const SIGNED_BURN_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
+    + ACCOUNT_ID_BIT_WIDTH
+    + ADDRESS_WIDTH
+    + COLLECTIONID_BIT_WIDTH
+    + TOKENID_BIT_WIDTH
+    + FEE_EXPONENT_BIT_WIDTH
+    + FEE_MANTISSA_BIT_WIDTH
+    + NONCE_BIT_WIDTH;
+
+/// Size of the data that is signed for approve tx
+pub const SIGNED_APPROVE_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
+    + ACCOUNT_ID_BIT_WIDTH
+    + 2 * ADDRESS_WIDTH
+    + COLLECTIONID_BIT_WIDTH
+    + TOKENID_BIT_WIDTH
+    + FEE_EXPONENT_BIT_WIDTH
+    + FEE_MANTISSA_BIT_WIDTH
+    + NONCE_BIT_WIDTH;
+
+/// Size of the data that is signed for approve for all tx, approval is encoded with 1 byte
+pub const SIGNED_APPROVE_FOR_ALL_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
+    + ACCOUNT_ID_BIT_WIDTH
+    + 2 * ADDRESS_WIDTH
+    + 8
+    + FEE_EXPONENT_BIT_WIDTH
+    + FEE_MANTISSA_BIT_WIDTH
+    + NONCE_BIT_WIDTH;
+
+/// Number of tokens in a batch transfer is encoded with 1 byte
+pub const TOKENS_COUNT_BIT_WIDTH: usize = 8;
+/// Max number of tokens moved by a single batch transfer
+pub const MAX_BATCH_TRANSFER_TOKENS: usize = 32;
+/// Depth of the tree a list of tokens is committed to by, one leaf per token of the list.
+/// Fits both `MAX_BATCH_TRANSFER_TOKENS` and `MAX_ORDER_BUY_TOKENS`.
+pub const NFT_LIST_TREE_DEPTH: usize = 5;
+
+/// Size of the data that is signed for batch transfer tx, tokens are committed to by their list tree root
+pub const SIGNED_BATCH_TRANSFER_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
+    + ACCOUNT_ID_BIT_WIDTH
+    + 2 * ADDRESS_WIDTH
+    + TOKENS_COUNT_BIT_WIDTH
+    + FR_BIT_WIDTH_PADDED
+    + FEE_EXPONENT_BIT_WIDTH
+    + FEE_MANTISSA_BIT_WIDTH
+    + NONCE_BIT_WIDTH;
+
+/// Max number of tokens an order accepts in exchange
+pub const MAX_ORDER_BUY_TOKENS: usize = 32;
+
+/// Size of the order that is signed by each account of the swap tx,
+/// accepted tokens are committed to by their list tree root
+pub const SIGNED_ORDER_BIT_WIDTH: usize = TX_TYPE_BIT_WIDTH
+    + ACCOUNT_ID_BIT_WIDTH
+    + COLLECTIONID_BIT_WIDTH
     + TOKENID_BIT_WIDTH
+    + TOKENS_COUNT_BIT_WIDTH
+    + FR_BIT_WIDTH_PADDED
     + FEE_EXPONENT_BIT_WIDTH
     + FEE_MANTISSA_BIT_WIDTH
     + NONCE_BIT_WIDTH;
@@ -480,6 +480,31 @@ pub fn bytes_slice_to_uint128(bytes: &[u8]) -> Option<u128> {
     Some(u128::from_be_bytes(new_bytes.try_into().ok()?))
 }
 
+pub fn bytes_slice_to_uint256(bytes: &[u8]) -> Option<U256> {
+    if bytes.len() > 32 {
+        return None;
+    }
+    Some(U256::from_big_endian(bytes))
+}
+
+pub fn u256_to_be_bytes(number: &U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    number.to_big_endian(&mut bytes);
+    bytes
+}
+
+/// Its important to use this, instead of converting through floats
+pub fn u256_to_bigdecimal(n: &U256) -> BigDecimal {
+    n.to_string().parse().unwrap()
+}
+
+pub fn big_decimal_to_u256(big_decimal: &BigDecimal) -> Option<U256> {
+    if !big_decimal.is_integer() {
+        return None;
+    }
+    U256::from_dec_str(&big_decimal.with_scale(0).to_string()).ok()
+}
+
 pub fn bytes32_from_slice(bytes: &[u8]) -> Option<[u8; 32]> {
     if bytes.len() != 32 {
         return None;
@@ -502,4 +527,15 @@ mod test {
         let out: Vec<bool> = BitIteratorLe::new(&test_vector).collect();
         assert_eq!(reference, out);
     }
+
+    #[test]
+    fn test_u256_roundtrip() {
+        let number = U256::max_value() - U256::from(0xbeef);
+
+        let bytes = u256_to_be_bytes(&number);
+        assert_eq!(bytes_slice_to_uint256(&bytes), Some(number));
+
+        let big_decimal = u256_to_bigdecimal(&number);
+        assert_eq!(big_decimal_to_u256(&big_decimal), Some(number));
+    }
 }
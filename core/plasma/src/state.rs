@@ -2,18 +2,20 @@ use bigdecimal::BigDecimal;
 use failure::{bail, ensure, format_err, Error};
 use log::trace;
 use models::node::operations::{
-    ChangePubKeyOp, CloseOp, DepositOp, FranklinOp, FullExitOp, TransferOp, TransferToNewOp,
-    WithdrawOp,
+    ApproveForAllOp, ApproveOp, BatchTransferOp, BurnOp, ChangePubKeyOp, CloseOp, DepositEthOp,
+    DepositOp, ForcedWithdrawOp, FranklinOp, FullExitOp, MintOp, SwapOp, TransferOp,
+    TransferToNewOp, WithdrawOp,
 };
 use models::node::tx::ChangePubKey;
 use models::node::Address;
-use models::node::{Account, AccountTree, FranklinPriorityOp, PubKeyHash};
+use models::node::{Account, AccountTree, CollectionId, FranklinPriorityOp, PubKeyHash};
+use models::node::{AccountId, AccountMap, AccountUpdate, AccountUpdates, BlockNumber, Fr, Nonce};
 use models::node::{
-    AccountId, AccountMap, AccountUpdate, AccountUpdates, BlockNumber, Fr, TokenId,
+    Approve, ApproveForAll, BatchTransfer, Burn, Close, Deposit, DepositEth, ForcedWithdraw,
+    FranklinTx, FullExit, Mint, Nft, Sponsorship, Swap, Transfer, Withdraw,
 };
-use models::node::{Close, Deposit, FranklinTx, FullExit, Transfer, Withdraw};
 use models::params;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct OpSuccess {
@@ -29,14 +31,52 @@ pub struct PlasmaState {
 
     account_id_by_address: HashMap<Address, AccountId>,
 
+    /// Unoccupied ids below `next_account_id`, e.g. the ids of the closed accounts
+    free_account_ids: BTreeSet<AccountId>,
+
+    /// Id following the highest id ever occupied
+    next_account_id: AccountId,
+
+    /// Number of approvals given to the operator, both for a single token and for all tokens
+    operator_approvals: HashMap<AccountId, usize>,
+
+    /// Account holding the token, for every token of the rollup
+    token_owners: HashMap<Nft, AccountId>,
+
+    /// Tokens destroyed by `Burn` txs, they can not appear in the rollup again
+    burned_tokens: HashSet<Nft>,
+
+    /// Tokens created by `Mint` txs, an id is minted once since the token may exist on L1
+    /// after it is withdrawn
+    minted_tokens: HashSet<Nft>,
+
     /// Current block number
     pub block_number: BlockNumber,
+
+    /// Timestamp of the block being formed, in seconds since the UNIX epoch
+    pub block_timestamp: u32,
+
+    /// Accounts allowed by the governance contract to mint tokens, with the collection they mint.
+    /// Set by the state keeper
+    pub minters: HashSet<(CollectionId, Address)>,
+
+    /// Changes to revert at the end of `execute_and_revert`, `None` outside of it
+    journal: Option<StateJournal>,
+}
+
+/// Original state of everything changed since the journal was started.
+#[derive(Debug, Clone, Default)]
+struct StateJournal {
+    /// Accounts as they were before the first change, `None` for the created ones
+    accounts: HashMap<AccountId, Option<Account>>,
+    burned_tokens: Vec<Nft>,
+    minted_tokens: Vec<Nft>,
 }
 
-// TODO ADE: must be removed
+/// ETH fee of the tx, credited to the fee account by `collect_fee`.
+/// The fee is charged from the tx initiator or its sponsor when the tx is applied.
 #[derive(Debug, Clone)]
 pub struct CollectedFee {
-    pub token: TokenId,
     pub amount: BigDecimal,
 }
 
@@ -47,7 +87,16 @@ impl PlasmaState {
         Self {
             token_tree,
             block_number: 0,
+            block_timestamp: 0,
+            minters: HashSet::new(),
             account_id_by_address: HashMap::new(),
+            free_account_ids: BTreeSet::new(),
+            next_account_id: 0,
+            operator_approvals: HashMap::new(),
+            token_owners: HashMap::new(),
+            burned_tokens: HashSet::new(),
+            minted_tokens: HashSet::new(),
+            journal: None,
         }
     }
 
@@ -63,13 +112,30 @@ impl PlasmaState {
     pub fn new(
         token_tree: AccountTree,
         account_id_by_address: HashMap<Address, AccountId>,
+        burned_tokens: HashSet<Nft>,
+        minted_tokens: HashSet<Nft>,
         current_block: BlockNumber,
     ) -> Self {
-        Self {
+        let mut state = Self {
             token_tree,
             block_number: current_block,
+            block_timestamp: 0,
+            minters: HashSet::new(),
             account_id_by_address,
+            free_account_ids: BTreeSet::new(),
+            next_account_id: 0,
+            operator_approvals: HashMap::new(),
+            token_owners: HashMap::new(),
+            burned_tokens,
+            minted_tokens,
+            journal: None,
+        };
+        for (id, account) in state.get_accounts() {
+            state.occupy_account_id(id);
+            state.count_operator_approvals(&account, true);
+            state.index_token_owners(id, &account, true);
         }
+        state
     }
 
     pub fn get_accounts(&self) -> Vec<(u32, Account)> {
@@ -106,21 +172,121 @@ impl PlasmaState {
         match op {
             FranklinPriorityOp::Deposit(op) => self.apply_deposit(op),
             FranklinPriorityOp::FullExit(op) => self.apply_full_exit(op),
+            FranklinPriorityOp::ForcedWithdraw(op) => self.apply_forced_withdraw(op),
+            FranklinPriorityOp::DepositEth(op) => self.apply_deposit_eth(op),
         }
     }
 
     pub fn execute_tx(&mut self, tx: FranklinTx) -> Result<OpSuccess, Error> {
+        ensure!(
+            tx.validity_window()
+                .0
+                .map_or(true, |valid_from| valid_from <= self.block_timestamp),
+            "Tx is not valid yet"
+        );
+        ensure!(!tx.is_expired(self.block_timestamp), "Tx has expired");
         match tx {
             FranklinTx::Transfer(tx) => self.apply_transfer(*tx),
+            FranklinTx::BatchTransfer(tx) => self.apply_batch_transfer(*tx),
+            FranklinTx::Swap(tx) => self.apply_swap(*tx),
             FranklinTx::Withdraw(tx) => self.apply_withdraw(*tx),
             FranklinTx::Close(tx) => self.apply_close(*tx),
             FranklinTx::ChangePubKey(tx) => self.apply_change_pubkey(*tx),
+            FranklinTx::Mint(tx) => self.apply_mint(*tx),
+            FranklinTx::Burn(tx) => self.apply_burn(*tx),
+            FranklinTx::Approve(tx) => self.apply_approve(*tx),
+            FranklinTx::ApproveForAll(tx) => self.apply_approve_for_all(*tx),
+        }
+    }
+
+    /// Runs `f` against the state and reverts every change it made, so txs can be
+    /// simulated without copying the whole account tree.
+    pub fn execute_and_revert<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        assert!(self.journal.is_none(), "State journal is already started");
+        self.journal = Some(StateJournal::default());
+        let result = f(self);
+
+        let journal = self.journal.take().expect("State journal was dropped");
+        for (id, account) in journal.accounts {
+            self.remove_account(id);
+            if let Some(account) = account {
+                self.insert_account(id, account);
+            }
+        }
+        for token in journal.burned_tokens {
+            self.burned_tokens.remove(&token);
+        }
+        for token in journal.minted_tokens {
+            self.minted_tokens.remove(&token);
+        }
+        result
+    }
+
+    /// Records the account before its first change inside `execute_and_revert`.
+    fn journal_account(&mut self, id: AccountId) {
+        if let Some(journal) = &self.journal {
+            if !journal.accounts.contains_key(&id) {
+                let account = self.get_account(id);
+                if let Some(journal) = &mut self.journal {
+                    journal.accounts.insert(id, account);
+                }
+            }
+        }
+    }
+
+    fn burn_token(&mut self, token: Nft) {
+        if self.burned_tokens.insert(token) {
+            if let Some(journal) = &mut self.journal {
+                journal.burned_tokens.push(token);
+            }
+        }
+    }
+
+    fn mint_token(&mut self, token: Nft) {
+        if self.minted_tokens.insert(token) {
+            if let Some(journal) = &mut self.journal {
+                journal.minted_tokens.push(token);
+            }
         }
     }
 
+    /// Returns the lowest unoccupied account id, ids of the closed accounts are reused.
     fn get_free_account_id(&self) -> AccountId {
-        // TODO check for collisions.
-        self.token_tree.items.len() as u32
+        self.free_account_ids
+            .iter()
+            .next()
+            .cloned()
+            .unwrap_or(self.next_account_id)
+    }
+
+    fn occupy_account_id(&mut self, id: AccountId) {
+        if id >= self.next_account_id {
+            self.free_account_ids.extend(self.next_account_id..id);
+            self.next_account_id = id + 1;
+        } else {
+            self.free_account_ids.remove(&id);
+        }
+    }
+
+    /// Adds (or subtracts, if `approved` is `false`) the approvals given by the account
+    /// to the operator approvals index.
+    fn count_operator_approvals(&mut self, account: &Account, approved: bool) {
+        let operators = account
+            .get_approvals()
+            .into_iter()
+            .map(|(_, operator)| operator)
+            .chain(account.get_operators());
+        for operator in operators {
+            let approvals = self.operator_approvals.entry(operator).or_insert(0);
+            if approved {
+                *approvals += 1;
+            } else {
+                *approvals -= 1;
+                if *approvals == 0 {
+                    self.operator_approvals.remove(&operator);
+                }
+            }
+        }
     }
 
     fn apply_deposit(&mut self, priority_op: Deposit) -> OpSuccess {
@@ -130,9 +296,17 @@ impl PlasmaState {
         } else {
             self.get_free_account_id()
         };
+        // a new account always has a free slot, the token may already be in the rollup if it
+        // was minted there and exists on L1 too
+        let success = !self.is_token_in_use(priority_op.nft())
+            && self
+                .get_account(account_id)
+                .map(|account| account.free_token_slot().is_some())
+                .unwrap_or(true);
         let deposit_op = DepositOp {
             priority_op,
             account_id,
+            success,
         };
 
         let updates = self.apply_deposit_op(&deposit_op);
@@ -143,6 +317,26 @@ impl PlasmaState {
         }
     }
 
+    fn apply_deposit_eth(&mut self, priority_op: DepositEth) -> OpSuccess {
+        let account_id = if let Some((account_id, _)) = self.get_account_by_address(&priority_op.to)
+        {
+            account_id
+        } else {
+            self.get_free_account_id()
+        };
+        let deposit_op = DepositEthOp {
+            priority_op,
+            account_id,
+        };
+
+        let updates = self.apply_deposit_eth_op(&deposit_op);
+        OpSuccess {
+            fee: None,
+            updates,
+            executed_op: FranklinOp::DepositEth(Box::new(deposit_op)),
+        }
+    }
+
     fn apply_full_exit(&mut self, priority_op: FullExit) -> OpSuccess {
         // NOTE: Authroization of the FullExit is verified on the contract.
         // TODO ADE: with balances, full exit returns the total amount of the token. check what to do in case of 721
@@ -168,8 +362,9 @@ impl PlasmaState {
 
         let old_nonce = account.nonce;
 
-        let tokens = account.get_tokens();
-        for token in tokens {
+        let tokens = account.get_token_slots();
+        for (slot, token, content_hash) in tokens {
+            updates.extend(revoke_approval(account_id, &mut account, token, old_nonce));
             account.remove_token(token);
             updates.push((
                 account_id,
@@ -177,6 +372,8 @@ impl PlasmaState {
                     new_nonce: old_nonce,
                     old_nonce,
                     token,
+                    content_hash,
+                    slot,
                 },
             ))
         }
@@ -185,11 +382,63 @@ impl PlasmaState {
         updates
     }
 
+    fn apply_forced_withdraw(&mut self, priority_op: ForcedWithdraw) -> OpSuccess {
+        // NOTE: the contract only checks that the request is paid for, ownership is verified here.
+        trace!("Processing {:?}", priority_op);
+        let success = self
+            .get_account(priority_op.account_id)
+            .map(|account| {
+                account.address == priority_op.eth_address && account.has_token(priority_op.nft())
+            })
+            .unwrap_or(false);
+        let op = ForcedWithdrawOp {
+            priority_op,
+            success,
+        };
+
+        OpSuccess {
+            fee: None,
+            updates: self.apply_forced_withdraw_op(&op),
+            executed_op: FranklinOp::ForcedWithdraw(Box::new(op)),
+        }
+    }
+
+    pub fn apply_forced_withdraw_op(&mut self, op: &ForcedWithdrawOp) -> AccountUpdates {
+        let mut updates = Vec::new();
+        if !op.success {
+            return updates;
+        }
+
+        let account_id = op.priority_op.account_id;
+        let token = op.priority_op.nft();
+
+        // expect is ok since the ownership was verified before
+        let mut account = self
+            .get_account(account_id)
+            .expect("Forced withdraw account not found");
+
+        let nonce = account.nonce;
+        updates.extend(revoke_approval(account_id, &mut account, token, nonce));
+        let (slot, content_hash) = account
+            .remove_token(token)
+            .expect("Forced withdraw token not found");
+        self.insert_account(account_id, account);
+
+        updates.push((
+            account_id,
+            AccountUpdate::RemoveToken {
+                token,
+                content_hash,
+                slot,
+                old_nonce: nonce,
+                new_nonce: nonce,
+            },
+        ));
+
+        updates
+    }
+
     fn apply_transfer(&mut self, tx: Transfer) -> Result<OpSuccess, Error> {
-        ensure!(
-            tx.token_id < (params::total_tokens() as TokenId),
-            "Token id is not supported"
-        );
         let (from, from_account) = self
             .get_account_by_address(&tx.from)
             .ok_or_else(|| format_err!("From account does not exist"))?;
@@ -197,11 +446,18 @@ impl PlasmaState {
             from_account.pub_key_hash != PubKeyHash::default(),
             "Account is locked"
         );
+        let signer = tx
+            .verify_signature()
+            .ok_or_else(|| format_err!("Transfer signature is incorrect"))?;
         ensure!(
-            tx.verify_signature() == Some(from_account.pub_key_hash),
+            signer == from_account.pub_key_hash
+                || self.is_signed_by_operator(&from_account, tx.nft(), &signer),
             "Transfer signature is incorrect"
         );
         ensure!(from == tx.account_id, "Transfer account id is incorrect");
+        if let Some(sponsor) = &tx.sponsor {
+            self.check_sponsorship(sponsor, &tx.get_bytes())?;
+        }
 
         if let Some((to, _)) = self.get_account_by_address(&tx.to) {
             let transfer_op = TransferOp { tx, from, to };
@@ -225,11 +481,75 @@ impl PlasmaState {
         }
     }
 
-    fn apply_withdraw(&mut self, tx: Withdraw) -> Result<OpSuccess, Error> {
+    fn apply_batch_transfer(&mut self, tx: BatchTransfer) -> Result<OpSuccess, Error> {
+        let (from, from_account) = self
+            .get_account_by_address(&tx.from)
+            .ok_or_else(|| format_err!("From account does not exist"))?;
+        ensure!(
+            from_account.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        ensure!(
+            tx.verify_signature() == Some(from_account.pub_key_hash),
+            "Batch transfer signature is incorrect"
+        );
+        ensure!(
+            from == tx.account_id,
+            "Batch transfer account id is incorrect"
+        );
+
+        let to = if let Some((to, _)) = self.get_account_by_address(&tx.to) {
+            to
+        } else {
+            self.get_free_account_id()
+        };
+        let batch_transfer_op = BatchTransferOp { tx, from, to };
+
+        let (fee, updates) = self.apply_batch_transfer_op(&batch_transfer_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::BatchTransfer(Box::new(batch_transfer_op)),
+        })
+    }
+
+    fn apply_swap(&mut self, tx: Swap) -> Result<OpSuccess, Error> {
+        let (account_a, account_a_state) = self
+            .get_account_by_address(&tx.order_a.account)
+            .ok_or_else(|| format_err!("Swap account a does not exist"))?;
+        let (account_b, account_b_state) = self
+            .get_account_by_address(&tx.order_b.account)
+            .ok_or_else(|| format_err!("Swap account b does not exist"))?;
+        ensure!(
+            account_a_state.pub_key_hash != PubKeyHash::default()
+                && account_b_state.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        let (signer_a, signer_b) = tx.verify_signatures();
+        ensure!(
+            signer_a == Some(account_a_state.pub_key_hash)
+                && signer_b == Some(account_b_state.pub_key_hash),
+            "Swap signature is incorrect"
+        );
         ensure!(
-            tx.token_id < (params::total_tokens() as TokenId),
-            "Token id is not supported"
+            account_a == tx.order_a.account_id && account_b == tx.order_b.account_id,
+            "Swap account id is incorrect"
         );
+        let swap_op = SwapOp {
+            tx,
+            account_a,
+            account_b,
+        };
+
+        let (fee, updates) = self.apply_swap_op(&swap_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::Swap(Box::new(swap_op)),
+        })
+    }
+
+    fn apply_withdraw(&mut self, tx: Withdraw) -> Result<OpSuccess, Error> {
         let (account_id, account) = self
             .get_account_by_address(&tx.from)
             .ok_or_else(|| format_err!("Account does not exist"))?;
@@ -245,6 +565,9 @@ impl PlasmaState {
             account_id == tx.account_id,
             "Withdraw account id is incorrect"
         );
+        if let Some(sponsor) = &tx.sponsor {
+            self.check_sponsorship(sponsor, &tx.get_bytes())?;
+        }
         let withdraw_op = WithdrawOp { tx, account_id };
 
         let (fee, updates) = self.apply_withdraw_op(&withdraw_op)?;
@@ -255,24 +578,152 @@ impl PlasmaState {
         })
     }
 
-    fn apply_close(&mut self, _tx: Close) -> Result<OpSuccess, Error> {
-        bail!("Account closing is disabled");
-        // let (account_id, account) = self
-        //     .get_account_by_address(&tx.account)
-        //     .ok_or_else(|| format_err!("Account does not exist"))?;
-        // let close_op = CloseOp { tx, account_id };
-        //        ensure!(account.pub_key_hash != PubKeyHash::default(), "Account is locked");
-        // ensure!(
-        //     tx.verify_signature() == Some(account.pub_key_hash),
-        //     "withdraw signature is incorrect"
-        // );
+    /// The minter must be authorized by the governance contract, the contract checks it again
+    /// when the block is committed.
+    fn apply_mint(&mut self, tx: Mint) -> Result<OpSuccess, Error> {
+        let (account_id, account) = self
+            .get_account_by_address(&tx.account)
+            .ok_or_else(|| format_err!("Minter account does not exist"))?;
+        ensure!(
+            account.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        ensure!(
+            tx.verify_signature() == Some(account.pub_key_hash),
+            "Mint signature is incorrect"
+        );
+        ensure!(account_id == tx.account_id, "Mint account id is incorrect");
+        ensure!(
+            self.minters.contains(&(tx.collection_id, tx.account)),
+            "Minter is not authorized"
+        );
+        ensure!(!self.is_token_in_use(tx.nft()), "Token is already in use");
+        ensure!(!self.is_token_burned(tx.nft()), "Token is burned");
+        ensure!(!self.is_token_minted(tx.nft()), "Token was already minted");
+        let mint_op = MintOp { tx, account_id };
+
+        let (fee, updates) = self.apply_mint_op(&mint_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::Mint(Box::new(mint_op)),
+        })
+    }
+
+    fn apply_burn(&mut self, tx: Burn) -> Result<OpSuccess, Error> {
+        let (account_id, account) = self
+            .get_account_by_address(&tx.from)
+            .ok_or_else(|| format_err!("Account does not exist"))?;
+        ensure!(
+            account.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        ensure!(
+            tx.verify_signature() == Some(account.pub_key_hash),
+            "Burn signature is incorrect"
+        );
+        ensure!(account_id == tx.account_id, "Burn account id is incorrect");
+        let burn_op = BurnOp { tx, account_id };
+
+        let (fee, updates) = self.apply_burn_op(&burn_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::Burn(Box::new(burn_op)),
+        })
+    }
+
+    fn apply_approve(&mut self, tx: Approve) -> Result<OpSuccess, Error> {
+        let (account_id, account) = self
+            .get_account_by_address(&tx.from)
+            .ok_or_else(|| format_err!("Account does not exist"))?;
+        ensure!(
+            account.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        ensure!(
+            tx.verify_signature() == Some(account.pub_key_hash),
+            "Approve signature is incorrect"
+        );
+        ensure!(
+            account_id == tx.account_id,
+            "Approve account id is incorrect"
+        );
+        let operator_id = if tx.is_revoke() {
+            None
+        } else {
+            let (operator_id, _) = self
+                .get_account_by_address(&tx.operator)
+                .ok_or_else(|| format_err!("Operator account does not exist"))?;
+            Some(operator_id)
+        };
+        let approve_op = ApproveOp {
+            tx,
+            account_id,
+            operator_id,
+        };
+
+        let (fee, updates) = self.apply_approve_op(&approve_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::Approve(Box::new(approve_op)),
+        })
+    }
+
+    fn apply_approve_for_all(&mut self, tx: ApproveForAll) -> Result<OpSuccess, Error> {
+        let (account_id, account) = self
+            .get_account_by_address(&tx.from)
+            .ok_or_else(|| format_err!("Account does not exist"))?;
+        ensure!(
+            account.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        ensure!(
+            tx.verify_signature() == Some(account.pub_key_hash),
+            "ApproveForAll signature is incorrect"
+        );
+        ensure!(
+            account_id == tx.account_id,
+            "ApproveForAll account id is incorrect"
+        );
+        let (operator_id, _) = self
+            .get_account_by_address(&tx.operator)
+            .ok_or_else(|| format_err!("Operator account does not exist"))?;
+        let approve_for_all_op = ApproveForAllOp {
+            tx,
+            account_id,
+            operator_id,
+        };
+
+        let (fee, updates) = self.apply_approve_for_all_op(&approve_for_all_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::ApproveForAll(Box::new(approve_for_all_op)),
+        })
+    }
+
+    fn apply_close(&mut self, tx: Close) -> Result<OpSuccess, Error> {
+        let (account_id, account) = self
+            .get_account_by_address(&tx.account)
+            .ok_or_else(|| format_err!("Account does not exist"))?;
+        ensure!(
+            account.pub_key_hash != PubKeyHash::default(),
+            "Account is locked"
+        );
+        ensure!(
+            tx.verify_signature() == Some(account.pub_key_hash),
+            "Close signature is incorrect"
+        );
+        let close_op = CloseOp { tx, account_id };
 
-        // let (fee, updates) = self.apply_close_op(&close_op)?;
-        // Ok(OpSuccess {
-        //     fee: Some(fee),
-        //     updates,
-        //     executed_op: FranklinOp::Close(Box::new(close_op)),
-        // })
+        let (fee, updates) = self.apply_close_op(&close_op)?;
+        Ok(OpSuccess {
+            fee: Some(fee),
+            updates,
+            executed_op: FranklinOp::Close(Box::new(close_op)),
+        })
     }
 
     fn apply_change_pubkey(&mut self, tx: ChangePubKey) -> Result<OpSuccess, Error> {
@@ -307,30 +758,27 @@ impl PlasmaState {
             )
         });
 
-        // TODO ADE: fees are (currently) disabled
-        /*
-        for fee in fees {
-            if fee.amount == BigDecimal::from(0) {
-                continue;
-            }
-
-            let old_amount = account.get_balance(fee.token).clone();
+        let total = fees
+            .iter()
+            .fold(BigDecimal::from(0), |total, fee| total + &fee.amount);
+        if total != BigDecimal::from(0) {
+            let old_balance = account.balance.clone();
             let nonce = account.nonce;
-            account.add_balance(fee.token, &fee.amount);
-            let new_amount = account.get_balance(fee.token).clone();
+            account.balance += &total;
+            let new_balance = account.balance.clone();
 
             updates.push((
                 fee_account,
                 AccountUpdate::UpdateBalance {
-                    balance_update: (fee.token, old_amount, new_amount),
+                    old_balance,
+                    new_balance,
                     old_nonce: nonce,
                     new_nonce: nonce,
                 },
             ));
-        }
 
-        self.insert_account(fee_account, account);
-        */
+            self.insert_account(fee_account, account);
+        }
 
         updates
     }
@@ -344,32 +792,159 @@ impl PlasmaState {
         ))
     }
 
-    #[doc(hidden)] // Public for benches.
-    pub fn insert_account(&mut self, id: AccountId, account: Account) {
-        self.account_id_by_address
-            .insert(account.address.clone(), id);
-        self.token_tree.insert(id, account);
+    /// Returns `true` if the signer is the current signing key of an operator approved
+    /// by the owner for the token, either for this token only or for all the owner tokens.
+    fn is_signed_by_operator(&self, owner: &Account, token: Nft, signer: &PubKeyHash) -> bool {
+        let token_operator = owner
+            .token_slot(token)
+            .and_then(|slot| owner.approved(slot));
+        token_operator
+            .into_iter()
+            .chain(owner.get_operators())
+            .filter_map(|operator_id| self.get_account(operator_id))
+            .any(|operator| {
+                operator.pub_key_hash != PubKeyHash::default() && operator.pub_key_hash == *signer
+            })
     }
-
-    #[allow(dead_code)]
-    fn remove_account(&mut self, id: AccountId) {
-        if let Some(account) = self.get_account(id) {
-            self.account_id_by_address.remove(&account.address);
-            self.token_tree.remove(id);
+    /// Adds (or removes, if `held` is `false`) the tokens of the account to the token owners index.
+    /// A token moved to another account may already be indexed to it, such entries are kept.
+    fn index_token_owners(&mut self, id: AccountId, account: &Account, held: bool) {
+        for token in account.get_tokens() {
+            if held {
+                self.token_owners.insert(token, id);
+            } else if self.token_owners.get(&token) == Some(&id) {
+                self.token_owners.remove(&token);
+            }
         }
     }
 
-    pub fn apply_deposit_op(&mut self, op: &DepositOp) -> AccountUpdates {
-        let mut updates = Vec::new();
+    /// Returns `true` if any account has approved the operator for any of its tokens.
+    fn is_approved_operator(&self, operator: AccountId) -> bool {
+        self.operator_approvals.contains_key(&operator)
+    }
 
-        let mut account = self.get_account(op.account_id).unwrap_or_else(|| {
-            let (account, upd) = Account::create_account(op.account_id, op.priority_op.to);
-            updates.extend(upd.into_iter());
-            account
-        });
+    /// Returns `true` if the token is held by any account of the rollup.
+    /// Tokens withdrawn to L1 are not known to the state.
+    pub fn is_token_in_use(&self, token: Nft) -> bool {
+        self.token_owners.contains_key(&token)
+    }
 
-        let old_nonce = account.nonce;
-        account.add_token(op.priority_op.token_id);
+    /// Returns `true` if the token was destroyed by a `Burn` tx.
+    pub fn is_token_burned(&self, token: Nft) -> bool {
+        self.burned_tokens.contains(&token)
+    }
+
+    /// Returns `true` if the token was created by a `Mint` tx, even if it left the rollup since.
+    pub fn is_token_minted(&self, token: Nft) -> bool {
+        self.minted_tokens.contains(&token)
+    }
+
+    /// Checks that the sponsor account can sign txs and has signed the tx with the given bytes.
+    fn check_sponsorship(&self, sponsor: &Sponsorship, tx_bytes: &[u8]) -> Result<(), Error> {
+        let sponsor_account = self
+            .get_account(sponsor.account_id)
+            .ok_or_else(|| format_err!("Sponsor account does not exist"))?;
+        ensure!(
+            sponsor_account.pub_key_hash != PubKeyHash::default(),
+            "Sponsor account is locked"
+        );
+        ensure!(
+            sponsor.verify_signature(tx_bytes) == Some(sponsor_account.pub_key_hash),
+            "Sponsor signature is incorrect"
+        );
+        Ok(())
+    }
+
+    /// Charges the fee of a tx that can be sponsored from the tx initiator.
+    /// The sponsor is charged by `charge_sponsor` once the accounts of the tx are updated,
+    /// so only its balance is checked here.
+    fn charge_sponsored_fee(
+        &self,
+        sponsor: Option<&Sponsorship>,
+        account_id: AccountId,
+        account: &mut Account,
+        fee: &BigDecimal,
+        nonce: Nonce,
+    ) -> Result<Option<(AccountId, AccountUpdate)>, Error> {
+        let sponsor = match sponsor {
+            Some(sponsor) => sponsor,
+            None => return charge_fee(account_id, account, fee, nonce),
+        };
+        let sponsor_account = self
+            .get_account(sponsor.account_id)
+            .ok_or_else(|| format_err!("Sponsor account does not exist"))?;
+        ensure!(
+            sponsor_account.balance >= *fee,
+            "Not enough sponsor balance to pay the fee"
+        );
+        Ok(None)
+    }
+
+    /// Charges the fee of a sponsored tx from the sponsor, the same way the block circuit does
+    /// in the sponsor chunk of the operation: after the accounts of the tx are updated.
+    /// Tx accounts do not pay the sponsored fee, so the sponsor balance checked by
+    /// `charge_sponsored_fee` stays the same.
+    fn charge_sponsor(
+        &mut self,
+        sponsor: Option<&Sponsorship>,
+        fee: &BigDecimal,
+    ) -> Option<(AccountId, AccountUpdate)> {
+        let sponsor_id = sponsor?.account_id;
+        let mut sponsor_account = self
+            .get_account(sponsor_id)
+            .expect("Sponsor account is checked by charge_sponsored_fee");
+        let nonce = sponsor_account.nonce;
+        let update = charge_fee(sponsor_id, &mut sponsor_account, fee, nonce)
+            .expect("Sponsor balance is checked by charge_sponsored_fee");
+        self.insert_account(sponsor_id, sponsor_account);
+        update
+    }
+
+    #[doc(hidden)] // Public for benches.
+    pub fn insert_account(&mut self, id: AccountId, account: Account) {
+        self.journal_account(id);
+        match self.get_account(id) {
+            Some(old_account) => {
+                self.count_operator_approvals(&old_account, false);
+                self.index_token_owners(id, &old_account, false);
+            }
+            None => self.occupy_account_id(id),
+        }
+        self.count_operator_approvals(&account, true);
+        self.index_token_owners(id, &account, true);
+        self.account_id_by_address
+            .insert(account.address.clone(), id);
+        self.token_tree.insert(id, account);
+    }
+
+    fn remove_account(&mut self, id: AccountId) {
+        self.journal_account(id);
+        if let Some(account) = self.get_account(id) {
+            self.count_operator_approvals(&account, false);
+            self.index_token_owners(id, &account, false);
+            self.free_account_ids.insert(id);
+            self.account_id_by_address.remove(&account.address);
+            self.token_tree.remove(id);
+        }
+    }
+
+    pub fn apply_deposit_op(&mut self, op: &DepositOp) -> AccountUpdates {
+        let mut updates = Vec::new();
+        if !op.success {
+            return updates;
+        }
+
+        let mut account = self.get_account(op.account_id).unwrap_or_else(|| {
+            let (account, upd) = Account::create_account(op.account_id, op.priority_op.to);
+            updates.extend(upd.into_iter());
+            account
+        });
+
+        let old_nonce = account.nonce;
+        // expect is ok since the free slot was checked before
+        let slot = account
+            .add_token(op.priority_op.nft(), op.priority_op.content_hash)
+            .expect("Deposit account is full");
 
         self.insert_account(op.account_id, account);
 
@@ -378,7 +953,38 @@ impl PlasmaState {
             AccountUpdate::AddToken {
                 old_nonce,
                 new_nonce: old_nonce,
-                token: op.priority_op.token_id,
+                token: op.priority_op.nft(),
+                content_hash: op.priority_op.content_hash,
+                slot,
+            },
+        ));
+
+        updates
+    }
+
+    pub fn apply_deposit_eth_op(&mut self, op: &DepositEthOp) -> AccountUpdates {
+        let mut updates = Vec::new();
+
+        let mut account = self.get_account(op.account_id).unwrap_or_else(|| {
+            let (account, upd) = Account::create_account(op.account_id, op.priority_op.to);
+            updates.extend(upd.into_iter());
+            account
+        });
+
+        let old_balance = account.balance.clone();
+        account.balance += &op.priority_op.amount;
+        let new_balance = account.balance.clone();
+        let nonce = account.nonce;
+
+        self.insert_account(op.account_id, account);
+
+        updates.push((
+            op.account_id,
+            AccountUpdate::UpdateBalance {
+                old_balance,
+                new_balance,
+                old_nonce: nonce,
+                new_nonce: nonce,
             },
         ));
 
@@ -404,12 +1010,28 @@ impl PlasmaState {
         let mut from_account = self.get_account(op.from).unwrap();
         let from_old_nonce = from_account.nonce;
         ensure!(op.tx.nonce == from_old_nonce, "Nonce mismatch");
-        ensure!(from_account.has_token(op.tx.token_id), "Not current owner");
-        from_account.remove_token(op.tx.token_id);
+        updates.extend(self.charge_sponsored_fee(
+            op.tx.sponsor.as_ref(),
+            op.from,
+            &mut from_account,
+            &op.tx.fee,
+            from_old_nonce,
+        )?);
+        updates.extend(revoke_approval(
+            op.from,
+            &mut from_account,
+            op.tx.nft(),
+            from_old_nonce,
+        ));
+        let (from_slot, content_hash) = from_account
+            .remove_token(op.tx.nft())
+            .ok_or_else(|| format_err!("Not current owner"))?;
         from_account.nonce += 1;
         let from_new_nonce = from_account.nonce;
         let to_account_nonce = to_account.nonce;
-        to_account.add_token(op.tx.token_id);
+        let to_slot = to_account
+            .add_token(op.tx.nft(), content_hash)
+            .ok_or_else(|| account_full_error(op.to))?;
 
         self.insert_account(op.from, from_account);
         self.insert_account(op.to, to_account);
@@ -417,7 +1039,9 @@ impl PlasmaState {
         updates.push((
             op.from,
             AccountUpdate::RemoveToken {
-                token: op.tx.token_id,
+                token: op.tx.nft(),
+                content_hash,
+                slot: from_slot,
                 old_nonce: from_old_nonce,
                 new_nonce: from_new_nonce,
             },
@@ -425,15 +1049,16 @@ impl PlasmaState {
         updates.push((
             op.to,
             AccountUpdate::AddToken {
-                token: op.tx.token_id,
+                token: op.tx.nft(),
+                content_hash,
+                slot: to_slot,
                 old_nonce: to_account_nonce,
                 new_nonce: to_account_nonce,
             },
         ));
+        updates.extend(self.charge_sponsor(op.tx.sponsor.as_ref(), &op.tx.fee));
 
-        // TODO ADE: remove fees
         let fee = CollectedFee {
-            token: op.tx.token_id,
             amount: op.tx.fee.clone(),
         };
 
@@ -450,9 +1075,22 @@ impl PlasmaState {
         let from_old_nonce = from_account.nonce;
 
         ensure!(op.tx.nonce == from_old_nonce, "Nonce mismatch");
-        ensure!(from_account.has_token(op.tx.token_id), "Not current owner");
-
-        from_account.remove_token(op.tx.token_id);
+        updates.extend(self.charge_sponsored_fee(
+            op.tx.sponsor.as_ref(),
+            op.account_id,
+            &mut from_account,
+            &op.tx.fee,
+            from_old_nonce,
+        )?);
+        updates.extend(revoke_approval(
+            op.account_id,
+            &mut from_account,
+            op.tx.nft(),
+            from_old_nonce,
+        ));
+        let (from_slot, content_hash) = from_account
+            .remove_token(op.tx.nft())
+            .ok_or_else(|| format_err!("Not current owner"))?;
         from_account.nonce += 1;
 
         let from_new_nonce = from_account.nonce;
@@ -462,15 +1100,16 @@ impl PlasmaState {
         updates.push((
             op.account_id,
             AccountUpdate::RemoveToken {
-                token: op.tx.token_id,
+                token: op.tx.nft(),
+                content_hash,
+                slot: from_slot,
                 old_nonce: from_old_nonce,
                 new_nonce: from_new_nonce,
             },
         ));
+        updates.extend(self.charge_sponsor(op.tx.sponsor.as_ref(), &op.tx.fee));
 
-        // TODO ADE: remove fees
         let fee = CollectedFee {
-            token: op.tx.token_id,
             amount: op.tx.fee.clone(),
         };
 
@@ -484,11 +1123,28 @@ impl PlasmaState {
         let mut updates = Vec::new();
         let account = self.get_account(op.account_id).unwrap();
 
-        for token in 0..params::total_tokens() {
-            if !account.get_tokens().is_empty() {
-                bail!("Account is not empty");
-            }
+        let tokens = account.get_tokens();
+        if !tokens.is_empty() {
+            let tokens = tokens
+                .iter()
+                .map(|token| format!("{} of collection {}", token.token_id, token.collection_id))
+                .collect::<Vec<_>>();
+            bail!(
+                "Account is not empty, it holds tokens: {}",
+                tokens.join(", ")
+            );
         }
+        ensure!(
+            account.balance == BigDecimal::from(0),
+            "Account is not empty, it holds {} wei",
+            account.balance
+        );
+        // The id of the closed account is given to the next new account,
+        // which must not inherit the approvals.
+        ensure!(
+            !self.is_approved_operator(op.account_id),
+            "Account is an approved operator of other accounts"
+        );
 
         ensure!(op.tx.nonce == account.nonce, "Nonce mismatch");
 
@@ -503,7 +1159,6 @@ impl PlasmaState {
         ));
 
         let fee = CollectedFee {
-            token: params::ETH_TOKEN_ID,
             amount: BigDecimal::from(0),
         };
 
@@ -540,7 +1195,6 @@ impl PlasmaState {
         ));
 
         let fee = CollectedFee {
-            token: params::ETH_TOKEN_ID,
             amount: BigDecimal::from(0),
         };
 
@@ -562,16 +1216,32 @@ impl PlasmaState {
         let from_old_nonce = from_account.nonce;
 
         ensure!(op.tx.nonce == from_old_nonce, "Nonce mismatch");
-        ensure!(from_account.has_token(op.tx.token_id), "Not current owner");
 
-        from_account.remove_token(op.tx.token_id);
+        updates.extend(self.charge_sponsored_fee(
+            op.tx.sponsor.as_ref(),
+            op.from,
+            &mut from_account,
+            &op.tx.fee,
+            from_old_nonce,
+        )?);
+        updates.extend(revoke_approval(
+            op.from,
+            &mut from_account,
+            op.tx.nft(),
+            from_old_nonce,
+        ));
+        let (from_slot, content_hash) = from_account
+            .remove_token(op.tx.nft())
+            .ok_or_else(|| format_err!("Not current owner"))?;
         from_account.nonce += 1;
 
         let from_new_nonce = from_account.nonce;
 
         let to_account_nonce = to_account.nonce;
 
-        to_account.add_token(op.tx.token_id);
+        let to_slot = to_account
+            .add_token(op.tx.nft(), content_hash)
+            .ok_or_else(|| account_full_error(op.to))?;
 
         self.insert_account(op.from, from_account);
         self.insert_account(op.to, to_account);
@@ -579,7 +1249,9 @@ impl PlasmaState {
         updates.push((
             op.from,
             AccountUpdate::RemoveToken {
-                token: op.tx.token_id,
+                token: op.tx.nft(),
+                content_hash,
+                slot: from_slot,
                 old_nonce: from_old_nonce,
                 new_nonce: from_new_nonce,
             },
@@ -588,15 +1260,370 @@ impl PlasmaState {
         updates.push((
             op.to,
             AccountUpdate::AddToken {
-                token: op.tx.token_id,
+                token: op.tx.nft(),
+                content_hash,
+                slot: to_slot,
                 old_nonce: to_account_nonce,
                 new_nonce: to_account_nonce,
             },
         ));
+        updates.extend(self.charge_sponsor(op.tx.sponsor.as_ref(), &op.tx.fee));
+
+        let fee = CollectedFee {
+            amount: op.tx.fee.clone(),
+        };
+
+        Ok((fee, updates))
+    }
+
+    /// Moves all the tokens of the batch, creating the recipient account if it does not exist.
+    /// Nothing is changed if any of the tokens can't be moved.
+    pub fn apply_batch_transfer_op(
+        &mut self,
+        op: &BatchTransferOp,
+    ) -> Result<(CollectedFee, AccountUpdates), Error> {
+        ensure!(op.from != op.to, "Batch transfer to self");
+
+        let mut updates = Vec::new();
+        let mut from_account = self.get_account(op.from).unwrap();
+        let mut to_account = match self.get_account(op.to) {
+            Some(account) => account,
+            None => {
+                let (account, upd) = Account::create_account(op.to, op.tx.to);
+                updates.extend(upd.into_iter());
+                account
+            }
+        };
+
+        let from_old_nonce = from_account.nonce;
+        ensure!(op.tx.nonce == from_old_nonce, "Nonce mismatch");
+        updates.extend(charge_fee(
+            op.from,
+            &mut from_account,
+            &op.tx.fee,
+            from_old_nonce,
+        )?);
+        from_account.nonce += 1;
+        let from_new_nonce = from_account.nonce;
+        let to_account_nonce = to_account.nonce;
+
+        for (i, token) in op.tx.tokens.iter().enumerate() {
+            // nonce is incremented once, together with the first token removal
+            let (old_nonce, new_nonce) = if i == 0 {
+                (from_old_nonce, from_new_nonce)
+            } else {
+                (from_new_nonce, from_new_nonce)
+            };
+
+            updates.extend(revoke_approval(
+                op.from,
+                &mut from_account,
+                *token,
+                old_nonce,
+            ));
+            let (from_slot, content_hash) = from_account
+                .remove_token(*token)
+                .ok_or_else(|| format_err!("Not current owner"))?;
+            let to_slot = to_account
+                .add_token(*token, content_hash)
+                .ok_or_else(|| account_full_error(op.to))?;
+
+            updates.push((
+                op.from,
+                AccountUpdate::RemoveToken {
+                    token: *token,
+                    content_hash,
+                    slot: from_slot,
+                    old_nonce,
+                    new_nonce,
+                },
+            ));
+            updates.push((
+                op.to,
+                AccountUpdate::AddToken {
+                    token: *token,
+                    content_hash,
+                    slot: to_slot,
+                    old_nonce: to_account_nonce,
+                    new_nonce: to_account_nonce,
+                },
+            ));
+        }
+
+        self.insert_account(op.from, from_account);
+        self.insert_account(op.to, to_account);
+
+        let fee = CollectedFee {
+            amount: op.tx.fee.clone(),
+        };
+
+        Ok((fee, updates))
+    }
+
+    /// Exchanges the tokens of the swap, nothing is changed if either side can't be applied.
+    /// Both tokens are removed before they are added, so the swap works for full accounts.
+    pub fn apply_swap_op(&mut self, op: &SwapOp) -> Result<(CollectedFee, AccountUpdates), Error> {
+        ensure!(op.account_a != op.account_b, "Swap with self");
+
+        let token_a = op.tx.order_a.token_sell;
+        let token_b = op.tx.order_b.token_sell;
+        let mut account_a = self.get_account(op.account_a).unwrap();
+        let mut account_b = self.get_account(op.account_b).unwrap();
+
+        let a_old_nonce = account_a.nonce;
+        let b_old_nonce = account_b.nonce;
+        ensure!(op.tx.order_a.nonce == a_old_nonce, "Nonce mismatch");
+        ensure!(op.tx.order_b.nonce == b_old_nonce, "Nonce mismatch");
+
+        let mut updates: AccountUpdates = charge_fee(
+            op.account_a,
+            &mut account_a,
+            &op.tx.order_a.fee,
+            a_old_nonce,
+        )?
+        .into_iter()
+        .chain(charge_fee(
+            op.account_b,
+            &mut account_b,
+            &op.tx.order_b.fee,
+            b_old_nonce,
+        )?)
+        .chain(revoke_approval(
+            op.account_a,
+            &mut account_a,
+            token_a,
+            a_old_nonce,
+        ))
+        .chain(revoke_approval(
+            op.account_b,
+            &mut account_b,
+            token_b,
+            b_old_nonce,
+        ))
+        .collect();
+        let (a_slot, content_hash_a) = account_a
+            .remove_token(token_a)
+            .ok_or_else(|| format_err!("Not current owner"))?;
+        let (b_slot, content_hash_b) = account_b
+            .remove_token(token_b)
+            .ok_or_else(|| format_err!("Not current owner"))?;
+        account_a.nonce += 1;
+        account_b.nonce += 1;
+        let a_new_nonce = account_a.nonce;
+        let b_new_nonce = account_b.nonce;
+
+        let a_new_slot = account_a
+            .add_token(token_b, content_hash_b)
+            .ok_or_else(|| account_full_error(op.account_a))?;
+        let b_new_slot = account_b
+            .add_token(token_a, content_hash_a)
+            .ok_or_else(|| account_full_error(op.account_b))?;
+
+        self.insert_account(op.account_a, account_a);
+        self.insert_account(op.account_b, account_b);
+
+        updates.extend(vec![
+            (
+                op.account_a,
+                AccountUpdate::RemoveToken {
+                    token: token_a,
+                    content_hash: content_hash_a,
+                    slot: a_slot,
+                    old_nonce: a_old_nonce,
+                    new_nonce: a_new_nonce,
+                },
+            ),
+            (
+                op.account_b,
+                AccountUpdate::RemoveToken {
+                    token: token_b,
+                    content_hash: content_hash_b,
+                    slot: b_slot,
+                    old_nonce: b_old_nonce,
+                    new_nonce: b_new_nonce,
+                },
+            ),
+            (
+                op.account_b,
+                AccountUpdate::AddToken {
+                    token: token_a,
+                    content_hash: content_hash_a,
+                    slot: b_new_slot,
+                    old_nonce: b_new_nonce,
+                    new_nonce: b_new_nonce,
+                },
+            ),
+            (
+                op.account_a,
+                AccountUpdate::AddToken {
+                    token: token_b,
+                    content_hash: content_hash_b,
+                    slot: a_new_slot,
+                    old_nonce: a_new_nonce,
+                    new_nonce: a_new_nonce,
+                },
+            ),
+        ]);
+
+        let fee = CollectedFee {
+            amount: &op.tx.order_a.fee + &op.tx.order_b.fee,
+        };
+
+        Ok((fee, updates))
+    }
+
+    pub fn apply_mint_op(&mut self, op: &MintOp) -> Result<(CollectedFee, AccountUpdates), Error> {
+        let mut account = self.get_account(op.account_id).unwrap();
+
+        let old_nonce = account.nonce;
+        ensure!(op.tx.nonce == old_nonce, "Nonce mismatch");
+        let mut updates: AccountUpdates =
+            charge_fee(op.account_id, &mut account, &op.tx.fee, old_nonce)?
+                .into_iter()
+                .collect();
+        let slot = account
+            .add_token(op.tx.nft(), op.tx.content_hash)
+            .ok_or_else(|| account_full_error(op.account_id))?;
+        account.nonce += 1;
+        let new_nonce = account.nonce;
+
+        self.insert_account(op.account_id, account);
+        self.mint_token(op.tx.nft());
+
+        updates.push((
+            op.account_id,
+            AccountUpdate::AddToken {
+                token: op.tx.nft(),
+                content_hash: op.tx.content_hash,
+                slot,
+                old_nonce,
+                new_nonce,
+            },
+        ));
+
+        let fee = CollectedFee {
+            amount: op.tx.fee.clone(),
+        };
+
+        Ok((fee, updates))
+    }
+
+    pub fn apply_burn_op(&mut self, op: &BurnOp) -> Result<(CollectedFee, AccountUpdates), Error> {
+        let mut account = self.get_account(op.account_id).unwrap();
+
+        let old_nonce = account.nonce;
+        ensure!(op.tx.nonce == old_nonce, "Nonce mismatch");
+        let mut updates: AccountUpdates =
+            charge_fee(op.account_id, &mut account, &op.tx.fee, old_nonce)?
+                .into_iter()
+                .chain(revoke_approval(
+                    op.account_id,
+                    &mut account,
+                    op.tx.nft(),
+                    old_nonce,
+                ))
+                .collect();
+        let (slot, content_hash) = account
+            .remove_token(op.tx.nft())
+            .ok_or_else(|| format_err!("Not current owner"))?;
+        account.nonce += 1;
+        let new_nonce = account.nonce;
+
+        self.insert_account(op.account_id, account);
+        self.burn_token(op.tx.nft());
+
+        updates.push((
+            op.account_id,
+            AccountUpdate::RemoveToken {
+                token: op.tx.nft(),
+                content_hash,
+                slot,
+                old_nonce,
+                new_nonce,
+            },
+        ));
+
+        let fee = CollectedFee {
+            amount: op.tx.fee.clone(),
+        };
+
+        Ok((fee, updates))
+    }
+
+    pub fn apply_approve_op(
+        &mut self,
+        op: &ApproveOp,
+    ) -> Result<(CollectedFee, AccountUpdates), Error> {
+        ensure!(op.operator_id != Some(op.account_id), "Approve to self");
+        // zero in the token leaf means that the token has no operator
+        ensure!(op.operator_id != Some(0), "Approve to the fee account");
+        let mut account = self.get_account(op.account_id).unwrap();
+
+        let old_nonce = account.nonce;
+        ensure!(op.tx.nonce == old_nonce, "Nonce mismatch");
+        let slot = account
+            .token_slot(op.tx.nft())
+            .ok_or_else(|| format_err!("Not current owner"))?;
+        let mut updates: AccountUpdates =
+            charge_fee(op.account_id, &mut account, &op.tx.fee, old_nonce)?
+                .into_iter()
+                .collect();
+        let old_operator = account.set_approved(slot, op.operator_id);
+        account.nonce += 1;
+        let new_nonce = account.nonce;
+
+        self.insert_account(op.account_id, account);
+
+        updates.push((
+            op.account_id,
+            AccountUpdate::ChangeApproval {
+                token: op.tx.nft(),
+                slot,
+                old_operator,
+                new_operator: op.operator_id,
+                old_nonce,
+                new_nonce,
+            },
+        ));
+
+        let fee = CollectedFee {
+            amount: op.tx.fee.clone(),
+        };
+
+        Ok((fee, updates))
+    }
+
+    pub fn apply_approve_for_all_op(
+        &mut self,
+        op: &ApproveForAllOp,
+    ) -> Result<(CollectedFee, AccountUpdates), Error> {
+        ensure!(op.operator_id != op.account_id, "Approve to self");
+        let mut account = self.get_account(op.account_id).unwrap();
+
+        let old_nonce = account.nonce;
+        ensure!(op.tx.nonce == old_nonce, "Nonce mismatch");
+        let mut updates: AccountUpdates =
+            charge_fee(op.account_id, &mut account, &op.tx.fee, old_nonce)?
+                .into_iter()
+                .collect();
+        let old_approved = account.set_operator(op.operator_id, op.tx.approved);
+        account.nonce += 1;
+        let new_nonce = account.nonce;
+
+        self.insert_account(op.account_id, account);
+
+        updates.push((
+            op.account_id,
+            AccountUpdate::ChangeApprovalForAll {
+                operator: op.operator_id,
+                old_approved,
+                new_approved: op.tx.approved,
+                old_nonce,
+                new_nonce,
+            },
+        ));
 
-        // TODO ADE: remove fees
         let fee = CollectedFee {
-            token: op.tx.token_id,
             amount: op.tx.fee.clone(),
         };
 
@@ -618,19 +1645,389 @@ impl PlasmaState {
         let old_nonce = account.nonce;
 
         ensure!(op.tx.nonce == old_nonce, "Nonce mismatch");
-        ensure!(account.has_token(op.tx.token_id), "Not current owner");
-
+        ensure!(account.has_token(op.tx.nft()), "Not current owner");
+
+        let mut updates: AccountUpdates = self
+            .charge_sponsored_fee(
+                op.tx.sponsor.as_ref(),
+                op.from,
+                &mut account,
+                &op.tx.fee,
+                old_nonce,
+            )?
+            .into_iter()
+            .collect();
         account.nonce += 1;
 
-        let new_nonce = account.nonce;
-
         self.insert_account(op.from, account);
+        updates.extend(self.charge_sponsor(op.tx.sponsor.as_ref(), &op.tx.fee));
 
         let fee = CollectedFee {
-            token: op.tx.token_id,
             amount: op.tx.fee.clone(),
         };
 
-        Ok((fee, Vec::new()))
+        Ok((fee, updates))
+    }
+}
+
+/// Error of an operation that would put a token into an account without free token slots.
+fn account_full_error(account_id: AccountId) -> Error {
+    format_err!(
+        "Account {} is full, it can hold at most {} tokens",
+        account_id,
+        params::total_tokens()
+    )
+}
+
+/// Charges the tx fee from the ETH balance of the account.
+/// Like `revoke_approval`, the change is recorded with the nonce the tx starts from.
+fn charge_fee(
+    account_id: AccountId,
+    account: &mut Account,
+    fee: &BigDecimal,
+    nonce: Nonce,
+) -> Result<Option<(AccountId, AccountUpdate)>, Error> {
+    if *fee == BigDecimal::from(0) {
+        return Ok(None);
+    }
+    ensure!(account.balance >= *fee, "Not enough balance to pay the fee");
+
+    let old_balance = account.balance.clone();
+    account.balance -= fee;
+    Ok(Some((
+        account_id,
+        AccountUpdate::UpdateBalance {
+            old_balance,
+            new_balance: account.balance.clone(),
+            old_nonce: nonce,
+            new_nonce: nonce,
+        },
+    )))
+}
+
+/// Revokes the approval of the token that is about to leave the account.
+/// The change is recorded before the token removal (with the nonce the removal starts from),
+/// so that reverting the updates restores the approval together with the token.
+fn revoke_approval(
+    account_id: AccountId,
+    account: &mut Account,
+    token: Nft,
+    nonce: Nonce,
+) -> Option<(AccountId, AccountUpdate)> {
+    let slot = account.token_slot(token)?;
+    let old_operator = account.set_approved(slot, None)?;
+    Some((
+        account_id,
+        AccountUpdate::ChangeApproval {
+            token,
+            slot,
+            old_operator: Some(old_operator),
+            new_operator: None,
+            old_nonce: nonce,
+            new_nonce: nonce,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_exports::franklin_crypto::bellman::pairing::ff::PrimeField;
+    use models::node::{priv_key_from_fs, Fs};
+
+    fn deposit(to: Address, token_id: u32) -> FranklinPriorityOp {
+        FranklinPriorityOp::Deposit(Deposit {
+            from: Address::from_low_u64_be(0xc0),
+            collection_id: 1,
+            token_id: token_id.into(),
+            to,
+            content_hash: Default::default(),
+        })
+    }
+
+    fn close_op(state: &PlasmaState, account_id: AccountId) -> CloseOp {
+        let account = state.get_account(account_id).unwrap();
+        CloseOp {
+            tx: Close {
+                account: account.address,
+                nonce: account.nonce,
+                signature: Default::default(),
+            },
+            account_id,
+        }
+    }
+
+    fn insert_empty_account(state: &mut PlasmaState, address: Address) -> AccountId {
+        let account_id = state.get_free_account_id();
+        state.insert_account(account_id, Account::default_with_address(&address));
+        account_id
+    }
+
+    #[test]
+    fn close_account() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let mut state = PlasmaState::empty();
+        state.execute_priority_op(deposit(alice, 1));
+        let (alice_id, _) = state.get_account_by_address(&alice).unwrap();
+        let bob_id = insert_empty_account(&mut state, bob);
+
+        // Accounts holding tokens can not be closed.
+        assert!(state.apply_close_op(&close_op(&state, alice_id)).is_err());
+
+        // Neither can the operators of other accounts, by a token approval or by an approval
+        // for all tokens.
+        let mut account = state.get_account(alice_id).unwrap();
+        let slot = account.token_slot(Nft::new(1, 1.into())).unwrap();
+        account.set_approved(slot, Some(bob_id));
+        state.insert_account(alice_id, account.clone());
+        assert!(state.apply_close_op(&close_op(&state, bob_id)).is_err());
+        account.set_approved(slot, None);
+        account.set_operator(bob_id, true);
+        state.insert_account(alice_id, account.clone());
+        assert!(state.apply_close_op(&close_op(&state, bob_id)).is_err());
+        account.set_operator(bob_id, false);
+        state.insert_account(alice_id, account);
+
+        let (_, updates) = state.apply_close_op(&close_op(&state, bob_id)).unwrap();
+        assert!(
+            matches!(updates.as_slice(), [(id, AccountUpdate::Delete { .. })] if *id == bob_id)
+        );
+        assert!(state.get_account(bob_id).is_none());
+        assert!(state.get_account_by_address(&bob).is_none());
+    }
+
+    #[test]
+    fn reuse_closed_account_id() {
+        let mut state = PlasmaState::empty();
+        let ids = (1..=3)
+            .map(|i| insert_empty_account(&mut state, Address::from_low_u64_be(i)))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        // The lowest id of the closed accounts is given to the next new account.
+        state.apply_close_op(&close_op(&state, 2)).unwrap();
+        state.apply_close_op(&close_op(&state, 1)).unwrap();
+        let carol = Address::from_low_u64_be(4);
+        state.execute_priority_op(deposit(carol, 1));
+        assert_eq!(state.get_account_by_address(&carol).unwrap().0, 1);
+        assert_eq!(state.get_free_account_id(), 2);
+        assert_eq!(
+            insert_empty_account(&mut state, Address::from_low_u64_be(5)),
+            2
+        );
+        assert_eq!(state.get_free_account_id(), 3);
+
+        // Free ids are restored from the account tree.
+        state.apply_close_op(&close_op(&state, 0)).unwrap();
+        let restored = PlasmaState::new(
+            state.token_tree.clone(),
+            state.account_id_by_address.clone(),
+            HashSet::new(),
+            HashSet::new(),
+            0,
+        );
+        assert_eq!(restored.get_free_account_id(), 0);
+    }
+
+    #[test]
+    fn deposit_to_full_account_fails() {
+        let alice = Address::from_low_u64_be(1);
+        let mut state = PlasmaState::empty();
+        state.execute_priority_op(deposit(alice, 0));
+        let (alice_id, mut account) = state.get_account_by_address(&alice).unwrap();
+        for token_id in 1..params::total_tokens() as u32 {
+            account.insert_token(token_id, Nft::new(1, token_id.into()), Default::default());
+        }
+        state.insert_account(alice_id, account.clone());
+
+        // The deposit is executed as failed, the token is refunded by the contract.
+        let OpSuccess {
+            updates,
+            executed_op,
+            ..
+        } = state.execute_priority_op(deposit(alice, params::total_tokens() as u32));
+        assert!(updates.is_empty());
+        match executed_op {
+            FranklinOp::Deposit(op) => assert!(!op.success),
+            op => panic!("Unexpected op {:?}", op),
+        }
+        assert!(executed_op.withdrawal_data().is_some());
+        assert_eq!(
+            state.get_account(alice_id).unwrap().get_tokens().len(),
+            params::total_tokens()
+        );
+    }
+
+    #[test]
+    fn deposit_of_token_in_use_fails() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let mut state = PlasmaState::empty();
+        state.execute_priority_op(deposit(alice, 1));
+
+        let OpSuccess {
+            updates,
+            executed_op,
+            ..
+        } = state.execute_priority_op(deposit(bob, 1));
+        assert!(updates.is_empty());
+        assert!(executed_op.withdrawal_data().is_some());
+        assert!(state.get_account_by_address(&bob).is_none());
+        let (alice_id, _) = state.get_account_by_address(&alice).unwrap();
+        assert_eq!(
+            state.token_owners.get(&Nft::new(1, 1.into())),
+            Some(&alice_id)
+        );
+    }
+
+    #[test]
+    fn mint_checks_minter_and_token_id() {
+        let alice = Address::from_low_u64_be(1);
+        let key = priv_key_from_fs(Fs::from_str("1").unwrap());
+        let mut state = PlasmaState::empty();
+        let alice_id = insert_empty_account(&mut state, alice);
+        let mut account = state.get_account(alice_id).unwrap();
+        account.pub_key_hash = PubKeyHash::from_privkey(&key);
+        state.insert_account(alice_id, account);
+        let mint = |nonce: Nonce| {
+            let tx = Mint::new_signed(
+                alice_id,
+                alice,
+                1,
+                1.into(),
+                Default::default(),
+                BigDecimal::from(0),
+                nonce,
+                &key,
+            )
+            .unwrap();
+            FranklinTx::Mint(Box::new(tx))
+        };
+
+        // The minter is authorized once the governance contract allows it.
+        assert!(state.execute_tx(mint(0)).is_err());
+        state.minters.insert((1, alice));
+        state
+            .execute_tx(mint(0))
+            .expect("Mint of the authorized minter failed");
+        assert!(state.is_token_in_use(Nft::new(1, 1.into())));
+
+        // The token id can not be minted again once the token leaves the rollup.
+        let mut account = state.get_account(alice_id).unwrap();
+        account.remove_token(Nft::new(1, 1.into()));
+        state.insert_account(alice_id, account);
+        assert!(state.execute_tx(mint(1)).is_err());
+    }
+
+    /// Checks that the sponsor balance is debited by every sponsored tx of the block,
+    /// so the sponsor can not pay more fees than its balance.
+    #[test]
+    fn sponsor_is_charged_by_each_tx() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let carol = Address::from_low_u64_be(3);
+        let mut state = PlasmaState::empty();
+        state.execute_priority_op(deposit(alice, 1));
+        state.execute_priority_op(deposit(alice, 2));
+        let (alice_id, _) = state.get_account_by_address(&alice).unwrap();
+        let bob_id = insert_empty_account(&mut state, bob);
+        state.execute_priority_op(FranklinPriorityOp::DepositEth(DepositEth {
+            from: carol,
+            amount: BigDecimal::from(15),
+            to: carol,
+        }));
+        let (carol_id, _) = state.get_account_by_address(&carol).unwrap();
+
+        let transfer_op = |token_id: u32, nonce: Nonce| {
+            let mut tx = Transfer::new(
+                alice_id,
+                alice,
+                bob,
+                1,
+                token_id.into(),
+                BigDecimal::from(10),
+                nonce,
+                None,
+                None,
+                None,
+            );
+            tx.sponsor = Some(Sponsorship {
+                account_id: carol_id,
+                signature: Default::default(),
+            });
+            TransferOp {
+                tx,
+                from: alice_id,
+                to: bob_id,
+            }
+        };
+
+        let (_, updates) = state.apply_transfer_op(&transfer_op(1, 0)).unwrap();
+        assert!(matches!(
+            updates.last(),
+            Some((id, AccountUpdate::UpdateBalance { .. })) if *id == carol_id
+        ));
+        assert_eq!(
+            state.get_account(carol_id).unwrap().balance,
+            BigDecimal::from(5)
+        );
+
+        assert!(state.apply_transfer_op(&transfer_op(2, 1)).is_err());
+        assert_eq!(
+            state.get_account(carol_id).unwrap().balance,
+            BigDecimal::from(5)
+        );
+        assert!(state
+            .get_account(alice_id)
+            .unwrap()
+            .has_token(Nft::new(1, 2.into())));
+    }
+
+    #[test]
+    fn execute_and_revert() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let mut state = PlasmaState::empty();
+        state.execute_priority_op(deposit(alice, 1));
+        let root_hash = state.root_hash();
+
+        let updates = state.execute_and_revert(|state| {
+            let mut updates = state.execute_priority_op(deposit(alice, 2)).updates;
+            updates.extend(state.execute_priority_op(deposit(bob, 3)).updates);
+            assert!(state.is_token_in_use(Nft::new(1, 3.into())));
+            updates
+        });
+
+        assert!(!updates.is_empty());
+        assert_eq!(state.root_hash(), root_hash);
+        assert!(state.get_account_by_address(&bob).is_none());
+        let (_, account) = state.get_account_by_address(&alice).unwrap();
+        assert_eq!(account.get_tokens(), vec![Nft::new(1, 1.into())]);
+        assert!(state.is_token_in_use(Nft::new(1, 1.into())));
+        assert!(!state.is_token_in_use(Nft::new(1, 2.into())));
+        assert!(!state.is_token_in_use(Nft::new(1, 3.into())));
+    }
+
+    #[test]
+    fn token_owners_follow_accounts() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let token = Nft::new(1, 1.into());
+        let mut state = PlasmaState::empty();
+        state.execute_priority_op(deposit(alice, 1));
+        let (alice_id, mut alice_account) = state.get_account_by_address(&alice).unwrap();
+        let bob_id = insert_empty_account(&mut state, bob);
+
+        // The receiver is updated before the sender, the token stays indexed to the receiver.
+        let mut bob_account = state.get_account(bob_id).unwrap();
+        bob_account.add_token(token, Default::default());
+        state.insert_account(bob_id, bob_account);
+        alice_account.remove_token(token);
+        state.insert_account(alice_id, alice_account);
+        assert_eq!(state.token_owners.get(&token), Some(&bob_id));
+
+        state.remove_account(bob_id);
+        assert!(!state.is_token_in_use(token));
     }
 }
@@ -1,21 +1,22 @@
 //! Generate exit proof for exodus mode given account and token
 
-use bigdecimal::BigDecimal;
 use circuit::exit_circuit::create_exit_circuit_with_public_input;
-use failure::format_err;
+use failure::{ensure, format_err};
 use log::info;
 use models::circuit::account::CircuitAccount;
 use models::circuit::CircuitAccountTree;
-use models::node::{AccountId, AccountMap, Address, TokenId};
+use models::node::{AccountId, AccountMap, Address, Nft};
 use models::prover_utils::{gen_verified_proof_for_exit_circuit, EncodedProofPlonk};
 use std::time::Instant;
 
+/// Creates a proof that account `account_id` owned by `owner` holds `token` in the given state.
 pub fn create_exit_proof(
     accounts: AccountMap,
     account_id: AccountId,
     owner: Address,
-) -> Result<(EncodedProofPlonk, BigDecimal), failure::Error> {
-    /*let timer = Instant::now();
+    token: Nft,
+) -> Result<EncodedProofPlonk, failure::Error> {
+    let timer = Instant::now();
     let mut circuit_account_tree = CircuitAccountTree::new(models::params::account_tree_depth());
 
     let mut target_account = None;
@@ -26,23 +27,33 @@ pub fn create_exit_proof(
         circuit_account_tree.insert(id, CircuitAccount::from(account));
     }
 
-    let balance = target_account
-        .map(|acc| acc.get_balance(token_id))
-        .ok_or_else(|| {
-            format_err!(
-                "Fund account not found: id: {}, address: 0x{:x}",
-                account_id,
-                owner
-            )
-        })?;
+    let target_account = target_account.ok_or_else(|| {
+        format_err!(
+            "Fund account not found: id: {}, address: 0x{:x}",
+            account_id,
+            owner
+        )
+    })?;
+    ensure!(
+        target_account.address == owner,
+        "Account {} is not owned by 0x{:x}",
+        account_id,
+        owner
+    );
+    ensure!(
+        target_account.token_slot(token).is_some(),
+        "Account {} does not hold token {} of collection {}",
+        account_id,
+        token.token_id,
+        token.collection_id
+    );
 
     let zksync_exit_circuit =
-        create_exit_circuit_with_public_input(&mut circuit_account_tree, account_id, token_id);
+        create_exit_circuit_with_public_input(&mut circuit_account_tree, account_id, &token);
 
     let proof = gen_verified_proof_for_exit_circuit(zksync_exit_circuit)
         .map_err(|e| format_err!("Failed to generate proof: {}", e))?;
 
     info!("Exit proof created: {} s", timer.elapsed().as_secs());
-    Ok((proof, balance))*/
-    unimplemented!()
+    Ok(proof)
 }
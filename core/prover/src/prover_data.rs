@@ -2,7 +2,7 @@
 // External
 use serde::{Deserialize, Serialize};
 // Workspace
-use circuit::account::AccountWitness;
+use circuit::account::{AccountWitness, TokenWitness};
 use circuit::circuit::FranklinCircuit;
 use circuit::operation::{
     OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
@@ -25,8 +25,8 @@ pub struct ProverData {
     pub new_root: Fr,
     #[serde(with = "FrSerde")]
     pub validator_address: Fr,
-    #[serde(with = "VecOptionalFrSerde")]
-    pub validator_balances: Vec<Option<Fr>>,
+    #[serde(with = "OptionalFrSerde")]
+    pub validator_token_root: Option<Fr>,
     #[serde(with = "VecOptionalFrSerde")]
     pub validator_audit_path: Vec<Option<Fr>>,
     #[serde(with = "VecOperationsSerde")]
@@ -45,7 +45,7 @@ impl ProverData {
             validator_address: Some(self.validator_address),
             pub_data_commitment: Some(self.public_data_commitment),
             operations: self.operations,
-            validator_balances: self.validator_balances,
+            validator_token_root: self.validator_token_root,
             validator_audit_path: self.validator_audit_path,
             validator_account: self.validator_account,
         }
@@ -61,6 +61,27 @@ struct AccountWitnessDef {
     pub pub_key_hash: Option<Fr>,
     #[serde(with = "OptionalFrSerde")]
     pub address: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub balance: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub operators_root: Option<Fr>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "circuit::account::TokenWitness::<Engine>")]
+struct TokenWitnessDef {
+    #[serde(with = "OptionalFrSerde")]
+    pub collection_id: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub id_hi: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub id_lo: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub content_hash_hi: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub content_hash_lo: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub approved: Option<Fr>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -100,10 +121,34 @@ pub struct OperationArgumentsDef {
     //pub b: Option<Fr>,
     //#[serde(with = "OptionalFrSerde")]
     //pub amount_packed: Option<Fr>,
-    //#[serde(with = "OptionalFrSerde")]
-    //pub full_amount: Option<Fr>,
     #[serde(with = "OptionalFrSerde")]
-    pub token_id: Option<Fr>,
+    pub full_amount: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub collection_id: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub token_id_hi: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub token_id_lo: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub content_hash_hi: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub content_hash_lo: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub operator_pub_key_hash: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub operator_id: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub is_operator: Option<Fr>,
+    #[serde(with = "VecOptionalFrSerde")]
+    pub operator_path: Vec<Option<Fr>>,
+    #[serde(with = "OptionalFrSerde")]
+    pub approved: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub valid_from: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub valid_until: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub fee_payer: Option<Fr>,
     #[serde(with = "OptionalFrSerde")]
     pub fee: Option<Fr>,
     #[serde(with = "OptionalFrSerde")]
@@ -112,6 +157,30 @@ pub struct OperationArgumentsDef {
     pub eth_address: Option<Fr>,
     #[serde(with = "OptionalFrSerde")]
     pub pub_nonce: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub tokens_count: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub tokens_root: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub token_index: Option<Fr>,
+    #[serde(with = "VecOptionalFrSerde")]
+    pub token_list_path: Vec<Option<Fr>>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_collection_id: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_token_id_hi: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_token_id_lo: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_content_hash_hi: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_content_hash_lo: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_fee: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_tokens_count: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub second_tokens_root: Option<Fr>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -121,6 +190,8 @@ pub struct OperationBranchDef {
     pub address: Option<Fr>,
     //#[serde(with = "OptionalFrSerde")]
     //pub token: Option<Fr>,
+    #[serde(with = "OptionalFrSerde")]
+    pub token_slot: Option<Fr>,
     #[serde(with = "OperationBranchWitnessDef")]
     pub witness: OperationBranchWitness<Engine>,
 }
@@ -134,6 +205,8 @@ pub struct OperationBranchWitnessDef {
     pub account_path: Vec<Option<Fr>>,
     //#[serde(with = "OptionalFrSerde")]
     //pub balance_value: Option<Fr>,
+    #[serde(with = "TokenWitnessDef")]
+    pub token_witness: TokenWitness<Engine>,
     #[serde(with = "VecOptionalFrSerde")]
     pub token_subtree_path: Vec<Option<Fr>>,
 }
@@ -134,8 +134,10 @@ fn new_test_data_for_prover() -> ProverData {
             token: 0,
             amount: BigDecimal::from(1),
             to: empty_account_address,
+            content_hash: Default::default(),
         },
         account_id: empty_account_id,
+        success: true,
     };
 
     let deposit_witness = DepositWitness::apply_tx(&mut witness_accum.account_tree, &deposit_op);
@@ -157,7 +159,7 @@ fn new_test_data_for_prover() -> ProverData {
         validator_address: Fr::from_str(&witness_accum.fee_account_id.to_string())
             .expect("failed to parse"),
         operations: witness_accum.operations,
-        validator_balances: witness_accum.fee_account_balances.unwrap(),
+        validator_token_root: witness_accum.fee_account_token_root,
         validator_audit_path: witness_accum.fee_account_audit_path.unwrap(),
         validator_account: witness_accum.fee_account_witness.unwrap(),
     }
@@ -13,6 +13,7 @@ use storage::ConnectionPool;
 use crate::{
     eth_watch::EthWatchRequest,
     mempool::MempoolRequest,
+    order_book::OrderBookRequest,
     signature_checker,
     state_keeper::{ExecutedOpsNotify, StateKeeperRequest},
 };
@@ -28,6 +29,7 @@ pub fn start_api_server(
     connection_pool: ConnectionPool,
     panic_notify: mpsc::Sender<bool>,
     mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    order_book_request_sender: mpsc::Sender<OrderBookRequest>,
     executed_tx_receiver: mpsc::Receiver<ExecutedOpsNotify>,
     state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
     eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
@@ -55,6 +57,7 @@ pub fn start_api_server(
         op_notify_receiver,
         connection_pool.clone(),
         mempool_request_sender.clone(),
+        order_book_request_sender.clone(),
         executed_tx_receiver,
         state_keeper_request_sender.clone(),
         sign_check_sender.clone(),
@@ -67,6 +70,7 @@ pub fn start_api_server(
         config_options,
         connection_pool,
         mempool_request_sender,
+        order_book_request_sender,
         state_keeper_request_sender,
         sign_check_sender,
         eth_watcher_request_sender,
@@ -8,9 +8,15 @@ use actix_web::{
 };
 use futures::channel::mpsc;
 use models::config_options::ThreadPanicNotify;
-use models::node::{Account, AccountId, Address, ExecutedOperations, FranklinPriorityOp};
+use models::node::{
+    Account, AccountId, Address, CollectionId, ContentHash, ExecutedOperations, FranklinPriorityOp,
+    Nft, Token, TokenId, TokenLike,
+};
+use models::primitives::big_decimal_to_u256;
 use models::NetworkStatus;
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
+use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use storage::chain::block::records::BlockDetails;
@@ -19,7 +25,9 @@ use storage::{ConnectionPool, StorageProcessor};
 use tokio::{runtime::Runtime, time};
 use web3::types::H160;
 
-use super::rpc_server::get_ongoing_priority_ops;
+use super::rpc_server::{
+    get_exodus_mode, get_ongoing_priority_ops, TokenHistoryQuery, TokenHistoryResp,
+};
 use crate::eth_watch::EthWatchRequest;
 use storage::chain::operations_ext::records::TransactionsHistoryItem;
 
@@ -68,6 +76,20 @@ fn try_parse_hash(query: &str) -> Option<Vec<u8>> {
     }
 }
 
+/// Parses the token id of the form `{collection_id}:{token_id}`,
+/// the ERC-721 token id is either decimal or `0x` prefixed hex.
+fn try_parse_nft(query: &str) -> Option<Nft> {
+    let mut parts = query.splitn(2, ':');
+    let collection_id = parts.next()?.parse().ok()?;
+    let token_id = parts.next()?;
+    let token_id = if token_id.starts_with("0x") {
+        TokenId::from_str(&token_id[2..]).ok()?
+    } else {
+        TokenId::from_dec_str(token_id).ok()?
+    };
+    Some(Nft::new(collection_id, token_id))
+}
+
 /// Caches used by REST API server.
 #[derive(Debug, Clone)]
 struct Caches {
@@ -146,6 +168,9 @@ impl AppState {
                             .block_schema()
                             .get_last_verified_block()
                             .unwrap_or(0);
+                        let exodus_mode = get_exodus_mode(&state.eth_watcher_request_sender)
+                            .await
+                            .unwrap_or_else(|_| state.network_status.read().exodus_mode);
                         let status = NetworkStatus {
                             next_block_at_max: None,
                             last_committed: storage
@@ -164,6 +189,7 @@ impl AppState {
                                 .stats_schema()
                                 .count_outstanding_proofs(last_verified)
                                 .unwrap_or(0),
+                            exodus_mode,
                         };
 
                         // save status to state
@@ -407,9 +433,311 @@ fn handle_get_account_state(
     Ok(HttpResponse::Ok().json(res))
 }
 
-// TODO ADE must be removed
-fn handle_get_tokens(data: web::Data<AppState>) -> ActixResult<HttpResponse> {
-    Ok(HttpResponse::Ok().json(Vec::<u16>::new()))
+/// Default and max amount of the tokens in a page of the account holdings.
+const HOLDINGS_DEFAULT_LIMIT: u32 = 20;
+const HOLDINGS_MAX_LIMIT: u32 = 100;
+
+#[derive(Debug, Deserialize)]
+struct HoldingsQuery {
+    /// Last token of the previous page, `{collection_id}:{token_id}`
+    after: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokensQuery {
+    /// Lists the tokens held by the account instead of the collections
+    owner: Option<String>,
+    after: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HeldToken {
+    collection_id: CollectionId,
+    token_id: TokenId,
+    content_hash: ContentHash,
+    /// Held by the account after the last committed block
+    committed: bool,
+    /// Held by the account after the last verified block
+    verified: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HoldingsResponse {
+    tokens: Vec<HeldToken>,
+    /// Cursor to pass as `after` for the next page, `None` for the last page
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenResponse {
+    collection_id: CollectionId,
+    token_id: TokenId,
+    collection: Token,
+    /// Owner after the last committed block, `None` if the token is not in the rollup
+    owner: Option<Address>,
+    /// Owner after the last verified block
+    verified_owner: Option<Address>,
+    /// Whether the committed owner is already verified
+    verified: bool,
+    content_hash: Option<ContentHash>,
+    burned: bool,
+}
+
+/// Page of the tokens held by the account, ordered by the collection and the token id.
+fn account_holdings(
+    data: &AppState,
+    address: Address,
+    after: Option<&str>,
+    limit: Option<u32>,
+) -> ActixResult<HttpResponse> {
+    let after = match after {
+        Some(after) => {
+            Some(try_parse_nft(after).ok_or_else(|| HttpResponse::BadRequest().finish())?)
+        }
+        None => None,
+    };
+    let limit = limit.unwrap_or(HOLDINGS_DEFAULT_LIMIT);
+    if limit == 0 || limit > HOLDINGS_MAX_LIMIT {
+        return Err(HttpResponse::BadRequest().finish().into());
+    }
+
+    let storage = data.access_storage()?;
+
+    // The verified holdings are paginated by the `tokens` table, while the committed
+    // ones are taken from the committed state of the account.
+    let verified = storage
+        .colexi_queries()
+        .get_account_tokens(&address, after.as_ref(), i64::from(limit))
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: ({}, {:?}, {})",
+                file!(),
+                line!(),
+                column!(),
+                err,
+                address,
+                after,
+                limit,
+            );
+            HttpResponse::InternalServerError().finish()
+        })?;
+    let committed = storage
+        .chain()
+        .account_schema()
+        .account_state_by_address(&address)
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: {}",
+                file!(),
+                line!(),
+                column!(),
+                err,
+                address,
+            );
+            HttpResponse::InternalServerError().finish()
+        })?
+        .committed;
+
+    let mut holdings = BTreeMap::new();
+    for token in verified {
+        let token_id =
+            big_decimal_to_u256(&token.token_id).expect("token id is stored as an uint256");
+        let collection_id = token.collection_id as CollectionId;
+        holdings.insert(
+            Nft::new(collection_id, token_id),
+            HeldToken {
+                collection_id,
+                token_id,
+                content_hash: ContentHash::from_slice(&token.content_hash),
+                committed: false,
+                verified: true,
+            },
+        );
+    }
+    if let Some((_, account)) = committed {
+        for (_, token, content_hash) in account.get_token_slots() {
+            if after.map_or(false, |after| token <= after) {
+                continue;
+            }
+            holdings
+                .entry(token)
+                .or_insert(HeldToken {
+                    collection_id: token.collection_id,
+                    token_id: token.token_id,
+                    content_hash,
+                    committed: true,
+                    verified: false,
+                })
+                .committed = true;
+        }
+    }
+
+    let tokens: Vec<_> = holdings
+        .into_iter()
+        .map(|(_, held)| held)
+        .take(limit as usize)
+        .collect();
+    // a full page may be followed by more tokens, otherwise it is the last one
+    let next_cursor = if tokens.len() == limit as usize {
+        tokens
+            .last()
+            .map(|held| format!("{}:{}", held.collection_id, held.token_id))
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(HoldingsResponse {
+        tokens,
+        next_cursor,
+    }))
+}
+
+fn handle_get_account_tokens(
+    data: web::Data<AppState>,
+    account_address: web::Path<String>,
+    query: web::Query<HoldingsQuery>,
+) -> ActixResult<HttpResponse> {
+    let account_address =
+        try_parse_address(&account_address).ok_or_else(|| HttpResponse::BadRequest().finish())?;
+
+    account_holdings(
+        &data,
+        account_address,
+        query.after.as_ref().map(String::as_str),
+        query.limit,
+    )
+}
+
+fn handle_get_tokens(
+    data: web::Data<AppState>,
+    query: web::Query<TokensQuery>,
+) -> ActixResult<HttpResponse> {
+    if let Some(owner) = &query.owner {
+        let owner = try_parse_address(owner).ok_or_else(|| HttpResponse::BadRequest().finish())?;
+        return account_holdings(
+            &data,
+            owner,
+            query.after.as_ref().map(String::as_str),
+            query.limit,
+        );
+    }
+
+    let storage = data.access_storage()?;
+    let tokens = storage.tokens_schema().load_tokens().map_err(|err| {
+        log::warn!(
+            "[{}:{}:{}] Internal Server Error: '{}'; input: N/A",
+            file!(),
+            line!(),
+            column!(),
+            err
+        );
+        HttpResponse::InternalServerError().finish()
+    })?;
+
+    let mut vec_tokens = tokens.values().cloned().collect::<Vec<_>>();
+    vec_tokens.sort_by_key(|t| t.id);
+
+    Ok(HttpResponse::Ok().json(vec_tokens))
+}
+
+fn handle_get_token(
+    data: web::Data<AppState>,
+    token_id: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let token = try_parse_nft(&token_id).ok_or_else(|| HttpResponse::BadRequest().finish())?;
+    let storage = data.access_storage()?;
+    let internal_error = |err: diesel::result::Error| {
+        log::warn!(
+            "[{}:{}:{}] Internal Server Error: '{}'; input: {:?}",
+            file!(),
+            line!(),
+            column!(),
+            err,
+            token,
+        );
+        HttpResponse::InternalServerError().finish()
+    };
+
+    let collection = storage
+        .tokens_schema()
+        .get_token(TokenLike::Id(token.collection_id))
+        .map_err(internal_error)?
+        .ok_or_else(|| HttpResponse::NotFound().finish())?;
+    let owner = storage
+        .colexi_queries()
+        .get_committed_owner(&token)
+        .map_err(internal_error)?
+        .map(|account| Address::from_slice(&account.address));
+    let verified_owner = storage
+        .colexi_queries()
+        .get_current_owner(&token)
+        .map_err(internal_error)?
+        .map(|account| Address::from_slice(&account.address));
+    let mut content_hash = storage
+        .colexi_queries()
+        .get_content_hash(&token)
+        .map_err(internal_error)?
+        .map(|content| ContentHash::from_slice(&content.content_hash));
+    // the token deposited or minted in an unverified block is only in the committed state
+    if let (None, Some(owner)) = (content_hash, owner) {
+        content_hash = storage
+            .chain()
+            .account_schema()
+            .account_state_by_address(&owner)
+            .map_err(internal_error)?
+            .committed
+            .and_then(|(_, account)| account.content_hash(token));
+    }
+    let burned = storage
+        .colexi_queries()
+        .is_burned(&token)
+        .map_err(internal_error)?;
+
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        collection_id: token.collection_id,
+        token_id: token.token_id,
+        collection,
+        owner,
+        verified_owner,
+        verified: owner.is_some() && owner == verified_owner,
+        content_hash,
+        burned,
+    }))
+}
+
+fn handle_get_token_history(
+    data: web::Data<AppState>,
+    token_id: web::Path<String>,
+    query: web::Query<TokenHistoryQuery>,
+) -> ActixResult<HttpResponse> {
+    let token = try_parse_nft(&token_id).ok_or_else(|| HttpResponse::BadRequest().finish())?;
+    let storage_query = query
+        .storage_query()
+        .ok_or_else(|| HttpResponse::BadRequest().finish())?;
+    let storage = data.access_storage()?;
+
+    let page = storage
+        .colexi_queries()
+        .get_transfer_history(&token, &storage_query)
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: ({:?}, {:?})",
+                file!(),
+                line!(),
+                column!(),
+                err,
+                token,
+                query,
+            );
+            HttpResponse::InternalServerError().finish()
+        })?;
+
+    Ok(HttpResponse::Ok().json(TokenHistoryResp::from(page)))
 }
 
 fn handle_get_account_transactions_history(
@@ -475,6 +803,7 @@ fn handle_get_account_transactions_history(
                 "priority_op": {
                     "from": deposit.from,
                     "to": deposit.to,
+                    "collection_id": deposit.collection_id,
                     "token_id": deposit.token_id
                 },
                 "type": "Deposit"
@@ -730,7 +1059,16 @@ fn start_server(state: AppState, bind_to: SocketAddr) {
                         "/account/{address}",
                         web::get().to(handle_get_account_state),
                     )
+                    .route(
+                        "/accounts/{address}/tokens",
+                        web::get().to(handle_get_account_tokens),
+                    )
                     .route("/tokens", web::get().to(handle_get_tokens))
+                    .route("/tokens/{id}", web::get().to(handle_get_token))
+                    .route(
+                        "/tokens/{id}/history",
+                        web::get().to(handle_get_token_history),
+                    )
                     .route(
                         "/account/{address}/history/{offset}/{limit}",
                         web::get().to(handle_get_account_transactions_history),
@@ -14,24 +14,32 @@ use models::{
     node::{
         closest_packable_fee_amount,
         tx::{TxEthSignature, TxHash},
-        Account, AccountId, Address, FranklinPriorityOp, FranklinTx, Nonce, PriorityOp, PubKeyHash,
-        Token, TokenId, TokenLike,
+        Account, AccountId, Address, ApproveForAllOp, ApproveOp, BlockNumber, BurnOp, CollectionId,
+        ContentHash, FranklinPriorityOp, FranklinTx, MintOp, Nft, Nonce, Order, PriorityOp,
+        PubKeyHash, SwapOp, Token, TokenId, TransferOp, TransferToNewOp, WithdrawOp,
     },
-    primitives::{big_decimal_to_u128, floor_big_decimal, u128_to_bigdecimal},
+    params::{CHUNK_GAS, COMMIT_BLOCK_GAS, VERIFY_BLOCK_GAS, WITHDRAW_COMPLETION_GAS},
+    primitives::{big_decimal_to_u128, u128_to_bigdecimal, u256_to_bigdecimal},
+    ExodusMode,
 };
 use storage::{
     chain::{
         block::records::BlockDetails, operations::records::StoredExecutedPriorityOperation,
         operations_ext::records::TxReceiptResponse,
     },
+    colexi::{
+        records::{TransferHistoryPage, TransferOperation},
+        TransferHistoryQuery,
+    },
     ConnectionPool, StorageProcessor,
 };
 // Local uses
 use crate::{
     eth_watch::EthWatchRequest,
-    mempool::{MempoolRequest, TxAddError},
+    mempool::{MempoolRequest, SponsorQuota, TxAddError},
+    order_book::{OrderAddError, OrderBookRequest, OrdersFilter},
     signature_checker::{VerifiedTx, VerifyTxSignatureRequest},
-    state_keeper::StateKeeperRequest,
+    state_keeper::{StateKeeperRequest, TxSimulationResult},
     utils::shared_lru_cache::SharedLruCache,
 };
 
@@ -39,7 +47,11 @@ use crate::{
 #[serde(rename_all = "camelCase")]
 pub struct ResponseAccountState {
     //pub balances: HashMap<String, BigDecimal>,
-    pub tokens: BTreeSet<u16>,
+    pub tokens: BTreeSet<Nft>,
+    /// Content hashes of the account tokens, ordered the same way as `tokens`.
+    pub content_hashes: Vec<ContentHash>,
+    /// ETH balance in wei, fees of the account txs are paid from it.
+    pub balance: BigDecimal,
     pub nonce: Nonce,
     pub pub_key_hash: PubKeyHash,
 }
@@ -59,9 +71,15 @@ impl ResponseAccountState {
         for token in account.get_tokens() {
             tokens.insert(token);
         }
+        let content_hashes = tokens
+            .iter()
+            .filter_map(|&token| account.content_hash(token))
+            .collect();
 
         Ok(Self {
             tokens,
+            content_hashes,
+            balance: account.balance,
             nonce: account.nonce,
             pub_key_hash: account.pub_key_hash,
         })
@@ -71,7 +89,8 @@ impl ResponseAccountState {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DepositingToken {
-    token_id: u16,
+    collection_id: CollectionId,
+    token_id: TokenId,
     expected_accept_block: u64,
 }
 
@@ -91,6 +110,7 @@ impl DepositingAccountTokens {
 
             //tokens.insert(op.token_id);
             let mut depositing_token = DepositingToken::default();
+            depositing_token.collection_id = op.collection_id;
             depositing_token.token_id = op.token_id;
 
             // `depositing_token.expected_accept_block` should be the greatest block number among
@@ -147,22 +167,134 @@ pub struct ContractAddressResp {
     pub gov_contract: String,
 }
 
+/// L1 status of the token withdrawal.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WithdrawalStatus {
+    /// Block of the withdrawal is not verified on L1 yet
+    Pending,
+    /// Block is verified, the token waits for `completeWithdrawals` to be sent to the recipient
+    Verified,
+    /// Token is sent to the recipient on L1
+    Completed,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TransferHistory {
+    /// Type of the tx or of the priority operation, e.g. `Transfer` or `Deposit`
+    pub tx_type: String,
     pub from: Option<String>,
-    pub to: String,
+    /// `None` for the burn of the token
+    pub to: Option<String>,
     pub verified: bool,
     pub block_number: i64,
     pub date: String,
     pub tx_hash: String,
+    /// `None` unless the token is withdrawn to L1
+    pub withdrawal_status: Option<WithdrawalStatus>,
+}
+
+impl From<TransferOperation> for TransferHistory {
+    fn from(transfer: TransferOperation) -> Self {
+        let withdrawal_status = match transfer.kind.as_str() {
+            "Withdraw" | "ForcedWithdraw" => Some(if transfer.withdrawals_completed {
+                WithdrawalStatus::Completed
+            } else if transfer.verify_confirmed {
+                WithdrawalStatus::Verified
+            } else {
+                WithdrawalStatus::Pending
+            }),
+            _ => None,
+        };
+        Self {
+            tx_type: transfer.kind,
+            from: transfer
+                .from
+                .map(|address| format!("0x{}", hex::encode(&address))),
+            to: transfer
+                .to
+                .map(|address| format!("0x{}", hex::encode(&address))),
+            verified: transfer.proof_block_number.is_some(),
+            block_number: transfer.block_number,
+            date: chrono::DateTime::<chrono::Utc>::from_utc(transfer.created_at, chrono::Utc)
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
+            tx_hash: format!("0x{}", hex::encode(&transfer.tx_hash)),
+            withdrawal_status,
+        }
+    }
+}
+
+/// Cursor and filters of the token history, all of them are optional.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TokenHistoryQuery {
+    /// Cursor returned with the previous page, the history starts from the beginning if `None`
+    pub after: Option<i64>,
+    /// Max amount of entries in the page
+    pub limit: Option<u32>,
+    pub from_block: Option<BlockNumber>,
+    pub to_block: Option<BlockNumber>,
+    /// Skip the entries of the blocks which are not verified yet
+    pub verified_only: bool,
+    /// Only the entries sent from or to this address
+    pub counterparty: Option<Address>,
+}
+
+impl TokenHistoryQuery {
+    pub const DEFAULT_LIMIT: u32 = 20;
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// Storage query selecting the requested page, `None` if the limit is out of bounds.
+    pub fn storage_query(&self) -> Option<TransferHistoryQuery> {
+        let limit = self.limit.unwrap_or(Self::DEFAULT_LIMIT);
+        if limit == 0 || limit > Self::MAX_LIMIT {
+            return None;
+        }
+        Some(TransferHistoryQuery {
+            after: self.after,
+            limit: i64::from(limit),
+            from_block: self.from_block.map(i64::from),
+            to_block: self.to_block.map(i64::from),
+            verified_only: self.verified_only,
+            counterparty: self.counterparty,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenHistoryResp {
+    pub history: Vec<TransferHistory>,
+    /// Cursor of the next page, `None` if this page is the last one
+    pub next_cursor: Option<i64>,
+}
+
+impl From<TransferHistoryPage> for TokenHistoryResp {
+    fn from(page: TransferHistoryPage) -> Self {
+        Self {
+            history: page
+                .operations
+                .into_iter()
+                .map(TransferHistory::from)
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenInfoResp {
+    /// First page of the token history
     pub history: Vec<TransferHistory>,
+    /// Cursor to load the rest of the history with `token_history`, `None` if it is complete
+    pub history_next_cursor: Option<i64>,
     pub owner: Option<String>,
+    /// Content hash the token leaf commits to, `None` if the token is not in the rollup
+    pub content_hash: Option<String>,
+    pub burned: bool,
 }
 
 /// Flattened `PriorityOp` object representing a deposit operation.
@@ -171,14 +303,15 @@ pub struct TokenInfoResp {
 #[serde(rename_all = "camelCase")]
 pub struct OngoingDeposit {
     received_on_block: u64,
-    token_id: u16,
+    collection_id: CollectionId,
+    token_id: TokenId,
     eth_tx_hash: String,
 }
 
 impl OngoingDeposit {
     pub fn new(received_on_block: u64, priority_op: PriorityOp) -> Self {
-        let token_id = match priority_op.data {
-            FranklinPriorityOp::Deposit(deposit) => deposit.token_id,
+        let (collection_id, token_id) = match priority_op.data {
+            FranklinPriorityOp::Deposit(deposit) => (deposit.collection_id, deposit.token_id),
             other => {
                 panic!("Incorrect input for OngoingDeposit: {:?}", other);
             }
@@ -188,6 +321,7 @@ impl OngoingDeposit {
 
         Self {
             received_on_block,
+            collection_id,
             token_id,
             eth_tx_hash,
         }
@@ -224,36 +358,104 @@ pub struct OngoingDepositsResp {
 pub enum TxFeeTypes {
     Withdraw,
     Transfer,
+    TransferToNew,
+    Mint,
+    Burn,
+    Approve,
+    ApproveForAll,
+    /// Fee of one order of a swap, both matched orders pay it.
+    SwapOrder,
+}
+
+impl TxFeeTypes {
+    /// Number of block chunks taken by the operation of the tx.
+    fn chunks(&self) -> usize {
+        match self {
+            TxFeeTypes::Withdraw => WithdrawOp::CHUNKS,
+            TxFeeTypes::Transfer => TransferOp::CHUNKS,
+            TxFeeTypes::TransferToNew => TransferToNewOp::CHUNKS,
+            TxFeeTypes::Mint => MintOp::CHUNKS,
+            TxFeeTypes::Burn => BurnOp::CHUNKS,
+            TxFeeTypes::Approve => ApproveOp::CHUNKS,
+            TxFeeTypes::ApproveForAll => ApproveForAllOp::CHUNKS,
+            TxFeeTypes::SwapOrder => SwapOp::CHUNKS / 2,
+        }
+    }
+
+    /// L1 gas the operator spends on the tx, the commit and verify cost of the block
+    /// is shared between the chunks of the largest block.
+    fn gas(&self, max_block_chunks: usize) -> u64 {
+        let chunks = self.chunks() as u64;
+        let amortized_block_gas =
+            (COMMIT_BLOCK_GAS + VERIFY_BLOCK_GAS) * chunks / max_block_chunks as u64;
+        let completion_gas = match self {
+            TxFeeTypes::Withdraw => WITHDRAW_COMPLETION_GAS,
+            _ => 0,
+        };
+        chunks * CHUNK_GAS + amortized_block_gas + completion_gas
+    }
 }
 
 #[derive(Debug)]
 pub enum RpcErrorCodes {
     NonceMismatch = 101,
+    NonceGap = 102,
     IncorrectTx = 103,
+    Duplicate = 104,
+    TokenNotOwned = 105,
+    TxExpired = 106,
+    SponsorQuotaExceeded = 107,
+    FeeTooLow = 108,
 
     MissingEthSignature = 200,
     EIP1271SignatureVerificationFail = 201,
     IncorrectEthSignature = 202,
     ChangePkNotAuthorized = 203,
+    MinterNotAuthorized = 204,
 
     Other = 300,
-    AccountCloseDisabled = 301,
+    ExodusMode = 302,
+    MempoolFull = 303,
+
+    IncorrectOrderSigner = 400,
 }
 
 impl From<TxAddError> for RpcErrorCodes {
     fn from(error: TxAddError) -> Self {
         match error {
             TxAddError::NonceMismatch => Self::NonceMismatch,
+            TxAddError::NonceGap => Self::NonceGap,
+            TxAddError::Duplicate => Self::Duplicate,
+            TxAddError::TokenNotOwned => Self::TokenNotOwned,
+            TxAddError::TxExpired => Self::TxExpired,
+            TxAddError::SponsorQuotaExceeded => Self::SponsorQuotaExceeded,
+            TxAddError::FeeTooLow => Self::FeeTooLow,
             TxAddError::IncorrectTx => Self::IncorrectTx,
             TxAddError::MissingEthSignature => Self::MissingEthSignature,
             TxAddError::EIP1271SignatureVerificationFail => Self::EIP1271SignatureVerificationFail,
             TxAddError::IncorrectEthSignature => Self::IncorrectEthSignature,
             TxAddError::ChangePkNotAuthorized => Self::ChangePkNotAuthorized,
+            TxAddError::MinterNotAuthorized => Self::MinterNotAuthorized,
+            TxAddError::ExodusMode => Self::ExodusMode,
+            TxAddError::MempoolFull => Self::MempoolFull,
             TxAddError::Other => Self::Other,
         }
     }
 }
 
+impl From<OrderAddError> for RpcErrorCodes {
+    fn from(error: OrderAddError) -> Self {
+        match error {
+            OrderAddError::NonceMismatch => Self::NonceMismatch,
+            OrderAddError::Duplicate => Self::Duplicate,
+            OrderAddError::TokenNotOwned => Self::TokenNotOwned,
+            OrderAddError::IncorrectOrder => Self::IncorrectTx,
+            OrderAddError::IncorrectSigner => Self::IncorrectOrderSigner,
+            OrderAddError::Other => Self::Other,
+        }
+    }
+}
+
 impl Into<ErrorCode> for RpcErrorCodes {
     fn into(self) -> ErrorCode {
         (self as i64).into()
@@ -281,26 +483,87 @@ pub trait Rpc {
         signature: Box<Option<TxEthSignature>>,
     ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send>;
 
+    /// Executes the tx against a copy of the current state without submitting it.
+    #[rpc(name = "tx_simulate", returns = "TxSimulationResult")]
+    fn tx_simulate(
+        &self,
+        tx: Box<FranklinTx>,
+        signature: Box<Option<TxEthSignature>>,
+    ) -> Box<dyn futures01::Future<Item = TxSimulationResult, Error = Error> + Send>;
+
     #[rpc(name = "contract_address")]
     fn contract_address(&self) -> Result<ContractAddressResp>;
 
-    /// "ETH" | #ERC20_ADDRESS => {Token}
+    /// #COLLECTION_SYMBOL => {Token}
     #[rpc(name = "tokens")]
     fn tokens(&self) -> Result<HashMap<String, Token>>;
 
+    /// Fee in wei the tx of the given type is expected to pay, `tx_submit` rejects the txs paying less.
     #[rpc(name = "get_tx_fee")]
-    fn get_tx_fee(
+    fn get_tx_fee(&self, tx_type: TxFeeTypes) -> Result<BigDecimal>;
+
+    /// Fee quota of the sponsor account along with the part of it used by the recent sponsored txs.
+    #[rpc(name = "sponsor_quota", returns = "SponsorQuota")]
+    fn sponsor_quota(
         &self,
-        tx_type: TxFeeTypes,
-        amount: BigDecimal,
-        token_like: TokenLike,
-    ) -> Result<BigDecimal>;
+        sponsor: AccountId,
+    ) -> Box<dyn futures01::Future<Item = SponsorQuota, Error = Error> + Send>;
 
     #[rpc(name = "get_confirmations_for_eth_op_amount", returns = "u64")]
     fn get_confirmations_for_eth_op_amount(&self) -> Result<u64>;
 
     #[rpc(name = "token_info")]
-    fn token_info(&self, token_id: u16) -> Result<TokenInfoResp>;
+    fn token_info(&self, collection_id: CollectionId, token_id: TokenId) -> Result<TokenInfoResp>;
+
+    /// Page of the token history, starting from the first page if the query has no cursor.
+    #[rpc(name = "token_history")]
+    fn token_history(
+        &self,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        query: Option<TokenHistoryQuery>,
+    ) -> Result<TokenHistoryResp>;
+
+    /// Account approved to transfer the token on behalf of its owner, `None` if there is none.
+    #[rpc(name = "get_approved")]
+    fn get_approved(
+        &self,
+        collection_id: CollectionId,
+        token_id: TokenId,
+    ) -> Result<Option<Address>>;
+
+    /// Whether the operator is approved to transfer all the tokens of the owner.
+    #[rpc(name = "is_approved_for_all")]
+    fn is_approved_for_all(&self, owner: Address, operator: Address) -> Result<bool>;
+
+    /// Places the signed order into the order book, returns the order hash.
+    /// Order is turned into a swap once a matching order is placed.
+    #[rpc(name = "order_submit", returns = "TxHash")]
+    fn order_submit(
+        &self,
+        order: Box<Order>,
+    ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send>;
+
+    /// Orders of the account waiting to be matched.
+    #[rpc(name = "orders_by_account", returns = "Vec<Order>")]
+    fn orders_by_account(
+        &self,
+        address: Address,
+    ) -> Box<dyn futures01::Future<Item = Vec<Order>, Error = Error> + Send>;
+
+    /// Orders selling the token waiting to be matched.
+    #[rpc(name = "orders_by_token", returns = "Vec<Order>")]
+    fn orders_by_token(
+        &self,
+        collection_id: CollectionId,
+        token_id: TokenId,
+    ) -> Box<dyn futures01::Future<Item = Vec<Order>, Error = Error> + Send>;
+
+    /// Exodus mode state, `null` while the network operates normally.
+    #[rpc(name = "exodus_mode", returns = "Option<ExodusMode>")]
+    fn exodus_mode(
+        &self,
+    ) -> Box<dyn futures01::Future<Item = Option<ExodusMode>, Error = Error> + Send>;
 }
 
 #[derive(Clone)]
@@ -310,6 +573,7 @@ pub struct RpcApp {
     cache_of_transaction_receipts: SharedLruCache<Vec<u8>, TxReceiptResponse>,
 
     pub mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    pub order_book_request_sender: mpsc::Sender<OrderBookRequest>,
     pub state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
     pub eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
     pub sign_verify_request_sender: mpsc::Sender<VerifyTxSignatureRequest>,
@@ -317,6 +581,8 @@ pub struct RpcApp {
     pub connection_pool: ConnectionPool,
 
     pub confirmations_for_eth_event: u64,
+    /// Chunks of the largest block, used to amortize the block cost in tx fees.
+    pub max_block_chunks: usize,
 }
 
 impl RpcApp {
@@ -324,12 +590,17 @@ impl RpcApp {
         config_options: &ConfigurationOptions,
         connection_pool: ConnectionPool,
         mempool_request_sender: mpsc::Sender<MempoolRequest>,
+        order_book_request_sender: mpsc::Sender<OrderBookRequest>,
         state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
         sign_verify_request_sender: mpsc::Sender<VerifyTxSignatureRequest>,
         eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
     ) -> Self {
         let api_requests_caches_size = config_options.api_requests_caches_size;
         let confirmations_for_eth_event = config_options.confirmations_for_eth_event;
+        let max_block_chunks = *config_options
+            .available_block_chunk_sizes
+            .last()
+            .expect("no available block chunk sizes");
 
         RpcApp {
             cache_of_executed_priority_operations: SharedLruCache::new(api_requests_caches_size),
@@ -339,11 +610,13 @@ impl RpcApp {
             connection_pool,
 
             mempool_request_sender,
+            order_book_request_sender,
             state_keeper_request_sender,
             sign_verify_request_sender,
             eth_watcher_request_sender,
 
             confirmations_for_eth_event,
+            max_block_chunks,
         }
     }
 
@@ -357,7 +630,12 @@ impl RpcApp {
     fn get_tx_info_message_to_sign(&self, tx: &FranklinTx) -> Result<Option<String>> {
         match tx {
             FranklinTx::Transfer(tx) => Ok(Some(tx.get_ethereum_sign_message())),
+            FranklinTx::BatchTransfer(tx) => Ok(Some(tx.get_ethereum_sign_message())),
             FranklinTx::Withdraw(tx) => Ok(Some(tx.get_ethereum_sign_message())),
+            FranklinTx::Mint(tx) => Ok(Some(tx.get_ethereum_sign_message())),
+            FranklinTx::Burn(tx) => Ok(Some(tx.get_ethereum_sign_message())),
+            FranklinTx::Approve(tx) => Ok(Some(tx.get_ethereum_sign_message())),
+            FranklinTx::ApproveForAll(tx) => Ok(Some(tx.get_ethereum_sign_message())),
             _ => Ok(None),
         }
     }
@@ -393,6 +671,89 @@ pub(crate) async fn get_ongoing_priority_ops(
         .map_err(|_| Error::internal_error())
 }
 
+pub(crate) async fn get_exodus_mode(
+    eth_watcher_request_sender: &mpsc::Sender<EthWatchRequest>,
+) -> Result<Option<ExodusMode>> {
+    let mut eth_watcher_request_sender = eth_watcher_request_sender.clone();
+
+    let eth_watcher_response = oneshot::channel();
+
+    eth_watcher_request_sender
+        .send(EthWatchRequest::GetExodusMode {
+            resp: eth_watcher_response.0,
+        })
+        .await
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: N/A",
+                file!(),
+                line!(),
+                column!(),
+                err
+            );
+            Error::internal_error()
+        })?;
+
+    eth_watcher_response
+        .1
+        .await
+        .map_err(|_| Error::internal_error())
+}
+
+async fn get_sponsor_quota(
+    mut mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    sponsor: AccountId,
+) -> Result<SponsorQuota> {
+    let mempool_response = oneshot::channel();
+
+    mempool_request_sender
+        .send(MempoolRequest::GetSponsorQuota(sponsor, mempool_response.0))
+        .await
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: {}",
+                file!(),
+                line!(),
+                column!(),
+                err,
+                sponsor,
+            );
+            Error::internal_error()
+        })?;
+
+    mempool_response
+        .1
+        .await
+        .map_err(|_| Error::internal_error())
+}
+
+async fn get_orders(
+    mut order_book_request_sender: mpsc::Sender<OrderBookRequest>,
+    filter: OrdersFilter,
+) -> Result<Vec<Order>> {
+    let order_book_response = oneshot::channel();
+
+    order_book_request_sender
+        .send(OrderBookRequest::GetOrders(filter, order_book_response.0))
+        .await
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: {:?}",
+                file!(),
+                line!(),
+                column!(),
+                err,
+                filter,
+            );
+            Error::internal_error()
+        })?;
+
+    order_book_response
+        .1
+        .await
+        .map_err(|_| Error::internal_error())
+}
+
 impl RpcApp {
     fn access_storage(&self) -> Result<StorageProcessor> {
         self.connection_pool
@@ -400,6 +761,134 @@ impl RpcApp {
             .map_err(|_| Error::internal_error())
     }
 
+    /// Last committed state of the account with the given address.
+    fn get_committed_account(
+        storage: &StorageProcessor,
+        address: &Address,
+    ) -> Result<Option<(AccountId, Account)>> {
+        let account_state = storage
+            .chain()
+            .account_schema()
+            .account_state_by_address(address)
+            .map_err(|err| {
+                log::warn!(
+                    "[{}:{}:{}] Internal Server Error: '{}'; input: {}",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err,
+                    address,
+                );
+                Error::internal_error()
+            })?;
+        Ok(account_state.committed)
+    }
+
+    /// Address of the committed account with the given id.
+    fn get_committed_account_address(
+        storage: &StorageProcessor,
+        account_id: AccountId,
+    ) -> Result<Option<Address>> {
+        let account = storage
+            .chain()
+            .account_schema()
+            .last_committed_state_for_account(account_id)
+            .map_err(|err| {
+                log::warn!(
+                    "[{}:{}:{}] Internal Server Error: '{}'; input: {}",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err,
+                    account_id,
+                );
+                Error::internal_error()
+            })?;
+        Ok(account.map(|account| account.address))
+    }
+
+    /// Accounts allowed to provide the Ethereum signature of the tx: the tx account itself and,
+    /// for a transfer, the operators approved by the token owner.
+    /// Fee in wei the tx of the given type is expected to pay, rounded up to a packable amount.
+    fn estimate_tx_fee(&self, tx_type: &TxFeeTypes) -> Result<BigDecimal> {
+        let storage = self.access_storage()?;
+        // Gas price limit is kept up to date by the gas adjuster of eth sender, so the fee
+        // covers the price the operator may actually pay for the block txs.
+        let gas_price = storage
+            .ethereum_schema()
+            .load_gas_price_limit()
+            .map_err(|err| {
+                log::warn!(
+                    "[{}:{}:{}] Internal Server Error: '{}'; input: {:?}",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err,
+                    tx_type,
+                );
+                Error::internal_error()
+            })?;
+        let fee =
+            u256_to_bigdecimal(&gas_price) * BigDecimal::from(tx_type.gas(self.max_block_chunks));
+        Ok(closest_packable_fee_amount(&fee))
+    }
+
+    /// Checks that the fees paid by the tx are not lower than the `get_tx_fee` estimates.
+    fn check_tx_fee(&self, tx: &FranklinTx) -> Result<()> {
+        let fees = match tx {
+            FranklinTx::Transfer(tx) => {
+                let storage = self.access_storage()?;
+                let fee_type = if Self::get_committed_account(&storage, &tx.to)?.is_some() {
+                    TxFeeTypes::Transfer
+                } else {
+                    TxFeeTypes::TransferToNew
+                };
+                vec![(fee_type, &tx.fee)]
+            }
+            FranklinTx::Withdraw(tx) => vec![(TxFeeTypes::Withdraw, &tx.fee)],
+            FranklinTx::Mint(tx) => vec![(TxFeeTypes::Mint, &tx.fee)],
+            FranklinTx::Burn(tx) => vec![(TxFeeTypes::Burn, &tx.fee)],
+            FranklinTx::Approve(tx) => vec![(TxFeeTypes::Approve, &tx.fee)],
+            FranklinTx::ApproveForAll(tx) => vec![(TxFeeTypes::ApproveForAll, &tx.fee)],
+            FranklinTx::Swap(tx) => vec![
+                (TxFeeTypes::SwapOrder, &tx.order_a.fee),
+                (TxFeeTypes::SwapOrder, &tx.order_b.fee),
+            ],
+            _ => Vec::new(),
+        };
+        for (fee_type, fee) in fees {
+            if fee < &self.estimate_tx_fee(&fee_type)? {
+                return Err(Error {
+                    code: RpcErrorCodes::FeeTooLow.into(),
+                    message: TxAddError::FeeTooLow.to_string(),
+                    data: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn get_eth_signers(&self, tx: &FranklinTx) -> Result<Vec<Address>> {
+        let mut signers = vec![tx.account()];
+        if let FranklinTx::Transfer(transfer) = tx {
+            let storage = self.access_storage()?;
+            if let Some((_, owner)) = Self::get_committed_account(&storage, &transfer.from)? {
+                let mut operators = owner.get_operators();
+                if let Some(approved) = owner
+                    .token_slot(transfer.nft())
+                    .and_then(|slot| owner.approved(slot))
+                {
+                    operators.push(approved);
+                }
+                for operator in operators {
+                    let address = Self::get_committed_account_address(&storage, operator)?;
+                    signers.extend(address);
+                }
+            }
+        }
+        Ok(signers)
+    }
+
     /// Async version of `get_ongoing_deposits` which does not use old futures as a return type.
     async fn get_ongoing_deposits_impl(&self, address: Address) -> Result<OngoingDepositsResp> {
         let confirmations_for_eth_event = self.confirmations_for_eth_event;
@@ -651,6 +1140,15 @@ impl Rpc for RpcApp {
         })
     }
 
+    fn sponsor_quota(
+        &self,
+        sponsor: AccountId,
+    ) -> Box<dyn futures01::Future<Item = SponsorQuota, Error = Error> + Send> {
+        let mempool_sender = self.mempool_request_sender.clone();
+        let resp = async move { get_sponsor_quota(mempool_sender, sponsor).await };
+        Box::new(resp.boxed().compat())
+    }
+
     fn get_confirmations_for_eth_op_amount(&self) -> Result<u64> {
         Ok(self.confirmations_for_eth_event)
     }
@@ -683,18 +1181,17 @@ impl Rpc for RpcApp {
         tx: Box<FranklinTx>,
         signature: Box<Option<TxEthSignature>>,
     ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send> {
-        if tx.is_close() {
-            return Box::new(futures01::future::err(Error {
-                code: RpcErrorCodes::AccountCloseDisabled.into(),
-                message: "Account close tx is disabled.".to_string(),
-                data: None,
-            }));
-        }
-
         let msg_to_sign = match self.get_tx_info_message_to_sign(&tx) {
             Ok(res) => res,
             Err(e) => return Box::new(futures01::future::err(e)),
         };
+        let eth_signers = match self.get_eth_signers(&tx) {
+            Ok(res) => res,
+            Err(e) => return Box::new(futures01::future::err(e)),
+        };
+        if let Err(e) = self.check_tx_fee(&tx) {
+            return Box::new(futures01::future::err(e));
+        }
 
         let mut mempool_sender = self.mempool_request_sender.clone();
         let sign_verify_channel = self.sign_verify_request_sender.clone();
@@ -703,6 +1200,7 @@ impl Rpc for RpcApp {
                 &tx,
                 *signature.clone(),
                 msg_to_sign,
+                eth_signers,
                 sign_verify_channel,
             )
             .await?;
@@ -736,6 +1234,66 @@ impl Rpc for RpcApp {
         Box::new(mempool_resp.boxed().compat())
     }
 
+    fn tx_simulate(
+        &self,
+        tx: Box<FranklinTx>,
+        signature: Box<Option<TxEthSignature>>,
+    ) -> Box<dyn futures01::Future<Item = TxSimulationResult, Error = Error> + Send> {
+        let msg_to_sign = match self.get_tx_info_message_to_sign(&tx) {
+            Ok(res) => res,
+            Err(e) => return Box::new(futures01::future::err(e)),
+        };
+        let eth_signers = match self.get_eth_signers(&tx) {
+            Ok(res) => res,
+            Err(e) => return Box::new(futures01::future::err(e)),
+        };
+
+        let mut state_keeper_request_sender = self.state_keeper_request_sender.clone();
+        let sign_verify_channel = self.sign_verify_request_sender.clone();
+        let simulation_resp = async move {
+            let verified_tx = verify_tx_info_message_signature(
+                &tx,
+                *signature.clone(),
+                msg_to_sign,
+                eth_signers,
+                sign_verify_channel,
+            )
+            .await?;
+
+            let state_keeper_resp = oneshot::channel();
+            state_keeper_request_sender
+                .send(StateKeeperRequest::SimulateTx(
+                    Box::new(verified_tx.into_inner()),
+                    state_keeper_resp.0,
+                ))
+                .await
+                .map_err(|err| {
+                    log::warn!(
+                        "[{}:{}:{}] Internal Server Error: '{}'; input: <Tx: '{:?}', signature: '{:?}'>",
+                        file!(),
+                        line!(),
+                        column!(),
+                        err,
+                        tx,
+                        signature,
+                    );
+                    Error::internal_error()
+                })?;
+            state_keeper_resp.1.await.map_err(|err| {
+                log::warn!(
+                    "[{}:{}:{}] Internal Server Error: '{}'; input: N/A",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err
+                );
+                Error::internal_error()
+            })
+        };
+
+        Box::new(simulation_resp.boxed().compat())
+    }
+
     fn contract_address(&self) -> Result<ContractAddressResp> {
         let storage = self.access_storage()?;
         let config = storage.config_schema().load_config().map_err(|err| {
@@ -764,40 +1322,49 @@ impl Rpc for RpcApp {
     }
 
     fn tokens(&self) -> Result<HashMap<String, Token>> {
-        Ok(HashMap::new()) // TODO ADE must be removed
-    }
-
-    fn get_tx_fee(
-        &self,
-        _tx_type: TxFeeTypes,
-        amount: BigDecimal,
-        _token_like: TokenLike,
-    ) -> Result<BigDecimal> {
-        // first approximation - just give 1 percent
-        Ok(closest_packable_fee_amount(&floor_big_decimal(
-            &(amount / BigDecimal::from(100)),
-        )))
-    }
-
-    fn token_info(&self, token_id: u16) -> Result<TokenInfoResp> {
-        log::debug!("Get token_info for token {}", token_id);
         let storage = self.access_storage()?;
-        let mut full_history = storage.colexi_queries().get_initial_deposit(token_id).map_err(|err| {
-            log::error!(
-                "[{}:{}:{}] Internal Server Error: '{}';",
+        let mut tokens = storage.tokens_schema().load_tokens().map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: N/A",
                 file!(),
                 line!(),
                 column!(),
                 err
             );
             Error::internal_error()
-        }).map(|tr| {
-            match tr {
-                Some(tr) => vec!(tr),
-                None => Vec::new()
-            }
-        })?; 
-        let mut history = storage.colexi_queries().get_transfer_history(token_id).map_err(|err| {
+        })?;
+        Ok(tokens
+            .drain()
+            .map(|(_, token)| (token.symbol.clone(), token))
+            .collect())
+    }
+
+    fn get_tx_fee(&self, tx_type: TxFeeTypes) -> Result<BigDecimal> {
+        self.estimate_tx_fee(&tx_type)
+    }
+
+    fn token_info(&self, collection_id: CollectionId, token_id: TokenId) -> Result<TokenInfoResp> {
+        log::debug!("Get token_info for token {} of collection {}", token_id, collection_id);
+        let token = Nft::new(collection_id, token_id);
+        let storage = self.access_storage()?;
+        let query = TokenHistoryQuery::default()
+            .storage_query()
+            .expect("default history query is valid");
+        let history: TokenHistoryResp = storage
+            .colexi_queries()
+            .get_transfer_history(&token, &query)
+            .map_err(|err| {
+                log::error!(
+                    "[{}:{}:{}] Internal Server Error: '{}';",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err
+                );
+                Error::internal_error()
+            })?
+            .into();
+        let owner = storage.colexi_queries().get_current_owner(&token).map_err(|err| {
             log::error!(
                 "[{}:{}:{}] Internal Server Error: '{}';",
                 file!(),
@@ -807,18 +1374,20 @@ impl Rpc for RpcApp {
             );
             Error::internal_error()
         })?;
-        full_history.append(&mut history);
-        let history: Vec<TransferHistory> = full_history.iter().map(|transfer| {
-            TransferHistory {
-                from: transfer.from.clone(),
-                to: transfer.to.clone(),
-                verified: transfer.proof_block_number.is_some(),
-                block_number: transfer.block_number,
-                date: chrono::DateTime::<chrono::Utc>::from_utc(transfer.created_at, chrono::Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                tx_hash: format!("0x{}", hex::encode(&transfer.tx_hash)),
-            }
-        }).collect();
-        let owner = storage.colexi_queries().get_current_owner(token_id).map_err(|err| {
+        let content_hash = storage
+            .colexi_queries()
+            .get_content_hash(&token)
+            .map_err(|err| {
+                log::error!(
+                    "[{}:{}:{}] Internal Server Error: '{}';",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err
+                );
+                Error::internal_error()
+            })?;
+        let burned = storage.colexi_queries().is_burned(&token).map_err(|err| {
             log::error!(
                 "[{}:{}:{}] Internal Server Error: '{}';",
                 file!(),
@@ -829,12 +1398,156 @@ impl Rpc for RpcApp {
             Error::internal_error()
         })?;
         let result = TokenInfoResp {
-            history: history,
+            history: history.history,
+            history_next_cursor: history.next_cursor,
             owner: owner.map(|account| format!("0x{}", hex::encode(&account.address))),
+            content_hash: content_hash
+                .map(|token| format!("0x{}", hex::encode(&token.content_hash))),
+            burned,
         };
 
         return Ok(result);
     }
+
+    fn token_history(
+        &self,
+        collection_id: CollectionId,
+        token_id: TokenId,
+        query: Option<TokenHistoryQuery>,
+    ) -> Result<TokenHistoryResp> {
+        let token = Nft::new(collection_id, token_id);
+        let query = query.unwrap_or_default();
+        let storage_query = query.storage_query().ok_or_else(|| {
+            Error::invalid_params(format!(
+                "limit should be from 1 to {}",
+                TokenHistoryQuery::MAX_LIMIT
+            ))
+        })?;
+        let storage = self.access_storage()?;
+        let page = storage
+            .colexi_queries()
+            .get_transfer_history(&token, &storage_query)
+            .map_err(|err| {
+                log::warn!(
+                    "[{}:{}:{}] Internal Server Error: '{}'; input: {:?}",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err,
+                    query,
+                );
+                Error::internal_error()
+            })?;
+        Ok(page.into())
+    }
+
+    fn get_approved(
+        &self,
+        collection_id: CollectionId,
+        token_id: TokenId,
+    ) -> Result<Option<Address>> {
+        let token = Nft::new(collection_id, token_id);
+        let storage = self.access_storage()?;
+        let owner = storage
+            .colexi_queries()
+            .get_current_owner(&token)
+            .map_err(|err| {
+                log::error!(
+                    "[{}:{}:{}] Internal Server Error: '{}';",
+                    file!(),
+                    line!(),
+                    column!(),
+                    err
+                );
+                Error::internal_error()
+            })?;
+        let owner = match owner {
+            Some(owner) => Address::from_slice(&owner.address),
+            None => return Ok(None),
+        };
+        let approved = Self::get_committed_account(&storage, &owner)?.and_then(|(_, account)| {
+            account
+                .token_slot(token)
+                .and_then(|slot| account.approved(slot))
+        });
+        match approved {
+            Some(operator) => Self::get_committed_account_address(&storage, operator),
+            None => Ok(None),
+        }
+    }
+
+    fn is_approved_for_all(&self, owner: Address, operator: Address) -> Result<bool> {
+        let storage = self.access_storage()?;
+        let owner = Self::get_committed_account(&storage, &owner)?;
+        let operator = Self::get_committed_account(&storage, &operator)?;
+        Ok(match (owner, operator) {
+            (Some((_, owner)), Some((operator_id, _))) => owner.is_operator(operator_id),
+            _ => false,
+        })
+    }
+
+    fn order_submit(
+        &self,
+        order: Box<Order>,
+    ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send> {
+        let mut order_book_sender = self.order_book_request_sender.clone();
+        let order_book_resp = async move {
+            let hash = order.hash();
+            let order_book_resp = oneshot::channel();
+            order_book_sender
+                .send(OrderBookRequest::NewOrder(order.clone(), order_book_resp.0))
+                .await
+                .map_err(|err| {
+                    log::warn!(
+                        "[{}:{}:{}] Internal Server Error: '{}'; input: <Order: '{:?}'>",
+                        file!(),
+                        line!(),
+                        column!(),
+                        err,
+                        order,
+                    );
+                    Error::internal_error()
+                })?;
+            let order_add_result = order_book_resp.1.await.unwrap_or(Err(OrderAddError::Other));
+
+            order_add_result.map(|_| hash).map_err(|e| Error {
+                code: RpcErrorCodes::from(e).into(),
+                message: e.to_string(),
+                data: None,
+            })
+        };
+
+        Box::new(order_book_resp.boxed().compat())
+    }
+
+    fn orders_by_account(
+        &self,
+        address: Address,
+    ) -> Box<dyn futures01::Future<Item = Vec<Order>, Error = Error> + Send> {
+        let order_book_sender = self.order_book_request_sender.clone();
+        let resp =
+            async move { get_orders(order_book_sender, OrdersFilter::Account(address)).await };
+        Box::new(resp.boxed().compat())
+    }
+
+    fn orders_by_token(
+        &self,
+        collection_id: CollectionId,
+        token_id: TokenId,
+    ) -> Box<dyn futures01::Future<Item = Vec<Order>, Error = Error> + Send> {
+        let order_book_sender = self.order_book_request_sender.clone();
+        let token = Nft::new(collection_id, token_id);
+        let resp = async move { get_orders(order_book_sender, OrdersFilter::Token(token)).await };
+        Box::new(resp.boxed().compat())
+    }
+
+    fn exodus_mode(
+        &self,
+    ) -> Box<dyn futures01::Future<Item = Option<ExodusMode>, Error = Error> + Send> {
+        let eth_watcher_request_sender = self.eth_watcher_request_sender.clone();
+        let resp = async move { get_exodus_mode(&eth_watcher_request_sender).await };
+        Box::new(resp.boxed().compat())
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -842,6 +1555,7 @@ pub fn start_rpc_server(
     config_options: ConfigurationOptions,
     connection_pool: ConnectionPool,
     mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    order_book_request_sender: mpsc::Sender<OrderBookRequest>,
     state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
     sign_verify_request_sender: mpsc::Sender<VerifyTxSignatureRequest>,
     eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
@@ -858,6 +1572,7 @@ pub fn start_rpc_server(
                 &config_options,
                 connection_pool,
                 mempool_request_sender,
+                order_book_request_sender,
                 state_keeper_request_sender,
                 sign_verify_request_sender,
                 eth_watcher_request_sender,
@@ -875,6 +1590,7 @@ async fn verify_tx_info_message_signature(
     tx: &FranklinTx,
     signature: Option<TxEthSignature>,
     msg_to_sign: Option<String>,
+    eth_signers: Vec<Address>,
     mut req_channel: mpsc::Sender<VerifyTxSignatureRequest>,
 ) -> Result<VerifiedTx> {
     fn rpc_message(error: TxAddError) -> Error {
@@ -900,6 +1616,7 @@ async fn verify_tx_info_message_signature(
     let request = VerifyTxSignatureRequest {
         tx: tx.clone(),
         eth_sign_data,
+        eth_signers,
         response: resp.0,
     };
 
@@ -963,4 +1680,29 @@ mod test {
             assert_eq!(query, de);
         }
     }
+
+    #[test]
+    fn tx_fee_gas() {
+        let max_block_chunks = 100;
+        // Block cost is shared between the chunks of the block.
+        let block_gas_per_chunk = (COMMIT_BLOCK_GAS + VERIFY_BLOCK_GAS) / max_block_chunks;
+        assert_eq!(
+            TxFeeTypes::Transfer.gas(max_block_chunks as usize),
+            TransferOp::CHUNKS as u64 * (CHUNK_GAS + block_gas_per_chunk)
+        );
+        // Withdrawal has to be completed on L1 as well.
+        assert_eq!(
+            TxFeeTypes::Withdraw.gas(max_block_chunks as usize),
+            WithdrawOp::CHUNKS as u64 * (CHUNK_GAS + block_gas_per_chunk) + WITHDRAW_COMPLETION_GAS
+        );
+        assert!(
+            TxFeeTypes::ApproveForAll.gas(max_block_chunks as usize)
+                < TxFeeTypes::Mint.gas(max_block_chunks as usize)
+        );
+        // Both orders of a swap pay for a half of its chunks.
+        assert_eq!(
+            TxFeeTypes::SwapOrder.gas(max_block_chunks as usize) * 2,
+            SwapOp::CHUNKS as u64 * (CHUNK_GAS + block_gas_per_chunk)
+        );
+    }
 }
@@ -22,6 +22,7 @@ use crate::{
     api_server::event_notify::{start_sub_notifier, EventNotifierRequest, EventSubscribeRequest},
     api_server::rpc_server::{ETHOpInfoResp, ResponseAccountState, TransactionInfoResp},
     mempool::MempoolRequest,
+    order_book::OrderBookRequest,
     signature_checker::VerifyTxSignatureRequest,
     state_keeper::{ExecutedOpsNotify, StateKeeperRequest},
 };
@@ -184,6 +185,7 @@ pub fn start_ws_server(
     op_recv: mpsc::Receiver<Operation>,
     db_pool: ConnectionPool,
     mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    order_book_request_sender: mpsc::Sender<OrderBookRequest>,
     executed_tx_receiver: mpsc::Receiver<ExecutedOpsNotify>,
     state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
     sign_verify_request_sender: mpsc::Sender<VerifyTxSignatureRequest>,
@@ -201,6 +203,7 @@ pub fn start_ws_server(
         config_options,
         db_pool.clone(),
         mempool_request_sender,
+        order_book_request_sender,
         state_keeper_request_sender.clone(),
         sign_verify_request_sender,
         eth_watcher_request_sender,
@@ -1,9 +1,10 @@
 use futures::{channel::mpsc, SinkExt};
 use log::*;
 use server::eth_watch::{EthWatch, EthWatchRequest};
+use std::collections::HashSet;
 use std::time::Duration;
 use storage::ConnectionPool;
-use tokio::{runtime::Runtime, time};
+use tokio::{runtime::Runtime, sync::watch, time};
 
 fn main() {
     let mut main_runtime = Runtime::new().expect("main runtime start");
@@ -27,6 +28,8 @@ fn main() {
     let web3 = web3::Web3::new(transport);
 
     let (eth_req_sender, eth_req_receiver) = mpsc::channel(256);
+    let (exodus_mode_sender, _exodus_mode_receiver) = watch::channel(None);
+    let (minters_sender, _minters_receiver) = watch::channel(HashSet::new());
 
     let watcher = EthWatch::new(
         web3,
@@ -37,6 +40,8 @@ fn main() {
         contract_address,
         0,
         eth_req_receiver,
+        exodus_mode_sender,
+        minters_sender,
     );
 
     main_runtime.spawn(watcher.run());
@@ -1,21 +1,25 @@
 //! Generate exit proof for exodus mode given account and token
 //! correct verified state should be present in the db (could be restored using `data-restore` module)
 
-use bigdecimal::BigDecimal;
 use clap::{App, Arg};
 use log::info;
-use models::node::{AccountId, Address, TokenId, TokenLike};
+use models::abi::zksync_contract;
+use models::node::{AccountId, Address, CollectionId, Nft, TokenId};
 use models::prover_utils::EncodedProofPlonk;
 use serde::Serialize;
 use std::time::Instant;
 use storage::ConnectionPool;
+use web3::contract::tokens::Tokenize;
 
 #[derive(Serialize, Debug)]
 struct ExitProofData {
     account_id: AccountId,
     account_address: Address,
-    amount: BigDecimal,
+    collection_id: CollectionId,
+    token_id: TokenId,
     proof: EncodedProofPlonk,
+    /// Input data of the `exit` call to send to the zkSync contract from the account address
+    calldata: String,
 }
 
 fn main() {
@@ -31,11 +35,18 @@ fn main() {
                 .help("Account id of the account"),
         )
         .arg(
-            Arg::with_name("Token")
-                .long("token")
+            Arg::with_name("Collection id")
+                .long("collection_id")
                 .takes_value(true)
                 .required(true)
-                .help("Token to withdraw - \"ETH\" or address of the ERC20 token"),
+                .help("Id of the NFT collection the token belongs to"),
+        )
+        .arg(
+            Arg::with_name("Token id")
+                .long("token_id")
+                .takes_value(true)
+                .required(true)
+                .help("ERC-721 id of the token to withdraw, in decimal"),
         )
         .get_matches();
 
@@ -46,8 +57,14 @@ fn main() {
         .unwrap();
 
     let token = {
-        let token = cli.value_of("Token").expect("required argument");
-        serde_json::from_str::<TokenLike>(token).expect("invalid token argument")
+        let collection_id = cli
+            .value_of("Collection id")
+            .expect("required argument")
+            .parse::<CollectionId>()
+            .expect("invalid collection id argument");
+        let token_id = cli.value_of("Token id").expect("required argument");
+        let token_id = TokenId::from_dec_str(token_id).expect("invalid token id argument");
+        Nft::new(collection_id, token_id)
     };
 
     let timer = Instant::now();
@@ -73,14 +90,31 @@ fn main() {
 
     info!("Resotred state from db: {} s", timer.elapsed().as_secs());
 
-    let (proof, amount) = prover::exit_proof::create_exit_proof(accounts, account_id, address)
+    let proof = prover::exit_proof::create_exit_proof(accounts, account_id, address, token)
         .expect("Failed to generate exit proof");
 
+    // function exit(uint24 _accountId, uint16 _collectionId, uint256 _tokenId, uint256[] calldata _proof)
+    let calldata = zksync_contract()
+        .function("exit")
+        .expect("failed to get exit function")
+        .encode_input(
+            &(
+                u64::from(account_id),
+                u64::from(token.collection_id),
+                token.token_id,
+                proof.proof.clone(),
+            )
+                .into_tokens(),
+        )
+        .expect("failed to encode exit parameters");
+
     let proof_data = ExitProofData {
         account_id,
         account_address: address,
-        amount,
+        collection_id: token.collection_id,
+        token_id: token.token_id,
         proof,
+        calldata: format!("0x{}", hex::encode(calldata)),
     };
 
     println!(
@@ -4,10 +4,10 @@ use std::time::Duration;
 use futures::channel::mpsc::{Receiver, Sender};
 use futures::{SinkExt, StreamExt};
 // Workspace uses
-use crate::mempool::MempoolRequest;
-use models::{Action, CommitRequest, Operation};
+use crate::{mempool::MempoolRequest, order_book::OrderBookRequest};
+use models::{Action, CommitRequest, ExodusMode, Operation};
 use storage::ConnectionPool;
-use tokio::{runtime::Runtime, time};
+use tokio::{runtime::Runtime, sync::watch, time};
 
 const PROOF_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
@@ -16,13 +16,26 @@ async fn handle_new_commit_task(
     mut tx_for_eth: Sender<Operation>,
     mut op_notify_sender: Sender<Operation>,
     mut mempool_req_sender: Sender<MempoolRequest>,
+    mut order_book_req_sender: Sender<OrderBookRequest>,
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
     pool: ConnectionPool,
 ) {
     while let Some(CommitRequest {
         block,
         accounts_updated,
+        executed_txs,
+        failed_txs,
     }) = rx_for_ops.next().await
     {
+        // contract does not accept blocks in the exodus mode
+        if exodus_mode.borrow().is_some() {
+            warn!(
+                "Block #{} is not committed, network is in exodus mode",
+                block.block_number
+            );
+            continue;
+        }
+
         let storage = pool
             .access_storage()
             .expect("db connection fail for committer");
@@ -38,6 +51,15 @@ async fn handle_new_commit_task(
                 .block_schema()
                 .save_block_transactions(block)
                 .expect("committer failed tx save");
+            mempool_req_sender
+                .send(MempoolRequest::BlockCommitted {
+                    updates: Vec::new(),
+                    executed_txs,
+                    failed_txs,
+                })
+                .await
+                .map_err(|e| warn!("Failed notify mempool about failed txs: {}", e))
+                .unwrap_or_default();
             continue;
         }
 
@@ -66,15 +88,31 @@ async fn handle_new_commit_task(
             .map_err(|e| warn!("Failed notify about commit op confirmation: {}", e))
             .unwrap_or_default();
 
+        order_book_req_sender
+            .send(OrderBookRequest::UpdateAccounts(
+                op.accounts_updated.clone(),
+            ))
+            .await
+            .map_err(|e| warn!("Failed notify order book about account updates: {}", e))
+            .unwrap_or_default();
+
         mempool_req_sender
-            .send(MempoolRequest::UpdateNonces(op.accounts_updated))
+            .send(MempoolRequest::BlockCommitted {
+                updates: op.accounts_updated,
+                executed_txs,
+                failed_txs,
+            })
             .await
             .map_err(|e| warn!("Failed notify mempool about account updates: {}", e))
             .unwrap_or_default();
     }
 }
 
-async fn poll_for_new_proofs_task(mut tx_for_eth: Sender<Operation>, pool: ConnectionPool) {
+async fn poll_for_new_proofs_task(
+    mut tx_for_eth: Sender<Operation>,
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
+    pool: ConnectionPool,
+) {
     let mut last_verified_block = {
         let storage = pool
             .access_storage()
@@ -90,6 +128,10 @@ async fn poll_for_new_proofs_task(mut tx_for_eth: Sender<Operation>, pool: Conne
     loop {
         timer.tick().await;
 
+        if exodus_mode.borrow().is_some() {
+            continue;
+        }
+
         let storage = pool
             .access_storage()
             .expect("db connection failed for committer");
@@ -134,6 +176,8 @@ pub fn run_committer(
     tx_for_eth: Sender<Operation>,
     op_notify_sender: Sender<Operation>,
     mempool_req_sender: Sender<MempoolRequest>,
+    order_book_req_sender: Sender<OrderBookRequest>,
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
     pool: ConnectionPool,
     runtime: &Runtime,
 ) {
@@ -142,7 +186,9 @@ pub fn run_committer(
         tx_for_eth.clone(),
         op_notify_sender,
         mempool_req_sender,
+        order_book_req_sender,
+        exodus_mode.clone(),
         pool.clone(),
     ));
-    runtime.spawn(poll_for_new_proofs_task(tx_for_eth, pool));
+    runtime.spawn(poll_for_new_proofs_task(tx_for_eth, exodus_mode, pool));
 }
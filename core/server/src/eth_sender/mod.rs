@@ -8,6 +8,7 @@ use std::collections::VecDeque;
 // External uses
 use futures::channel::mpsc;
 use tokio::runtime::Runtime;
+use tokio::sync::watch;
 use tokio::time;
 use web3::contract::Options;
 use web3::types::{TransactionReceipt, H256};
@@ -17,7 +18,7 @@ use models::{
     config_options::{ConfigurationOptions, EthSenderOptions, ThreadPanicNotify},
     ethereum::{ETHOperation, OperationType},
     node::config,
-    Action, Operation,
+    Action, ExodusMode, Operation,
 };
 use storage::ConnectionPool;
 // Local uses
@@ -103,6 +104,11 @@ enum TxCheckMode {
 /// report the incident to the log and then panic to prevent continue working in a probably
 /// erroneous conditions. Failure handling policy is determined by a corresponding callback,
 /// which can be changed if needed.
+///
+/// # Exodus mode
+///
+/// Once the contract enters the exodus mode, it doesn't accept any operations, so `ETHSender`
+/// stops sending new transactions and only keeps track of the ones that were already sent.
 struct ETHSender<ETH: EthereumInterface, DB: DatabaseAccess> {
     /// Ongoing operations queue.
     ongoing_ops: VecDeque<ETHOperation>,
@@ -120,6 +126,8 @@ struct ETHSender<ETH: EthereumInterface, DB: DatabaseAccess> {
     gas_adjuster: GasAdjuster<ETH, DB>,
     /// Settings for the `ETHSender`.
     options: EthSenderOptions,
+    /// Exodus mode state broadcast by the Ethereum watcher.
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
 }
 
 impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
@@ -129,6 +137,7 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         ethereum: ETH,
         rx_for_eth: mpsc::Receiver<Operation>,
         op_notify: mpsc::Sender<Operation>,
+        exodus_mode: watch::Receiver<Option<ExodusMode>>,
     ) -> Self {
         let (ongoing_ops, unprocessed_ops) = db.restore_state().expect("Can't restore state");
 
@@ -154,6 +163,7 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             tx_queue,
             gas_adjuster,
             options,
+            exodus_mode,
         };
 
         // Add all the unprocessed operations to the queue.
@@ -210,14 +220,17 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         // Queue for storing all the operations that were not finished at this iteration.
         let mut new_ongoing_ops = VecDeque::new();
 
-        while let Some(tx) = self.tx_queue.pop_front() {
-            self.initialize_operation(tx.clone()).unwrap_or_else(|e| {
-                warn!("Error while trying to complete uncommitted op: {}", e);
+        // Transactions sent in the exodus mode would be reverted anyway.
+        if self.exodus_mode.borrow().is_none() {
+            while let Some(tx) = self.tx_queue.pop_front() {
+                self.initialize_operation(tx.clone()).unwrap_or_else(|e| {
+                    warn!("Error while trying to complete uncommitted op: {}", e);
 
-                // Return the unperformed operation to the queue, since failing the
-                // operation initialization means that it was not stored in the database.
-                self.tx_queue.return_popped(tx);
-            });
+                    // Return the unperformed operation to the queue, since failing the
+                    // operation initialization means that it was not stored in the database.
+                    self.tx_queue.return_popped(tx);
+                });
+            }
         }
 
         // Commit the next operations (if any).
@@ -646,6 +659,7 @@ pub fn start_eth_sender(
     panic_notify: mpsc::Sender<bool>,
     op_notify_sender: mpsc::Sender<Operation>,
     send_requst_receiver: mpsc::Receiver<Operation>,
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
     config_options: ConfigurationOptions,
 ) {
     std::thread::Builder::new()
@@ -667,6 +681,7 @@ pub fn start_eth_sender(
                 ethereum,
                 send_requst_receiver,
                 op_notify_sender,
+                exodus_mode,
             );
             runtime.block_on(eth_sender.run());
         })
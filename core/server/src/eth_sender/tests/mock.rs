@@ -5,6 +5,7 @@ use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, VecDeque};
 // External uses
 use futures::channel::mpsc;
+use tokio::sync::watch;
 use web3::contract::{tokens::Tokenize, Options};
 use web3::types::{H256, U256};
 // Workspace uses
@@ -399,7 +400,16 @@ fn build_eth_sender(
         tx_poll_period: Default::default(),
     };
 
-    let eth_sender = ETHSender::new(options, db, ethereum, operation_receiver, notify_sender);
+    let (_, exodus_mode) = watch::channel(None);
+
+    let eth_sender = ETHSender::new(
+        options,
+        db,
+        ethereum,
+        operation_receiver,
+        notify_sender,
+        exodus_mode,
+    );
 
     (eth_sender, operation_sender, notify_receiver)
 }
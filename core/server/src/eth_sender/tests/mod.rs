@@ -1,6 +1,7 @@
 // External uses
+use tokio::sync::watch;
 // Workspace uses
-use models::ethereum::ETHOperation;
+use models::{ethereum::ETHOperation, ExodusMode};
 // Local uses
 use self::mock::{
     concurrent_eth_sender, create_signed_tx, create_signed_withdraw_tx, default_eth_sender,
@@ -265,6 +266,26 @@ fn operation_commitment_workflow() {
     );
 }
 
+/// Checks that no new transactions are sent once the contract has entered the exodus mode,
+/// since the contract would reject them.
+#[test]
+fn exodus_mode_stops_sending() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+
+    let (_, exodus_mode) = watch::channel(Some(ExodusMode {
+        last_verified_block: 0,
+    }));
+    eth_sender.exodus_mode = exodus_mode;
+
+    sender.try_send(test_data::commit_operation(0)).unwrap();
+    eth_sender.retrieve_operations();
+    eth_sender.proceed_next_operations();
+
+    // The operation stays in the queue and nothing is sent to the Ethereum.
+    assert!(eth_sender.ongoing_ops.is_empty());
+    assert!(eth_sender.tx_queue.pop_front().is_some());
+}
+
 /// A simple scenario for a stuck transaction:
 /// - A transaction is sent to the Ethereum.
 /// - It is not processed after some blocks.
@@ -1,12 +1,15 @@
 //! Ethereum watcher polls the Ethereum node for new events
-//! such as PriorityQueue events or NewToken events.
+//! such as PriorityQueue events, NewToken events or MinterStatusUpdate events.
 //! New events are accepted to the zkSync network once they have the sufficient amount of confirmations.
 //!
+//! Ethereum watcher also checks whether the contract has entered the exodus mode, and broadcasts
+//! it to the actors that have to stop processing transactions and sending blocks to the contract.
+//!
 //! Poll interval is configured using the `ETH_POLL_INTERVAL` constant.
 //! Number of confirmations is configured using the `CONFIRMATIONS_FOR_ETH_EVENT` environment variable.
 
 // Built-in deps
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 // External uses
 use failure::format_err;
@@ -23,10 +26,14 @@ use models::abi::{eip1271_contract, governance_contract, zksync_contract};
 use models::config_options::ConfigurationOptions;
 use models::misc::constants::EIP1271_SUCCESS_RETURN_VALUE;
 use models::node::tx::EIP1271Signature;
-use models::node::{Nonce, PriorityOp, PubKeyHash, Token, TokenId};
+use models::node::{
+    BlockNumber, CollectionId, MinterStatusEvent, Nonce, PriorityOp, PubKeyHash, Token,
+    TokenAddedEvent,
+};
 use models::params::PRIORITY_EXPIRATION;
+use models::ExodusMode;
 use storage::ConnectionPool;
-use tokio::{runtime::Runtime, time};
+use tokio::{runtime::Runtime, sync::watch, time};
 use web3::transports::EventLoopHandle;
 
 type EthBlockId = u64;
@@ -38,6 +45,11 @@ pub enum EthWatchRequest {
         pubkey_hash: PubKeyHash,
         resp: oneshot::Sender<bool>,
     },
+    IsMinterAuthorized {
+        address: Address,
+        collection_id: CollectionId,
+        resp: oneshot::Sender<bool>,
+    },
     GetPriorityQueueOps {
         op_start_id: u64,
         max_chunks: usize,
@@ -52,6 +64,9 @@ pub enum EthWatchRequest {
         signature: EIP1271Signature,
         resp: oneshot::Sender<Result<bool, failure::Error>>,
     },
+    GetExodusMode {
+        resp: oneshot::Sender<Option<ExodusMode>>,
+    },
 }
 
 pub struct EthWatch<T: Transport> {
@@ -67,15 +82,22 @@ pub struct EthWatch<T: Transport> {
     number_of_confirmations_for_event: u64,
 
     eth_watch_req: mpsc::Receiver<EthWatchRequest>,
+    /// Channel to broadcast the exodus mode to the actors once the contract enters it.
+    exodus_mode_notify: watch::Sender<Option<ExodusMode>>,
+    /// Channel to broadcast the authorized minters to the state keeper whenever they change.
+    minters_notify: watch::Sender<HashSet<(CollectionId, Address)>>,
 }
 
 /// Gathered state of the Ethereum network.
-/// Contains information about the known token types and incoming
+/// Contains information about the registered collections, authorized minters and incoming
 /// priority operations (such as `Deposit` and `FullExit`).
 #[derive(Debug)]
 pub struct ETHState {
-    /// Tokens known to zkSync.
-    pub tokens: HashMap<TokenId, Address>,
+    /// ERC-721 collections registered in the governance contract.
+    pub tokens: HashMap<CollectionId, Address>,
+    /// Accounts allowed by the governance contract to mint tokens inside the rollup,
+    /// with the collection they mint.
+    pub minters: HashSet<(CollectionId, Address)>,
     /// Queue of priority operations that are accepted by Ethereum network,
     /// but not yet have enough confirmations to be processed by zkSync.
     ///
@@ -87,12 +109,22 @@ pub struct ETHState {
     /// Queue of priority operations that passed the confirmation
     /// threshold and are waiting to be executed.
     pub priority_queue: HashMap<u64, PriorityOp>,
+    /// Set once the contract has entered the exodus mode, which can not be left.
+    pub exodus_mode: Option<ExodusMode>,
 }
 
 impl ETHState {
-    fn add_new_token(&mut self, id: TokenId, address: Address) {
+    fn add_new_token(&mut self, id: CollectionId, address: Address) {
         self.tokens.insert(id, address);
     }
+
+    fn update_minter(&mut self, event: MinterStatusEvent) {
+        if event.is_active {
+            self.minters.insert((event.collection_id, event.address));
+        } else {
+            self.minters.remove(&(event.collection_id, event.address));
+        }
+    }
 }
 
 impl<T: Transport> EthWatch<T> {
@@ -104,6 +136,8 @@ impl<T: Transport> EthWatch<T> {
         zksync_contract_addr: H160,
         number_of_confirmations_for_event: u64,
         eth_watch_req: mpsc::Receiver<EthWatchRequest>,
+        exodus_mode_notify: watch::Sender<Option<ExodusMode>>,
+        minters_notify: watch::Sender<HashSet<(CollectionId, Address)>>,
     ) -> Self {
         let gov_contract = {
             (
@@ -125,14 +159,18 @@ impl<T: Transport> EthWatch<T> {
             last_ethereum_block: 0,
             eth_state: ETHState {
                 tokens: HashMap::new(),
+                minters: HashSet::new(),
                 unconfirmed_queue: Vec::new(),
                 priority_queue: HashMap::new(),
+                exodus_mode: None,
             },
             web3,
             _web3_event_loop_handle: web3_event_loop_handle,
             db_pool,
             eth_watch_req,
             number_of_confirmations_for_event,
+            exodus_mode_notify,
+            minters_notify,
         }
     }
 
@@ -155,6 +193,21 @@ impl<T: Transport> EthWatch<T> {
             .build()
     }
 
+    fn get_minter_status_event_filter(&self, from: BlockNumber, to: BlockNumber) -> Filter {
+        let minter_status_event_topic = self
+            .gov_contract
+            .0
+            .event("MinterStatusUpdate")
+            .expect("gov contract abi error")
+            .signature();
+        FilterBuilder::default()
+            .address(vec![self.gov_contract.1.address()])
+            .from_block(from)
+            .to_block(to)
+            .topics(Some(vec![minter_status_event_topic]), None, None, None)
+            .build()
+    }
+
     fn get_priority_op_event_filter(&self, from: BlockNumber, to: BlockNumber) -> Filter {
         let priority_op_event_topic = self
             .zksync_contract
@@ -170,6 +223,47 @@ impl<T: Transport> EthWatch<T> {
             .build()
     }
 
+    async fn get_new_token_events(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<TokenAddedEvent>, failure::Error> {
+        let filter = self.get_new_token_event_filter(from, to);
+        self.web3
+            .eth()
+            .logs(filter)
+            .compat()
+            .await?
+            .into_iter()
+            .map(|event| {
+                TokenAddedEvent::try_from(event).map_err(|e| {
+                    format_err!("Failed to parse token added event log from ETH: {:?}", e)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the minter status updates in the order they were emitted.
+    async fn get_minter_status_events(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<MinterStatusEvent>, failure::Error> {
+        let filter = self.get_minter_status_event_filter(from, to);
+        self.web3
+            .eth()
+            .logs(filter)
+            .compat()
+            .await?
+            .into_iter()
+            .map(|event| {
+                MinterStatusEvent::try_from(event).map_err(|e| {
+                    format_err!("Failed to parse minter status event log from ETH: {:?}", e)
+                })
+            })
+            .collect()
+    }
+
     /// Filters and parses the priority operation events from the Ethereum
     /// within the provided range of blocks.
     /// Returns the list of priority operations together with the block
@@ -260,6 +354,32 @@ impl<T: Transport> EthWatch<T> {
             .await
             .expect("Failed to restore pending queue events from ETH");
 
+        // restore collections
+        let token_events = self
+            .get_new_token_events(
+                BlockNumber::Earliest,
+                BlockNumber::Number(new_block_with_accepted_events.into()),
+            )
+            .await
+            .expect("Failed to restore token list from ETH");
+        for token in token_events.into_iter() {
+            self.eth_state.add_new_token(token.id, token.address);
+        }
+
+        // restore minters
+        let minter_events = self
+            .get_minter_status_events(
+                BlockNumber::Earliest,
+                BlockNumber::Number(new_block_with_accepted_events.into()),
+            )
+            .await
+            .expect("Failed to restore minter list from ETH");
+        for minter in minter_events.into_iter() {
+            self.eth_state.update_minter(minter);
+        }
+        self.notify_minters()
+            .expect("Failed to notify the minter list");
+
         // restore priority queue
         let prior_queue_events = self
             .get_priority_op_events(
@@ -300,6 +420,34 @@ impl<T: Transport> EthWatch<T> {
                 .insert(priority_op.serial_id, priority_op);
         }
 
+        // Get new collections
+        let token_events = self
+            .get_new_token_events(
+                BlockNumber::Number(previous_block_with_accepted_events.into()),
+                BlockNumber::Number(new_block_with_accepted_events.into()),
+            )
+            .await?;
+        for token in token_events.into_iter() {
+            debug!("New collection: {:?}", token);
+            self.eth_state.add_new_token(token.id, token.address);
+        }
+
+        // Get minter updates
+        let minter_events = self
+            .get_minter_status_events(
+                BlockNumber::Number(previous_block_with_accepted_events.into()),
+                BlockNumber::Number(new_block_with_accepted_events.into()),
+            )
+            .await?;
+        let minters_updated = !minter_events.is_empty();
+        for minter in minter_events.into_iter() {
+            debug!("Minter status update: {:?}", minter);
+            self.eth_state.update_minter(minter);
+        }
+        if minters_updated {
+            self.notify_minters()?;
+        }
+
         // Get new pending ops
         self.update_unconfirmed_queue(current_eth_block).await?;
 
@@ -310,18 +458,17 @@ impl<T: Transport> EthWatch<T> {
     }
 
     fn commit_state(&self) {
-        // TODO ADE : to be checked
-        /*self.db_pool
-        .access_storage()
-        .map(|storage| {
-            for (&id, &address) in &self.eth_state.tokens {
-                let token = Token::new(id, address, &format!("ERC20-{}", id));
-                if let Err(e) = storage.tokens_schema().store_token(token) {
-                    warn!("Failed to add token to db: {:?}", e);
+        self.db_pool
+            .access_storage()
+            .map(|storage| {
+                for (&id, &address) in &self.eth_state.tokens {
+                    let token = Token::new(id, address, &format!("ERC721-{}", id));
+                    if let Err(e) = storage.tokens_schema().store_token(token) {
+                        warn!("Failed to add token to db: {:?}", e);
+                    }
                 }
-            }
-        })
-        .unwrap_or_default();*/
+            })
+            .unwrap_or_default();
     }
 
     fn get_priority_requests(&self, first_serial_id: u64, max_chunks: usize) -> Vec<PriorityOp> {
@@ -387,6 +534,57 @@ impl<T: Transport> EthWatch<T> {
         Ok(auth_fact.as_slice() == tiny_keccak::keccak256(&pub_key_hash.data[..]))
     }
 
+    /// Returns the exodus mode state if the contract has entered it.
+    async fn get_exodus_mode(&self) -> Result<Option<ExodusMode>, failure::Error> {
+        let exodus_mode: bool = self
+            .zksync_contract
+            .1
+            .query("exodusMode", (), None, Options::default(), None)
+            .compat()
+            .await
+            .map_err(|e| format_err!("Failed to query contract exodusMode: {}", e))?;
+        if !exodus_mode {
+            return Ok(None);
+        }
+
+        let last_verified_block: u64 = self
+            .zksync_contract
+            .1
+            .query("totalBlocksVerified", (), None, Options::default(), None)
+            .compat()
+            .await
+            .map_err(|e| format_err!("Failed to query contract totalBlocksVerified: {}", e))?;
+        Ok(Some(ExodusMode {
+            last_verified_block: last_verified_block as BlockNumber,
+        }))
+    }
+
+    /// Broadcasts the authorized minters to the state keeper.
+    fn notify_minters(&self) -> Result<(), failure::Error> {
+        self.minters_notify
+            .broadcast(self.eth_state.minters.clone())
+            .map_err(|_| format_err!("All minters receivers dropped"))
+    }
+
+    /// Checks whether the contract has entered the exodus mode and notifies the actors if so.
+    async fn update_exodus_mode(&mut self) -> Result<(), failure::Error> {
+        if self.eth_state.exodus_mode.is_some() {
+            return Ok(());
+        }
+
+        if let Some(exodus_mode) = self.get_exodus_mode().await? {
+            error!(
+                "Contract entered the exodus mode, last verified block: {}",
+                exodus_mode.last_verified_block
+            );
+            self.eth_state.exodus_mode = Some(exodus_mode);
+            self.exodus_mode_notify
+                .broadcast(Some(exodus_mode))
+                .map_err(|_| format_err!("All exodus mode receivers dropped"))?;
+        }
+        Ok(())
+    }
+
     pub async fn run(mut self) {
         let block = self
             .web3
@@ -399,6 +597,9 @@ impl<T: Transport> EthWatch<T> {
         self.last_ethereum_block = block;
         self.restore_state_from_eth(block.saturating_sub(self.number_of_confirmations_for_event))
             .await;
+        self.update_exodus_mode()
+            .await
+            .expect("Failed to check exodus mode of the contract");
 
         while let Some(request) = self.eth_watch_req.next().await {
             match request {
@@ -416,6 +617,10 @@ impl<T: Transport> EthWatch<T> {
                             .map_err(|e| warn!("Failed to process new blocks {}", e))
                             .unwrap_or_default();
                         self.commit_state();
+                        self.update_exodus_mode()
+                            .await
+                            .map_err(|e| warn!("Failed to check exodus mode {}", e))
+                            .unwrap_or_default();
                     }
                 }
                 EthWatchRequest::GetPriorityQueueOps {
@@ -442,6 +647,14 @@ impl<T: Transport> EthWatch<T> {
                         .unwrap_or(false);
                     resp.send(authorized).unwrap_or_default();
                 }
+                EthWatchRequest::IsMinterAuthorized {
+                    address,
+                    collection_id,
+                    resp,
+                } => {
+                    resp.send(self.eth_state.minters.contains(&(collection_id, address)))
+                        .unwrap_or_default();
+                }
                 EthWatchRequest::CheckEIP1271Signature {
                     address,
                     message,
@@ -454,6 +667,9 @@ impl<T: Transport> EthWatch<T> {
 
                     resp.send(signature_correct).unwrap_or_default();
                 }
+                EthWatchRequest::GetExodusMode { resp } => {
+                    resp.send(self.eth_state.exodus_mode).unwrap_or_default();
+                }
             }
         }
     }
@@ -464,6 +680,8 @@ pub fn start_eth_watch(
     config_options: ConfigurationOptions,
     eth_req_sender: mpsc::Sender<EthWatchRequest>,
     eth_req_receiver: mpsc::Receiver<EthWatchRequest>,
+    exodus_mode_notify: watch::Sender<Option<ExodusMode>>,
+    minters_notify: watch::Sender<HashSet<(CollectionId, Address)>>,
     runtime: &Runtime,
 ) {
     let (web3_event_loop_handle, transport) =
@@ -478,6 +696,8 @@ pub fn start_eth_watch(
         config_options.contract_eth_addr,
         config_options.confirmations_for_eth_event,
         eth_req_receiver,
+        exodus_mode_notify,
+        minters_notify,
     );
     runtime.spawn(eth_watch.run());
 
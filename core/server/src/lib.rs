@@ -13,6 +13,7 @@ pub mod eth_watch;
 pub mod leader_election;
 pub mod mempool;
 pub mod observer_mode;
+pub mod order_book;
 pub mod prover_server;
 pub mod signature_checker;
 pub mod state_keeper;
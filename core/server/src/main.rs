@@ -1,10 +1,11 @@
 // Built-in deps
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::time::Duration;
 // External uses
 use clap::{App, Arg};
 use futures::{channel::mpsc, executor::block_on, SinkExt, StreamExt};
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, sync::watch};
 use web3::types::H160;
 // Workspace uses
 use models::{
@@ -26,6 +27,7 @@ use server::{
     leader_election,
     mempool::run_mempool_task,
     observer_mode,
+    order_book::run_order_book_task,
     prover_server::start_prover_server,
     state_keeper::{start_state_keeper, PlasmaStateKeeper},
 };
@@ -139,11 +141,15 @@ fn main() {
     }
 
     let (eth_watch_req_sender, eth_watch_req_receiver) = mpsc::channel(256);
+    let (exodus_mode_sender, exodus_mode_receiver) = watch::channel(None);
+    let (minters_sender, minters_receiver) = watch::channel(HashSet::new());
     start_eth_watch(
         connection_pool.clone(),
         config_opts.clone(),
         eth_watch_req_sender.clone(),
         eth_watch_req_receiver,
+        exodus_mode_sender,
+        minters_sender,
         &main_runtime,
     );
 
@@ -151,6 +157,7 @@ fn main() {
     let (state_keeper_req_sender, state_keeper_req_receiver) = mpsc::channel(256);
     let (executed_tx_notify_sender, executed_tx_notify_receiver) = mpsc::channel(256);
     let (mempool_request_sender, mempool_request_receiver) = mpsc::channel(256);
+    let (order_book_request_sender, order_book_request_receiver) = mpsc::channel(256);
     let state_keeper = PlasmaStateKeeper::new(
         observer_mode_final_state.state_keeper_init,
         config_opts.operator_franklin_addr,
@@ -158,6 +165,8 @@ fn main() {
         proposed_blocks_sender,
         executed_tx_notify_sender,
         config_opts.available_block_chunk_sizes.clone(),
+        exodus_mode_receiver.clone(),
+        minters_receiver,
     );
     start_state_keeper(state_keeper, &main_runtime);
 
@@ -168,6 +177,7 @@ fn main() {
         stop_signal_sender.clone(),
         zksync_commit_notify_sender.clone(), // eth sender sends only verify blocks notifications
         eth_send_request_receiver,
+        exodus_mode_receiver.clone(),
         config_opts.clone(),
     );
 
@@ -176,6 +186,8 @@ fn main() {
         eth_send_request_sender,
         zksync_commit_notify_sender, // commiter sends only commit block notifications
         mempool_request_sender.clone(),
+        order_book_request_sender.clone(),
+        exodus_mode_receiver.clone(),
         connection_pool.clone(),
         &main_runtime,
     );
@@ -184,6 +196,7 @@ fn main() {
         connection_pool.clone(),
         stop_signal_sender.clone(),
         mempool_request_sender.clone(),
+        order_book_request_sender,
         executed_tx_notify_receiver,
         state_keeper_req_sender.clone(),
         eth_watch_req_sender.clone(),
@@ -201,10 +214,17 @@ fn main() {
         observer_mode_final_state.circuit_tree_block,
     );
 
+    run_order_book_task(
+        connection_pool.clone(),
+        order_book_request_receiver,
+        mempool_request_sender.clone(),
+        &main_runtime,
+    );
     run_mempool_task(
         connection_pool,
         mempool_request_receiver,
         eth_watch_req_sender,
+        exodus_mode_receiver,
         &config_opts,
         &main_runtime,
     );
@@ -1,33 +1,62 @@
 //! Mempool is simple in memory buffer for transactions.
 //!
 //! Its role is to:
-//! 1) Accept transactions from api, check signatures and basic nonce correctness(nonce not too small).
+//! 1) Accept transactions from api, check signatures and basic nonce correctness(nonce not too small,
+//! no gaps between the nonces of the account transactions).
 //! To do nonce correctness check mempool stores mapping `AccountAddress -> Nonce`, this mapping is updated
 //! when new block is committed.
+//! Transfers, batch transfers, swaps and withdrawals are also checked to move tokens the sender will own
+//! at the moment of execution, according to the committed token owners and the token moves of the
+//! not yet committed transactions.
 //! 2) When polled return vector of the transactions in the queue.
 //!
-//! Mempool is not persisted on disc, all transactions will be lost on node shutdown.
+//! Transactions are queued per account and ordered by nonce. A transaction with the nonce of an
+//! already queued one replaces it, while the nonces of the transactions already proposed for a block
+//! can not be reused until the block is committed, then the nonces of the failed ones are free again.
+//! Block proposal only takes transactions with contiguous nonces starting from the
+//! expected nonce of the account, serving the accounts in the order of their oldest transaction.
+//! Transactions waiting for longer than `MEMPOOL_TX_TTL` are dropped, and the total amount of the queued
+//! transactions is capped by `MEMPOOL_MAX_TXS`.
+//!
+//! Sponsored transfers and withdrawals are limited by the fee quota of their sponsor: fees of the txs
+//! of a sponsor accepted within `SPONSOR_QUOTA_PERIOD` can not exceed the quota configured for it
+//! in `SPONSOR_FEE_QUOTAS` in total, accounts without a quota can not sponsor txs.
+//! Fees of the txs dropped from the mempool or failed in a block are returned to the quota.
+//!
+//! Accepted transactions are persisted in the `mempool_txs` table and removed from there
+//! once they are included into a committed block, so they survive the node restart.
 //!
 //! Communication channel with other actors:
 //! Mempool does not push information to other actors, only accepts requests. (see `MempoolRequest`)
 //!
 //! Communication with db:
-//! on restart mempool restores nonces of the accounts that are stored in the account tree.
+//! on restart mempool restores nonces of the accounts that are stored in the account tree
+//! and reloads the stored transactions, dropping the ones whose nonce is already used.
+//! Usage of the sponsor quotas is rebuilt from the stored transactions and the sponsored
+//! transactions executed within the quota period.
+//!
+//! Once the contract enters the exodus mode (see `eth_watch`), mempool rejects new transactions
+//! and proposes only empty blocks.
 
 // Built-in deps
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
 // External uses
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 use failure::Fail;
 use futures::{
     channel::{mpsc, oneshot},
     SinkExt, StreamExt,
 };
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, sync::watch};
 // Workspace uses
+use models::node::tx::TxHash;
 use models::node::{
-    AccountId, AccountUpdate, AccountUpdates, Address, FranklinTx, Nonce, PriorityOp, TransferOp,
-    TransferToNewOp,
+    AccountId, AccountUpdate, AccountUpdates, Address, FranklinTx, Nft, Nonce, PriorityOp,
+    TransferOp, TransferToNewOp,
 };
+use models::ExodusMode;
 use storage::ConnectionPool;
 // Local uses
 use crate::{eth_watch::EthWatchRequest, signature_checker::VerifiedTx};
@@ -38,6 +67,27 @@ pub enum TxAddError {
     #[fail(display = "Tx nonce is too low.")]
     NonceMismatch,
 
+    #[fail(display = "Tx nonce is too high, txs with the previous nonces are missing")]
+    NonceGap,
+
+    #[fail(display = "Tx is already in the mempool")]
+    Duplicate,
+
+    #[fail(display = "Mempool is full")]
+    MempoolFull,
+
+    #[fail(display = "Token is not owned by the sender")]
+    TokenNotOwned,
+
+    #[fail(display = "Tx validity window has ended")]
+    TxExpired,
+
+    #[fail(display = "Sponsor fee quota is exceeded")]
+    SponsorQuotaExceeded,
+
+    #[fail(display = "Tx fee is lower than the expected one, see `get_tx_fee`")]
+    FeeTooLow,
+
     #[fail(display = "Tx is incorrect")]
     IncorrectTx,
 
@@ -53,6 +103,12 @@ pub enum TxAddError {
     #[fail(display = "Change pubkey tx is not authorized onchain")]
     ChangePkNotAuthorized,
 
+    #[fail(display = "Mint tx is not authorized onchain")]
+    MinterNotAuthorized,
+
+    #[fail(display = "Network is in exodus mode, transactions are not accepted")]
+    ExodusMode,
+
     #[fail(display = "Internal error")]
     Other,
 }
@@ -79,20 +135,143 @@ pub enum MempoolRequest {
     /// for correctness (including its Ethereum and ZKSync signatures).
     /// oneshot is used to receive tx add result.
     NewTx(Box<VerifiedTx>, oneshot::Sender<Result<(), TxAddError>>),
-    /// When block is committed, nonces of the account tree should be updated too.
-    UpdateNonces(AccountUpdates),
+    /// When block is committed, nonces of the account tree should be updated too,
+    /// and the txs of the block, executed or failed, are no longer pending.
+    BlockCommitted {
+        updates: AccountUpdates,
+        executed_txs: Vec<TxHash>,
+        failed_txs: Vec<TxHash>,
+    },
     /// Get transactions from the mempool.
     GetBlock(GetBlockRequest),
+    /// Get the fee quota of the sponsor account and its usage.
+    GetSponsorQuota(AccountId, oneshot::Sender<SponsorQuota>),
+}
+
+/// Fee quota of a sponsor, amounts are in wei.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsorQuota {
+    pub quota: BigDecimal,
+    /// Fees of the sponsored txs accepted within the last quota period.
+    pub used: BigDecimal,
+    pub remaining: BigDecimal,
+    /// Length of the quota period in seconds.
+    pub period: u64,
+}
+
+/// Transaction waiting in the mempool.
+struct MempoolTx {
+    tx: FranklinTx,
+    received_at: Instant,
+}
+
+/// Token move made by a tx that is queued or proposed for a block, but not committed yet.
+struct PendingMove {
+    tx_hash: TxHash,
+    from: Address,
+    // `None` if the token leaves the rollup
+    to: Option<Address>,
+}
+
+/// Fee of a sponsored tx counted against the quota of its sponsor.
+struct SponsoredFee {
+    tx_hash: TxHash,
+    amount: BigDecimal,
+    // wall clock time, so the fees of the txs executed before the restart are restored
+    accepted_at: DateTime<Utc>,
+}
+
+/// Returns the sponsor of the tx along with the fee it pays.
+fn sponsored_fee(tx: &FranklinTx) -> Option<(AccountId, BigDecimal)> {
+    let (sponsor, fee) = match tx {
+        FranklinTx::Transfer(tx) => (tx.sponsor.as_ref()?, &tx.fee),
+        FranklinTx::Withdraw(tx) => (tx.sponsor.as_ref()?, &tx.fee),
+        _ => return None,
+    };
+    Some((sponsor.account_id, fee.clone()))
+}
+
+/// Returns the second account of the swap along with the nonce of its order.
+fn swap_counterparty(tx: &FranklinTx) -> Option<(Address, Nonce)> {
+    match tx {
+        FranklinTx::Swap(tx) => Some((tx.order_b.account, tx.order_b.nonce)),
+        _ => None,
+    }
+}
+
+/// Returns the tokens moved by the tx along with their moves.
+fn token_moves(tx: &FranklinTx) -> Vec<(Nft, PendingMove)> {
+    let tx_hash = tx.hash();
+    let token_move = |from: Address, to: Option<Address>| PendingMove {
+        tx_hash: tx_hash.clone(),
+        from,
+        to,
+    };
+    match tx {
+        FranklinTx::Transfer(tx) => vec![(tx.nft(), token_move(tx.from, Some(tx.to)))],
+        FranklinTx::BatchTransfer(tx) => tx
+            .tokens
+            .iter()
+            .map(|token| (*token, token_move(tx.from, Some(tx.to))))
+            .collect(),
+        FranklinTx::Swap(tx) => {
+            let (a, b) = (&tx.order_a, &tx.order_b);
+            vec![
+                (a.token_sell, token_move(a.account, Some(b.account))),
+                (b.token_sell, token_move(b.account, Some(a.account))),
+            ]
+        }
+        FranklinTx::Withdraw(tx) => vec![(tx.nft(), token_move(tx.from, None))],
+        FranklinTx::Burn(tx) => vec![(tx.nft(), token_move(tx.from, None))],
+        _ => Vec::new(),
+    }
 }
 
 struct MempoolState {
     // account and last committed nonce
     account_nonces: HashMap<Address, Nonce>,
     account_ids: HashMap<AccountId, Address>,
-    ready_txs: VecDeque<FranklinTx>,
+    // account and the nonces of its txs proposed for a block, until the block is committed
+    proposed_nonces: HashMap<Address, BTreeMap<Nonce, TxHash>>,
+    // txs of each account ordered by nonce
+    account_txs: HashMap<Address, BTreeMap<Nonce, MempoolTx>>,
+    // token and its committed owner
+    token_owners: HashMap<Nft, Address>,
+    // token and the moves of the not committed txs, in the order of admission
+    pending_moves: HashMap<Nft, Vec<PendingMove>>,
+    // sponsor and the fees of its txs accepted within the quota period
+    sponsored_fees: HashMap<AccountId, Vec<SponsoredFee>>,
+    txs_count: usize,
+    max_txs: usize,
+    tx_ttl: Duration,
+    sponsor_fee_quotas: HashMap<AccountId, BigDecimal>,
+    sponsor_quota_period: Duration,
 }
 
 impl MempoolState {
+    fn new(
+        max_txs: usize,
+        tx_ttl: Duration,
+        sponsor_fee_quotas: HashMap<AccountId, BigDecimal>,
+        sponsor_quota_period: Duration,
+    ) -> Self {
+        Self {
+            account_nonces: HashMap::new(),
+            account_ids: HashMap::new(),
+            proposed_nonces: HashMap::new(),
+            account_txs: HashMap::new(),
+            token_owners: HashMap::new(),
+            pending_moves: HashMap::new(),
+            sponsored_fees: HashMap::new(),
+            txs_count: 0,
+            max_txs,
+            tx_ttl,
+            sponsor_fee_quotas,
+            sponsor_quota_period,
+        }
+    }
+
     fn chunks_for_tx(&self, tx: &FranklinTx) -> usize {
         match tx {
             FranklinTx::Transfer(tx) => {
@@ -106,7 +285,13 @@ impl MempoolState {
         }
     }
 
-    fn restore_from_db(db_pool: &ConnectionPool) -> Self {
+    fn restore_from_db(
+        db_pool: &ConnectionPool,
+        max_txs: usize,
+        tx_ttl: Duration,
+        sponsor_fee_quotas: HashMap<AccountId, BigDecimal>,
+        sponsor_quota_period: Duration,
+    ) -> Self {
         let storage = db_pool.access_storage().expect("mempool db restore");
         let (_, accounts) = storage
             .chain()
@@ -114,48 +299,549 @@ impl MempoolState {
             .load_committed_state(None)
             .expect("mempool account state load");
 
-        let mut account_ids = HashMap::new();
-        let mut account_nonces = HashMap::new();
-
+        let mut mempool_state =
+            Self::new(max_txs, tx_ttl, sponsor_fee_quotas, sponsor_quota_period);
         for (id, account) in accounts {
-            account_ids.insert(id, account.address.clone());
-            account_nonces.insert(account.address, account.nonce);
+            for token in account.get_tokens() {
+                mempool_state.token_owners.insert(token, account.address);
+            }
+            mempool_state.account_ids.insert(id, account.address);
+            mempool_state
+                .account_nonces
+                .insert(account.address, account.nonce);
         }
 
-        Self {
-            account_nonces,
-            account_ids,
-            ready_txs: VecDeque::new(),
+        let stored_txs = storage
+            .mempool_schema()
+            .load_txs()
+            .expect("mempool txs load");
+        let mut stale_hashes = Vec::new();
+        for tx in stored_txs {
+            match mempool_state.check_tx(&tx) {
+                Ok(()) => {
+                    if let Some(replaced) = mempool_state.insert_tx(tx) {
+                        stale_hashes.push(replaced.hash());
+                    }
+                }
+                Err(_) => stale_hashes.push(tx.hash()),
+            }
         }
+        if !stale_hashes.is_empty() {
+            info!("Removing {} stale txs from the mempool", stale_hashes.len());
+            storage
+                .mempool_schema()
+                .remove_txs(&stale_hashes)
+                .expect("mempool stale txs removal");
+        }
+
+        let executed_txs = storage
+            .chain()
+            .operations_schema()
+            .load_executed_txs_since(mempool_state.quota_period_start().naive_utc())
+            .expect("mempool executed txs load");
+        for executed in executed_txs {
+            let tx: FranklinTx =
+                serde_json::from_value(executed.tx).expect("Unparsable executed tx");
+            mempool_state.add_sponsored_fee(&tx, DateTime::from_utc(executed.created_at, Utc));
+        }
+
+        mempool_state
     }
 
     fn nonce(&self, address: &Address) -> Nonce {
         *self.account_nonces.get(address).unwrap_or(&0)
     }
 
-    fn add_tx(&mut self, tx: FranklinTx) -> Result<(), TxAddError> {
-        // Correctness should be checked by `signature_checker`, thus
-        // `tx.check_correctness()` is not invoked here.
+    /// Returns the nonce the next executed tx of the account is expected to have:
+    /// the committed nonce, or the one following the txs already proposed for a block.
+    fn expected_nonce(&self, address: &Address) -> Nonce {
+        let nonce = self.nonce(address);
+        self.proposed_nonces
+            .get(address)
+            .and_then(|nonces| nonces.keys().next_back())
+            .map_or(nonce, |proposed_nonce| nonce.max(proposed_nonce + 1))
+    }
+
+    /// Returns the nonce the next tx of the account is expected to have,
+    /// taking into account the txs which are queued or already proposed for a block.
+    fn next_nonce(&self, address: &Address) -> Nonce {
+        let expected_nonce = self.expected_nonce(address);
+        self.account_txs
+            .get(address)
+            .and_then(|txs| txs.keys().next_back())
+            .map_or(expected_nonce, |nonce| expected_nonce.max(nonce + 1))
+    }
+
+    /// Checks that the tx can be put into the queue of its account.
+    fn check_tx(&self, tx: &FranklinTx) -> Result<(), TxAddError> {
+        let address = tx.account();
+        let nonce = tx.nonce();
+        // Nonces of the proposed txs are used as well, so a proposed tx can not be replaced.
+        if nonce < self.expected_nonce(&address) {
+            return Err(TxAddError::NonceMismatch);
+        }
+        if tx.is_expired(chrono::Utc::now().timestamp() as u32) {
+            return Err(TxAddError::TxExpired);
+        }
+        // The swap is queued by its first account, the nonce of the second one is only checked
+        // not to be used already.
+        if let FranklinTx::Swap(tx) = tx {
+            if tx.order_b.nonce < self.expected_nonce(&tx.order_b.account) {
+                return Err(TxAddError::NonceMismatch);
+            }
+        }
 
-        if tx.nonce() >= self.nonce(&tx.account()) {
-            self.ready_txs.push_back(tx);
-            Ok(())
+        let queued = self
+            .account_txs
+            .get(&address)
+            .and_then(|txs| txs.get(&nonce));
+        let replaced_tx_hash = match queued {
+            Some(queued) if queued.tx.hash() == tx.hash() => return Err(TxAddError::Duplicate),
+            // Tx with the same nonce is replaced, so the mempool does not grow.
+            Some(queued) => Some(queued.tx.hash()),
+            None if nonce > self.next_nonce(&address) => return Err(TxAddError::NonceGap),
+            None if self.txs_count >= self.max_txs => return Err(TxAddError::MempoolFull),
+            None => None,
+        };
+
+        for (token, token_move) in token_moves(tx) {
+            if self.token_owner(&token, replaced_tx_hash.as_ref()) != Some(token_move.from) {
+                return Err(TxAddError::TokenNotOwned);
+            }
+        }
+
+        if let Some((sponsor, fee)) = sponsored_fee(tx) {
+            let used = self.sponsor_fees_used(sponsor, replaced_tx_hash.as_ref());
+            if used + fee > self.sponsor_fee_quota(sponsor) {
+                return Err(TxAddError::SponsorQuotaExceeded);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fee quota configured for the sponsor, zero if it can not sponsor txs.
+    fn sponsor_fee_quota(&self, sponsor: AccountId) -> BigDecimal {
+        self.sponsor_fee_quotas
+            .get(&sponsor)
+            .cloned()
+            .unwrap_or_else(|| BigDecimal::from(0))
+    }
+
+    /// Returns the moment the current quota period started at.
+    fn quota_period_start(&self) -> DateTime<Utc> {
+        Utc::now()
+            - chrono::Duration::from_std(self.sponsor_quota_period)
+                .expect("sponsor quota period is out of range")
+    }
+
+    /// Counts the fee of the sponsored tx accepted at the given moment against the quota
+    /// of its sponsor.
+    fn add_sponsored_fee(&mut self, tx: &FranklinTx, accepted_at: DateTime<Utc>) {
+        let tx_hash = tx.hash();
+        if let Some((sponsor, amount)) = sponsored_fee(tx) {
+            let fees = self.sponsored_fees.entry(sponsor).or_default();
+            if fees.iter().all(|fee| fee.tx_hash != tx_hash) {
+                fees.push(SponsoredFee {
+                    tx_hash,
+                    amount,
+                    accepted_at,
+                });
+            }
+        }
+    }
+
+    /// Returns the total fee of the sponsor txs accepted within the quota period,
+    /// except for the one of `ignored_tx_hash`.
+    fn sponsor_fees_used(
+        &self,
+        sponsor: AccountId,
+        ignored_tx_hash: Option<&TxHash>,
+    ) -> BigDecimal {
+        let period_start = self.quota_period_start();
+        self.sponsored_fees
+            .get(&sponsor)
+            .into_iter()
+            .flatten()
+            .filter(|fee| fee.accepted_at > period_start && Some(&fee.tx_hash) != ignored_tx_hash)
+            .fold(BigDecimal::from(0), |used, fee| used + &fee.amount)
+    }
+
+    fn sponsor_quota(&self, sponsor: AccountId) -> SponsorQuota {
+        let quota = self.sponsor_fee_quota(sponsor);
+        let used = self.sponsor_fees_used(sponsor, None);
+        let remaining = if used < quota {
+            quota.clone() - &used
         } else {
-            Err(TxAddError::NonceMismatch)
+            BigDecimal::from(0)
+        };
+        SponsorQuota {
+            quota,
+            used,
+            remaining,
+            period: self.sponsor_quota_period.as_secs(),
+        }
+    }
+
+    /// Returns the fees of the txs dropped from the mempool to the quotas of their sponsors.
+    fn forget_sponsored_fees(&mut self, tx_hashes: &[TxHash]) {
+        for fees in self.sponsored_fees.values_mut() {
+            fees.retain(|fee| !tx_hashes.contains(&fee.tx_hash));
+        }
+        self.sponsored_fees.retain(|_, fees| !fees.is_empty());
+    }
+
+    /// Drops the sponsored fees accepted before the current quota period.
+    fn remove_outdated_sponsored_fees(&mut self) {
+        let period_start = self.quota_period_start();
+        for fees in self.sponsored_fees.values_mut() {
+            fees.retain(|fee| fee.accepted_at > period_start);
+        }
+        self.sponsored_fees.retain(|_, fees| !fees.is_empty());
+    }
+
+    /// Returns the owner of the token after all the pending moves,
+    /// except for the one made by `ignored_tx_hash`.
+    fn token_owner(&self, token: &Nft, ignored_tx_hash: Option<&TxHash>) -> Option<Address> {
+        let last_move = self.pending_moves.get(token).and_then(|moves| {
+            moves
+                .iter()
+                .rev()
+                .find(|pending_move| Some(&pending_move.tx_hash) != ignored_tx_hash)
+        });
+        match last_move {
+            Some(pending_move) => pending_move.to,
+            None => self.token_owners.get(token).cloned(),
+        }
+    }
+
+    /// Forgets the pending moves of the txs dropped from the mempool.
+    fn forget_pending_moves(&mut self, tx_hashes: &[TxHash]) {
+        for moves in self.pending_moves.values_mut() {
+            moves.retain(|pending_move| !tx_hashes.contains(&pending_move.tx_hash));
+        }
+        self.pending_moves.retain(|_, moves| !moves.is_empty());
+    }
+
+    /// Forgets the pending state of the txs dropped from the queues.
+    fn forget_txs(&mut self, tx_hashes: &[TxHash]) {
+        self.txs_count -= tx_hashes.len();
+        self.forget_pending_moves(tx_hashes);
+        self.forget_sponsored_fees(tx_hashes);
+    }
+
+    /// Drops the pending state of the txs settled by the committed block.
+    /// Nonces of the failed txs can be used again, since the account nonce has not changed,
+    /// and their token moves did not happen.
+    fn settle_txs(&mut self, tx_hashes: &[TxHash]) {
+        for nonces in self.proposed_nonces.values_mut() {
+            nonces.retain(|_, tx_hash| !tx_hashes.contains(tx_hash));
+        }
+        self.proposed_nonces.retain(|_, nonces| !nonces.is_empty());
+        self.forget_pending_moves(tx_hashes);
+    }
+
+    /// Puts the checked tx into the queue of its account.
+    /// Returns the tx with the same nonce replaced by the new one, if any.
+    fn insert_tx(&mut self, tx: FranklinTx) -> Option<FranklinTx> {
+        for (token, token_move) in token_moves(&tx) {
+            self.pending_moves
+                .entry(token)
+                .or_default()
+                .push(token_move);
+        }
+        self.add_sponsored_fee(&tx, Utc::now());
+
+        let replaced = self.account_txs.entry(tx.account()).or_default().insert(
+            tx.nonce(),
+            MempoolTx {
+                tx,
+                received_at: Instant::now(),
+            },
+        );
+        match replaced {
+            Some(replaced) => {
+                self.forget_pending_moves(&[replaced.tx.hash()]);
+                self.forget_sponsored_fees(&[replaced.tx.hash()]);
+                Some(replaced.tx)
+            }
+            None => {
+                self.txs_count += 1;
+                None
+            }
+        }
+    }
+
+    /// Forgets the closed account along with its queued txs, which can not be executed anymore.
+    /// The id and the address may later be used by a new account starting from the zero nonce.
+    ///
+    /// Returns the hashes of the dropped txs.
+    fn remove_account(&mut self, id: AccountId, address: &Address) -> Vec<TxHash> {
+        self.account_ids.remove(&id);
+        self.account_nonces.remove(address);
+        self.proposed_nonces.remove(address);
+        let removed_hashes: Vec<_> = self
+            .account_txs
+            .remove(address)
+            .into_iter()
+            .flat_map(|txs| txs.into_iter().map(|(_, queued)| queued.tx.hash()))
+            .collect();
+        self.forget_txs(&removed_hashes);
+        removed_hashes
+    }
+
+    /// Applies the account updates of the committed block and settles its executed and failed txs.
+    ///
+    /// Returns the hashes of the txs dropped from the queues.
+    fn commit_block(
+        &mut self,
+        updates: AccountUpdates,
+        executed_txs: &[TxHash],
+        failed_txs: &[TxHash],
+    ) -> Vec<TxHash> {
+        let mut removed_hashes = Vec::new();
+        for (id, update) in updates {
+            match update {
+                AccountUpdate::Create { address, nonce } => {
+                    self.account_ids.insert(id, address);
+                    self.account_nonces.insert(address, nonce);
+                }
+                AccountUpdate::Delete { address, .. } => {
+                    removed_hashes.extend(self.remove_account(id, &address));
+                }
+                AccountUpdate::AddToken {
+                    new_nonce, token, ..
+                } => {
+                    if let Some(address) = self.account_ids.get(&id) {
+                        if let Some(nonce) = self.account_nonces.get_mut(address) {
+                            *nonce = new_nonce;
+                        }
+                        self.token_owners.insert(token, *address);
+                    }
+                }
+                AccountUpdate::RemoveToken {
+                    new_nonce, token, ..
+                } => {
+                    if let Some(address) = self.account_ids.get(&id) {
+                        if let Some(nonce) = self.account_nonces.get_mut(address) {
+                            *nonce = new_nonce;
+                        }
+                        if self.token_owners.get(&token) == Some(address) {
+                            self.token_owners.remove(&token);
+                        }
+                    }
+                }
+                AccountUpdate::ChangePubKeyHash { new_nonce, .. }
+                | AccountUpdate::ChangeApproval { new_nonce, .. }
+                | AccountUpdate::ChangeApprovalForAll { new_nonce, .. }
+                | AccountUpdate::UpdateBalance { new_nonce, .. } => {
+                    if let Some(address) = self.account_ids.get(&id) {
+                        if let Some(nonce) = self.account_nonces.get_mut(address) {
+                            *nonce = new_nonce;
+                        }
+                    }
+                }
+            }
+        }
+        self.settle_txs(executed_txs);
+        self.settle_txs(failed_txs);
+        // failed txs do not pay the fee
+        self.forget_sponsored_fees(failed_txs);
+        removed_hashes.extend(self.remove_stale_txs());
+        removed_hashes
+    }
+
+    /// Drops the queued txs whose nonces are already used by the committed or proposed txs,
+    /// e.g. by a swap queued by another account.
+    ///
+    /// Returns the hashes of the dropped txs.
+    fn remove_stale_txs(&mut self) -> Vec<TxHash> {
+        let expected_nonces = self
+            .account_txs
+            .keys()
+            .map(|address| (*address, self.expected_nonce(address)))
+            .collect::<HashMap<_, _>>();
+        let mut stale_hashes = Vec::new();
+        for (address, txs) in self.account_txs.iter_mut() {
+            let actual = txs.split_off(&expected_nonces[address]);
+            stale_hashes.extend(txs.values().map(|queued| queued.tx.hash()));
+            *txs = actual;
+        }
+        self.account_txs.retain(|_, txs| !txs.is_empty());
+
+        if !stale_hashes.is_empty() {
+            info!("Removing {} stale txs from the mempool", stale_hashes.len());
+            self.forget_txs(&stale_hashes);
+        }
+        stale_hashes
+    }
+
+    /// Drops the txs that have been waiting longer than the TTL or whose validity window
+    /// has ended, along with the later txs of the same accounts, which cannot be executed
+    /// without them.
+    ///
+    /// Returns the hashes of the dropped txs.
+    fn remove_expired_txs(&mut self) -> Vec<TxHash> {
+        let tx_ttl = self.tx_ttl;
+        let now = chrono::Utc::now().timestamp() as u32;
+        let mut expired_hashes = Vec::new();
+        for txs in self.account_txs.values_mut() {
+            let first_expired = txs
+                .iter()
+                .find(|(_, queued)| {
+                    queued.received_at.elapsed() > tx_ttl || queued.tx.is_expired(now)
+                })
+                .map(|(nonce, _)| *nonce);
+            if let Some(nonce) = first_expired {
+                let expired = txs.split_off(&nonce);
+                expired_hashes.extend(expired.values().map(|queued| queued.tx.hash()));
+            }
+        }
+        self.account_txs.retain(|_, txs| !txs.is_empty());
+
+        if !expired_hashes.is_empty() {
+            info!(
+                "Removing {} expired txs from the mempool",
+                expired_hashes.len()
+            );
+            self.forget_txs(&expired_hashes);
         }
+        expired_hashes
+    }
+
+    /// Takes the txs for the block, serving the accounts in the order of their
+    /// oldest tx. Only the txs with contiguous nonces starting from the expected
+    /// nonce are taken from each account.
+    ///
+    /// Returns: chunks left from `chunks_left`, txs selected
+    fn take_txs_for_block(&mut self, mut chunks_left: usize) -> (usize, Vec<FranklinTx>) {
+        let mut accounts = self
+            .account_txs
+            .iter()
+            .filter_map(|(address, txs)| {
+                txs.values()
+                    .next()
+                    .map(|queued| (queued.received_at, *address))
+            })
+            .collect::<Vec<_>>();
+        accounts.sort();
+
+        let mut txs_for_commit = Vec::new();
+        let mut block_is_full = false;
+        for (_, address) in accounts {
+            let mut expected_nonce = self.expected_nonce(&address);
+            loop {
+                let (nonce, chunks_for_tx, counterparty) = match self
+                    .account_txs
+                    .get(&address)
+                    .and_then(|txs| txs.values().next())
+                {
+                    Some(queued) => (
+                        queued.tx.nonce(),
+                        self.chunks_for_tx(&queued.tx),
+                        swap_counterparty(&queued.tx),
+                    ),
+                    None => break,
+                };
+                if nonce != expected_nonce {
+                    break;
+                }
+                // The order of the second swap account must not be outdated by its own txs.
+                if let Some((counterparty, counterparty_nonce)) = counterparty {
+                    if counterparty_nonce != self.expected_nonce(&counterparty) {
+                        break;
+                    }
+                }
+                if chunks_for_tx > chunks_left {
+                    block_is_full = true;
+                    break;
+                }
+
+                let queued = self
+                    .account_txs
+                    .get_mut(&address)
+                    .and_then(|txs| txs.remove(&nonce))
+                    .expect("queued tx");
+                let tx_hash = queued.tx.hash();
+                if let Some((counterparty, counterparty_nonce)) = counterparty {
+                    self.proposed_nonces
+                        .entry(counterparty)
+                        .or_default()
+                        .insert(counterparty_nonce, tx_hash.clone());
+                }
+                self.proposed_nonces
+                    .entry(address)
+                    .or_default()
+                    .insert(nonce, tx_hash);
+                chunks_left -= chunks_for_tx;
+                self.txs_count -= 1;
+                expected_nonce += 1;
+                txs_for_commit.push(queued.tx);
+            }
+            if block_is_full {
+                break;
+            }
+        }
+        self.account_txs.retain(|_, txs| !txs.is_empty());
+
+        (chunks_left, txs_for_commit)
     }
 }
 
 struct Mempool {
     mempool_state: MempoolState,
+    db_pool: ConnectionPool,
     requests: mpsc::Receiver<MempoolRequest>,
     eth_watch_req: mpsc::Sender<EthWatchRequest>,
     max_block_size_chunks: usize,
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
 }
 
 impl Mempool {
+    fn is_exodus_mode(&self) -> bool {
+        self.exodus_mode.borrow().is_some()
+    }
+
     fn add_tx(&mut self, tx: FranklinTx) -> Result<(), TxAddError> {
-        self.mempool_state.add_tx(tx)
+        if self.is_exodus_mode() {
+            return Err(TxAddError::ExodusMode);
+        }
+        // Batch transfer size depends on the number of tokens, it may not fit into any block.
+        if self.mempool_state.chunks_for_tx(&tx) > self.max_block_size_chunks {
+            return Err(TxAddError::IncorrectTx);
+        }
+        // Correctness should be checked by `signature_checker`, thus
+        // `tx.check_correctness()` is not invoked here.
+        self.mempool_state.check_tx(&tx)?;
+
+        let storage = self.db_pool.access_storage().map_err(|e| {
+            warn!("Failed to access storage for mempool tx: {}", e);
+            TxAddError::Other
+        })?;
+        storage.mempool_schema().insert_tx(&tx).map_err(|e| {
+            warn!("Failed to store mempool tx: {}", e);
+            TxAddError::Other
+        })?;
+        if let Some(replaced) = self.mempool_state.insert_tx(tx) {
+            self.remove_stored_txs(&[replaced.hash()]);
+        }
+        Ok(())
+    }
+
+    /// Removes the txs dropped from the mempool from the database.
+    fn remove_stored_txs(&self, tx_hashes: &[TxHash]) {
+        if tx_hashes.is_empty() {
+            return;
+        }
+        let remove_result = self
+            .db_pool
+            .access_storage()
+            .map_err(failure::Error::from)
+            .and_then(|storage| Ok(storage.mempool_schema().remove_txs(tx_hashes)?));
+        if let Err(e) = remove_result {
+            // Leftovers are dropped on the next restore anyway.
+            warn!("Failed to remove txs from the mempool storage: {}", e);
+        }
     }
 
     async fn run(mut self) {
@@ -171,56 +857,37 @@ impl Mempool {
                         .send(self.propose_new_block(block.last_priority_op_number).await)
                         .expect("mempool proposed block response send failed");
                 }
-                MempoolRequest::UpdateNonces(updates) => {
-                    for (id, update) in updates {
-                        match update {
-                            AccountUpdate::Create { address, nonce } => {
-                                self.mempool_state.account_ids.insert(id, address.clone());
-                                self.mempool_state.account_nonces.insert(address, nonce);
-                            }
-                            AccountUpdate::Delete { address, .. } => {
-                                self.mempool_state.account_ids.remove(&id);
-                                self.mempool_state.account_nonces.remove(&address);
-                            }
-                            AccountUpdate::AddToken { new_nonce, .. } => {
-                                if let Some(address) = self.mempool_state.account_ids.get(&id) {
-                                    if let Some(nonce) =
-                                        self.mempool_state.account_nonces.get_mut(address)
-                                    {
-                                        *nonce = new_nonce;
-                                    }
-                                }
-                            }
-                            AccountUpdate::RemoveToken { new_nonce, .. } => {
-                                if let Some(address) = self.mempool_state.account_ids.get(&id) {
-                                    if let Some(nonce) =
-                                        self.mempool_state.account_nonces.get_mut(address)
-                                    {
-                                        *nonce = new_nonce;
-                                    }
-                                }
-                            }
-                            AccountUpdate::ChangePubKeyHash { new_nonce, .. } => {
-                                if let Some(address) = self.mempool_state.account_ids.get(&id) {
-                                    if let Some(nonce) =
-                                        self.mempool_state.account_nonces.get_mut(address)
-                                    {
-                                        *nonce = new_nonce;
-                                    }
-                                }
-                            }
-                        }
-                    }
+                MempoolRequest::GetSponsorQuota(sponsor, resp) => {
+                    resp.send(self.mempool_state.sponsor_quota(sponsor))
+                        .unwrap_or_default();
+                }
+                MempoolRequest::BlockCommitted {
+                    updates,
+                    executed_txs,
+                    failed_txs,
+                } => {
+                    let removed_hashes =
+                        self.mempool_state
+                            .commit_block(updates, &executed_txs, &failed_txs);
+                    self.remove_stored_txs(&removed_hashes);
                 }
             }
         }
     }
 
     async fn propose_new_block(&mut self, current_unprocessed_priority_op: u64) -> ProposedBlock {
+        if self.is_exodus_mode() {
+            return ProposedBlock::default();
+        }
+
         let (chunks_left, priority_ops) = self
             .select_priority_ops(current_unprocessed_priority_op)
             .await;
-        let (_chunks_left, txs) = self.prepare_tx_for_block(chunks_left);
+        let mut removed_hashes = self.mempool_state.remove_expired_txs();
+        removed_hashes.extend(self.mempool_state.remove_stale_txs());
+        self.remove_stored_txs(&removed_hashes);
+        self.mempool_state.remove_outdated_sponsored_fees();
+        let (_chunks_left, txs) = self.mempool_state.take_txs_for_block(chunks_left);
         trace!("Proposed priority ops for block: {:#?}", priority_ops);
         trace!("Proposed txs for block: {:#?}", txs);
         ProposedBlock { priority_ops, txs }
@@ -253,41 +920,27 @@ impl Mempool {
             priority_ops,
         )
     }
-
-    fn prepare_tx_for_block(&mut self, mut chunks_left: usize) -> (usize, Vec<FranklinTx>) {
-        let mut txs_for_commit = Vec::new();
-        let mut tx_for_reinsert = None;
-
-        while let Some(tx) = self.mempool_state.ready_txs.pop_front() {
-            let chunks_for_tx = self.mempool_state.chunks_for_tx(&tx);
-            if chunks_left >= chunks_for_tx {
-                txs_for_commit.push(tx);
-                chunks_left -= chunks_for_tx;
-            } else {
-                tx_for_reinsert = Some(tx);
-                break;
-            }
-        }
-
-        if let Some(tx) = tx_for_reinsert {
-            self.mempool_state.ready_txs.push_front(tx);
-        }
-
-        (chunks_left, txs_for_commit)
-    }
 }
 
 pub fn run_mempool_task(
     db_pool: ConnectionPool,
     requests: mpsc::Receiver<MempoolRequest>,
     eth_watch_req: mpsc::Sender<EthWatchRequest>,
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
     config: &ConfigurationOptions,
     runtime: &Runtime,
 ) {
-    let mempool_state = MempoolState::restore_from_db(&db_pool);
+    let mempool_state = MempoolState::restore_from_db(
+        &db_pool,
+        config.mempool_max_txs,
+        config.mempool_tx_ttl,
+        config.sponsor_fee_quotas.clone(),
+        config.sponsor_quota_period,
+    );
 
     let mempool = Mempool {
         mempool_state,
+        db_pool,
         requests,
         eth_watch_req,
         max_block_size_chunks: *config
@@ -295,6 +948,510 @@ pub fn run_mempool_task(
             .iter()
             .max()
             .expect("failed to find max block chunks size"),
+        exodus_mode,
     };
     runtime.spawn(mempool.run());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::tx::TxSignature;
+    use models::node::{
+        ApproveForAll, ApproveForAllOp, Burn, ContentHash, Order, Sponsorship, Swap, TokenId,
+        Transfer,
+    };
+
+    const TX_TTL: Duration = Duration::from_secs(3600);
+    /// The only account with a sponsor fee quota.
+    const SPONSOR_ID: AccountId = 10;
+
+    fn address(account_id: AccountId) -> Address {
+        Address::repeat_byte(account_id as u8)
+    }
+
+    fn nft(token_id: u64) -> Nft {
+        Nft::new(1, TokenId::from(token_id))
+    }
+
+    /// Mempool state with the committed accounts `(id, nonce)`, every account holds the token
+    /// with the id of the account.
+    fn mempool_state(accounts: &[(AccountId, Nonce)], tx_ttl: Duration) -> MempoolState {
+        let mut state = MempoolState::new(
+            100,
+            tx_ttl,
+            vec![(SPONSOR_ID, BigDecimal::from(1_000))]
+                .into_iter()
+                .collect(),
+            Duration::from_secs(3600),
+        );
+        for &(id, nonce) in accounts {
+            state.account_ids.insert(id, address(id));
+            state.account_nonces.insert(address(id), nonce);
+            state.token_owners.insert(nft(id.into()), address(id));
+        }
+        state
+    }
+
+    /// Tx that does not move any token.
+    fn approve_for_all(account_id: AccountId, nonce: Nonce) -> FranklinTx {
+        FranklinTx::ApproveForAll(Box::new(ApproveForAll::new(
+            account_id,
+            address(account_id),
+            address(account_id + 100),
+            true,
+            BigDecimal::from(0),
+            nonce,
+            None,
+        )))
+    }
+
+    fn transfer(
+        from: AccountId,
+        to: AccountId,
+        token: Nft,
+        nonce: Nonce,
+        valid_until: Option<u32>,
+    ) -> FranklinTx {
+        FranklinTx::Transfer(Box::new(Transfer::new(
+            from,
+            address(from),
+            address(to),
+            token.collection_id,
+            token.token_id,
+            BigDecimal::from(0),
+            nonce,
+            None,
+            valid_until,
+            None,
+        )))
+    }
+
+    fn sponsored_transfer(
+        from: AccountId,
+        to: AccountId,
+        nonce: Nonce,
+        sponsor: AccountId,
+        fee: u64,
+    ) -> FranklinTx {
+        let mut tx = Transfer::new(
+            from,
+            address(from),
+            address(to),
+            1,
+            TokenId::from(u64::from(from)),
+            BigDecimal::from(fee),
+            nonce,
+            None,
+            None,
+            None,
+        );
+        tx.sponsor = Some(Sponsorship {
+            account_id: sponsor,
+            signature: TxSignature::default(),
+        });
+        FranklinTx::Transfer(Box::new(tx))
+    }
+
+    fn swap(a: AccountId, a_nonce: Nonce, b: AccountId, b_nonce: Nonce) -> FranklinTx {
+        let order = |account_id: AccountId, nonce: Nonce, sell: Nft, buy: Nft| {
+            Order::new(
+                account_id,
+                address(account_id),
+                sell,
+                vec![buy],
+                BigDecimal::from(0),
+                nonce,
+                None,
+            )
+        };
+        FranklinTx::Swap(Box::new(Swap::new(
+            order(a, a_nonce, nft(a.into()), nft(b.into())),
+            order(b, b_nonce, nft(b.into()), nft(a.into())),
+        )))
+    }
+
+    fn burn(account_id: AccountId, token: Nft, nonce: Nonce) -> FranklinTx {
+        FranklinTx::Burn(Box::new(Burn::new(
+            account_id,
+            address(account_id),
+            token.collection_id,
+            token.token_id,
+            BigDecimal::from(0),
+            nonce,
+            None,
+        )))
+    }
+
+    fn add_tx(state: &mut MempoolState, tx: FranklinTx) -> Result<(), TxAddError> {
+        state.check_tx(&tx)?;
+        state.insert_tx(tx);
+        Ok(())
+    }
+
+    fn queued_nonces(state: &MempoolState, account_id: AccountId) -> Vec<Nonce> {
+        state
+            .account_txs
+            .get(&address(account_id))
+            .map(|txs| txs.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn nonce_update(account_id: AccountId, old_nonce: Nonce, new_nonce: Nonce) -> AccountUpdates {
+        vec![(
+            account_id,
+            AccountUpdate::ChangeApprovalForAll {
+                operator: account_id + 100,
+                old_approved: false,
+                new_approved: true,
+                old_nonce,
+                new_nonce,
+            },
+        )]
+    }
+
+    /// Checks that the txs are queued only with the nonces following the committed one.
+    #[test]
+    fn check_tx_nonce_queue() {
+        let mut state = mempool_state(&[(1, 2)], TX_TTL);
+
+        assert_eq!(state.next_nonce(&address(1)), 2);
+        assert!(matches!(
+            state.check_tx(&approve_for_all(1, 1)),
+            Err(TxAddError::NonceMismatch)
+        ));
+        assert!(matches!(
+            state.check_tx(&approve_for_all(1, 3)),
+            Err(TxAddError::NonceGap)
+        ));
+
+        add_tx(&mut state, approve_for_all(1, 2)).expect("first tx");
+        add_tx(&mut state, approve_for_all(1, 3)).expect("second tx");
+        assert_eq!(state.next_nonce(&address(1)), 4);
+        assert!(matches!(
+            state.check_tx(&approve_for_all(1, 5)),
+            Err(TxAddError::NonceGap)
+        ));
+        assert_eq!(queued_nonces(&state, 1), vec![2, 3]);
+        assert_eq!(state.txs_count, 2);
+
+        // Unknown account starts from the zero nonce.
+        assert_eq!(state.next_nonce(&address(2)), 0);
+        add_tx(&mut state, approve_for_all(2, 0)).expect("new account tx");
+        assert_eq!(state.txs_count, 3);
+    }
+
+    /// Checks that a queued tx is replaced by the tx with the same nonce,
+    /// while the tx with the nonce of a proposed one is rejected.
+    #[test]
+    fn check_tx_replacement() {
+        let mut state = mempool_state(&[(1, 0), (2, 0)], TX_TTL);
+
+        let tx = transfer(1, 2, nft(1), 0, None);
+        add_tx(&mut state, tx.clone()).expect("tx");
+        assert!(matches!(state.check_tx(&tx), Err(TxAddError::Duplicate)));
+
+        let replacement = approve_for_all(1, 0);
+        state.check_tx(&replacement).expect("replacement");
+        let replaced = state.insert_tx(replacement).expect("replaced tx");
+        assert_eq!(replaced.hash(), tx.hash());
+        assert_eq!(state.txs_count, 1);
+        // The token move of the replaced transfer is forgotten.
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(1)));
+
+        let (_, txs) = state.take_txs_for_block(100);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(state.txs_count, 0);
+        assert_eq!(state.next_nonce(&address(1)), 1);
+        assert!(matches!(
+            state.check_tx(&transfer(1, 2, nft(1), 0, None)),
+            Err(TxAddError::NonceMismatch)
+        ));
+        add_tx(&mut state, transfer(1, 2, nft(1), 1, None)).expect("next tx");
+
+        // Once the block is committed, the proposed nonce is settled.
+        let removed = state.commit_block(nonce_update(1, 0, 1), &[txs[0].hash()], &[]);
+        assert!(removed.is_empty());
+        assert!(state.proposed_nonces.is_empty());
+        assert_eq!(state.next_nonce(&address(1)), 2);
+    }
+
+    /// Checks that the mempool size is capped, replacements are accepted by the full mempool.
+    #[test]
+    fn check_tx_mempool_full() {
+        let mut state = mempool_state(&[(1, 0)], TX_TTL);
+        state.max_txs = 2;
+
+        add_tx(&mut state, approve_for_all(1, 0)).expect("first tx");
+        add_tx(&mut state, approve_for_all(1, 1)).expect("second tx");
+        assert!(matches!(
+            state.check_tx(&approve_for_all(1, 2)),
+            Err(TxAddError::MempoolFull)
+        ));
+        add_tx(&mut state, transfer(1, 2, nft(1), 1, None)).expect("replacement");
+        assert_eq!(state.txs_count, 2);
+    }
+
+    /// Checks that the txs of an account are taken in the nonce order within the block size.
+    #[test]
+    fn take_txs_for_block() {
+        let mut state = mempool_state(&[(1, 0), (2, 5)], TX_TTL);
+        for nonce in 0..3 {
+            add_tx(&mut state, approve_for_all(1, nonce)).expect("account 1 tx");
+        }
+        add_tx(&mut state, approve_for_all(2, 5)).expect("account 2 tx");
+
+        let chunks = ApproveForAllOp::CHUNKS;
+        let (chunks_left, txs) = state.take_txs_for_block(2 * chunks + 1);
+        assert_eq!(chunks_left, 1);
+        assert_eq!(
+            txs.iter()
+                .map(|tx| (tx.account(), tx.nonce()))
+                .collect::<Vec<_>>(),
+            vec![(address(1), 0), (address(1), 1)]
+        );
+        assert_eq!(queued_nonces(&state, 1), vec![2]);
+        assert_eq!(state.expected_nonce(&address(1)), 2);
+        assert_eq!(state.txs_count, 2);
+
+        let (chunks_left, txs) = state.take_txs_for_block(10 * chunks);
+        assert_eq!(chunks_left, 8 * chunks);
+        assert_eq!(txs.len(), 2);
+        assert_eq!(state.expected_nonce(&address(1)), 3);
+        assert_eq!(state.expected_nonce(&address(2)), 6);
+        assert!(state.account_txs.is_empty());
+        assert_eq!(state.txs_count, 0);
+    }
+
+    /// Checks that the txs are only taken starting from the expected nonce of the account
+    /// and the txs with the already used nonces are dropped.
+    #[test]
+    fn take_txs_for_block_expected_nonce() {
+        let mut state = mempool_state(&[(1, 0)], TX_TTL);
+        add_tx(&mut state, approve_for_all(1, 0)).expect("first tx");
+        add_tx(&mut state, approve_for_all(1, 1)).expect("second tx");
+
+        // The committed nonce has passed the first tx, which can not be executed anymore.
+        let removed = state.commit_block(nonce_update(1, 0, 1), &[], &[]);
+        assert_eq!(removed, vec![approve_for_all(1, 0).hash()]);
+        assert_eq!(queued_nonces(&state, 1), vec![1]);
+        assert_eq!(state.txs_count, 1);
+
+        // Tx ahead of the expected nonce is not taken.
+        state.insert_tx(approve_for_all(1, 3));
+        let (_, txs) = state.take_txs_for_block(100);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].nonce(), 1);
+        assert_eq!(queued_nonces(&state, 1), vec![3]);
+        let (_, txs) = state.take_txs_for_block(100);
+        assert!(txs.is_empty());
+    }
+
+    /// Checks that the expired txs are dropped along with the later txs of the account.
+    #[test]
+    fn remove_expired_txs() {
+        let now = chrono::Utc::now().timestamp() as u32;
+        let mut state = mempool_state(&[(1, 0), (2, 0)], TX_TTL);
+        add_tx(&mut state, approve_for_all(1, 0)).expect("valid tx");
+        assert!(matches!(
+            state.check_tx(&transfer(1, 2, nft(1), 1, Some(now - 1))),
+            Err(TxAddError::TxExpired)
+        ));
+        add_tx(&mut state, transfer(1, 2, nft(1), 1, Some(now + 3600))).expect("windowed tx");
+        add_tx(&mut state, approve_for_all(1, 2)).expect("later tx");
+        add_tx(&mut state, approve_for_all(2, 0)).expect("other account tx");
+
+        // The validity window of the transfer ends while it is queued.
+        let expiring = transfer(1, 2, nft(1), 1, Some(now - 1));
+        state.insert_tx(expiring.clone());
+        let removed = state.remove_expired_txs();
+        assert_eq!(removed, vec![expiring.hash(), approve_for_all(1, 2).hash()]);
+        assert_eq!(queued_nonces(&state, 1), vec![0]);
+        assert_eq!(queued_nonces(&state, 2), vec![0]);
+        assert_eq!(state.txs_count, 2);
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(1)));
+
+        // Txs waiting for longer than the TTL are dropped.
+        state.tx_ttl = Duration::from_secs(0);
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(state.remove_expired_txs().len(), 2);
+        assert!(state.account_txs.is_empty());
+        assert_eq!(state.txs_count, 0);
+    }
+
+    /// Checks that the token can be moved further by its pending owner only.
+    #[test]
+    fn pending_moves_transfer_chain() {
+        let mut state = mempool_state(&[(1, 0), (2, 0), (3, 0)], TX_TTL);
+        let first = transfer(1, 2, nft(1), 0, None);
+        add_tx(&mut state, first.clone()).expect("first transfer");
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(2)));
+        assert_eq!(
+            state.token_owner(&nft(1), Some(&first.hash())),
+            Some(address(1))
+        );
+
+        assert!(matches!(
+            state.check_tx(&transfer(1, 3, nft(1), 1, None)),
+            Err(TxAddError::TokenNotOwned)
+        ));
+        add_tx(&mut state, transfer(2, 3, nft(1), 0, None)).expect("second transfer");
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(3)));
+        assert!(matches!(
+            state.check_tx(&transfer(2, 1, nft(1), 1, None)),
+            Err(TxAddError::TokenNotOwned)
+        ));
+
+        // Moves of the committed txs are settled.
+        let (_, txs) = state.take_txs_for_block(100);
+        assert_eq!(txs.len(), 2);
+        let token_update = |added: bool, old_nonce: Nonce, new_nonce: Nonce| {
+            let (token, content_hash, slot) = (nft(1), ContentHash::default(), 0);
+            if added {
+                AccountUpdate::AddToken {
+                    old_nonce,
+                    new_nonce,
+                    token,
+                    content_hash,
+                    slot,
+                }
+            } else {
+                AccountUpdate::RemoveToken {
+                    old_nonce,
+                    new_nonce,
+                    token,
+                    content_hash,
+                    slot,
+                }
+            }
+        };
+        let tx_hashes = txs.iter().map(FranklinTx::hash).collect::<Vec<_>>();
+        state.commit_block(
+            vec![
+                (1, token_update(false, 0, 1)),
+                (2, token_update(true, 0, 0)),
+                (2, token_update(false, 0, 1)),
+                (3, token_update(true, 0, 0)),
+            ],
+            &tx_hashes,
+            &[],
+        );
+        assert!(state.pending_moves.is_empty());
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(3)));
+    }
+
+    /// Checks that both tokens of a pending swap move to the other account.
+    #[test]
+    fn pending_moves_swap() {
+        let mut state = mempool_state(&[(1, 0), (2, 3)], TX_TTL);
+        assert!(matches!(
+            state.check_tx(&swap(1, 0, 2, 2)),
+            Err(TxAddError::NonceMismatch)
+        ));
+        add_tx(&mut state, swap(1, 0, 2, 3)).expect("swap");
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(2)));
+        assert_eq!(state.token_owner(&nft(2), None), Some(address(1)));
+
+        assert!(matches!(
+            state.check_tx(&transfer(2, 1, nft(2), 3, None)),
+            Err(TxAddError::TokenNotOwned)
+        ));
+        add_tx(&mut state, transfer(2, 1, nft(1), 3, None)).expect("swapped token transfer");
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(1)));
+
+        // The transfer of the second account uses the nonce of its order, so it is dropped
+        // once the swap is proposed.
+        let (_, txs) = state.take_txs_for_block(100);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(state.expected_nonce(&address(2)), 4);
+        assert_eq!(state.remove_stale_txs().len(), 1);
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(2)));
+    }
+
+    /// Checks that the nonces and the token moves of the proposed txs that failed
+    /// in the committed block can be used again.
+    #[test]
+    fn failed_txs_are_settled() {
+        let mut state = mempool_state(&[(1, 0), (2, 0), (3, 0)], TX_TTL);
+        let failed_transfer = transfer(1, 2, nft(1), 0, None);
+        let failed_swap = swap(2, 0, 3, 0);
+        add_tx(&mut state, failed_transfer.clone()).expect("transfer");
+        add_tx(&mut state, approve_for_all(1, 1)).expect("later tx");
+        add_tx(&mut state, failed_swap.clone()).expect("swap");
+        let (_, txs) = state.take_txs_for_block(100);
+        assert_eq!(txs.len(), 3);
+
+        // The later tx of the account is still proposed, so the nonces are not free yet.
+        let removed = state.commit_block(
+            Vec::new(),
+            &[],
+            &[failed_transfer.hash(), failed_swap.hash()],
+        );
+        assert!(removed.is_empty());
+        assert_eq!(state.expected_nonce(&address(1)), 2);
+        assert_eq!(state.expected_nonce(&address(2)), 0);
+        assert_eq!(state.expected_nonce(&address(3)), 0);
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(1)));
+        assert_eq!(state.token_owner(&nft(2), None), Some(address(2)));
+        assert_eq!(state.token_owner(&nft(3), None), Some(address(3)));
+
+        state.commit_block(Vec::new(), &[], &[approve_for_all(1, 1).hash()]);
+        assert!(state.proposed_nonces.is_empty());
+        assert!(state.pending_moves.is_empty());
+        add_tx(&mut state, transfer(1, 3, nft(1), 0, None)).expect("resubmitted transfer");
+        add_tx(&mut state, transfer(3, 1, nft(3), 0, None)).expect("counterparty transfer");
+    }
+
+    /// Checks that the fees of the sponsored txs are limited by the quota of their sponsor,
+    /// and the fees of the dropped or failed txs are returned to the quota.
+    #[test]
+    fn sponsor_fee_quota() {
+        let mut state = mempool_state(&[(1, 0), (2, 0), (3, 0)], TX_TTL);
+        // Account without a quota can not sponsor txs.
+        assert!(matches!(
+            state.check_tx(&sponsored_transfer(1, 2, 0, 3, 10)),
+            Err(TxAddError::SponsorQuotaExceeded)
+        ));
+
+        add_tx(&mut state, sponsored_transfer(1, 2, 0, SPONSOR_ID, 600)).expect("transfer");
+        let transfer_b = sponsored_transfer(2, 1, 0, SPONSOR_ID, 500);
+        assert!(matches!(
+            state.check_tx(&transfer_b),
+            Err(TxAddError::SponsorQuotaExceeded)
+        ));
+        // The fee of the replaced tx does not count against the quota.
+        let transfer_a = sponsored_transfer(1, 3, 0, SPONSOR_ID, 500);
+        add_tx(&mut state, transfer_a.clone()).expect("replacement");
+        add_tx(&mut state, transfer_b.clone()).expect("second transfer");
+        let quota = state.sponsor_quota(SPONSOR_ID);
+        assert_eq!(quota.used, BigDecimal::from(1_000));
+        assert_eq!(quota.remaining, BigDecimal::from(0));
+
+        let (_, txs) = state.take_txs_for_block(100);
+        assert_eq!(txs.len(), 2);
+        state.commit_block(Vec::new(), &[transfer_a.hash()], &[transfer_b.hash()]);
+        assert_eq!(state.sponsor_quota(SPONSOR_ID).used, BigDecimal::from(500));
+    }
+
+    /// Checks that the burned token can not be moved by the later txs.
+    #[test]
+    fn pending_moves_burn() {
+        let mut state = mempool_state(&[(1, 0), (2, 0)], TX_TTL);
+        add_tx(&mut state, burn(1, nft(1), 0)).expect("burn");
+        assert_eq!(state.token_owner(&nft(1), None), None);
+        assert!(matches!(
+            state.check_tx(&transfer(1, 2, nft(1), 1, None)),
+            Err(TxAddError::TokenNotOwned)
+        ));
+        assert!(matches!(
+            state.check_tx(&burn(1, nft(1), 1)),
+            Err(TxAddError::TokenNotOwned)
+        ));
+
+        // Replacing the burn gives the token back to the account.
+        add_tx(&mut state, transfer(1, 2, nft(1), 0, None)).expect("replacement");
+        assert_eq!(state.token_owner(&nft(1), None), Some(address(2)));
+        add_tx(&mut state, burn(2, nft(1), 0)).expect("burn by the new owner");
+        assert_eq!(state.token_owner(&nft(1), None), None);
+    }
+}
@@ -3,8 +3,10 @@
 
 use crate::state_keeper::PlasmaStateInitParams;
 use circuit::witness::{
-    ChangePubkeyOffChainWitness, CloseAccountWitness, DepositWitness, FullExitWitness,
-    TransferToNewWitness, TransferWitness, WithdrawWitness, Witness,
+    ApproveForAllWitness, ApproveWitness, BatchTransferWitness, BurnWitness,
+    ChangePubkeyOffChainWitness, CloseAccountWitness, DepositEthWitness, DepositWitness,
+    ForcedWithdrawWitness, FullExitWitness, MintWitness, SwapWitness, TransferToNewWitness,
+    TransferWitness, WithdrawWitness, Witness,
 };
 use log::info;
 use models::circuit::account::CircuitAccount;
@@ -114,9 +116,27 @@ impl ObservedState {
                 FranklinOp::TransferToNew(transfer_to_new) => {
                     TransferToNewWitness::apply_tx(&mut self.circuit_acc_tree, &transfer_to_new);
                 }
+                FranklinOp::BatchTransfer(batch_transfer) => {
+                    BatchTransferWitness::apply_tx(&mut self.circuit_acc_tree, &batch_transfer);
+                }
+                FranklinOp::Swap(swap) => {
+                    SwapWitness::apply_tx(&mut self.circuit_acc_tree, &swap);
+                }
                 FranklinOp::Withdraw(withdraw) => {
                     WithdrawWitness::apply_tx(&mut self.circuit_acc_tree, &withdraw);
                 }
+                FranklinOp::Mint(mint) => {
+                    MintWitness::apply_tx(&mut self.circuit_acc_tree, &mint);
+                }
+                FranklinOp::Burn(burn) => {
+                    BurnWitness::apply_tx(&mut self.circuit_acc_tree, &burn);
+                }
+                FranklinOp::Approve(approve) => {
+                    ApproveWitness::apply_tx(&mut self.circuit_acc_tree, &approve);
+                }
+                FranklinOp::ApproveForAll(approve_for_all) => {
+                    ApproveForAllWitness::apply_tx(&mut self.circuit_acc_tree, &approve_for_all);
+                }
                 FranklinOp::Close(close) => {
                     CloseAccountWitness::apply_tx(&mut self.circuit_acc_tree, &close);
                 }
@@ -129,6 +149,15 @@ impl ObservedState {
                     // TODO ADE: full exit is not managed for now
                     unimplemented!()
                 }
+                FranklinOp::ForcedWithdraw(forced_withdraw_op) => {
+                    ForcedWithdrawWitness::apply_tx(
+                        &mut self.circuit_acc_tree,
+                        &forced_withdraw_op,
+                    );
+                }
+                FranklinOp::DepositEth(deposit_eth) => {
+                    DepositEthWitness::apply_tx(&mut self.circuit_acc_tree, &deposit_eth);
+                }
                 FranklinOp::ChangePubKeyOffchain(change_pkhash_op) => {
                     ChangePubkeyOffChainWitness::apply_tx(
                         &mut self.circuit_acc_tree,
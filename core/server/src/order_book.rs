@@ -0,0 +1,694 @@
+//! Order book keeps the signed limit orders of the accounts and matches them into swaps.
+//!
+//! Order is an offer of the account to give one of its tokens in exchange for any token
+//! of the given set (see `Order`). Its role is to:
+//! 1) Accept orders from api, check their signatures and basic correctness: the order is signed
+//! by the current signing key of the account, the sold token is owned by the account and the order
+//! nonce is not used yet.
+//! 2) Match the new order against the stored ones: two orders of different accounts match if each
+//! of them accepts the token sold by the other one. Matched orders are turned into a `Swap` tx
+//! which is sent to the mempool.
+//! 3) Cancel orders which cannot be executed anymore: once the sold token leaves the account or the
+//! account nonce passes the order nonce. Users cancel their orders by spending the order nonce.
+//!
+//! Communication channel with other actors:
+//! Order book sends matched swaps to the mempool (see `MempoolRequest::NewTx`) and accepts requests
+//! from api and committer (see `OrderBookRequest`).
+//!
+//! Communication with db:
+//! accepted orders are persisted in the `orders` table and removed from there once they are matched
+//! or cancelled. On restart order book restores the committed account state and reloads the stored
+//! orders, dropping the ones which cannot be executed anymore.
+
+// Built-in deps
+use std::collections::HashMap;
+// External uses
+use failure::Fail;
+use futures::{
+    channel::{mpsc, oneshot},
+    SinkExt, StreamExt,
+};
+use tokio::runtime::Runtime;
+// Workspace uses
+use models::node::{
+    tx::TxHash, AccountId, AccountUpdate, AccountUpdates, Address, Nft, Nonce, Order, PubKeyHash,
+    Swap,
+};
+use storage::ConnectionPool;
+// Local uses
+use crate::{
+    mempool::{MempoolRequest, TxAddError},
+    signature_checker::VerifiedTx,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Fail)]
+pub enum OrderAddError {
+    #[fail(display = "Order nonce is too low.")]
+    NonceMismatch,
+
+    #[fail(display = "Order is already in the order book")]
+    Duplicate,
+
+    #[fail(display = "Token is not owned by the account")]
+    TokenNotOwned,
+
+    #[fail(display = "Order is incorrect")]
+    IncorrectOrder,
+
+    #[fail(display = "Order is not signed by the account signing key")]
+    IncorrectSigner,
+
+    #[fail(display = "Internal error")]
+    Other,
+}
+
+/// Orders to look up in the order book.
+#[derive(Debug, Clone, Copy)]
+pub enum OrdersFilter {
+    /// Orders placed by the account.
+    Account(Address),
+    /// Orders selling the token.
+    Token(Nft),
+}
+
+pub enum OrderBookRequest {
+    /// Add new order to the order book, the order is matched against the stored ones.
+    /// oneshot is used to receive order add result.
+    NewOrder(Box<Order>, oneshot::Sender<Result<(), OrderAddError>>),
+    /// Get the orders waiting to be matched.
+    GetOrders(OrdersFilter, oneshot::Sender<Vec<Order>>),
+    /// When block is committed, account state of the order book should be updated too.
+    UpdateAccounts(AccountUpdates),
+}
+
+struct OrderBookAccount {
+    address: Address,
+    // nonce following the last committed tx or the last matched order of the account
+    nonce: Nonce,
+    pub_key_hash: PubKeyHash,
+}
+
+#[derive(Default)]
+struct OrderBookState {
+    accounts: HashMap<AccountId, OrderBookAccount>,
+    // token and its committed owner
+    token_owners: HashMap<Nft, AccountId>,
+    // orders waiting to be matched, in the order of admission
+    orders: Vec<Order>,
+}
+
+impl OrderBookState {
+    fn restore_from_db(db_pool: &ConnectionPool) -> Self {
+        let storage = db_pool.access_storage().expect("order book db restore");
+        let (_, committed_accounts) = storage
+            .chain()
+            .state_schema()
+            .load_committed_state(None)
+            .expect("order book account state load");
+
+        let mut accounts = HashMap::new();
+        let mut token_owners = HashMap::new();
+        for (id, account) in committed_accounts {
+            for token in account.get_tokens() {
+                token_owners.insert(token, id);
+            }
+            accounts.insert(
+                id,
+                OrderBookAccount {
+                    address: account.address,
+                    nonce: account.nonce,
+                    pub_key_hash: account.pub_key_hash,
+                },
+            );
+        }
+
+        let mut order_book_state = Self {
+            accounts,
+            token_owners,
+            orders: Vec::new(),
+        };
+
+        let stored_orders = storage
+            .order_book_schema()
+            .load_orders()
+            .expect("order book orders load");
+        let mut stale_hashes = Vec::new();
+        for mut order in stored_orders {
+            match order_book_state.check_order(&mut order) {
+                Ok(()) => order_book_state.orders.push(order),
+                Err(_) => stale_hashes.push(order.hash()),
+            }
+        }
+        if !stale_hashes.is_empty() {
+            info!(
+                "Removing {} stale orders from the order book",
+                stale_hashes.len()
+            );
+            storage
+                .order_book_schema()
+                .remove_orders(&stale_hashes)
+                .expect("order book stale orders removal");
+        }
+
+        order_book_state
+    }
+
+    /// Checks that the order can be executed in the current state of the account.
+    fn check_order(&self, order: &mut Order) -> Result<(), OrderAddError> {
+        if !order.check_correctness() {
+            return Err(OrderAddError::IncorrectOrder);
+        }
+
+        let account = self
+            .accounts
+            .get(&order.account_id)
+            .filter(|account| account.address == order.account)
+            .ok_or(OrderAddError::IncorrectOrder)?;
+        if order.verify_signature() != Some(account.pub_key_hash.clone()) {
+            return Err(OrderAddError::IncorrectSigner);
+        }
+        if order.nonce < account.nonce {
+            return Err(OrderAddError::NonceMismatch);
+        }
+        if self.token_owners.get(&order.token_sell) != Some(&order.account_id) {
+            return Err(OrderAddError::TokenNotOwned);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the positions of the stored orders which can be matched with `order`,
+    /// oldest first.
+    fn matching_orders(&self, order: &Order) -> Vec<usize> {
+        self.orders
+            .iter()
+            .enumerate()
+            .filter(|(_, stored)| {
+                stored.account_id != order.account_id
+                    && stored.accepts(&order.token_sell)
+                    && order.accepts(&stored.token_sell)
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Marks the order nonce as used, so the other orders with the same nonce are not matched
+    /// until the swap is committed.
+    fn use_nonce(&mut self, order: &Order) {
+        if let Some(account) = self.accounts.get_mut(&order.account_id) {
+            account.nonce = account.nonce.max(order.nonce + 1);
+        }
+    }
+
+    /// Matches `order` with the oldest stored order that accepts it and sends their swap to
+    /// the mempool. Returns the hashes of the orders removed from the order book if the swap
+    /// is accepted.
+    async fn match_order(
+        &mut self,
+        order: &Order,
+        mempool_request_sender: &mut mpsc::Sender<MempoolRequest>,
+    ) -> Option<Vec<TxHash>> {
+        for position in self.matching_orders(order) {
+            let stored = self.orders[position].clone();
+            match submit_swap(mempool_request_sender, stored.clone(), order.clone()).await {
+                Ok(()) => {
+                    info!(
+                        "Orders {} and {} are matched",
+                        stored.hash().to_string(),
+                        order.hash().to_string()
+                    );
+                    self.use_nonce(&stored);
+                    self.use_nonce(order);
+                    self.orders.remove(position);
+
+                    let mut removed_hashes = vec![stored.hash()];
+                    removed_hashes.extend(self.remove_stale_orders());
+                    return Some(removed_hashes);
+                }
+                // The stored order may be not executable at the moment, e.g. its token is
+                // moved by a not committed tx, the next matching order is tried then.
+                Err(e) => warn!(
+                    "Swap of the orders {} and {} is rejected by mempool: {}",
+                    stored.hash().to_string(),
+                    order.hash().to_string(),
+                    e
+                ),
+            }
+        }
+        None
+    }
+
+    /// Removes the orders which cannot be executed anymore from the order book,
+    /// returns their hashes.
+    fn remove_stale_orders(&mut self) -> Vec<TxHash> {
+        let accounts = &self.accounts;
+        let token_owners = &self.token_owners;
+        let (orders, stale_orders): (Vec<_>, Vec<_>) = self.orders.drain(..).partition(|order| {
+            accounts
+                .get(&order.account_id)
+                .map_or(false, |account| order.nonce >= account.nonce)
+                && token_owners.get(&order.token_sell) == Some(&order.account_id)
+        });
+        self.orders = orders;
+
+        if !stale_orders.is_empty() {
+            info!(
+                "Removing {} cancelled orders from the order book",
+                stale_orders.len()
+            );
+        }
+        stale_orders.iter().map(Order::hash).collect()
+    }
+
+    fn orders(&self, filter: OrdersFilter) -> Vec<Order> {
+        self.orders
+            .iter()
+            .filter(|order| match filter {
+                OrdersFilter::Account(address) => order.account == address,
+                OrdersFilter::Token(token) => order.token_sell == token,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the hashes of the orders cancelled by the updates.
+    fn update_accounts(&mut self, updates: AccountUpdates) -> Vec<TxHash> {
+        for (id, update) in updates {
+            match update {
+                AccountUpdate::Create { address, nonce } => {
+                    self.accounts.insert(
+                        id,
+                        OrderBookAccount {
+                            address,
+                            nonce,
+                            pub_key_hash: PubKeyHash::default(),
+                        },
+                    );
+                }
+                AccountUpdate::Delete { .. } => {
+                    self.accounts.remove(&id);
+                }
+                AccountUpdate::AddToken {
+                    new_nonce, token, ..
+                } => {
+                    self.update_nonce(id, new_nonce);
+                    self.token_owners.insert(token, id);
+                }
+                AccountUpdate::RemoveToken {
+                    new_nonce, token, ..
+                } => {
+                    self.update_nonce(id, new_nonce);
+                    if self.token_owners.get(&token) == Some(&id) {
+                        self.token_owners.remove(&token);
+                    }
+                }
+                AccountUpdate::ChangePubKeyHash {
+                    new_pub_key_hash,
+                    new_nonce,
+                    ..
+                } => {
+                    self.update_nonce(id, new_nonce);
+                    if let Some(account) = self.accounts.get_mut(&id) {
+                        account.pub_key_hash = new_pub_key_hash;
+                    }
+                }
+                AccountUpdate::ChangeApproval { new_nonce, .. }
+                | AccountUpdate::ChangeApprovalForAll { new_nonce, .. }
+                | AccountUpdate::UpdateBalance { new_nonce, .. } => {
+                    self.update_nonce(id, new_nonce);
+                }
+            }
+        }
+        self.remove_stale_orders()
+    }
+
+    fn update_nonce(&mut self, id: AccountId, new_nonce: Nonce) {
+        if let Some(account) = self.accounts.get_mut(&id) {
+            // nonces of the matched orders stay used until their swaps are committed
+            account.nonce = account.nonce.max(new_nonce);
+        }
+    }
+}
+
+struct OrderBook {
+    order_book_state: OrderBookState,
+    db_pool: ConnectionPool,
+    requests: mpsc::Receiver<OrderBookRequest>,
+    mempool_request_sender: mpsc::Sender<MempoolRequest>,
+}
+
+impl OrderBook {
+    async fn add_order(&mut self, mut order: Order) -> Result<(), OrderAddError> {
+        let order_hash = order.hash();
+        if self
+            .order_book_state
+            .orders
+            .iter()
+            .any(|stored| stored.hash() == order_hash)
+        {
+            return Err(OrderAddError::Duplicate);
+        }
+        self.order_book_state.check_order(&mut order)?;
+
+        if let Some(removed_hashes) = self
+            .order_book_state
+            .match_order(&order, &mut self.mempool_request_sender)
+            .await
+        {
+            self.remove_stored_orders(&removed_hashes);
+            return Ok(());
+        }
+
+        self.store_order(&order)?;
+        self.order_book_state.orders.push(order);
+        Ok(())
+    }
+
+    fn store_order(&self, order: &Order) -> Result<(), OrderAddError> {
+        let storage = self.db_pool.access_storage().map_err(|e| {
+            warn!("Failed to access storage for order: {}", e);
+            OrderAddError::Other
+        })?;
+        storage
+            .order_book_schema()
+            .insert_order(order)
+            .map_err(|e| {
+                warn!("Failed to store order: {}", e);
+                OrderAddError::Other
+            })
+    }
+
+    /// Removes the matched or cancelled orders from the database.
+    fn remove_stored_orders(&self, order_hashes: &[TxHash]) {
+        if order_hashes.is_empty() {
+            return;
+        }
+        let remove_result = self
+            .db_pool
+            .access_storage()
+            .map_err(failure::Error::from)
+            .and_then(|storage| Ok(storage.order_book_schema().remove_orders(order_hashes)?));
+        if let Err(e) = remove_result {
+            // Leftovers are dropped on the next restore anyway.
+            warn!("Failed to remove orders from the order book storage: {}", e);
+        }
+    }
+
+    async fn run(mut self) {
+        while let Some(request) = self.requests.next().await {
+            match request {
+                OrderBookRequest::NewOrder(order, resp) => {
+                    let order_add_result = self.add_order(*order).await;
+                    resp.send(order_add_result).unwrap_or_default();
+                }
+                OrderBookRequest::GetOrders(filter, resp) => {
+                    resp.send(self.order_book_state.orders(filter))
+                        .unwrap_or_default();
+                }
+                OrderBookRequest::UpdateAccounts(updates) => {
+                    let cancelled_hashes = self.order_book_state.update_accounts(updates);
+                    self.remove_stored_orders(&cancelled_hashes);
+                }
+            }
+        }
+    }
+}
+
+/// Sends the swap of the matched orders to the mempool, each account pays the fee of its order.
+async fn submit_swap(
+    mempool_request_sender: &mut mpsc::Sender<MempoolRequest>,
+    order_a: Order,
+    order_b: Order,
+) -> Result<(), TxAddError> {
+    let swap = VerifiedTx::verify_swap(Swap::new(order_a, order_b))?;
+
+    let mempool_resp = oneshot::channel();
+    mempool_request_sender
+        .send(MempoolRequest::NewTx(Box::new(swap), mempool_resp.0))
+        .await
+        .map_err(|e| {
+            warn!("Failed to send swap to mempool: {}", e);
+            TxAddError::Other
+        })?;
+    mempool_resp.1.await.unwrap_or(Err(TxAddError::Other))
+}
+
+pub fn run_order_book_task(
+    db_pool: ConnectionPool,
+    requests: mpsc::Receiver<OrderBookRequest>,
+    mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    runtime: &Runtime,
+) {
+    let order_book = OrderBook {
+        order_book_state: OrderBookState::restore_from_db(&db_pool),
+        db_pool,
+        requests,
+        mempool_request_sender,
+    };
+    runtime.spawn(order_book.run());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use crypto_exports::rand::{Rng, SeedableRng, XorShiftRng};
+    use futures::executor::block_on;
+    use models::node::{priv_key_from_fs, FranklinTx, PrivateKey, TokenId};
+
+    fn address(account_id: AccountId) -> Address {
+        Address::repeat_byte(account_id as u8)
+    }
+
+    fn nft(token_id: u64) -> Nft {
+        Nft::new(1, TokenId::from(token_id))
+    }
+
+    fn private_key(account_id: AccountId) -> PrivateKey {
+        let mut rng = XorShiftRng::from_seed([account_id + 1, 2, 3, 4]);
+        priv_key_from_fs(rng.gen())
+    }
+
+    /// Order book state with the accounts `0..accounts`, every account holds the token
+    /// with the id of the account.
+    fn order_book_state(accounts: AccountId) -> OrderBookState {
+        let mut state = OrderBookState::default();
+        for id in 0..accounts {
+            state.accounts.insert(
+                id,
+                OrderBookAccount {
+                    address: address(id),
+                    nonce: 0,
+                    pub_key_hash: PubKeyHash::from_privkey(&private_key(id)),
+                },
+            );
+            state.token_owners.insert(nft(id.into()), id);
+        }
+        state
+    }
+
+    fn signed_order(
+        account_id: AccountId,
+        sell: u64,
+        buy: &[u64],
+        nonce: Nonce,
+        private_key: &PrivateKey,
+    ) -> Order {
+        Order::new_signed(
+            account_id,
+            address(account_id),
+            nft(sell),
+            buy.iter().map(|&token_id| nft(token_id)).collect(),
+            BigDecimal::from(0),
+            nonce,
+            private_key,
+        )
+        .expect("correct order")
+    }
+
+    fn order(account_id: AccountId, sell: u64, buy: &[u64], nonce: Nonce) -> Order {
+        signed_order(account_id, sell, buy, nonce, &private_key(account_id))
+    }
+
+    fn hashes(orders: &[Order]) -> Vec<TxHash> {
+        orders.iter().map(Order::hash).collect()
+    }
+
+    /// Runs `match_order` against a mempool that answers the swaps with `responses`,
+    /// returns its result and the ids of the `a` accounts of the sent swaps.
+    fn match_order(
+        state: &mut OrderBookState,
+        order: &Order,
+        responses: Vec<Result<(), TxAddError>>,
+    ) -> (Option<Vec<TxHash>>, Vec<AccountId>) {
+        let (mut mempool_request_sender, mempool_requests) = mpsc::channel(responses.len());
+        let mempool = mempool_requests
+            .zip(futures::stream::iter(responses))
+            .map(|(request, response)| match request {
+                MempoolRequest::NewTx(tx, resp) => {
+                    resp.send(response).unwrap_or_default();
+                    match tx.into_inner() {
+                        FranklinTx::Swap(swap) => swap.order_a.account_id,
+                        _ => panic!("Order book sends swaps only"),
+                    }
+                }
+                _ => panic!("Order book sends new txs only"),
+            })
+            .collect::<Vec<_>>();
+
+        block_on(async {
+            let matched = state.match_order(order, &mut mempool_request_sender);
+            futures::join!(matched, mempool)
+        })
+    }
+
+    #[test]
+    fn check_order() {
+        let mut state = order_book_state(2);
+        state.accounts.get_mut(&1).unwrap().nonce = 2;
+
+        assert_eq!(state.check_order(&mut order(0, 0, &[1], 0)), Ok(()));
+        // Orders of the used nonces are rejected, the future ones are kept until the
+        // previous nonces are used.
+        assert_eq!(state.check_order(&mut order(1, 1, &[0], 2)), Ok(()));
+        assert_eq!(
+            state.check_order(&mut order(1, 1, &[0], 1)),
+            Err(OrderAddError::NonceMismatch)
+        );
+        assert_eq!(
+            state.check_order(&mut order(0, 1, &[0], 0)),
+            Err(OrderAddError::TokenNotOwned)
+        );
+        assert_eq!(
+            state.check_order(&mut signed_order(0, 0, &[1], 0, &private_key(1))),
+            Err(OrderAddError::IncorrectSigner)
+        );
+
+        // Account id has to match the address, the account has to exist.
+        let mut wrong_address = order(0, 0, &[1], 0);
+        wrong_address.account = address(1);
+        assert_eq!(
+            state.check_order(&mut wrong_address),
+            Err(OrderAddError::IncorrectOrder)
+        );
+        assert_eq!(
+            state.check_order(&mut order(2, 2, &[0], 0)),
+            Err(OrderAddError::IncorrectOrder)
+        );
+
+        // Order can not buy the token it sells.
+        let mut buys_own_token = Order::new(
+            0,
+            address(0),
+            nft(0),
+            vec![nft(0), nft(1)],
+            BigDecimal::from(0),
+            0,
+            None,
+        );
+        assert_eq!(
+            state.check_order(&mut buys_own_token),
+            Err(OrderAddError::IncorrectOrder)
+        );
+    }
+
+    #[test]
+    fn matching_orders() {
+        let mut state = order_book_state(4);
+        state.orders = vec![
+            order(1, 1, &[0], 0),
+            // Does not accept the token of the new order.
+            order(2, 2, &[3], 0),
+            order(3, 3, &[5, 0], 0),
+            // Orders of the same account are never matched.
+            order(0, 0, &[1], 0),
+        ];
+
+        let new_order = order(0, 0, &[1, 3], 0);
+        assert_eq!(state.matching_orders(&new_order), vec![0, 2]);
+
+        // Both orders have to accept the token of the other one.
+        let new_order = order(0, 0, &[2], 0);
+        assert!(state.matching_orders(&new_order).is_empty());
+    }
+
+    #[test]
+    fn use_nonce() {
+        let mut state = order_book_state(1);
+
+        state.use_nonce(&order(0, 0, &[1], 3));
+        assert_eq!(state.accounts[&0].nonce, 4);
+
+        // Nonce never goes back, e.g. for an order matched earlier with a lower nonce.
+        state.use_nonce(&order(0, 0, &[1], 1));
+        assert_eq!(state.accounts[&0].nonce, 4);
+    }
+
+    #[test]
+    fn remove_stale_orders() {
+        let mut state = order_book_state(3);
+        let orders = vec![
+            order(0, 0, &[1], 0),
+            order(1, 1, &[0], 0),
+            order(2, 2, &[0], 5),
+        ];
+        state.orders = orders.clone();
+        assert!(state.remove_stale_orders().is_empty());
+
+        // The nonce of the first order is used, the token of the second one is moved.
+        let cancelled_hashes = state.update_accounts(vec![
+            (
+                0,
+                AccountUpdate::ChangeApprovalForAll {
+                    operator: 2,
+                    old_approved: false,
+                    new_approved: true,
+                    old_nonce: 0,
+                    new_nonce: 1,
+                },
+            ),
+            (
+                1,
+                AccountUpdate::RemoveToken {
+                    token: nft(1),
+                    content_hash: Default::default(),
+                    slot: 0,
+                    old_nonce: 0,
+                    new_nonce: 0,
+                },
+            ),
+        ]);
+        assert_eq!(cancelled_hashes, hashes(&orders[..2]));
+        assert_eq!(hashes(&state.orders), hashes(&orders[2..]));
+    }
+
+    #[test]
+    fn match_order_fallback() {
+        let mut state = order_book_state(3);
+        let stored_orders = vec![order(1, 1, &[0], 0), order(2, 2, &[0], 0)];
+        state.orders = stored_orders.clone();
+        let new_order = order(0, 0, &[1, 2], 0);
+
+        // Mempool rejects the swap with the oldest matching order, the next one is tried.
+        let (removed_hashes, swapped_accounts) = match_order(
+            &mut state,
+            &new_order,
+            vec![Err(TxAddError::TokenNotOwned), Ok(())],
+        );
+        assert_eq!(swapped_accounts, vec![1, 2]);
+        assert_eq!(removed_hashes, Some(hashes(&stored_orders[1..])));
+        assert_eq!(hashes(&state.orders), hashes(&stored_orders[..1]));
+        assert_eq!(state.accounts[&0].nonce, 1);
+        assert_eq!(state.accounts[&1].nonce, 0);
+        assert_eq!(state.accounts[&2].nonce, 1);
+
+        // Order is not matched if mempool rejects all the swaps.
+        let new_order = order(0, 0, &[1], 1);
+        let (removed_hashes, swapped_accounts) =
+            match_order(&mut state, &new_order, vec![Err(TxAddError::TokenNotOwned)]);
+        assert_eq!(swapped_accounts, vec![1]);
+        assert_eq!(removed_hashes, None);
+        assert_eq!(hashes(&state.orders), hashes(&stored_orders[..1]));
+        assert_eq!(state.accounts[&0].nonce, 1);
+    }
+}
@@ -9,8 +9,10 @@ use log::info;
 // Workspace deps
 use circuit::witness::{
     utils::{SigDataInput, WitnessBuilder},
-    ChangePubkeyOffChainWitness, CloseAccountWitness, DepositWitness, FullExitWitness,
-    TransferToNewWitness, TransferWitness, WithdrawWitness, Witness,
+    ApproveForAllWitness, ApproveWitness, BatchTransferWitness, BurnWitness,
+    ChangePubkeyOffChainWitness, CloseAccountWitness, DepositEthWitness, DepositWitness,
+    ForcedWithdrawWitness, FullExitWitness, MintWitness, SwapWitness, TransferToNewWitness,
+    TransferWitness, WithdrawWitness, Witness,
 };
 use models::{
     circuit::CircuitAccountTree,
@@ -299,7 +301,6 @@ impl Maintainer {
 
                     operations.extend(transfer_operations);
                     fees.push(CollectedFee {
-                        token: 0, // TODO ADE must be removed
                         amount: transfer.tx.fee,
                     });
                     pub_data.extend(transfer_witness.get_pubdata());
@@ -316,11 +317,39 @@ impl Maintainer {
 
                     operations.extend(transfer_to_new_operations);
                     fees.push(CollectedFee {
-                        token: 0, // TODO ADE must be removed
                         amount: transfer_to_new.tx.fee,
                     });
                     pub_data.extend(transfer_to_new_witness.get_pubdata());
                 }
+                FranklinOp::BatchTransfer(batch_transfer) => {
+                    let batch_transfer_witness = BatchTransferWitness::apply_tx(
+                        &mut witness_accum.account_tree,
+                        &batch_transfer,
+                    );
+
+                    let input = SigDataInput::from_batch_transfer_op(&batch_transfer)?;
+                    let batch_transfer_operations =
+                        batch_transfer_witness.calculate_operations(input);
+
+                    operations.extend(batch_transfer_operations);
+                    fees.push(CollectedFee {
+                        amount: batch_transfer.tx.fee,
+                    });
+                    pub_data.extend(batch_transfer_witness.get_pubdata());
+                }
+                FranklinOp::Swap(swap) => {
+                    let swap_witness =
+                        SwapWitness::apply_tx(&mut witness_accum.account_tree, &swap);
+
+                    let input = SigDataInput::from_swap_op(&swap)?;
+                    let swap_operations = swap_witness.calculate_operations(input);
+
+                    operations.extend(swap_operations);
+                    fees.push(CollectedFee {
+                        amount: swap.tx.order_a.fee + swap.tx.order_b.fee,
+                    });
+                    pub_data.extend(swap_witness.get_pubdata());
+                }
                 FranklinOp::Withdraw(withdraw) => {
                     let withdraw_witness =
                         WithdrawWitness::apply_tx(&mut witness_accum.account_tree, &withdraw);
@@ -330,11 +359,65 @@ impl Maintainer {
 
                     operations.extend(withdraw_operations);
                     fees.push(CollectedFee {
-                        token: 0, // TODO ADE must be removed
                         amount: withdraw.tx.fee,
                     });
                     pub_data.extend(withdraw_witness.get_pubdata());
                 }
+                FranklinOp::Mint(mint) => {
+                    let mint_witness =
+                        MintWitness::apply_tx(&mut witness_accum.account_tree, &mint);
+
+                    let input = SigDataInput::from_mint_op(&mint)?;
+                    let mint_operations = mint_witness.calculate_operations(input);
+
+                    operations.extend(mint_operations);
+                    fees.push(CollectedFee {
+                        amount: mint.tx.fee,
+                    });
+                    pub_data.extend(mint_witness.get_pubdata());
+                }
+                FranklinOp::Burn(burn) => {
+                    let burn_witness =
+                        BurnWitness::apply_tx(&mut witness_accum.account_tree, &burn);
+
+                    let input = SigDataInput::from_burn_op(&burn)?;
+                    let burn_operations = burn_witness.calculate_operations(input);
+
+                    operations.extend(burn_operations);
+                    fees.push(CollectedFee {
+                        amount: burn.tx.fee,
+                    });
+                    pub_data.extend(burn_witness.get_pubdata());
+                }
+                FranklinOp::Approve(approve) => {
+                    let approve_witness =
+                        ApproveWitness::apply_tx(&mut witness_accum.account_tree, &approve);
+
+                    let input = SigDataInput::from_approve_op(&approve)?;
+                    let approve_operations = approve_witness.calculate_operations(input);
+
+                    operations.extend(approve_operations);
+                    fees.push(CollectedFee {
+                        amount: approve.tx.fee,
+                    });
+                    pub_data.extend(approve_witness.get_pubdata());
+                }
+                FranklinOp::ApproveForAll(approve_for_all) => {
+                    let approve_for_all_witness = ApproveForAllWitness::apply_tx(
+                        &mut witness_accum.account_tree,
+                        &approve_for_all,
+                    );
+
+                    let input = SigDataInput::from_approve_for_all_op(&approve_for_all)?;
+                    let approve_for_all_operations =
+                        approve_for_all_witness.calculate_operations(input);
+
+                    operations.extend(approve_for_all_operations);
+                    fees.push(CollectedFee {
+                        amount: approve_for_all.tx.fee,
+                    });
+                    pub_data.extend(approve_for_all_witness.get_pubdata());
+                }
                 FranklinOp::Close(close) => {
                     let close_account_witness =
                         CloseAccountWitness::apply_tx(&mut witness_accum.account_tree, &close);
@@ -361,6 +444,26 @@ impl Maintainer {
                     // TODO ADE full exit not managed for now
                     unimplemented!()
                 }
+                FranklinOp::ForcedWithdraw(forced_withdraw_op) => {
+                    let forced_withdraw_witness = ForcedWithdrawWitness::apply_tx(
+                        &mut witness_accum.account_tree,
+                        &forced_withdraw_op,
+                    );
+
+                    let forced_withdraw_operations =
+                        forced_withdraw_witness.calculate_operations(());
+
+                    operations.extend(forced_withdraw_operations);
+                    pub_data.extend(forced_withdraw_witness.get_pubdata());
+                }
+                FranklinOp::DepositEth(deposit_eth) => {
+                    let deposit_eth_witness =
+                        DepositEthWitness::apply_tx(&mut witness_accum.account_tree, &deposit_eth);
+
+                    let deposit_eth_operations = deposit_eth_witness.calculate_operations(());
+                    operations.extend(deposit_eth_operations);
+                    pub_data.extend(deposit_eth_witness.get_pubdata());
+                }
                 FranklinOp::ChangePubKeyOffchain(change_pkhash_op) => {
                     let change_pkhash_witness = ChangePubkeyOffChainWitness::apply_tx(
                         &mut witness_accum.account_tree,
@@ -397,8 +500,8 @@ impl Maintainer {
             validator_address: Fr::from_str(&commit_operation.block.fee_account.to_string())
                 .expect("failed to parse"),
             operations: witness_accum.operations,
-            validator_balances: vec![], //witness_accum.fee_account_balances.unwrap(),
-            validator_audit_path: vec![], //witness_accum.fee_account_audit_path.unwrap(),
+            validator_token_root: witness_accum.fee_account_token_root,
+            validator_audit_path: witness_accum.fee_account_audit_path.unwrap(),
             validator_account: witness_accum.fee_account_witness.unwrap(),
         })
     }
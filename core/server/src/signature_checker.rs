@@ -13,7 +13,7 @@ use tokio::runtime::{Builder, Handle};
 // Workspace uses
 use models::{
     config_options::ThreadPanicNotify,
-    node::{tx::TxEthSignature, FranklinTx},
+    node::{tx::TxEthSignature, Address, FranklinTx, Swap},
 };
 // Local uses
 use crate::eth_watch::EthWatchRequest;
@@ -41,6 +41,12 @@ impl VerifiedTx {
             .map(Self)
     }
 
+    /// Checks the swap of two matched orders. Swap does not require an Ethereum
+    /// signature, each side of it is authorized by the `ZKSync` signature of its order.
+    pub fn verify_swap(swap: Swap) -> Result<Self, TxAddError> {
+        verify_tx_correctness(FranklinTx::Swap(Box::new(swap))).map(Self)
+    }
+
     /// Takes the `FranklinTx` out of the wrapper.
     pub fn into_inner(self) -> FranklinTx {
         self.0
@@ -75,6 +81,25 @@ async fn verify_eth_signature(
         }
     }
 
+    // Check that the minter is allowed to mint by the governance contract.
+    if let FranklinTx::Mint(mint) = &request.tx {
+        let eth_watch_resp = oneshot::channel();
+        eth_watch_req
+            .clone()
+            .send(EthWatchRequest::IsMinterAuthorized {
+                address: mint.account,
+                collection_id: mint.collection_id,
+                resp: eth_watch_resp.0,
+            })
+            .await
+            .expect("ETH watch req receiver dropped");
+
+        let is_authorized = eth_watch_resp.1.await.expect("Err response from eth watch");
+        if !is_authorized {
+            return Err(TxAddError::MinterNotAuthorized);
+        }
+    }
+
     // Check the signature.
     if let Some((signature, message)) = &request.eth_sign_data {
         match &signature {
@@ -83,7 +108,7 @@ async fn verify_eth_signature(
                     .signature_recover_signer(message.as_bytes())
                     .or(Err(TxAddError::IncorrectEthSignature))?;
 
-                if signer_account != request.tx.account() {
+                if !request.eth_signers.contains(&signer_account) {
                     return Err(TxAddError::IncorrectEthSignature);
                 }
             }
@@ -137,6 +162,9 @@ pub struct VerifyTxSignatureRequest {
     /// which user should have signed with their private key.
     /// Can be `None` if the Ethereum signature is not required.
     pub eth_sign_data: Option<(TxEthSignature, String)>,
+    /// Accounts whose Ethereum signature is accepted for the tx: the tx account and,
+    /// for a transfer, the operators approved by the token owner.
+    pub eth_signers: Vec<Address>,
     /// Channel for sending the check response.
     pub response: oneshot::Sender<Result<VerifiedTx, TxAddError>>,
 }
@@ -1,19 +1,21 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 // External uses
 use futures::channel::{mpsc, oneshot};
 use futures::stream::StreamExt;
 use futures::SinkExt;
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, sync::watch};
 // Workspace uses
 use crate::mempool::ProposedBlock;
 use crypto_exports::ff;
 use models::node::block::{Block, ExecutedOperations, ExecutedPriorityOp, ExecutedTx};
 use models::node::tx::{FranklinTx, TxHash};
 use models::node::{
-    Account, AccountId, AccountTree, AccountUpdate, AccountUpdates, BlockNumber, PriorityOp,
+    Account, AccountId, AccountTree, AccountUpdate, AccountUpdates, BlockNumber, CollectionId,
+    FranklinOp, Nft, PriorityOp,
 };
 use models::ActionType;
 use models::CommitRequest;
+use models::ExodusMode;
 use plasma::state::{OpSuccess, PlasmaState};
 use storage::ConnectionPool;
 use web3::types::Address;
@@ -29,6 +31,20 @@ pub enum StateKeeperRequest {
     ExecuteMiniBlock(ProposedBlock),
     GetExecutedInPendingBlock(ExecutedOpId, oneshot::Sender<Option<(BlockNumber, bool)>>),
     SealBlock,
+    /// Executes the tx against a copy of the current state, leaving the state intact.
+    SimulateTx(Box<FranklinTx>, oneshot::Sender<TxSimulationResult>),
+}
+
+/// Result of the tx execution against a copy of the current state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSimulationResult {
+    pub success: bool,
+    /// Operation the tx is executed as, e.g. `Transfer` or `TransferToNew`.
+    pub op: Option<FranklinOp>,
+    pub chunks: Option<usize>,
+    pub updates: AccountUpdates,
+    pub fail_reason: Option<String>,
 }
 
 pub struct ExecutedOpsNotify {
@@ -78,11 +94,19 @@ pub struct PlasmaStateKeeper {
     executed_tx_notify_sender: mpsc::Sender<ExecutedOpsNotify>,
 
     available_block_chunk_sizes: Vec<usize>,
+
+    /// Once the contract enters the exodus mode, no more blocks are formed.
+    exodus_mode: watch::Receiver<Option<ExodusMode>>,
+
+    /// Accounts allowed by the governance contract to mint tokens, updated by the Ethereum watcher.
+    minters: watch::Receiver<HashSet<(CollectionId, Address)>>,
 }
 
 pub struct PlasmaStateInitParams {
     pub tree: AccountTree,
     pub acc_id_by_addr: HashMap<Address, AccountId>,
+    pub burned_tokens: HashSet<Nft>,
+    pub minted_tokens: HashSet<Nft>,
     pub last_block_number: BlockNumber,
     pub unprocessed_priority_op: u64,
 }
@@ -98,6 +122,8 @@ impl PlasmaStateInitParams {
         Self {
             tree: AccountTree::new(models::params::account_tree_depth()),
             acc_id_by_addr: HashMap::new(),
+            burned_tokens: HashSet::new(),
+            minted_tokens: HashSet::new(),
             last_block_number: 0,
             unprocessed_priority_op: 0,
         }
@@ -125,6 +151,8 @@ impl PlasmaStateInitParams {
         for (account_id, account) in accounts.into_iter() {
             self.insert_account(account_id, account);
         }
+        self.load_burned_tokens(storage)?;
+        self.load_minted_tokens(storage)?;
         self.last_block_number = block_number;
         self.unprocessed_priority_op = Self::unprocessed_priority_op_id(&storage, block_number)?;
         Ok(())
@@ -147,6 +175,8 @@ impl PlasmaStateInitParams {
                     self.insert_account(id, account);
                 }
             }
+            self.load_burned_tokens(storage)?;
+            self.load_minted_tokens(storage)?;
             self.unprocessed_priority_op =
                 Self::unprocessed_priority_op_id(&storage, block_number)?;
             self.last_block_number = block_number;
@@ -154,6 +184,30 @@ impl PlasmaStateInitParams {
         Ok(())
     }
 
+    fn load_burned_tokens(
+        &mut self,
+        storage: &storage::StorageProcessor,
+    ) -> Result<(), failure::Error> {
+        let burned_tokens = storage
+            .colexi_queries()
+            .get_burned_tokens()
+            .map_err(|e| failure::format_err!("couldn't load burned tokens: {}", e))?;
+        self.burned_tokens = burned_tokens.into_iter().collect();
+        Ok(())
+    }
+
+    fn load_minted_tokens(
+        &mut self,
+        storage: &storage::StorageProcessor,
+    ) -> Result<(), failure::Error> {
+        let minted_tokens = storage
+            .colexi_queries()
+            .get_minted_tokens()
+            .map_err(|e| failure::format_err!("couldn't load minted tokens: {}", e))?;
+        self.minted_tokens = minted_tokens.into_iter().collect();
+        Ok(())
+    }
+
     pub fn insert_account(&mut self, id: u32, acc: Account) {
         self.acc_id_by_addr.insert(acc.address, id);
         self.tree.insert(id, acc);
@@ -197,6 +251,8 @@ impl PlasmaStateKeeper {
         tx_for_commitments: mpsc::Sender<CommitRequest>,
         executed_tx_notify_sender: mpsc::Sender<ExecutedOpsNotify>,
         available_block_chunk_sizes: Vec<usize>,
+        exodus_mode: watch::Receiver<Option<ExodusMode>>,
+        minters: watch::Receiver<HashSet<(CollectionId, Address)>>,
     ) -> Self {
         assert!(!available_block_chunk_sizes.is_empty());
 
@@ -210,6 +266,8 @@ impl PlasmaStateKeeper {
         let state = PlasmaState::new(
             initial_state.tree,
             initial_state.acc_id_by_addr,
+            initial_state.burned_tokens,
+            initial_state.minted_tokens,
             initial_state.last_block_number + 1,
         );
 
@@ -227,6 +285,8 @@ impl PlasmaStateKeeper {
             pending_block: PendingBlock::new(initial_state.unprocessed_priority_op, max_block_size),
             executed_tx_notify_sender,
             available_block_chunk_sizes,
+            exodus_mode,
+            minters,
         };
 
         let root = keeper.state.root_hash();
@@ -285,19 +345,30 @@ impl PlasmaStateKeeper {
                         .unwrap_or_default();
                 }
                 StateKeeperRequest::ExecuteMiniBlock(proposed_block) => {
-                    self.execute_tx_batch(proposed_block).await;
+                    if !self.is_exodus_mode() {
+                        self.execute_tx_batch(proposed_block).await;
+                    }
                 }
                 StateKeeperRequest::GetExecutedInPendingBlock(op_id, sender) => {
                     let result = self.check_executed_in_pending_block(op_id);
                     sender.send(result).unwrap_or_default();
                 }
                 StateKeeperRequest::SealBlock => {
-                    self.seal_pending_block().await;
+                    if !self.is_exodus_mode() {
+                        self.seal_pending_block().await;
+                    }
+                }
+                StateKeeperRequest::SimulateTx(tx, sender) => {
+                    sender.send(self.simulate_tx(*tx)).unwrap_or_default();
                 }
             }
         }
     }
 
+    fn is_exodus_mode(&self) -> bool {
+        self.exodus_mode.borrow().is_some()
+    }
+
     async fn notify_executed_ops(&self, executed_ops: &mut Vec<ExecutedOperations>) {
         self.executed_tx_notify_sender
             .clone()
@@ -313,6 +384,9 @@ impl PlasmaStateKeeper {
 
     async fn execute_tx_batch(&mut self, proposed_block: ProposedBlock) {
         let mut executed_ops = Vec::new();
+        // Validity windows of the txs are checked against the time the batch is executed at
+        self.state.block_timestamp = chrono::Utc::now().timestamp() as u32;
+        self.state.minters = self.minters.borrow().clone();
 
         let mut priority_op_queue = proposed_block
             .priority_ops
@@ -453,6 +527,46 @@ impl PlasmaStateKeeper {
         Ok(exec_result)
     }
 
+    /// Executes the tx the same way `apply_tx` does, but against a copy of the state,
+    /// which includes the changes of the pending block.
+    fn simulate_tx(&mut self, tx: FranklinTx) -> TxSimulationResult {
+        let fee_account_id = self.fee_account_id;
+        let block_timestamp = self.state.block_timestamp;
+        self.state.block_timestamp = chrono::Utc::now().timestamp() as u32;
+        self.state.minters = self.minters.borrow().clone();
+        let result = self.state.execute_and_revert(|state| {
+            state.execute_tx(tx).map(|mut success| {
+                if let Some(fee) = &success.fee {
+                    let fee_updates = state.collect_fee(&[fee.clone()], fee_account_id);
+                    success.updates.extend(fee_updates);
+                }
+                success
+            })
+        });
+        self.state.block_timestamp = block_timestamp;
+
+        match result {
+            Ok(OpSuccess {
+                updates,
+                executed_op,
+                ..
+            }) => TxSimulationResult {
+                success: true,
+                chunks: Some(executed_op.chunks()),
+                op: Some(executed_op),
+                updates,
+                fail_reason: None,
+            },
+            Err(e) => TxSimulationResult {
+                success: false,
+                op: None,
+                chunks: None,
+                updates: Vec::new(),
+                fail_reason: Some(e.to_string()),
+            },
+        }
+    }
+
     async fn seal_pending_block(&mut self) {
         let pending_block = std::mem::replace(
             &mut self.pending_block,
@@ -465,6 +579,20 @@ impl PlasmaStateKeeper {
             ),
         );
 
+        let executed_txs = pending_block
+            .success_operations
+            .iter()
+            .filter_map(|op| match op {
+                ExecutedOperations::Tx(exec_tx) => Some(exec_tx.tx.hash()),
+                ExecutedOperations::PriorityOp(_) => None,
+            })
+            .collect();
+        let failed_txs = pending_block
+            .failed_txs
+            .iter()
+            .map(|failed_tx| failed_tx.tx.hash())
+            .collect();
+
         let mut block_transactions = pending_block.success_operations;
         block_transactions.extend(
             pending_block
@@ -486,6 +614,8 @@ impl PlasmaStateKeeper {
                 &self.available_block_chunk_sizes,
             ),
             accounts_updated: pending_block.account_updates,
+            executed_txs,
+            failed_txs,
         };
         self.state.block_number += 1;
 
@@ -251,6 +251,7 @@ pub fn test_operation_and_wanted_prover_data(
             &models::node::operations::DepositOp {
                 priority_op: deposit_op,
                 account_id: 0,
+                success: true,
             },
         );
 
@@ -282,7 +283,7 @@ pub fn test_operation_and_wanted_prover_data(
     }
     let _: models::node::Fr = circuit_tree.root_hash();
     let (root_after_fee, validator_account_witness) =
-        circuit::witness::utils::apply_fee(&mut circuit_tree, block.fee_account, 0, 0);
+        circuit::witness::utils::apply_fee(&mut circuit_tree, block.fee_account, 0);
 
     assert_eq!(root_after_fee, block.new_root_hash);
     let (validator_audit_path, _) =
@@ -57,7 +57,14 @@ fn check_tx_signature(req: web::Json<FranklinTx>) -> impl Responder {
     info!("tx bytes: {}", hex::encode(tx.get_bytes()));
     let pub_key_hash = match tx {
         FranklinTx::Transfer(tx) => tx.verify_signature(),
+        FranklinTx::BatchTransfer(tx) => tx.verify_signature(),
+        // the order is signed by both accounts, the first signer is reported
+        FranklinTx::Swap(tx) => tx.verify_signatures().0,
         FranklinTx::Withdraw(tx) => tx.verify_signature(),
+        FranklinTx::Mint(tx) => tx.verify_signature(),
+        FranklinTx::Burn(tx) => tx.verify_signature(),
+        FranklinTx::Approve(tx) => tx.verify_signature(),
+        FranklinTx::ApproveForAll(tx) => tx.verify_signature(),
         FranklinTx::Close(tx) => tx.verify_signature(),
         _ => None,
     };
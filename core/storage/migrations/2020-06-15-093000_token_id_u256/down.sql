@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE account_tokens_updates ALTER COLUMN token_id TYPE INTEGER USING token_id::INTEGER;
+ALTER TABLE tokens ALTER COLUMN token_id TYPE INTEGER USING token_id::INTEGER;
@@ -0,0 +1,3 @@
+-- ERC-721 token ids are uint256 values, which do not fit into INTEGER.
+ALTER TABLE tokens ALTER COLUMN token_id TYPE NUMERIC(78, 0);
+ALTER TABLE account_tokens_updates ALTER COLUMN token_id TYPE NUMERIC(78, 0);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE account_tokens_updates DROP COLUMN collection_id;
+
+ALTER TABLE tokens DROP CONSTRAINT tokens_pkey;
+ALTER TABLE tokens DROP COLUMN collection_id;
+ALTER TABLE tokens ADD PRIMARY KEY (account_id, token_id);
+
+DROP INDEX collections_symbol_index;
+DROP INDEX collections_address_index;
+DROP TABLE IF EXISTS collections;
@@ -0,0 +1,33 @@
+-- ERC-721 collections registered in the governance contract (`Governance.addToken`).
+CREATE TABLE collections (
+    id INTEGER NOT NULL PRIMARY KEY,
+    address TEXT NOT NULL,
+    symbol TEXT NOT NULL
+);
+CREATE INDEX collections_address_index ON collections (address);
+CREATE INDEX collections_symbol_index ON collections (symbol);
+
+-- A token is identified by the pair (collection_id, token_id).
+ALTER TABLE tokens ADD COLUMN collection_id INTEGER;
+ALTER TABLE account_tokens_updates ADD COLUMN collection_id INTEGER;
+
+-- Tokens stored so far belong to the single ERC-721 contract supported before collections.
+-- `DeployFactory` registers it right after the genesis ERC-20 tokens, so its id depends on the
+-- network and has to be provided when the database is not empty, e.g.
+-- `PGOPTIONS="-c collexi.legacy_collection_id=1" diesel migration run`.
+-- Collection 0 is never registered, it marks the empty token leaves of the tree.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM tokens) OR EXISTS (SELECT 1 FROM account_tokens_updates) THEN
+        UPDATE tokens
+            SET collection_id = current_setting('collexi.legacy_collection_id')::INTEGER;
+        UPDATE account_tokens_updates
+            SET collection_id = current_setting('collexi.legacy_collection_id')::INTEGER;
+    END IF;
+END $$;
+
+ALTER TABLE tokens ALTER COLUMN collection_id SET NOT NULL;
+ALTER TABLE tokens DROP CONSTRAINT tokens_pkey;
+ALTER TABLE tokens ADD PRIMARY KEY (account_id, collection_id, token_id);
+
+ALTER TABLE account_tokens_updates ALTER COLUMN collection_id SET NOT NULL;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE account_tokens_updates DROP COLUMN slot;
+
+DROP INDEX tokens_account_slot_index;
+ALTER TABLE tokens DROP COLUMN slot;
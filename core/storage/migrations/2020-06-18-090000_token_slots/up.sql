@@ -0,0 +1,15 @@
+-- Slot of the account token subtree leaf assigned to the token when it entered the account.
+ALTER TABLE tokens ADD COLUMN slot INTEGER;
+UPDATE tokens SET slot = numbered.slot
+FROM (
+    SELECT account_id, collection_id, token_id,
+        (ROW_NUMBER() OVER (PARTITION BY account_id ORDER BY collection_id, token_id) - 1) AS slot
+    FROM tokens
+) AS numbered
+WHERE tokens.account_id = numbered.account_id
+    AND tokens.collection_id = numbered.collection_id
+    AND tokens.token_id = numbered.token_id;
+ALTER TABLE tokens ALTER COLUMN slot SET NOT NULL;
+CREATE UNIQUE INDEX tokens_account_slot_index ON tokens (account_id, slot);
+
+ALTER TABLE account_tokens_updates ADD COLUMN slot INTEGER NOT NULL DEFAULT 0;
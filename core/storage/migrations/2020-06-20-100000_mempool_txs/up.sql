@@ -0,0 +1,8 @@
+-- Transactions accepted by the mempool and not yet included into a committed block.
+CREATE TABLE mempool_txs (
+    id BIGSERIAL PRIMARY KEY,
+    tx_hash BYTEA NOT NULL UNIQUE,
+    tx JSONB NOT NULL,
+    nonce BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
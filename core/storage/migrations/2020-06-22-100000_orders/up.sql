@@ -0,0 +1,8 @@
+-- Signed limit orders waiting in the order book to be matched into swaps.
+CREATE TABLE orders (
+    id BIGSERIAL PRIMARY KEY,
+    order_hash BYTEA NOT NULL UNIQUE,
+    account_id BIGINT NOT NULL,
+    order_data JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE account_tokens_updates DROP COLUMN content_hash;
+ALTER TABLE tokens DROP COLUMN content_hash;
@@ -0,0 +1,4 @@
+-- Hash of the token metadata or content committed to by the token subtree leaf,
+-- tokens deposited before it was introduced commit to the zero hash.
+ALTER TABLE tokens ADD COLUMN content_hash BYTEA NOT NULL DEFAULT decode(repeat('00', 32), 'hex');
+ALTER TABLE account_tokens_updates ADD COLUMN content_hash BYTEA NOT NULL DEFAULT decode(repeat('00', 32), 'hex');
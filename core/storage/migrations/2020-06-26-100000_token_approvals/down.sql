@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE account_operator_updates;
+DROP TABLE account_approval_updates;
+DROP TABLE account_operators;
+DROP TABLE token_approvals;
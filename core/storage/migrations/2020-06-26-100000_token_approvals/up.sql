@@ -0,0 +1,44 @@
+-- Operators approved to transfer a single token of the account, keyed by the token slot.
+CREATE TABLE token_approvals (
+    account_id BIGINT NOT NULL,
+    slot INTEGER NOT NULL,
+    operator_id BIGINT NOT NULL,
+    PRIMARY KEY (account_id, slot)
+);
+
+-- Operators approved to transfer any token of the account.
+CREATE TABLE account_operators (
+    account_id BIGINT NOT NULL,
+    operator_id BIGINT NOT NULL,
+    PRIMARY KEY (account_id, operator_id)
+);
+
+-- Committed changes of the token approvals, `NULL` operator stands for no approval.
+CREATE TABLE account_approval_updates (
+    approval_update_id serial NOT NULL,
+    update_order_id INTEGER NOT NULL,
+    account_id BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    collection_id INTEGER NOT NULL,
+    token_id NUMERIC NOT NULL,
+    slot INTEGER NOT NULL,
+    old_operator_id BIGINT,
+    new_operator_id BIGINT,
+    old_nonce BIGINT NOT NULL,
+    new_nonce BIGINT NOT NULL,
+    PRIMARY KEY (approval_update_id)
+);
+
+-- Committed changes of the approvals for all tokens.
+CREATE TABLE account_operator_updates (
+    operator_update_id serial NOT NULL,
+    update_order_id INTEGER NOT NULL,
+    account_id BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    operator_id BIGINT NOT NULL,
+    old_approved BOOLEAN NOT NULL,
+    new_approved BOOLEAN NOT NULL,
+    old_nonce BIGINT NOT NULL,
+    new_nonce BIGINT NOT NULL,
+    PRIMARY KEY (operator_update_id)
+);
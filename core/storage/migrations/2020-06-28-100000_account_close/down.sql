@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE account_creates DROP CONSTRAINT account_creates_pkey;
+ALTER TABLE account_creates ADD PRIMARY KEY (account_id, block_number);
@@ -0,0 +1,4 @@
+-- Ids of the closed accounts are reused, so an account may be deleted and another one
+-- created with the same id within a single block.
+ALTER TABLE account_creates DROP CONSTRAINT account_creates_pkey;
+ALTER TABLE account_creates ADD PRIMARY KEY (account_id, block_number, update_order_id);
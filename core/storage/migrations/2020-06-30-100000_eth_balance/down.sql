@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX account_eth_balance_updates_block_index;
+DROP TABLE account_eth_balance_updates;
+ALTER TABLE accounts DROP COLUMN balance;
@@ -0,0 +1,16 @@
+-- ETH balance of the account in wei, fees of the account txs are paid from it.
+ALTER TABLE accounts ADD COLUMN balance NUMERIC NOT NULL DEFAULT 0;
+
+-- Committed changes of the account ETH balances.
+CREATE TABLE account_eth_balance_updates (
+    balance_update_id serial NOT NULL,
+    update_order_id INTEGER NOT NULL,
+    account_id BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    old_balance NUMERIC NOT NULL,
+    new_balance NUMERIC NOT NULL,
+    old_nonce BIGINT NOT NULL,
+    new_nonce BIGINT NOT NULL,
+    PRIMARY KEY (balance_update_id)
+);
+CREATE INDEX account_eth_balance_updates_block_index ON account_eth_balance_updates (block_number);
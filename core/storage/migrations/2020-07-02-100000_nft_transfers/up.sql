@@ -0,0 +1,125 @@
+-- Tokens moved by the executed txs and priority operations, one row per moved token.
+CREATE TABLE nft_transfers (
+    id BIGSERIAL NOT NULL,
+    collection_id INTEGER NOT NULL,
+    token_id NUMERIC(78, 0) NOT NULL,
+    -- `NULL` for the deposited and minted tokens
+    from_address BYTEA,
+    -- `NULL` for the burned tokens
+    to_address BYTEA,
+    -- Type of the tx or of the priority operation, e.g. `Transfer` or `Deposit`
+    kind TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_index INTEGER NOT NULL,
+    tx_hash BYTEA NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    PRIMARY KEY (id)
+);
+CREATE INDEX nft_transfers_token_index ON nft_transfers (collection_id, token_id, id);
+CREATE INDEX nft_transfers_from_index ON nft_transfers (from_address);
+CREATE INDEX nft_transfers_to_index ON nft_transfers (to_address);
+CREATE INDEX nft_transfers_kind_index ON nft_transfers (kind);
+
+-- Token ids are serialized as `0x` prefixed hex strings in the json columns.
+CREATE FUNCTION nft_transfers_hex_to_numeric(hex TEXT) RETURNS NUMERIC AS $$
+DECLARE
+    result NUMERIC := 0;
+BEGIN
+    FOR i IN 3..length(hex) LOOP
+        result := result * 16 + (position(lower(substr(hex, i, 1)) IN '0123456789abcdef') - 1);
+    END LOOP;
+    RETURN result;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+-- Backfill from the already executed operations, in the order they were executed.
+INSERT INTO nft_transfers (
+    collection_id, token_id, from_address, to_address, kind,
+    block_number, block_index, tx_hash, created_at
+)
+SELECT
+    t.collection_id,
+    nft_transfers_hex_to_numeric(t.token_id),
+    decode(substr(t.from_address, 3), 'hex'),
+    decode(substr(t.to_address, 3), 'hex'),
+    t.kind,
+    t.block_number,
+    t.block_index,
+    t.tx_hash,
+    t.created_at
+FROM (
+    -- Single token txs
+    SELECT
+        (tx.tx ->> 'collectionId')::INTEGER AS collection_id,
+        tx.tx ->> 'tokenId' AS token_id,
+        CASE WHEN tx.tx ->> 'type' = 'Mint' THEN NULL ELSE tx.tx ->> 'from' END AS from_address,
+        CASE tx.tx ->> 'type'
+            WHEN 'Mint' THEN tx.tx ->> 'account'
+            WHEN 'Burn' THEN NULL
+            ELSE tx.tx ->> 'to'
+        END AS to_address,
+        tx.tx ->> 'type' AS kind,
+        tx.block_number,
+        tx.block_index,
+        0 AS token_index,
+        tx.tx_hash,
+        tx.created_at
+    FROM executed_transactions tx
+    WHERE tx.success = TRUE AND tx.tx ->> 'type' IN ('Transfer', 'Withdraw', 'Mint', 'Burn')
+    UNION ALL
+    -- Every token of the batch goes to the same recipient
+    SELECT
+        (token.value ->> 'collectionId')::INTEGER,
+        token.value ->> 'tokenId',
+        tx.tx ->> 'from',
+        tx.tx ->> 'to',
+        tx.tx ->> 'type',
+        tx.block_number,
+        tx.block_index,
+        token.ordinality::INTEGER,
+        tx.tx_hash,
+        tx.created_at
+    FROM executed_transactions tx,
+        jsonb_array_elements(tx.tx -> 'tokens') WITH ORDINALITY AS token
+    WHERE tx.success = TRUE AND tx.tx ->> 'type' = 'BatchTransfer'
+    UNION ALL
+    -- Token sold by each order of the swap goes to the other order account
+    SELECT
+        (tx.tx -> sides.seller -> 'tokenSell' ->> 'collectionId')::INTEGER,
+        tx.tx -> sides.seller -> 'tokenSell' ->> 'tokenId',
+        tx.tx -> sides.seller ->> 'account',
+        tx.tx -> sides.buyer ->> 'account',
+        tx.tx ->> 'type',
+        tx.block_number,
+        tx.block_index,
+        sides.token_index,
+        tx.tx_hash,
+        tx.created_at
+    FROM executed_transactions tx,
+        (VALUES ('orderA', 'orderB', 0), ('orderB', 'orderA', 1)) AS sides (seller, buyer, token_index)
+    WHERE tx.success = TRUE AND tx.tx ->> 'type' = 'Swap'
+    UNION ALL
+    SELECT
+        (op.operation -> 'priority_op' ->> 'collection_id')::INTEGER,
+        op.operation -> 'priority_op' ->> 'token_id',
+        CASE WHEN op.operation ->> 'type' = 'Deposit'
+            THEN NULL
+            ELSE op.operation -> 'priority_op' ->> 'eth_address'
+        END,
+        CASE WHEN op.operation ->> 'type' = 'Deposit'
+            THEN op.operation -> 'priority_op' ->> 'to'
+            ELSE op.operation -> 'priority_op' ->> 'eth_address'
+        END,
+        op.operation ->> 'type',
+        op.block_number,
+        op.block_index,
+        0,
+        op.eth_hash,
+        op.created_at
+    FROM executed_priority_operations op
+    WHERE op.operation ->> 'type' = 'Deposit'
+        OR (op.operation ->> 'type' = 'ForcedWithdraw' AND (op.operation ->> 'success')::BOOLEAN)
+) t
+ORDER BY t.block_number, t.block_index, t.token_index;
+
+DROP FUNCTION nft_transfers_hex_to_numeric(TEXT);
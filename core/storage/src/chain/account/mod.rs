@@ -29,7 +29,10 @@ impl<'a> AccountSchema<'a> {
         let account_create_record = account_creates::table
             .filter(account_creates::address.eq(address.as_bytes().to_vec()))
             .filter(account_creates::is_create.eq(true))
-            .order(account_creates::block_number.desc())
+            .order((
+                account_creates::block_number.desc(),
+                account_creates::update_order_id.desc(),
+            ))
             .first::<StorageAccountCreation>(self.0.conn())
             .optional()?;
 
@@ -45,11 +48,14 @@ impl<'a> AccountSchema<'a> {
         };
 
         // Load committed & verified states, and return them.
+        // The id of a closed account may be reused, so the states of other accounts are skipped.
         let committed = self
             .last_committed_state_for_account(account_id)?
+            .filter(|a| a.address == *address)
             .map(|a| (account_id, a));
         let verified = self
             .last_verified_state_for_account(account_id)?
+            .filter(|a| a.address == *address)
             .map(|a| (account_id, a));
         Ok(StoredAccountState {
             committed,
@@ -69,7 +75,13 @@ impl<'a> AccountSchema<'a> {
         let (last_block, account) = self.get_account_and_last_block(account_id)?;
 
         // Collect the diffs that we have to apply to the account.
-        let (account_tokens_diff, account_creation_diff) = self
+        let (
+            account_tokens_diff,
+            account_creation_diff,
+            account_approval_diff,
+            account_operator_diff,
+            account_balance_diff,
+        ) = self
             .0
             .conn()
             .transaction::<_, diesel::result::Error, _>(|| {
@@ -90,7 +102,38 @@ impl<'a> AccountSchema<'a> {
                         .load::<StorageAccountCreation>(self.0.conn())?
                 };
 
-                Ok((account_tokens_diff, account_creation_diff))
+                // The same as above, but for the approvals of the account tokens.
+                let account_approval_diff: Vec<StorageAccountApprovalUpdate> = {
+                    account_approval_updates::table
+                        .filter(account_approval_updates::account_id.eq(&(i64::from(account_id))))
+                        .filter(account_approval_updates::block_number.gt(&last_block))
+                        .load::<StorageAccountApprovalUpdate>(self.0.conn())?
+                };
+
+                let account_operator_diff: Vec<StorageAccountOperatorUpdate> = {
+                    account_operator_updates::table
+                        .filter(account_operator_updates::account_id.eq(&(i64::from(account_id))))
+                        .filter(account_operator_updates::block_number.gt(&last_block))
+                        .load::<StorageAccountOperatorUpdate>(self.0.conn())?
+                };
+
+                // The same as above, but for the ETH balance of the account.
+                let account_balance_diff: Vec<StorageAccountBalanceUpdate> = {
+                    account_eth_balance_updates::table
+                        .filter(
+                            account_eth_balance_updates::account_id.eq(&(i64::from(account_id))),
+                        )
+                        .filter(account_eth_balance_updates::block_number.gt(&last_block))
+                        .load::<StorageAccountBalanceUpdate>(self.0.conn())?
+                };
+
+                Ok((
+                    account_tokens_diff,
+                    account_creation_diff,
+                    account_approval_diff,
+                    account_operator_diff,
+                    account_balance_diff,
+                ))
             })?;
 
         // Chain the diffs, converting them into `StorageAccountDiff`.
@@ -106,6 +149,21 @@ impl<'a> AccountSchema<'a> {
                     .into_iter()
                     .map(StorageAccountDiff::from),
             );
+            account_diff.extend(
+                account_approval_diff
+                    .into_iter()
+                    .map(StorageAccountDiff::from),
+            );
+            account_diff.extend(
+                account_operator_diff
+                    .into_iter()
+                    .map(StorageAccountDiff::from),
+            );
+            account_diff.extend(
+                account_balance_diff
+                    .into_iter()
+                    .map(StorageAccountDiff::from),
+            );
             account_diff.sort_by(StorageAccountDiff::cmp_order);
             account_diff
                 .into_iter()
@@ -148,9 +206,13 @@ impl<'a> AccountSchema<'a> {
         if let Some(account) = maybe_account {
             let tokens: Vec<StorageToken> =
                 StorageToken::belonging_to(&account).load(self.0.conn())?;
+            let approvals: Vec<StorageTokenApproval> =
+                StorageTokenApproval::belonging_to(&account).load(self.0.conn())?;
+            let operators: Vec<StorageAccountOperator> =
+                StorageAccountOperator::belonging_to(&account).load(self.0.conn())?;
 
             let last_block = account.last_block;
-            let (_, account) = restore_account(account, tokens);
+            let (_, account) = restore_account(account, tokens, approvals, operators);
             Ok((last_block, Some(account)))
         } else {
             Ok((0, None))
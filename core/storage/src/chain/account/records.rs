@@ -10,6 +10,7 @@ pub struct StorageAccount {
     pub nonce: i64,
     pub address: Vec<u8>,
     pub pubkey_hash: Vec<u8>,
+    pub balance: BigDecimal,
 }
 
 #[derive(Debug, Insertable, Queryable, QueryableByName)]
@@ -30,10 +31,13 @@ pub struct StorageAccountUpdate {
     pub account_id: i64,
     pub block_number: i64,
     pub old_nonce: i64,
-    pub token_id: i32,
+    pub token_id: BigDecimal,
     pub added: bool,
     pub new_nonce: i64,
     pub update_order_id: i32,
+    pub collection_id: i32,
+    pub slot: i32,
+    pub content_hash: Vec<u8>,
 }
 
 #[derive(Debug, Insertable)]
@@ -43,9 +47,12 @@ pub struct StorageAccountUpdateInsert {
     pub account_id: i64,
     pub block_number: i64,
     pub old_nonce: i64,
-    pub token_id: i32,
+    pub token_id: BigDecimal,
     pub added: bool,
     pub new_nonce: i64,
+    pub collection_id: i32,
+    pub slot: i32,
+    pub content_hash: Vec<u8>,
 }
 
 #[derive(Debug, Insertable)]
@@ -75,9 +82,114 @@ pub struct StorageAccountPubkeyUpdate {
 
 #[derive(Debug, Identifiable, Insertable, QueryableByName, Queryable, Associations)]
 #[belongs_to(StorageAccount, foreign_key = "account_id")]
-#[primary_key(account_id, token_id)]
+#[primary_key(account_id, collection_id, token_id)]
 #[table_name = "tokens"]
 pub struct StorageToken {
     pub account_id: i64,
-    pub token_id: i32,
+    pub token_id: BigDecimal,
+    pub collection_id: i32,
+    pub slot: i32,
+    pub content_hash: Vec<u8>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "account_approval_updates"]
+pub struct StorageAccountApprovalUpdateInsert {
+    pub update_order_id: i32,
+    pub account_id: i64,
+    pub block_number: i64,
+    pub collection_id: i32,
+    pub token_id: BigDecimal,
+    pub slot: i32,
+    pub old_operator_id: Option<i64>,
+    pub new_operator_id: Option<i64>,
+    pub old_nonce: i64,
+    pub new_nonce: i64,
+}
+
+#[derive(Debug, Queryable, QueryableByName)]
+#[table_name = "account_approval_updates"]
+pub struct StorageAccountApprovalUpdate {
+    pub approval_update_id: i32,
+    pub update_order_id: i32,
+    pub account_id: i64,
+    pub block_number: i64,
+    pub collection_id: i32,
+    pub token_id: BigDecimal,
+    pub slot: i32,
+    pub old_operator_id: Option<i64>,
+    pub new_operator_id: Option<i64>,
+    pub old_nonce: i64,
+    pub new_nonce: i64,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "account_operator_updates"]
+pub struct StorageAccountOperatorUpdateInsert {
+    pub update_order_id: i32,
+    pub account_id: i64,
+    pub block_number: i64,
+    pub operator_id: i64,
+    pub old_approved: bool,
+    pub new_approved: bool,
+    pub old_nonce: i64,
+    pub new_nonce: i64,
+}
+
+#[derive(Debug, Queryable, QueryableByName)]
+#[table_name = "account_operator_updates"]
+pub struct StorageAccountOperatorUpdate {
+    pub operator_update_id: i32,
+    pub update_order_id: i32,
+    pub account_id: i64,
+    pub block_number: i64,
+    pub operator_id: i64,
+    pub old_approved: bool,
+    pub new_approved: bool,
+    pub old_nonce: i64,
+    pub new_nonce: i64,
+}
+
+#[derive(Debug, Identifiable, Insertable, QueryableByName, Queryable, Associations)]
+#[belongs_to(StorageAccount, foreign_key = "account_id")]
+#[primary_key(account_id, slot)]
+#[table_name = "token_approvals"]
+pub struct StorageTokenApproval {
+    pub account_id: i64,
+    pub slot: i32,
+    pub operator_id: i64,
+}
+
+#[derive(Debug, Identifiable, Insertable, QueryableByName, Queryable, Associations)]
+#[belongs_to(StorageAccount, foreign_key = "account_id")]
+#[primary_key(account_id, operator_id)]
+#[table_name = "account_operators"]
+pub struct StorageAccountOperator {
+    pub account_id: i64,
+    pub operator_id: i64,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "account_eth_balance_updates"]
+pub struct StorageAccountBalanceUpdateInsert {
+    pub update_order_id: i32,
+    pub account_id: i64,
+    pub block_number: i64,
+    pub old_balance: BigDecimal,
+    pub new_balance: BigDecimal,
+    pub old_nonce: i64,
+    pub new_nonce: i64,
+}
+
+#[derive(Debug, Queryable, QueryableByName)]
+#[table_name = "account_eth_balance_updates"]
+pub struct StorageAccountBalanceUpdate {
+    pub balance_update_id: i32,
+    pub update_order_id: i32,
+    pub account_id: i64,
+    pub block_number: i64,
+    pub old_balance: BigDecimal,
+    pub new_balance: BigDecimal,
+    pub old_nonce: i64,
+    pub new_nonce: i64,
 }
@@ -3,20 +3,39 @@
 use web3::types::Address;
 // Workspace imports
 use models::node::PubKeyHash;
-use models::node::{Account, AccountId, TokenId};
+use models::node::{Account, AccountId, ContentHash, Nft};
+use models::primitives::big_decimal_to_u256;
 // Local imports
 use super::records::*;
 
 pub(crate) fn restore_account(
     stored_account: StorageAccount,
     stored_tokens: Vec<StorageToken>,
+    stored_approvals: Vec<StorageTokenApproval>,
+    stored_operators: Vec<StorageAccountOperator>,
 ) -> (AccountId, Account) {
     let mut account = Account::default();
     for t in stored_tokens.into_iter() {
         assert_eq!(t.account_id, stored_account.id);
-        account.add_token(t.token_id as u16); // TODO ADE : type conversion
+        account.insert_token(
+            t.slot as u32,
+            Nft::new(
+                t.collection_id as u16,
+                big_decimal_to_u256(&t.token_id).expect("db stored token id should be a uint256"),
+            ),
+            ContentHash::from_slice(&t.content_hash),
+        );
+    }
+    for approval in stored_approvals.into_iter() {
+        assert_eq!(approval.account_id, stored_account.id);
+        account.set_approved(approval.slot as u32, Some(approval.operator_id as u32));
+    }
+    for operator in stored_operators.into_iter() {
+        assert_eq!(operator.account_id, stored_account.id);
+        account.set_operator(operator.operator_id as u32, true);
     }
     account.nonce = stored_account.nonce as u32;
+    account.balance = stored_account.balance;
     account.address = Address::from_slice(&stored_account.address);
     account.pub_key_hash = PubKeyHash::from_bytes(&stored_account.pubkey_hash)
         .expect("db stored pubkey hash deserialize");
@@ -5,13 +5,15 @@
 // Built-in deps
 use std::convert::TryFrom;
 // External imports
+use chrono::{DateTime, Utc};
 // Workspace imports
 use diesel::prelude::*;
 use models::{
     node::{
         block::{ExecutedPriorityOp, ExecutedTx},
-        BlockNumber, FranklinOp, FranklinTx, PriorityOp,
+        Address, BlockNumber, FranklinOp, FranklinTx, Nft, PriorityOp,
     },
+    primitives::u256_to_bigdecimal,
     Action, ActionType, Operation,
 };
 // Local imports
@@ -24,6 +26,7 @@ use crate::{
         },
         state::StateSchema,
     },
+    colexi::records::NewNftTransfer,
     prover::ProverSchema,
     StorageProcessor,
 };
@@ -103,10 +106,17 @@ impl NewExecutedPriorityOperation {
 
         let (from_account, to_account) = match exec_prior_op.op {
             FranklinOp::Deposit(deposit) => (deposit.priority_op.from, deposit.priority_op.to),
+            FranklinOp::DepositEth(deposit_eth) => {
+                (deposit_eth.priority_op.from, deposit_eth.priority_op.to)
+            }
             FranklinOp::FullExit(full_exit) => {
                 let eth_address = full_exit.priority_op.eth_address;
                 (eth_address, eth_address)
             }
+            FranklinOp::ForcedWithdraw(forced_withdraw) => {
+                let eth_address = forced_withdraw.priority_op.eth_address;
+                (eth_address, eth_address)
+            }
             _ => panic!(
                 "Incorrect type of priority op: {:?}",
                 exec_prior_op.priority_op
@@ -142,14 +152,27 @@ impl NewExecutedTransaction {
         let operation = serde_json::to_value(&exec_tx.op).expect("Cannot serialize operation");
 
         let (from_account_hex, to_account_hex): (String, Option<String>) = match exec_tx.tx {
-            FranklinTx::Withdraw(_) | FranklinTx::Transfer(_) => (
+            FranklinTx::Withdraw(_) | FranklinTx::Transfer(_) | FranklinTx::BatchTransfer(_) => (
                 serde_json::from_value(tx["from"].clone()).unwrap(),
                 serde_json::from_value(tx["to"].clone()).unwrap(),
             ),
+            FranklinTx::Swap(_) => (
+                serde_json::from_value(tx["orderA"]["account"].clone()).unwrap(),
+                serde_json::from_value(tx["orderB"]["account"].clone()).unwrap(),
+            ),
             FranklinTx::ChangePubKey(_) => (
                 serde_json::from_value(tx["account"].clone()).unwrap(),
                 serde_json::from_value(tx["newPkHash"].clone()).unwrap(),
             ),
+            FranklinTx::Burn(_) => (serde_json::from_value(tx["from"].clone()).unwrap(), None),
+            FranklinTx::Approve(_) | FranklinTx::ApproveForAll(_) => (
+                serde_json::from_value(tx["from"].clone()).unwrap(),
+                serde_json::from_value(tx["operator"].clone()).unwrap(),
+            ),
+            FranklinTx::Mint(_) => (
+                serde_json::from_value(tx["account"].clone()).unwrap(),
+                serde_json::from_value(tx["account"].clone()).unwrap(),
+            ),
             FranklinTx::Close(_) => (
                 serde_json::from_value(tx["account"].clone()).unwrap(),
                 serde_json::from_value(tx["account"].clone()).unwrap(),
@@ -176,3 +199,119 @@ impl NewExecutedTransaction {
         }
     }
 }
+
+/// Creates the rows of the tokens moved by the operation at the given position of the block.
+fn nft_transfer_builder(
+    block: BlockNumber,
+    block_index: u32,
+    tx_hash: Vec<u8>,
+    created_at: DateTime<Utc>,
+) -> impl Fn(&str, &Nft, Option<&Address>, Option<&Address>) -> NewNftTransfer {
+    move |kind, token, from, to| NewNftTransfer {
+        collection_id: i32::from(token.collection_id),
+        token_id: u256_to_bigdecimal(&token.token_id),
+        from_address: from.map(|address| address.as_bytes().to_vec()),
+        to_address: to.map(|address| address.as_bytes().to_vec()),
+        kind: kind.to_string(),
+        block_number: i64::from(block),
+        block_index: block_index as i32,
+        tx_hash: tx_hash.clone(),
+        created_at,
+    }
+}
+
+impl NewNftTransfer {
+    /// Tokens moved by the tx, none for the failed txs.
+    pub fn prepare_stored_transfers(exec_tx: &ExecutedTx, block: BlockNumber) -> Vec<Self> {
+        let (op, block_index) = match (&exec_tx.op, exec_tx.block_index) {
+            (Some(op), Some(block_index)) if exec_tx.success => (op, block_index),
+            _ => return Vec::new(),
+        };
+        let transfer = nft_transfer_builder(
+            block,
+            block_index,
+            exec_tx.tx.hash().as_ref().to_vec(),
+            exec_tx.created_at,
+        );
+
+        match op {
+            FranklinOp::Transfer(op) => vec![transfer(
+                "Transfer",
+                &op.tx.nft(),
+                Some(&op.tx.from),
+                Some(&op.tx.to),
+            )],
+            FranklinOp::TransferToNew(op) => vec![transfer(
+                "Transfer",
+                &op.tx.nft(),
+                Some(&op.tx.from),
+                Some(&op.tx.to),
+            )],
+            FranklinOp::Withdraw(op) => vec![transfer(
+                "Withdraw",
+                &op.tx.nft(),
+                Some(&op.tx.from),
+                Some(&op.tx.to),
+            )],
+            FranklinOp::BatchTransfer(op) => op
+                .tx
+                .tokens
+                .iter()
+                .map(|token| transfer("BatchTransfer", token, Some(&op.tx.from), Some(&op.tx.to)))
+                .collect(),
+            FranklinOp::Swap(op) => {
+                let (order_a, order_b) = (&op.tx.order_a, &op.tx.order_b);
+                vec![
+                    transfer(
+                        "Swap",
+                        &order_a.token_sell,
+                        Some(&order_a.account),
+                        Some(&order_b.account),
+                    ),
+                    transfer(
+                        "Swap",
+                        &order_b.token_sell,
+                        Some(&order_b.account),
+                        Some(&order_a.account),
+                    ),
+                ]
+            }
+            FranklinOp::Mint(op) => {
+                vec![transfer("Mint", &op.tx.nft(), None, Some(&op.tx.account))]
+            }
+            FranklinOp::Burn(op) => vec![transfer("Burn", &op.tx.nft(), Some(&op.tx.from), None)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Tokens moved by the priority operation, none for the failed deposits and forced withdrawals.
+    pub fn prepare_stored_priority_transfers(
+        exec_prior_op: &ExecutedPriorityOp,
+        block: BlockNumber,
+    ) -> Vec<Self> {
+        let transfer = nft_transfer_builder(
+            block,
+            exec_prior_op.block_index,
+            exec_prior_op.priority_op.eth_hash.clone(),
+            Utc::now(),
+        );
+
+        match &exec_prior_op.op {
+            FranklinOp::Deposit(op) if op.success => vec![transfer(
+                "Deposit",
+                &op.priority_op.nft(),
+                None,
+                Some(&op.priority_op.to),
+            )],
+            // token leaves the account of its owner, who is the requester of the withdrawal
+            FranklinOp::ForcedWithdraw(op) if op.success => vec![transfer(
+                "ForcedWithdraw",
+                &op.priority_op.nft(),
+                Some(&op.priority_op.eth_address),
+                Some(&op.priority_op.eth_address),
+            )],
+            // TODO ADE: full exit op does not list the withdrawn tokens
+            _ => Vec::new(),
+        }
+    }
+}
@@ -11,6 +11,8 @@ use models::node::{
 use models::{fe_from_bytes, fe_to_bytes, Action, ActionType, Operation};
 // Local imports
 use self::records::{BlockDetails, BlockTransactionItem, StorageBlock};
+use crate::colexi::{records::NewNftTransfer, ColexiQueries};
+use crate::mempool::MempoolSchema;
 use crate::prover::records::StoredProof;
 use crate::prover::ProverSchema;
 use crate::schema::*;
@@ -77,26 +79,44 @@ impl<'a> BlockSchema<'a> {
         })
     }
 
-    /// Given a block, stores its transactions in the database.
+    /// Given a block, stores its transactions in the database along with the tokens moved by them.
+    /// Stored transactions are removed from the mempool.
     pub fn save_block_transactions(&self, block: Block) -> QueryResult<()> {
-        for block_tx in block.block_transactions.into_iter() {
-            match block_tx {
-                ExecutedOperations::Tx(tx) => {
-                    // Store the executed operation in the corresponding schema.
-                    let new_tx = NewExecutedTransaction::prepare_stored_tx(*tx, block.block_number);
-                    OperationsSchema(self.0).store_executed_operation(new_tx)?;
-                }
-                ExecutedOperations::PriorityOp(prior_op) => {
-                    // For priority operation we should only store it in the Operations schema.
-                    let new_priority_op = NewExecutedPriorityOperation::prepare_stored_priority_op(
-                        *prior_op,
-                        block.block_number,
-                    );
-                    OperationsSchema(self.0).store_executed_priority_operation(new_priority_op)?;
+        self.0.conn().transaction(|| {
+            let mut executed_tx_hashes = Vec::new();
+            let mut nft_transfers = Vec::new();
+            for block_tx in block.block_transactions.into_iter() {
+                match block_tx {
+                    ExecutedOperations::Tx(tx) => {
+                        executed_tx_hashes.push(tx.tx.hash());
+                        nft_transfers.extend(NewNftTransfer::prepare_stored_transfers(
+                            &tx,
+                            block.block_number,
+                        ));
+                        // Store the executed operation in the corresponding schema.
+                        let new_tx =
+                            NewExecutedTransaction::prepare_stored_tx(*tx, block.block_number);
+                        OperationsSchema(self.0).store_executed_operation(new_tx)?;
+                    }
+                    ExecutedOperations::PriorityOp(prior_op) => {
+                        nft_transfers.extend(NewNftTransfer::prepare_stored_priority_transfers(
+                            &prior_op,
+                            block.block_number,
+                        ));
+                        // For priority operation we should only store it in the Operations schema.
+                        let new_priority_op =
+                            NewExecutedPriorityOperation::prepare_stored_priority_op(
+                                *prior_op,
+                                block.block_number,
+                            );
+                        OperationsSchema(self.0)
+                            .store_executed_priority_operation(new_priority_op)?;
+                    }
                 }
             }
-        }
-        Ok(())
+            ColexiQueries(self.0).store_nft_transfers(&nft_transfers)?;
+            MempoolSchema(self.0).remove_txs(&executed_tx_hashes)
+        })
     }
 
     /// Given the block number, attempts to retrieve it from the database.
@@ -1,5 +1,6 @@
 // Built-in deps
 // External imports
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 // Workspace imports
 use models::{node::BlockNumber, ActionType};
@@ -43,6 +44,18 @@ impl<'a> OperationsSchema<'a> {
             .optional()
     }
 
+    /// Loads the successfully executed transactions stored since the given moment, oldest first.
+    pub fn load_executed_txs_since(
+        &self,
+        since: NaiveDateTime,
+    ) -> QueryResult<Vec<StoredExecutedTransaction>> {
+        executed_transactions::table
+            .filter(executed_transactions::success.eq(true))
+            .filter(executed_transactions::created_at.ge(since))
+            .order(executed_transactions::id.asc())
+            .load::<StoredExecutedTransaction>(self.0.conn())
+    }
+
     pub fn get_executed_priority_operation(
         &self,
         priority_op_id: u32,
@@ -369,7 +369,9 @@ impl<'a> OperationsExtSchema<'a> {
                         log::warn!("Tx history item type not found, tx: {:?}", tx_item);
                         continue;
                     }
-                    "Deposit" | "FullExit" => tx_item.tx.get_mut("priority_op"),
+                    "Deposit" | "DepositEth" | "FullExit" | "ForcedWithdraw" => {
+                        tx_item.tx.get_mut("priority_op")
+                    }
                     _ => Some(&mut tx_item.tx),
                 };
 
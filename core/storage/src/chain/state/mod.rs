@@ -1,18 +1,23 @@
 // Built-in deps
 use std::cmp;
 // External imports
+use bigdecimal::BigDecimal;
 use diesel::dsl::{delete, insert_into, max, update};
 use diesel::prelude::*;
 // Workspace imports
 use models::node::PubKeyHash;
 use models::node::{apply_updates, reverse_updates, AccountMap, AccountUpdate, AccountUpdates};
+use models::primitives::u256_to_bigdecimal;
 // Local imports
 use crate::chain::{
     account::{
         records::{
-            StorageAccount, StorageAccountCreation, StorageAccountPubkeyUpdate,
+            StorageAccount, StorageAccountApprovalUpdate, StorageAccountApprovalUpdateInsert,
+            StorageAccountBalanceUpdate, StorageAccountBalanceUpdateInsert, StorageAccountCreation,
+            StorageAccountOperator, StorageAccountOperatorUpdate,
+            StorageAccountOperatorUpdateInsert, StorageAccountPubkeyUpdate,
             StorageAccountPubkeyUpdateInsert, StorageAccountUpdate, StorageAccountUpdateInsert,
-            StorageToken,
+            StorageToken, StorageTokenApproval,
         },
         restore_account,
     },
@@ -90,6 +95,8 @@ impl<'a> StateSchema<'a> {
                     }
                     AccountUpdate::AddToken {
                         token,
+                        content_hash,
+                        slot,
                         old_nonce,
                         new_nonce,
                     } => {
@@ -97,10 +104,13 @@ impl<'a> StateSchema<'a> {
                             update_order_id: update_order_id as i32,
                             account_id: i64::from(*id),
                             block_number: i64::from(block_number),
-                            token_id: token as i32, // TODO ADE: type conversion
+                            token_id: u256_to_bigdecimal(&token.token_id),
                             added: true,
                             old_nonce: i64::from(old_nonce),
                             new_nonce: i64::from(new_nonce),
+                            collection_id: i32::from(token.collection_id),
+                            slot: slot as i32,
+                            content_hash: content_hash.as_bytes().to_vec(),
                         };
 
                         diesel::insert_into(account_tokens_updates::table)
@@ -109,6 +119,8 @@ impl<'a> StateSchema<'a> {
                     }
                     AccountUpdate::RemoveToken {
                         token,
+                        content_hash,
+                        slot,
                         old_nonce,
                         new_nonce,
                     } => {
@@ -116,10 +128,13 @@ impl<'a> StateSchema<'a> {
                             update_order_id: update_order_id as i32,
                             account_id: i64::from(*id),
                             block_number: i64::from(block_number),
-                            token_id: token as i32, // TODO ADE: type conversion
+                            token_id: u256_to_bigdecimal(&token.token_id),
                             added: false,
                             old_nonce: i64::from(old_nonce),
                             new_nonce: i64::from(new_nonce),
+                            collection_id: i32::from(token.collection_id),
+                            slot: slot as i32,
+                            content_hash: content_hash.as_bytes().to_vec(),
                         };
 
                         diesel::insert_into(account_tokens_updates::table)
@@ -146,6 +161,73 @@ impl<'a> StateSchema<'a> {
                             .values(&change_pubkey_hash)
                             .execute(self.0.conn())?;
                     }
+                    AccountUpdate::ChangeApproval {
+                        token,
+                        slot,
+                        old_operator,
+                        new_operator,
+                        old_nonce,
+                        new_nonce,
+                    } => {
+                        let change_approval = StorageAccountApprovalUpdateInsert {
+                            update_order_id: update_order_id as i32,
+                            account_id: i64::from(*id),
+                            block_number: i64::from(block_number),
+                            collection_id: i32::from(token.collection_id),
+                            token_id: u256_to_bigdecimal(&token.token_id),
+                            slot: slot as i32,
+                            old_operator_id: old_operator.map(i64::from),
+                            new_operator_id: new_operator.map(i64::from),
+                            old_nonce: i64::from(old_nonce),
+                            new_nonce: i64::from(new_nonce),
+                        };
+
+                        diesel::insert_into(account_approval_updates::table)
+                            .values(&change_approval)
+                            .execute(self.0.conn())?;
+                    }
+                    AccountUpdate::ChangeApprovalForAll {
+                        operator,
+                        old_approved,
+                        new_approved,
+                        old_nonce,
+                        new_nonce,
+                    } => {
+                        let change_operator = StorageAccountOperatorUpdateInsert {
+                            update_order_id: update_order_id as i32,
+                            account_id: i64::from(*id),
+                            block_number: i64::from(block_number),
+                            operator_id: i64::from(operator),
+                            old_approved,
+                            new_approved,
+                            old_nonce: i64::from(old_nonce),
+                            new_nonce: i64::from(new_nonce),
+                        };
+
+                        diesel::insert_into(account_operator_updates::table)
+                            .values(&change_operator)
+                            .execute(self.0.conn())?;
+                    }
+                    AccountUpdate::UpdateBalance {
+                        ref old_balance,
+                        ref new_balance,
+                        old_nonce,
+                        new_nonce,
+                    } => {
+                        let change_balance = StorageAccountBalanceUpdateInsert {
+                            update_order_id: update_order_id as i32,
+                            account_id: i64::from(*id),
+                            block_number: i64::from(block_number),
+                            old_balance: old_balance.clone(),
+                            new_balance: new_balance.clone(),
+                            old_nonce: i64::from(old_nonce),
+                            new_nonce: i64::from(new_nonce),
+                        };
+
+                        diesel::insert_into(account_eth_balance_updates::table)
+                            .values(&change_balance)
+                            .execute(self.0.conn())?;
+                    }
                 }
             }
             Ok(())
@@ -156,10 +238,13 @@ impl<'a> StateSchema<'a> {
     pub fn apply_state_update(&self, block_number: u32) -> QueryResult<()> {
         log::info!("Applying state update for block: {}", block_number);
         self.0.conn().transaction(|| {
-            // Collect the stored updates. This includes collecting entries from three tables:
+            // Collect the stored updates. This includes collecting entries from six tables:
             // `account_creates` (for creating/removing accounts),
             // `account_balance_updates` (for changing the balance of accounts),
-            // `account_pubkey_updates` (for changing the accounts public keys).
+            // `account_pubkey_updates` (for changing the accounts public keys),
+            // `account_approval_updates` and `account_operator_updates` (for changing
+            // the operators approved by the accounts),
+            // `account_eth_balance_updates` (for changing the ETH balance of accounts).
             let account_tokens_diff = account_tokens_updates::table
                 .filter(account_tokens_updates::block_number.eq(&(i64::from(block_number))))
                 .load::<StorageAccountUpdate>(self.0.conn())?;
@@ -172,6 +257,18 @@ impl<'a> StateSchema<'a> {
                 .filter(account_pubkey_updates::block_number.eq(&(i64::from(block_number))))
                 .load::<StorageAccountPubkeyUpdate>(self.0.conn())?;
 
+            let account_approval_diff = account_approval_updates::table
+                .filter(account_approval_updates::block_number.eq(&(i64::from(block_number))))
+                .load::<StorageAccountApprovalUpdate>(self.0.conn())?;
+
+            let account_operator_diff = account_operator_updates::table
+                .filter(account_operator_updates::block_number.eq(&(i64::from(block_number))))
+                .load::<StorageAccountOperatorUpdate>(self.0.conn())?;
+
+            let account_balance_diff = account_eth_balance_updates::table
+                .filter(account_eth_balance_updates::block_number.eq(&(i64::from(block_number))))
+                .load::<StorageAccountBalanceUpdate>(self.0.conn())?;
+
             // Collect the updates into one list of `StorageAccountDiff`.
             let account_updates: Vec<StorageAccountDiff> = {
                 let mut account_diff = Vec::new();
@@ -190,6 +287,21 @@ impl<'a> StateSchema<'a> {
                         .into_iter()
                         .map(StorageAccountDiff::from),
                 );
+                account_diff.extend(
+                    account_approval_diff
+                        .into_iter()
+                        .map(StorageAccountDiff::from),
+                );
+                account_diff.extend(
+                    account_operator_diff
+                        .into_iter()
+                        .map(StorageAccountDiff::from),
+                );
+                account_diff.extend(
+                    account_balance_diff
+                        .into_iter()
+                        .map(StorageAccountDiff::from),
+                );
                 account_diff.sort_by(StorageAccountDiff::cmp_order);
                 account_diff
             };
@@ -203,6 +315,9 @@ impl<'a> StateSchema<'a> {
                         let storage_token = StorageToken {
                             token_id: upd.token_id,
                             account_id: upd.account_id,
+                            collection_id: upd.collection_id,
+                            slot: upd.slot,
+                            content_hash: upd.content_hash,
                         };
                         insert_into(tokens::table)
                             .values(&storage_token)
@@ -220,6 +335,7 @@ impl<'a> StateSchema<'a> {
                             tokens::table.filter(
                                 tokens::account_id
                                     .eq(upd.account_id)
+                                    .and(tokens::collection_id.eq(upd.collection_id))
                                     .and(tokens::token_id.eq(upd.token_id)),
                             ),
                         )
@@ -239,6 +355,7 @@ impl<'a> StateSchema<'a> {
                             nonce: upd.nonce,
                             address: upd.address,
                             pubkey_hash: PubKeyHash::default().data.to_vec(),
+                            balance: BigDecimal::from(0),
                         };
                         insert_into(accounts::table)
                             .values(&storage_account)
@@ -247,6 +364,18 @@ impl<'a> StateSchema<'a> {
                     StorageAccountDiff::Delete(upd) => {
                         delete(accounts::table.filter(accounts::id.eq(upd.account_id)))
                             .execute(self.0.conn())?;
+                        // The id may be given to a new account, which must not inherit
+                        // the approvals made by the closed one.
+                        delete(
+                            token_approvals::table
+                                .filter(token_approvals::account_id.eq(upd.account_id)),
+                        )
+                        .execute(self.0.conn())?;
+                        delete(
+                            account_operators::table
+                                .filter(account_operators::account_id.eq(upd.account_id)),
+                        )
+                        .execute(self.0.conn())?;
                     }
                     StorageAccountDiff::ChangePubKey(upd) => {
                         update(accounts::table.filter(accounts::id.eq(upd.account_id)))
@@ -257,6 +386,74 @@ impl<'a> StateSchema<'a> {
                             ))
                             .execute(self.0.conn())?;
                     }
+                    StorageAccountDiff::ChangeApproval(upd) => {
+                        delete(
+                            token_approvals::table.filter(
+                                token_approvals::account_id
+                                    .eq(upd.account_id)
+                                    .and(token_approvals::slot.eq(upd.slot)),
+                            ),
+                        )
+                        .execute(self.0.conn())?;
+
+                        if let Some(operator_id) = upd.new_operator_id {
+                            let storage_approval = StorageTokenApproval {
+                                account_id: upd.account_id,
+                                slot: upd.slot,
+                                operator_id,
+                            };
+                            insert_into(token_approvals::table)
+                                .values(&storage_approval)
+                                .execute(self.0.conn())?;
+                        }
+
+                        update(accounts::table.filter(accounts::id.eq(upd.account_id)))
+                            .set((
+                                accounts::last_block.eq(upd.block_number),
+                                accounts::nonce.eq(upd.new_nonce),
+                            ))
+                            .execute(self.0.conn())?;
+                    }
+                    StorageAccountDiff::ChangeApprovalForAll(upd) => {
+                        let storage_operator = StorageAccountOperator {
+                            account_id: upd.account_id,
+                            operator_id: upd.operator_id,
+                        };
+                        if upd.new_approved {
+                            insert_into(account_operators::table)
+                                .values(&storage_operator)
+                                .on_conflict_do_nothing()
+                                .execute(self.0.conn())?;
+                        } else {
+                            delete(
+                                account_operators::table.filter(
+                                    account_operators::account_id
+                                        .eq(storage_operator.account_id)
+                                        .and(
+                                            account_operators::operator_id
+                                                .eq(storage_operator.operator_id),
+                                        ),
+                                ),
+                            )
+                            .execute(self.0.conn())?;
+                        }
+
+                        update(accounts::table.filter(accounts::id.eq(upd.account_id)))
+                            .set((
+                                accounts::last_block.eq(upd.block_number),
+                                accounts::nonce.eq(upd.new_nonce),
+                            ))
+                            .execute(self.0.conn())?;
+                    }
+                    StorageAccountDiff::ChangeBalance(upd) => {
+                        update(accounts::table.filter(accounts::id.eq(upd.account_id)))
+                            .set((
+                                accounts::last_block.eq(upd.block_number),
+                                accounts::nonce.eq(upd.new_nonce),
+                                accounts::balance.eq(upd.new_balance),
+                            ))
+                            .execute(self.0.conn())?;
+                    }
                 }
             }
 
@@ -300,12 +497,22 @@ impl<'a> StateSchema<'a> {
             let tokens: Vec<Vec<StorageToken>> = StorageToken::belonging_to(&accounts)
                 .load(self.0.conn())?
                 .grouped_by(&accounts);
+            let approvals: Vec<Vec<StorageTokenApproval>> =
+                StorageTokenApproval::belonging_to(&accounts)
+                    .load(self.0.conn())?
+                    .grouped_by(&accounts);
+            let operators: Vec<Vec<StorageAccountOperator>> =
+                StorageAccountOperator::belonging_to(&accounts)
+                    .load(self.0.conn())?
+                    .grouped_by(&accounts);
 
             let account_map: AccountMap = accounts
                 .into_iter()
                 .zip(tokens.into_iter())
-                .map(|(stored_account, tokens)| {
-                    let (id, account) = restore_account(stored_account, tokens);
+                .zip(approvals.into_iter().zip(operators.into_iter()))
+                .map(|((stored_account, tokens), (approvals, operators))| {
+                    let (id, account) =
+                        restore_account(stored_account, tokens, approvals, operators);
                     (id, account)
                 })
                 .collect();
@@ -345,10 +552,13 @@ impl<'a> StateSchema<'a> {
                 cmp::max(from_block, to_block_resolved),
             );
 
-            // Collect the stored updates. This includes collecting entries from three tables:
+            // Collect the stored updates. This includes collecting entries from six tables:
             // `account_creates` (for creating/removing accounts),
             // `account_balance_updates` (for changing the balance of accounts),
-            // `account_pubkey_updates` (for changing the accounts public keys).
+            // `account_pubkey_updates` (for changing the accounts public keys),
+            // `account_approval_updates` and `account_operator_updates` (for changing
+            // the operators approved by the accounts),
+            // `account_eth_balance_updates` (for changing the ETH balance of accounts).
             // The updates are loaded for the given blocks range.
             let account_tokens_diff = account_tokens_updates::table
                 .filter(
@@ -371,6 +581,27 @@ impl<'a> StateSchema<'a> {
                         .and(account_pubkey_updates::block_number.le(&(i64::from(end_block)))),
                 )
                 .load::<StorageAccountPubkeyUpdate>(self.0.conn())?;
+            let account_approval_diff = account_approval_updates::table
+                .filter(
+                    account_approval_updates::block_number
+                        .gt(&(i64::from(start_block)))
+                        .and(account_approval_updates::block_number.le(&(i64::from(end_block)))),
+                )
+                .load::<StorageAccountApprovalUpdate>(self.0.conn())?;
+            let account_operator_diff = account_operator_updates::table
+                .filter(
+                    account_operator_updates::block_number
+                        .gt(&(i64::from(start_block)))
+                        .and(account_operator_updates::block_number.le(&(i64::from(end_block)))),
+                )
+                .load::<StorageAccountOperatorUpdate>(self.0.conn())?;
+            let account_balance_diff = account_eth_balance_updates::table
+                .filter(
+                    account_eth_balance_updates::block_number
+                        .gt(&(i64::from(start_block)))
+                        .and(account_eth_balance_updates::block_number.le(&(i64::from(end_block)))),
+                )
+                .load::<StorageAccountBalanceUpdate>(self.0.conn())?;
 
             log::debug!(
                 "Loading state diff: forward: {}, start_block: {}, end_block: {}, unbounded: {}",
@@ -401,6 +632,21 @@ impl<'a> StateSchema<'a> {
                         .into_iter()
                         .map(StorageAccountDiff::from),
                 );
+                account_diff.extend(
+                    account_approval_diff
+                        .into_iter()
+                        .map(StorageAccountDiff::from),
+                );
+                account_diff.extend(
+                    account_operator_diff
+                        .into_iter()
+                        .map(StorageAccountDiff::from),
+                );
+                account_diff.extend(
+                    account_balance_diff
+                        .into_iter()
+                        .map(StorageAccountDiff::from),
+                );
                 let last_block = account_diff
                     .iter()
                     .map(|acc| acc.block_number())
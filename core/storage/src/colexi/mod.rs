@@ -1,62 +1,218 @@
 use diesel::prelude::*;
+use models::node::{Address, Nft};
+use models::primitives::{big_decimal_to_u256, u256_to_bigdecimal};
+use crate::schema::*;
 use crate::StorageProcessor;
 
 pub mod records;
 
+/// Cursor and filters of the token history.
+#[derive(Debug, Clone)]
+pub struct TransferHistoryQuery {
+  /// Id of the history entry to load the history after, from the beginning if `None`
+  pub after: Option<i64>,
+  /// Max amount of entries in the page
+  pub limit: i64,
+  pub from_block: Option<i64>,
+  pub to_block: Option<i64>,
+  /// Skip the entries of the blocks which are not verified yet
+  pub verified_only: bool,
+  /// Only the entries sent from or to this address
+  pub counterparty: Option<Address>,
+}
+
 pub struct ColexiQueries<'a>(pub &'a StorageProcessor);
 
 impl<'a> ColexiQueries<'a> {
-  pub fn get_transfer_history(&self, token_id: u16) -> QueryResult<Vec<records::TransferOperation>> {
-    let query = diesel::
+  /// Stores the tokens moved by the operations of the committed block.
+  pub fn store_nft_transfers(&self, transfers: &[records::NewNftTransfer]) -> QueryResult<()> {
+    if transfers.is_empty() {
+      return Ok(());
+    }
+    diesel::insert_into(nft_transfers::table)
+      .values(transfers)
+      .execute(self.0.conn())?;
+    Ok(())
+  }
+
+  /// Loads the page of the token history selected by the query.
+  pub fn get_transfer_history(
+    &self,
+    token: &Nft,
+    query: &TransferHistoryQuery,
+  ) -> QueryResult<records::TransferHistoryPage> {
+    let operations: Vec<records::TransferOperation> = diesel::
       sql_query(
         "SELECT
-            tx.tx ->> 'from' AS from,
-            tx.tx ->> 'to' AS to,
-            tx.tx_hash AS tx_hash,
-            tx.created_at as created_at,
-            tx.block_number as block_number,
-            p.block_number as proof_block_number
-        FROM executed_transactions tx
-        LEFT OUTER JOIN proofs p ON p.block_number = tx.block_number
-        WHERE 
-            (tx.tx ->> 'tokenId')::NUMERIC = $1
-            AND tx.success = TRUE
-        ORDER BY tx.id ASC"
-      )
-      .bind::<diesel::sql_types::Integer,_>(token_id as i32);
-    query.get_results(self.0.conn())
+            h.id AS id,
+            h.kind AS kind,
+            h.from_address AS from,
+            h.to_address AS to,
+            h.tx_hash AS tx_hash,
+            h.created_at AS created_at,
+            h.block_number AS block_number,
+            h.block_index AS block_index,
+            p.block_number AS proof_block_number,
+            EXISTS (
+              SELECT 1 FROM operations v
+              WHERE v.block_number = h.block_number AND v.action_type = 'VERIFY' AND v.confirmed = TRUE
+            ) AS verify_confirmed,
+            -- pending withdrawals are completed by the first `completeWithdrawals` call after the verify
+            EXISTS (
+              SELECT 1 FROM operations v
+              JOIN eth_ops_binding b ON b.op_id = v.id
+              JOIN eth_operations w ON w.id > b.eth_op_id
+              WHERE v.block_number = h.block_number AND v.action_type = 'VERIFY' AND v.confirmed = TRUE
+                AND w.op_type = 'withdraw' AND w.confirmed = TRUE
+            ) AS withdrawals_completed
+        FROM nft_transfers h
+        LEFT OUTER JOIN proofs p ON p.block_number = h.block_number
+        WHERE
+            h.collection_id = $1
+            AND h.token_id = $2
+            AND ($3::BIGINT IS NULL OR h.id > $3)
+            AND ($4::BIGINT IS NULL OR h.block_number >= $4)
+            AND ($5::BIGINT IS NULL OR h.block_number <= $5)
+            AND (NOT $6 OR p.block_number IS NOT NULL)
+            AND ($7::BYTEA IS NULL OR h.from_address = $7 OR h.to_address = $7)
+        ORDER BY h.id ASC
+        LIMIT $8"
+      )
+      .bind::<diesel::sql_types::Integer,_>(i32::from(token.collection_id))
+      .bind::<diesel::sql_types::Numeric,_>(u256_to_bigdecimal(&token.token_id))
+      .bind::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>,_>(query.after)
+      .bind::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>,_>(query.from_block)
+      .bind::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>,_>(query.to_block)
+      .bind::<diesel::sql_types::Bool,_>(query.verified_only)
+      .bind::<diesel::sql_types::Nullable<diesel::sql_types::Binary>,_>(
+        query.counterparty.map(|address| address.as_bytes().to_vec()),
+      )
+      .bind::<diesel::sql_types::BigInt,_>(query.limit)
+      .get_results(self.0.conn())?;
+    // a full page may be followed by more entries, otherwise it is the last one
+    let next_cursor = if operations.len() as i64 == query.limit {
+      operations.last().map(|op| op.id)
+    } else {
+      None
+    };
+    Ok(records::TransferHistoryPage { operations, next_cursor })
   }
 
-  pub fn get_current_owner(&self, token_id: u16) -> QueryResult<Option<records::Account>> {
+  pub fn get_current_owner(&self, token: &Nft) -> QueryResult<Option<records::Account>> {
     let query = diesel::
       sql_query(
         "SELECT a.address AS address
             FROM tokens t 
             LEFT JOIN accounts a ON a.id = t.account_id
-            WHERE token_id = $1
+            WHERE t.collection_id = $1 AND t.token_id = $2
             LIMIT 1"
       )
-      .bind::<diesel::sql_types::Integer,_>(token_id as i32);
+      .bind::<diesel::sql_types::Integer,_>(i32::from(token.collection_id))
+      .bind::<diesel::sql_types::Numeric,_>(u256_to_bigdecimal(&token.token_id));
     query.get_result(self.0.conn()).optional()
   }
 
-  pub fn get_initial_deposit(&self, token_id: u16) -> QueryResult<Option<records::TransferOperation>> {
-    let query  = diesel::
+  /// Owner of the token after the last committed block, `None` if the token
+  /// is not in the rollup (e.g. it was withdrawn or burned).
+  /// Withdrawals record the L1 recipient as `to_address`, it does not own the token in the rollup.
+  pub fn get_committed_owner(&self, token: &Nft) -> QueryResult<Option<records::Account>> {
+    let query = diesel::
       sql_query(
-        "SELECT
-            NULL as from,
-            op.operation -> 'priority_op' ->> 'to' AS to,
-            encode(op.eth_hash, 'hex') AS tx_hash,
-            op.created_at AS created_at,
-            op.block_number AS block_number,
-            p.block_number AS proof_block_number
-          FROM executed_priority_operations op
-          LEFT OUTER JOIN proofs p ON p.block_number = op.block_number
-        WHERE
-          (op.operation -> 'priority_op' ->> 'token_id')::NUMERIC = $1
-          AND op.operation ->> 'type' = 'Deposit'"
+        "SELECT h.to_address AS address
+            FROM (
+              SELECT to_address, kind FROM nft_transfers
+              WHERE collection_id = $1 AND token_id = $2
+              ORDER BY id DESC
+              LIMIT 1
+            ) h
+            WHERE h.to_address IS NOT NULL
+              AND h.kind NOT IN ('Withdraw', 'ForcedWithdraw')"
+      )
+      .bind::<diesel::sql_types::Integer,_>(i32::from(token.collection_id))
+      .bind::<diesel::sql_types::Numeric,_>(u256_to_bigdecimal(&token.token_id));
+    query.get_result(self.0.conn()).optional()
+  }
+
+  /// Loads the tokens held by the account in the verified state, ordered by the
+  /// collection and the token id and starting after the `after` token.
+  pub fn get_account_tokens(
+    &self,
+    address: &Address,
+    after: Option<&Nft>,
+    limit: i64,
+  ) -> QueryResult<Vec<records::OwnedToken>> {
+    let query = diesel::
+      sql_query(
+        "SELECT t.collection_id AS collection_id, t.token_id AS token_id, t.content_hash AS content_hash
+            FROM tokens t
+            JOIN accounts a ON a.id = t.account_id
+            WHERE a.address = $1
+              AND ($2::INTEGER IS NULL OR (t.collection_id, t.token_id) > ($2, $3))
+            ORDER BY t.collection_id ASC, t.token_id ASC
+            LIMIT $4"
+      )
+      .bind::<diesel::sql_types::Binary,_>(address.as_bytes().to_vec())
+      .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>,_>(
+        after.map(|token| i32::from(token.collection_id)),
+      )
+      .bind::<diesel::sql_types::Nullable<diesel::sql_types::Numeric>,_>(
+        after.map(|token| u256_to_bigdecimal(&token.token_id)),
+      )
+      .bind::<diesel::sql_types::BigInt,_>(limit);
+    query.get_results(self.0.conn())
+  }
+
+  pub fn get_content_hash(&self, token: &Nft) -> QueryResult<Option<records::TokenContent>> {
+    let query = diesel::
+      sql_query(
+        "SELECT t.content_hash AS content_hash
+            FROM tokens t
+            WHERE t.collection_id = $1 AND t.token_id = $2
+            LIMIT 1"
       )
-      .bind::<diesel::sql_types::Integer,_>(token_id as i32);
+      .bind::<diesel::sql_types::Integer,_>(i32::from(token.collection_id))
+      .bind::<diesel::sql_types::Numeric,_>(u256_to_bigdecimal(&token.token_id));
     query.get_result(self.0.conn()).optional()
   }
+
+  pub fn is_burned(&self, token: &Nft) -> QueryResult<bool> {
+    let query = diesel::
+      sql_query(
+        "SELECT t.collection_id AS collection_id, t.token_id AS token_id
+        FROM nft_transfers t
+        WHERE t.collection_id = $1 AND t.token_id = $2 AND t.kind = 'Burn'
+        LIMIT 1"
+      )
+      .bind::<diesel::sql_types::Integer,_>(i32::from(token.collection_id))
+      .bind::<diesel::sql_types::Numeric,_>(u256_to_bigdecimal(&token.token_id));
+    query.get_result::<records::TransferredToken>(self.0.conn()).optional().map(|burn| burn.is_some())
+  }
+
+  pub fn get_burned_tokens(&self) -> QueryResult<Vec<Nft>> {
+    self.get_tokens_of_kind("Burn")
+  }
+
+  /// Tokens minted inside the rollup, including the burned and the withdrawn ones.
+  pub fn get_minted_tokens(&self) -> QueryResult<Vec<Nft>> {
+    self.get_tokens_of_kind("Mint")
+  }
+
+  fn get_tokens_of_kind(&self, kind: &str) -> QueryResult<Vec<Nft>> {
+    let query = diesel::
+      sql_query(
+        "SELECT t.collection_id AS collection_id, t.token_id AS token_id
+        FROM nft_transfers t
+        WHERE t.kind = $1"
+      )
+      .bind::<diesel::sql_types::Text,_>(kind);
+    let tokens: Vec<records::TransferredToken> = query.get_results(self.0.conn())?;
+    Ok(tokens
+      .into_iter()
+      .map(|token| {
+        let token_id = big_decimal_to_u256(&token.token_id)
+          .expect("token id is stored as an uint256");
+        Nft::new(token.collection_id as u16, token_id)
+      })
+      .collect())
+  }
 }
\ No newline at end of file
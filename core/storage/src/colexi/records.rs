@@ -1,24 +1,93 @@
-use diesel::sql_types::{Nullable, BigInt, Binary, Text, Timestamp};
-use chrono::NaiveDateTime;
+use diesel::sql_types::{Nullable, BigInt, Binary, Bool, Integer, Numeric, Timestamp, Text};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use crate::schema::*;
 
+/// Token moved by an executed tx or priority operation.
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "nft_transfers"]
+pub struct NewNftTransfer {
+    pub collection_id: i32,
+    pub token_id: BigDecimal,
+    /// `None` for the deposited and minted tokens
+    pub from_address: Option<Vec<u8>>,
+    /// `None` for the burned tokens
+    pub to_address: Option<Vec<u8>>,
+    /// Type of the tx or of the priority operation, e.g. `Transfer` or `Deposit`
+    pub kind: String,
+    pub block_number: i64,
+    pub block_index: i32,
+    pub tx_hash: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Entry of the token history: a tx or a priority operation moving the token.
 #[derive(Debug, QueryableByName)]
 pub struct TransferOperation {
+    /// Id of the entry in the `nft_transfers` table
     #[sql_type = "BigInt"]
-    pub block_number: i64,
-    #[sql_type = "Nullable<Text>"]
-    pub from: Option<String>,
+    pub id: i64,
+    /// Type of the tx or of the priority operation, e.g. `Transfer` or `Deposit`
     #[sql_type = "Text"]
-    pub to: String,
+    pub kind: String,
+    #[sql_type = "BigInt"]
+    pub block_number: i64,
+    #[sql_type = "Integer"]
+    pub block_index: i32,
+    #[sql_type = "Nullable<Binary>"]
+    pub from: Option<Vec<u8>>,
+    #[sql_type = "Nullable<Binary>"]
+    pub to: Option<Vec<u8>>,
     #[sql_type = "Binary"]
     pub tx_hash: Vec<u8>,
     #[sql_type = "Nullable<BigInt>"]
     pub proof_block_number: Option<i64>,
     #[sql_type = "Timestamp"]
     pub created_at: NaiveDateTime,
+    /// Whether the verification of the block is confirmed on L1
+    #[sql_type = "Bool"]
+    pub verify_confirmed: bool,
+    /// Whether `completeWithdrawals` was confirmed on L1 after the block verification
+    #[sql_type = "Bool"]
+    pub withdrawals_completed: bool,
+}
+
+/// Page of the token history, ordered by the execution of the entries.
+#[derive(Debug)]
+pub struct TransferHistoryPage {
+    pub operations: Vec<TransferOperation>,
+    /// Id of the entry to continue the history after, `None` for the last page
+    pub next_cursor: Option<i64>,
 }
 
 #[derive(Debug, QueryableByName)]
 pub struct Account {
     #[sql_type = "Binary"]
     pub address: Vec<u8>
-}
\ No newline at end of file
+}
+
+/// Token held by an account in the verified state.
+#[derive(Debug, QueryableByName)]
+pub struct OwnedToken {
+    #[sql_type = "Integer"]
+    pub collection_id: i32,
+    #[sql_type = "Numeric"]
+    pub token_id: BigDecimal,
+    #[sql_type = "Binary"]
+    pub content_hash: Vec<u8>,
+}
+
+#[derive(Debug, QueryableByName)]
+pub struct TokenContent {
+    #[sql_type = "Binary"]
+    pub content_hash: Vec<u8>
+}
+
+/// Token of an `nft_transfers` entry, e.g. a burned or a minted one.
+#[derive(Debug, QueryableByName)]
+pub struct TransferredToken {
+    #[sql_type = "Integer"]
+    pub collection_id: i32,
+    #[sql_type = "Numeric"]
+    pub token_id: BigDecimal,
+}
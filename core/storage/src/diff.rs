@@ -4,7 +4,8 @@ use std::cmp::Ordering;
 use web3::types::Address;
 // Workspace imports
 use models::node::PubKeyHash;
-use models::node::{AccountUpdate, TokenId};
+use models::node::{AccountUpdate, ContentHash, Nft};
+use models::primitives::big_decimal_to_u256;
 // Local imports
 use crate::chain::account::records::*;
 
@@ -15,6 +16,9 @@ use crate::chain::account::records::*;
 /// - Removing of the existing account.
 /// - Changing balance of the account.
 /// - Changing the public key of the account.
+/// - Changing the operator approved for a token of the account.
+/// - Changing the approval of an operator for all the tokens of the account.
+/// - Changing the ETH balance of the account.
 ///
 /// This enum allows one to process account updates in a generic way.
 #[derive(Debug)]
@@ -24,6 +28,9 @@ pub enum StorageAccountDiff {
     Create(StorageAccountCreation),
     Delete(StorageAccountCreation),
     ChangePubKey(StorageAccountPubkeyUpdate),
+    ChangeApproval(StorageAccountApprovalUpdate),
+    ChangeApprovalForAll(StorageAccountOperatorUpdate),
+    ChangeBalance(StorageAccountBalanceUpdate),
 }
 
 impl From<StorageAccountUpdate> for StorageAccountDiff {
@@ -52,6 +59,24 @@ impl From<StorageAccountPubkeyUpdate> for StorageAccountDiff {
     }
 }
 
+impl From<StorageAccountApprovalUpdate> for StorageAccountDiff {
+    fn from(update: StorageAccountApprovalUpdate) -> Self {
+        StorageAccountDiff::ChangeApproval(update)
+    }
+}
+
+impl From<StorageAccountOperatorUpdate> for StorageAccountDiff {
+    fn from(update: StorageAccountOperatorUpdate) -> Self {
+        StorageAccountDiff::ChangeApprovalForAll(update)
+    }
+}
+
+impl From<StorageAccountBalanceUpdate> for StorageAccountDiff {
+    fn from(update: StorageAccountBalanceUpdate) -> Self {
+        StorageAccountDiff::ChangeBalance(update)
+    }
+}
+
 impl Into<(u32, AccountUpdate)> for StorageAccountDiff {
     fn into(self) -> (u32, AccountUpdate) {
         match self {
@@ -60,7 +85,13 @@ impl Into<(u32, AccountUpdate)> for StorageAccountDiff {
                 AccountUpdate::AddToken {
                     old_nonce: upd.old_nonce as u32,
                     new_nonce: upd.new_nonce as u32,
-                    token: upd.token_id as u16, // TODO ADE: type conversion
+                    token: Nft::new(
+                        upd.collection_id as u16,
+                        big_decimal_to_u256(&upd.token_id)
+                            .expect("db stored token id should be a uint256"),
+                    ),
+                    content_hash: ContentHash::from_slice(&upd.content_hash),
+                    slot: upd.slot as u32,
                 },
             ),
             StorageAccountDiff::RemoveToken(upd) => (
@@ -68,7 +99,13 @@ impl Into<(u32, AccountUpdate)> for StorageAccountDiff {
                 AccountUpdate::RemoveToken {
                     old_nonce: upd.old_nonce as u32,
                     new_nonce: upd.new_nonce as u32,
-                    token: upd.token_id as u16, // TODO ADE: type conversion
+                    token: Nft::new(
+                        upd.collection_id as u16,
+                        big_decimal_to_u256(&upd.token_id)
+                            .expect("db stored token id should be a uint256"),
+                    ),
+                    content_hash: ContentHash::from_slice(&upd.content_hash),
+                    slot: upd.slot as u32,
                 },
             ),
             StorageAccountDiff::Create(upd) => (
@@ -96,6 +133,40 @@ impl Into<(u32, AccountUpdate)> for StorageAccountDiff {
                         .expect("PubkeyHash update from db deserialize"),
                 },
             ),
+            StorageAccountDiff::ChangeApproval(upd) => (
+                upd.account_id as u32,
+                AccountUpdate::ChangeApproval {
+                    token: Nft::new(
+                        upd.collection_id as u16,
+                        big_decimal_to_u256(&upd.token_id)
+                            .expect("db stored token id should be a uint256"),
+                    ),
+                    slot: upd.slot as u32,
+                    old_operator: upd.old_operator_id.map(|id| id as u32),
+                    new_operator: upd.new_operator_id.map(|id| id as u32),
+                    old_nonce: upd.old_nonce as u32,
+                    new_nonce: upd.new_nonce as u32,
+                },
+            ),
+            StorageAccountDiff::ChangeApprovalForAll(upd) => (
+                upd.account_id as u32,
+                AccountUpdate::ChangeApprovalForAll {
+                    operator: upd.operator_id as u32,
+                    old_approved: upd.old_approved,
+                    new_approved: upd.new_approved,
+                    old_nonce: upd.old_nonce as u32,
+                    new_nonce: upd.new_nonce as u32,
+                },
+            ),
+            StorageAccountDiff::ChangeBalance(upd) => (
+                upd.account_id as u32,
+                AccountUpdate::UpdateBalance {
+                    old_balance: upd.old_balance,
+                    new_balance: upd.new_balance,
+                    old_nonce: upd.old_nonce as u32,
+                    new_nonce: upd.new_nonce as u32,
+                },
+            ),
         }
     }
 }
@@ -120,6 +191,18 @@ impl StorageAccountDiff {
                 update_order_id,
                 ..
             }) => update_order_id,
+            StorageAccountDiff::ChangeApproval(StorageAccountApprovalUpdate {
+                update_order_id,
+                ..
+            }) => update_order_id,
+            StorageAccountDiff::ChangeApprovalForAll(StorageAccountOperatorUpdate {
+                update_order_id,
+                ..
+            }) => update_order_id,
+            StorageAccountDiff::ChangeBalance(StorageAccountBalanceUpdate {
+                update_order_id,
+                ..
+            }) => update_order_id,
         }
     }
 
@@ -142,6 +225,17 @@ impl StorageAccountDiff {
             StorageAccountDiff::ChangePubKey(StorageAccountPubkeyUpdate {
                 block_number, ..
             }) => block_number,
+            StorageAccountDiff::ChangeApproval(StorageAccountApprovalUpdate {
+                block_number,
+                ..
+            }) => block_number,
+            StorageAccountDiff::ChangeApprovalForAll(StorageAccountOperatorUpdate {
+                block_number,
+                ..
+            }) => block_number,
+            StorageAccountDiff::ChangeBalance(StorageAccountBalanceUpdate {
+                block_number, ..
+            }) => block_number,
         }
     }
 }
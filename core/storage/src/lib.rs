@@ -33,8 +33,10 @@
 //! - config, for the server config.
 //! - data_restore, for the data_restore crate.
 //! - ethereum, for the data associated with the Ethereum blockchain.
+//! - mempool, for the transactions waiting to be included into a block.
+//! - order_book, for the signed orders waiting to be matched into swaps.
 //! - prover, for the data on prover jobs, proofs, etc.
-//! - tokens, for storing and loading known tokens.
+//! - tokens, for storing and loading registered ERC-721 collections.
 //! - chain - the biggest one, which includes several schemas for the ZKSync sidechain itself.
 //!
 //! The chain module includes the following schemas:
@@ -93,8 +95,10 @@ pub mod data_restore;
 pub mod diff;
 pub mod ethereum;
 pub mod leader_election;
+pub mod mempool;
+pub mod order_book;
 pub mod prover;
-//pub mod tokens; // TODO ADE: remove token
+pub mod tokens;
 pub mod utils;
 pub mod colexi;
 
@@ -149,16 +153,25 @@ impl StorageProcessor {
         ethereum::EthereumSchema(self)
     }
 
+    /// Gains access to the `Mempool` schema.
+    pub fn mempool_schema(&self) -> mempool::MempoolSchema<'_> {
+        mempool::MempoolSchema(self)
+    }
+
+    /// Gains access to the `OrderBook` schema.
+    pub fn order_book_schema(&self) -> order_book::OrderBookSchema<'_> {
+        order_book::OrderBookSchema(self)
+    }
+
     /// Gains access to the `Prover` schema.
     pub fn prover_schema(&self) -> prover::ProverSchema<'_> {
         prover::ProverSchema(self)
     }
 
     /// Gains access to the `Tokens` schema.
-    // TODO ADE: remove token
-    /*pub fn tokens_schema(&self) -> tokens::TokensSchema<'_> {
+    pub fn tokens_schema(&self) -> tokens::TokensSchema<'_> {
         tokens::TokensSchema(self)
-    }*/
+    }
 
     // Gains access to the `LeaderElection` schema.
     pub fn leader_election_schema(&self) -> leader_election::LeaderElectionSchema<'_> {
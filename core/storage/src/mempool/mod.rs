@@ -0,0 +1,56 @@
+// Built-in deps
+use std::collections::VecDeque;
+// External imports
+use diesel::prelude::*;
+// Workspace imports
+use models::node::{FranklinTx, TxHash};
+// Local imports
+use self::records::{NewMempoolTx, StoredMempoolTx};
+use crate::schema::*;
+use crate::StorageProcessor;
+
+pub mod records;
+
+/// Mempool schema handles the `mempool_txs` table, which keeps the transactions
+/// accepted by the mempool until they are included into a committed block,
+/// so they are not lost on the server restart.
+#[derive(Debug)]
+pub struct MempoolSchema<'a>(pub &'a StorageProcessor);
+
+impl<'a> MempoolSchema<'a> {
+    /// Loads all the stored transactions, ordered by nonce and then by the insertion order.
+    pub fn load_txs(&self) -> QueryResult<VecDeque<FranklinTx>> {
+        let txs = mempool_txs::table
+            .order((mempool_txs::nonce.asc(), mempool_txs::id.asc()))
+            .load::<StoredMempoolTx>(self.0.conn())?;
+
+        Ok(txs
+            .into_iter()
+            .map(|stored| serde_json::from_value(stored.tx).expect("Unparsable mempool tx"))
+            .collect())
+    }
+
+    /// Stores the transaction accepted by the mempool.
+    /// Storing the same transaction twice is a no-op.
+    pub fn insert_tx(&self, tx: &FranklinTx) -> QueryResult<()> {
+        let new_tx = NewMempoolTx {
+            tx_hash: tx.hash().as_ref().to_vec(),
+            tx: serde_json::to_value(tx).expect("Cannot serialize tx"),
+            nonce: i64::from(tx.nonce()),
+        };
+        diesel::insert_into(mempool_txs::table)
+            .values(&new_tx)
+            .on_conflict(mempool_txs::tx_hash)
+            .do_nothing()
+            .execute(self.0.conn())
+            .map(drop)
+    }
+
+    /// Removes the transactions with the given hashes, e.g. the ones included into a block.
+    pub fn remove_txs(&self, tx_hashes: &[TxHash]) -> QueryResult<()> {
+        let tx_hashes: Vec<Vec<u8>> = tx_hashes.iter().map(|h| h.as_ref().to_vec()).collect();
+        diesel::delete(mempool_txs::table.filter(mempool_txs::tx_hash.eq_any(tx_hashes)))
+            .execute(self.0.conn())
+            .map(drop)
+    }
+}
@@ -0,0 +1,23 @@
+// External imports
+use chrono::prelude::*;
+use serde_json::value::Value;
+// Workspace imports
+// Local imports
+use crate::schema::*;
+
+#[derive(Debug, Clone, Queryable)]
+pub struct StoredMempoolTx {
+    pub id: i64,
+    pub tx_hash: Vec<u8>,
+    pub tx: Value,
+    pub nonce: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "mempool_txs"]
+pub struct NewMempoolTx {
+    pub tx_hash: Vec<u8>,
+    pub tx: Value,
+    pub nonce: i64,
+}
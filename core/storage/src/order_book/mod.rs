@@ -0,0 +1,53 @@
+// External imports
+use diesel::prelude::*;
+// Workspace imports
+use models::node::{tx::TxHash, Order};
+// Local imports
+use self::records::{NewOrder, StoredOrder};
+use crate::schema::*;
+use crate::StorageProcessor;
+
+pub mod records;
+
+/// Order book schema handles the `orders` table, which keeps the signed orders
+/// waiting to be matched into swaps, so they are not lost on the server restart.
+#[derive(Debug)]
+pub struct OrderBookSchema<'a>(pub &'a StorageProcessor);
+
+impl<'a> OrderBookSchema<'a> {
+    /// Loads all the stored orders in the insertion order.
+    pub fn load_orders(&self) -> QueryResult<Vec<Order>> {
+        let orders = orders::table
+            .order(orders::id.asc())
+            .load::<StoredOrder>(self.0.conn())?;
+
+        Ok(orders
+            .into_iter()
+            .map(|stored| serde_json::from_value(stored.order_data).expect("Unparsable order"))
+            .collect())
+    }
+
+    /// Stores the order accepted by the order book.
+    /// Storing the same order twice is a no-op.
+    pub fn insert_order(&self, order: &Order) -> QueryResult<()> {
+        let new_order = NewOrder {
+            order_hash: order.hash().as_ref().to_vec(),
+            account_id: i64::from(order.account_id),
+            order_data: serde_json::to_value(order).expect("Cannot serialize order"),
+        };
+        diesel::insert_into(orders::table)
+            .values(&new_order)
+            .on_conflict(orders::order_hash)
+            .do_nothing()
+            .execute(self.0.conn())
+            .map(drop)
+    }
+
+    /// Removes the orders with the given hashes, e.g. the matched or cancelled ones.
+    pub fn remove_orders(&self, order_hashes: &[TxHash]) -> QueryResult<()> {
+        let order_hashes: Vec<Vec<u8>> = order_hashes.iter().map(|h| h.as_ref().to_vec()).collect();
+        diesel::delete(orders::table.filter(orders::order_hash.eq_any(order_hashes)))
+            .execute(self.0.conn())
+            .map(drop)
+    }
+}
@@ -0,0 +1,23 @@
+// External imports
+use chrono::prelude::*;
+use serde_json::value::Value;
+// Workspace imports
+// Local imports
+use crate::schema::*;
+
+#[derive(Debug, Clone, Queryable)]
+pub struct StoredOrder {
+    pub id: i64,
+    pub order_hash: Vec<u8>,
+    pub account_id: i64,
+    pub order_data: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "orders"]
+pub struct NewOrder {
+    pub order_hash: Vec<u8>,
+    pub account_id: i64,
+    pub order_data: Value,
+}
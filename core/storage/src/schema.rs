@@ -1,5 +1,21 @@
 table! {
-    account_creates (account_id, block_number) {
+    account_approval_updates (approval_update_id) {
+        approval_update_id -> Int4,
+        update_order_id -> Int4,
+        account_id -> Int8,
+        block_number -> Int8,
+        collection_id -> Int4,
+        token_id -> Numeric,
+        slot -> Int4,
+        old_operator_id -> Nullable<Int8>,
+        new_operator_id -> Nullable<Int8>,
+        old_nonce -> Int8,
+        new_nonce -> Int8,
+    }
+}
+
+table! {
+    account_creates (account_id, block_number, update_order_id) {
         account_id -> Int8,
         is_create -> Bool,
         block_number -> Int8,
@@ -9,6 +25,40 @@ table! {
     }
 }
 
+table! {
+    account_eth_balance_updates (balance_update_id) {
+        balance_update_id -> Int4,
+        update_order_id -> Int4,
+        account_id -> Int8,
+        block_number -> Int8,
+        old_balance -> Numeric,
+        new_balance -> Numeric,
+        old_nonce -> Int8,
+        new_nonce -> Int8,
+    }
+}
+
+table! {
+    account_operators (account_id, operator_id) {
+        account_id -> Int8,
+        operator_id -> Int8,
+    }
+}
+
+table! {
+    account_operator_updates (operator_update_id) {
+        operator_update_id -> Int4,
+        update_order_id -> Int4,
+        account_id -> Int8,
+        block_number -> Int8,
+        operator_id -> Int8,
+        old_approved -> Bool,
+        new_approved -> Bool,
+        old_nonce -> Int8,
+        new_nonce -> Int8,
+    }
+}
+
 table! {
     account_pubkey_updates (pubkey_update_id) {
         pubkey_update_id -> Int4,
@@ -29,6 +79,7 @@ table! {
         nonce -> Int8,
         address -> Bytea,
         pubkey_hash -> Bytea,
+        balance -> Numeric,
     }
 }
 
@@ -38,10 +89,13 @@ table! {
         account_id -> Int8,
         block_number -> Int8,
         old_nonce -> Int8,
-        token_id -> Int4,
+        token_id -> Numeric,
         added -> Bool,
         new_nonce -> Int8,
         update_order_id -> Int4,
+        collection_id -> Int4,
+        slot -> Int4,
+        content_hash -> Bytea,
     }
 }
 
@@ -66,6 +120,14 @@ table! {
     }
 }
 
+table! {
+    collections (id) {
+        id -> Int4,
+        address -> Text,
+        symbol -> Text,
+    }
+}
+
 table! {
     data_restore_events_state (id) {
         id -> Int4,
@@ -180,6 +242,31 @@ table! {
     }
 }
 
+table! {
+    mempool_txs (id) {
+        id -> Int8,
+        tx_hash -> Bytea,
+        tx -> Jsonb,
+        nonce -> Int8,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    nft_transfers (id) {
+        id -> Int8,
+        collection_id -> Int4,
+        token_id -> Numeric,
+        from_address -> Nullable<Bytea>,
+        to_address -> Nullable<Bytea>,
+        kind -> Text,
+        block_number -> Int8,
+        block_index -> Int4,
+        tx_hash -> Bytea,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     operations (id) {
         id -> Int8,
@@ -190,6 +277,16 @@ table! {
     }
 }
 
+table! {
+    orders (id) {
+        id -> Int8,
+        order_hash -> Bytea,
+        account_id -> Int8,
+        order_data -> Jsonb,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     proofs (block_number) {
         block_number -> Int8,
@@ -217,9 +314,20 @@ table! {
 }
 
 table! {
-    tokens (account_id, token_id) {
+    token_approvals (account_id, slot) {
+        account_id -> Int8,
+        slot -> Int4,
+        operator_id -> Int8,
+    }
+}
+
+table! {
+    tokens (account_id, collection_id, token_id) {
         account_id -> Int8,
-        token_id -> Int4,
+        token_id -> Numeric,
+        collection_id -> Int4,
+        slot -> Int4,
+        content_hash -> Bytea,
     }
 }
 
@@ -228,12 +336,17 @@ joinable!(eth_ops_binding -> operations (op_id));
 joinable!(eth_tx_hashes -> eth_operations (eth_op_id));
 
 allow_tables_to_appear_in_same_query!(
+    account_approval_updates,
     account_creates,
+    account_eth_balance_updates,
+    account_operators,
+    account_operator_updates,
     account_pubkey_updates,
     accounts,
     account_tokens_updates,
     active_provers,
     blocks,
+    collections,
     data_restore_events_state,
     data_restore_last_watched_eth_block,
     data_restore_rollup_ops,
@@ -245,9 +358,13 @@ allow_tables_to_appear_in_same_query!(
     executed_priority_operations,
     executed_transactions,
     leader_election,
+    mempool_txs,
+    nft_transfers,
     operations,
+    orders,
     proofs,
     prover_runs,
     server_config,
+    token_approvals,
     tokens,
 );
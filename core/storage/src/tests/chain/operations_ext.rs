@@ -48,8 +48,10 @@ fn get_account_transactions_history() {
                 token: tokens[0].id,
                 amount: amount.clone(),
                 to: to_account_address,
+                content_hash: Default::default(),
             },
             account_id: from_account_id,
+            success: true,
         }));
 
         let executed_op = ExecutedPriorityOp {
@@ -0,0 +1,436 @@
+// External imports
+use bigdecimal::BigDecimal;
+use diesel::QueryResult;
+// Workspace imports
+use crypto_exports::ff::Field;
+use models::node::block::Block;
+use models::node::{
+    Address, BatchTransfer, BatchTransferOp, Burn, BurnOp, ExecutedOperations, ExecutedPriorityOp,
+    ExecutedTx, ForcedWithdraw, ForcedWithdrawOp, Fr, FranklinOp, Nft, Order, PriorityOp, Swap,
+    SwapOp, TokenId, Transfer, TransferOp, Withdraw, WithdrawOp,
+};
+use models::primitives::u256_to_bigdecimal;
+// Local imports
+use crate::tests::db_test;
+use crate::{
+    colexi::{records::NewNftTransfer, ColexiQueries, TransferHistoryQuery},
+    StorageProcessor,
+};
+
+fn nft_transfer(
+    token: &Nft,
+    kind: &str,
+    from: Option<Address>,
+    to: Option<Address>,
+    block_number: i64,
+    block_index: i32,
+) -> NewNftTransfer {
+    NewNftTransfer {
+        collection_id: i32::from(token.collection_id),
+        token_id: u256_to_bigdecimal(&token.token_id),
+        from_address: from.map(|address| address.as_bytes().to_vec()),
+        to_address: to.map(|address| address.as_bytes().to_vec()),
+        kind: kind.to_string(),
+        block_number,
+        block_index,
+        tx_hash: vec![block_number as u8, block_index as u8],
+        created_at: chrono::Utc::now(),
+    }
+}
+
+/// Checks that the token history is split into pages by the entry id
+/// and that the filters are applied to every kind of the entries.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn transfer_history_pagination() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let token = Nft::new(1, TokenId::from(7));
+        let other_token = Nft::new(1, TokenId::from(8));
+        let (alice, bob, carol) = (
+            Address::from([1u8; 20]),
+            Address::from([2u8; 20]),
+            Address::from([3u8; 20]),
+        );
+
+        ColexiQueries(&conn).store_nft_transfers(&[
+            nft_transfer(&token, "Deposit", None, Some(alice), 1, 0),
+            nft_transfer(&token, "Transfer", Some(alice), Some(bob), 1, 1),
+            nft_transfer(&other_token, "Transfer", Some(alice), Some(bob), 1, 2),
+            nft_transfer(&token, "Transfer", Some(bob), Some(carol), 2, 1),
+            nft_transfer(&token, "Burn", Some(carol), None, 3, 0),
+        ])?;
+
+        let mut query = TransferHistoryQuery {
+            after: None,
+            limit: 3,
+            from_block: None,
+            to_block: None,
+            verified_only: false,
+            counterparty: None,
+        };
+        let first_page = ColexiQueries(&conn).get_transfer_history(&token, &query)?;
+        let kinds: Vec<_> = first_page
+            .operations
+            .iter()
+            .map(|op| op.kind.as_str())
+            .collect();
+        assert_eq!(kinds, vec!["Deposit", "Transfer", "Transfer"]);
+        assert_eq!(
+            first_page.next_cursor,
+            first_page.operations.last().map(|op| op.id)
+        );
+
+        query.after = first_page.next_cursor;
+        let second_page = ColexiQueries(&conn).get_transfer_history(&token, &query)?;
+        assert_eq!(second_page.operations.len(), 1);
+        assert_eq!(second_page.operations[0].kind, "Burn");
+        assert_eq!(second_page.next_cursor, None);
+        assert!(ColexiQueries(&conn).is_burned(&token)?);
+        assert!(!ColexiQueries(&conn).is_burned(&other_token)?);
+
+        let query = TransferHistoryQuery {
+            after: None,
+            limit: 10,
+            from_block: None,
+            to_block: Some(2),
+            verified_only: false,
+            counterparty: Some(carol),
+        };
+        let filtered = ColexiQueries(&conn).get_transfer_history(&token, &query)?;
+        assert_eq!(filtered.operations.len(), 1);
+        assert_eq!(filtered.operations[0].block_number, 2);
+        assert!(!filtered.operations[0].verify_confirmed);
+
+        Ok(())
+    });
+}
+
+/// Checks that the committed owner is the recipient of the last token transfer
+/// and that burned and withdrawn tokens have no owner.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn committed_owner() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let token = Nft::new(1, TokenId::from(7));
+        let other_token = Nft::new(1, TokenId::from(8));
+        let (alice, bob) = (Address::from([1u8; 20]), Address::from([2u8; 20]));
+
+        ColexiQueries(&conn).store_nft_transfers(&[
+            nft_transfer(&token, "Deposit", None, Some(alice), 1, 0),
+            nft_transfer(&other_token, "Deposit", None, Some(alice), 1, 1),
+            nft_transfer(&token, "Transfer", Some(alice), Some(bob), 2, 0),
+        ])?;
+        let owner = ColexiQueries(&conn).get_committed_owner(&token)?;
+        assert_eq!(
+            owner.map(|account| account.address),
+            Some(bob.as_bytes().to_vec())
+        );
+
+        ColexiQueries(&conn).store_nft_transfers(&[nft_transfer(
+            &token,
+            "Burn",
+            Some(bob),
+            None,
+            3,
+            0,
+        )])?;
+        assert!(ColexiQueries(&conn).get_committed_owner(&token)?.is_none());
+        let other_owner = ColexiQueries(&conn).get_committed_owner(&other_token)?;
+        assert_eq!(
+            other_owner.map(|account| account.address),
+            Some(alice.as_bytes().to_vec())
+        );
+
+        // Withdrawals have the L1 recipient as `to`, the token leaves the rollup though.
+        let (withdrawn, forced_withdrawn) = (
+            Nft::new(1, TokenId::from(9)),
+            Nft::new(1, TokenId::from(10)),
+        );
+        ColexiQueries(&conn).store_nft_transfers(&[
+            nft_transfer(&withdrawn, "Deposit", None, Some(alice), 4, 0),
+            nft_transfer(&forced_withdrawn, "Deposit", None, Some(alice), 4, 1),
+            nft_transfer(&withdrawn, "Withdraw", Some(alice), Some(bob), 5, 0),
+            nft_transfer(
+                &forced_withdrawn,
+                "ForcedWithdraw",
+                Some(alice),
+                Some(alice),
+                5,
+                1,
+            ),
+        ])?;
+        assert!(ColexiQueries(&conn)
+            .get_committed_owner(&withdrawn)?
+            .is_none());
+        assert!(ColexiQueries(&conn)
+            .get_committed_owner(&forced_withdrawn)?
+            .is_none());
+
+        Ok(())
+    });
+}
+
+fn executed_tx(op: FranklinOp, block_index: u32, success: bool) -> ExecutedOperations {
+    ExecutedOperations::Tx(Box::new(ExecutedTx {
+        tx: op.try_get_tx().unwrap(),
+        success,
+        op: if success { Some(op) } else { None },
+        fail_reason: if success {
+            None
+        } else {
+            Some("Not current owner".to_string())
+        },
+        block_index: Some(block_index),
+        created_at: chrono::Utc::now(),
+    }))
+}
+
+fn executed_priority_op(op: FranklinOp, block_index: u32) -> ExecutedOperations {
+    ExecutedOperations::PriorityOp(Box::new(ExecutedPriorityOp {
+        priority_op: PriorityOp {
+            serial_id: 0,
+            data: op.try_get_priority_op().unwrap(),
+            deadline_block: 0,
+            eth_hash: vec![block_index as u8],
+        },
+        op,
+        block_index,
+    }))
+}
+
+/// Token history as `(kind, from, to)` entries.
+fn token_history(
+    conn: &StorageProcessor,
+    token: &Nft,
+) -> QueryResult<Vec<(String, Option<Vec<u8>>, Option<Vec<u8>>)>> {
+    let query = TransferHistoryQuery {
+        after: None,
+        limit: 10,
+        from_block: None,
+        to_block: None,
+        verified_only: false,
+        counterparty: None,
+    };
+    let page = ColexiQueries(conn).get_transfer_history(token, &query)?;
+    Ok(page
+        .operations
+        .into_iter()
+        .map(|op| (op.kind, op.from, op.to))
+        .collect())
+}
+
+/// Checks that saving the block transactions stores a row for every token moved
+/// by the successful txs and priority operations of the block.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn save_block_nft_transfers() {
+    let tokens: Vec<_> = (1..=6).map(|id| Nft::new(1, TokenId::from(id))).collect();
+    let (alice_id, bob_id, carol_id) = (1, 2, 3);
+    let (alice, bob, carol) = (
+        Address::from([1u8; 20]),
+        Address::from([2u8; 20]),
+        Address::from([3u8; 20]),
+    );
+    let fee = BigDecimal::from(0);
+
+    let transfer = FranklinOp::Transfer(Box::new(TransferOp {
+        tx: Transfer::new(
+            alice_id,
+            alice,
+            bob,
+            tokens[0].collection_id,
+            tokens[0].token_id,
+            fee.clone(),
+            0,
+            None,
+            None,
+            None,
+        ),
+        from: alice_id,
+        to: bob_id,
+    }));
+    let swap = FranklinOp::Swap(Box::new(SwapOp {
+        tx: Swap::new(
+            Order::new(
+                alice_id,
+                alice,
+                tokens[1],
+                vec![tokens[2]],
+                fee.clone(),
+                1,
+                None,
+            ),
+            Order::new(
+                bob_id,
+                bob,
+                tokens[2],
+                vec![tokens[1]],
+                fee.clone(),
+                0,
+                None,
+            ),
+        ),
+        account_a: alice_id,
+        account_b: bob_id,
+    }));
+    let batch_transfer = FranklinOp::BatchTransfer(Box::new(BatchTransferOp {
+        tx: BatchTransfer::new(
+            alice_id,
+            alice,
+            carol,
+            vec![tokens[3], tokens[4]],
+            fee.clone(),
+            2,
+            None,
+        ),
+        from: alice_id,
+        to: carol_id,
+    }));
+    // Token is withdrawn to the L1 address of carol.
+    let withdraw = FranklinOp::Withdraw(Box::new(WithdrawOp {
+        tx: Withdraw::new(
+            bob_id,
+            bob,
+            carol,
+            tokens[0].collection_id,
+            tokens[0].token_id,
+            fee.clone(),
+            1,
+            None,
+            None,
+            None,
+        ),
+        account_id: bob_id,
+    }));
+    let burn = FranklinOp::Burn(Box::new(BurnOp {
+        tx: Burn::new(
+            carol_id,
+            carol,
+            tokens[3].collection_id,
+            tokens[3].token_id,
+            fee.clone(),
+            0,
+            None,
+        ),
+        account_id: carol_id,
+    }));
+    let failed_transfer = FranklinOp::Transfer(Box::new(TransferOp {
+        tx: Transfer::new(
+            carol_id,
+            carol,
+            alice,
+            tokens[5].collection_id,
+            tokens[5].token_id,
+            fee,
+            1,
+            None,
+            None,
+            None,
+        ),
+        from: carol_id,
+        to: alice_id,
+    }));
+    let forced_withdraw = |token: &Nft, success| {
+        FranklinOp::ForcedWithdraw(Box::new(ForcedWithdrawOp {
+            priority_op: ForcedWithdraw {
+                account_id: bob_id,
+                eth_address: bob,
+                collection_id: token.collection_id,
+                token_id: token.token_id,
+            },
+            success,
+        }))
+    };
+
+    let block = Block::new(
+        1,
+        Fr::zero(),
+        0,
+        vec![
+            executed_tx(transfer, 0, true),
+            executed_tx(swap, 1, true),
+            executed_tx(batch_transfer, 2, true),
+            executed_tx(withdraw, 3, true),
+            executed_tx(burn, 4, true),
+            executed_tx(failed_transfer, 5, false),
+            executed_priority_op(forced_withdraw(&tokens[1], true), 6),
+            // bob does not hold the token, nothing is withdrawn
+            executed_priority_op(forced_withdraw(&tokens[5], false), 7),
+        ],
+        (0, 2),
+        100,
+    );
+
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        conn.chain().block_schema().save_block_transactions(block)?;
+
+        let some = |address: Address| Some(address.as_bytes().to_vec());
+        let entry = |kind: &str, from, to| (kind.to_string(), from, to);
+        assert_eq!(
+            token_history(&conn, &tokens[0])?,
+            vec![
+                entry("Transfer", some(alice), some(bob)),
+                entry("Withdraw", some(bob), some(carol)),
+            ]
+        );
+        // Each order of the swap sends its token to the other order account.
+        assert_eq!(
+            token_history(&conn, &tokens[1])?,
+            vec![
+                entry("Swap", some(alice), some(bob)),
+                entry("ForcedWithdraw", some(bob), some(bob)),
+            ]
+        );
+        assert_eq!(
+            token_history(&conn, &tokens[2])?,
+            vec![entry("Swap", some(bob), some(alice))]
+        );
+        assert_eq!(
+            token_history(&conn, &tokens[3])?,
+            vec![
+                entry("BatchTransfer", some(alice), some(carol)),
+                entry("Burn", some(carol), None),
+            ]
+        );
+        assert_eq!(
+            token_history(&conn, &tokens[4])?,
+            vec![entry("BatchTransfer", some(alice), some(carol))]
+        );
+        assert!(token_history(&conn, &tokens[5])?.is_empty());
+
+        // Rows keep the position of the tx in the block.
+        let query = TransferHistoryQuery {
+            after: None,
+            limit: 10,
+            from_block: None,
+            to_block: None,
+            verified_only: false,
+            counterparty: None,
+        };
+        let burn_entry = ColexiQueries(&conn)
+            .get_transfer_history(&tokens[3], &query)?
+            .operations
+            .pop()
+            .unwrap();
+        assert_eq!((burn_entry.block_number, burn_entry.block_index), (1, 4));
+        assert!(ColexiQueries(&conn).is_burned(&tokens[3])?);
+
+        let owners = tokens
+            .iter()
+            .map(|token| {
+                ColexiQueries(&conn)
+                    .get_committed_owner(token)
+                    .map(|owner| owner.map(|account| account.address))
+            })
+            .collect::<QueryResult<Vec<_>>>()?;
+        assert_eq!(
+            owners,
+            vec![None, None, some(alice), None, some(carol), None]
+        );
+
+        Ok(())
+    });
+}
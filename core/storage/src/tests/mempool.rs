@@ -0,0 +1,54 @@
+// External imports
+// Workspace imports
+use models::node::{FranklinTx, TokenId, Transfer};
+// Local imports
+use crate::tests::db_test;
+use crate::{mempool::MempoolSchema, StorageProcessor};
+
+fn transfer_tx(token_id: u64, nonce: u32) -> FranklinTx {
+    FranklinTx::Transfer(Box::new(Transfer::new(
+        1,
+        "0000000000000000000000000000000000000001".parse().unwrap(),
+        "0000000000000000000000000000000000000002".parse().unwrap(),
+        1,
+        TokenId::from(token_id),
+        Default::default(),
+        nonce,
+        None,
+        None,
+        None,
+    )))
+}
+
+/// Checks that the mempool transactions are loaded in the nonce order
+/// and removed once included into a block.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn mempool_storage() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        // Mempool is empty by default.
+        assert!(MempoolSchema(&conn).load_txs()?.is_empty());
+
+        // Store the transactions out of order, one of them twice.
+        let second_tx = transfer_tx(2, 1);
+        let first_tx = transfer_tx(1, 0);
+        MempoolSchema(&conn).insert_tx(&second_tx)?;
+        MempoolSchema(&conn).insert_tx(&first_tx)?;
+        MempoolSchema(&conn).insert_tx(&second_tx)?;
+
+        let txs = MempoolSchema(&conn).load_txs()?;
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].hash(), first_tx.hash());
+        assert_eq!(txs[1].hash(), second_tx.hash());
+
+        // Remove the first transaction.
+        MempoolSchema(&conn).remove_txs(&[first_tx.hash()])?;
+
+        let txs = MempoolSchema(&conn).load_txs()?;
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash(), second_tx.hash());
+
+        Ok(())
+    });
+}
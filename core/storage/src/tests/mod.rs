@@ -31,10 +31,13 @@ use crypto_exports::rand::{SeedableRng, XorShiftRng};
 use diesel::Connection;
 
 mod chain;
+mod colexi;
 mod config;
 mod data_restore;
 mod ethereum;
 mod leader_election;
+mod mempool;
+mod order_book;
 mod prover;
 mod tokens;
 
@@ -0,0 +1,52 @@
+// External imports
+use bigdecimal::BigDecimal;
+// Workspace imports
+use models::node::{Nft, Order, TokenId};
+// Local imports
+use crate::tests::db_test;
+use crate::{order_book::OrderBookSchema, StorageProcessor};
+
+fn order(token_id: u64, nonce: u32) -> Order {
+    Order::new(
+        1,
+        "0000000000000000000000000000000000000001".parse().unwrap(),
+        Nft::new(1, TokenId::from(token_id)),
+        vec![Nft::new(2, TokenId::from(token_id))],
+        BigDecimal::from(0),
+        nonce,
+        None,
+    )
+}
+
+/// Checks that the orders are loaded in the insertion order
+/// and removed once matched or cancelled.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn order_book_storage() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        // Order book is empty by default.
+        assert!(OrderBookSchema(&conn).load_orders()?.is_empty());
+
+        // Store the orders, one of them twice.
+        let first_order = order(1, 0);
+        let second_order = order(2, 0);
+        OrderBookSchema(&conn).insert_order(&first_order)?;
+        OrderBookSchema(&conn).insert_order(&second_order)?;
+        OrderBookSchema(&conn).insert_order(&first_order)?;
+
+        let orders = OrderBookSchema(&conn).load_orders()?;
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].hash(), first_order.hash());
+        assert_eq!(orders[1].hash(), second_order.hash());
+
+        // Remove the first order.
+        OrderBookSchema(&conn).remove_orders(&[first_order.hash()])?;
+
+        let orders = OrderBookSchema(&conn).load_orders()?;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].hash(), second_order.hash());
+
+        Ok(())
+    });
+}
@@ -5,25 +5,19 @@ use crate::tests::db_test;
 use crate::{tokens::TokensSchema, StorageProcessor};
 use models::node::{Token, TokenLike};
 
-/// Verifies the token save & load mechanism.
+/// Verifies the collection save & load mechanism.
 #[test]
 #[cfg_attr(not(feature = "db_test"), ignore)]
 fn tokens_storage() {
     let conn = StorageProcessor::establish_connection().unwrap();
     db_test(conn.conn(), || {
-        // There should be only Ethereum main token by default.
+        // There are no registered collections by default.
         let tokens = TokensSchema(&conn)
             .load_tokens()
             .expect("Load tokens query failed");
-        assert_eq!(tokens.len(), 1);
-        let eth_token = Token {
-            id: 0,
-            address: "0000000000000000000000000000000000000000".parse().unwrap(),
-            symbol: "ETH".into(),
-        };
-        assert_eq!(tokens[&0], eth_token);
+        assert!(tokens.is_empty());
 
-        // Add two tokens.
+        // Add two collections.
         let token_a = Token {
             id: 1,
             address: "0000000000000000000000000000000000000001".parse().unwrap(),
@@ -47,8 +41,7 @@ fn tokens_storage() {
             .load_tokens()
             .expect("Load tokens query failed");
 
-        assert_eq!(tokens.len(), 3);
-        assert_eq!(tokens[&eth_token.id], eth_token);
+        assert_eq!(tokens.len(), 2);
         assert_eq!(tokens[&token_a.id], token_a);
         assert_eq!(tokens[&token_b.id], token_b);
 
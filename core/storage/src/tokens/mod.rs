@@ -3,7 +3,7 @@ use std::collections::HashMap;
 // External imports
 use diesel::prelude::*;
 // Workspace imports
-use models::node::{Token, TokenId, TokenLike};
+use models::node::{CollectionId, Token, TokenLike};
 // Local imports
 use self::records::DbToken;
 use crate::schema::*;
@@ -13,31 +13,29 @@ use crate::StorageProcessor;
 pub mod records;
 mod utils;
 
-/// Tokens schema handles the `tokens` table, providing methods to
-/// get and store new tokens.
+/// Tokens schema handles the `collections` table, providing methods to
+/// get and store the ERC-721 collections registered in the governance contract.
 #[derive(Debug)]
 pub struct TokensSchema<'a>(pub &'a StorageProcessor);
 
 impl<'a> TokensSchema<'a> {
-    /// Persists the token in the database.
+    /// Persists the collection in the database.
     pub fn store_token(&self, token: Token) -> QueryResult<()> {
         let new_token: DbToken = token.into();
-        diesel::insert_into(tokens::table)
+        diesel::insert_into(collections::table)
             .values(&new_token)
-            .on_conflict(tokens::id)
+            .on_conflict(collections::id)
             .do_update()
-            // update token address but not symbol -- so we can update it externally
-            .set(tokens::address.eq(new_token.address.clone()))
+            // update collection address but not symbol -- so we can update it externally
+            .set(collections::address.eq(new_token.address.clone()))
             .execute(self.0.conn())
             .map(drop)
     }
 
-    /// Loads all the stored tokens from the database.
-    /// Alongside with the tokens added via `store_token` method, the default `ETH` token
-    /// is returned.
-    pub fn load_tokens(&self) -> QueryResult<HashMap<TokenId, Token>> {
-        let tokens = tokens::table
-            .order(tokens::id.asc())
+    /// Loads all the stored collections from the database.
+    pub fn load_tokens(&self) -> QueryResult<HashMap<CollectionId, Token>> {
+        let tokens = collections::table
+            .order(collections::id.asc())
             .load::<DbToken>(self.0.conn())?;
         Ok(tokens
             .into_iter()
@@ -48,19 +46,19 @@ impl<'a> TokensSchema<'a> {
             .collect())
     }
 
-    /// Given the numeric token ID, symbol or address, returns token.
+    /// Given the numeric collection ID, symbol or contract address, returns collection.
     pub fn get_token(&self, token_like: TokenLike) -> QueryResult<Option<Token>> {
         let db_token = match token_like {
-            TokenLike::Id(token_id) => tokens::table
-                .find(i32::from(token_id))
+            TokenLike::Id(collection_id) => collections::table
+                .find(i32::from(collection_id))
                 .first::<DbToken>(self.0.conn())
                 .optional(),
-            TokenLike::Address(token_address) => tokens::table
-                .filter(tokens::address.eq(address_to_stored_string(&token_address)))
+            TokenLike::Address(token_address) => collections::table
+                .filter(collections::address.eq(address_to_stored_string(&token_address)))
                 .first::<DbToken>(self.0.conn())
                 .optional(),
-            TokenLike::Symbol(token_symbol) => tokens::table
-                .filter(tokens::symbol.eq(token_symbol))
+            TokenLike::Symbol(token_symbol) => collections::table
+                .filter(collections::symbol.eq(token_symbol))
                 .first::<DbToken>(self.0.conn())
                 .optional(),
         }?;
@@ -4,7 +4,7 @@ use serde_derive::{Deserialize, Serialize};
 // Local imports
 use crate::schema::*;
 use crate::tokens::utils::{address_to_stored_string, stored_str_address_to_address};
-use models::node::{Token, TokenId};
+use models::node::{CollectionId, Token};
 
 #[derive(
     Debug,
@@ -17,7 +17,7 @@ use models::node::{Token, TokenId};
     AsChangeset,
     PartialEq,
 )]
-#[table_name = "tokens"]
+#[table_name = "collections"]
 
 pub struct DbToken {
     pub id: i32,
@@ -38,7 +38,7 @@ impl From<Token> for DbToken {
 impl Into<Token> for DbToken {
     fn into(self) -> Token {
         Token {
-            id: self.id as TokenId,
+            id: self.id as CollectionId,
             address: stored_str_address_to_address(&self.address),
             symbol: self.symbol,
         }
@@ -19,10 +19,10 @@ use server::mempool::ProposedBlock;
 use server::state_keeper::{
     start_state_keeper, PlasmaStateInitParams, PlasmaStateKeeper, StateKeeperRequest,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::thread::JoinHandle;
 use std::time::Instant;
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, sync::watch};
 use web3::transports::Http;
 use web3::Transport;
 
@@ -222,6 +222,8 @@ pub fn spawn_state_keeper(
         proposed_blocks_sender,
         executed_tx_notify_sender,
         vec![TESKIT_BLOCK_CHUNKS_SIZE],
+        watch::channel(None).1,
+        watch::channel(HashSet::new()).1,
     );
 
     let (stop_state_keeper_sender, stop_state_keeper_receiver) = oneshot::channel::<()>();